@@ -0,0 +1,23 @@
+use thiserror::Error;
+
+use crate::{mate::MateId, InstanceId};
+
+pub type AssemblyResult<T> = Result<T, AssemblyError>;
+
+#[derive(Debug, Error, PartialEq)]
+pub enum AssemblyError {
+    #[error("Instance {0:?} does not exist")]
+    MissingInstance(InstanceId),
+
+    #[error("Mate {0:?} does not exist")]
+    MissingMate(MateId),
+
+    #[error("A mate has to join two different instances")]
+    SelfMate,
+
+    #[error("Mate value is out of range: {0}")]
+    InvalidValue(f64),
+
+    #[error("Mates {0:?} could not be met")]
+    Unsolved(Vec<MateId>),
+}
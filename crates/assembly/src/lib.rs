@@ -0,0 +1,614 @@
+//! Assemblies of parts: instances of part files placed in a shared space, and
+//! mates between them that hold them in place relative to each other. Solving
+//! an assembly moves the instances that aren't grounded as little as possible to
+//! meet the mates, and reports how many degrees of freedom are left.
+
+use std::path::PathBuf;
+
+use serde::{Deserialize, Serialize};
+use sketch::solver::{independent_rows, jacobian, solve, System};
+use space::{EPlacement3, EVec3, EVector, Tolerance};
+
+pub mod error;
+pub mod mate;
+
+use error::{AssemblyError, AssemblyResult};
+use mate::{to_local_direction, Anchor, Mate, MateId, MateKind};
+
+/// Identifies an instance for as long as it is in its assembly
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
+pub struct InstanceId(pub(crate) u64);
+
+/// A part placed in an assembly. A part can be placed any number of times.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Instance {
+    pub id: InstanceId,
+    pub name: String,
+
+    /// The part file, relative to the assembly's folder
+    pub part: PathBuf,
+
+    /// Where the part's coordinate system is in the assembly
+    pub placement: EPlacement3,
+
+    /// Whether the instance stays where it is when the assembly is solved
+    pub grounded: bool,
+}
+
+/// The result of solving an assembly
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Solution {
+    /// How many ways the instances can still move without breaking a mate
+    pub free_degrees: usize,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct Assembly {
+    instances: Vec<Instance>,
+    mates: Vec<Mate>,
+    next_id: u64,
+}
+impl Assembly {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn instances(&self) -> &[Instance] {
+        &self.instances
+    }
+
+    pub fn instance(&self, id: InstanceId) -> Option<&Instance> {
+        self.instances.iter().find(|i| i.id == id)
+    }
+
+    pub fn mates(&self) -> &[Mate] {
+        &self.mates
+    }
+
+    pub fn mate(&self, id: MateId) -> Option<&Mate> {
+        self.mates.iter().find(|m| m.id == id)
+    }
+
+    /// Places a part in the assembly. The first instance is grounded.
+    pub fn add_instance(
+        &mut self,
+        name: &str,
+        part: impl Into<PathBuf>,
+        placement: EPlacement3,
+    ) -> InstanceId {
+        let id = InstanceId(self.next_id());
+        self.instances.push(Instance {
+            id,
+            name: name.to_string(),
+            part: part.into(),
+            placement,
+            grounded: self.instances.is_empty(),
+        });
+        id
+    }
+
+    /// Removes an instance along with the mates that use it
+    pub fn remove_instance(&mut self, id: InstanceId) -> AssemblyResult<Instance> {
+        let index = self.instance_index(id)?;
+        self.mates
+            .retain(|m| m.a.instance != id && m.b.instance != id);
+        Ok(self.instances.remove(index))
+    }
+
+    pub fn rename_instance(&mut self, id: InstanceId, name: &str) -> AssemblyResult<()> {
+        self.instance_mut(id)?.name = name.to_string();
+        Ok(())
+    }
+
+    pub fn set_placement(&mut self, id: InstanceId, placement: EPlacement3) -> AssemblyResult<()> {
+        self.instance_mut(id)?.placement = placement;
+        Ok(())
+    }
+
+    pub fn set_grounded(&mut self, id: InstanceId, grounded: bool) -> AssemblyResult<()> {
+        self.instance_mut(id)?.grounded = grounded;
+        Ok(())
+    }
+
+    /// Adds a mate between frames on two different instances
+    pub fn add_mate(
+        &mut self,
+        name: &str,
+        kind: MateKind,
+        a: Anchor,
+        b: Anchor,
+    ) -> AssemblyResult<MateId> {
+        self.check_anchors(&a, &b)?;
+        kind.check()?;
+
+        let id = MateId(self.next_id());
+        self.mates.push(Mate {
+            id,
+            name: name.to_string(),
+            kind,
+            a,
+            b,
+            suppressed: false,
+        });
+        Ok(id)
+    }
+
+    /// Adds a mate that keeps two instances where they are relative to each
+    /// other
+    pub fn add_fixed(
+        &mut self,
+        name: &str,
+        a: InstanceId,
+        b: InstanceId,
+    ) -> AssemblyResult<MateId> {
+        let placement_a = self.instance(a).ok_or(AssemblyError::MissingInstance(a))?;
+        let placement_b = self.instance(b).ok_or(AssemblyError::MissingInstance(b))?;
+        let (placement_a, placement_b) = (&placement_a.placement, &placement_b.placement);
+
+        // Anchor both at the first instance's frame
+        let frame = EPlacement3 {
+            origin: placement_b.to_local(placement_a.origin),
+            x_dir: to_local_direction(placement_b, placement_a.x_dir),
+            y_dir: to_local_direction(placement_b, placement_a.y_dir),
+        };
+        self.add_mate(
+            name,
+            MateKind::Fixed,
+            Anchor::new(a, EPlacement3::default()),
+            Anchor::new(b, frame),
+        )
+    }
+
+    /// Removes a mate, leaving its instances where they are
+    pub fn remove_mate(&mut self, id: MateId) -> AssemblyResult<Mate> {
+        let index = self.mate_index(id)?;
+        Ok(self.mates.remove(index))
+    }
+
+    pub fn rename_mate(&mut self, id: MateId, name: &str) -> AssemblyResult<()> {
+        let index = self.mate_index(id)?;
+        self.mates[index].name = name.to_string();
+        Ok(())
+    }
+
+    pub fn set_mate_kind(&mut self, id: MateId, kind: MateKind) -> AssemblyResult<()> {
+        let index = self.mate_index(id)?;
+        kind.check()?;
+        self.mates[index].kind = kind;
+        Ok(())
+    }
+
+    pub fn set_anchors(&mut self, id: MateId, a: Anchor, b: Anchor) -> AssemblyResult<()> {
+        let index = self.mate_index(id)?;
+        self.check_anchors(&a, &b)?;
+        self.mates[index].a = a;
+        self.mates[index].b = b;
+        Ok(())
+    }
+
+    pub fn set_suppressed(&mut self, id: MateId, suppressed: bool) -> AssemblyResult<()> {
+        let index = self.mate_index(id)?;
+        self.mates[index].suppressed = suppressed;
+        Ok(())
+    }
+
+    /// Moves the instances that aren't grounded to meet every mate that isn't
+    /// suppressed. If the mates can't all be met, the instances are left where
+    /// they were and the mates that weren't met are returned in the error.
+    pub fn solve(&mut self) -> AssemblyResult<Solution> {
+        self.solve_with(&Tolerance::DEFAULT)
+    }
+
+    /// Solves the assembly like [`Self::solve`], meeting the mates to the
+    /// tolerances of a model rather than the default ones
+    pub fn solve_with(&mut self, tolerance: &Tolerance) -> AssemblyResult<Solution> {
+        let placer = Placer::new(self);
+        let mut x = vec![0.0; placer.free.len() * 6];
+        let solved = solve(&placer, &mut x, tolerance);
+
+        if !solved {
+            let placements = placer.placements(&x);
+            let unmet = placer
+                .mates
+                .iter()
+                .filter(|mate| {
+                    let residuals = placer.mate_residuals(mate, &placements);
+                    residuals.iter().any(|r| r.abs() > tolerance.linear)
+                })
+                .map(|mate| mate.id)
+                .collect();
+            return Err(AssemblyError::Unsolved(unmet));
+        }
+
+        let independent = independent_rows(&jacobian(&placer, &x))
+            .into_iter()
+            .filter(|independent| *independent)
+            .count();
+        let solution = Solution {
+            free_degrees: x.len() - independent,
+        };
+
+        let placements = placer.placements(&x);
+        for (instance, placement) in self.instances.iter_mut().zip(placements) {
+            instance.placement = placement;
+        }
+        Ok(solution)
+    }
+
+    fn next_id(&mut self) -> u64 {
+        let id = self.next_id;
+        self.next_id += 1;
+        id
+    }
+
+    fn instance_index(&self, id: InstanceId) -> AssemblyResult<usize> {
+        self.instances
+            .iter()
+            .position(|i| i.id == id)
+            .ok_or(AssemblyError::MissingInstance(id))
+    }
+
+    fn instance_mut(&mut self, id: InstanceId) -> AssemblyResult<&mut Instance> {
+        let index = self.instance_index(id)?;
+        Ok(&mut self.instances[index])
+    }
+
+    fn mate_index(&self, id: MateId) -> AssemblyResult<usize> {
+        self.mates
+            .iter()
+            .position(|m| m.id == id)
+            .ok_or(AssemblyError::MissingMate(id))
+    }
+
+    fn check_anchors(&self, a: &Anchor, b: &Anchor) -> AssemblyResult<()> {
+        self.instance_index(a.instance)?;
+        self.instance_index(b.instance)?;
+        if a.instance == b.instance {
+            return Err(AssemblyError::SelfMate);
+        }
+        Ok(())
+    }
+}
+
+/// The mates of an assembly as a system of equations. Each instance that isn't
+/// grounded has six unknowns: a translation of its origin, and a rotation about
+/// it given as a vector along the axis as long as the angle.
+struct Placer<'a> {
+    assembly: &'a Assembly,
+
+    /// The index of each instance's unknowns, for those that aren't grounded
+    free: Vec<usize>,
+    mates: Vec<&'a Mate>,
+}
+impl<'a> Placer<'a> {
+    fn new(assembly: &'a Assembly) -> Self {
+        Self {
+            assembly,
+            free: assembly
+                .instances
+                .iter()
+                .enumerate()
+                .filter(|(_, instance)| !instance.grounded)
+                .map(|(index, _)| index)
+                .collect(),
+            mates: assembly.mates.iter().filter(|m| !m.suppressed).collect(),
+        }
+    }
+
+    /// The placement of every instance, once those that aren't grounded are
+    /// moved by `x`
+    fn placements(&self, x: &[f64]) -> Vec<EPlacement3> {
+        let mut placements = self
+            .assembly
+            .instances
+            .iter()
+            .map(|instance| instance.placement.clone())
+            .collect::<Vec<_>>();
+        for (unknowns, index) in x.chunks(6).zip(self.free.iter()) {
+            let placement = &mut placements[*index];
+            let rotation = EVec3::new(unknowns[3], unknowns[4], unknowns[5]);
+            placement.origin += EVec3::new(unknowns[0], unknowns[1], unknowns[2]);
+            placement.x_dir = rotate(placement.x_dir, rotation);
+            placement.y_dir = rotate(placement.y_dir, rotation);
+        }
+        placements
+    }
+
+    fn mate_residuals(&self, mate: &Mate, placements: &[EPlacement3]) -> Vec<f64> {
+        let placement = |anchor: &Anchor| {
+            let index = self
+                .assembly
+                .instance_index(anchor.instance)
+                .expect("mates only join instances of their assembly");
+            anchor.global(&placements[index])
+        };
+        mate.kind
+            .residuals(&placement(&mate.a), &placement(&mate.b))
+    }
+}
+impl<'a> System for Placer<'a> {
+    fn residuals(&self, x: &[f64]) -> Vec<f64> {
+        let placements = self.placements(x);
+        self.mates
+            .iter()
+            .flat_map(|mate| self.mate_residuals(mate, &placements))
+            .collect()
+    }
+}
+
+/// Rotates a vector about the axis of `rotation` by its length in radians
+fn rotate(v: EVec3, rotation: EVec3) -> EVec3 {
+    let angle = rotation.magnitude();
+    if angle <= f64::EPSILON {
+        return v + rotation.cross(&v);
+    }
+    let axis = rotation / angle;
+    let (sin, cos) = angle.sin_cos();
+    v * cos + axis.cross(&v) * sin + axis * (axis.dot(&v) * (1.0 - cos))
+}
+
+#[cfg(test)]
+mod tests {
+    use space::{EPlacement3, EVec3, EVector};
+
+    use crate::{
+        error::AssemblyError,
+        mate::{Anchor, MateKind},
+        Assembly,
+    };
+
+    fn frame(origin: [f64; 3], axis: [f64; 3]) -> EPlacement3 {
+        let [x, y, z] = origin;
+        let [i, j, k] = axis;
+        EPlacement3::from_axis(EVec3::new(x, y, z), EVec3::new(i, j, k))
+    }
+
+    fn assert_near(a: EVec3, b: EVec3) {
+        assert!((a - b).magnitude() < 1e-6, "{a:?} != {b:?}");
+    }
+
+    /// A grounded base and a block tilted and moved away from it, with the
+    /// base's top face and the block's bottom face to mate
+    fn base_and_block() -> (Assembly, Anchor, Anchor) {
+        let mut assembly = Assembly::new();
+        let base = assembly.add_instance("Base", "base.cadpart", EPlacement3::default());
+        let block = assembly.add_instance(
+            "Block",
+            "block.cadpart",
+            EPlacement3::from_axis_and_x(
+                EVec3::new(2.0, -3.0, 7.0),
+                EVec3::new(0.2, -0.1, 1.0),
+                EVec3::new(1.0, 0.0, 0.0),
+            ),
+        );
+        let top = Anchor::new(base, frame([0.0, 0.0, 1.0], [0.0, 0.0, 1.0]));
+        let bottom = Anchor::new(block, frame([0.0, 0.0, 0.0], [0.0, 0.0, -1.0]));
+        (assembly, top, bottom)
+    }
+
+    fn placement(assembly: &Assembly, anchor: &Anchor) -> EPlacement3 {
+        anchor.global(&assembly.instance(anchor.instance).unwrap().placement)
+    }
+
+    #[test]
+    fn coincident() {
+        let (mut assembly, top, bottom) = base_and_block();
+        assembly
+            .add_mate("Rest", MateKind::Coincident, top.clone(), bottom.clone())
+            .unwrap();
+
+        // The block can slide over the base and turn on it
+        assert_eq!(assembly.solve().unwrap().free_degrees, 3);
+        let (a, b) = (placement(&assembly, &top), placement(&assembly, &bottom));
+        assert!((b.origin - a.origin).dot(&a.z_dir()).abs() < 1e-6);
+        assert_near(b.z_dir(), -a.z_dir());
+    }
+
+    #[test]
+    fn concentric() {
+        let (mut assembly, top, bottom) = base_and_block();
+        assembly
+            .add_mate("Axis", MateKind::Concentric, top.clone(), bottom.clone())
+            .unwrap();
+
+        // The block can slide along the axis and turn about it
+        assert_eq!(assembly.solve().unwrap().free_degrees, 2);
+        let (a, b) = (placement(&assembly, &top), placement(&assembly, &bottom));
+        assert!((b.origin - a.origin).cross(&a.z_dir()).magnitude() < 1e-6);
+        assert!(b.z_dir().cross(&a.z_dir()).magnitude() < 1e-6);
+    }
+
+    #[test]
+    fn distance() {
+        let (mut assembly, top, bottom) = base_and_block();
+        assembly
+            .add_mate("Gap", MateKind::Distance(2.5), top.clone(), bottom.clone())
+            .unwrap();
+
+        assert_eq!(assembly.solve().unwrap().free_degrees, 3);
+        let (a, b) = (placement(&assembly, &top), placement(&assembly, &bottom));
+        assert!(((b.origin - a.origin).dot(&a.z_dir()) - 2.5).abs() < 1e-6);
+        assert_near(b.z_dir(), -a.z_dir());
+
+        assert_eq!(
+            assembly.add_mate("Negative", MateKind::Distance(-1.0), top, bottom),
+            Err(AssemblyError::InvalidValue(-1.0))
+        );
+    }
+
+    #[test]
+    fn angle() {
+        let (mut assembly, top, bottom) = base_and_block();
+        let angle = 0.4;
+        assembly
+            .add_mate("Tilt", MateKind::Angle(angle), top.clone(), bottom.clone())
+            .unwrap();
+
+        // Only the angle between the axes is held
+        assert_eq!(assembly.solve().unwrap().free_degrees, 5);
+        let (a, b) = (placement(&assembly, &top), placement(&assembly, &bottom));
+        assert!((a.z_dir().dot(&b.z_dir()) - angle.cos()).abs() < 1e-6);
+    }
+
+    #[test]
+    fn fixed() {
+        let (mut assembly, top, bottom) = base_and_block();
+        assembly
+            .add_mate("Weld", MateKind::Fixed, top.clone(), bottom.clone())
+            .unwrap();
+
+        assert_eq!(assembly.solve().unwrap().free_degrees, 0);
+        let (a, b) = (placement(&assembly, &top), placement(&assembly, &bottom));
+        assert_near(b.origin, a.origin);
+        assert_near(b.x_dir, a.x_dir);
+        assert_near(b.y_dir, a.y_dir);
+    }
+
+    #[test]
+    fn degrees_of_freedom() {
+        let (mut assembly, top, bottom) = base_and_block();
+        let block = bottom.instance;
+
+        // A free instance can move every way, and a grounded one can't
+        assert_eq!(assembly.solve().unwrap().free_degrees, 6);
+        assembly.set_grounded(block, true).unwrap();
+        assert_eq!(assembly.solve().unwrap().free_degrees, 0);
+
+        // Two free instances fixed to each other move as one
+        assembly.set_grounded(top.instance, false).unwrap();
+        assembly.set_grounded(block, false).unwrap();
+        let weld = assembly.add_fixed("Weld", top.instance, block).unwrap();
+        assert_eq!(assembly.solve().unwrap().free_degrees, 6);
+
+        // Suppressed mates don't hold anything
+        assembly.set_suppressed(weld, true).unwrap();
+        assert_eq!(assembly.solve().unwrap().free_degrees, 12);
+    }
+
+    #[test]
+    fn over_constrained() {
+        let (mut assembly, top, bottom) = base_and_block();
+        assembly
+            .add_mate("Rest", MateKind::Coincident, top.clone(), bottom.clone())
+            .unwrap();
+
+        // A mate that agrees with the others is redundant and removes no
+        // further freedom
+        assembly
+            .add_mate("Again", MateKind::Coincident, top.clone(), bottom.clone())
+            .unwrap();
+        assert_eq!(assembly.solve().unwrap().free_degrees, 3);
+
+        // One that contradicts them can't be met, and nothing moves
+        let solved = assembly.clone();
+        let gap = assembly
+            .add_mate("Gap", MateKind::Distance(1.0), top, bottom)
+            .unwrap();
+        let Err(AssemblyError::Unsolved(unmet)) = assembly.solve() else {
+            panic!("contradicting mates were met");
+        };
+        assert!(unmet.contains(&gap));
+        assert_eq!(assembly.instances(), solved.instances());
+    }
+
+    #[test]
+    fn pin_in_hole() {
+        let mut assembly = Assembly::new();
+        let base = assembly.add_instance("Base", "base.cadpart", EPlacement3::default());
+        let pin = assembly.add_instance(
+            "Pin",
+            "pin.cadpart",
+            EPlacement3::from_axis_and_x(
+                EVec3::new(3.0, 4.0, -2.0),
+                EVec3::new(0.3, 0.2, 1.0),
+                EVec3::new(1.0, 0.0, 0.0),
+            ),
+        );
+        assert!(assembly.instance(base).unwrap().grounded);
+
+        // The pin goes down a hole in the base until its shoulder sits on top
+        let hole = Anchor::new(base, frame([10.0, 0.0, 0.0], [0.0, 0.0, 1.0]));
+        let axis = Anchor::new(pin, frame([0.0, 0.0, 0.0], [0.0, 0.0, 1.0]));
+        assembly
+            .add_mate("Concentric", MateKind::Concentric, hole, axis)
+            .unwrap();
+        let top = Anchor::new(base, frame([0.0, 0.0, 5.0], [0.0, 0.0, 1.0]));
+        let shoulder = Anchor::new(pin, frame([0.0, 0.0, 2.0], [0.0, 0.0, -1.0]));
+        let seat = assembly
+            .add_mate("Seat", MateKind::Coincident, top.clone(), shoulder.clone())
+            .unwrap();
+
+        // The pin can still turn in its hole
+        let solution = assembly.solve().unwrap();
+        assert_eq!(solution.free_degrees, 1);
+        let placement = &assembly.instance(pin).unwrap().placement;
+        assert_near(placement.origin, EVec3::new(10.0, 0.0, 3.0));
+        assert_near(placement.z_dir(), EVec3::new(0.0, 0.0, 1.0));
+
+        // Lifting it changes the gap under the shoulder
+        assembly
+            .set_mate_kind(seat, MateKind::Distance(1.5))
+            .unwrap();
+        assembly.solve().unwrap();
+        let placement = &assembly.instance(pin).unwrap().placement;
+        assert_near(placement.origin, EVec3::new(10.0, 0.0, 4.5));
+
+        // A third part tilted against the base, then fixed to the pin so it
+        // turns with it
+        let plate = assembly.add_instance(
+            "Plate",
+            "plate.cadpart",
+            frame([0.0, 20.0, 0.0], [0.0, 1.0, 1.0]),
+        );
+        let base_z = Anchor::new(base, EPlacement3::default());
+        let plate_z = Anchor::new(plate, EPlacement3::default());
+        let angle = std::f64::consts::FRAC_PI_3;
+        assembly
+            .add_mate("Tilt", MateKind::Angle(angle), base_z, plate_z)
+            .unwrap();
+        assembly.solve().unwrap();
+        let placement = &assembly.instance(plate).unwrap().placement;
+        assert!((placement.z_dir().z - angle.cos()).abs() < 1e-6);
+
+        let offset = placement.origin - EVec3::new(10.0, 0.0, 4.5);
+        assembly.add_fixed("Held", pin, plate).unwrap();
+        assert_eq!(assembly.solve().unwrap().free_degrees, 1);
+        let placement = &assembly.instance(plate).unwrap().placement;
+        assert_near(
+            placement.origin - assembly.instance(pin).unwrap().placement.origin,
+            offset,
+        );
+
+        // Mates that fight each other are reported, and nothing moves
+        let placements = assembly.clone();
+        let fight = assembly
+            .add_mate("Fight", MateKind::Distance(3.0), top, shoulder)
+            .unwrap();
+        assert_eq!(
+            assembly.solve(),
+            Err(AssemblyError::Unsolved(vec![seat, fight]))
+        );
+        assert_eq!(assembly.instances(), placements.instances());
+
+        assert_eq!(
+            assembly.add_mate(
+                "Self",
+                MateKind::Fixed,
+                Anchor::new(pin, EPlacement3::default()),
+                Anchor::new(pin, EPlacement3::default())
+            ),
+            Err(AssemblyError::SelfMate)
+        );
+        assert_eq!(
+            assembly.set_mate_kind(fight, MateKind::Angle(4.0)),
+            Err(AssemblyError::InvalidValue(4.0))
+        );
+
+        // Removing an instance takes its mates with it
+        assembly.remove_instance(plate).unwrap();
+        assert_eq!(assembly.mates().len(), 3);
+
+        let text = serde_json::to_string(&assembly).unwrap();
+        assert_eq!(serde_json::from_str::<Assembly>(&text).unwrap(), assembly);
+    }
+}
@@ -0,0 +1,128 @@
+use serde::{Deserialize, Serialize};
+use space::{EPlacement3, EVec3, EVector};
+
+use crate::{
+    error::{AssemblyError, AssemblyResult},
+    InstanceId,
+};
+
+/// Identifies a mate for as long as it is in its assembly
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
+pub struct MateId(pub(crate) u64);
+
+/// A frame on one instance that a mate joins to a frame on another, given in the
+/// coordinates of the instance's part. Planes are the frame's XY plane, facing
+/// along its Z axis, and axes are its Z axis.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Anchor {
+    pub instance: InstanceId,
+    pub frame: EPlacement3,
+}
+impl Anchor {
+    pub fn new(instance: InstanceId, frame: EPlacement3) -> Self {
+        Self { instance, frame }
+    }
+
+    /// The frame in assembly coordinates, for an instance placed at `placement`
+    pub fn global(&self, placement: &EPlacement3) -> EPlacement3 {
+        EPlacement3 {
+            origin: placement.to_global(self.frame.origin),
+            x_dir: to_global_direction(placement, self.frame.x_dir),
+            y_dir: to_global_direction(placement, self.frame.y_dir),
+        }
+    }
+}
+
+/// How a mate holds its anchors relative to each other
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub enum MateKind {
+    /// The anchors' planes are the same plane, facing each other
+    Coincident,
+
+    /// The anchors' axes are the same line, in either direction
+    Concentric,
+
+    /// The anchors' planes are parallel and facing each other, with a gap
+    /// between them
+    Distance(f64),
+
+    /// The anchors' axes are an angle in radians apart, from 0 to π
+    Angle(f64),
+
+    /// The anchors' frames are the same, so the instances move as one
+    Fixed,
+}
+impl MateKind {
+    pub const NAMES: [&'static str; 5] = ["Coincident", "Concentric", "Distance", "Angle", "Fixed"];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::Coincident => Self::NAMES[0],
+            Self::Concentric => Self::NAMES[1],
+            Self::Distance(_) => Self::NAMES[2],
+            Self::Angle(_) => Self::NAMES[3],
+            Self::Fixed => Self::NAMES[4],
+        }
+    }
+
+    pub(crate) fn check(&self) -> AssemblyResult<()> {
+        match *self {
+            Self::Distance(distance) if !(distance >= 0.0 && distance.is_finite()) => {
+                Err(AssemblyError::InvalidValue(distance))
+            }
+            Self::Angle(angle) if !(0.0..=std::f64::consts::PI).contains(&angle) => {
+                Err(AssemblyError::InvalidValue(angle))
+            }
+            _ => Ok(()),
+        }
+    }
+
+    /// How far anchors in assembly coordinates are from meeting the mate, as
+    /// values that are all zero once they do
+    pub(crate) fn residuals(&self, a: &EPlacement3, b: &EPlacement3) -> Vec<f64> {
+        let (za, zb) = (a.z_dir(), b.z_dir());
+        let offset = b.origin - a.origin;
+        match *self {
+            Self::Coincident => facing(za, zb, offset.dot(&za)),
+            Self::Distance(distance) => facing(za, zb, offset.dot(&za) - distance),
+            Self::Concentric => {
+                let turn = za.cross(&zb);
+                let apart = offset.cross(&za);
+                vec![turn.x, turn.y, turn.z, apart.x, apart.y, apart.z]
+            }
+            Self::Angle(angle) => vec![za.dot(&zb) - angle.cos()],
+            Self::Fixed => {
+                let [o, x, y] = [offset, b.x_dir - a.x_dir, b.y_dir - a.y_dir];
+                vec![o.x, o.y, o.z, x.x, x.y, x.z, y.x, y.y, y.z]
+            }
+        }
+    }
+}
+
+/// Residuals of planes that face each other with a signed gap between them
+fn facing(za: EVec3, zb: EVec3, gap: f64) -> Vec<f64> {
+    let sum = za + zb;
+    vec![gap, sum.x, sum.y, sum.z]
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Mate {
+    pub id: MateId,
+    pub name: String,
+    pub kind: MateKind,
+    pub a: Anchor,
+    pub b: Anchor,
+    pub suppressed: bool,
+}
+
+pub(crate) fn to_global_direction(placement: &EPlacement3, local: EVec3) -> EVec3 {
+    placement.x_dir * local.x + placement.y_dir * local.y + placement.z_dir() * local.z
+}
+
+pub(crate) fn to_local_direction(placement: &EPlacement3, global: EVec3) -> EVec3 {
+    EVec3::new(
+        global.dot(&placement.x_dir),
+        global.dot(&placement.y_dir),
+        global.dot(&placement.z_dir()),
+    )
+}
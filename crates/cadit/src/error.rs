@@ -1,5 +1,6 @@
 use std::{ffi::OsString, path::PathBuf};
 
+use document::error::DocumentError;
 use thiserror::Error;
 
 pub type CaditResult<T> = Result<T, CaditError>;
@@ -17,4 +18,14 @@ pub enum CaditError {
 
     #[error("Cannot open `{}` as a file because it is a directory", .0.to_string_lossy())]
     AttemptToOpenDirectoryAsFile(PathBuf),
+
+    #[error("Cannot open or save the document: {0}")]
+    Document(#[from] DocumentError),
+
+    #[error("Cannot show instance `{instance}` because its part `{}` cannot be opened: {error}", .path.to_string_lossy())]
+    InstancePart {
+        instance: String,
+        path: PathBuf,
+        error: DocumentError,
+    },
 }
@@ -1,10 +1,14 @@
+use self::organisms::panes::EditorPane;
 use self::organisms::workspace::Workspace;
 use self::organisms::{menu, status_bar};
-use eframe::egui::{self};
+use crate::error::CaditError;
+use components::{Gui, Window};
+use document::project::Project;
+use eframe::egui::{self, Key};
 use egui_modal::Modal;
 use std::collections::VecDeque;
+use std::path::PathBuf;
 use std::sync::Arc;
-use components::{Gui, Window};
 
 mod organisms;
 
@@ -15,6 +19,12 @@ type GlowContext = Arc<eframe::glow::Context>;
 
 pub(crate) enum UiMessage {
     ErrorDialog(String),
+    OpenFolder(PathBuf),
+    CloseFolder,
+    OpenFile(PathBuf),
+    Save,
+    Undo,
+    Redo,
 }
 
 pub(crate) struct MessageBus {
@@ -54,10 +64,46 @@ impl CaditUi {
         while let Some(message) = self.messages.pop() {
             match message {
                 UiMessage::ErrorDialog(content) => self.error_dialog = Some(content),
+                UiMessage::OpenFolder(path) => match Project::open(path) {
+                    Ok(project) => self.workspace.open_project(project),
+                    Err(err) => self.error_dialog = Some(CaditError::from(err).to_string()),
+                },
+                UiMessage::CloseFolder => self.workspace.close_project(),
+                UiMessage::OpenFile(path) => match EditorPane::open(&path) {
+                    Ok(pane) => self.workspace.open_editor(pane),
+                    Err(err) => self.error_dialog = Some(err.to_string()),
+                },
+                UiMessage::Save => {
+                    if let Err(err) = self.workspace.save() {
+                        self.error_dialog = Some(err.to_string());
+                    }
+                }
+                UiMessage::Undo => self.workspace.undo(),
+                UiMessage::Redo => self.workspace.redo(),
             }
         }
     }
 
+    /// Turns the editing shortcuts into messages. Text fields that have focus
+    /// keep the shortcuts for undoing their own typing.
+    fn handle_shortcuts(&mut self, ctx: &egui::Context) {
+        if ctx.wants_keyboard_input() {
+            return;
+        }
+
+        let input = ctx.input();
+        if !input.modifiers.command {
+            return;
+        }
+        if (input.key_pressed(Key::Z) && input.modifiers.shift) || input.key_pressed(Key::Y) {
+            self.messages.push(UiMessage::Redo);
+        } else if input.key_pressed(Key::Z) {
+            self.messages.push(UiMessage::Undo);
+        } else if input.key_pressed(Key::S) {
+            self.messages.push(UiMessage::Save);
+        }
+    }
+
     fn show_dialogs(&mut self, ctx: &egui::Context) {
         if let Some(error_dialog) = self.error_dialog.clone() {
             let err_modal = Modal::new(ctx, "error_modal");
@@ -82,9 +128,12 @@ impl Window for CaditUi {
     fn draw(&mut self, gui: &mut Gui) {
         gui.immediate_ui(|gui| {
             let ctx = &gui.egui_ctx;
+            let (undo, redo) = self.workspace.edit_names();
             egui::TopBottomPanel::top("menu")
                 .height_range(MENU_HEIGHT..=MENU_HEIGHT)
-                .show(ctx, |ui| menu::show(ui));
+                .show(ctx, |ui| {
+                    menu::show(ui, &mut self.messages, undo.as_deref(), redo.as_deref())
+                });
 
             egui::TopBottomPanel::bottom("status_bar")
                 .height_range(STATUS_BAR_HEIGHT..=STATUS_BAR_HEIGHT)
@@ -94,6 +143,7 @@ impl Window for CaditUi {
                 self.workspace.show(ctx, ui, &mut self.messages);
             });
 
+            self.handle_shortcuts(ctx);
             self.handle_messages();
             self.show_dialogs(ctx);
         });
@@ -1,18 +1,31 @@
-use eframe::egui::{InnerResponse, Ui};
-
-pub fn show(ui: &mut Ui) -> InnerResponse<()> {
+use eframe::egui::{Button, InnerResponse, Ui};
+
+use crate::ui::{MessageBus, UiMessage};
+
+/// Shows the main menu. The Edit menu names the edits to the active document that
+/// would be undone and redone.
+pub fn show(
+    ui: &mut Ui,
+    messages: &mut MessageBus,
+    undo: Option<&str>,
+    redo: Option<&str>,
+) -> InnerResponse<()> {
     ui.horizontal(|ui| {
         ui.style_mut().visuals.button_frame = false;
 
         ui.menu_button("File", |ui| {
             if ui.button("Open folder").clicked() {
-                println!("opening folder");
+                ui.close_menu();
+                if let Some(folder) = rfd::FileDialog::new().pick_folder() {
+                    messages.push(UiMessage::OpenFolder(folder));
+                }
             }
 
             ui.separator();
 
-            if ui.button("Save").clicked() {
-                println!("Saving file");
+            if ui.button("Save").on_hover_text("Ctrl+S").clicked() {
+                ui.close_menu();
+                messages.push(UiMessage::Save);
             }
 
             if ui.button("Save As...").clicked() {
@@ -26,7 +39,8 @@ pub fn show(ui: &mut Ui) -> InnerResponse<()> {
             ui.separator();
 
             if ui.button("Close folder").clicked() {
-                println!("Closing folder");
+                ui.close_menu();
+                messages.push(UiMessage::CloseFolder);
             }
 
             ui.separator();
@@ -36,7 +50,33 @@ pub fn show(ui: &mut Ui) -> InnerResponse<()> {
                 // TODO: Actually close app
             }
         });
-        ui.menu_button("Edit", |ui| if ui.button("Some edit stuff").clicked() {});
+        ui.menu_button("Edit", |ui| {
+            let label = match undo {
+                Some(name) => format!("Undo {name}"),
+                None => "Undo".to_owned(),
+            };
+            if ui
+                .add_enabled(undo.is_some(), Button::new(label))
+                .on_hover_text("Ctrl+Z")
+                .clicked()
+            {
+                ui.close_menu();
+                messages.push(UiMessage::Undo);
+            }
+
+            let label = match redo {
+                Some(name) => format!("Redo {name}"),
+                None => "Redo".to_owned(),
+            };
+            if ui
+                .add_enabled(redo.is_some(), Button::new(label))
+                .on_hover_text("Ctrl+Y")
+                .clicked()
+            {
+                ui.close_menu();
+                messages.push(UiMessage::Redo);
+            }
+        });
         ui.menu_button(
             "Window",
             |ui| if ui.button("Some window stuff").clicked() {},
@@ -0,0 +1,55 @@
+use components::panes::assembly::AssemblyTree;
+
+use crate::ui::{MessageBus, UiMessage};
+
+use super::{ActiveAssembly, Pane};
+
+/// Shows the instances and mates of the assembly being edited, and records each
+/// edit to them in the assembly's history
+pub struct AssemblyPane {
+    assembly: ActiveAssembly,
+    tree: AssemblyTree,
+}
+impl AssemblyPane {
+    pub fn new(assembly: ActiveAssembly) -> Self {
+        Self {
+            assembly,
+            tree: AssemblyTree::new(),
+        }
+    }
+}
+impl Pane for AssemblyPane {
+    fn title(&self) -> String {
+        "Assembly".to_owned()
+    }
+
+    fn show(&mut self, ui: &mut eframe::egui::Ui, messages: &mut MessageBus) {
+        let assembly = self.assembly.borrow().clone();
+        match assembly {
+            Some(assembly) => {
+                let mut open = assembly.borrow_mut();
+                let open = &mut *open;
+                let before = open.document.assembly.clone();
+                if let Some(name) =
+                    self.tree
+                        .show(ui, &mut open.document.assembly, Some(&open.solution))
+                {
+                    open.record(name, before);
+                }
+
+                ui.separator();
+                if ui
+                    .add_enabled(open.history.is_dirty(), eframe::egui::Button::new("Save"))
+                    .clicked()
+                {
+                    if let Err(err) = open.save() {
+                        messages.push(UiMessage::ErrorDialog(err.to_string()));
+                    }
+                }
+            }
+            None => {
+                ui.label("Open an assembly to see its instances and mates");
+            }
+        }
+    }
+}
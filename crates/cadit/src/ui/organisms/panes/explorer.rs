@@ -0,0 +1,62 @@
+use std::{
+    cell::RefCell,
+    rc::Rc,
+    time::{Duration, Instant},
+};
+
+use components::panes::explorer::Explorer;
+use document::project::Project;
+use eframe::egui::Ui;
+
+use crate::{
+    error::CaditError,
+    ui::{MessageBus, UiMessage},
+};
+
+use super::Pane;
+
+/// How often the project's folder is scanned for changes
+const REFRESH_INTERVAL: Duration = Duration::from_secs(1);
+
+pub struct ExplorerPane {
+    project: Rc<RefCell<Option<Project>>>,
+    explorer: Explorer,
+    last_refresh: Instant,
+}
+impl ExplorerPane {
+    /// Creates a pane listing the files of the workspace's open project, which
+    /// it shares with the workspace
+    pub fn new(project: Rc<RefCell<Option<Project>>>) -> Self {
+        Self {
+            project,
+            explorer: Explorer::new(),
+            last_refresh: Instant::now(),
+        }
+    }
+}
+impl Pane for ExplorerPane {
+    fn title(&self) -> String {
+        "Explorer".to_owned()
+    }
+
+    fn show(&mut self, ui: &mut Ui, messages: &mut MessageBus) {
+        let mut project = self.project.borrow_mut();
+        let Some(open_project) = project.as_mut() else {
+            ui.label("Open a folder to see its parts and assemblies".to_owned());
+            return;
+        };
+
+        if self.last_refresh.elapsed() >= REFRESH_INTERVAL {
+            self.last_refresh = Instant::now();
+            if let Err(err) = open_project.refresh() {
+                messages.push(UiMessage::ErrorDialog(CaditError::from(err).to_string()));
+                *project = None;
+                return;
+            }
+        }
+
+        if let Some(path) = self.explorer.show(ui, open_project) {
+            messages.push(UiMessage::OpenFile(path));
+        }
+    }
+}
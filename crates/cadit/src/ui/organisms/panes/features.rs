@@ -1,9 +1,20 @@
-use super::Pane;
+use components::panes::features::FeatureList;
 
-pub struct FeaturesPane {}
+use crate::ui::MessageBus;
+
+use super::{ActiveDocument, Pane};
+
+/// Shows the feature tree of the part being edited
+pub struct FeaturesPane {
+    document: ActiveDocument,
+    list: FeatureList,
+}
 impl FeaturesPane {
-    pub fn new() -> Self {
-        Self {}
+    pub fn new(document: ActiveDocument) -> Self {
+        Self {
+            document,
+            list: FeatureList::new(),
+        }
     }
 }
 impl Pane for FeaturesPane {
@@ -11,7 +22,20 @@ impl Pane for FeaturesPane {
         "Features".to_owned()
     }
 
-    fn show(&mut self, ui: &mut eframe::egui::Ui) {
-        ui.label("This is where the part's feature timeline will be shown".to_owned());
+    fn show(&mut self, ui: &mut eframe::egui::Ui, _messages: &mut MessageBus) {
+        let document = self.document.borrow().clone();
+        match document {
+            Some(document) => {
+                let mut open = document.borrow_mut();
+                let before = open.document.features.clone();
+                if self.list.show(ui, &mut open.document.features) {
+                    open.record("Edit features", "features", |d| &mut d.features, before);
+                    open.document.regenerate();
+                }
+            }
+            None => {
+                ui.label("Open a part to see its features");
+            }
+        }
     }
 }
@@ -0,0 +1,48 @@
+use components::panes::interference::InterferenceList;
+
+use crate::ui::{MessageBus, UiMessage};
+
+use super::{ActiveAssembly, Pane};
+
+/// Checks the instances of the assembly being edited for interference and
+/// lists the pairs that overlap or are too close, which the assembly view
+/// highlights
+pub struct InterferencePane {
+    assembly: ActiveAssembly,
+    list: InterferenceList,
+}
+impl InterferencePane {
+    pub fn new(assembly: ActiveAssembly) -> Self {
+        Self {
+            assembly,
+            list: InterferenceList::new(),
+        }
+    }
+}
+impl Pane for InterferencePane {
+    fn title(&self) -> String {
+        "Interference".to_owned()
+    }
+
+    fn show(&mut self, ui: &mut eframe::egui::Ui, messages: &mut MessageBus) {
+        let assembly = self.assembly.borrow().clone();
+        match assembly {
+            Some(assembly) => {
+                let mut open = assembly.borrow_mut();
+                let open = &mut *open;
+                if self
+                    .list
+                    .show(ui, &open.document.assembly, open.interference.as_deref())
+                {
+                    if let Err(err) = open.check_interference() {
+                        messages.push(UiMessage::ErrorDialog(err.to_string()));
+                    }
+                }
+                open.set_highlight(self.list.min_clearance(), self.list.selected());
+            }
+            None => {
+                ui.label("Open an assembly to check its instances for interference");
+            }
+        }
+    }
+}
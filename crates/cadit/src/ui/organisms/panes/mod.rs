@@ -1,29 +1,378 @@
-use cgmath::{point3, vec3, Deg, InnerSpace};
-use components::{
-    editors::{assembly::AssemblyEditor, part::PartEditor, Editor},
-    rgb,
+use std::{
+    cell::RefCell,
+    collections::HashMap,
+    path::{Path, PathBuf},
+    rc::Rc,
+};
+
+use assembly::{error::AssemblyResult, Assembly, InstanceId, Solution};
+use cgmath::{vec3, InnerSpace};
+use components::editors::{assembly::AssemblyEditor, part::PartEditor, Editor};
+use document::{
+    assembly::AssemblyDocument,
+    camera::CameraState,
+    history::{Change, History},
+    project::FileKind,
+    Document,
 };
 use eframe::egui::{self, Ui};
 use egui_dock::NodeIndex;
 use render::{
     camera::Camera,
-    lights::DirectionalLight,
-    model::{Edge, EdgeVertex, Point, Surface, SurfaceVertex},
-    model::{Model, ModelEdge, ModelPoint, ModelSurface, OpaqueMaterial},
+    lights::Lights,
+    model::Geometry,
     rgba,
-    scene::{Scene, SceneLights},
+    scene::{Scene, SceneBuilder},
     Rgb, Rgba,
 };
+use space::Transform3;
+use topology::interference::{Interference, PairInterference};
 
-use crate::ui::MessageBus;
+use crate::{
+    error::{CaditError, CaditResult},
+    ui::{MessageBus, UiMessage},
+};
 
-use self::features::FeaturesPane;
+use self::{
+    assembly::AssemblyPane, features::FeaturesPane, interference::InterferencePane,
+    parameters::ParametersPane, properties::PropertiesPane, units::UnitsPane,
+};
 
 use super::workspace::PaneToAdd;
 
+pub mod assembly;
+pub mod explorer;
 pub mod features;
+pub mod interference;
+pub mod parameters;
+pub mod properties;
+pub mod units;
+
+/// The part document of the editor last used, shared with the panes that show
+/// parts of it
+pub(crate) type ActiveDocument = Rc<RefCell<Option<Rc<RefCell<OpenDocument>>>>>;
+
+/// How far the tessellation of a body shown in the panes may stray from its faces
+const DISPLAY_DEVIATION: f64 = 0.01;
+
+/// How sharply the faces of a body have to meet for an edge to be drawn between
+/// them, in radians
+const FEATURE_ANGLE: f64 = 0.5;
+
+/// Tells the kind of a file to open from its extension
+fn file_kind(path: &Path) -> CaditResult<FileKind> {
+    if path.is_dir() {
+        return Err(CaditError::AttemptToOpenDirectoryAsFile(path.to_path_buf()));
+    }
+    let extension = path.extension().ok_or(CaditError::MissingFileExtension)?;
+    let extension = extension
+        .to_str()
+        .ok_or_else(|| CaditError::UnreadableFileExtension(extension.to_os_string()))?;
+    FileKind::from_extension(extension)
+        .ok_or_else(|| CaditError::InvalidFileExtension(extension.to_owned()))
+}
+
+/// A part document opened from a file, along with the edits made to it since
+pub(crate) struct OpenDocument {
+    pub document: Document,
+    pub history: History,
+    pub path: PathBuf,
+    /// Counts the changes made to the document, so views of it know when to
+    /// rebuild
+    pub revision: u64,
+}
+impl OpenDocument {
+    pub fn load(path: &Path) -> CaditResult<Self> {
+        let mut document = Document::load(path)?;
+        document.apply_parameters();
+        document.regenerate();
+        Ok(Self {
+            document,
+            history: History::new(),
+            path: path.to_path_buf(),
+            revision: 0,
+        })
+    }
+
+    /// Records a change already made to the part of the document `field`
+    /// selects, which `key` names, given its value from before the change
+    pub fn record<T: 'static>(
+        &mut self,
+        name: &str,
+        key: &'static str,
+        field: fn(&mut Document) -> &mut T,
+        before: T,
+    ) {
+        self.history
+            .record(Box::new(Change::new(name, key, field, before)));
+        self.revision += 1;
+    }
+
+    /// Changes the part of the document `field` selects, which `key` names, to
+    /// `value`
+    pub fn execute<T: 'static>(
+        &mut self,
+        name: &str,
+        key: &'static str,
+        field: fn(&mut Document) -> &mut T,
+        value: T,
+    ) {
+        self.history.execute(
+            &mut self.document,
+            Box::new(Change::new(name, key, field, value)),
+        );
+        self.revision += 1;
+    }
+
+    pub fn undo(&mut self) -> bool {
+        let undone = self.history.undo(&mut self.document);
+        self.revision += undone as u64;
+        undone
+    }
 
-pub struct PaneView {
+    pub fn redo(&mut self) -> bool {
+        let redone = self.history.redo(&mut self.document);
+        self.revision += redone as u64;
+        redone
+    }
+
+    pub fn save(&mut self) -> CaditResult<()> {
+        self.document.save(&self.path)?;
+        self.history.mark_saved();
+        Ok(())
+    }
+}
+
+/// The assembly of the editor last used, shared with the panes that show it
+pub(crate) type ActiveAssembly = Rc<RefCell<Option<Rc<RefCell<OpenAssembly>>>>>;
+
+/// An assembly document opened from a file, along with the edits made to it
+/// since and how it was last solved and checked for interference
+pub(crate) struct OpenAssembly {
+    pub document: AssemblyDocument,
+    pub history: History<AssemblyDocument>,
+    pub path: PathBuf,
+    pub solution: AssemblyResult<Solution>,
+    pub interference: Option<Vec<(InstanceId, InstanceId, Interference)>>,
+    /// How close instances can be before the assembly view marks them, in
+    /// millimeters
+    pub min_clearance: f64,
+    /// The pair the assembly view highlights, or `None` for every pair the last
+    /// check found
+    pub highlighted: Option<(InstanceId, InstanceId)>,
+    /// Counts the changes made to the assembly, so views of it know when to
+    /// place the instances again
+    pub revision: u64,
+    /// Counts the checks and changes to what the assembly view highlights, so
+    /// it knows when to rebuild the highlights alone
+    pub highlight_revision: u64,
+}
+impl OpenAssembly {
+    pub fn load(path: &Path) -> CaditResult<Self> {
+        let mut document = AssemblyDocument::load(path)?;
+        let solution = document.assembly.solve();
+        Ok(Self {
+            document,
+            history: History::new(),
+            path: path.to_path_buf(),
+            solution,
+            interference: None,
+            min_clearance: 0.0,
+            highlighted: None,
+            revision: 0,
+            highlight_revision: 0,
+        })
+    }
+
+    /// Records an edit already made to the assembly, given the assembly from
+    /// before it, and places the instances to meet the mates again. Undoing the
+    /// edit puts back the instances that solving moved.
+    pub fn record(&mut self, name: &str, before: Assembly) {
+        self.solve();
+        self.history.record(Box::new(Change::new(
+            name,
+            "assembly",
+            |d: &mut AssemblyDocument| &mut d.assembly,
+            before,
+        )));
+        self.revision += 1;
+    }
+
+    pub fn undo(&mut self) -> bool {
+        let undone = self.history.undo(&mut self.document);
+        if undone {
+            self.solve();
+            self.revision += 1;
+        }
+        undone
+    }
+
+    pub fn redo(&mut self) -> bool {
+        let redone = self.history.redo(&mut self.document);
+        if redone {
+            self.solve();
+            self.revision += 1;
+        }
+        redone
+    }
+
+    /// Places the instances to meet the mates again, after the assembly changed
+    fn solve(&mut self) {
+        self.solution = self.document.assembly.solve();
+        self.interference = None;
+    }
+
+    /// Checks the instances' bodies against each other where they are placed,
+    /// each pair to the looser of its parts' tolerances. The bodies are
+    /// tessellated as the assembly view shows them, so the triangles found line
+    /// up with its models.
+    pub fn check_interference(&mut self) -> CaditResult<()> {
+        self.interference = Some(self.document.interference(&self.path, DISPLAY_DEVIATION)?);
+        self.highlight_revision += 1;
+        Ok(())
+    }
+
+    /// Changes what the assembly view highlights of the last check
+    pub fn set_highlight(
+        &mut self,
+        min_clearance: f64,
+        highlighted: Option<(InstanceId, InstanceId)>,
+    ) {
+        if (min_clearance, highlighted) != (self.min_clearance, self.highlighted) {
+            self.min_clearance = min_clearance;
+            self.highlighted = highlighted;
+            self.highlight_revision += self.interference.is_some() as u64;
+        }
+    }
+
+    /// The pairs found by the last check that the assembly view highlights, with
+    /// the instances given by their order in `shown`, which is the order of the
+    /// view's models. Pairs with an instance the view doesn't show are left out.
+    pub fn highlighted_pairs(&self, shown: &[InstanceId]) -> Vec<PairInterference> {
+        let Some(pairs) = &self.interference else {
+            return Vec::new();
+        };
+        let index = |id: InstanceId| shown.iter().position(|i| *i == id);
+        pairs
+            .iter()
+            .filter(|(a, b, _)| match self.highlighted {
+                Some(pair) => pair == (*a, *b),
+                None => true,
+            })
+            .filter_map(|(a, b, interference)| {
+                Some(PairInterference {
+                    first: index(*a)?,
+                    second: index(*b)?,
+                    interference: interference.clone(),
+                })
+            })
+            .collect()
+    }
+
+    pub fn save(&mut self) -> CaditResult<()> {
+        self.document.save(&self.path)?;
+        self.history.mark_saved();
+        Ok(())
+    }
+}
+
+/// A part's body as the editors show it
+fn part_geometry(document: &Document) -> Geometry {
+    let mut geometry = Geometry::new();
+    geometry.insert_part(
+        document,
+        Transform3::identity(),
+        DISPLAY_DEVIATION,
+        FEATURE_ANGLE,
+        Rgba::BLACK,
+    );
+    geometry
+}
+
+/// The instances of an assembly as its view shows them, kept so that changing
+/// what the view highlights doesn't read and tessellate the parts again
+struct AssemblyParts {
+    geometry: Geometry,
+    /// The instances shown, in the order of the geometry's models
+    instances: Vec<InstanceId>,
+}
+
+/// Shows each instance's part where the instance is placed, one model per
+/// instance in the assembly's order. Parts are read from beside the assembly.
+/// Instances whose part can't be read are left out, and the reason is shown in
+/// the error dialog, so the rest of the assembly still shows.
+fn assembly_parts(open: &OpenAssembly, messages: &mut MessageBus) -> AssemblyParts {
+    let mut parts: HashMap<PathBuf, Option<Document>> = HashMap::new();
+    let mut shown = AssemblyParts {
+        geometry: Geometry::new(),
+        instances: Vec::new(),
+    };
+    for instance in open.document.assembly.instances() {
+        let path = AssemblyDocument::part_path(&open.path, instance);
+        let part = parts
+            .entry(path.clone())
+            .or_insert_with(|| match Document::load(&path) {
+                Ok(mut part) => {
+                    part.apply_parameters();
+                    part.regenerate();
+                    Some(part)
+                }
+                Err(error) => {
+                    let error = CaditError::InstancePart {
+                        instance: instance.name.clone(),
+                        path,
+                        error,
+                    };
+                    messages.push(UiMessage::ErrorDialog(error.to_string()));
+                    None
+                }
+            });
+        let Some(part) = part else {
+            continue;
+        };
+        shown.geometry.insert_part(
+            part,
+            instance.placement.transform(),
+            DISPLAY_DEVIATION,
+            FEATURE_ANGLE,
+            Rgba::BLACK,
+        );
+        shown.instances.push(instance.id);
+    }
+    shown
+}
+
+/// The assembly's parts with what the last interference check found
+/// highlighted over them
+fn assembly_geometry(open: &OpenAssembly, parts: &AssemblyParts) -> Geometry {
+    let mut geometry = parts.geometry.clone();
+    if open.interference.is_some() {
+        geometry.highlight_interference(
+            &open.highlighted_pairs(&parts.instances),
+            open.min_clearance,
+            Rgba::RED,
+            Rgba::YELLOW,
+        );
+    }
+    geometry
+}
+
+/// A scene showing `geometry` from a view saved with a document
+fn editor_scene(camera: &CameraState, geometry: Geometry) -> Scene {
+    let mut scene = SceneBuilder::empty();
+    scene
+        .background(rgba(0.1, 0.2, 0.4, 1.0))
+        .lights(
+            Lights::new()
+                .directional(vec3(1.0, 0.0, 1.0).normalize(), Rgb::BLUE, 1.0)
+                .directional(vec3(-1.0, 0.0, 1.0).normalize(), Rgb::YELLOW, 1.0),
+        )
+        .camera(Camera::from_state([0, 0], camera))
+        .geometry(geometry);
+    scene.build()
+}
+
+pub(crate) struct PaneView {
     pub pane: Box<dyn Pane>,
 }
 impl PaneView {
@@ -34,21 +383,48 @@ impl PaneView {
     }
 }
 
-pub trait Pane {
+pub(crate) trait Pane {
     fn title(&self) -> String;
-    fn show(&mut self, ui: &mut Ui);
+    fn show(&mut self, ui: &mut Ui, messages: &mut MessageBus);
+
+    /// The part document the pane edits, if any
+    fn document(&self) -> Option<Rc<RefCell<OpenDocument>>> {
+        None
+    }
+
+    /// The assembly the pane edits, if any
+    fn assembly(&self) -> Option<Rc<RefCell<OpenAssembly>>> {
+        None
+    }
 }
 
 pub(super) struct PaneViewer<'a> {
     pub messages: &'a mut MessageBus,
     pub panes_to_add: &'a mut Vec<PaneToAdd>,
+    pub active: &'a ActiveDocument,
+    pub active_assembly: &'a ActiveAssembly,
+    pub focus: &'a mut Option<FileKind>,
 }
 impl<'a> egui_dock::TabViewer for PaneViewer<'a> {
     type Tab = PaneView;
 
     fn ui(&mut self, ui: &mut egui::Ui, tab: &mut Self::Tab) {
-        tab.pane.show(ui);
+        let rect = ui.max_rect();
+        tab.pane.show(ui, self.messages);
         //tab.show(ui);
+
+        // Clicking in an editor makes its document the one the other panes show
+        // and the one edits are undone in
+        if ui.input().pointer.any_pressed() && ui.rect_contains_pointer(rect) {
+            if let Some(document) = tab.pane.document() {
+                *self.active.borrow_mut() = Some(document);
+                *self.focus = Some(FileKind::Part);
+            }
+            if let Some(assembly) = tab.pane.assembly() {
+                *self.active_assembly.borrow_mut() = Some(assembly);
+                *self.focus = Some(FileKind::Assembly);
+            }
+        }
     }
 
     fn context_menu(&mut self, ui: &mut Ui, _tab: &mut Self::Tab) {
@@ -74,110 +450,193 @@ impl<'a> egui_dock::TabViewer for PaneViewer<'a> {
                 .push(PaneToAdd::new(node, EditorPane::assembly()));
         }
 
+        if ui.button("Assembly tree").clicked() {
+            self.panes_to_add.push(PaneToAdd::new(
+                node,
+                AssemblyPane::new(self.active_assembly.clone()),
+            ))
+        }
+
+        if ui.button("Interference").clicked() {
+            self.panes_to_add.push(PaneToAdd::new(
+                node,
+                InterferencePane::new(self.active_assembly.clone()),
+            ))
+        }
+
         if ui.button("Features").clicked() {
             self.panes_to_add
-                .push(PaneToAdd::new(node, FeaturesPane::new()))
+                .push(PaneToAdd::new(node, FeaturesPane::new(self.active.clone())))
+        }
+
+        if ui.button("Parameters").clicked() {
+            self.panes_to_add.push(PaneToAdd::new(
+                node,
+                ParametersPane::new(self.active.clone()),
+            ))
+        }
+
+        if ui.button("Properties").clicked() {
+            self.panes_to_add.push(PaneToAdd::new(
+                node,
+                PropertiesPane::new(self.active.clone()),
+            ))
+        }
+
+        if ui.button("Units").clicked() {
+            self.panes_to_add
+                .push(PaneToAdd::new(node, UnitsPane::new(self.active.clone())))
         }
     }
 }
 
 pub struct EditorPane {
     editor: Box<dyn Editor>,
+    path: Option<PathBuf>,
+    document: Option<Rc<RefCell<OpenDocument>>>,
+    assembly: Option<Rc<RefCell<OpenAssembly>>>,
+    /// The revision of the document the editor last showed
+    revision: u64,
+    /// The highlight revision of the assembly the editor last showed
+    highlight_revision: u64,
+    /// The assembly's instances as last placed, or `None` until they are first
+    /// shown
+    parts: Option<AssemblyParts>,
 }
 impl EditorPane {
+    /// Opens a part or assembly file in the editor for its kind, which is told
+    /// by the file's extension
+    pub fn open(path: &Path) -> CaditResult<Self> {
+        let mut pane = match file_kind(path)? {
+            FileKind::Part => {
+                let open = OpenDocument::load(path)?;
+                let mut pane = Self::with_part(&open.document);
+                pane.revision = open.revision;
+                pane.document = Some(Rc::new(RefCell::new(open)));
+                pane
+            }
+            FileKind::Assembly => {
+                let open = OpenAssembly::load(path)?;
+                let mut pane = Self::with_assembly(&open);
+                pane.assembly = Some(Rc::new(RefCell::new(open)));
+                pane
+            }
+        };
+        pane.path = Some(path.to_path_buf());
+        Ok(pane)
+    }
+
+    pub fn path(&self) -> Option<&Path> {
+        self.path.as_deref()
+    }
+
+    /// A part editor for a new, empty part
     pub fn part() -> Self {
+        Self::with_part(&Document::new())
+    }
+
+    fn with_part(document: &Document) -> Self {
         Self {
-            editor: Box::new(PartEditor::new(Scene::new(
-                rgba(0.1, 0.2, 0.4, 1.0),
-                SceneLights::new(
-                    vec![
-                        //AmbientLight::new(Rgb::WHITE, 0.05),
-                        //AmbientLight::new(Rgb::RED, 0.5),
-                    ],
-                    vec![
-                        DirectionalLight::new(vec3(1.0, 0.0, 1.0).normalize(), Rgb::BLUE, 1.0),
-                        DirectionalLight::new(vec3(-1.0, 0.0, 1.0).normalize(), Rgb::YELLOW, 1.0),
-                    ],
-                    vec![
-                        //PointLight::new(point3(3.0, 3.0, -5.0), Rgb::RED, 7.0),
-                        //PointLight::new(point3(-3.0, -3.0, -5.0), Rgb::GREEN, 2.0),
-                    ],
-                ),
-                Camera::create_perspective(
-                    [0, 0],
-                    point3(0.0, 0.0, -5.0),
-                    vec3(0.0, 0.0, 1.0),
-                    vec3(0.0, -1.0, 0.0).normalize(),
-                    Deg(70.0).into(),
-                    0.01,
-                    5.0,
-                ),
-                vec![Model::new(
-                    vec![ModelSurface::new(
-                        0.into(),
-                        Surface::new(
-                            [
-                                SurfaceVertex::new(point3(-0.9, -0.9, 0.0), vec3(0.0, 0.0, -1.0)),
-                                SurfaceVertex::new(point3(-0.9, 0.9, 0.0), vec3(0.0, 0.0, -1.0)),
-                                SurfaceVertex::new(point3(0.9, -0.9, 0.0), vec3(0.0, 0.0, -1.0)),
-                                SurfaceVertex::new(point3(0.6, 0.6, 0.0), vec3(0.0, 0.0, -1.0)),
-                            ],
-                            [0, 1, 2, 2, 1, 3],
-                        ),
-                        0,
-                    )],
-                    vec![],
-                    vec![ModelEdge::new(
-                        0.into(),
-                        Edge::new([
-                            EdgeVertex::new(point3(-0.9, -0.9, 0.0), vec3(0.0, 0.0, -1.0)),
-                            EdgeVertex::new(point3(-0.9, 0.9, 0.0), vec3(0.0, 0.0, -1.0)),
-                            EdgeVertex::new(point3(0.9, -0.9, 0.0), vec3(0.0, 0.0, -1.0)),
-                            EdgeVertex::new(point3(0.6, 0.6, 0.0), vec3(0.0, 0.0, -1.0)),
-                        ]),
-                        Rgba::BLACK,
-                    )],
-                    vec![
-                        ModelPoint::new(
-                            0.into(),
-                            Point::new(point3(-0.9, -0.9, 0.0), vec3(0.0, 0.0, -1.0)),
-                            Rgba::WHITE,
-                        ),
-                        ModelPoint::new(
-                            0.into(),
-                            Point::new(point3(-0.9, 0.9, 0.0), vec3(0.0, 0.0, -1.0)),
-                            Rgba::WHITE,
-                        ),
-                        ModelPoint::new(
-                            0.into(),
-                            Point::new(point3(0.9, -0.9, 0.0), vec3(0.0, 0.0, -1.0)),
-                            Rgba::WHITE,
-                        ),
-                        ModelPoint::new(
-                            0.into(),
-                            Point::new(point3(0.6, 0.6, 0.0), vec3(0.0, 0.0, -1.0)),
-                            Rgba::WHITE,
-                        ),
-                    ],
-                )],
-                vec![OpaqueMaterial::new(rgb(1.0, 1.0, 1.0), 0.5)],
-                vec![],
+            path: None,
+            document: None,
+            assembly: None,
+            revision: 0,
+            highlight_revision: 0,
+            parts: None,
+            editor: Box::new(PartEditor::new(editor_scene(
+                &document.camera,
+                part_geometry(document),
             ))),
         }
     }
 
+    /// An assembly editor for a new, empty assembly
     pub fn assembly() -> Self {
         Self {
-            editor: Box::new(AssemblyEditor::new()),
+            editor: Box::new(AssemblyEditor::new(editor_scene(
+                &CameraState::default(),
+                Geometry::new(),
+            ))),
+            path: None,
+            document: None,
+            assembly: None,
+            revision: 0,
+            highlight_revision: 0,
+            parts: None,
+        }
+    }
+
+    /// An assembly editor from the view saved with an assembly, whose instances
+    /// are placed when the pane is first shown
+    fn with_assembly(open: &OpenAssembly) -> Self {
+        Self {
+            editor: Box::new(AssemblyEditor::new(editor_scene(
+                &open.document.camera,
+                Geometry::new(),
+            ))),
+            path: None,
+            document: None,
+            assembly: None,
+            revision: 0,
+            highlight_revision: 0,
+            parts: None,
         }
     }
 }
 impl Pane for EditorPane {
+    /// The name of the file being edited, marked when it has unsaved changes
     fn title(&self) -> String {
-        self.editor.title()
+        let title = match self.path.as_ref().and_then(|path| path.file_name()) {
+            Some(name) => name.to_string_lossy().into_owned(),
+            None => self.editor.title(),
+        };
+        let dirty = match (&self.document, &self.assembly) {
+            (Some(document), _) => document.borrow().history.is_dirty(),
+            (_, Some(assembly)) => assembly.borrow().history.is_dirty(),
+            _ => false,
+        };
+        if dirty {
+            format!("{title} *")
+        } else {
+            title
+        }
+    }
+
+    /// The part document being edited, if the pane was opened from a part file
+    fn document(&self) -> Option<Rc<RefCell<OpenDocument>>> {
+        self.document.clone()
     }
 
-    fn show(&mut self, ui: &mut eframe::egui::Ui) {
+    /// The assembly being edited, if the pane was opened from an assembly file
+    fn assembly(&self) -> Option<Rc<RefCell<OpenAssembly>>> {
+        self.assembly.clone()
+    }
+
+    fn show(&mut self, ui: &mut eframe::egui::Ui, messages: &mut MessageBus) {
+        // Show the part as it is rebuilt after edits, undos and redos
+        if let Some(document) = &self.document {
+            let open = document.borrow();
+            if open.revision != self.revision {
+                self.editor.set_geometry(part_geometry(&open.document));
+                self.revision = open.revision;
+            }
+        }
+        // and the instances as they are placed again after assembly edits, with
+        // only the highlights rebuilt after checks
+        if let Some(assembly) = &self.assembly {
+            let open = assembly.borrow();
+            let placed = self.parts.is_some() && open.revision == self.revision;
+            if !placed || open.highlight_revision != self.highlight_revision {
+                let parts = match self.parts.take() {
+                    Some(parts) if placed => parts,
+                    _ => assembly_parts(&open, messages),
+                };
+                self.editor.set_geometry(assembly_geometry(&open, &parts));
+                self.parts = Some(parts);
+                self.revision = open.revision;
+                self.highlight_revision = open.highlight_revision;
+            }
+        }
         self.editor.show(ui);
 
         /*
@@ -190,3 +649,272 @@ impl Pane for EditorPane {
         */
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use std::path::{Path, PathBuf};
+
+    use document::{assembly::AssemblyDocument, error::DocumentError, project::FileKind, Document};
+    use features::feature::{Combine, FeatureKind};
+    use space::{EPlacement3, EVec2, EVec3};
+
+    use crate::{
+        error::CaditError,
+        ui::{MessageBus, UiMessage},
+    };
+
+    use super::{assembly_parts, file_kind, OpenAssembly, OpenDocument};
+
+    fn temp_path(name: &str) -> PathBuf {
+        std::env::temp_dir().join(format!("cadit-panes-{}-{name}", std::process::id()))
+    }
+
+    #[test]
+    fn file_kinds() {
+        assert_eq!(
+            file_kind(Path::new("bracket.cadpart")).unwrap(),
+            FileKind::Part
+        );
+        assert_eq!(
+            file_kind(Path::new("models/Gearbox.CADASM")).unwrap(),
+            FileKind::Assembly
+        );
+
+        assert!(matches!(
+            file_kind(Path::new("README")),
+            Err(CaditError::MissingFileExtension)
+        ));
+        assert!(matches!(
+            file_kind(Path::new("notes.txt")),
+            Err(CaditError::InvalidFileExtension(extension)) if extension == "txt"
+        ));
+        assert!(matches!(
+            file_kind(&std::env::temp_dir()),
+            Err(CaditError::AttemptToOpenDirectoryAsFile(_))
+        ));
+
+        #[cfg(unix)]
+        {
+            use std::{ffi::OsStr, os::unix::ffi::OsStrExt};
+            assert!(matches!(
+                file_kind(Path::new(OsStr::from_bytes(b"part.\xff"))),
+                Err(CaditError::UnreadableFileExtension(_))
+            ));
+        }
+    }
+
+    #[test]
+    fn part_round_trip() {
+        let path = temp_path("round-trip.cadpart");
+        Document::new().save(&path).unwrap();
+        let mut open = OpenDocument::load(&path).unwrap();
+        assert!(!open.history.is_dirty());
+
+        open.execute(
+            "Edit tolerance",
+            "tolerance.linear",
+            |d| &mut d.tolerance.linear,
+            1e-4,
+        );
+        assert!(open.history.is_dirty());
+        assert_eq!(open.revision, 1);
+        open.save().unwrap();
+        assert!(!open.history.is_dirty());
+
+        let reopened = OpenDocument::load(&path);
+        std::fs::remove_file(&path).unwrap();
+        let reopened = reopened.unwrap();
+        assert_eq!(reopened.document, open.document);
+        assert_eq!(reopened.document.tolerance.linear, 1e-4);
+        assert_eq!(reopened.path, path);
+
+        // Undoing past the save makes the document dirty again
+        assert!(open.undo());
+        assert_eq!(open.revision, 2);
+        assert!(open.history.is_dirty());
+        assert!(!open.undo());
+        assert_eq!(open.revision, 2);
+    }
+
+    #[test]
+    fn assembly_round_trip() {
+        let path = temp_path("round-trip.cadasm");
+        AssemblyDocument::new().save(&path).unwrap();
+        let mut open = OpenAssembly::load(&path).unwrap();
+        assert!(open.solution.is_ok());
+        assert!(!open.history.is_dirty());
+
+        let before = open.document.assembly.clone();
+        let origin = EPlacement3::from_origin(EVec3::new(1.0, 0.0, 0.0));
+        open.document
+            .assembly
+            .add_instance("Base", "base.cadpart", origin);
+        open.record("Add instance", before);
+        assert_eq!(open.revision, 1);
+        assert_eq!(open.history.undo_name(), Some("Add instance"));
+        assert!(open.history.is_dirty());
+
+        open.save().unwrap();
+        assert!(!open.history.is_dirty());
+        let reopened = OpenAssembly::load(&path);
+        std::fs::remove_file(&path).unwrap();
+        assert_eq!(reopened.unwrap().document, open.document);
+
+        // Undoing and redoing the edit puts the instance back and forth
+        assert!(open.undo());
+        assert!(open.document.assembly.instances().is_empty());
+        assert!(open.history.is_dirty());
+        assert!(open.redo());
+        assert_eq!(open.document.assembly.instances().len(), 1);
+        assert!(!open.history.is_dirty());
+        assert_eq!(open.revision, 3);
+        assert!(!open.redo());
+    }
+
+    /// A part whose body is a unit cube with a corner at the origin
+    fn cube() -> Document {
+        let mut document = Document::new();
+        let sketch = document
+            .features
+            .insert(
+                "Sketch 1",
+                FeatureKind::Sketch {
+                    placement: EPlacement3::default(),
+                    outer: vec![
+                        EVec2::new(0.0, 0.0),
+                        EVec2::new(1.0, 0.0),
+                        EVec2::new(1.0, 1.0),
+                        EVec2::new(0.0, 1.0),
+                    ],
+                    holes: Vec::new(),
+                },
+                &[],
+            )
+            .unwrap();
+        document
+            .features
+            .insert(
+                "Extrude 1",
+                FeatureKind::Extrude {
+                    sketch,
+                    combine: Combine::Add,
+                },
+                &[("depth", 1.0)],
+            )
+            .unwrap();
+        document
+    }
+
+    #[test]
+    fn interference_highlights() {
+        let part = temp_path("cube.cadpart");
+        let path = temp_path("interference.cadasm");
+        cube().save(&part).unwrap();
+        let mut document = AssemblyDocument::new();
+        let at = |x: f64| EPlacement3::from_origin(EVec3::new(x, 0.0, 0.0));
+        let name = part.file_name().unwrap();
+        let base = document.assembly.add_instance("Base", name, at(0.0));
+        let crossing = document.assembly.add_instance("Crossing", name, at(0.5));
+        document.assembly.add_instance("Apart", name, at(3.0));
+        document.save(&path).unwrap();
+
+        let mut open = OpenAssembly::load(&path).unwrap();
+        let checked = open.check_interference();
+        std::fs::remove_file(&part).unwrap();
+        std::fs::remove_file(&path).unwrap();
+        checked.unwrap();
+        assert_eq!((open.revision, open.highlight_revision), (0, 1));
+
+        // Every pair is highlighted, given by the instances' order
+        let shown = open
+            .document
+            .assembly
+            .instances()
+            .iter()
+            .map(|instance| instance.id)
+            .collect::<Vec<_>>();
+        let pairs = open.highlighted_pairs(&shown);
+        let order = pairs
+            .iter()
+            .map(|p| (p.first, p.second))
+            .collect::<Vec<_>>();
+        assert_eq!(order, [(0, 1), (0, 2), (1, 2)]);
+        assert!(pairs[0].interference.interferes());
+
+        // until one is picked
+        open.set_highlight(0.5, Some((base, crossing)));
+        assert_eq!((open.revision, open.highlight_revision), (0, 2));
+        let pairs = open.highlighted_pairs(&shown);
+        assert_eq!(pairs.len(), 1);
+        assert_eq!((pairs[0].first, pairs[0].second), (0, 1));
+        open.set_highlight(0.5, Some((base, crossing)));
+        assert_eq!(open.highlight_revision, 2);
+
+        // Instances the view doesn't show have no models to highlight
+        assert!(open.highlighted_pairs(&shown[1..]).is_empty());
+
+        // Edits put the instances somewhere else, so the check is dropped
+        let before = open.document.assembly.clone();
+        open.document.assembly.set_grounded(crossing, true).unwrap();
+        open.record("Ground instance", before);
+        assert!(open.interference.is_none());
+        assert!(open.highlighted_pairs(&shown).is_empty());
+    }
+
+    #[test]
+    fn missing_parts() {
+        let part = temp_path("shown.cadpart");
+        let path = temp_path("missing-parts.cadasm");
+        cube().save(&part).unwrap();
+        let mut document = AssemblyDocument::new();
+        let origin = EPlacement3::default();
+        let shown =
+            document
+                .assembly
+                .add_instance("Shown", part.file_name().unwrap(), origin.clone());
+        document
+            .assembly
+            .add_instance("Missing", "missing.cadpart", origin);
+        document.save(&path).unwrap();
+
+        let open = OpenAssembly::load(&path).unwrap();
+        let mut messages = MessageBus::new();
+        let parts = assembly_parts(&open, &mut messages);
+        std::fs::remove_file(&part).unwrap();
+        std::fs::remove_file(&path).unwrap();
+
+        // The instance whose part is missing is left out, and the dialog says why
+        assert_eq!(parts.instances, [shown]);
+        assert!(matches!(
+            messages.pop(),
+            Some(UiMessage::ErrorDialog(message)) if message.contains("`Missing`")
+        ));
+        assert!(messages.pop().is_none());
+    }
+
+    #[test]
+    fn open_errors() {
+        let missing = temp_path("missing.cadpart");
+        assert!(matches!(
+            OpenDocument::load(&missing),
+            Err(CaditError::Document(DocumentError::Io(_)))
+        ));
+        assert!(matches!(
+            OpenAssembly::load(&missing.with_extension("cadasm")),
+            Err(CaditError::Document(DocumentError::Io(_)))
+        ));
+
+        // A part where an assembly is expected, and a file that isn't JSON
+        let path = temp_path("errors.cadasm");
+        Document::new().save(&path).unwrap();
+        let part = OpenAssembly::load(&path);
+        std::fs::write(&path, "not a document").unwrap();
+        let garbage = OpenDocument::load(&path);
+        std::fs::remove_file(&path).unwrap();
+        assert!(matches!(
+            part,
+            Err(CaditError::Document(DocumentError::NotADocument))
+        ));
+        assert!(matches!(garbage, Err(CaditError::Document(_))));
+    }
+}
@@ -0,0 +1,75 @@
+use components::panes::parameters::{ParameterEdit, ParameterList};
+
+use crate::ui::MessageBus;
+
+use super::{ActiveDocument, Pane};
+
+/// Shows the parameter table of the part being edited, and rebuilds the features
+/// driven by it when it changes. Renaming a parameter begins a transaction, so
+/// the rename and the edits of the expressions that used the old name are
+/// undone as one.
+pub struct ParametersPane {
+    document: ActiveDocument,
+    list: ParameterList,
+    /// The old names of the parameters renamed in the open transaction
+    renamed: Vec<String>,
+}
+impl ParametersPane {
+    pub fn new(document: ActiveDocument) -> Self {
+        Self {
+            document,
+            list: ParameterList::new(),
+            renamed: Vec::new(),
+        }
+    }
+}
+impl Pane for ParametersPane {
+    fn title(&self) -> String {
+        "Parameters".to_owned()
+    }
+
+    fn show(&mut self, ui: &mut eframe::egui::Ui, _messages: &mut MessageBus) {
+        let document = self.document.borrow().clone();
+        match document {
+            Some(document) => {
+                let mut open = document.borrow_mut();
+                let before = open.document.parameters.clone();
+                let length = open.document.units.length();
+                if let Some(edit) = self.list.show(ui, &mut open.document.parameters, length) {
+                    if let ParameterEdit::Renamed(name) = edit {
+                        if self.renamed.is_empty() {
+                            open.history.begin("Rename parameter");
+                        }
+                        self.renamed.push(name);
+                    }
+                    open.record(
+                        "Edit parameters",
+                        "parameters",
+                        |d| &mut d.parameters,
+                        before,
+                    );
+                    open.document.apply_parameters();
+                    open.document.regenerate();
+                }
+
+                // Undoing commits the transaction, otherwise it lasts until nothing
+                // uses the old names
+                if !self.renamed.is_empty() {
+                    if !open.history.in_transaction() {
+                        self.renamed.clear();
+                    } else if !self
+                        .renamed
+                        .iter()
+                        .any(|name| open.document.uses_parameter(name))
+                    {
+                        open.history.commit();
+                        self.renamed.clear();
+                    }
+                }
+            }
+            None => {
+                ui.label("Open a part to see its parameters");
+            }
+        }
+    }
+}
@@ -0,0 +1,97 @@
+use std::{
+    cell::RefCell,
+    rc::{Rc, Weak},
+};
+
+use eframe::egui::{DragValue, Grid, Ui};
+use exchange::mesh::MeshPart;
+use space::MassProperties;
+
+use crate::ui::MessageBus;
+
+use super::{ActiveDocument, OpenDocument, Pane, DISPLAY_DEVIATION};
+
+/// Shows the mass properties of the body built by the part being edited,
+/// tessellating it again whenever the part changes
+pub struct PropertiesPane {
+    document: ActiveDocument,
+    /// The body last tessellated, along with the document and revision it came from
+    body: Option<(Weak<RefCell<OpenDocument>>, u64, Option<MeshPart>)>,
+    density: f64,
+}
+impl PropertiesPane {
+    pub fn new(document: ActiveDocument) -> Self {
+        Self {
+            document,
+            body: None,
+            density: 1.0,
+        }
+    }
+}
+impl Pane for PropertiesPane {
+    fn title(&self) -> String {
+        "Properties".to_owned()
+    }
+
+    fn show(&mut self, ui: &mut Ui, _messages: &mut MessageBus) {
+        let document = self.document.borrow().clone();
+        let Some(document) = document else {
+            ui.label("Open a part to see its mass properties");
+            return;
+        };
+
+        let open = document.borrow();
+        let current = match &self.body {
+            Some((built, revision, _)) => {
+                built.ptr_eq(&Rc::downgrade(&document)) && *revision == open.revision
+            }
+            None => false,
+        };
+        if !current {
+            let mesh = open.document.body_mesh(DISPLAY_DEVIATION);
+            self.body = Some((Rc::downgrade(&document), open.revision, mesh));
+        }
+        let Some((_, _, Some(mesh))) = &self.body else {
+            ui.label("The part's features don't build a body yet");
+            return;
+        };
+
+        ui.horizontal(|ui| {
+            ui.label("Density");
+            ui.add(
+                DragValue::new(&mut self.density)
+                    .speed(0.01)
+                    .clamp_range(0.0..=f64::MAX),
+            );
+        });
+
+        let props = MassProperties::from_triangles(
+            (0..mesh.triangles.len()).map(|index| mesh.triangle_points(index)),
+            self.density,
+        );
+        let c = props.centroid;
+        Grid::new("mass_properties").striped(true).show(ui, |ui| {
+            for (name, value) in [
+                ("Surface area", format!("{:.6}", props.area)),
+                ("Volume", format!("{:.6}", props.volume)),
+                ("Mass", format!("{:.6}", props.mass)),
+                (
+                    "Center of mass",
+                    format!("({:.6}, {:.6}, {:.6})", c.x, c.y, c.z),
+                ),
+            ] {
+                ui.label(name);
+                ui.label(value);
+                ui.end_row();
+            }
+
+            ui.label("Inertia tensor");
+            ui.vertical(|ui| {
+                for row in props.inertia.iter() {
+                    ui.label(format!("{:.6}  {:.6}  {:.6}", row[0], row[1], row[2]));
+                }
+            });
+            ui.end_row();
+        });
+    }
+}
@@ -0,0 +1,63 @@
+use components::panes::units::UnitSettings;
+
+use crate::ui::MessageBus;
+
+use super::{ActiveDocument, Pane};
+
+/// Shows the units and tolerances of the part being edited, and rebuilds it when
+/// the tolerances change. Dragging a tolerance is undone as one change.
+pub struct UnitsPane {
+    document: ActiveDocument,
+    settings: UnitSettings,
+    dragging: bool,
+}
+impl UnitsPane {
+    pub fn new(document: ActiveDocument) -> Self {
+        Self {
+            document,
+            settings: UnitSettings::new(),
+            dragging: false,
+        }
+    }
+}
+impl Pane for UnitsPane {
+    fn title(&self) -> String {
+        "Units".to_owned()
+    }
+
+    fn show(&mut self, ui: &mut eframe::egui::Ui, _messages: &mut MessageBus) {
+        let document = self.document.borrow().clone();
+        match document {
+            Some(document) => {
+                let mut open = document.borrow_mut();
+                let mut units = open.document.units;
+                let mut tolerance = open.document.tolerance;
+                let changed = self.settings.show(ui, &mut units, &mut tolerance);
+
+                if self.settings.dragging() && !self.dragging {
+                    open.history.begin("Change tolerance");
+                    self.dragging = true;
+                }
+                if units != open.document.units {
+                    open.execute("Change units", "units", |d| &mut d.units, units);
+                }
+                if changed {
+                    open.execute(
+                        "Change tolerance",
+                        "tolerance",
+                        |d| &mut d.tolerance,
+                        tolerance,
+                    );
+                    open.document.regenerate();
+                }
+                if !self.settings.dragging() && self.dragging {
+                    open.history.commit();
+                    self.dragging = false;
+                }
+            }
+            None => {
+                ui.label("Open a part to see its units");
+            }
+        }
+    }
+}
@@ -1,9 +1,15 @@
+use std::{cell::RefCell, rc::Rc};
+
+use document::project::{FileKind, Project};
 use eframe::egui::{self};
 use egui_dock::{DockArea, NodeIndex, StyleBuilder, Tree};
 
-use crate::{ui::organisms::panes::Pane, ui::MessageBus};
+use crate::{error::CaditResult, ui::organisms::panes::Pane, ui::MessageBus};
 
-use super::panes::{features::FeaturesPane, EditorPane, PaneView, PaneViewer};
+use super::panes::{
+    assembly::AssemblyPane, explorer::ExplorerPane, features::FeaturesPane,
+    parameters::ParametersPane, ActiveAssembly, ActiveDocument, EditorPane, PaneView, PaneViewer,
+};
 
 pub(super) struct PaneToAdd {
     parent_node: NodeIndex,
@@ -20,17 +26,137 @@ impl PaneToAdd {
 
 pub(crate) struct Workspace {
     tree: Tree<PaneView>,
+    editors: NodeIndex,
+    project: Rc<RefCell<Option<Project>>>,
+    active: ActiveDocument,
+    active_assembly: ActiveAssembly,
+    /// The kind of document the editor last used edits, which saving, undoing
+    /// and redoing apply to
+    focus: Option<FileKind>,
 }
 impl Workspace {
     pub fn new() -> Self {
+        let project = Rc::new(RefCell::new(None));
+        let active = Rc::new(RefCell::new(None));
+        let active_assembly = Rc::new(RefCell::new(None));
+
         let mut tree = Tree::new(vec![PaneView::new(EditorPane::part())]);
-        tree.split_left(
+        let [editors, _] = tree.split_left(
             NodeIndex::root(),
             0.15,
-            vec![PaneView::new(FeaturesPane::new())],
+            vec![
+                PaneView::new(ExplorerPane::new(project.clone())),
+                PaneView::new(FeaturesPane::new(active.clone())),
+                PaneView::new(ParametersPane::new(active.clone())),
+                PaneView::new(AssemblyPane::new(active_assembly.clone())),
+            ],
         );
 
-        Self { tree }
+        Self {
+            tree,
+            editors,
+            project,
+            active,
+            active_assembly,
+            focus: None,
+        }
+    }
+
+    /// Shows the project's files in the explorer, replacing any open project
+    pub fn open_project(&mut self, project: Project) {
+        *self.project.borrow_mut() = Some(project);
+    }
+
+    pub fn close_project(&mut self) {
+        *self.project.borrow_mut() = None;
+    }
+
+    /// Adds an editor as a tab beside the other editors, making its document the
+    /// active one
+    pub fn open_editor(&mut self, pane: EditorPane) {
+        if let Some(document) = pane.document() {
+            *self.active.borrow_mut() = Some(document);
+            self.focus = Some(FileKind::Part);
+        }
+        if let Some(assembly) = pane.assembly() {
+            *self.active_assembly.borrow_mut() = Some(assembly);
+            self.focus = Some(FileKind::Assembly);
+        }
+        self.tree.set_focused_node(self.editors);
+        self.tree.push_to_focused_leaf(PaneView::new(pane));
+    }
+
+    pub fn save(&self) -> CaditResult<()> {
+        match self.focus {
+            Some(FileKind::Part) => match self.active.borrow().as_ref() {
+                Some(document) => document.borrow_mut().save(),
+                None => Ok(()),
+            },
+            Some(FileKind::Assembly) => match self.active_assembly.borrow().as_ref() {
+                Some(assembly) => assembly.borrow_mut().save(),
+                None => Ok(()),
+            },
+            None => Ok(()),
+        }
+    }
+
+    pub fn undo(&self) {
+        match self.focus {
+            Some(FileKind::Part) => {
+                if let Some(document) = self.active.borrow().as_ref() {
+                    document.borrow_mut().undo();
+                }
+            }
+            Some(FileKind::Assembly) => {
+                if let Some(assembly) = self.active_assembly.borrow().as_ref() {
+                    assembly.borrow_mut().undo();
+                }
+            }
+            None => {}
+        }
+    }
+
+    pub fn redo(&self) {
+        match self.focus {
+            Some(FileKind::Part) => {
+                if let Some(document) = self.active.borrow().as_ref() {
+                    document.borrow_mut().redo();
+                }
+            }
+            Some(FileKind::Assembly) => {
+                if let Some(assembly) = self.active_assembly.borrow().as_ref() {
+                    assembly.borrow_mut().redo();
+                }
+            }
+            None => {}
+        }
+    }
+
+    /// The names of the edits that undoing and redoing would apply to
+    pub fn edit_names(&self) -> (Option<String>, Option<String>) {
+        match self.focus {
+            Some(FileKind::Part) => match self.active.borrow().as_ref() {
+                Some(document) => {
+                    let history = &document.borrow().history;
+                    (
+                        history.undo_name().map(str::to_owned),
+                        history.redo_name().map(str::to_owned),
+                    )
+                }
+                None => (None, None),
+            },
+            Some(FileKind::Assembly) => match self.active_assembly.borrow().as_ref() {
+                Some(assembly) => {
+                    let history = &assembly.borrow().history;
+                    (
+                        history.undo_name().map(str::to_owned),
+                        history.redo_name().map(str::to_owned),
+                    )
+                }
+                None => (None, None),
+            },
+            None => (None, None),
+        }
     }
 
     pub fn show(&mut self, ctx: &egui::Context, ui: &mut egui::Ui, messages: &mut MessageBus) {
@@ -48,6 +174,9 @@ impl Workspace {
                 &mut PaneViewer {
                     messages,
                     panes_to_add: &mut panes_to_add,
+                    active: &self.active,
+                    active_assembly: &self.active_assembly,
+                    focus: &mut self.focus,
                 },
             );
 
@@ -1,11 +1,25 @@
-use cgmath::Quaternion;
+use crate::scene::SceneViewer;
+use cgmath::{vec3, Quaternion};
+use render::{model::Geometry, scene::Scene};
 
 use super::Editor;
 
-pub struct AssemblyEditor {}
+/// Shows the instances of an assembly, each where it is placed
+pub struct AssemblyEditor {
+    viewer: SceneViewer,
+}
 impl AssemblyEditor {
-    pub fn new() -> Self {
-        Self {}
+    pub fn new(scene: Scene) -> Self {
+        Self {
+            viewer: SceneViewer::new(
+                Quaternion::new(1.0, 0.0, 0.0, 0.0),
+                vec3(0.0, 0.0, 0.0),
+                true,
+                true,
+                true,
+                scene,
+            ),
+        }
     }
 }
 impl Editor for AssemblyEditor {
@@ -14,7 +28,7 @@ impl Editor for AssemblyEditor {
     }
 
     fn show(&mut self, ui: &mut eframe::egui::Ui) {
-        ui.label("Edit assembly here");
+        self.viewer.show(ui);
     }
 
     /*
@@ -23,8 +37,12 @@ impl Editor for AssemblyEditor {
     }
     */
 
-    fn set_rotation(&mut self, _rotation: Quaternion<f32>) {
-        // nothing
+    fn set_rotation(&mut self, rotation: Quaternion<f32>) {
+        self.viewer.set_rotation(rotation);
+    }
+
+    fn set_geometry(&mut self, geometry: Geometry) {
+        self.viewer.set_geometry(geometry);
     }
 
     /*
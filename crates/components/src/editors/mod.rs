@@ -1,4 +1,5 @@
 use cgmath::Quaternion;
+use render::model::Geometry;
 
 pub mod assembly;
 pub mod part;
@@ -8,5 +9,8 @@ pub trait Editor {
     fn show(&mut self, ui: &mut eframe::egui::Ui);
     //fn clicked(&self) -> Option<SceneObjectProps>;
     fn set_rotation(&mut self, rotation: Quaternion<f32>);
+
+    /// Replaces what the editor shows, after the document it edits changed
+    fn set_geometry(&mut self, geometry: Geometry);
     //fn animate_rotation(&mut self, rotation: Quaternion<f32>);
 }
@@ -1,7 +1,7 @@
 use crate::{gizmo::Gizmo, scene::SceneViewer};
 use cgmath::{vec3, Quaternion};
 use eframe::egui;
-use render::scene::Scene;
+use render::{model::Geometry, scene::Scene};
 
 use super::Editor;
 
@@ -31,6 +31,10 @@ impl Editor for PartEditor {
         self.viewer.set_rotation(rotation);
     }
 
+    fn set_geometry(&mut self, geometry: Geometry) {
+        self.viewer.set_geometry(geometry);
+    }
+
     fn show(&mut self, ui: &mut egui::Ui) {
         self.viewer.show(ui);
 
@@ -0,0 +1,273 @@
+use std::{f64::consts::FRAC_PI_2, path::Path};
+
+use assembly::{
+    error::AssemblyResult,
+    mate::{Anchor, MateId, MateKind},
+    Assembly, InstanceId, Solution,
+};
+use eframe::egui::{self, CollapsingHeader, DragValue, RichText};
+use space::EPlacement3;
+
+enum Action {
+    Ground(InstanceId, bool),
+    RemoveInstance(InstanceId),
+    AddInstance(String),
+    Suppress(MateId, bool),
+    SetKind(MateId, MateKind),
+    RemoveMate(MateId),
+    AddMate,
+}
+impl Action {
+    /// What the action does, as it is undone from the Edit menu
+    fn name(&self) -> &'static str {
+        match self {
+            Self::Ground(_, true) => "Ground instance",
+            Self::Ground(_, false) => "Unground instance",
+            Self::RemoveInstance(_) => "Remove instance",
+            Self::AddInstance(_) => "Add instance",
+            Self::Suppress(_, true) => "Suppress mate",
+            Self::Suppress(_, false) => "Unsuppress mate",
+            Self::SetKind(..) => "Edit mate",
+            Self::RemoveMate(_) => "Remove mate",
+            Self::AddMate => "Add mate",
+        }
+    }
+}
+
+/// Lists an assembly's instances and the mates between them. Instances can be
+/// grounded and mates suppressed with their checkboxes, and new mates join the
+/// origins of the instances picked for them.
+pub struct AssemblyTree {
+    selected: Option<InstanceId>,
+    new_part: String,
+    new_kind: MateKind,
+    new_a: Option<InstanceId>,
+    new_b: Option<InstanceId>,
+    error: Option<String>,
+}
+impl AssemblyTree {
+    pub fn new() -> Self {
+        Self {
+            selected: None,
+            new_part: String::new(),
+            new_kind: MateKind::Coincident,
+            new_a: None,
+            new_b: None,
+            error: None,
+        }
+    }
+
+    pub fn selected(&self) -> Option<InstanceId> {
+        self.selected
+    }
+
+    /// Shows the assembly along with how it was last solved. If it was changed
+    /// and needs solving again, returns the name of the edit made to it.
+    pub fn show(
+        &mut self,
+        ui: &mut egui::Ui,
+        assembly: &mut Assembly,
+        solution: Option<&AssemblyResult<Solution>>,
+    ) -> Option<&'static str> {
+        let mut action = None;
+
+        CollapsingHeader::new("Instances")
+            .default_open(true)
+            .show(ui, |ui| {
+                for instance in assembly.instances() {
+                    ui.horizontal(|ui| {
+                        let mut grounded = instance.grounded;
+                        if ui
+                            .checkbox(&mut grounded, "")
+                            .on_hover_text("Grounded")
+                            .changed()
+                        {
+                            action = Some(Action::Ground(instance.id, grounded));
+                        }
+                        let selected = self.selected == Some(instance.id);
+                        if ui
+                            .selectable_label(selected, &instance.name)
+                            .on_hover_text(instance.part.to_string_lossy())
+                            .clicked()
+                        {
+                            self.selected = Some(instance.id);
+                        }
+                        if ui.small_button("✖").on_hover_text("Remove").clicked() {
+                            action = Some(Action::RemoveInstance(instance.id));
+                        }
+                    });
+                }
+
+                ui.horizontal(|ui| {
+                    ui.text_edit_singleline(&mut self.new_part)
+                        .on_hover_text("Part file, relative to the assembly");
+                    if ui.button("Add").clicked() {
+                        action = Some(Action::AddInstance(self.new_part.trim().to_string()));
+                    }
+                });
+            });
+
+        let name = |id: InstanceId| match assembly.instance(id) {
+            Some(instance) => instance.name.as_str(),
+            None => "",
+        };
+
+        CollapsingHeader::new("Mates")
+            .default_open(true)
+            .show(ui, |ui| {
+                for mate in assembly.mates() {
+                    ui.horizontal(|ui| {
+                        let mut active = !mate.suppressed;
+                        if ui
+                            .checkbox(&mut active, "")
+                            .on_hover_text("Active")
+                            .changed()
+                        {
+                            action = Some(Action::Suppress(mate.id, !active));
+                        }
+
+                        let mut text = RichText::new(&mate.name);
+                        if mate.suppressed {
+                            text = text.weak();
+                        }
+                        ui.label(text).on_hover_text(format!(
+                            "{} of {} and {}",
+                            mate.kind.name(),
+                            name(mate.a.instance),
+                            name(mate.b.instance)
+                        ));
+
+                        match mate.kind {
+                            MateKind::Distance(mut distance) => {
+                                let response = ui.add(
+                                    DragValue::new(&mut distance)
+                                        .speed(0.1)
+                                        .clamp_range(0.0..=f64::MAX)
+                                        .suffix(" mm"),
+                                );
+                                if response.changed() {
+                                    let kind = MateKind::Distance(distance);
+                                    action = Some(Action::SetKind(mate.id, kind));
+                                }
+                            }
+                            MateKind::Angle(angle) => {
+                                let mut degrees = angle.to_degrees();
+                                let response = ui.add(
+                                    DragValue::new(&mut degrees)
+                                        .speed(1.0)
+                                        .clamp_range(0.0..=180.0)
+                                        .suffix("°"),
+                                );
+                                if response.changed() {
+                                    let kind = MateKind::Angle(degrees.to_radians());
+                                    action = Some(Action::SetKind(mate.id, kind));
+                                }
+                            }
+                            kind => {
+                                ui.label(RichText::new(kind.name()).weak());
+                            }
+                        }
+
+                        if ui.small_button("✖").on_hover_text("Remove").clicked() {
+                            action = Some(Action::RemoveMate(mate.id));
+                        }
+                    });
+                }
+
+                ui.horizontal(|ui| {
+                    egui::ComboBox::from_id_source("mate kind")
+                        .selected_text(self.new_kind.name())
+                        .show_ui(ui, |ui| {
+                            for kind in [
+                                MateKind::Coincident,
+                                MateKind::Concentric,
+                                MateKind::Distance(0.0),
+                                MateKind::Angle(FRAC_PI_2),
+                                MateKind::Fixed,
+                            ] {
+                                ui.selectable_value(&mut self.new_kind, kind, kind.name());
+                            }
+                        });
+                    for (id_source, instance) in
+                        [("mate a", &mut self.new_a), ("mate b", &mut self.new_b)]
+                    {
+                        egui::ComboBox::from_id_source(id_source)
+                            .selected_text(instance.map_or("", name))
+                            .show_ui(ui, |ui| {
+                                for option in assembly.instances() {
+                                    ui.selectable_value(instance, Some(option.id), &option.name);
+                                }
+                            });
+                    }
+                    if ui.button("Add").clicked() {
+                        action = Some(Action::AddMate);
+                    }
+                });
+            });
+
+        match solution {
+            Some(Ok(solution)) if solution.free_degrees == 0 => {
+                ui.label("Fully placed");
+            }
+            Some(Ok(solution)) => {
+                ui.label(format!("{} degrees of freedom left", solution.free_degrees));
+            }
+            Some(Err(error)) => {
+                ui.label(RichText::new(error.to_string()).color(ui.visuals().error_fg_color));
+            }
+            None => {}
+        }
+        if let Some(error) = &self.error {
+            ui.label(RichText::new(error).color(ui.visuals().error_fg_color));
+        }
+
+        let name = action.as_ref()?.name();
+        let result = match action {
+            Some(Action::Ground(id, grounded)) => assembly.set_grounded(id, grounded),
+            Some(Action::RemoveInstance(id)) => {
+                if self.selected == Some(id) {
+                    self.selected = None;
+                }
+                assembly.remove_instance(id).map(|_| ())
+            }
+            Some(Action::AddInstance(part)) if part.is_empty() => return None,
+            Some(Action::AddInstance(part)) => {
+                let name = match Path::new(&part).file_stem() {
+                    Some(stem) => stem.to_string_lossy().into_owned(),
+                    None => part.clone(),
+                };
+                assembly.add_instance(&name, part, EPlacement3::default());
+                self.new_part.clear();
+                Ok(())
+            }
+            Some(Action::Suppress(id, suppressed)) => assembly.set_suppressed(id, suppressed),
+            Some(Action::SetKind(id, kind)) => assembly.set_mate_kind(id, kind),
+            Some(Action::RemoveMate(id)) => assembly.remove_mate(id).map(|_| ()),
+            Some(Action::AddMate) => {
+                let (Some(a), Some(b)) = (self.new_a, self.new_b) else {
+                    return None;
+                };
+                let mate = format!("{} {}", self.new_kind.name(), assembly.mates().len() + 1);
+                let result = match self.new_kind {
+                    MateKind::Fixed => assembly.add_fixed(&mate, a, b),
+                    kind => {
+                        let anchor = |instance| Anchor::new(instance, EPlacement3::default());
+                        assembly.add_mate(&mate, kind, anchor(a), anchor(b))
+                    }
+                };
+                result.map(|_| ())
+            }
+            None => return None,
+        };
+        match result {
+            Ok(()) => {
+                self.error = None;
+                Some(name)
+            }
+            Err(error) => {
+                self.error = Some(error.to_string());
+                None
+            }
+        }
+    }
+}
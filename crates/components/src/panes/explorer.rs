@@ -0,0 +1,79 @@
+use std::{
+    collections::BTreeMap,
+    ffi::OsStr,
+    path::{Path, PathBuf},
+};
+
+use document::project::{FileKind, Project, ProjectFile};
+use eframe::egui::{self, CollapsingHeader};
+
+/// Lists the files of a project as a tree of folders. Clicking a file selects it
+/// and double-clicking opens it.
+pub struct Explorer {
+    selected: Option<PathBuf>,
+}
+impl Explorer {
+    pub fn new() -> Self {
+        Self { selected: None }
+    }
+
+    /// The selected file, relative to the project's folder
+    pub fn selected(&self) -> Option<&Path> {
+        self.selected.as_deref()
+    }
+
+    /// Shows the project's files, returning the full path of the one to open if
+    /// one was double-clicked
+    pub fn show(&mut self, ui: &mut egui::Ui, project: &Project) -> Option<PathBuf> {
+        let files = project.files().iter().collect::<Vec<_>>();
+        let mut open = None;
+        self.show_folder(ui, Path::new(""), &files, &mut open);
+        open.map(|path| project.root().join(path))
+    }
+
+    fn show_folder(
+        &mut self,
+        ui: &mut egui::Ui,
+        folder: &Path,
+        files: &[&ProjectFile],
+        open: &mut Option<PathBuf>,
+    ) {
+        let mut subfolders = BTreeMap::<&OsStr, Vec<&ProjectFile>>::new();
+        let mut own_files = Vec::new();
+        for file in files {
+            let mut components = file.path.strip_prefix(folder).unwrap().components();
+            let first = components.next().unwrap().as_os_str();
+            if components.next().is_some() {
+                subfolders.entry(first).or_default().push(file);
+            } else {
+                own_files.push(file);
+            }
+        }
+
+        for (name, files) in subfolders {
+            let path = folder.join(name);
+            CollapsingHeader::new(name.to_string_lossy())
+                .id_source(&path)
+                .default_open(true)
+                .show(ui, |ui| self.show_folder(ui, &path, &files, open));
+        }
+
+        for file in own_files {
+            let name = file.path.file_name().unwrap().to_string_lossy();
+            let selected = self.selected.as_ref() == Some(&file.path);
+            let response = ui
+                .selectable_label(selected, name)
+                .on_hover_text(match file.kind {
+                    FileKind::Part => "Part",
+                    FileKind::Assembly => "Assembly",
+                });
+
+            if response.clicked() {
+                self.selected = Some(file.path.clone());
+            }
+            if response.double_clicked() {
+                *open = Some(file.path.clone());
+            }
+        }
+    }
+}
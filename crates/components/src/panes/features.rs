@@ -0,0 +1,111 @@
+use eframe::egui::{self, RichText};
+use features::{feature::FeatureId, tree::FeatureTree};
+
+enum Action {
+    Suppress(FeatureId, bool),
+    Move(FeatureId, usize),
+    Rollback(Option<usize>),
+}
+
+/// Lists a part's features in history order, with the rollback bar after the
+/// ones that are built. Features can be suppressed with their checkboxes, and
+/// moved or rolled back to from their context menus. Features that failed to
+/// build are shown in red, with the reason when hovered.
+pub struct FeatureList {
+    selected: Option<FeatureId>,
+}
+impl FeatureList {
+    pub fn new() -> Self {
+        Self { selected: None }
+    }
+
+    pub fn selected(&self) -> Option<FeatureId> {
+        self.selected
+    }
+
+    /// Shows the features, returning whether the tree was changed and needs
+    /// regenerating
+    pub fn show(&mut self, ui: &mut egui::Ui, tree: &mut FeatureTree) -> bool {
+        let rollback = tree.rollback();
+        let count = tree.features().len();
+        let mut action = None;
+
+        for (index, feature) in tree.features().iter().enumerate() {
+            if index == rollback {
+                Self::show_rollback_bar(ui, &mut action);
+            }
+
+            ui.horizontal(|ui| {
+                let mut active = !feature.suppressed;
+                if ui
+                    .checkbox(&mut active, "")
+                    .on_hover_text("Active")
+                    .changed()
+                {
+                    action = Some(Action::Suppress(feature.id, !active));
+                }
+
+                let mut text = RichText::new(&feature.name);
+                if feature.suppressed || index >= rollback {
+                    text = text.weak();
+                }
+                let error = tree.error(feature.id);
+                if error.is_some() {
+                    text = text.color(ui.visuals().error_fg_color);
+                }
+
+                let mut response = ui.selectable_label(self.selected == Some(feature.id), text);
+                if let Some(error) = error {
+                    response = response.on_hover_text(error.to_string());
+                }
+                if response.clicked() {
+                    self.selected = Some(feature.id);
+                }
+                response.context_menu(|ui| {
+                    if ui
+                        .add_enabled(index > 0, egui::Button::new("Move up"))
+                        .clicked()
+                    {
+                        action = Some(Action::Move(feature.id, index - 1));
+                        ui.close_menu();
+                    }
+                    if ui
+                        .add_enabled(index + 1 < count, egui::Button::new("Move down"))
+                        .clicked()
+                    {
+                        action = Some(Action::Move(feature.id, index + 1));
+                        ui.close_menu();
+                    }
+                    if ui.button("Roll back to here").clicked() {
+                        action = Some(Action::Rollback(Some(index + 1)));
+                        ui.close_menu();
+                    }
+                });
+            });
+        }
+        if rollback == count {
+            Self::show_rollback_bar(ui, &mut action);
+        }
+
+        match action {
+            Some(Action::Suppress(id, suppressed)) => tree.set_suppressed(id, suppressed).is_ok(),
+            Some(Action::Move(id, index)) => tree.move_feature(id, index).is_ok(),
+            Some(Action::Rollback(position)) => {
+                tree.set_rollback(position);
+                true
+            }
+            None => false,
+        }
+    }
+
+    fn show_rollback_bar(ui: &mut egui::Ui, action: &mut Option<Action>) {
+        ui.separator();
+        ui.horizontal(|ui| {
+            ui.label(RichText::new("Rollback").small().weak());
+            if ui.small_button("To end").clicked() {
+                *action = Some(Action::Rollback(None));
+            }
+        });
+        ui.separator();
+    }
+}
@@ -0,0 +1,104 @@
+use assembly::{Assembly, InstanceId};
+use eframe::egui::{self, DragValue, RichText};
+use topology::interference::Interference;
+
+/// Lists how pairs of an assembly's instances meet, as found by the last check:
+/// the volume a pair shares where it overlaps, and how far apart it is
+/// otherwise. Pairs that overlap or are closer than the minimum clearance are
+/// shown in red, and the others only when asked for.
+pub struct InterferenceList {
+    min_clearance: f64,
+    show_clear: bool,
+    selected: Option<(InstanceId, InstanceId)>,
+}
+impl InterferenceList {
+    pub fn new() -> Self {
+        Self {
+            min_clearance: 0.0,
+            show_clear: false,
+            selected: None,
+        }
+    }
+
+    /// How close instances can be before they are flagged, in millimeters
+    pub fn min_clearance(&self) -> f64 {
+        self.min_clearance
+    }
+
+    /// The pair picked to be highlighted
+    pub fn selected(&self) -> Option<(InstanceId, InstanceId)> {
+        self.selected
+    }
+
+    /// Shows the pairs found by the last check, or `None` if the assembly hasn't
+    /// been checked since it changed, returning whether a check was asked for
+    pub fn show(
+        &mut self,
+        ui: &mut egui::Ui,
+        assembly: &Assembly,
+        pairs: Option<&[(InstanceId, InstanceId, Interference)]>,
+    ) -> bool {
+        let mut check = false;
+        ui.horizontal(|ui| {
+            check = ui.button("Check").clicked();
+            ui.label("Minimum clearance");
+            ui.add(
+                DragValue::new(&mut self.min_clearance)
+                    .speed(0.1)
+                    .clamp_range(0.0..=f64::MAX)
+                    .suffix(" mm"),
+            );
+        });
+        ui.checkbox(&mut self.show_clear, "Show clear pairs");
+
+        let Some(pairs) = pairs else {
+            ui.label(RichText::new("Not checked since the last change").weak());
+            return check;
+        };
+
+        let name = |id: InstanceId| match assembly.instance(id) {
+            Some(instance) => instance.name.as_str(),
+            None => "",
+        };
+        let mut shown = 0;
+        for (a, b, interference) in pairs {
+            let (text, problem) = match interference.clearance_distance() {
+                _ if interference.interferes() => {
+                    (format!("{:.3} mm³ overlap", interference.volume), true)
+                }
+                Some(distance) if distance <= 0.0 => {
+                    ("Touching".to_string(), self.min_clearance > 0.0)
+                }
+                Some(distance) => (
+                    format!("{distance:.3} mm apart"),
+                    distance < self.min_clearance,
+                ),
+                None => ("No surfaces".to_string(), false),
+            };
+            if !problem && !self.show_clear {
+                continue;
+            }
+            shown += 1;
+
+            ui.horizontal(|ui| {
+                let selected = self.selected == Some((*a, *b));
+                if ui
+                    .selectable_label(selected, format!("{} and {}", name(*a), name(*b)))
+                    .clicked()
+                {
+                    self.selected = if selected { None } else { Some((*a, *b)) };
+                }
+                let mut text = RichText::new(text);
+                if problem {
+                    text = text.color(ui.visuals().error_fg_color);
+                }
+                ui.label(text);
+            });
+        }
+        if shown == 0 {
+            ui.label("No interference found");
+        }
+
+        check
+    }
+}
@@ -1,2 +1,6 @@
+pub mod assembly;
 pub mod explorer;
 pub mod features;
+pub mod interference;
+pub mod parameters;
+pub mod units;
@@ -0,0 +1,189 @@
+use std::collections::HashMap;
+
+use eframe::egui::{self, RichText};
+use parameters::{unit::Unit, ParameterTable};
+
+enum Action {
+    Set(String, String, Unit),
+    Rename(String, String),
+    Remove(String),
+    Add(String),
+}
+
+/// How the parameter table was changed
+pub enum ParameterEdit {
+    /// A parameter was added or removed, or its expression or unit was set
+    Changed,
+    /// The parameter with this name was renamed. Expressions that use the old
+    /// name fail until they are changed too.
+    Renamed(String),
+}
+
+/// Lists a part's parameters with their expressions, units and values. Changed
+/// expressions are kept as typed until they parse, and parameters that fail to
+/// evaluate show the reason in red.
+pub struct ParameterList {
+    /// Expressions being edited that haven't parsed yet, by parameter name
+    drafts: HashMap<String, String>,
+    /// Names being edited, by the parameter's current name
+    names: HashMap<String, String>,
+    new_name: String,
+    error: Option<String>,
+}
+impl ParameterList {
+    pub fn new() -> Self {
+        Self {
+            drafts: HashMap::new(),
+            names: HashMap::new(),
+            new_name: String::new(),
+            error: None,
+        }
+    }
+
+    /// Shows the parameters, returning how the table was changed if the
+    /// features driven by it need updating. New parameters are measured in
+    /// `default_unit`.
+    pub fn show(
+        &mut self,
+        ui: &mut egui::Ui,
+        table: &mut ParameterTable,
+        default_unit: Unit,
+    ) -> Option<ParameterEdit> {
+        let values = table.evaluate();
+        let mut action = None;
+
+        egui::Grid::new("parameters")
+            .num_columns(5)
+            .striped(true)
+            .show(ui, |ui| {
+                ui.label(RichText::new("Name").strong());
+                ui.label(RichText::new("Expression").strong());
+                ui.label(RichText::new("Unit").strong());
+                ui.label(RichText::new("Value").strong());
+                ui.end_row();
+
+                for parameter in table.parameters() {
+                    let mut name = self
+                        .names
+                        .get(&parameter.name)
+                        .cloned()
+                        .unwrap_or_else(|| parameter.name.clone());
+                    let response = ui.text_edit_singleline(&mut name);
+                    if response.changed() {
+                        self.names.insert(parameter.name.clone(), name.clone());
+                    }
+                    if response.lost_focus() && self.names.contains_key(&parameter.name) {
+                        action = Some(Action::Rename(parameter.name.clone(), name.trim().into()));
+                    }
+
+                    let mut text = self
+                        .drafts
+                        .get(&parameter.name)
+                        .cloned()
+                        .unwrap_or_else(|| parameter.expression.text().to_string());
+                    let response = ui.text_edit_singleline(&mut text);
+                    if response.changed() {
+                        self.drafts.insert(parameter.name.clone(), text.clone());
+                    }
+                    if response.lost_focus() && self.drafts.contains_key(&parameter.name) {
+                        action = Some(Action::Set(parameter.name.clone(), text, parameter.unit));
+                    }
+
+                    let mut unit = parameter.unit;
+                    egui::ComboBox::from_id_source(("unit", &parameter.name))
+                        .selected_text(unit_name(unit))
+                        .show_ui(ui, |ui| {
+                            for option in Unit::ALL {
+                                ui.selectable_value(&mut unit, option, unit_name(option));
+                            }
+                        });
+                    if unit != parameter.unit {
+                        let expression = parameter.expression.text().to_string();
+                        action = Some(Action::Set(parameter.name.clone(), expression, unit));
+                    }
+
+                    match &values[&parameter.name] {
+                        Ok(value) => {
+                            let shown = value.value_in(parameter.unit).unwrap_or(value.value);
+                            ui.label(format!("{shown:.4} {}", parameter.unit.symbol()));
+                        }
+                        Err(error) => {
+                            ui.label(
+                                RichText::new(error.to_string()).color(ui.visuals().error_fg_color),
+                            );
+                        }
+                    }
+
+                    if ui.small_button("✖").on_hover_text("Remove").clicked() {
+                        action = Some(Action::Remove(parameter.name.clone()));
+                    }
+                    ui.end_row();
+                }
+            });
+
+        ui.horizontal(|ui| {
+            ui.text_edit_singleline(&mut self.new_name);
+            if ui.button("Add").clicked() {
+                action = Some(Action::Add(self.new_name.trim().to_string()));
+            }
+        });
+        if let Some(error) = &self.error {
+            ui.label(RichText::new(error).color(ui.visuals().error_fg_color));
+        }
+
+        let result = match action {
+            Some(Action::Set(name, expression, unit)) => {
+                let result = table.set(&name, &expression, unit);
+                if result.is_ok() {
+                    self.drafts.remove(&name);
+                }
+                result.map(|_| Some(ParameterEdit::Changed))
+            }
+            Some(Action::Rename(name, new_name)) if new_name == name => {
+                self.names.remove(&name);
+                Ok(None)
+            }
+            Some(Action::Rename(name, new_name)) => {
+                let result = table.rename(&name, &new_name);
+                if result.is_ok() {
+                    self.names.remove(&name);
+                    if let Some(draft) = self.drafts.remove(&name) {
+                        self.drafts.insert(new_name, draft);
+                    }
+                }
+                result.map(|_| Some(ParameterEdit::Renamed(name)))
+            }
+            Some(Action::Remove(name)) => {
+                self.drafts.remove(&name);
+                self.names.remove(&name);
+                Ok(table.remove(&name).map(|_| ParameterEdit::Changed))
+            }
+            Some(Action::Add(name)) if table.get(&name).is_some() => Ok(None),
+            Some(Action::Add(name)) => {
+                let result = table.set(&name, "0", default_unit);
+                if result.is_ok() {
+                    self.new_name.clear();
+                }
+                result.map(|_| Some(ParameterEdit::Changed))
+            }
+            None => return None,
+        };
+        match result {
+            Ok(edit) => {
+                self.error = None;
+                edit
+            }
+            Err(error) => {
+                self.error = Some(error.to_string());
+                None
+            }
+        }
+    }
+}
+
+fn unit_name(unit: Unit) -> &'static str {
+    match unit {
+        Unit::None => "none",
+        unit => unit.symbol(),
+    }
+}
@@ -0,0 +1,102 @@
+use document::units::UnitSystem;
+use eframe::egui::{self, DragValue, RichText};
+use parameters::unit::{Dimension, Unit};
+use space::Tolerance;
+
+/// Edits the units a part is shown in and the tolerances its geometry is
+/// modeled to
+pub struct UnitSettings {
+    dragging: bool,
+}
+impl UnitSettings {
+    pub fn new() -> Self {
+        Self { dragging: false }
+    }
+
+    /// Whether a tolerance is being dragged, so the changes made until it is let
+    /// go can be taken as one
+    pub fn dragging(&self) -> bool {
+        self.dragging
+    }
+
+    /// Shows the settings, returning whether the tolerances were changed and the
+    /// part needs rebuilding
+    pub fn show(
+        &mut self,
+        ui: &mut egui::Ui,
+        units: &mut UnitSystem,
+        tolerance: &mut Tolerance,
+    ) -> bool {
+        let mut length = units.length();
+        let mut angle = units.angle();
+        let mut changed = false;
+        self.dragging = false;
+
+        egui::Grid::new("units").num_columns(2).show(ui, |ui| {
+            ui.label(RichText::new("Units").strong());
+            ui.end_row();
+
+            ui.label("Length");
+            unit_combo(ui, "length", &mut length, Dimension::LENGTH);
+            ui.end_row();
+
+            ui.label("Angle");
+            unit_combo(ui, "angle", &mut angle, Dimension::ANGLE);
+            ui.end_row();
+
+            ui.label(RichText::new("Tolerances").strong());
+            ui.end_row();
+
+            // Tolerances are kept in millimeters and radians, but shown like the
+            // rest of the part
+            ui.label("Linear");
+            let mut linear = units.to_length(tolerance.linear);
+            let speed = linear * 0.1;
+            let response = ui.add(
+                DragValue::new(&mut linear)
+                    .speed(speed)
+                    .clamp_range(f64::MIN_POSITIVE..=f64::MAX)
+                    .suffix(format!(" {}", units.length().symbol())),
+            );
+            self.dragging |= response.dragged();
+            if response.changed() {
+                tolerance.linear = units.from_length(linear);
+                changed = true;
+            }
+            ui.end_row();
+
+            ui.label("Angular");
+            let mut angular = units.to_angle(tolerance.angular);
+            let speed = angular * 0.1;
+            let response = ui.add(
+                DragValue::new(&mut angular)
+                    .speed(speed)
+                    .clamp_range(f64::MIN_POSITIVE..=f64::MAX)
+                    .suffix(format!(" {}", units.angle().symbol())),
+            );
+            self.dragging |= response.dragged();
+            if response.changed() {
+                tolerance.angular = units.from_angle(angular);
+                changed = true;
+            }
+            ui.end_row();
+        });
+
+        if let Some(new) = UnitSystem::new(length, angle) {
+            *units = new;
+        }
+        changed
+    }
+}
+
+fn unit_combo(ui: &mut egui::Ui, id: &str, unit: &mut Unit, dimension: Dimension) {
+    egui::ComboBox::from_id_source(("unit", id))
+        .selected_text(unit.symbol())
+        .show_ui(ui, |ui| {
+            for option in Unit::ALL {
+                if option.dimension() == dimension {
+                    ui.selectable_value(unit, option, option.symbol());
+                }
+            }
+        });
+}
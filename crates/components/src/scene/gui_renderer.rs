@@ -3,7 +3,7 @@ use eframe::epaint::{PaintCallbackInfo, Pos2};
 use egui_winit_vulkano::{CallbackContext, RenderResources};
 use vulkano::image::SampleCount;
 
-use render::{renderer::Renderer, scene::Scene, PixelViewport};
+use render::{model::Geometry, renderer::Renderer, scene::Scene, PixelViewport};
 
 use super::{egui_transfer::EguiTransfer, ColorId, SceneObject};
 
@@ -93,6 +93,14 @@ impl GuiRenderer {
         }
     }
 
+    pub(super) fn set_geometry(&mut self, geometry: Geometry) {
+        match (&mut self.internal, &mut self.scene) {
+            (Some(internal), _) => internal.scene_renderer.set_geometry(geometry),
+            (None, Some(scene)) => scene.set_geometry(geometry),
+            (None, None) => {}
+        }
+    }
+
     fn require_internal<'a>(&mut self, resources: &RenderResources<'a>) -> &InternalGuiRenderer {
         self.internal
             .get_or_insert_with(|| InternalGuiRenderer::new(self.scene.take().unwrap(), resources))
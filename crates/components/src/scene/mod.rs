@@ -6,7 +6,7 @@ use eframe::{
     epaint::{mutex::Mutex, PaintCallback, PaintCallbackInfo, Pos2, Rect, Vec2},
 };
 use egui_winit_vulkano::CallbackFn;
-use render::scene::Scene;
+use render::{model::Geometry, scene::Scene};
 use vulkano::pipeline::graphics::viewport::Viewport;
 
 use self::gui_renderer::GuiRenderer;
@@ -131,6 +131,11 @@ impl SceneViewer {
         self.rotation = rotation;
     }
 
+    /// Replaces what the viewer shows, keeping its view of the scene
+    pub fn set_geometry(&mut self, geometry: Geometry) {
+        self.renderer.lock().set_geometry(geometry);
+    }
+
     pub fn rect(&self) -> Rect {
         self.scene_rect
     }
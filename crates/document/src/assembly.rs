@@ -0,0 +1,255 @@
+use std::{
+    collections::HashMap,
+    path::{Path, PathBuf},
+};
+
+use assembly::{Assembly, Instance, InstanceId};
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+use space::Tolerance;
+use topology::{
+    interference::{Interference, PlacedMesh},
+    mesh::TriMesh,
+};
+
+use crate::{
+    camera::CameraState,
+    error::{DocumentError, DocumentResult},
+    history::Editable,
+    Document,
+};
+
+/// Identifies cadit assembly documents among other JSON files
+pub const ASSEMBLY_FORMAT: &str = "cadit-assembly";
+
+/// The version of the format that assemblies are saved in
+pub const ASSEMBLY_VERSION: u64 = 1;
+
+/// An assembly file: the instances of parts and the mates between them, and the
+/// view they were last looked at from. Parts are referred to by paths relative
+/// to the assembly file, so a project folder can be moved as a whole.
+#[derive(Debug, Clone, PartialEq, Default, Serialize, Deserialize)]
+pub struct AssemblyDocument {
+    pub assembly: Assembly,
+    pub camera: CameraState,
+}
+impl AssemblyDocument {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// The part file of an instance, for an assembly saved at `path`
+    pub fn part_path(path: &Path, instance: &Instance) -> PathBuf {
+        match path.parent() {
+            Some(folder) => folder.join(&instance.part),
+            None => instance.part.clone(),
+        }
+    }
+
+    /// Builds the body of each instance's part, tessellated to within
+    /// `deviation`, and places it in the assembly along with the part's
+    /// tolerance, so the instances can be checked for interference. Parts are
+    /// read from beside the assembly saved at `path`, and instances of parts
+    /// without a body are left out. The triangles are those of
+    /// [`Document::body_mesh`], so results can be shown on the parts' meshes.
+    pub fn placed_bodies(
+        &self,
+        path: &Path,
+        deviation: f64,
+    ) -> DocumentResult<Vec<(InstanceId, PlacedMesh, Tolerance)>> {
+        let mut meshes: HashMap<PathBuf, Option<(TriMesh, Tolerance)>> = HashMap::new();
+        let mut bodies = Vec::new();
+        for instance in self.assembly.instances() {
+            let part_path = Self::part_path(path, instance);
+            if !meshes.contains_key(&part_path) {
+                let mut part = Document::load(&part_path)?;
+                part.apply_parameters();
+                part.regenerate();
+                let mesh = part.body_mesh(deviation).map(|body| {
+                    let vertices = body.vertices.iter().map(|v| v.position).collect();
+                    (TriMesh::new(vertices, body.triangles), part.tolerance)
+                });
+                meshes.insert(part_path.clone(), mesh);
+            }
+            if let Some((mesh, tolerance)) = &meshes[&part_path] {
+                let placed = PlacedMesh::new(mesh, &instance.placement.transform());
+                bodies.push((instance.id, placed, *tolerance));
+            }
+        }
+        Ok(bodies)
+    }
+
+    /// Checks every pair of instances with bodies for interference, for an
+    /// assembly saved at `path`. Each pair is checked to the looser of its two
+    /// parts' tolerances.
+    pub fn interference(
+        &self,
+        path: &Path,
+        deviation: f64,
+    ) -> DocumentResult<Vec<(InstanceId, InstanceId, Interference)>> {
+        let bodies = self.placed_bodies(path, deviation)?;
+        let mut pairs = Vec::new();
+        for (index, (first, a, a_tolerance)) in bodies.iter().enumerate() {
+            for (second, b, b_tolerance) in &bodies[index + 1..] {
+                let tolerance = a_tolerance.looser(b_tolerance);
+                pairs.push((*first, *second, a.interference(b, &tolerance)));
+            }
+        }
+        Ok(pairs)
+    }
+
+    pub fn to_json(&self) -> String {
+        let mut object = Map::new();
+        object.insert("format".to_string(), ASSEMBLY_FORMAT.into());
+        object.insert("version".to_string(), ASSEMBLY_VERSION.into());
+        match serde_json::to_value(self).unwrap() {
+            Value::Object(fields) => object.extend(fields),
+            _ => unreachable!("assemblies serialize as objects"),
+        }
+        serde_json::to_string_pretty(&Value::Object(object)).unwrap()
+    }
+
+    pub fn from_json(text: &str) -> DocumentResult<Self> {
+        let Value::Object(mut object) = serde_json::from_str(text)? else {
+            return Err(DocumentError::NotADocument);
+        };
+        if object.remove("format") != Some(ASSEMBLY_FORMAT.into()) {
+            return Err(DocumentError::NotADocument);
+        }
+        let version = object
+            .remove("version")
+            .and_then(|version| version.as_u64())
+            .ok_or(DocumentError::NotADocument)?;
+        if version > ASSEMBLY_VERSION {
+            return Err(DocumentError::UnsupportedVersion {
+                found: version,
+                supported: ASSEMBLY_VERSION,
+            });
+        }
+        Ok(serde_json::from_value(Value::Object(object))?)
+    }
+
+    pub fn save(&self, path: impl AsRef<Path>) -> DocumentResult<()> {
+        Ok(std::fs::write(path, self.to_json())?)
+    }
+
+    pub fn load(path: impl AsRef<Path>) -> DocumentResult<Self> {
+        Self::from_json(&std::fs::read_to_string(path)?)
+    }
+}
+impl Editable for AssemblyDocument {
+    /// Placements are part of the assembly, so undoing an edit puts the
+    /// instances back where they were and there is nothing to rebuild
+    fn refresh(&mut self) {}
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::PathBuf;
+
+    use features::feature::{Combine, FeatureKind};
+    use space::{EPlacement3, EVec2, EVec3, Tolerance};
+
+    use crate::Document;
+
+    use super::AssemblyDocument;
+
+    /// A cube of the given size with a corner at the origin
+    fn block(size: f64) -> Document {
+        let mut document = Document::new();
+        let sketch = document
+            .features
+            .insert(
+                "Sketch 1",
+                FeatureKind::Sketch {
+                    placement: EPlacement3::default(),
+                    outer: vec![
+                        EVec2::new(0.0, 0.0),
+                        EVec2::new(size, 0.0),
+                        EVec2::new(size, size),
+                        EVec2::new(0.0, size),
+                    ],
+                    holes: Vec::new(),
+                },
+                &[],
+            )
+            .unwrap();
+        document
+            .features
+            .insert(
+                "Extrude 1",
+                FeatureKind::Extrude {
+                    sketch,
+                    combine: Combine::Add,
+                },
+                &[("depth", size)],
+            )
+            .unwrap();
+        document
+    }
+
+    #[test]
+    fn interference() {
+        let folder = std::env::temp_dir().join(format!("cadit-assembly-{}", std::process::id()));
+        std::fs::create_dir_all(&folder).unwrap();
+        let save = |name: &str, part: &Document| -> PathBuf {
+            part.save(folder.join(name)).unwrap();
+            PathBuf::from(name)
+        };
+        let cube = save("cube.cadpart", &block(1.0));
+        let small = save("small.cadpart", &block(0.5));
+        let mut loose = block(1.0);
+        loose.tolerance = Tolerance::new(1e-4, 1e-5);
+        let loose = save("loose.cadpart", &loose);
+        let empty = save("empty.cadpart", &Document::new());
+
+        let mut document = AssemblyDocument::new();
+        let at = |x: f64, y: f64, z: f64| EPlacement3::from_origin(EVec3::new(x, y, z));
+        let assembly = &mut document.assembly;
+        let base = assembly.add_instance("Base", &cube, at(0.0, 0.0, 0.0));
+        let crossing = assembly.add_instance("Crossing", &cube, at(0.5, 0.0, 0.0));
+        let touching = assembly.add_instance("Touching", &cube, at(-1.0, 0.0, 0.0));
+        let nested = assembly.add_instance("Nested", &small, at(0.25, 0.25, 0.25));
+        let near = assembly.add_instance("Near", &cube, at(0.0, 0.0, 1.0 + 1e-5));
+        let near_loose = assembly.add_instance("Near loose", &loose, at(0.0, 0.0, -1.0 - 1e-5));
+        assembly.add_instance("Empty", &empty, at(0.0, 0.0, 0.0));
+
+        let path = folder.join("assembly.cadasm");
+        let pairs = document.interference(&path, 0.01);
+        std::fs::remove_dir_all(&folder).unwrap();
+        let pairs = pairs.unwrap();
+
+        // Every pair of the six instances with bodies, leaving out the empty part
+        assert_eq!(pairs.len(), 15);
+        let pair = |a, b| {
+            &pairs
+                .iter()
+                .find(|(first, second, _)| (*first, *second) == (a, b))
+                .unwrap()
+                .2
+        };
+
+        let result = pair(base, crossing);
+        assert!((result.volume - 0.5).abs() <= 1e-9);
+        assert_eq!(result.clearance_distance(), Some(0.0));
+
+        let result = pair(base, touching);
+        assert!(!result.interferes());
+        assert_eq!(result.clearance_distance(), Some(0.0));
+
+        let result = pair(base, nested);
+        assert!((result.volume - 0.125).abs() <= 1e-9);
+        assert!((result.clearance_distance().unwrap() - 0.25).abs() <= 1e-9);
+        assert!(result.first_contacts.is_empty());
+
+        // Apart by more than the default tolerance, but within the looser one
+        // of one of the parts
+        let result = pair(base, near);
+        assert!(!result.interferes());
+        assert!(result.first_contacts.is_empty());
+        assert!((result.clearance_distance().unwrap() - 1e-5).abs() <= 1e-9);
+        let result = pair(base, near_loose);
+        assert!(!result.interferes());
+        assert!(!result.first_contacts.is_empty());
+    }
+}
@@ -0,0 +1,33 @@
+use serde::{Deserialize, Serialize};
+
+/// How the camera projects the scene, with the field of view in radians
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub enum Projection {
+    Orthographic { height: f32 },
+    Perspective { fov_y: f32 },
+}
+
+/// Where a document's view was looking when it was saved
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct CameraState {
+    pub position: [f32; 3],
+    pub direction: [f32; 3],
+    pub up: [f32; 3],
+    pub projection: Projection,
+    pub near_dist: f32,
+    pub far_dist: f32,
+}
+impl Default for CameraState {
+    fn default() -> Self {
+        Self {
+            position: [0.0, 0.0, -5.0],
+            direction: [0.0, 0.0, 1.0],
+            up: [0.0, -1.0, 0.0],
+            projection: Projection::Perspective {
+                fov_y: 70f32.to_radians(),
+            },
+            near_dist: 0.01,
+            far_dist: 5.0,
+        }
+    }
+}
@@ -0,0 +1,24 @@
+use thiserror::Error;
+
+pub type DocumentResult<T> = Result<T, DocumentError>;
+
+#[derive(Debug, Error)]
+pub enum DocumentError {
+    #[error("Could not read or write the document: {0}")]
+    Io(#[from] std::io::Error),
+
+    #[error("Could not parse the document: {0}")]
+    Json(#[from] serde_json::Error),
+
+    #[error("The file is not a cadit document")]
+    NotADocument,
+
+    #[error("Document version {found} is newer than the newest supported version {supported}")]
+    UnsupportedVersion { found: u64, supported: u64 },
+
+    #[error("Could not migrate the document from version {version}: {message}")]
+    Migration { version: u64, message: String },
+
+    #[error("Invalid geometry: {0}")]
+    InvalidGeometry(String),
+}
@@ -0,0 +1,113 @@
+use serde::{Deserialize, Serialize};
+use space::{hspace::HSpace3, HVec3};
+use spline::{math::knot_vector::KnotVector, nurbs_curve::NurbsCurve, nurbs_surface::NurbsSurface};
+
+use crate::error::{DocumentError, DocumentResult};
+
+/// A named NURBS curve, with its control points stored as `[x, y, z, h]`
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CurveRecord {
+    pub name: String,
+    pub knots: Vec<f64>,
+    pub control_points: Vec<[f64; 4]>,
+}
+impl CurveRecord {
+    pub fn new(name: &str, curve: &NurbsCurve<HSpace3>) -> Self {
+        Self {
+            name: name.to_string(),
+            knots: curve.knot_vector().iter().copied().collect(),
+            control_points: curve.control_points().iter().map(point).collect(),
+        }
+    }
+
+    /// Rebuilds the curve, checking that the record describes a valid one
+    pub fn to_curve(&self) -> DocumentResult<NurbsCurve<HSpace3>> {
+        check_knots(&self.name, &self.knots, self.control_points.len())?;
+        Ok(NurbsCurve::new(
+            control_points(&self.name, &self.control_points)?,
+            KnotVector::from_slice(&self.knots),
+        ))
+    }
+}
+
+/// A named NURBS surface, with its control points stored in rows along u as
+/// `[x, y, z, h]`
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SurfaceRecord {
+    pub name: String,
+    pub knots_u: Vec<f64>,
+    pub knots_v: Vec<f64>,
+    pub control_points: Vec<Vec<[f64; 4]>>,
+}
+impl SurfaceRecord {
+    pub fn new(name: &str, surface: &NurbsSurface<HSpace3>) -> Self {
+        Self {
+            name: name.to_string(),
+            knots_u: surface.knot_vector_u().iter().copied().collect(),
+            knots_v: surface.knot_vector_v().iter().copied().collect(),
+            control_points: surface
+                .control_points()
+                .iter()
+                .map(|row| row.iter().map(point).collect())
+                .collect(),
+        }
+    }
+
+    /// Rebuilds the surface, checking that the record describes a valid one
+    pub fn to_surface(&self) -> DocumentResult<NurbsSurface<HSpace3>> {
+        let columns = self.control_points.first().map_or(0, |row| row.len());
+        if self.control_points.iter().any(|row| row.len() != columns) {
+            return Err(DocumentError::InvalidGeometry(format!(
+                "the rows of control points of surface '{}' differ in length",
+                self.name
+            )));
+        }
+        check_knots(&self.name, &self.knots_u, self.control_points.len())?;
+        check_knots(&self.name, &self.knots_v, columns)?;
+
+        Ok(NurbsSurface::new(
+            self.control_points
+                .iter()
+                .map(|row| control_points(&self.name, row))
+                .collect::<DocumentResult<_>>()?,
+            KnotVector::from_slice(&self.knots_u),
+            KnotVector::from_slice(&self.knots_v),
+        ))
+    }
+}
+
+fn point(point: &HVec3) -> [f64; 4] {
+    [point.x, point.y, point.z, point.h]
+}
+
+fn control_points(name: &str, points: &[[f64; 4]]) -> DocumentResult<Vec<HVec3>> {
+    points
+        .iter()
+        .map(|[x, y, z, h]| {
+            if [x, y, z, h].iter().all(|c| c.is_finite()) && *h > 0.0 {
+                Ok(HVec3::new(*x, *y, *z, *h))
+            } else {
+                Err(DocumentError::InvalidGeometry(format!(
+                    "'{name}' has a control point that isn't finite or has no positive weight"
+                )))
+            }
+        })
+        .collect()
+}
+
+/// Knot vectors need at least two more knots than control points, for a degree
+/// of at least one, and must not decrease
+fn check_knots(name: &str, knots: &[f64], num_control_points: usize) -> DocumentResult<()> {
+    if num_control_points < 2 || knots.len() < num_control_points + 2 {
+        return Err(DocumentError::InvalidGeometry(format!(
+            "'{name}' has {} knots for {num_control_points} control points",
+            knots.len()
+        )));
+    }
+    if knots.iter().any(|knot| !knot.is_finite()) || knots.windows(2).any(|w| w[1] < w[0]) {
+        return Err(DocumentError::InvalidGeometry(format!(
+            "'{name}' has knots that aren't finite and non-decreasing"
+        )));
+    }
+    Ok(())
+}
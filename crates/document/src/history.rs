@@ -0,0 +1,554 @@
+//! Undo and redo of document edits. Every change to a document is made by a
+//! [`Command`] that knows how to revert itself, and the commands are kept in a
+//! [`History`] in transactions, which are undone and redone as a whole. Part
+//! documents and assemblies each keep their own history.
+
+use std::any::Any;
+
+use crate::Document;
+
+/// The most transactions kept for undoing. Older ones are forgotten.
+const LIMIT: usize = 200;
+
+/// A document whose edits can be undone
+pub trait Editable: 'static {
+    /// Brings what is built from the document up to date after commands were
+    /// undone or redone
+    fn refresh(&mut self);
+}
+impl Editable for Document {
+    /// Rebuilds the features from the parameters and tolerances
+    fn refresh(&mut self) {
+        self.apply_parameters();
+        self.regenerate();
+    }
+}
+
+/// An edit of a document that can be reverted
+pub trait Command<D = Document> {
+    /// What the command does, as shown in the Edit menu
+    fn name(&self) -> &str;
+
+    fn apply(&mut self, document: &mut D);
+
+    /// Puts the document back the way it was before the command was applied
+    fn revert(&mut self, document: &mut D);
+
+    /// Takes in a command done right after this one, returning whether it did.
+    /// Commands that are merged are undone as one.
+    fn merge(&mut self, _next: &dyn Command<D>) -> bool {
+        false
+    }
+
+    fn as_any(&self) -> &dyn Any;
+}
+
+/// Replaces one part of a document, such as its feature tree, with another
+/// value. Applying and reverting swap the document's value with the one held.
+pub struct Change<T, D = Document> {
+    name: String,
+    /// Names the part of the document `field` selects, such as `"tolerance"`
+    key: &'static str,
+    field: fn(&mut D) -> &mut T,
+    value: T,
+}
+impl<T: 'static, D: 'static> Change<T, D> {
+    /// Creates a change that puts `value` into the part of the document `field`
+    /// selects, which `key` names. A change made to the document already is
+    /// recorded by giving the value from before it instead.
+    pub fn new(name: &str, key: &'static str, field: fn(&mut D) -> &mut T, value: T) -> Self {
+        Self {
+            name: name.to_string(),
+            key,
+            field,
+            value,
+        }
+    }
+}
+impl<T: 'static, D: 'static> Command<D> for Change<T, D> {
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn apply(&mut self, document: &mut D) {
+        std::mem::swap((self.field)(document), &mut self.value);
+    }
+
+    fn revert(&mut self, document: &mut D) {
+        std::mem::swap((self.field)(document), &mut self.value);
+    }
+
+    /// Changes of the same name to the same part of the document are merged,
+    /// keeping the value from before the first of them
+    fn merge(&mut self, next: &dyn Command<D>) -> bool {
+        match next.as_any().downcast_ref::<Self>() {
+            Some(next) => next.name == self.name && next.key == self.key,
+            None => false,
+        }
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+}
+
+/// Commands that are undone and redone together
+struct Transaction<D> {
+    name: String,
+    commands: Vec<Box<dyn Command<D>>>,
+}
+impl<D> Transaction<D> {
+    fn new(name: &str) -> Self {
+        Self {
+            name: name.to_string(),
+            commands: Vec::new(),
+        }
+    }
+}
+
+/// The edits made to a document, which can be undone and redone, and whether
+/// the document has changed since it was last saved
+pub struct History<D = Document> {
+    done: Vec<Transaction<D>>,
+    undone: Vec<Transaction<D>>,
+
+    /// The transaction commands are being added to, and how many times it was
+    /// begun without being committed
+    open: Option<(Transaction<D>, usize)>,
+
+    /// How many transactions were done when the document was saved, or `None`
+    /// if that state can't be reached by undoing or redoing anymore
+    saved: Option<usize>,
+}
+impl<D: Editable> History<D> {
+    pub fn new() -> Self {
+        Self {
+            done: Vec::new(),
+            undone: Vec::new(),
+            open: None,
+            saved: Some(0),
+        }
+    }
+
+    /// Applies a command to the document and records it
+    pub fn execute(&mut self, document: &mut D, mut command: Box<dyn Command<D>>) {
+        command.apply(document);
+        self.record(command);
+    }
+
+    /// Records a command whose change has been made to the document already
+    pub fn record(&mut self, command: Box<dyn Command<D>>) {
+        if matches!(self.saved, Some(saved) if saved > self.done.len()) {
+            self.saved = None;
+        }
+        self.undone.clear();
+
+        match &mut self.open {
+            Some((transaction, _)) => {
+                let merged = match transaction.commands.last_mut() {
+                    Some(last) => last.merge(command.as_ref()),
+                    None => false,
+                };
+                if !merged {
+                    transaction.commands.push(command);
+                }
+            }
+            None => {
+                let mut transaction = Transaction::new(command.name());
+                transaction.commands.push(command);
+                self.push_done(transaction);
+            }
+        }
+    }
+
+    /// Starts grouping the commands that follow into one transaction named
+    /// `name`, until it is committed. Transactions begun inside another are part
+    /// of the outer one.
+    pub fn begin(&mut self, name: &str) {
+        match &mut self.open {
+            Some((_, depth)) => *depth += 1,
+            None => self.open = Some((Transaction::new(name), 1)),
+        }
+    }
+
+    /// Ends the transaction begun last
+    pub fn commit(&mut self) {
+        let Some((_, depth)) = &mut self.open else {
+            return;
+        };
+        *depth -= 1;
+        if *depth > 0 {
+            return;
+        }
+
+        let (transaction, _) = self.open.take().unwrap();
+        if !transaction.commands.is_empty() {
+            self.push_done(transaction);
+        }
+    }
+
+    /// Whether a transaction has been begun and not committed
+    pub fn in_transaction(&self) -> bool {
+        self.open.is_some()
+    }
+
+    /// Reverts the commands of the open transaction and drops it
+    pub fn cancel(&mut self, document: &mut D) {
+        if let Some((transaction, _)) = self.open.take() {
+            for mut command in transaction.commands.into_iter().rev() {
+                command.revert(document);
+            }
+            document.refresh();
+        }
+    }
+
+    /// Reverts the last transaction, returning whether there was one. An open
+    /// transaction is committed first.
+    pub fn undo(&mut self, document: &mut D) -> bool {
+        while self.in_transaction() {
+            self.commit();
+        }
+        let Some(mut transaction) = self.done.pop() else {
+            return false;
+        };
+        for command in transaction.commands.iter_mut().rev() {
+            command.revert(document);
+        }
+        self.undone.push(transaction);
+        document.refresh();
+        true
+    }
+
+    /// Applies the last undone transaction again, returning whether there was
+    /// one
+    pub fn redo(&mut self, document: &mut D) -> bool {
+        let Some(mut transaction) = self.undone.pop() else {
+            return false;
+        };
+        for command in transaction.commands.iter_mut() {
+            command.apply(document);
+        }
+        self.done.push(transaction);
+        document.refresh();
+        true
+    }
+
+    /// The name of the transaction that would be undone next
+    pub fn undo_name(&self) -> Option<&str> {
+        self.done.last().map(|t| t.name.as_str())
+    }
+
+    /// The name of the transaction that would be redone next
+    pub fn redo_name(&self) -> Option<&str> {
+        self.undone.last().map(|t| t.name.as_str())
+    }
+
+    /// Whether the document has changed since it was saved
+    pub fn is_dirty(&self) -> bool {
+        let pending =
+            matches!(&self.open, Some((transaction, _)) if !transaction.commands.is_empty());
+        pending || self.saved != Some(self.done.len())
+    }
+
+    /// Notes that the document was saved as it is now
+    pub fn mark_saved(&mut self) {
+        while self.in_transaction() {
+            self.commit();
+        }
+        self.saved = Some(self.done.len());
+    }
+
+    fn push_done(&mut self, transaction: Transaction<D>) {
+        self.done.push(transaction);
+        if self.done.len() > LIMIT {
+            self.done.remove(0);
+            self.saved = self.saved.and_then(|saved| saved.checked_sub(1));
+        }
+    }
+}
+impl<D: Editable> Default for History<D> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use parameters::unit::Unit;
+
+    use crate::{
+        history::{Change, History},
+        units::UnitSystem,
+        Document,
+    };
+
+    #[test]
+    fn undo_and_redo() {
+        let mut document = Document::new();
+        let mut history = History::new();
+        assert!(!history.is_dirty());
+
+        // A change made in place is recorded with the value from before it
+        let before = document.parameters.clone();
+        document
+            .parameters
+            .set("width", "10", Unit::Millimeter)
+            .unwrap();
+        history.record(Box::new(Change::new(
+            "Edit parameters",
+            "parameters",
+            |d: &mut Document| &mut d.parameters,
+            before,
+        )));
+        assert!(history.is_dirty());
+        history.mark_saved();
+        assert!(!history.is_dirty());
+
+        // Commands in a transaction are undone together, and like changes merge
+        let inches = UnitSystem::new(Unit::Inch, Unit::Degree).unwrap();
+        history.begin("Change units");
+        history.execute(
+            &mut document,
+            Box::new(Change::new(
+                "Change units",
+                "units",
+                |d: &mut Document| &mut d.units,
+                inches,
+            )),
+        );
+        let mut tolerance = document.tolerance;
+        for linear in [1e-6, 1e-5] {
+            tolerance.linear = linear;
+            let change = Change::new(
+                "Change tolerance",
+                "tolerance",
+                |d: &mut Document| &mut d.tolerance,
+                tolerance,
+            );
+            history.execute(&mut document, Box::new(change));
+        }
+        history.commit();
+        assert_eq!(history.undo_name(), Some("Change units"));
+        assert_eq!(document.tolerance.linear, 1e-5);
+
+        assert!(history.undo(&mut document));
+        assert_eq!(document.units, UnitSystem::default());
+        assert_eq!(document.tolerance, Default::default());
+        assert!(!history.is_dirty());
+
+        assert!(history.undo(&mut document));
+        assert!(document.parameters.get("width").is_none());
+        assert!(!history.undo(&mut document));
+        assert!(history.is_dirty());
+
+        assert!(history.redo(&mut document));
+        assert!(history.redo(&mut document));
+        assert_eq!(document.units, inches);
+        assert_eq!(document.tolerance.linear, 1e-5);
+        assert!(!history.redo(&mut document));
+
+        // A new command after undoing drops what could have been redone
+        assert!(history.undo(&mut document));
+        history.execute(
+            &mut document,
+            Box::new(Change::new(
+                "Change units",
+                "units",
+                |d: &mut Document| &mut d.units,
+                inches,
+            )),
+        );
+        assert_eq!(history.redo_name(), None);
+        history.begin("Nothing");
+        history.commit();
+        assert_eq!(history.undo_name(), Some("Change units"));
+    }
+
+    fn tolerance_change(linear: f64) -> Box<Change<space::Tolerance>> {
+        let tolerance = space::Tolerance {
+            linear,
+            ..Default::default()
+        };
+        Box::new(Change::new(
+            "Change tolerance",
+            "tolerance",
+            |d: &mut Document| &mut d.tolerance,
+            tolerance,
+        ))
+    }
+
+    #[test]
+    fn transactions() {
+        let mut document = Document::new();
+        let mut history = History::new();
+
+        // Transactions begun inside another belong to the outer one
+        history.begin("Outer");
+        history.begin("Inner");
+        history.execute(&mut document, tolerance_change(1e-5));
+        history.commit();
+        assert!(history.in_transaction());
+        history.execute(
+            &mut document,
+            Box::new(Change::new(
+                "Change units",
+                "units",
+                |d: &mut Document| &mut d.units,
+                UnitSystem::default(),
+            )),
+        );
+        history.commit();
+        assert!(!history.in_transaction());
+        assert_eq!(history.undo_name(), Some("Outer"));
+        assert!(history.undo(&mut document));
+        assert_eq!(document.tolerance, Default::default());
+        assert!(!history.undo(&mut document));
+
+        // Cancelling reverts the open transaction and records nothing
+        history.begin("Cancelled");
+        history.execute(&mut document, tolerance_change(1e-3));
+        history.cancel(&mut document);
+        assert_eq!(document.tolerance, Default::default());
+        assert!(!history.in_transaction());
+        assert_eq!(history.undo_name(), None);
+        assert_eq!(history.redo_name(), None);
+
+        // Undoing commits an open transaction first
+        history.begin("Open");
+        history.execute(&mut document, tolerance_change(1e-3));
+        assert!(history.undo(&mut document));
+        assert!(!history.in_transaction());
+        assert_eq!(document.tolerance, Default::default());
+        assert_eq!(history.redo_name(), Some("Open"));
+    }
+
+    #[test]
+    fn new_edit_clears_redo() {
+        let mut document = Document::new();
+        let mut history = History::new();
+        history.execute(&mut document, tolerance_change(1e-5));
+        history.execute(&mut document, tolerance_change(1e-4));
+        assert!(history.undo(&mut document));
+        assert_eq!(history.redo_name(), Some("Change tolerance"));
+
+        history.execute(&mut document, tolerance_change(1e-3));
+        assert_eq!(history.redo_name(), None);
+        assert!(!history.redo(&mut document));
+        assert_eq!(document.tolerance.linear, 1e-3);
+
+        assert!(history.undo(&mut document));
+        assert_eq!(document.tolerance.linear, 1e-5);
+    }
+
+    #[test]
+    fn merging() {
+        let mut document = Document::new();
+        let mut history = History::new();
+
+        // Like changes in a transaction merge, keeping the first value
+        history.begin("Drag");
+        for linear in [1e-5, 1e-4, 1e-3] {
+            history.execute(&mut document, tolerance_change(linear));
+        }
+        history.commit();
+        assert!(history.undo(&mut document));
+        assert_eq!(document.tolerance, Default::default());
+        assert!(history.redo(&mut document));
+        assert_eq!(document.tolerance.linear, 1e-3);
+
+        // Changes of the same name and type to different fields don't merge
+        history.begin("Edit");
+        history.execute(
+            &mut document,
+            Box::new(Change::new(
+                "Edit",
+                "tolerance.linear",
+                |d: &mut Document| &mut d.tolerance.linear,
+                1e-2,
+            )),
+        );
+        history.execute(
+            &mut document,
+            Box::new(Change::new(
+                "Edit",
+                "tolerance.angular",
+                |d: &mut Document| &mut d.tolerance.angular,
+                1e-2,
+            )),
+        );
+        history.commit();
+        assert!(history.undo(&mut document));
+        assert_eq!(document.tolerance.linear, 1e-3);
+        assert_eq!(
+            document.tolerance.angular,
+            space::Tolerance::default().angular
+        );
+
+        // Changes to the same field merge however the field is selected
+        history.begin("Edit");
+        history.execute(
+            &mut document,
+            Box::new(Change::new(
+                "Edit",
+                "tolerance.linear",
+                |d: &mut Document| &mut d.tolerance.linear,
+                1e-2,
+            )),
+        );
+        history.execute(
+            &mut document,
+            Box::new(Change::new(
+                "Edit",
+                "tolerance.linear",
+                |d: &mut Document| {
+                    let tolerance = &mut d.tolerance;
+                    &mut tolerance.linear
+                },
+                2e-2,
+            )),
+        );
+        history.commit();
+        assert!(history.undo(&mut document));
+        assert_eq!(document.tolerance.linear, 1e-3);
+
+        // Changes outside a transaction are undone one at a time
+        history.execute(&mut document, tolerance_change(1e-2));
+        history.execute(&mut document, tolerance_change(1e-1));
+        assert!(history.undo(&mut document));
+        assert_eq!(document.tolerance.linear, 1e-2);
+    }
+
+    #[test]
+    fn dirty_tracking() {
+        let mut document = Document::new();
+        let mut history = History::new();
+        history.execute(&mut document, tolerance_change(1e-5));
+        history.mark_saved();
+        assert!(!history.is_dirty());
+
+        // Undoing away from the save point and redoing back to it
+        history.execute(&mut document, tolerance_change(1e-4));
+        assert!(history.is_dirty());
+        assert!(history.undo(&mut document));
+        assert!(!history.is_dirty());
+        assert!(history.undo(&mut document));
+        assert!(history.is_dirty());
+        assert!(history.redo(&mut document));
+        assert!(!history.is_dirty());
+
+        // A pending transaction counts as a change
+        history.begin("Pending");
+        assert!(!history.is_dirty());
+        history.execute(&mut document, tolerance_change(1e-3));
+        assert!(history.is_dirty());
+        history.cancel(&mut document);
+        assert!(!history.is_dirty());
+
+        // Once a new edit replaces the redo leading to the save point, it can't
+        // be reached again
+        assert!(history.undo(&mut document));
+        history.execute(&mut document, tolerance_change(1e-3));
+        assert!(history.undo(&mut document));
+        assert!(history.is_dirty());
+    }
+}
@@ -0,0 +1,422 @@
+//! The native cadit document format: a part's curves, surfaces, feature history,
+//! parameters, units, materials and view, saved as JSON. Every file records the version
+//! of the format it was written in, and older files are migrated when they are
+//! loaded. Edits are made through a history so they can be undone.
+//! Documents are kept in project folders alongside each other, and assemblies of
+//! them are saved in a format of their own.
+
+use std::path::Path;
+
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+
+pub mod assembly;
+pub mod camera;
+pub mod error;
+pub mod geometry;
+pub mod history;
+pub mod material;
+mod migration;
+pub mod project;
+pub mod units;
+
+use camera::CameraState;
+use error::{DocumentError, DocumentResult};
+use exchange::{
+    geometry::Geometry,
+    mesh::{Mesh, MeshPart},
+};
+use features::{error::FeatureError, feature::FeatureId, tree::FeatureTree};
+use geometry::{CurveRecord, SurfaceRecord};
+use material::MaterialRecord;
+use migration::{migrate, Migration, MIGRATIONS};
+use parameters::ParameterTable;
+use space::Tolerance;
+use units::UnitSystem;
+
+/// Identifies cadit documents among other JSON files
+pub const FORMAT: &str = "cadit";
+
+/// The version of the format that documents are saved in
+pub const VERSION: u64 = MIGRATIONS.len() as u64 + 1;
+
+#[derive(Debug, Clone, PartialEq, Default, Serialize, Deserialize)]
+pub struct Document {
+    pub curves: Vec<CurveRecord>,
+    pub surfaces: Vec<SurfaceRecord>,
+    pub features: FeatureTree,
+    pub parameters: ParameterTable,
+    pub units: UnitSystem,
+
+    /// The tolerances the part's features are built to
+    pub tolerance: Tolerance,
+    pub materials: Vec<MaterialRecord>,
+    pub camera: CameraState,
+}
+impl Document {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Evaluates the parameter table and sets the feature parameters driven by
+    /// it, returning the ones that couldn't be set. The features still have to be
+    /// regenerated.
+    pub fn apply_parameters(&mut self) -> Vec<(FeatureId, FeatureError)> {
+        self.features.apply_parameters(&self.parameters.evaluate())
+    }
+
+    /// Whether an expression of a parameter or feature uses the parameter `name`
+    pub fn uses_parameter(&self, name: &str) -> bool {
+        let expressions = self.parameters.parameters().iter().map(|p| &p.expression);
+        let feature_expressions = self
+            .features
+            .features()
+            .iter()
+            .flat_map(|feature| feature.expressions.values());
+        expressions
+            .chain(feature_expressions)
+            .any(|expression| expression.references().contains(name))
+    }
+
+    /// Builds the features that changed, along with those that depend on them,
+    /// to the document's tolerances
+    pub fn regenerate(&mut self) -> Vec<FeatureId> {
+        self.features.set_tolerance(self.tolerance);
+        self.features.regenerate()
+    }
+
+    /// The part's body tessellated so that no triangle strays further than
+    /// `deviation` from its faces, or `None` if the features haven't built one
+    pub fn body_mesh(&self, deviation: f64) -> Option<MeshPart> {
+        let geometry = Geometry {
+            solids: vec![self.features.body()?.clone()],
+            ..Geometry::new()
+        };
+        Mesh::from_geometry_with(&geometry, deviation, &self.tolerance)
+            .parts
+            .pop()
+    }
+
+    pub fn to_json(&self) -> String {
+        let mut object = Map::new();
+        object.insert("format".to_string(), FORMAT.into());
+        object.insert("version".to_string(), VERSION.into());
+        match serde_json::to_value(self).unwrap() {
+            Value::Object(fields) => object.extend(fields),
+            _ => unreachable!("documents serialize as objects"),
+        }
+        serde_json::to_string_pretty(&Value::Object(object)).unwrap()
+    }
+
+    /// Reads a document of this or any earlier version
+    pub fn from_json(text: &str) -> DocumentResult<Self> {
+        Self::from_json_with(text, MIGRATIONS)
+    }
+
+    fn from_json_with(text: &str, migrations: &[Migration]) -> DocumentResult<Self> {
+        let Value::Object(mut object) = serde_json::from_str(text)? else {
+            return Err(DocumentError::NotADocument);
+        };
+        if object.remove("format") != Some(FORMAT.into()) {
+            return Err(DocumentError::NotADocument);
+        }
+        let version = object
+            .remove("version")
+            .and_then(|version| version.as_u64())
+            .ok_or(DocumentError::NotADocument)?;
+
+        migrate(&mut object, version, migrations)?;
+        Ok(serde_json::from_value(Value::Object(object))?)
+    }
+
+    pub fn save(&self, path: impl AsRef<Path>) -> DocumentResult<()> {
+        Ok(std::fs::write(path, self.to_json())?)
+    }
+
+    pub fn load(path: impl AsRef<Path>) -> DocumentResult<Self> {
+        Self::from_json(&std::fs::read_to_string(path)?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use features::feature::{Combine, FeatureKind};
+    use parameters::{expression::Expression, unit::Unit};
+    use space::{hspace::HSpace3, EPlacement3, EVec2, MassProperties, Tolerance};
+    use spline::{nurbs_curve::NurbsCurve, nurbs_surface::NurbsSurface};
+
+    use crate::{
+        assembly::AssemblyDocument,
+        camera::Projection,
+        error::DocumentError,
+        geometry::{CurveRecord, SurfaceRecord},
+        material::MaterialRecord,
+        units::UnitSystem,
+        Document, VERSION,
+    };
+
+    fn document() -> Document {
+        let mut document = Document::new();
+        document.curves.push(CurveRecord::new(
+            "circle",
+            &NurbsCurve::<HSpace3>::example_circle(),
+        ));
+        document.surfaces.push(SurfaceRecord::new(
+            "sphere",
+            &NurbsSurface::sphere(&EPlacement3::default(), 12.5),
+        ));
+        let sketch = document
+            .features
+            .insert(
+                "Sketch 1",
+                FeatureKind::Sketch {
+                    placement: EPlacement3::default(),
+                    outer: vec![
+                        EVec2::new(0.0, 0.0),
+                        EVec2::new(1.0, 0.0),
+                        EVec2::new(0.0, 1.0),
+                    ],
+                    holes: Vec::new(),
+                },
+                &[],
+            )
+            .unwrap();
+        let extrude = document
+            .features
+            .insert(
+                "Extrude 1",
+                FeatureKind::Extrude {
+                    sketch,
+                    combine: Combine::Add,
+                },
+                &[("depth", 0.1 + 0.2)],
+            )
+            .unwrap();
+        document
+            .parameters
+            .set("thickness", "0.1 + 0.2", Unit::Millimeter)
+            .unwrap();
+        document
+            .features
+            .set_expression(
+                extrude,
+                "depth",
+                Some(Expression::parse("thickness").unwrap()),
+            )
+            .unwrap();
+        assert!(document.apply_parameters().is_empty());
+        document.features.set_suppressed(extrude, true).unwrap();
+        document.materials.push(MaterialRecord {
+            name: "Glass".to_string(),
+            color: [0.2, 0.4, 0.9, 0.3],
+            roughness: 0.05,
+        });
+        document.units = UnitSystem::new(Unit::Inch, Unit::Radian).unwrap();
+        document.tolerance = Tolerance::new(1e-5, 1e-4);
+        document.camera.projection = Projection::Orthographic { height: 40.0 };
+        document
+    }
+
+    #[test]
+    fn round_trip() {
+        let document = document();
+        let path = std::env::temp_dir().join(format!("cadit-document-{}.json", std::process::id()));
+        document.save(&path).unwrap();
+        let loaded = Document::load(&path);
+        std::fs::remove_file(&path).unwrap();
+        let loaded = loaded.unwrap();
+
+        // Geometry comes back to the last bit, and rebuilds the same shapes
+        assert_eq!(loaded, document);
+        let sphere = loaded.surfaces[0].to_surface().unwrap();
+        let [a, b] = [sphere, document.surfaces[0].to_surface().unwrap()]
+            .map(|surface| surface.point(0.3, 0.6));
+        assert_eq!([a.x, a.y, a.z], [b.x, b.y, b.z]);
+        assert!(loaded.curves[0]
+            .to_curve()
+            .unwrap()
+            .is_closed(&Tolerance::DEFAULT));
+    }
+
+    #[test]
+    fn body_mesh() {
+        let mut document = document();
+        document.regenerate();
+        assert!(document.body_mesh(0.01).is_none());
+
+        // The extruded triangle, 0.3 deep
+        let extrude = document.features.features()[1].id;
+        document.features.set_suppressed(extrude, false).unwrap();
+        document.regenerate();
+        let body = document.body_mesh(0.01).unwrap();
+        let props = MassProperties::from_triangles(
+            (0..body.triangles.len()).map(|i| body.triangle_points(i)),
+            1.0,
+        );
+        assert!((props.volume - 0.15).abs() <= 1e-9);
+    }
+
+    #[test]
+    fn uses_parameter() {
+        let mut document = document();
+        assert!(document.uses_parameter("thickness"));
+
+        // Renaming leaves the extrude depth using the old name until it's changed
+        document.parameters.rename("thickness", "wall").unwrap();
+        assert!(document.uses_parameter("thickness"));
+        let extrude = document.features.features()[1].id;
+        document
+            .features
+            .set_expression(extrude, "depth", Some(Expression::parse("wall").unwrap()))
+            .unwrap();
+        assert!(!document.uses_parameter("thickness"));
+        assert!(document.uses_parameter("wall"));
+    }
+
+    #[test]
+    fn reject_unknown_files() {
+        let text = document().to_json();
+        assert!(text.contains(&format!("\"version\": {VERSION}")));
+
+        let newer = text.replace(
+            &format!("\"version\": {VERSION}"),
+            &format!("\"version\": {}", VERSION + 1),
+        );
+        assert!(matches!(
+            Document::from_json(&newer),
+            Err(DocumentError::UnsupportedVersion { .. })
+        ));
+        assert!(matches!(
+            Document::from_json("{\"curves\": []}"),
+            Err(DocumentError::NotADocument)
+        ));
+        assert!(matches!(
+            Document::from_json("[1, 2]"),
+            Err(DocumentError::NotADocument)
+        ));
+
+        // Parts and assemblies aren't read as each other
+        let mut assembly = AssemblyDocument::new();
+        assembly
+            .assembly
+            .add_instance("Base", "base.cadpart", EPlacement3::default());
+        let assembly_text = assembly.to_json();
+        assert_eq!(
+            AssemblyDocument::from_json(&assembly_text).unwrap(),
+            assembly
+        );
+        assert!(matches!(
+            Document::from_json(&assembly_text),
+            Err(DocumentError::NotADocument)
+        ));
+        assert!(matches!(
+            AssemblyDocument::from_json(&text),
+            Err(DocumentError::NotADocument)
+        ));
+
+        let mut broken = document();
+        broken.curves[0].knots.truncate(4);
+        let broken = Document::from_json(&broken.to_json()).unwrap();
+        assert!(matches!(
+            broken.curves[0].to_curve(),
+            Err(DocumentError::InvalidGeometry(_))
+        ));
+    }
+
+    #[test]
+    fn migrate_older_versions() {
+        // Pretend the current format renamed a field of version 1 and then added
+        // another in version 2
+        fn rename(document: &mut serde_json::Map<String, serde_json::Value>) -> Result<(), String> {
+            let shapes = document.remove("shapes").ok_or("no shapes")?;
+            document.insert("curves".to_string(), shapes);
+            Ok(())
+        }
+        fn add(document: &mut serde_json::Map<String, serde_json::Value>) -> Result<(), String> {
+            document.insert("materials".to_string(), serde_json::json!([]));
+            Ok(())
+        }
+
+        let text = document()
+            .to_json()
+            .replace(&format!("\"version\": {VERSION}"), "\"version\": 1")
+            .replace("\"curves\"", "\"shapes\"");
+        let text = {
+            let mut value: serde_json::Value = serde_json::from_str(&text).unwrap();
+            value.as_object_mut().unwrap().remove("materials");
+            value.to_string()
+        };
+
+        let migrated = Document::from_json_with(&text, &[rename, add]).unwrap();
+        let mut expected = document();
+        expected.materials.clear();
+        assert_eq!(migrated, expected);
+
+        // A failing migration reports the version it started from
+        assert!(matches!(
+            Document::from_json_with(&text.replace("shapes", "other"), &[rename, add]),
+            Err(DocumentError::Migration { version: 1, .. })
+        ));
+
+        // Version 1 listed feature records, which became a feature tree
+        let version1 = |features: serde_json::Value| {
+            serde_json::json!({
+                "format": "cadit",
+                "version": 1,
+                "curves": [],
+                "surfaces": [],
+                "features": features,
+                "materials": [],
+                "camera": document().camera,
+            })
+            .to_string()
+        };
+        let migrated = Document::from_json(&version1(serde_json::json!([]))).unwrap();
+        assert!(migrated.features.features().is_empty());
+        assert!(migrated.parameters.parameters().is_empty());
+        assert!(matches!(
+            Document::from_json(&version1(serde_json::json!([{ "id": 7 }]))),
+            Err(DocumentError::Migration { version: 1, .. })
+        ));
+
+        // Version 2 had no parameters, so nothing drove the features
+        let mut version2: serde_json::Value = serde_json::from_str(&document().to_json()).unwrap();
+        version2["version"] = 2.into();
+        version2.as_object_mut().unwrap().remove("parameters");
+        for feature in version2["features"]["features"].as_array_mut().unwrap() {
+            feature.as_object_mut().unwrap().remove("expressions");
+        }
+        let migrated = Document::from_json(&version2.to_string()).unwrap();
+        let mut expected = document();
+        expected.parameters = Default::default();
+        expected.units = Default::default();
+        expected.tolerance = Default::default();
+        for feature in expected
+            .features
+            .features()
+            .iter()
+            .map(|f| f.id)
+            .collect::<Vec<_>>()
+        {
+            expected
+                .features
+                .set_expression(feature, "depth", None)
+                .unwrap();
+        }
+        assert_eq!(migrated, expected);
+
+        // Version 3 had no units or tolerances, so it was in millimeters and
+        // degrees and built to the defaults
+        let mut version3: serde_json::Value = serde_json::from_str(&document().to_json()).unwrap();
+        version3["version"] = 3.into();
+        version3.as_object_mut().unwrap().remove("units");
+        version3.as_object_mut().unwrap().remove("tolerance");
+        let migrated = Document::from_json(&version3.to_string()).unwrap();
+        let mut expected = document();
+        expected.units = UnitSystem::default();
+        expected.tolerance = Tolerance::default();
+        assert_eq!(migrated, expected);
+        assert_eq!(migrated.units.length(), Unit::Millimeter);
+        assert_eq!(migrated.units.angle(), Unit::Degree);
+    }
+}
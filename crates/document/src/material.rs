@@ -0,0 +1,9 @@
+use serde::{Deserialize, Serialize};
+
+/// A named material, with a linear RGBA color and a roughness from 0 to 1
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct MaterialRecord {
+    pub name: String,
+    pub color: [f32; 4],
+    pub roughness: f32,
+}
@@ -0,0 +1,90 @@
+use serde_json::{json, Map, Value};
+
+use crate::error::{DocumentError, DocumentResult};
+
+/// Rewrites the JSON object of a document from one version to the next, or
+/// explains why it can't
+pub(crate) type Migration = fn(&mut Map<String, Value>) -> Result<(), String>;
+
+/// Every migration so far, where the one at index `i` upgrades documents from
+/// version `i + 1`. Changing the format means adding a migration here, which also
+/// bumps [`crate::VERSION`].
+pub(crate) const MIGRATIONS: &[Migration] = &[feature_tree, parameter_table, units];
+
+/// Brings a document of `version` up to date by running the migrations after it
+/// in order
+pub(crate) fn migrate(
+    document: &mut Map<String, Value>,
+    version: u64,
+    migrations: &[Migration],
+) -> DocumentResult<()> {
+    let supported = migrations.len() as u64 + 1;
+    if version == 0 {
+        return Err(DocumentError::NotADocument);
+    }
+    if version > supported {
+        return Err(DocumentError::UnsupportedVersion {
+            found: version,
+            supported,
+        });
+    }
+
+    for (from, migration) in migrations.iter().enumerate().skip(version as usize - 1) {
+        migration(document).map_err(|message| DocumentError::Migration {
+            version: from as u64 + 1,
+            message,
+        })?;
+    }
+    Ok(())
+}
+
+/// Version 2 replaced the list of feature records with a parametric feature tree.
+/// The records held no geometry to build features from, so only documents
+/// without any can be brought forward.
+fn feature_tree(document: &mut Map<String, Value>) -> Result<(), String> {
+    match document.get("features") {
+        Some(Value::Array(records)) if records.is_empty() => {}
+        Some(Value::Array(_)) => {
+            return Err("version 1 feature records carry no feature definitions".to_string())
+        }
+        _ => return Err("no feature records".to_string()),
+    }
+    document.insert(
+        "features".to_string(),
+        json!({ "features": [], "next_id": 0, "rollback": null }),
+    );
+    Ok(())
+}
+
+/// Version 3 added a table of parameters, and expressions that drive the
+/// parameters of features from it
+fn parameter_table(document: &mut Map<String, Value>) -> Result<(), String> {
+    let features = document
+        .get_mut("features")
+        .and_then(|tree| tree.get_mut("features"))
+        .and_then(|features| features.as_array_mut())
+        .ok_or("no feature tree")?;
+    for feature in features.iter_mut() {
+        feature
+            .as_object_mut()
+            .ok_or("a feature is not an object")?
+            .insert("expressions".to_string(), json!({}));
+    }
+    document.insert("parameters".to_string(), json!({ "parameters": [] }));
+    Ok(())
+}
+
+/// Version 4 added the units values are shown in and the tolerances features are
+/// built to. Earlier documents were in millimeters and degrees, built to the
+/// default tolerances.
+fn units(document: &mut Map<String, Value>) -> Result<(), String> {
+    document.insert(
+        "units".to_string(),
+        json!({ "length": "Millimeter", "angle": "Degree" }),
+    );
+    document.insert(
+        "tolerance".to_string(),
+        json!({ "linear": 1e-7, "angular": 1e-5 }),
+    );
+    Ok(())
+}
@@ -0,0 +1,191 @@
+use std::{
+    collections::BTreeMap,
+    path::{Path, PathBuf},
+    time::SystemTime,
+};
+
+use crate::error::DocumentResult;
+
+/// The kinds of files a project is made of, told apart by their extensions
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum FileKind {
+    Part,
+    Assembly,
+}
+impl FileKind {
+    pub const ALL: [Self; 2] = [Self::Part, Self::Assembly];
+
+    pub fn extension(&self) -> &'static str {
+        match self {
+            Self::Part => "cadpart",
+            Self::Assembly => "cadasm",
+        }
+    }
+
+    pub fn from_extension(extension: &str) -> Option<Self> {
+        Self::ALL
+            .into_iter()
+            .find(|kind| kind.extension().eq_ignore_ascii_case(extension))
+    }
+
+    pub fn from_path(path: &Path) -> Option<Self> {
+        Self::from_extension(path.extension()?.to_str()?)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ProjectFile {
+    /// The path of the file relative to the project's folder
+    pub path: PathBuf,
+    pub kind: FileKind,
+    pub modified: Option<SystemTime>,
+}
+
+/// How the files of a project changed between two scans of its folder
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ProjectChange {
+    Added(PathBuf),
+    Removed(PathBuf),
+    Modified(PathBuf),
+}
+
+/// A folder of part and assembly files. Other files are ignored, as are hidden
+/// files and folders.
+#[derive(Debug, Clone)]
+pub struct Project {
+    root: PathBuf,
+    files: Vec<ProjectFile>,
+}
+impl Project {
+    pub fn open(root: impl Into<PathBuf>) -> DocumentResult<Self> {
+        let root = root.into();
+        let files = scan(&root)?;
+        Ok(Self { root, files })
+    }
+
+    pub fn root(&self) -> &Path {
+        &self.root
+    }
+
+    /// The project's files, sorted by path
+    pub fn files(&self) -> &[ProjectFile] {
+        &self.files
+    }
+
+    pub fn absolute_path(&self, file: &ProjectFile) -> PathBuf {
+        self.root.join(&file.path)
+    }
+
+    /// Scans the folder again, returning how the files changed since the last
+    /// scan. Calling this regularly is how a project watches its folder.
+    pub fn refresh(&mut self) -> DocumentResult<Vec<ProjectChange>> {
+        let files = scan(&self.root)?;
+
+        let before = self
+            .files
+            .iter()
+            .map(|file| (&file.path, file.modified))
+            .collect::<BTreeMap<_, _>>();
+        let mut changes = Vec::new();
+        for file in files.iter() {
+            match before.get(&file.path) {
+                None => changes.push(ProjectChange::Added(file.path.clone())),
+                Some(modified) if *modified != file.modified => {
+                    changes.push(ProjectChange::Modified(file.path.clone()))
+                }
+                Some(_) => {}
+            }
+        }
+        for file in self.files.iter() {
+            if files.binary_search_by(|f| f.path.cmp(&file.path)).is_err() {
+                changes.push(ProjectChange::Removed(file.path.clone()));
+            }
+        }
+
+        self.files = files;
+        Ok(changes)
+    }
+}
+
+fn scan(root: &Path) -> DocumentResult<Vec<ProjectFile>> {
+    let mut files = Vec::new();
+    let mut folders = vec![root.to_path_buf()];
+    while let Some(folder) = folders.pop() {
+        for entry in std::fs::read_dir(&folder)? {
+            let entry = entry?;
+            if entry.file_name().to_string_lossy().starts_with('.') {
+                continue;
+            }
+
+            let path = entry.path();
+            let metadata = entry.metadata()?;
+            if metadata.is_dir() {
+                folders.push(path);
+            } else if let Some(kind) = FileKind::from_path(&path) {
+                files.push(ProjectFile {
+                    path: path.strip_prefix(root).unwrap().to_path_buf(),
+                    kind,
+                    modified: metadata.modified().ok(),
+                });
+            }
+        }
+    }
+
+    files.sort_by(|a, b| a.path.cmp(&b.path));
+    Ok(files)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::PathBuf;
+
+    use super::{FileKind, Project, ProjectChange};
+
+    #[test]
+    fn scan_and_refresh() {
+        let root = std::env::temp_dir().join(format!("cadit-project-{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&root);
+        std::fs::create_dir_all(root.join("brackets")).unwrap();
+        std::fs::create_dir_all(root.join(".history")).unwrap();
+        for file in [
+            "frame.cadasm",
+            "brackets/left.cadpart",
+            "brackets/right.CADPART",
+            "notes.txt",
+            ".history/old.cadpart",
+        ] {
+            std::fs::write(root.join(file), "").unwrap();
+        }
+
+        let mut project = Project::open(&root).unwrap();
+        let files = project
+            .files()
+            .iter()
+            .map(|file| (file.path.clone(), file.kind))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            files,
+            vec![
+                (PathBuf::from("brackets/left.cadpart"), FileKind::Part),
+                (PathBuf::from("brackets/right.CADPART"), FileKind::Part),
+                (PathBuf::from("frame.cadasm"), FileKind::Assembly),
+            ]
+        );
+        assert!(project.refresh().unwrap().is_empty());
+
+        std::fs::remove_file(root.join("brackets/left.cadpart")).unwrap();
+        std::fs::write(root.join("base.cadpart"), "").unwrap();
+        std::fs::write(root.join("readme.md"), "").unwrap();
+        let changes = project.refresh();
+        std::fs::remove_dir_all(&root).unwrap();
+
+        assert_eq!(
+            changes.unwrap(),
+            vec![
+                ProjectChange::Added(PathBuf::from("base.cadpart")),
+                ProjectChange::Removed(PathBuf::from("brackets/left.cadpart")),
+            ]
+        );
+        assert_eq!(project.files().len(), 3);
+    }
+}
@@ -0,0 +1,107 @@
+use parameters::unit::{Dimension, Unit};
+use serde::{Deserialize, Serialize};
+
+/// The units a part's lengths and angles are shown and entered in. Documents
+/// always store millimeters and radians, so changing units never changes the
+/// part, only how its values are presented.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct UnitSystem {
+    length: Unit,
+    angle: Unit,
+}
+impl UnitSystem {
+    /// Creates a unit system, or returns `None` if `length` isn't a unit of length
+    /// or `angle` a unit of angle
+    pub fn new(length: Unit, angle: Unit) -> Option<Self> {
+        (length.dimension() == Dimension::LENGTH && angle.dimension() == Dimension::ANGLE)
+            .then_some(Self { length, angle })
+    }
+
+    pub fn length(&self) -> Unit {
+        self.length
+    }
+
+    pub fn angle(&self) -> Unit {
+        self.angle
+    }
+
+    /// Converts a stored length in millimeters to the document's unit
+    pub fn to_length(&self, millimeters: f64) -> f64 {
+        millimeters / self.length.factor()
+    }
+
+    /// Converts a length in the document's unit to millimeters for storing
+    pub fn from_length(&self, value: f64) -> f64 {
+        value * self.length.factor()
+    }
+
+    /// Converts a stored angle in radians to the document's unit
+    pub fn to_angle(&self, radians: f64) -> f64 {
+        radians / self.angle.factor()
+    }
+
+    /// Converts an angle in the document's unit to radians for storing
+    pub fn from_angle(&self, value: f64) -> f64 {
+        value * self.angle.factor()
+    }
+
+    /// Formats a stored length in the document's unit, like `12.7 in`
+    pub fn format_length(&self, millimeters: f64, decimals: usize) -> String {
+        format!(
+            "{:.decimals$} {}",
+            self.to_length(millimeters),
+            self.length.symbol()
+        )
+    }
+
+    /// Formats a stored angle in the document's unit, like `45 deg`
+    pub fn format_angle(&self, radians: f64, decimals: usize) -> String {
+        format!(
+            "{:.decimals$} {}",
+            self.to_angle(radians),
+            self.angle.symbol()
+        )
+    }
+}
+impl Default for UnitSystem {
+    fn default() -> Self {
+        Self {
+            length: Unit::Millimeter,
+            angle: Unit::Degree,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::f64::consts::PI;
+
+    use parameters::unit::Unit;
+
+    use super::UnitSystem;
+
+    #[test]
+    fn conversions() {
+        assert!(UnitSystem::new(Unit::Degree, Unit::Degree).is_none());
+        assert!(UnitSystem::new(Unit::Inch, Unit::Meter).is_none());
+        assert!(UnitSystem::new(Unit::None, Unit::Radian).is_none());
+
+        let units = UnitSystem::new(Unit::Inch, Unit::Radian).unwrap();
+        assert_eq!(units.to_length(25.4), 1.0);
+        assert_eq!(units.from_length(2.0), 50.8);
+        assert_eq!(units.to_angle(PI), PI);
+        assert_eq!(units.format_length(12.7, 2), "0.50 in");
+        assert_eq!(units.format_angle(0.5, 1), "0.5 rad");
+
+        let units = UnitSystem::default();
+        assert_eq!(units.to_length(3.0), 3.0);
+        assert!((units.to_angle(PI) - 180.0).abs() < 1e-12);
+        assert!((units.from_angle(90.0) - PI / 2.0).abs() < 1e-12);
+        assert_eq!(units.format_angle(PI / 4.0, 0), "45 deg");
+
+        let units = UnitSystem::new(Unit::Meter, Unit::Degree).unwrap();
+        for value in [0.0, 1.5, -250.0] {
+            assert!((units.from_length(units.to_length(value)) - value).abs() < 1e-12);
+        }
+    }
+}
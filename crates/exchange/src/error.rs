@@ -0,0 +1,32 @@
+use thiserror::Error;
+use topology::error::TopologyError;
+
+pub type ExchangeResult<T> = Result<T, ExchangeError>;
+
+#[derive(Debug, Error)]
+pub enum ExchangeError {
+    #[error("Could not read or write the file: {0}")]
+    Io(#[from] std::io::Error),
+
+    #[error("Syntax error on line {line}: {message}")]
+    Syntax { line: usize, message: String },
+
+    #[error("Entity #{0} is referenced but never defined")]
+    MissingEntity(u64),
+
+    #[error("Entity #{id} should be {expected}, but is {found}")]
+    UnexpectedEntity {
+        id: u64,
+        expected: &'static str,
+        found: String,
+    },
+
+    #[error("Entity #{id} has invalid parameters: {message}")]
+    InvalidParameters { id: u64, message: String },
+
+    #[error("Entity {0} is not supported")]
+    Unsupported(String),
+
+    #[error("Could not build a solid from the file's faces: {0}")]
+    Topology(#[from] TopologyError),
+}
@@ -0,0 +1,9 @@
+/// Writes a real number the way STEP and IGES require, always with a decimal
+/// point
+pub(crate) fn real(value: f64) -> String {
+    let text = format!("{value:?}").to_uppercase();
+    match text.find('E') {
+        Some(e) if !text[..e].contains('.') => format!("{}.{}", &text[..e], &text[e..]),
+        _ => text,
+    }
+}
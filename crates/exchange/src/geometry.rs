@@ -0,0 +1,57 @@
+use space::hspace::HSpace3;
+use spline::{
+    nurbs_curve::NurbsCurve, nurbs_surface::NurbsSurface, trimmed_surface::TrimmedSurface,
+};
+use topology::{entities::FaceId, solid::Solid};
+
+/// The geometry read from or written to an exchange file: free curves and
+/// surfaces, surfaces trimmed by loops in their parameter space, and solids
+/// bounded by faces. Only IGES files carry trimmed surfaces.
+#[derive(Debug, Clone, Default)]
+pub struct Geometry {
+    pub curves: Vec<NurbsCurve<HSpace3>>,
+    pub surfaces: Vec<NurbsSurface<HSpace3>>,
+    pub trimmed_surfaces: Vec<TrimmedSurface>,
+    pub solids: Vec<Solid>,
+}
+impl Geometry {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.curves.is_empty()
+            && self.surfaces.is_empty()
+            && self.trimmed_surfaces.is_empty()
+            && self.solids.is_empty()
+    }
+}
+
+/// The faces of a solid that have surfaces and p-curves, as surfaces trimmed by
+/// the p-curves of their loops
+pub(crate) fn face_surfaces(solid: &Solid) -> Vec<(FaceId, TrimmedSurface)> {
+    solid
+        .faces()
+        .filter_map(|(id, face)| {
+            let surface = face.surface.clone()?;
+            let trim_loop = |id| {
+                solid
+                    .loop_half_edges(id)
+                    .ok()?
+                    .iter()
+                    .map(|he| solid.half_edge(*he)?.pcurve.clone())
+                    .collect::<Option<Vec<_>>>()
+                    .filter(|curves| !curves.is_empty())
+            };
+            let trimmed = TrimmedSurface {
+                surface,
+                outer: Some(trim_loop(face.outer_loop)?),
+                inner: face
+                    .inner_loops()
+                    .map(|id| trim_loop(*id))
+                    .collect::<Option<Vec<_>>>()?,
+            };
+            Some((id, trimmed))
+        })
+        .collect()
+}
@@ -0,0 +1,338 @@
+use std::collections::BTreeMap;
+
+use crate::error::{ExchangeError, ExchangeResult};
+
+/// Columns of a line holding data, before the section letter and sequence number
+const DATA_COLUMNS: usize = 72;
+
+/// Columns of a parameter data line holding parameters, before the pointer back
+/// to the entity's directory entry
+const PARAMETER_COLUMNS: usize = 64;
+
+/// The directory entry of an IGES entity, holding the fields this crate uses
+#[derive(Debug, Clone, PartialEq)]
+pub struct DirectoryEntry {
+    pub entity_type: i64,
+    pub form: i64,
+
+    /// Sequence number of the directory entry of the entity's transformation
+    /// matrix, or 0 if it has none
+    pub transform: usize,
+
+    /// The eight-digit status number: blank status, subordinate switch, entity
+    /// use and hierarchy, two digits each
+    pub status: String,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct IgesEntity {
+    /// Sequence number of the first line of the entity's directory entry, which
+    /// other entities use to refer to it
+    pub sequence: usize,
+
+    pub directory: DirectoryEntry,
+
+    /// The free-format parameters after the entity type, with strings decoded
+    pub params: Vec<String>,
+}
+
+/// The contents of an IGES file in fixed 80-column ASCII form
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct IgesFile {
+    /// The human-readable text of the start section
+    pub start: String,
+
+    /// The parameters of the global section, with strings decoded
+    pub global: Vec<String>,
+
+    /// Entities by the sequence number of the first line of their directory entry
+    pub entities: BTreeMap<usize, IgesEntity>,
+}
+impl IgesFile {
+    pub fn parse(text: &str) -> ExchangeResult<Self> {
+        let mut start = Vec::new();
+        let mut global = String::new();
+        let mut directory = Vec::new();
+        let mut parameters = BTreeMap::<usize, String>::new();
+
+        for (number, line) in text.lines().enumerate() {
+            let line = line.trim_end_matches('\r');
+            if line.trim().is_empty() {
+                continue;
+            }
+
+            let chars = line.chars().collect::<Vec<_>>();
+            if chars.len() <= DATA_COLUMNS {
+                return Err(ExchangeError::Syntax {
+                    line: number + 1,
+                    message: "line is too short to have a section letter".to_string(),
+                });
+            }
+
+            let data = chars[..DATA_COLUMNS].iter().collect::<String>();
+            match chars[DATA_COLUMNS] {
+                'S' => start.push(data.trim_end().to_string()),
+                'G' => global.push_str(&data),
+                'D' => directory.push((number + 1, data)),
+                'P' => {
+                    let pointer = chars[PARAMETER_COLUMNS..DATA_COLUMNS]
+                        .iter()
+                        .collect::<String>()
+                        .trim()
+                        .parse::<usize>()
+                        .map_err(|_| ExchangeError::Syntax {
+                            line: number + 1,
+                            message: "parameter data has no directory entry pointer".to_string(),
+                        })?;
+                    parameters
+                        .entry(pointer)
+                        .or_default()
+                        .extend(chars[..PARAMETER_COLUMNS].iter());
+                }
+                'T' => break,
+                section => {
+                    return Err(ExchangeError::Syntax {
+                        line: number + 1,
+                        message: format!("unknown section '{section}'"),
+                    })
+                }
+            }
+        }
+
+        let (delimiter, end) = delimiters(&global);
+        let mut file = Self {
+            start: start.join("\n"),
+            global: split_params(&global, delimiter, end),
+            entities: BTreeMap::new(),
+        };
+
+        if directory.len() % 2 != 0 {
+            return Err(ExchangeError::Syntax {
+                line: directory.last().map_or(0, |(line, _)| *line),
+                message: "directory entries must have two lines".to_string(),
+            });
+        }
+        for (index, pair) in directory.chunks(2).enumerate() {
+            let (line, first) = &pair[0];
+            let field =
+                |text: &str, index: usize| text[index * 8..index * 8 + 8].trim().to_string();
+            let number = |text: &str, index: usize| -> ExchangeResult<i64> {
+                let value = field(text, index);
+                if value.is_empty() {
+                    return Ok(0);
+                }
+                value.parse().map_err(|_| ExchangeError::Syntax {
+                    line: *line,
+                    message: format!("directory entry field {} is not a number", index + 1),
+                })
+            };
+
+            let sequence = index * 2 + 1;
+            let params = parameters.remove(&sequence).unwrap_or_default();
+            let mut params = split_params(&params, delimiter, end);
+            if params.is_empty() {
+                return Err(ExchangeError::MissingEntity(sequence as u64));
+            }
+            params.remove(0);
+
+            file.entities.insert(
+                sequence,
+                IgesEntity {
+                    sequence,
+                    directory: DirectoryEntry {
+                        entity_type: number(first, 0)?,
+                        form: number(&pair[1].1, 4)?,
+                        transform: number(first, 6)?.unsigned_abs() as usize,
+                        status: field(first, 8),
+                    },
+                    params,
+                },
+            );
+        }
+
+        Ok(file)
+    }
+}
+
+/// Finds the parameter and record delimiters declared at the start of the global
+/// section, which default to a comma and a semicolon
+fn delimiters(global: &str) -> (char, char) {
+    let chars = global.chars().collect::<Vec<_>>();
+    let (delimiter, rest) = if chars.starts_with(&['1', 'H']) && chars.len() > 3 {
+        (chars[2], 4)
+    } else {
+        (',', 1)
+    };
+    let end = if chars[rest.min(chars.len())..].starts_with(&['1', 'H']) && chars.len() > rest + 2 {
+        chars[rest + 2]
+    } else {
+        ';'
+    };
+    (delimiter, end)
+}
+
+/// Splits free-format parameters up to the record delimiter. Strings are written
+/// in Hollerith form, as a length, `H` and that many characters, so they may
+/// contain delimiters.
+fn split_params(text: &str, delimiter: char, end: char) -> Vec<String> {
+    let chars = text.chars().collect::<Vec<_>>();
+    let mut params = Vec::new();
+    let mut i = 0;
+
+    while i < chars.len() {
+        while i < chars.len() && chars[i] == ' ' {
+            i += 1;
+        }
+
+        let digits = chars[i..].iter().take_while(|c| c.is_ascii_digit()).count();
+        let mut param = String::new();
+        if digits > 0 && chars.get(i + digits) == Some(&'H') {
+            let length = chars[i..i + digits]
+                .iter()
+                .collect::<String>()
+                .parse::<usize>()
+                .unwrap();
+            let from = (i + digits + 1).min(chars.len());
+            let to = (from + length).min(chars.len());
+            param = chars[from..to].iter().collect();
+            i = to;
+            while i < chars.len() && chars[i] != delimiter && chars[i] != end {
+                i += 1;
+            }
+        } else {
+            while i < chars.len() && chars[i] != delimiter && chars[i] != end {
+                param.push(chars[i]);
+                i += 1;
+            }
+            param = param.trim().to_string();
+        }
+        params.push(param);
+
+        if i >= chars.len() || chars[i] == end {
+            break;
+        }
+        i += 1;
+    }
+
+    params
+}
+
+/// Writes a string in Hollerith form
+pub(crate) fn hollerith(text: &str) -> String {
+    format!("{}H{text}", text.chars().count())
+}
+
+/// An entity waiting to be written, with its parameters already formatted
+pub(crate) struct EntityRecord {
+    pub entity_type: i64,
+    pub form: i64,
+    pub transform: usize,
+    pub status: &'static str,
+    pub params: Vec<String>,
+}
+
+/// Lays out a file in fixed 80-column form. Entities refer to each other by the
+/// sequence numbers of their directory entries, which are `2 * index + 1`.
+pub(crate) fn format(start: &str, global: &[String], entities: &[EntityRecord]) -> String {
+    let mut lines = Vec::new();
+
+    let start_lines = wrap(&[start.to_string()], DATA_COLUMNS);
+    for (i, line) in start_lines.iter().enumerate() {
+        lines.push(format!("{line:<72}S{:>7}", i + 1));
+    }
+
+    let global_lines = wrap(&delimited(global), DATA_COLUMNS);
+    for (i, line) in global_lines.iter().enumerate() {
+        lines.push(format!("{line:<72}G{:>7}", i + 1));
+    }
+
+    let mut parameter_lines = Vec::new();
+    let mut directory_lines = Vec::new();
+    for (index, entity) in entities.iter().enumerate() {
+        let sequence = index * 2 + 1;
+        let mut params = vec![entity.entity_type.to_string()];
+        params.extend(entity.params.iter().cloned());
+        let chunk = wrap(&delimited(&params), PARAMETER_COLUMNS);
+
+        directory_lines.push(format!(
+            "{:>8}{:>8}{:>8}{:>8}{:>8}{:>8}{:>8}{:>8}{:>8}D{:>7}",
+            entity.entity_type,
+            parameter_lines.len() + 1,
+            0,
+            1,
+            0,
+            0,
+            entity.transform,
+            0,
+            entity.status,
+            sequence
+        ));
+        directory_lines.push(format!(
+            "{:>8}{:>8}{:>8}{:>8}{:>8}{:>8}{:>8}{:>8}{:>8}D{:>7}",
+            entity.entity_type,
+            0,
+            0,
+            chunk.len(),
+            entity.form,
+            "",
+            "",
+            "",
+            0,
+            sequence + 1
+        ));
+        for line in chunk {
+            parameter_lines.push(format!(
+                "{line:<64}{sequence:>8}P{:>7}",
+                parameter_lines.len() + 1
+            ));
+        }
+    }
+
+    let counts = format!(
+        "S{:>7}G{:>7}D{:>7}P{:>7}",
+        start_lines.len(),
+        global_lines.len(),
+        directory_lines.len(),
+        parameter_lines.len()
+    );
+    lines.extend(directory_lines);
+    lines.extend(parameter_lines);
+    lines.push(format!("{counts:<72}T{:>7}", 1));
+
+    let mut text = lines.join("\n");
+    text.push('\n');
+    text
+}
+
+/// Joins parameters with the default delimiters, ending the record
+fn delimited(params: &[String]) -> Vec<String> {
+    params
+        .iter()
+        .enumerate()
+        .map(|(i, param)| {
+            let delimiter = if i + 1 == params.len() { ';' } else { ',' };
+            format!("{param}{delimiter}")
+        })
+        .collect()
+}
+
+/// Packs pieces of text into lines of at most `width` characters, only breaking
+/// inside a piece that is too long for a line of its own
+fn wrap(pieces: &[String], width: usize) -> Vec<String> {
+    let mut lines = Vec::new();
+    let mut line = String::new();
+    for piece in pieces {
+        if !line.is_empty() && line.len() + piece.len() > width {
+            lines.push(std::mem::take(&mut line));
+        }
+        line.push_str(piece);
+        while line.len() > width {
+            let rest = line.split_off(width);
+            lines.push(std::mem::replace(&mut line, rest));
+        }
+    }
+    if !line.is_empty() || lines.is_empty() {
+        lines.push(line);
+    }
+    lines
+}
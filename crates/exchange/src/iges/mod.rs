@@ -0,0 +1,149 @@
+//! Reading and writing IGES files (version 5.3) in fixed 80-column ASCII form.
+//! Lines, circular arcs, rational B-spline curves and surfaces, composite curves
+//! and trimmed surfaces are supported. IGES has no solids in the subset this
+//! crate reads, so solids are only written, as the trimmed surfaces of their
+//! faces.
+
+mod file;
+mod reader;
+mod writer;
+
+use std::path::Path;
+
+pub use file::{DirectoryEntry, IgesEntity, IgesFile};
+
+use crate::{error::ExchangeResult, geometry::Geometry};
+
+pub fn read_iges(text: &str) -> ExchangeResult<Geometry> {
+    reader::read(&IgesFile::parse(text)?)
+}
+
+/// Writes geometry with `name` as the product identification
+pub fn write_iges(geometry: &Geometry, name: &str) -> String {
+    writer::write(geometry, name)
+}
+
+pub fn read_iges_file(path: impl AsRef<Path>) -> ExchangeResult<Geometry> {
+    read_iges(&std::fs::read_to_string(path)?)
+}
+
+pub fn write_iges_file(
+    path: impl AsRef<Path>,
+    geometry: &Geometry,
+    name: &str,
+) -> ExchangeResult<()> {
+    Ok(std::fs::write(path, write_iges(geometry, name))?)
+}
+
+#[cfg(test)]
+mod tests {
+    use space::{
+        hspace::{HSpace2, HSpace3},
+        EPlacement3, EVec2, EVec3, EVector,
+    };
+    use spline::{
+        nurbs_curve::NurbsCurve, nurbs_surface::NurbsSurface, trimmed_surface::TrimmedSurface,
+    };
+    use topology::solid::Solid;
+
+    use crate::geometry::Geometry;
+
+    use super::{
+        file::{format, hollerith, EntityRecord},
+        read_iges, write_iges, IgesFile,
+    };
+
+    #[test]
+    fn round_trip() {
+        let placement = EPlacement3::from_origin(EVec3::new(1.0, 2.0, 3.0));
+        let mut trimmed = TrimmedSurface::new(NurbsSurface::rectangle(&placement, 2.0, 1.0));
+        trimmed.inner.push(vec![NurbsCurve::<HSpace2>::arc(
+            EVec2::new(0.5, 0.5),
+            0.25,
+            0.0,
+            2.0 * std::f64::consts::PI,
+        )]);
+        let geometry = Geometry {
+            curves: vec![NurbsCurve::<HSpace3>::arc(&placement, 1.5, 0.0, 2.0)],
+            surfaces: vec![NurbsSurface::<HSpace3>::sphere(&placement, 2.0)],
+            trimmed_surfaces: vec![trimmed],
+            solids: vec![Solid::block(&EPlacement3::default(), EVec3::new(1.0, 2.0, 3.0)).unwrap()],
+        };
+
+        let text = write_iges(&geometry, "part, with; delimiters");
+        assert!(text.lines().all(|line| line.chars().count() == 80));
+        let file = IgesFile::parse(&text).unwrap();
+        assert_eq!(file.global[2], "part, with; delimiters");
+        let read = read_iges(&text).unwrap();
+
+        let (curve, original) = (&read.curves[0], &geometry.curves[0]);
+        let (surface, sphere) = (&read.surfaces[0], &geometry.surfaces[0]);
+        for t in [0.0, 0.3, 0.71, 1.0] {
+            let u = original.min_u() + (original.max_u() - original.min_u()) * t;
+            assert!((curve.point(u) - original.point(u)).magnitude() <= 1e-12);
+            assert!((surface.point(t, 1.0 - t) - sphere.point(t, 1.0 - t)).magnitude() <= 1e-12);
+        }
+
+        // The trimmed rectangle, then one trimmed surface per face of the block
+        assert_eq!(read.trimmed_surfaces.len(), 7);
+        let rectangle = &read.trimmed_surfaces[0];
+        assert!(rectangle.outer.is_none());
+        let hole = &rectangle.inner[0][0];
+        let original = &geometry.trimmed_surfaces[0].inner[0][0];
+        for t in [0.0, 0.4, 0.9] {
+            let u = original.min_u() + (original.max_u() - original.min_u()) * t;
+            assert!((hole.point(u) - original.point(u)).magnitude() <= 1e-12);
+        }
+        assert!(read.trimmed_surfaces[1..]
+            .iter()
+            .all(|face| face.outer.as_ref().unwrap().len() == 4 && face.inner.is_empty()));
+    }
+
+    #[test]
+    fn read_transformed_inches() {
+        let global = [
+            ",", ";", "part", "part.igs", "", "", "32", "38", "6", "308", "15", "part",
+        ]
+        .iter()
+        .map(|text| hollerith(text))
+        .chain(["1.0", "1", "4HINCH"].map(String::from))
+        .collect::<Vec<_>>();
+        let entity = |entity_type, transform, params: &[&str]| EntityRecord {
+            entity_type,
+            form: 0,
+            transform,
+            status: "00000000",
+            params: params.iter().map(|param| param.to_string()).collect(),
+        };
+        let text = format(
+            "",
+            &global,
+            &[
+                // A quarter turn about Z, then a shift along X
+                entity(
+                    124,
+                    0,
+                    &[
+                        "0.", "-1.", "0.", "1.", "1.", "0.", "0.", "0.", "0.", "0.", "1.", "0.",
+                    ],
+                ),
+                entity(110, 1, &["1.", "0.", "0.", "2.", "0.", "0."]),
+                entity(100, 0, &["1.", "0.", "0.", "1.", "0.", "0.", "1."]),
+            ],
+        );
+
+        let geometry = read_iges(&text).unwrap();
+        assert_eq!(geometry.curves.len(), 2);
+
+        let line = &geometry.curves[0];
+        let (start, end) = (line.point(line.min_u()), line.point(line.max_u()));
+        assert!((start - EVec3::new(25.4, 25.4, 0.0)).magnitude() <= 1e-12);
+        assert!((end - EVec3::new(25.4, 50.8, 0.0)).magnitude() <= 1e-12);
+
+        // The arc runs counterclockwise from +X to +Y at a height of one inch
+        let arc = &geometry.curves[1];
+        let middle = arc.point((arc.min_u() + arc.max_u()) / 2.0);
+        let half = 25.4 / 2f64.sqrt();
+        assert!((middle - EVec3::new(half, half, 25.4)).magnitude() <= 1e-9);
+    }
+}
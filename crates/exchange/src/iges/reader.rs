@@ -0,0 +1,389 @@
+use std::{collections::BTreeSet, f64::consts::PI};
+
+use space::{
+    hspace::{HSpace2, HSpace3},
+    EPlacement3, EVec2, EVec3, EVector, HVec2, HVec3, Transform3, TOL,
+};
+use spline::{
+    math::knot_vector::KnotVector, nurbs_curve::NurbsCurve, nurbs_surface::NurbsSurface,
+    trimmed_surface::TrimmedSurface,
+};
+
+use crate::{
+    error::{ExchangeError, ExchangeResult},
+    geometry::Geometry,
+};
+
+use super::file::{IgesEntity, IgesFile};
+
+/// Number of points a curve in model space is sampled at when projecting it into
+/// the parameter space of a surface it trims
+const PROJECTION_SAMPLES: usize = 64;
+
+/// Reads the curves and surfaces of a parsed file. Lines, arcs, B-spline curves
+/// and composite curves that are not part of a trimmed surface become free
+/// curves, untrimmed B-spline surfaces become free surfaces, and trimmed
+/// surfaces keep their boundaries in the surface's parameter space. Model space
+/// is converted to millimeters.
+pub fn read(file: &IgesFile) -> ExchangeResult<Geometry> {
+    let reader = Reader {
+        file,
+        scale: unit_scale(file)?,
+    };
+    let mut geometry = Geometry::new();
+
+    // Entities referenced by composite curves and trims are parts of them
+    let mut used = BTreeSet::new();
+    for entity in file.entities.values() {
+        let pointers = match entity.directory.entity_type {
+            102 => (1..entity.params.len()).collect::<Vec<_>>(),
+            142 => vec![1, 2, 3],
+            144 => std::iter::once(0).chain(3..entity.params.len()).collect(),
+            _ => continue,
+        };
+        for index in pointers {
+            used.insert(reader.pointer(entity, index)?);
+        }
+    }
+
+    for (sequence, entity) in file.entities.iter() {
+        if used.contains(sequence) {
+            continue;
+        }
+        match entity.directory.entity_type {
+            100 | 110 | 126 => geometry.curves.push(reader.curve(*sequence)?),
+            102 => geometry.curves.extend(reader.composite(*sequence)?),
+            128 => geometry.surfaces.push(reader.surface(*sequence)?),
+            144 => geometry
+                .trimmed_surfaces
+                .push(reader.trimmed_surface(*sequence)?),
+            _ => {}
+        }
+    }
+
+    Ok(geometry)
+}
+
+/// The number of millimeters in the file's unit of length, from the units flag
+/// in the global section
+fn unit_scale(file: &IgesFile) -> ExchangeResult<f64> {
+    let flag = file.global.get(13).map_or("", |flag| flag.as_str());
+    Ok(match flag {
+        "" | "2" => 1.0,
+        "1" => 25.4,
+        "4" => 304.8,
+        "5" => 1_609_344.0,
+        "6" => 1000.0,
+        "7" => 1_000_000.0,
+        "8" => 0.0254,
+        "9" => 0.001,
+        "10" => 10.0,
+        "11" => 0.0000254,
+        _ => {
+            return Err(ExchangeError::Unsupported(format!(
+                "IGES units flag {flag}"
+            )))
+        }
+    })
+}
+
+struct Reader<'a> {
+    file: &'a IgesFile,
+    scale: f64,
+}
+impl<'a> Reader<'a> {
+    fn entity(&self, sequence: usize) -> ExchangeResult<&'a IgesEntity> {
+        self.file
+            .entities
+            .get(&sequence)
+            .ok_or(ExchangeError::MissingEntity(sequence as u64))
+    }
+
+    fn invalid(&self, entity: &IgesEntity, message: &str) -> ExchangeError {
+        ExchangeError::InvalidParameters {
+            id: entity.sequence as u64,
+            message: format!("entity type {} {message}", entity.directory.entity_type),
+        }
+    }
+
+    fn real(&self, entity: &IgesEntity, index: usize) -> ExchangeResult<f64> {
+        let param = entity
+            .params
+            .get(index)
+            .ok_or_else(|| self.invalid(entity, &format!("has no parameter {}", index + 1)))?;
+        if param.is_empty() {
+            return Ok(0.0);
+        }
+        param
+            .replace(['D', 'd'], "E")
+            .parse()
+            .map_err(|_| self.invalid(entity, &format!("parameter {} is not a number", index + 1)))
+    }
+
+    fn integer(&self, entity: &IgesEntity, index: usize) -> ExchangeResult<i64> {
+        let value = self.real(entity, index)?;
+        if value.fract() != 0.0 {
+            return Err(self.invalid(
+                entity,
+                &format!("parameter {} is not an integer", index + 1),
+            ));
+        }
+        Ok(value as i64)
+    }
+
+    fn reals(&self, entity: &IgesEntity, from: usize, count: usize) -> ExchangeResult<Vec<f64>> {
+        (from..from + count).map(|i| self.real(entity, i)).collect()
+    }
+
+    fn pointer(&self, entity: &IgesEntity, index: usize) -> ExchangeResult<usize> {
+        Ok(self.integer(entity, index)?.unsigned_abs() as usize)
+    }
+
+    /// The transformation from an entity's own coordinates to those of the file,
+    /// before converting to millimeters
+    fn matrices(&self, entity: &IgesEntity) -> ExchangeResult<Transform3> {
+        let mut transform = Transform3::identity();
+        let mut pointer = entity.directory.transform;
+        let mut seen = BTreeSet::new();
+        while pointer != 0 && seen.insert(pointer) {
+            let matrix = self.entity(pointer)?;
+            if matrix.directory.entity_type != 124 {
+                return Err(self.invalid(entity, "has a transformation that is not a matrix"));
+            }
+            let m = self.reals(matrix, 0, 12)?;
+            transform = transform.then(&Transform3::affine(
+                [[m[0], m[1], m[2]], [m[4], m[5], m[6]], [m[8], m[9], m[10]]],
+                EVec3::new(m[3], m[7], m[11]),
+            ));
+            pointer = matrix.directory.transform;
+        }
+        Ok(transform)
+    }
+
+    /// The transformation from an entity's own coordinates to model space in
+    /// millimeters
+    fn transform(&self, entity: &IgesEntity) -> ExchangeResult<Transform3> {
+        Ok(self
+            .matrices(entity)?
+            .then(&Transform3::uniform_scale(self.scale)))
+    }
+
+    /// Reads a curve in model space
+    fn curve(&self, sequence: usize) -> ExchangeResult<NurbsCurve<HSpace3>> {
+        self.curve_in(sequence, &Transform3::uniform_scale(self.scale))
+    }
+
+    /// Reads a curve, transforming it by its own matrices and then by `outer`
+    fn curve_in(&self, sequence: usize, outer: &Transform3) -> ExchangeResult<NurbsCurve<HSpace3>> {
+        let entity = self.entity(sequence)?;
+        let mut curve = self.raw_curve(entity)?;
+        curve
+            .transform(&self.matrices(entity)?.then(outer))
+            .map_err(|_| {
+                self.invalid(entity, "has control points whose weights are not positive")
+            })?;
+        Ok(curve)
+    }
+
+    /// Reads a curve in the parameter space of a surface, dropping its Z
+    /// coordinates
+    fn parameter_curve(&self, sequence: usize) -> ExchangeResult<NurbsCurve<HSpace2>> {
+        let curve = self.raw_curve(self.entity(sequence)?)?;
+        Ok(NurbsCurve::new(
+            curve
+                .control_points()
+                .iter()
+                .map(|p| HVec2::new(p.x, p.y, p.h))
+                .collect(),
+            curve.knot_vector().clone(),
+        ))
+    }
+
+    /// Reads a curve in its own coordinates
+    fn raw_curve(&self, entity: &IgesEntity) -> ExchangeResult<NurbsCurve<HSpace3>> {
+        match entity.directory.entity_type {
+            // Circular arc, counterclockwise in a plane parallel to XY
+            100 => {
+                let p = self.reals(entity, 0, 7)?;
+                let center = EVec2::new(p[1], p[2]);
+                let (start, end) = (
+                    EVec2::new(p[3], p[4]) - center,
+                    EVec2::new(p[5], p[6]) - center,
+                );
+                let from = start.y.atan2(start.x);
+                let mut to = end.y.atan2(end.x);
+                while to <= from + TOL {
+                    to += 2.0 * PI;
+                }
+                Ok(NurbsCurve::<HSpace3>::arc(
+                    &EPlacement3::from_origin(EVec3::new(center.x, center.y, p[0])),
+                    start.magnitude(),
+                    from,
+                    to,
+                ))
+            }
+            110 => {
+                let p = self.reals(entity, 0, 6)?;
+                Ok(NurbsCurve::<HSpace3>::line(
+                    EVec3::new(p[0], p[1], p[2]),
+                    EVec3::new(p[3], p[4], p[5]),
+                ))
+            }
+            126 => {
+                let last = self.integer(entity, 0)? as usize;
+                let degree = self.integer(entity, 1)? as usize;
+                let knots = self.reals(entity, 6, last + degree + 2)?;
+                let weights_from = 6 + knots.len();
+                let weights = self.reals(entity, weights_from, last + 1)?;
+                let coordinates = self.reals(entity, weights_from + last + 1, 3 * (last + 1))?;
+
+                Ok(NurbsCurve::new(
+                    coordinates
+                        .chunks(3)
+                        .zip(weights.iter())
+                        .map(|(p, w)| HVec3::new(p[0], p[1], p[2], *w))
+                        .collect(),
+                    KnotVector::from_vec(knots),
+                ))
+            }
+            other => Err(ExchangeError::Unsupported(format!(
+                "IGES entity type {other} as a curve"
+            ))),
+        }
+    }
+
+    /// Reads the segments of a composite curve, or a single curve, in model space
+    fn composite(&self, sequence: usize) -> ExchangeResult<Vec<NurbsCurve<HSpace3>>> {
+        let entity = self.entity(sequence)?;
+        if entity.directory.entity_type != 102 {
+            return Ok(vec![self.curve(sequence)?]);
+        }
+
+        let transform = self.transform(entity)?;
+        let count = self.integer(entity, 0)? as usize;
+        (1..=count)
+            .map(|i| self.curve_in(self.pointer(entity, i)?, &transform))
+            .collect()
+    }
+
+    /// Reads the segments of a composite curve, or a single curve, in the
+    /// parameter space of a surface
+    fn parameter_composite(&self, sequence: usize) -> ExchangeResult<Vec<NurbsCurve<HSpace2>>> {
+        let entity = self.entity(sequence)?;
+        if entity.directory.entity_type != 102 {
+            return Ok(vec![self.parameter_curve(sequence)?]);
+        }
+
+        let count = self.integer(entity, 0)? as usize;
+        (1..=count)
+            .map(|i| self.parameter_curve(self.pointer(entity, i)?))
+            .collect()
+    }
+
+    fn surface(&self, sequence: usize) -> ExchangeResult<NurbsSurface<HSpace3>> {
+        let entity = self.entity(sequence)?;
+        if entity.directory.entity_type != 128 {
+            return Err(ExchangeError::Unsupported(format!(
+                "IGES entity type {} as a surface",
+                entity.directory.entity_type
+            )));
+        }
+
+        let (last_u, last_v) = (
+            self.integer(entity, 0)? as usize,
+            self.integer(entity, 1)? as usize,
+        );
+        let (degree_u, degree_v) = (
+            self.integer(entity, 2)? as usize,
+            self.integer(entity, 3)? as usize,
+        );
+        let knots_u = self.reals(entity, 9, last_u + degree_u + 2)?;
+        let knots_v = self.reals(entity, 9 + knots_u.len(), last_v + degree_v + 2)?;
+        let count = (last_u + 1) * (last_v + 1);
+        let weights_from = 9 + knots_u.len() + knots_v.len();
+        let weights = self.reals(entity, weights_from, count)?;
+        let coordinates = self.reals(entity, weights_from + count, 3 * count)?;
+
+        // Points are listed with u varying fastest
+        let control_points = (0..=last_u)
+            .map(|i| {
+                (0..=last_v)
+                    .map(|j| {
+                        let k = j * (last_u + 1) + i;
+                        let p = &coordinates[3 * k..3 * k + 3];
+                        HVec3::new(p[0], p[1], p[2], weights[k])
+                    })
+                    .collect()
+            })
+            .collect();
+
+        let mut surface = NurbsSurface::new(
+            control_points,
+            KnotVector::from_vec(knots_u),
+            KnotVector::from_vec(knots_v),
+        );
+        surface.transform(&self.transform(entity)?).map_err(|_| {
+            self.invalid(entity, "has control points whose weights are not positive")
+        })?;
+        Ok(surface)
+    }
+
+    fn trimmed_surface(&self, sequence: usize) -> ExchangeResult<TrimmedSurface> {
+        let entity = self.entity(sequence)?;
+        let surface = self.surface(self.pointer(entity, 0)?)?;
+        let has_outer = self.integer(entity, 1)? != 0;
+        let num_inner = self.integer(entity, 2)? as usize;
+
+        let outer = if has_outer {
+            Some(self.trim_loop(self.pointer(entity, 3)?, &surface)?)
+        } else {
+            None
+        };
+        let inner = (0..num_inner)
+            .map(|i| self.trim_loop(self.pointer(entity, 4 + i)?, &surface))
+            .collect::<ExchangeResult<Vec<_>>>()?;
+
+        Ok(TrimmedSurface {
+            surface,
+            outer,
+            inner,
+        })
+    }
+
+    /// Reads a curve on a surface as a loop in the surface's parameter space. If
+    /// the file only has the loop in model space, it is projected onto the surface.
+    fn trim_loop(
+        &self,
+        sequence: usize,
+        surface: &NurbsSurface<HSpace3>,
+    ) -> ExchangeResult<Vec<NurbsCurve<HSpace2>>> {
+        let entity = self.entity(sequence)?;
+        if entity.directory.entity_type != 142 {
+            return Err(self.invalid(entity, "is not a curve on a parametric surface"));
+        }
+
+        let parameter = self.pointer(entity, 2)?;
+        if parameter != 0 {
+            return self.parameter_composite(parameter);
+        }
+
+        let model = self.pointer(entity, 3)?;
+        if model == 0 {
+            return Err(self.invalid(entity, "has neither a parameter nor a model space curve"));
+        }
+        Ok(self
+            .composite(model)?
+            .iter()
+            .map(|curve| {
+                let points = (0..=PROJECTION_SAMPLES)
+                    .map(|i| {
+                        let t = i as f64 / PROJECTION_SAMPLES as f64;
+                        let u = curve.min_u() + (curve.max_u() - curve.min_u()) * t;
+                        surface.distance_to_point(curve.point(u), TOL).first.param
+                    })
+                    .collect::<Vec<_>>();
+                NurbsCurve::<HSpace2>::polyline(&points)
+            })
+            .collect())
+    }
+}
@@ -0,0 +1,273 @@
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use crate::{
+    format::real,
+    geometry::{face_surfaces, Geometry},
+};
+use space::{
+    hspace::{HSpace2, HSpace3},
+    HVec3, Tolerance, TOL,
+};
+use spline::{
+    nurbs_curve::NurbsCurve, nurbs_surface::NurbsSurface, trimmed_surface::TrimmedSurface,
+};
+
+use super::file::{format, hollerith, EntityRecord};
+
+/// Status of an entity that stands on its own
+const INDEPENDENT: &str = "00000000";
+
+/// Status of an entity that only exists as part of another
+const SUBORDINATE: &str = "00010000";
+
+/// Status of a subordinate curve in the parameter space of a surface
+const PARAMETER_CURVE: &str = "00010500";
+
+/// Writes geometry as a fixed-format IGES 5.3 file in millimeters. Curves are
+/// written as rational B-spline curves (type 126), surfaces as rational B-spline
+/// surfaces (type 128) and trimmed surfaces as type 144, with their boundaries
+/// as curves on the surface (type 142) in parameter space only. Each face of a
+/// solid with a surface and p-curves becomes a trimmed surface, since the file
+/// has no topology.
+pub fn write(geometry: &Geometry, name: &str) -> String {
+    let mut writer = Writer::default();
+
+    for curve in geometry.curves.iter() {
+        writer.curve(curve, INDEPENDENT);
+    }
+    for surface in geometry.surfaces.iter() {
+        writer.surface(surface, INDEPENDENT);
+    }
+    for trimmed in geometry.trimmed_surfaces.iter() {
+        writer.trimmed_surface(trimmed);
+    }
+    for solid in geometry.solids.iter() {
+        for (_, trimmed) in face_surfaces(solid) {
+            writer.trimmed_surface(&trimmed);
+        }
+    }
+
+    let max_coordinate = writer.max_coordinate.max(1.0);
+    let date = hollerith(&timestamp());
+    let global = vec![
+        hollerith(","),
+        hollerith(";"),
+        hollerith(name),
+        hollerith(&format!("{name}.igs")),
+        hollerith("cadit"),
+        hollerith(env!("CARGO_PKG_VERSION")),
+        "32".to_string(),
+        "38".to_string(),
+        "6".to_string(),
+        "308".to_string(),
+        "15".to_string(),
+        hollerith(name),
+        real(1.0),
+        "2".to_string(),
+        hollerith("MM"),
+        "1".to_string(),
+        real(1.0),
+        date.clone(),
+        real(TOL),
+        real(max_coordinate),
+        hollerith(""),
+        hollerith(""),
+        "11".to_string(),
+        "0".to_string(),
+        date,
+    ];
+
+    format(name, &global, &writer.entities)
+}
+
+/// The current time in UTC, as the `YYYYMMDD.HHNNSS` date IGES uses
+fn timestamp() -> String {
+    let seconds = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |time| time.as_secs());
+    let (days, time) = (seconds / 86_400, seconds % 86_400);
+
+    // Days since the epoch to a civil date, counting eras of 400 years that
+    // start in March so leap days fall at the end of each year
+    let days = days as i64 + 719_468;
+    let era = days / 146_097;
+    let day_of_era = days - era * 146_097;
+    let year_of_era =
+        (day_of_era - day_of_era / 1460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let month = (5 * day_of_year + 2) / 153;
+    let day = day_of_year - (153 * month + 2) / 5 + 1;
+    let month = if month < 10 { month + 3 } else { month - 9 };
+    let year = year_of_era + era * 400 + i64::from(month <= 2);
+
+    format!(
+        "{year:04}{month:02}{day:02}.{:02}{:02}{:02}",
+        time / 3600,
+        time / 60 % 60,
+        time % 60
+    )
+}
+
+#[derive(Default)]
+struct Writer {
+    entities: Vec<EntityRecord>,
+    max_coordinate: f64,
+}
+impl Writer {
+    /// Adds an entity, returning the sequence number of its directory entry
+    fn add(
+        &mut self,
+        entity_type: i64,
+        form: i64,
+        status: &'static str,
+        params: Vec<String>,
+    ) -> usize {
+        self.entities.push(EntityRecord {
+            entity_type,
+            form,
+            transform: 0,
+            status,
+            params,
+        });
+        self.entities.len() * 2 - 1
+    }
+
+    fn curve(&mut self, curve: &NurbsCurve<HSpace3>, status: &'static str) -> usize {
+        let points = curve.control_points();
+        let mut params = vec![
+            (points.len() - 1).to_string(),
+            curve.degree().to_string(),
+            "0".to_string(),
+            flag(curve.is_closed(&Tolerance::DEFAULT)),
+            flag(points.iter().all(|p| p.h == 1.0)),
+            "0".to_string(),
+        ];
+        params.extend(curve.knot_vector().iter().map(|k| real(*k)));
+        params.extend(points.iter().map(|p| real(p.h)));
+        for p in points.iter() {
+            params.extend(self.coordinates(p));
+        }
+        params.extend([real(curve.min_u()), real(curve.max_u())]);
+        params.extend(["0", "0", "0"].map(String::from));
+
+        self.add(126, 0, status, params)
+    }
+
+    /// Writes a curve in the parameter space of a surface as a planar curve in
+    /// the XY plane
+    fn parameter_curve(&mut self, curve: &NurbsCurve<HSpace2>) -> usize {
+        let points = curve.control_points();
+        let mut params = vec![
+            (points.len() - 1).to_string(),
+            curve.degree().to_string(),
+            "1".to_string(),
+            flag(curve.is_closed(&Tolerance::DEFAULT)),
+            flag(points.iter().all(|p| p.h == 1.0)),
+            "0".to_string(),
+        ];
+        params.extend(curve.knot_vector().iter().map(|k| real(*k)));
+        params.extend(points.iter().map(|p| real(p.h)));
+        for p in points.iter() {
+            params.extend([real(p.x), real(p.y), real(0.0)]);
+        }
+        params.extend([real(curve.min_u()), real(curve.max_u())]);
+        params.extend(["0", "0", "1"].map(String::from));
+
+        self.add(126, 0, PARAMETER_CURVE, params)
+    }
+
+    fn surface(&mut self, surface: &NurbsSurface<HSpace3>, status: &'static str) -> usize {
+        let points = surface.control_points();
+        let (count_u, count_v) = (points.len(), points[0].len());
+        let mut params = vec![
+            (count_u - 1).to_string(),
+            (count_v - 1).to_string(),
+            surface.degree_u().to_string(),
+            surface.degree_v().to_string(),
+            "0".to_string(),
+            "0".to_string(),
+            flag(points.iter().flatten().all(|p| p.h == 1.0)),
+            "0".to_string(),
+            "0".to_string(),
+        ];
+        params.extend(surface.knot_vector_u().iter().map(|k| real(*k)));
+        params.extend(surface.knot_vector_v().iter().map(|k| real(*k)));
+
+        // Points are listed with u varying fastest
+        let ordered = (0..count_v)
+            .flat_map(|j| points.iter().map(move |column| column[j]))
+            .collect::<Vec<_>>();
+        params.extend(ordered.iter().map(|p| real(p.h)));
+        for p in ordered.iter() {
+            params.extend(self.coordinates(p));
+        }
+        params.extend([
+            real(surface.min_u()),
+            real(surface.max_u()),
+            real(surface.min_v()),
+            real(surface.max_v()),
+        ]);
+
+        self.add(128, 0, status, params)
+    }
+
+    fn trimmed_surface(&mut self, trimmed: &TrimmedSurface) -> usize {
+        let surface = self.surface(&trimmed.surface, SUBORDINATE);
+        let outer = trimmed
+            .outer
+            .as_ref()
+            .map_or(0, |curves| self.trim_loop(surface, curves));
+        let inner = trimmed
+            .inner
+            .iter()
+            .map(|curves| self.trim_loop(surface, curves))
+            .collect::<Vec<_>>();
+
+        let mut params = vec![
+            surface.to_string(),
+            flag(outer != 0),
+            inner.len().to_string(),
+            outer.to_string(),
+        ];
+        params.extend(inner.iter().map(|pointer| pointer.to_string()));
+        self.add(144, 0, INDEPENDENT, params)
+    }
+
+    /// Writes a loop of curves in a surface's parameter space as a curve on the
+    /// surface, joining them into a composite curve if there is more than one
+    fn trim_loop(&mut self, surface: usize, curves: &[NurbsCurve<HSpace2>]) -> usize {
+        let pointers = curves
+            .iter()
+            .map(|curve| self.parameter_curve(curve))
+            .collect::<Vec<_>>();
+        let parameter = if pointers.len() == 1 {
+            pointers[0]
+        } else {
+            let mut params = vec![pointers.len().to_string()];
+            params.extend(pointers.iter().map(|pointer| pointer.to_string()));
+            self.add(102, 0, PARAMETER_CURVE, params)
+        };
+
+        self.add(
+            142,
+            0,
+            SUBORDINATE,
+            ["0", &surface.to_string(), &parameter.to_string(), "0", "1"]
+                .map(String::from)
+                .to_vec(),
+        )
+    }
+
+    /// The coordinates of a control point, keeping track of the largest one for
+    /// the global section
+    fn coordinates(&mut self, point: &HVec3) -> [String; 3] {
+        for value in [point.x, point.y, point.z] {
+            self.max_coordinate = self.max_coordinate.max(value.abs());
+        }
+        [real(point.x), real(point.y), real(point.z)]
+    }
+}
+
+fn flag(value: bool) -> String {
+    if value { "1" } else { "0" }.to_string()
+}
@@ -0,0 +1,6 @@
+pub mod error;
+mod format;
+pub mod geometry;
+pub mod iges;
+pub mod mesh;
+pub mod step;
@@ -0,0 +1,232 @@
+use std::collections::HashMap;
+
+use space::{EVec3, EVector};
+
+use super::{facet_normal, MeshPart, MeshVertex};
+
+/// Identifies vertices at the same position, however many copies of them a part
+/// has for different normals
+fn position_key(point: EVec3) -> [u64; 3] {
+    [point.x, point.y, point.z].map(f64::to_bits)
+}
+
+impl MeshPart {
+    /// Numbers the distinct positions of the part's vertices, returning the number
+    /// of each vertex's position and the positions themselves
+    fn positions(&self) -> (Vec<usize>, Vec<EVec3>) {
+        let mut numbers = HashMap::new();
+        let mut positions = Vec::new();
+        let ids = self
+            .vertices
+            .iter()
+            .map(|vertex| {
+                *numbers
+                    .entry(position_key(vertex.position))
+                    .or_insert_with(|| {
+                        positions.push(vertex.position);
+                        positions.len() - 1
+                    })
+            })
+            .collect();
+        (ids, positions)
+    }
+
+    /// Replaces the part's normals with ones averaged over the triangles around
+    /// each vertex, weighted by area. Triangles meeting at more than
+    /// `crease_angle` radians don't share normals, so creases stay sharp.
+    pub fn compute_normals(&mut self, crease_angle: f64) {
+        let (ids, _) = self.positions();
+        let min_cos = crease_angle.cos();
+        let units = (0..self.triangles.len())
+            .map(|i| facet_normal(self.triangle_points(i)))
+            .collect::<Vec<_>>();
+        let weighted = (0..self.triangles.len())
+            .map(|i| {
+                let [a, b, c] = self.triangle_points(i);
+                (b - a).cross(&(c - a))
+            })
+            .collect::<Vec<_>>();
+
+        let mut around = HashMap::<usize, Vec<usize>>::new();
+        for (i, triangle) in self.triangles.iter().enumerate() {
+            for vertex in triangle {
+                around.entry(ids[*vertex]).or_default().push(i);
+            }
+        }
+
+        let mut vertices = Vec::new();
+        let mut numbers = HashMap::new();
+        let mut triangles = Vec::with_capacity(self.triangles.len());
+        for (i, triangle) in self.triangles.iter().enumerate() {
+            triangles.push(triangle.map(|vertex| {
+                let sum = around[&ids[vertex]]
+                    .iter()
+                    .filter(|other| units[**other].dot(&units[i]) >= min_cos)
+                    .map(|other| weighted[*other])
+                    .sum::<EVec3>();
+                let normal = if sum.magnitude() > 0.0 {
+                    sum.normalize()
+                } else {
+                    units[i]
+                };
+
+                *numbers
+                    .entry((ids[vertex], position_key(normal)))
+                    .or_insert_with(|| {
+                        vertices.push(MeshVertex::new(self.vertices[vertex].position, normal));
+                        vertices.len() - 1
+                    })
+            }));
+        }
+
+        self.vertices = vertices;
+        self.triangles = triangles;
+    }
+
+    /// Finds the edges worth drawing: those on the part's boundary, those shared
+    /// by more than two triangles, and creases where triangles meet at more than
+    /// `feature_angle` radians. Edges are joined into polylines, whose vertices'
+    /// normals point out between the triangles they border.
+    pub fn feature_edges(&self, feature_angle: f64) -> Vec<Vec<MeshVertex>> {
+        let (ids, positions) = self.positions();
+        let min_cos = feature_angle.cos();
+        let units = (0..self.triangles.len())
+            .map(|i| facet_normal(self.triangle_points(i)))
+            .collect::<Vec<_>>();
+
+        let mut edges = HashMap::<(usize, usize), Vec<usize>>::new();
+        for (i, triangle) in self.triangles.iter().enumerate() {
+            let [a, b, c] = triangle.map(|vertex| ids[vertex]);
+            for (from, to) in [(a, b), (b, c), (c, a)] {
+                edges
+                    .entry((from.min(to), from.max(to)))
+                    .or_default()
+                    .push(i);
+            }
+        }
+
+        let mut segments = edges
+            .iter()
+            .filter(|(_, triangles)| match triangles[..] {
+                [first, second] => units[first].dot(&units[second]) < min_cos,
+                _ => true,
+            })
+            .map(|(edge, triangles)| {
+                let normal = triangles.iter().map(|i| units[*i]).sum::<EVec3>();
+                (*edge, normal)
+            })
+            .collect::<Vec<_>>();
+        segments.sort_by_key(|(edge, _)| *edge);
+
+        let mut at = HashMap::<usize, Vec<usize>>::new();
+        for (i, ((a, b), _)) in segments.iter().enumerate() {
+            at.entry(*a).or_default().push(i);
+            at.entry(*b).or_default().push(i);
+        }
+
+        // Chains start where edges end or branch, and whatever is left are loops
+        let mut used = vec![false; segments.len()];
+        let mut starts = at
+            .iter()
+            .filter(|(_, segments)| segments.len() != 2)
+            .map(|(vertex, _)| *vertex)
+            .collect::<Vec<_>>();
+        starts.sort_unstable();
+        starts.extend(segments.iter().map(|((a, _), _)| *a));
+
+        let mut chains = Vec::new();
+        for start in starts {
+            while let Some(first) = at[&start].iter().copied().find(|i| !used[*i]) {
+                let mut chain = vec![(start, None::<EVec3>)];
+                let mut next = Some(first);
+                while let Some(segment) = next {
+                    used[segment] = true;
+                    let ((a, b), normal) = segments[segment];
+                    let (current, other) = chain.last().copied().unwrap();
+                    let end = if a == current { b } else { a };
+                    *chain.last_mut().unwrap() =
+                        (current, Some(other.map_or(normal, |n| n + normal)));
+                    chain.push((end, Some(normal)));
+
+                    next = if at[&end].len() == 2 {
+                        at[&end].iter().copied().find(|i| !used[*i])
+                    } else {
+                        None
+                    };
+                }
+
+                // A closed loop shares its first vertex's normal with its last
+                if chain.len() > 2 && chain[0].0 == chain[chain.len() - 1].0 {
+                    let joined = chain[0].1.unwrap() + chain[chain.len() - 1].1.unwrap();
+                    chain[0].1 = Some(joined);
+                    let last = chain.len() - 1;
+                    chain[last].1 = Some(joined);
+                }
+
+                chains.push(
+                    chain
+                        .into_iter()
+                        .map(|(vertex, normal)| {
+                            let normal = normal.unwrap_or_else(EVec3::zero);
+                            let normal = if normal.magnitude() > 0.0 {
+                                normal.normalize()
+                            } else {
+                                normal
+                            };
+                            MeshVertex::new(positions[vertex], normal)
+                        })
+                        .collect(),
+                );
+            }
+        }
+        chains
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use space::{EPlacement3, EVec3, EVector};
+    use topology::{mesh::TriMesh, solid::Solid};
+
+    use crate::mesh::MeshPart;
+
+    #[test]
+    fn block_normals_and_edges() {
+        let block = Solid::block(&EPlacement3::default(), EVec3::new(1.0, 2.0, 3.0)).unwrap();
+        let mesh = TriMesh::from_solid(&block).unwrap();
+        let mut part = MeshPart::new("block");
+        part.vertices = mesh
+            .vertices
+            .iter()
+            .map(|point| crate::mesh::MeshVertex::new(*point, EVec3::zero()))
+            .collect();
+        part.triangles = mesh.triangles.clone();
+
+        // Every corner gets a normal for each of its three faces
+        part.compute_normals(30f64.to_radians());
+        assert_eq!(part.vertices.len(), 24);
+        assert!(part
+            .vertices
+            .iter()
+            .all(|vertex| (vertex.normal.magnitude() - 1.0).abs() <= 1e-12));
+
+        // Only the twelve edges of the block are features, not the diagonals
+        // splitting its faces
+        let edges = part.feature_edges(30f64.to_radians());
+        let segments = edges.iter().map(|chain| chain.len() - 1).sum::<usize>();
+        assert_eq!(segments, 12);
+        for chain in edges.iter() {
+            for pair in chain.windows(2) {
+                let direction = pair[1].position - pair[0].position;
+                let axes = [direction.x, direction.y, direction.z]
+                    .iter()
+                    .filter(|c| c.abs() > 1e-12)
+                    .count();
+                assert_eq!(axes, 1);
+            }
+        }
+
+        // A smooth cutoff above the right angles leaves no edges
+        assert!(part.feature_edges(100f64.to_radians()).is_empty());
+    }
+}
@@ -0,0 +1,304 @@
+use std::fmt::Write;
+
+use space::EVec3;
+
+use super::Mesh;
+
+/// Component types and buffer view targets from the glTF specification
+const FLOAT: u32 = 5126;
+const UNSIGNED_INT: u32 = 5125;
+const ARRAY_BUFFER: u32 = 34962;
+const ELEMENT_ARRAY_BUFFER: u32 = 34963;
+
+/// Chunk types of a binary glTF file
+const JSON_CHUNK: u32 = 0x4E4F534A;
+const BIN_CHUNK: u32 = 0x004E4942;
+
+/// Writes a mesh as a glTF 2.0 file, with its buffer embedded as a base64 data URI
+pub fn write_gltf(mesh: &Mesh) -> String {
+    document(mesh, true).0
+}
+
+/// Writes a mesh as a binary glTF 2.0 file
+pub fn write_glb(mesh: &Mesh) -> Vec<u8> {
+    let (json, buffer) = document(mesh, false);
+    let mut json = json.into_bytes();
+    pad(&mut json, b' ');
+    let mut buffer = buffer;
+    pad(&mut buffer, 0);
+
+    let length = 12
+        + 8
+        + json.len()
+        + if buffer.is_empty() {
+            0
+        } else {
+            8 + buffer.len()
+        };
+    let mut bytes = Vec::with_capacity(length);
+    bytes.extend(b"glTF");
+    bytes.extend(2u32.to_le_bytes());
+    bytes.extend((length as u32).to_le_bytes());
+    bytes.extend((json.len() as u32).to_le_bytes());
+    bytes.extend(JSON_CHUNK.to_le_bytes());
+    bytes.extend(json);
+    if !buffer.is_empty() {
+        bytes.extend((buffer.len() as u32).to_le_bytes());
+        bytes.extend(BIN_CHUNK.to_le_bytes());
+        bytes.extend(buffer);
+    }
+    bytes
+}
+
+/// Lays out the JSON document and its binary buffer. Every part with triangles
+/// becomes a mesh with its own node, under a root node that scales millimeters to
+/// the meters glTF uses. If `embed` is set, the buffer is also written into the
+/// document as a data URI.
+fn document(mesh: &Mesh, embed: bool) -> (String, Vec<u8>) {
+    let mut buffer = Vec::new();
+    let mut views = Vec::new();
+    let mut accessors = Vec::new();
+    let mut meshes = Vec::new();
+    let mut nodes = Vec::new();
+
+    for part in mesh.parts.iter().filter(|part| !part.triangles.is_empty()) {
+        let mut view = |data: Vec<u8>, target: u32| {
+            views.push(format!(
+                "{{\"buffer\":0,\"byteOffset\":{},\"byteLength\":{},\"target\":{target}}}",
+                buffer.len(),
+                data.len()
+            ));
+            buffer.extend(data);
+            views.len() - 1
+        };
+
+        let positions = part.vertices.iter().map(|v| v.position).collect::<Vec<_>>();
+        let normals = part.vertices.iter().map(|v| v.normal).collect::<Vec<_>>();
+        let indices = part
+            .triangles
+            .iter()
+            .flatten()
+            .flat_map(|i| (*i as u32).to_le_bytes())
+            .collect::<Vec<_>>();
+        let position_view = view(floats(&positions), ARRAY_BUFFER);
+        let normal_view = view(floats(&normals), ARRAY_BUFFER);
+        let index_view = view(indices, ELEMENT_ARRAY_BUFFER);
+
+        let (min, max) = bounds(&positions);
+        accessors.push(format!(
+            "{{\"bufferView\":{position_view},\"componentType\":{FLOAT},\"count\":{},\"type\":\"VEC3\",\"min\":{},\"max\":{}}}",
+            positions.len(),
+            array(&min),
+            array(&max)
+        ));
+        accessors.push(format!(
+            "{{\"bufferView\":{normal_view},\"componentType\":{FLOAT},\"count\":{},\"type\":\"VEC3\"}}",
+            normals.len()
+        ));
+        accessors.push(format!(
+            "{{\"bufferView\":{index_view},\"componentType\":{UNSIGNED_INT},\"count\":{},\"type\":\"SCALAR\"}}",
+            part.triangles.len() * 3
+        ));
+
+        let first = accessors.len() - 3;
+        let material = part
+            .material
+            .map(|material| format!(",\"material\":{material}"))
+            .unwrap_or_default();
+        meshes.push(format!(
+            "{{\"name\":{},\"primitives\":[{{\"attributes\":{{\"POSITION\":{first},\"NORMAL\":{}}},\"indices\":{}{material},\"mode\":4}}]}}",
+            string(&part.name),
+            first + 1,
+            first + 2
+        ));
+        nodes.push(format!(
+            "{{\"name\":{},\"mesh\":{}}}",
+            string(&part.name),
+            meshes.len() - 1
+        ));
+    }
+
+    let materials = mesh
+        .materials
+        .iter()
+        .map(|material| {
+            let alpha = if material.is_translucent() {
+                ",\"alphaMode\":\"BLEND\""
+            } else {
+                ""
+            };
+            format!(
+                "{{\"name\":{},\"pbrMetallicRoughness\":{{\"baseColorFactor\":{},\"metallicFactor\":0,\"roughnessFactor\":{}}}{alpha}}}",
+                string(&material.name),
+                array(&material.color),
+                material.roughness
+            )
+        })
+        .collect::<Vec<_>>();
+
+    let children = (1..=nodes.len()).map(|i| i.to_string()).collect::<Vec<_>>();
+    nodes.insert(
+        0,
+        format!(
+            "{{\"name\":\"root\",\"scale\":[0.001,0.001,0.001],\"children\":[{}]}}",
+            children.join(",")
+        ),
+    );
+
+    let mut json = String::new();
+    write!(
+        json,
+        "{{\"asset\":{{\"version\":\"2.0\",\"generator\":\"cadit\"}},\"scene\":0,\"scenes\":[{{\"nodes\":[0]}}],\"nodes\":[{}]",
+        nodes.join(",")
+    )
+    .unwrap();
+    for (name, items) in [
+        ("meshes", meshes),
+        ("materials", materials),
+        ("accessors", accessors),
+        ("bufferViews", views),
+    ] {
+        if !items.is_empty() {
+            write!(json, ",\"{name}\":[{}]", items.join(",")).unwrap();
+        }
+    }
+    if !buffer.is_empty() {
+        let uri = if embed {
+            format!(
+                ",\"uri\":\"data:application/octet-stream;base64,{}\"",
+                base64(&buffer)
+            )
+        } else {
+            String::new()
+        };
+        write!(
+            json,
+            ",\"buffers\":[{{\"byteLength\":{}{uri}}}]",
+            buffer.len()
+        )
+        .unwrap();
+    }
+    json.push('}');
+
+    (json, buffer)
+}
+
+fn floats(vectors: &[EVec3]) -> Vec<u8> {
+    vectors
+        .iter()
+        .flat_map(|v| [v.x, v.y, v.z])
+        .flat_map(|value| (value as f32).to_le_bytes())
+        .collect()
+}
+
+fn bounds(points: &[EVec3]) -> ([f32; 3], [f32; 3]) {
+    let mut min = [f32::MAX; 3];
+    let mut max = [f32::MIN; 3];
+    for point in points {
+        for (i, value) in [point.x, point.y, point.z].into_iter().enumerate() {
+            min[i] = min[i].min(value as f32);
+            max[i] = max[i].max(value as f32);
+        }
+    }
+    (min, max)
+}
+
+fn array(values: &[f32]) -> String {
+    let values = values.iter().map(|v| v.to_string()).collect::<Vec<_>>();
+    format!("[{}]", values.join(","))
+}
+
+/// Quotes a string for JSON
+fn string(text: &str) -> String {
+    let mut quoted = String::from("\"");
+    for c in text.chars() {
+        match c {
+            '"' => quoted.push_str("\\\""),
+            '\\' => quoted.push_str("\\\\"),
+            c if (c as u32) < 0x20 => write!(quoted, "\\u{:04x}", c as u32).unwrap(),
+            c => quoted.push(c),
+        }
+    }
+    quoted.push('"');
+    quoted
+}
+
+/// Pads data to a multiple of four bytes, as chunks of a binary file must be
+fn pad(data: &mut Vec<u8>, byte: u8) {
+    data.resize((data.len() + 3) & !3, byte);
+}
+
+fn base64(data: &[u8]) -> String {
+    const ALPHABET: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+
+    let mut text = String::with_capacity(data.len() * 4 / 3 + 4);
+    for chunk in data.chunks(3) {
+        let bytes = [
+            chunk[0],
+            *chunk.get(1).unwrap_or(&0),
+            *chunk.get(2).unwrap_or(&0),
+        ];
+        let bits = u32::from(bytes[0]) << 16 | u32::from(bytes[1]) << 8 | u32::from(bytes[2]);
+        for i in 0..4 {
+            if i <= chunk.len() {
+                text.push(ALPHABET[(bits >> (18 - 6 * i) & 0x3F) as usize] as char);
+            } else {
+                text.push('=');
+            }
+        }
+    }
+    text
+}
+
+#[cfg(test)]
+mod tests {
+    use space::{EPlacement3, EVec3};
+    use topology::{mesh::TriMesh, solid::Solid};
+
+    use crate::mesh::{Mesh, MeshMaterial, MeshPart};
+
+    use super::{base64, write_glb, write_gltf};
+
+    #[test]
+    fn encode_base64() {
+        assert_eq!(base64(b""), "");
+        assert_eq!(base64(b"f"), "Zg==");
+        assert_eq!(base64(b"fo"), "Zm8=");
+        assert_eq!(base64(b"foobar"), "Zm9vYmFy");
+    }
+
+    #[test]
+    fn embedded_and_binary() {
+        let block = Solid::block(&EPlacement3::default(), EVec3::new(1.0, 2.0, 3.0)).unwrap();
+        let mut part =
+            MeshPart::from_tri_mesh("a \"block\"", &TriMesh::from_solid(&block).unwrap());
+        part.material = Some(0);
+        let mesh = Mesh {
+            parts: vec![part, MeshPart::new("empty")],
+            materials: vec![MeshMaterial::new("glass", [0.5, 0.5, 1.0, 0.25], 0.1)],
+        };
+
+        // 36 vertices with positions and normals, and 36 indices
+        let length = 36 * 12 * 2 + 36 * 4;
+        let text = write_gltf(&mesh);
+        assert!(text.contains(&format!("\"byteLength\":{length},\"uri\":\"data:")));
+        assert!(text.contains("\"name\":\"a \\\"block\\\"\""));
+        assert!(text.contains("\"alphaMode\":\"BLEND\""));
+        assert!(text.contains("\"min\":[0,0,0],\"max\":[1,2,3]"));
+        assert_eq!(text.matches("\"mesh\":").count(), 1);
+
+        let binary = write_glb(&mesh);
+        assert_eq!(&binary[..4], b"glTF");
+        assert_eq!(
+            u32::from_le_bytes(binary[8..12].try_into().unwrap()) as usize,
+            binary.len()
+        );
+        let json_length = u32::from_le_bytes(binary[12..16].try_into().unwrap()) as usize;
+        let bin = 20 + json_length;
+        assert_eq!(
+            u32::from_le_bytes(binary[bin..bin + 4].try_into().unwrap()) as usize,
+            length
+        );
+        assert_eq!(binary.len(), bin + 8 + length);
+    }
+}
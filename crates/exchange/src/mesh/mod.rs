@@ -0,0 +1,387 @@
+//! Exporting triangle meshes to STL, OBJ, PLY and glTF 2.0, for 3D printers and
+//! viewers, and importing them from OBJ and STL as reference geometry. Meshes
+//! come either from tessellating exchange geometry to a tolerance or from already
+//! tessellated models.
+
+mod features;
+mod gltf;
+mod obj;
+mod ply;
+mod stl;
+mod tessellate;
+
+use std::path::Path;
+
+use space::{EVec3, EVector, Tolerance};
+use topology::mesh::TriMesh;
+
+pub use gltf::{write_glb, write_gltf};
+pub use obj::{material_libraries, read_mtl, read_obj, write_mtl, write_obj};
+pub use ply::{write_ply, PlyFormat};
+pub use stl::{read_stl, write_stl, StlFormat};
+
+use crate::{
+    error::{ExchangeError, ExchangeResult},
+    geometry::{face_surfaces, Geometry},
+};
+
+/// The angle in radians beyond which triangles meeting at an edge don't share
+/// vertex normals when normals are computed for imported meshes
+pub const CREASE_ANGLE: f64 = std::f64::consts::PI / 6.0;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct MeshVertex {
+    pub position: EVec3,
+    pub normal: EVec3,
+}
+impl MeshVertex {
+    pub fn new(position: EVec3, normal: EVec3) -> Self {
+        Self { position, normal }
+    }
+}
+
+/// A surface material, as a base color with alpha and a roughness between 0 and 1
+#[derive(Debug, Clone, PartialEq)]
+pub struct MeshMaterial {
+    pub name: String,
+    pub color: [f32; 4],
+    pub roughness: f32,
+}
+impl MeshMaterial {
+    pub fn new(name: &str, color: [f32; 4], roughness: f32) -> Self {
+        Self {
+            name: name.to_string(),
+            color,
+            roughness,
+        }
+    }
+
+    pub fn is_translucent(&self) -> bool {
+        self.color[3] < 1.0
+    }
+}
+
+/// A named piece of a mesh with one material. Triangles wind counterclockwise
+/// when seen from the side their normals point to.
+#[derive(Debug, Clone, Default)]
+pub struct MeshPart {
+    pub name: String,
+    pub vertices: Vec<MeshVertex>,
+    pub triangles: Vec<[usize; 3]>,
+
+    /// Index of the part's material in its mesh, if it has one
+    pub material: Option<usize>,
+}
+impl MeshPart {
+    pub fn new(name: &str) -> Self {
+        Self {
+            name: name.to_string(),
+            ..Default::default()
+        }
+    }
+
+    /// Makes a part from a mesh whose faces are flat, giving each triangle its own
+    /// vertices so they keep the triangle's normal
+    pub fn from_tri_mesh(name: &str, mesh: &TriMesh) -> Self {
+        let mut part = Self::new(name);
+        for index in 0..mesh.triangles.len() {
+            let [a, b, c] = mesh.triangle_points(index);
+            let normal = facet_normal([a, b, c]);
+            let first = part.vertices.len();
+            part.vertices
+                .extend([a, b, c].map(|point| MeshVertex::new(point, normal)));
+            part.triangles.push([first, first + 1, first + 2]);
+        }
+        part
+    }
+
+    pub fn triangle_points(&self, index: usize) -> [EVec3; 3] {
+        self.triangles[index].map(|i| self.vertices[i].position)
+    }
+
+    /// Turns the part inside out, reversing its triangles and normals
+    pub fn flip(&mut self) {
+        for vertex in self.vertices.iter_mut() {
+            vertex.normal = -vertex.normal;
+        }
+        for triangle in self.triangles.iter_mut() {
+            triangle.swap(1, 2);
+        }
+    }
+
+    /// Adds the vertices and triangles of another part
+    pub fn append(&mut self, other: &MeshPart) {
+        let offset = self.vertices.len();
+        self.vertices.extend(other.vertices.iter().copied());
+        self.triangles.extend(
+            other
+                .triangles
+                .iter()
+                .map(|triangle| triangle.map(|i| i + offset)),
+        );
+    }
+}
+
+/// Triangles to export, in parts that may each have a material. Positions are in
+/// millimeters.
+#[derive(Debug, Clone, Default)]
+pub struct Mesh {
+    pub parts: Vec<MeshPart>,
+    pub materials: Vec<MeshMaterial>,
+}
+impl Mesh {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Tessellates surfaces, trimmed surfaces and solids so that no triangle strays
+    /// further than about `tolerance` from the geometry it approximates. Solids
+    /// with only flat faces are triangulated exactly, and other solids through the
+    /// surfaces of their faces. Curves are left out, since they have no area.
+    pub fn from_geometry(geometry: &Geometry, tolerance: f64) -> Self {
+        Self::from_geometry_with(geometry, tolerance, &Tolerance::DEFAULT)
+    }
+
+    /// Tessellates geometry like [`Self::from_geometry`], to the tolerances of a
+    /// model rather than the default ones
+    pub fn from_geometry_with(geometry: &Geometry, tolerance: f64, model: &Tolerance) -> Self {
+        let mut mesh = Self::new();
+        for (i, surface) in geometry.surfaces.iter().enumerate() {
+            mesh.parts.push(tessellate::surface(
+                &format!("surface {}", i + 1),
+                surface,
+                tolerance,
+                model,
+            ));
+        }
+        for (i, trimmed) in geometry.trimmed_surfaces.iter().enumerate() {
+            mesh.parts.push(tessellate::trimmed_surface(
+                &format!("trimmed surface {}", i + 1),
+                trimmed,
+                tolerance,
+                model,
+            ));
+        }
+        for (i, solid) in geometry.solids.iter().enumerate() {
+            let name = format!("solid {}", i + 1);
+            match TriMesh::from_solid_with(solid, model) {
+                Ok(triangles) => mesh.parts.push(MeshPart::from_tri_mesh(&name, &triangles)),
+                Err(_) => {
+                    let mut part = MeshPart::new(&name);
+                    for (id, trimmed) in face_surfaces(solid) {
+                        let mut face =
+                            tessellate::trimmed_surface(&name, &trimmed, tolerance, model);
+                        if !solid.face(id).unwrap().same_sense {
+                            face.flip();
+                        }
+                        part.append(&face);
+                    }
+                    mesh.parts.push(part);
+                }
+            }
+        }
+        mesh
+    }
+
+    pub fn num_triangles(&self) -> usize {
+        self.parts.iter().map(|part| part.triangles.len()).sum()
+    }
+
+    /// All triangles of all parts, with their facet normals
+    pub fn facets(&self) -> impl Iterator<Item = ([EVec3; 3], EVec3)> + '_ {
+        self.parts.iter().flat_map(|part| {
+            (0..part.triangles.len()).map(move |i| {
+                let points = part.triangle_points(i);
+                (points, facet_normal(points))
+            })
+        })
+    }
+}
+
+/// The unit normal of a triangle wound counterclockwise, or zero if it has no area
+fn facet_normal([a, b, c]: [EVec3; 3]) -> EVec3 {
+    let normal = (b - a).cross(&(c - a));
+    let magnitude = normal.magnitude();
+    if magnitude == 0.0 {
+        EVec3::zero()
+    } else {
+        normal / magnitude
+    }
+}
+
+pub fn write_stl_file(
+    path: impl AsRef<Path>,
+    mesh: &Mesh,
+    format: StlFormat,
+) -> ExchangeResult<()> {
+    let name = file_stem(path.as_ref());
+    Ok(std::fs::write(path, write_stl(mesh, &name, format))?)
+}
+
+/// Writes a mesh to an OBJ file, with its materials in an MTL file of the same
+/// name next to it
+pub fn write_obj_file(path: impl AsRef<Path>, mesh: &Mesh) -> ExchangeResult<()> {
+    let path = path.as_ref();
+    if mesh.materials.is_empty() {
+        return Ok(std::fs::write(path, write_obj(mesh, None))?);
+    }
+
+    let library = path.with_extension("mtl");
+    let library_name = library
+        .file_name()
+        .map(|name| name.to_string_lossy().into_owned())
+        .unwrap_or_default();
+    std::fs::write(&library, write_mtl(mesh))?;
+    Ok(std::fs::write(path, write_obj(mesh, Some(&library_name)))?)
+}
+
+pub fn write_ply_file(
+    path: impl AsRef<Path>,
+    mesh: &Mesh,
+    format: PlyFormat,
+) -> ExchangeResult<()> {
+    Ok(std::fs::write(path, write_ply(mesh, format))?)
+}
+
+/// Writes a mesh to a glTF file, as a binary `.glb` file if the path has that
+/// extension or as JSON with the buffer embedded otherwise
+pub fn write_gltf_file(path: impl AsRef<Path>, mesh: &Mesh) -> ExchangeResult<()> {
+    let path = path.as_ref();
+    let extension = path.extension().and_then(|extension| extension.to_str());
+    if matches!(extension, Some(extension) if extension.eq_ignore_ascii_case("glb")) {
+        Ok(std::fs::write(path, write_glb(mesh))?)
+    } else {
+        Ok(std::fs::write(path, write_gltf(mesh))?)
+    }
+}
+
+/// Reads a mesh from an OBJ or STL file, chosen by the path's extension
+pub fn read_mesh_file(path: impl AsRef<Path>) -> ExchangeResult<Mesh> {
+    let path = path.as_ref();
+    let extension = path
+        .extension()
+        .map(|extension| extension.to_string_lossy().to_lowercase())
+        .unwrap_or_default();
+    match extension.as_str() {
+        "obj" => read_obj_file(path),
+        "stl" => read_stl_file(path),
+        _ => Err(ExchangeError::Unsupported(format!(
+            "mesh files with extension '{extension}'"
+        ))),
+    }
+}
+
+pub fn read_stl_file(path: impl AsRef<Path>) -> ExchangeResult<Mesh> {
+    read_stl(&std::fs::read(path)?)
+}
+
+/// Reads a mesh from an OBJ file, with materials from the MTL files it names next
+/// to it. Material libraries that are missing are skipped, leaving the parts
+/// that use them without materials.
+pub fn read_obj_file(path: impl AsRef<Path>) -> ExchangeResult<Mesh> {
+    let path = path.as_ref();
+    let text = std::fs::read_to_string(path)?;
+
+    let mut materials = Vec::new();
+    for library in material_libraries(&text) {
+        match std::fs::read_to_string(path.with_file_name(library)) {
+            Ok(library) => materials.extend(read_mtl(&library)?),
+            Err(error) if error.kind() == std::io::ErrorKind::NotFound => {}
+            Err(error) => return Err(error.into()),
+        }
+    }
+    read_obj(&text, &materials)
+}
+
+fn file_stem(path: &Path) -> String {
+    path.file_stem()
+        .map(|stem| stem.to_string_lossy().into_owned())
+        .unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use space::{
+        hspace::{HSpace2, HSpace3},
+        EPlacement3, EVec2, EVec3, EVector, MassProperties,
+    };
+    use spline::{
+        nurbs_curve::NurbsCurve, nurbs_surface::NurbsSurface, trimmed_surface::TrimmedSurface,
+    };
+    use topology::solid::Solid;
+
+    use crate::geometry::Geometry;
+
+    use super::Mesh;
+
+    #[test]
+    fn tessellate_within_tolerance() {
+        let placement = EPlacement3::from_origin(EVec3::new(1.0, 2.0, 3.0));
+        let geometry = Geometry {
+            surfaces: vec![NurbsSurface::<HSpace3>::sphere(&placement, 2.0)],
+            solids: vec![Solid::block(&EPlacement3::default(), EVec3::new(1.0, 2.0, 3.0)).unwrap()],
+            ..Default::default()
+        };
+
+        for tolerance in [0.1, 0.01] {
+            let mesh = Mesh::from_geometry(&geometry, tolerance);
+            assert_eq!(mesh.parts.len(), 2);
+
+            // Triangle corners lie on the sphere, and their midpoints within the
+            // tolerance of it
+            let sphere = &mesh.parts[0];
+            for i in 0..sphere.triangles.len() {
+                let points = sphere.triangle_points(i);
+                let center = points.iter().copied().sum::<EVec3>() / 3.0;
+                for point in points {
+                    assert!(((point - placement.origin).magnitude() - 2.0).abs() <= 1e-9);
+                }
+                assert!(2.0 - (center - placement.origin).magnitude() <= tolerance);
+            }
+
+            let volume = |part: usize| {
+                MassProperties::from_triangles(
+                    mesh.parts[part]
+                        .triangles
+                        .iter()
+                        .enumerate()
+                        .map(|(i, _)| mesh.parts[part].triangle_points(i)),
+                    1.0,
+                )
+                .volume
+            };
+            let exact = 4.0 / 3.0 * std::f64::consts::PI * 8.0;
+            assert!(volume(0) < exact && volume(0) > exact * (1.0 - 3.0 * tolerance));
+            assert!((volume(1) - 6.0).abs() <= 1e-12);
+        }
+    }
+
+    #[test]
+    fn tessellate_trimmed_surface() {
+        let mut trimmed =
+            TrimmedSurface::new(NurbsSurface::rectangle(&EPlacement3::default(), 2.0, 1.0));
+        trimmed.inner.push(vec![NurbsCurve::<HSpace2>::arc(
+            EVec2::new(0.5, 0.5),
+            0.25,
+            0.0,
+            2.0 * std::f64::consts::PI,
+        )]);
+        let geometry = Geometry {
+            trimmed_surfaces: vec![trimmed],
+            ..Default::default()
+        };
+
+        // The hole is an ellipse once the parameter space is stretched to the
+        // rectangle, and is cut out up to the grid's resolution
+        let mesh = Mesh::from_geometry(&geometry, 0.001);
+        let part = &mesh.parts[0];
+        let area = (0..part.triangles.len())
+            .map(|i| {
+                let [a, b, c] = part.triangle_points(i);
+                (b - a).cross(&(c - a)).magnitude() / 2.0
+            })
+            .sum::<f64>();
+        let exact = 2.0 - std::f64::consts::PI * 0.5 * 0.25;
+        assert!((area - exact).abs() <= exact * 0.02);
+    }
+}
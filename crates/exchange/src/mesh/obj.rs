@@ -0,0 +1,388 @@
+use std::{collections::HashMap, fmt::Write};
+
+use space::{EVec3, EVector};
+
+use crate::error::{ExchangeError, ExchangeResult};
+
+use super::{Mesh, MeshMaterial, MeshPart, MeshVertex, CREASE_ANGLE};
+
+/// Writes a mesh as a Wavefront OBJ file, with each part as an object with its
+/// own vertex normals. If `material_library` names an MTL file, parts use the
+/// materials in it.
+pub fn write_obj(mesh: &Mesh, material_library: Option<&str>) -> String {
+    let mut text = String::new();
+    writeln!(text, "# cadit").unwrap();
+    if let Some(library) = material_library {
+        writeln!(text, "mtllib {library}").unwrap();
+    }
+
+    // Vertices are numbered from 1 across the whole file
+    let mut offset = 1;
+    for part in mesh.parts.iter() {
+        writeln!(text, "o {}", identifier(&part.name)).unwrap();
+        for vertex in part.vertices.iter() {
+            let p = vertex.position;
+            writeln!(text, "v {} {} {}", p.x, p.y, p.z).unwrap();
+        }
+        for vertex in part.vertices.iter() {
+            let n = vertex.normal;
+            writeln!(text, "vn {} {} {}", n.x, n.y, n.z).unwrap();
+        }
+        if let (Some(_), Some(material)) = (material_library, part.material) {
+            writeln!(
+                text,
+                "usemtl {}",
+                identifier(&mesh.materials[material].name)
+            )
+            .unwrap();
+        }
+        for triangle in part.triangles.iter() {
+            let [a, b, c] = triangle.map(|i| i + offset);
+            writeln!(text, "f {a}//{a} {b}//{b} {c}//{c}").unwrap();
+        }
+        offset += part.vertices.len();
+    }
+    text
+}
+
+/// Writes the materials of a mesh as an MTL file for [`write_obj`], with
+/// roughness both as the PBR extension's `Pr` and as a specular exponent
+pub fn write_mtl(mesh: &Mesh) -> String {
+    let mut text = String::new();
+    writeln!(text, "# cadit").unwrap();
+    for material in mesh.materials.iter() {
+        let [r, g, b, a] = material.color;
+        writeln!(text, "newmtl {}", identifier(&material.name)).unwrap();
+        writeln!(text, "Kd {r} {g} {b}").unwrap();
+        writeln!(text, "d {a}").unwrap();
+        writeln!(text, "Pr {}", material.roughness).unwrap();
+        writeln!(text, "Ns {}", (1.0 - material.roughness) * 1000.0).unwrap();
+    }
+    text
+}
+
+/// Reads a Wavefront OBJ file. Objects, groups and changes of material start new
+/// parts, and faces with more than three corners are split into fans of
+/// triangles. Parts with any face missing normals have their normals computed,
+/// keeping creases sharper than [`CREASE_ANGLE`]. Materials are looked up by name
+/// in `materials`, which become the materials of the mesh.
+pub fn read_obj(text: &str, materials: &[MeshMaterial]) -> ExchangeResult<Mesh> {
+    let mut positions = Vec::new();
+    let mut normals = Vec::new();
+    let mut builder = PartBuilder::new("");
+    let mut mesh = Mesh {
+        parts: Vec::new(),
+        materials: materials.to_vec(),
+    };
+
+    for (number, line) in text.lines().enumerate() {
+        let line_number = number + 1;
+        let line = line.split('#').next().unwrap_or_default().trim();
+        let (keyword, rest) = line.split_once(char::is_whitespace).unwrap_or((line, ""));
+        let rest = rest.trim();
+
+        match keyword {
+            "v" => {
+                let p = numbers(rest, 3, line_number)?;
+                positions.push(EVec3::new(p[0], p[1], p[2]));
+            }
+            "vn" => {
+                let n = numbers(rest, 3, line_number)?;
+                normals.push(EVec3::new(n[0], n[1], n[2]));
+            }
+            "o" | "g" => {
+                let material = builder.part.material;
+                builder.finish(&mut mesh);
+                builder = PartBuilder::new(rest);
+                builder.part.material = material;
+            }
+            "usemtl" => {
+                let material = mesh.materials.iter().position(|m| m.name == rest);
+                if material != builder.part.material {
+                    let name = builder.part.name.clone();
+                    builder.finish(&mut mesh);
+                    builder = PartBuilder::new(&name);
+                    builder.part.material = material;
+                }
+            }
+            "f" => {
+                let corners = rest
+                    .split_whitespace()
+                    .map(|corner| {
+                        let mut indices = corner.split('/');
+                        let position = indices.next().unwrap_or_default();
+                        let normal = indices.nth(1).filter(|normal| !normal.is_empty());
+                        Ok((
+                            index(position, positions.len(), line_number)?,
+                            normal
+                                .map(|normal| index(normal, normals.len(), line_number))
+                                .transpose()?,
+                        ))
+                    })
+                    .collect::<ExchangeResult<Vec<_>>>()?;
+                if corners.len() < 3 {
+                    return Err(ExchangeError::Syntax {
+                        line: line_number,
+                        message: "a face needs at least three corners".to_string(),
+                    });
+                }
+
+                let corners = corners
+                    .into_iter()
+                    .map(|(position, normal)| {
+                        builder.vertex(&positions, &normals, position, normal)
+                    })
+                    .collect::<Vec<_>>();
+                for i in 1..corners.len() - 1 {
+                    builder
+                        .part
+                        .triangles
+                        .push([corners[0], corners[i], corners[i + 1]]);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    builder.finish(&mut mesh);
+    Ok(mesh)
+}
+
+/// The names of the material libraries an OBJ file uses
+pub fn material_libraries(text: &str) -> Vec<String> {
+    text.lines()
+        .filter_map(|line| line.trim().strip_prefix("mtllib "))
+        .flat_map(|names| names.split_whitespace())
+        .map(String::from)
+        .collect()
+}
+
+/// Reads the materials of an MTL file. The diffuse color and dissolve become the
+/// base color, and roughness comes from the PBR extension's `Pr` if it is given
+/// or from the specular exponent otherwise.
+pub fn read_mtl(text: &str) -> ExchangeResult<Vec<MeshMaterial>> {
+    let mut materials = Vec::<MeshMaterial>::new();
+    let mut has_roughness = false;
+
+    for (number, line) in text.lines().enumerate() {
+        let line_number = number + 1;
+        let line = line.split('#').next().unwrap_or_default().trim();
+        let (keyword, rest) = line.split_once(char::is_whitespace).unwrap_or((line, ""));
+        let rest = rest.trim();
+
+        if keyword == "newmtl" {
+            materials.push(MeshMaterial::new(rest, [0.8, 0.8, 0.8, 1.0], 0.5));
+            has_roughness = false;
+            continue;
+        }
+        let Some(material) = materials.last_mut() else {
+            continue;
+        };
+        match keyword {
+            "Kd" => {
+                let color = numbers(rest, 3, line_number)?;
+                for (channel, value) in material.color.iter_mut().zip(color) {
+                    *channel = value as f32;
+                }
+            }
+            "d" => material.color[3] = numbers(rest, 1, line_number)?[0] as f32,
+            "Tr" => material.color[3] = 1.0 - numbers(rest, 1, line_number)?[0] as f32,
+            "Pr" => {
+                material.roughness = numbers(rest, 1, line_number)?[0] as f32;
+                has_roughness = true;
+            }
+            "Ns" if !has_roughness => {
+                let exponent = numbers(rest, 1, line_number)?[0];
+                material.roughness = (1.0 - exponent / 1000.0).clamp(0.0, 1.0) as f32;
+            }
+            _ => {}
+        }
+    }
+
+    Ok(materials)
+}
+
+/// Collects the vertices and triangles of the part being read
+struct PartBuilder {
+    part: MeshPart,
+
+    /// Part vertices by the indices of their position and normal in the file
+    vertices: HashMap<(usize, Option<usize>), usize>,
+    missing_normals: bool,
+}
+impl PartBuilder {
+    fn new(name: &str) -> Self {
+        Self {
+            part: MeshPart::new(name),
+            vertices: HashMap::new(),
+            missing_normals: false,
+        }
+    }
+
+    fn vertex(
+        &mut self,
+        positions: &[EVec3],
+        normals: &[EVec3],
+        position: usize,
+        normal: Option<usize>,
+    ) -> usize {
+        self.missing_normals |= normal.is_none();
+        let part = &mut self.part;
+        *self.vertices.entry((position, normal)).or_insert_with(|| {
+            let normal = normal.map_or_else(EVec3::zero, |normal| normals[normal]);
+            part.vertices
+                .push(MeshVertex::new(positions[position], normal));
+            part.vertices.len() - 1
+        })
+    }
+
+    /// Adds the part to the mesh if it has any triangles
+    fn finish(self, mesh: &mut Mesh) {
+        let mut part = self.part;
+        if part.triangles.is_empty() {
+            return;
+        }
+        if self.missing_normals {
+            part.compute_normals(CREASE_ANGLE);
+        }
+        mesh.parts.push(part);
+    }
+}
+
+/// Reads at least `count` numbers, ignoring any after them
+fn numbers(text: &str, count: usize, line: usize) -> ExchangeResult<Vec<f64>> {
+    let values = text
+        .split_whitespace()
+        .take(count)
+        .map(|value| value.parse::<f64>())
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|_| ExchangeError::Syntax {
+            line,
+            message: format!("expected {count} numbers"),
+        })?;
+    if values.len() < count {
+        return Err(ExchangeError::Syntax {
+            line,
+            message: format!("expected {count} numbers"),
+        });
+    }
+    Ok(values)
+}
+
+/// Resolves a one-based index, or a negative one counting back from the end of
+/// the `count` elements read so far
+fn index(text: &str, count: usize, line: usize) -> ExchangeResult<usize> {
+    let invalid = || ExchangeError::Syntax {
+        line,
+        message: format!("'{text}' is not a valid index"),
+    };
+    let index = text.parse::<i64>().map_err(|_| invalid())?;
+    let resolved = if index < 0 {
+        count as i64 + index
+    } else {
+        index - 1
+    };
+    if resolved < 0 || resolved >= count as i64 {
+        return Err(invalid());
+    }
+    Ok(resolved as usize)
+}
+
+/// Names in OBJ and MTL files end at whitespace
+fn identifier(name: &str) -> String {
+    let name = name
+        .chars()
+        .map(|c| if c.is_whitespace() { '_' } else { c })
+        .collect::<String>();
+    if name.is_empty() {
+        "unnamed".to_string()
+    } else {
+        name
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use space::{EVec3, EVector};
+
+    use crate::mesh::{Mesh, MeshMaterial, MeshPart, MeshVertex};
+
+    use super::{material_libraries, read_mtl, read_obj, write_mtl, write_obj};
+
+    #[test]
+    fn read_faces_and_materials() {
+        let materials = read_mtl(
+            "newmtl Red paint
+Kd 1 0 0
+Ns 250
+newmtl Glass
+Kd 0.5 0.5 1.0
+d 0.25
+Pr 0.1
+Ns 900
+",
+        )
+        .unwrap();
+        assert_eq!(materials.len(), 2);
+        assert_eq!(materials[0].name, "Red paint");
+        assert_eq!(materials[0].roughness, 0.75);
+        assert_eq!(materials[1].color, [0.5, 0.5, 1.0, 0.25]);
+        assert_eq!(materials[1].roughness, 0.1);
+
+        // A quad with normals, then a triangle without that refers back to the
+        // quad's corners
+        let text = "mtllib parts.mtl
+v 0 0 0
+v 1 0 0
+v 1 1 0
+v 0 1 0
+vn 0 0 1
+o plate
+usemtl Glass
+f 1//1 2//1 3//1 4//1
+usemtl Missing
+f -4 -3 3/1
+";
+        assert_eq!(material_libraries(text), vec!["parts.mtl".to_string()]);
+        let mesh = read_obj(text, &materials).unwrap();
+        assert_eq!(mesh.parts.len(), 2);
+
+        let quad = &mesh.parts[0];
+        assert_eq!((quad.name.as_str(), quad.material), ("plate", Some(1)));
+        assert_eq!(quad.triangles, vec![[0, 1, 2], [0, 2, 3]]);
+        assert_eq!(quad.vertices.len(), 4);
+
+        let triangle = &mesh.parts[1];
+        assert_eq!(triangle.material, None);
+        assert_eq!(triangle.triangles.len(), 1);
+        assert!(triangle
+            .vertices
+            .iter()
+            .all(|vertex| (vertex.normal - EVec3::new(0.0, 0.0, 1.0)).magnitude() <= 1e-12));
+
+        assert!(read_obj("v 0 0 0\nf 1 2 3", &[]).is_err());
+    }
+
+    #[test]
+    fn round_trip() {
+        let mut part = MeshPart::new("a part");
+        part.vertices = [(0.0, 0.0), (1.0, 0.0), (0.0, 1.0)]
+            .iter()
+            .map(|(x, y)| MeshVertex::new(EVec3::new(*x, *y, 0.5), EVec3::new(0.0, 0.0, 1.0)))
+            .collect();
+        part.triangles = vec![[0, 1, 2]];
+        part.material = Some(0);
+        let mesh = Mesh {
+            parts: vec![part],
+            materials: vec![MeshMaterial::new("shiny blue", [0.0, 0.0, 1.0, 1.0], 0.2)],
+        };
+
+        let materials = read_mtl(&write_mtl(&mesh)).unwrap();
+        let read = read_obj(&write_obj(&mesh, Some("part.mtl")), &materials).unwrap();
+        assert_eq!(read.materials[0].roughness, 0.2);
+        assert_eq!(read.parts[0].name, "a_part");
+        assert_eq!(read.parts[0].material, Some(0));
+        assert_eq!(read.parts[0].vertices, mesh.parts[0].vertices);
+        assert_eq!(read.parts[0].triangles, mesh.parts[0].triangles);
+    }
+}
@@ -0,0 +1,87 @@
+use std::fmt::Write;
+
+use space::EVector;
+
+use super::Mesh;
+
+/// The encoding of a PLY file
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PlyFormat {
+    Ascii,
+    BinaryLittleEndian,
+}
+
+/// Writes all parts of a mesh as one PLY mesh, with vertex normals and vertex
+/// colors taken from the parts' materials. Parts without a material are white.
+pub fn write_ply(mesh: &Mesh, format: PlyFormat) -> Vec<u8> {
+    let num_vertices = mesh
+        .parts
+        .iter()
+        .map(|part| part.vertices.len())
+        .sum::<usize>();
+
+    let mut header = String::new();
+    writeln!(header, "ply").unwrap();
+    match format {
+        PlyFormat::Ascii => writeln!(header, "format ascii 1.0").unwrap(),
+        PlyFormat::BinaryLittleEndian => {
+            writeln!(header, "format binary_little_endian 1.0").unwrap()
+        }
+    }
+    writeln!(header, "comment cadit").unwrap();
+    writeln!(header, "element vertex {num_vertices}").unwrap();
+    for property in ["x", "y", "z", "nx", "ny", "nz"] {
+        writeln!(header, "property float {property}").unwrap();
+    }
+    for property in ["red", "green", "blue", "alpha"] {
+        writeln!(header, "property uchar {property}").unwrap();
+    }
+    writeln!(header, "element face {}", mesh.num_triangles()).unwrap();
+    writeln!(header, "property list uchar uint vertex_indices").unwrap();
+    writeln!(header, "end_header").unwrap();
+
+    let mut ascii = String::new();
+    let mut bytes = header.into_bytes();
+    for part in mesh.parts.iter() {
+        let color = part
+            .material
+            .map_or([1.0; 4], |material| mesh.materials[material].color)
+            .map(|c| (c.clamp(0.0, 1.0) * 255.0).round() as u8);
+        for vertex in part.vertices.iter() {
+            let [x, y, z] = vertex.position.f32s();
+            let [nx, ny, nz] = vertex.normal.f32s();
+            match format {
+                PlyFormat::Ascii => {
+                    let [r, g, b, a] = color;
+                    writeln!(ascii, "{x} {y} {z} {nx} {ny} {nz} {r} {g} {b} {a}").unwrap()
+                }
+                PlyFormat::BinaryLittleEndian => {
+                    for value in [x, y, z, nx, ny, nz] {
+                        bytes.extend(value.to_le_bytes());
+                    }
+                    bytes.extend(color);
+                }
+            }
+        }
+    }
+
+    let mut offset = 0;
+    for part in mesh.parts.iter() {
+        for triangle in part.triangles.iter() {
+            let [a, b, c] = triangle.map(|i| (i + offset) as u32);
+            match format {
+                PlyFormat::Ascii => writeln!(ascii, "3 {a} {b} {c}").unwrap(),
+                PlyFormat::BinaryLittleEndian => {
+                    bytes.push(3);
+                    for index in [a, b, c] {
+                        bytes.extend(index.to_le_bytes());
+                    }
+                }
+            }
+        }
+        offset += part.vertices.len();
+    }
+
+    bytes.extend(ascii.into_bytes());
+    bytes
+}
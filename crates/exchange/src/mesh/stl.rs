@@ -0,0 +1,215 @@
+use std::{collections::HashMap, fmt::Write};
+
+use space::{EVec3, EVector};
+
+use crate::error::{ExchangeError, ExchangeResult};
+
+use super::{Mesh, MeshPart, MeshVertex, CREASE_ANGLE};
+
+/// The encoding of an STL file
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StlFormat {
+    Ascii,
+    Binary,
+}
+
+/// Writes all triangles of a mesh as one STL solid called `name`. STL has no
+/// units, materials or vertex normals, so only facet normals are written.
+pub fn write_stl(mesh: &Mesh, name: &str, format: StlFormat) -> Vec<u8> {
+    match format {
+        StlFormat::Ascii => ascii(mesh, name).into_bytes(),
+        StlFormat::Binary => binary(mesh, name),
+    }
+}
+
+fn ascii(mesh: &Mesh, name: &str) -> String {
+    // The name runs to the end of the line, so it can't contain line breaks
+    let name = name.replace(['\r', '\n'], " ");
+    let mut text = String::new();
+    writeln!(text, "solid {name}").unwrap();
+    for (points, normal) in mesh.facets() {
+        let [nx, ny, nz] = normal.f32s();
+        writeln!(text, "  facet normal {nx:e} {ny:e} {nz:e}").unwrap();
+        writeln!(text, "    outer loop").unwrap();
+        for point in points {
+            let [x, y, z] = point.f32s();
+            writeln!(text, "      vertex {x:e} {y:e} {z:e}").unwrap();
+        }
+        writeln!(text, "    endloop").unwrap();
+        writeln!(text, "  endfacet").unwrap();
+    }
+    writeln!(text, "endsolid {name}").unwrap();
+    text
+}
+
+fn binary(mesh: &Mesh, name: &str) -> Vec<u8> {
+    // An 80-byte header holding the name, which must not start with "solid" or
+    // readers may take the file for ASCII
+    let mut header = [b' '; 80];
+    let text = if name.starts_with("solid") {
+        format!(" {name}")
+    } else {
+        name.to_string()
+    };
+    for (byte, source) in header.iter_mut().zip(text.bytes()) {
+        *byte = source;
+    }
+
+    let mut bytes = header.to_vec();
+    bytes.extend((mesh.num_triangles() as u32).to_le_bytes());
+    for (points, normal) in mesh.facets() {
+        for vector in std::iter::once(normal).chain(points) {
+            for value in vector.f32s() {
+                bytes.extend(value.to_le_bytes());
+            }
+        }
+
+        // Attribute byte count, which has no standard use
+        bytes.extend(0u16.to_le_bytes());
+    }
+    bytes
+}
+
+/// Reads an STL file in either encoding as a mesh with a single part. Facets are
+/// stitched together where their corners coincide exactly, and vertex normals
+/// are computed from the facets, keeping creases sharper than [`CREASE_ANGLE`].
+pub fn read_stl(bytes: &[u8]) -> ExchangeResult<Mesh> {
+    let (name, facets) = if is_binary(bytes) {
+        read_binary(bytes)
+    } else {
+        read_ascii(bytes)?
+    };
+
+    let mut part = MeshPart::new(&name);
+    let mut vertices = HashMap::new();
+    for facet in facets {
+        part.triangles.push(facet.map(|point| {
+            *vertices
+                .entry([point.x, point.y, point.z].map(f64::to_bits))
+                .or_insert_with(|| {
+                    part.vertices.push(MeshVertex::new(point, EVec3::zero()));
+                    part.vertices.len() - 1
+                })
+        }));
+    }
+    part.compute_normals(CREASE_ANGLE);
+
+    Ok(Mesh {
+        parts: vec![part],
+        materials: Vec::new(),
+    })
+}
+
+/// Binary files are recognized by their length matching the number of facets in
+/// their header, since some binary files also start with "solid"
+fn is_binary(bytes: &[u8]) -> bool {
+    if bytes.len() < 84 {
+        return false;
+    }
+    let count = u32::from_le_bytes([bytes[80], bytes[81], bytes[82], bytes[83]]) as usize;
+    bytes.len() == 84 + 50 * count
+}
+
+fn read_binary(bytes: &[u8]) -> (String, Vec<[EVec3; 3]>) {
+    let name = String::from_utf8_lossy(&bytes[..80])
+        .trim_matches(|c| c == '\0' || c == ' ')
+        .to_string();
+    let facets = bytes[84..]
+        .chunks_exact(50)
+        .map(|facet| {
+            let value = |i: usize| {
+                let at = 12 + 4 * i;
+                f32::from_le_bytes([facet[at], facet[at + 1], facet[at + 2], facet[at + 3]]) as f64
+            };
+            [0, 1, 2].map(|corner| {
+                EVec3::new(
+                    value(3 * corner),
+                    value(3 * corner + 1),
+                    value(3 * corner + 2),
+                )
+            })
+        })
+        .collect();
+    (name, facets)
+}
+
+fn read_ascii(bytes: &[u8]) -> ExchangeResult<(String, Vec<[EVec3; 3]>)> {
+    let text = std::str::from_utf8(bytes).map_err(|_| ExchangeError::Syntax {
+        line: 1,
+        message: "the file is neither binary STL nor text".to_string(),
+    })?;
+
+    let mut name = String::new();
+    let mut facets = Vec::new();
+    let mut corners = Vec::new();
+    for (number, line) in text.lines().enumerate() {
+        let line = line.trim();
+        let syntax = |message: &str| ExchangeError::Syntax {
+            line: number + 1,
+            message: message.to_string(),
+        };
+
+        if let Some(rest) = line.strip_prefix("solid") {
+            name = rest.trim().to_string();
+        } else if let Some(rest) = line.strip_prefix("vertex") {
+            let values = rest
+                .split_whitespace()
+                .map(|value| value.parse::<f64>())
+                .collect::<Result<Vec<_>, _>>()
+                .map_err(|_| syntax("a vertex needs three numbers"))?;
+            let [x, y, z] = values[..] else {
+                return Err(syntax("a vertex needs three numbers"));
+            };
+            corners.push(EVec3::new(x, y, z));
+        } else if line.starts_with("endloop") {
+            let [a, b, c] = corners[..] else {
+                return Err(syntax("a facet needs three vertices"));
+            };
+            facets.push([a, b, c]);
+            corners.clear();
+        }
+    }
+    Ok((name, facets))
+}
+
+#[cfg(test)]
+mod tests {
+    use space::{EPlacement3, EVec3};
+    use topology::{mesh::TriMesh, solid::Solid};
+
+    use crate::mesh::{Mesh, MeshPart};
+
+    use super::{read_stl, write_stl, StlFormat};
+
+    #[test]
+    fn ascii_and_binary() {
+        let block = Solid::block(&EPlacement3::default(), EVec3::new(1.0, 2.0, 3.0)).unwrap();
+        let mesh = Mesh {
+            parts: vec![MeshPart::from_tri_mesh(
+                "block",
+                &TriMesh::from_solid(&block).unwrap(),
+            )],
+            materials: Vec::new(),
+        };
+
+        let binary = write_stl(&mesh, "block", StlFormat::Binary);
+        assert_eq!(binary.len(), 84 + 50 * 12);
+        assert_eq!(u32::from_le_bytes(binary[80..84].try_into().unwrap()), 12);
+        assert!(!binary.starts_with(b"solid"));
+
+        let ascii = String::from_utf8(write_stl(&mesh, "block", StlFormat::Ascii)).unwrap();
+        assert!(ascii.starts_with("solid block\n") && ascii.ends_with("endsolid block\n"));
+        assert_eq!(ascii.matches("facet normal").count(), 12);
+        assert_eq!(ascii.matches("vertex").count(), 36);
+
+        // Both encodings read back as the block's eight corners, with a normal
+        // for each face at each corner
+        for bytes in [binary, ascii.into_bytes()] {
+            let read = read_stl(&bytes).unwrap();
+            let part = &read.parts[0];
+            assert_eq!(part.name, "block");
+            assert_eq!(part.triangles.len(), 12);
+            assert_eq!(part.vertices.len(), 24);
+        }
+    }
+}
@@ -0,0 +1,254 @@
+use space::{
+    hspace::{HSpace2, HSpace3},
+    EVec2, EVec3, EVector, Tolerance,
+};
+use spline::{
+    nurbs_curve::NurbsCurve, nurbs_surface::NurbsSurface, trimmed_surface::TrimmedSurface,
+};
+
+use super::{MeshPart, MeshVertex};
+
+/// Most pieces a knot span is split into along either direction
+const MAX_DIVISIONS: usize = 256;
+
+/// Fractions of a piece at which its distance from its chord is checked
+const CHECKS: [f64; 3] = [0.25, 0.5, 0.75];
+
+/// Fraction of the way towards the middle of the domain that a degenerate
+/// point's parameters are moved to find a normal near it
+const NORMAL_NUDGE: f64 = 1e-3;
+
+/// Samples a surface on a grid fine enough that chords between neighboring
+/// samples stay within `tolerance` of it along both directions. Samples within
+/// the model's linear tolerance of each other are treated as the same.
+pub(crate) fn surface(
+    name: &str,
+    surface: &NurbsSurface<HSpace3>,
+    tolerance: f64,
+    model: &Tolerance,
+) -> MeshPart {
+    let (us, vs) = grid(surface, tolerance, &[], model);
+    triangulate(name, surface, &us, &vs, model, |_| true)
+}
+
+/// Samples a trimmed surface like an untrimmed one, keeping the triangles whose
+/// middles lie inside its boundaries. Grid lines also pass through the sampled
+/// boundary points, so boundaries are followed to the resolution of the grid.
+pub(crate) fn trimmed_surface(
+    name: &str,
+    trimmed: &TrimmedSurface,
+    tolerance: f64,
+    model: &Tolerance,
+) -> MeshPart {
+    let surface = &trimmed.surface;
+    let polygon = |curves: &Vec<NurbsCurve<HSpace2>>| boundary(surface, curves, tolerance);
+    let outer = trimmed.outer.as_ref().map(polygon);
+    let inner = trimmed.inner.iter().map(polygon).collect::<Vec<_>>();
+
+    let boundary = outer
+        .iter()
+        .chain(inner.iter())
+        .flatten()
+        .copied()
+        .collect::<Vec<_>>();
+    let (us, vs) = grid(surface, tolerance, &boundary, model);
+    triangulate(name, surface, &us, &vs, model, |uv| {
+        let in_outer = match &outer {
+            Some(outer) => contains(outer, uv),
+            None => true,
+        };
+        in_outer && !inner.iter().any(|inner| contains(inner, uv))
+    })
+}
+
+/// Samples a loop of curves in the parameter space of a surface into a polygon
+/// whose image on the surface stays within `tolerance` of the loop's
+fn boundary(
+    surface: &NurbsSurface<HSpace3>,
+    curves: &[NurbsCurve<HSpace2>],
+    tolerance: f64,
+) -> Vec<EVec2> {
+    let mut points = Vec::new();
+    for curve in curves {
+        let knots = curve
+            .knot_vector()
+            .distinct_knots(curve.min_u(), curve.max_u());
+        let model = |t: f64| {
+            let uv = curve.point(t);
+            surface.point(uv.x, uv.y)
+        };
+        points.extend(
+            parameters(&knots, &[0.0], |t, _| model(t), tolerance)
+                .into_iter()
+                .map(|t| curve.point(t)),
+        );
+    }
+    points
+}
+
+/// Parameters of the grid lines of a surface in each direction, including those
+/// through `extra` points in its parameter space
+fn grid(
+    surface: &NurbsSurface<HSpace3>,
+    tolerance: f64,
+    extra: &[EVec2],
+    model: &Tolerance,
+) -> (Vec<f64>, Vec<f64>) {
+    let (knots_u, knots_v) = (surface.distinct_knots_u(), surface.distinct_knots_v());
+    let mut us = parameters(
+        &knots_u,
+        &samples(&knots_v),
+        |u, v| surface.point(u, v),
+        tolerance,
+    );
+    let mut vs = parameters(
+        &knots_v,
+        &samples(&knots_u),
+        |v, u| surface.point(u, v),
+        tolerance,
+    );
+
+    us.extend(extra.iter().map(|uv| uv.x));
+    vs.extend(extra.iter().map(|uv| uv.y));
+    for (params, min, max) in [
+        (&mut us, surface.min_u(), surface.max_u()),
+        (&mut vs, surface.min_v(), surface.max_v()),
+    ] {
+        params.retain(|t| *t >= min && *t <= max);
+        params.sort_by(|a, b| a.total_cmp(b));
+        params.dedup_by(|a, b| (*a - *b).abs() <= model.linear);
+    }
+    (us, vs)
+}
+
+/// Parameters that split each span between `knots` into equal pieces, enough
+/// for every curve `point(t, s)` with `s` in `curves` to stay within `tolerance`
+/// of its chords
+fn parameters(
+    knots: &[f64],
+    curves: &[f64],
+    point: impl Fn(f64, f64) -> EVec3,
+    tolerance: f64,
+) -> Vec<f64> {
+    let mut params = knots[..1].to_vec();
+    for span in knots.windows(2) {
+        let count = curves
+            .iter()
+            .map(|s| divisions(|t| point(t, *s), span[0], span[1], tolerance))
+            .max()
+            .unwrap_or(1);
+        params.extend((1..=count).map(|i| span[0] + (span[1] - span[0]) * i as f64 / count as f64));
+    }
+    params
+}
+
+/// The number of equal pieces `[a, b]` must be split into for the curve to stay
+/// within `tolerance` of the chords between their ends
+fn divisions(point: impl Fn(f64) -> EVec3, a: f64, b: f64, tolerance: f64) -> usize {
+    let mut count = 1;
+    while count < MAX_DIVISIONS {
+        let step = (b - a) / count as f64;
+        let flat = (0..count).all(|i| {
+            let t0 = a + step * i as f64;
+            let (p0, p1) = (point(t0), point(t0 + step));
+            CHECKS
+                .iter()
+                .all(|s| (point(t0 + step * s) - (p0 + (p1 - p0) * *s)).magnitude() <= tolerance)
+        });
+        if flat {
+            break;
+        }
+        count *= 2;
+    }
+    count
+}
+
+/// Knots and the middles of the spans between them, where isoparametric curves
+/// are checked against the tolerance
+fn samples(knots: &[f64]) -> Vec<f64> {
+    let mut samples = knots.to_vec();
+    samples.extend(knots.windows(2).map(|span| (span[0] + span[1]) / 2.0));
+    samples
+}
+
+/// Triangulates a grid of samples, keeping triangles whose middles in parameter
+/// space pass `keep` and dropping those collapsed at degenerate points
+fn triangulate(
+    name: &str,
+    surface: &NurbsSurface<HSpace3>,
+    us: &[f64],
+    vs: &[f64],
+    model: &Tolerance,
+    keep: impl Fn(EVec2) -> bool,
+) -> MeshPart {
+    let middle = EVec2::new(
+        (surface.min_u() + surface.max_u()) / 2.0,
+        (surface.min_v() + surface.max_v()) / 2.0,
+    );
+    let params = us
+        .iter()
+        .flat_map(|u| vs.iter().map(move |v| EVec2::new(*u, *v)))
+        .collect::<Vec<_>>();
+    let vertices = params
+        .iter()
+        .map(|uv| {
+            let normal = surface
+                .normal(uv.x, uv.y)
+                .or_else(|| {
+                    let nudged = *uv + (middle - *uv) * NORMAL_NUDGE;
+                    surface.normal(nudged.x, nudged.y)
+                })
+                .unwrap_or_else(EVec3::zero);
+            MeshVertex::new(surface.point(uv.x, uv.y), normal)
+        })
+        .collect::<Vec<_>>();
+
+    // Triangles wind counterclockwise around the surface normal, which is the
+    // cross product of the u and v derivatives
+    let mut triangles = Vec::new();
+    let index = |i: usize, j: usize| i * vs.len() + j;
+    for i in 0..us.len().saturating_sub(1) {
+        for j in 0..vs.len().saturating_sub(1) {
+            let (p00, p10) = (index(i, j), index(i + 1, j));
+            let (p01, p11) = (index(i, j + 1), index(i + 1, j + 1));
+            for triangle in [[p00, p10, p11], [p00, p11, p01]] {
+                let [a, b, c] = triangle.map(|k| vertices[k].position);
+                let collapsed = (a - b).magnitude() <= model.linear
+                    || (b - c).magnitude() <= model.linear
+                    || (c - a).magnitude() <= model.linear;
+                let center = triangle.iter().map(|k| params[*k]).sum::<EVec2>() / 3.0;
+                if !collapsed && keep(center) {
+                    triangles.push(triangle);
+                }
+            }
+        }
+    }
+
+    // Only keep the vertices of triangles that were kept
+    let mut part = MeshPart::new(name);
+    let mut remap = vec![usize::MAX; vertices.len()];
+    for triangle in triangles.iter() {
+        part.triangles.push(triangle.map(|k| {
+            if remap[k] == usize::MAX {
+                remap[k] = part.vertices.len();
+                part.vertices.push(vertices[k]);
+            }
+            remap[k]
+        }));
+    }
+    part
+}
+
+/// Whether a point lies inside a closed polygon, by the even-odd rule
+fn contains(polygon: &[EVec2], point: EVec2) -> bool {
+    let mut inside = false;
+    for (a, b) in polygon.iter().zip(polygon.iter().cycle().skip(1)) {
+        if (a.y > point.y) != (b.y > point.y) {
+            let x = a.x + (point.y - a.y) / (b.y - a.y) * (b.x - a.x);
+            if point.x < x {
+                inside = !inside;
+            }
+        }
+    }
+    inside
+}
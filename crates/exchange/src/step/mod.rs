@@ -0,0 +1,241 @@
+//! Reading and writing STEP files (ISO 10303-21) using the AP203 and AP214
+//! schemas. Solids are exchanged as manifold solid B-reps whose faces lie on
+//! planes or B-spline surfaces, and whose edges are lines, circles or B-spline
+//! curves.
+
+mod parser;
+mod reader;
+mod writer;
+
+use std::path::Path;
+
+pub use parser::{Parameter, Record, StepFile};
+
+use crate::{error::ExchangeResult, geometry::Geometry};
+
+/// The application protocol a file is written for
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Schema {
+    /// Configuration controlled 3D design (AP203)
+    Ap203,
+
+    /// Automotive mechanical design (AP214)
+    Ap214,
+}
+impl Schema {
+    /// The schema name written in a file's header
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::Ap203 => "CONFIG_CONTROL_DESIGN",
+            Self::Ap214 => "AUTOMOTIVE_DESIGN { 1 0 10303 214 1 1 1 1 }",
+        }
+    }
+}
+
+pub fn read_step(text: &str) -> ExchangeResult<Geometry> {
+    reader::read(&StepFile::parse(text)?)
+}
+
+/// Writes geometry as a single product called `name`
+pub fn write_step(geometry: &Geometry, schema: Schema, name: &str) -> String {
+    writer::write(geometry, schema, name)
+}
+
+pub fn read_step_file(path: impl AsRef<Path>) -> ExchangeResult<Geometry> {
+    read_step(&std::fs::read_to_string(path)?)
+}
+
+pub fn write_step_file(
+    path: impl AsRef<Path>,
+    geometry: &Geometry,
+    schema: Schema,
+    name: &str,
+) -> ExchangeResult<()> {
+    Ok(std::fs::write(path, write_step(geometry, schema, name))?)
+}
+
+#[cfg(test)]
+mod tests {
+    use space::{hspace::HSpace3, EPlacement3, EVec3, EVector, MassProperties};
+    use spline::{nurbs_curve::NurbsCurve, nurbs_surface::NurbsSurface};
+    use topology::{mesh::TriMesh, solid::Solid};
+
+    use crate::geometry::Geometry;
+
+    use super::{read_step, write_step, Schema};
+
+    fn volume(solid: &Solid) -> f64 {
+        let mesh = TriMesh::from_solid(solid).unwrap();
+        MassProperties::from_triangles(
+            (0..mesh.triangles.len()).map(|i| mesh.triangle_points(i)),
+            1.0,
+        )
+        .volume
+    }
+
+    /// A tetrahedron with unit legs, in a file that doesn't give its units
+    const TETRAHEDRON: &str = "ISO-10303-21;
+HEADER;
+FILE_SCHEMA(('AUTOMOTIVE_DESIGN'));
+ENDSEC;
+DATA;
+#1=CARTESIAN_POINT('',(0.,0.,0.));
+#2=CARTESIAN_POINT('',(1.,0.,0.));
+#3=CARTESIAN_POINT('',(0.,1.,0.));
+#4=CARTESIAN_POINT('',(0.,0.,1.));
+#5=VERTEX_POINT('',#1);
+#6=VERTEX_POINT('',#2);
+#7=VERTEX_POINT('',#3);
+#8=VERTEX_POINT('',#4);
+#9=DIRECTION('',(0.,0.,1.));
+#10=VECTOR('',#9,1.);
+#11=LINE('',#1,#10);
+#12=EDGE_CURVE('',#5,#6,#11,.T.);
+#13=EDGE_CURVE('',#5,#7,#11,.T.);
+#14=EDGE_CURVE('',#5,#8,#11,.T.);
+#15=EDGE_CURVE('',#6,#7,#11,.T.);
+#16=EDGE_CURVE('',#6,#8,#11,.T.);
+#17=EDGE_CURVE('',#7,#8,#11,.T.);
+#18=AXIS2_PLACEMENT_3D('',#1,#9,$);
+#19=PLANE('',#18);
+#20=EDGE_LOOP('',(#21,#22,#23));
+#21=ORIENTED_EDGE('',*,*,#13,.T.);
+#22=ORIENTED_EDGE('',*,*,#15,.F.);
+#23=ORIENTED_EDGE('',*,*,#12,.F.);
+#24=EDGE_LOOP('',(#25,#26,#27));
+#25=ORIENTED_EDGE('',*,*,#12,.T.);
+#26=ORIENTED_EDGE('',*,*,#16,.T.);
+#27=ORIENTED_EDGE('',*,*,#14,.F.);
+#28=EDGE_LOOP('',(#29,#30,#31));
+#29=ORIENTED_EDGE('',*,*,#14,.T.);
+#30=ORIENTED_EDGE('',*,*,#17,.F.);
+#31=ORIENTED_EDGE('',*,*,#13,.F.);
+#32=EDGE_LOOP('',(#33,#34,#35));
+#33=ORIENTED_EDGE('',*,*,#15,.T.);
+#34=ORIENTED_EDGE('',*,*,#17,.T.);
+#35=ORIENTED_EDGE('',*,*,#16,.F.);
+#36=ADVANCED_FACE('',(#40),#19,.T.);
+#37=ADVANCED_FACE('',(#41),#19,.T.);
+#38=ADVANCED_FACE('',(#42),#19,.T.);
+#39=ADVANCED_FACE('',(#43),#19,.T.);
+#40=FACE_OUTER_BOUND('',#20,.T.);
+#41=FACE_OUTER_BOUND('',#24,.T.);
+#42=FACE_OUTER_BOUND('',#28,.T.);
+#43=FACE_BOUND('',#32,.T.);
+#44=CLOSED_SHELL('',(#36,#37,#38,#39));
+#45=MANIFOLD_SOLID_BREP('',#44);
+ENDSEC;
+END-ISO-10303-21;
+";
+
+    #[test]
+    fn read_planar_tetrahedron() {
+        let text = TETRAHEDRON;
+        let geometry = read_step(text).unwrap();
+        assert!(geometry.curves.is_empty() && geometry.surfaces.is_empty());
+        assert_eq!(geometry.solids.len(), 1);
+
+        let solid = &geometry.solids[0];
+        assert_eq!(solid.num_faces(), 4);
+        assert_eq!(solid.num_edges(), 6);
+        assert!((volume(solid) - 1.0 / 6.0).abs() <= 1e-12);
+
+        // Lengths in other units are converted to millimeters
+        let inches = TETRAHEDRON.replace(
+            "ENDSEC;\nEND",
+            "#46=(LENGTH_UNIT()NAMED_UNIT(*)SI_UNIT(.MILLI.,.METRE.));
+#47=LENGTH_MEASURE_WITH_UNIT(LENGTH_MEASURE(25.4),#46);
+#48=(CONVERSION_BASED_UNIT('INCH',#47)LENGTH_UNIT()NAMED_UNIT(*));
+#49=(GEOMETRIC_REPRESENTATION_CONTEXT(3)GLOBAL_UNIT_ASSIGNED_CONTEXT((#48))\
+REPRESENTATION_CONTEXT('',''));
+ENDSEC;\nEND",
+        );
+        let solid = &read_step(&inches).unwrap().solids[0];
+        assert!((volume(solid) - 25.4f64.powi(3) / 6.0).abs() <= 1e-9);
+    }
+
+    #[test]
+    fn length_scale() {
+        let with_units = |units: &str| {
+            TETRAHEDRON.replace(
+                "ENDSEC;\nEND",
+                &format!(
+                    "{units}\n#60=(GEOMETRIC_REPRESENTATION_CONTEXT(3)\
+GLOBAL_UNIT_ASSIGNED_CONTEXT((#50,#51))REPRESENTATION_CONTEXT('',''));\nENDSEC;\nEND"
+                ),
+            )
+        };
+        let plane_angle = "#51=(NAMED_UNIT(*)PLANE_ANGLE_UNIT()SI_UNIT($,.RADIAN.));";
+        let volume_in = |length: &str| {
+            volume(
+                &read_step(&with_units(&format!("{length}\n{plane_angle}")))
+                    .unwrap()
+                    .solids[0],
+            )
+        };
+
+        // Metres have no prefix and centimetres are prefixed
+        let metres = volume_in("#50=(LENGTH_UNIT()NAMED_UNIT(*)SI_UNIT($,.METRE.));");
+        assert!((metres - 1e9 / 6.0).abs() <= 1e-3);
+        let centimetres = volume_in("#50=(LENGTH_UNIT()NAMED_UNIT(*)SI_UNIT(.CENTI.,.METRE.));");
+        assert!((centimetres - 1e3 / 6.0).abs() <= 1e-9);
+
+        // The assigned length unit is used even when other length units are defined
+        let feet = volume_in(
+            "#50=(CONVERSION_BASED_UNIT('FOOT',#53)LENGTH_UNIT()NAMED_UNIT(*));
+#52=(LENGTH_UNIT()NAMED_UNIT(*)SI_UNIT(.MILLI.,.METRE.));
+#53=LENGTH_MEASURE_WITH_UNIT(LENGTH_MEASURE(304.8),#52);",
+        );
+        assert!((feet - 304.8f64.powi(3) / 6.0).abs() <= 1e-3);
+
+        // Files without units are in millimeters and unknown prefixes are reported
+        assert!((volume(&read_step(TETRAHEDRON).unwrap().solids[0]) - 1.0 / 6.0).abs() <= 1e-12);
+        assert!(read_step(&with_units(&format!(
+            "#50=(LENGTH_UNIT()NAMED_UNIT(*)SI_UNIT(.PETA.,.METRE.));\n{plane_angle}"
+        )))
+        .is_err());
+    }
+
+    #[test]
+    fn round_trip() {
+        // A hollow block is written with a void
+        let block = Solid::block(&EPlacement3::default(), EVec3::new(2.0, 2.0, 2.0)).unwrap();
+        let hollow = block.hollow(0.25, &[]).unwrap();
+        let placement = EPlacement3::from_origin(EVec3::new(1.0, 2.0, 3.0));
+        let geometry = Geometry {
+            curves: vec![NurbsCurve::<HSpace3>::arc(&placement, 1.5, 0.0, 2.0)],
+            surfaces: vec![NurbsSurface::<HSpace3>::sphere(&placement, 2.0)],
+            trimmed_surfaces: Vec::new(),
+            solids: vec![
+                hollow,
+                Solid::thicken(&NurbsSurface::rectangle(&placement, 2.0, 1.0), 0.5, 1e-6).unwrap(),
+            ],
+        };
+
+        for schema in [Schema::Ap203, Schema::Ap214] {
+            let text = write_step(&geometry, schema, "it's a part");
+            assert!(text.contains(schema.name()));
+            let read = read_step(&text).unwrap();
+
+            let (curve, original) = (&read.curves[0], &geometry.curves[0]);
+            let (surface, sphere) = (&read.surfaces[0], &geometry.surfaces[0]);
+            for t in [0.0, 0.3, 0.71, 1.0] {
+                let u = original.min_u() + (original.max_u() - original.min_u()) * t;
+                assert!((curve.point(u) - original.point(u)).magnitude() <= 1e-12);
+                assert!(
+                    (surface.point(t, 1.0 - t) - sphere.point(t, 1.0 - t)).magnitude() <= 1e-12
+                );
+            }
+
+            let hollow = &read.solids[0];
+            assert_eq!(hollow.num_shells(), 2);
+            assert!((volume(hollow) - (8.0 - 1.5 * 1.5 * 1.5)).abs() <= 1e-12);
+
+            let slab = &read.solids[1];
+            assert_eq!(slab.num_faces(), 6);
+            assert!(slab
+                .faces()
+                .all(|(_, face)| face.surface.is_some() && !face.loops.is_empty()));
+        }
+    }
+}
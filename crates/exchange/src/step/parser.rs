@@ -0,0 +1,397 @@
+use std::collections::BTreeMap;
+
+use crate::error::{ExchangeError, ExchangeResult};
+
+/// A parameter of a STEP entity
+#[derive(Debug, Clone, PartialEq)]
+pub enum Parameter {
+    Integer(i64),
+    Real(f64),
+    String(String),
+
+    /// An enumeration value or boolean, like `.T.` or `.MILLI.`, without the dots
+    Enumeration(String),
+
+    /// A reference to another entity, like `#12`
+    Reference(u64),
+
+    List(Vec<Parameter>),
+
+    /// A value wrapped in its type, like `LENGTH_MEASURE(1.0)`
+    Typed(String, Vec<Parameter>),
+
+    /// A missing optional value, written `$`
+    Unset,
+
+    /// A value derived from other attributes, written `*`
+    Derived,
+}
+impl Parameter {
+    /// The value of an integer or real parameter
+    pub fn as_real(&self) -> Option<f64> {
+        match self {
+            Self::Integer(value) => Some(*value as f64),
+            Self::Real(value) => Some(*value),
+            _ => None,
+        }
+    }
+
+    pub fn as_integer(&self) -> Option<i64> {
+        match self {
+            Self::Integer(value) => Some(*value),
+            _ => None,
+        }
+    }
+
+    pub fn as_reference(&self) -> Option<u64> {
+        match self {
+            Self::Reference(id) => Some(*id),
+            _ => None,
+        }
+    }
+
+    pub fn as_list(&self) -> Option<&[Parameter]> {
+        match self {
+            Self::List(items) => Some(items),
+            _ => None,
+        }
+    }
+
+    pub fn as_bool(&self) -> Option<bool> {
+        match self {
+            Self::Enumeration(value) if value == "T" => Some(true),
+            Self::Enumeration(value) if value == "F" => Some(false),
+            _ => None,
+        }
+    }
+}
+
+/// A named set of parameters. Simple entities have one record, and complex
+/// entities, written as several records in parentheses, have one per type they
+/// combine.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Record {
+    pub name: String,
+    pub params: Vec<Parameter>,
+}
+
+/// The contents of an ISO 10303-21 exchange file
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct StepFile {
+    pub header: Vec<Record>,
+    pub entities: BTreeMap<u64, Vec<Record>>,
+}
+impl StepFile {
+    pub fn parse(text: &str) -> ExchangeResult<Self> {
+        let mut parser = Parser::new(text);
+        let mut file = Self::default();
+
+        parser.keyword("ISO-10303-21")?;
+        parser.punct(';')?;
+        parser.keyword("HEADER")?;
+        parser.punct(';')?;
+        while !parser.try_keyword("ENDSEC") {
+            file.header.push(parser.record()?);
+            parser.punct(';')?;
+        }
+        parser.punct(';')?;
+
+        parser.keyword("DATA")?;
+        parser.punct(';')?;
+        while !parser.try_keyword("ENDSEC") {
+            let id = parser.reference()?;
+            parser.punct('=')?;
+            let records = if parser.try_punct('(') {
+                let mut records = Vec::new();
+                while !parser.try_punct(')') {
+                    records.push(parser.record()?);
+                }
+                records
+            } else {
+                vec![parser.record()?]
+            };
+            parser.punct(';')?;
+
+            if file.entities.insert(id, records).is_some() {
+                return Err(parser.error(format!("entity #{id} is defined twice")));
+            }
+        }
+        parser.punct(';')?;
+        parser.keyword("END-ISO-10303-21")?;
+
+        Ok(file)
+    }
+}
+
+/// A recursive descent parser over the characters of a file, skipping whitespace
+/// and comments between tokens
+struct Parser<'a> {
+    chars: std::iter::Peekable<std::str::Chars<'a>>,
+    line: usize,
+}
+impl<'a> Parser<'a> {
+    fn new(text: &'a str) -> Self {
+        Self {
+            chars: text.chars().peekable(),
+            line: 1,
+        }
+    }
+
+    fn error(&self, message: String) -> ExchangeError {
+        ExchangeError::Syntax {
+            line: self.line,
+            message,
+        }
+    }
+
+    fn next(&mut self) -> Option<char> {
+        let c = self.chars.next();
+        if c == Some('\n') {
+            self.line += 1;
+        }
+        c
+    }
+
+    fn skip_space(&mut self) {
+        loop {
+            match self.chars.peek() {
+                Some(c) if c.is_whitespace() => {
+                    self.next();
+                }
+                Some('/') => {
+                    // Comments are the only place a slash may appear outside a string
+                    self.next();
+                    if self.chars.peek() == Some(&'*') {
+                        self.next();
+                        let mut last = ' ';
+                        while let Some(c) = self.next() {
+                            if last == '*' && c == '/' {
+                                break;
+                            }
+                            last = c;
+                        }
+                    }
+                }
+                _ => return,
+            }
+        }
+    }
+
+    fn peek(&mut self) -> Option<char> {
+        self.skip_space();
+        self.chars.peek().copied()
+    }
+
+    fn try_punct(&mut self, punct: char) -> bool {
+        if self.peek() == Some(punct) {
+            self.next();
+            true
+        } else {
+            false
+        }
+    }
+
+    fn punct(&mut self, punct: char) -> ExchangeResult<()> {
+        if self.try_punct(punct) {
+            Ok(())
+        } else {
+            let found = self.peek();
+            Err(self.error(format!("expected '{punct}', found {found:?}")))
+        }
+    }
+
+    fn word(&mut self) -> String {
+        self.skip_space();
+        let mut word = String::new();
+        while let Some(c) = self.chars.peek() {
+            if c.is_ascii_alphanumeric() || *c == '_' || *c == '-' || *c == '!' {
+                word.push(*c);
+                self.next();
+            } else {
+                break;
+            }
+        }
+        word
+    }
+
+    fn try_keyword(&mut self, keyword: &str) -> bool {
+        self.skip_space();
+        let mut ahead = self.chars.clone();
+        if keyword.chars().all(|c| ahead.next() == Some(c))
+            && !matches!(ahead.peek(), Some(c) if c.is_ascii_alphanumeric() || *c == '_')
+        {
+            for _ in keyword.chars() {
+                self.next();
+            }
+            true
+        } else {
+            false
+        }
+    }
+
+    fn keyword(&mut self, keyword: &str) -> ExchangeResult<()> {
+        if self.try_keyword(keyword) {
+            Ok(())
+        } else {
+            Err(self.error(format!("expected {keyword}")))
+        }
+    }
+
+    fn reference(&mut self) -> ExchangeResult<u64> {
+        self.punct('#')?;
+        let digits = self.word();
+        digits
+            .parse()
+            .map_err(|_| self.error(format!("invalid entity reference #{digits}")))
+    }
+
+    fn record(&mut self) -> ExchangeResult<Record> {
+        let name = self.word();
+        if name.is_empty() {
+            let found = self.peek();
+            return Err(self.error(format!("expected an entity name, found {found:?}")));
+        }
+        self.punct('(')?;
+        let params = self.params()?;
+        Ok(Record { name, params })
+    }
+
+    /// Parses a comma-separated list of parameters up to and including the
+    /// closing parenthesis
+    fn params(&mut self) -> ExchangeResult<Vec<Parameter>> {
+        let mut params = Vec::new();
+        if self.try_punct(')') {
+            return Ok(params);
+        }
+
+        loop {
+            params.push(self.param()?);
+            if self.try_punct(')') {
+                return Ok(params);
+            }
+            self.punct(',')?;
+        }
+    }
+
+    fn param(&mut self) -> ExchangeResult<Parameter> {
+        match self.peek() {
+            Some('$') => {
+                self.next();
+                Ok(Parameter::Unset)
+            }
+            Some('*') => {
+                self.next();
+                Ok(Parameter::Derived)
+            }
+            Some('#') => Ok(Parameter::Reference(self.reference()?)),
+            Some('(') => {
+                self.next();
+                Ok(Parameter::List(self.params()?))
+            }
+            Some('\'') => {
+                self.next();
+                let mut value = String::new();
+                loop {
+                    match self.next() {
+                        Some('\'') if self.chars.peek() == Some(&'\'') => {
+                            self.next();
+                            value.push('\'');
+                        }
+                        Some('\'') => return Ok(Parameter::String(value)),
+                        Some(c) => value.push(c),
+                        None => return Err(self.error("unterminated string".to_string())),
+                    }
+                }
+            }
+            Some('.') => {
+                self.next();
+                let value = self.word();
+                self.punct('.')?;
+                Ok(Parameter::Enumeration(value))
+            }
+            Some(c) if c.is_ascii_digit() || c == '-' || c == '+' => self.number(),
+            Some(c) if c.is_ascii_alphabetic() => {
+                let name = self.word();
+                self.punct('(')?;
+                Ok(Parameter::Typed(name, self.params()?))
+            }
+            found => Err(self.error(format!("expected a parameter, found {found:?}"))),
+        }
+    }
+
+    fn number(&mut self) -> ExchangeResult<Parameter> {
+        let mut text = String::new();
+        while let Some(c) = self.chars.peek() {
+            if c.is_ascii_digit() || matches!(c, '-' | '+' | '.' | 'E' | 'e') {
+                text.push(*c);
+                self.next();
+            } else {
+                break;
+            }
+        }
+
+        if let Ok(value) = text.parse::<i64>() {
+            return Ok(Parameter::Integer(value));
+        }
+
+        // Reals may end in a bare decimal point or have one before the exponent,
+        // like `1.` or `1.E-07`, which Rust's parser accepts
+        text.parse::<f64>()
+            .map(Parameter::Real)
+            .map_err(|_| self.error(format!("invalid number {text}")))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Parameter, StepFile};
+
+    #[test]
+    fn parse_simple_and_complex_entities() {
+        let text = "ISO-10303-21;
+HEADER;
+/* A comment */
+FILE_DESCRIPTION(('test'),'2;1');
+FILE_SCHEMA(('AUTOMOTIVE_DESIGN'));
+ENDSEC;
+DATA;
+#1=CARTESIAN_POINT('it''s',(0.,1.5,-2.E-01));
+#2=(LENGTH_UNIT()NAMED_UNIT(*)SI_UNIT(.MILLI.,.METRE.));
+#3=UNCERTAINTY_MEASURE_WITH_UNIT(LENGTH_MEASURE(1.E-07),#2,'',$);
+ENDSEC;
+END-ISO-10303-21;
+";
+        let file = StepFile::parse(text).unwrap();
+        assert_eq!(file.header.len(), 2);
+        assert_eq!(file.entities.len(), 3);
+
+        let point = &file.entities[&1][0];
+        assert_eq!(point.name, "CARTESIAN_POINT");
+        assert_eq!(point.params[0], Parameter::String("it's".to_string()));
+        assert_eq!(
+            point.params[1],
+            Parameter::List(vec![
+                Parameter::Real(0.0),
+                Parameter::Real(1.5),
+                Parameter::Real(-0.2)
+            ])
+        );
+
+        let unit = &file.entities[&2];
+        assert_eq!(unit.len(), 3);
+        assert_eq!(unit[1].params, vec![Parameter::Derived]);
+        assert_eq!(
+            unit[2].params[0],
+            Parameter::Enumeration("MILLI".to_string())
+        );
+
+        let uncertainty = &file.entities[&3][0];
+        assert_eq!(
+            uncertainty.params[0],
+            Parameter::Typed("LENGTH_MEASURE".to_string(), vec![Parameter::Real(1e-7)])
+        );
+        assert_eq!(uncertainty.params[3], Parameter::Unset);
+
+        assert!(StepFile::parse("ISO-10303-21;\nHEADER;\nENDSEC;\nDATA;\n#1=POINT(;").is_err());
+    }
+}
@@ -0,0 +1,624 @@
+use std::{
+    collections::{btree_map::Entry, BTreeMap, BTreeSet},
+    f64::consts::PI,
+};
+
+use space::{
+    hspace::{HSpace2, HSpace3},
+    EPlacement3, EVec3, EVector, HVec3, TOL,
+};
+use spline::{math::knot_vector::KnotVector, nurbs_curve::NurbsCurve, nurbs_surface::NurbsSurface};
+use topology::solid::Solid;
+
+use crate::{
+    error::{ExchangeError, ExchangeResult},
+    geometry::Geometry,
+};
+
+use super::parser::{Parameter, Record, StepFile};
+
+/// Reads the geometry of a parsed file. Every manifold solid B-rep becomes a
+/// solid, and B-spline curves, circles and B-spline surfaces that are not part
+/// of a B-rep or another entity become free curves and surfaces. Lengths are
+/// converted from the file's unit to millimeters.
+pub fn read(file: &StepFile) -> ExchangeResult<Geometry> {
+    let mut reader = Reader { file, scale: 1.0 };
+    reader.scale = reader.length_scale()?;
+    let mut geometry = Geometry::new();
+
+    // Entities used by anything other than a representation or a set of items are
+    // parts of something larger
+    let mut used = BTreeSet::new();
+    for records in file.entities.values() {
+        if records
+            .iter()
+            .all(|r| r.name.ends_with("REPRESENTATION") || r.name.ends_with("_SET"))
+        {
+            continue;
+        }
+        for record in records {
+            collect_references(&record.params, &mut used);
+        }
+    }
+
+    for (id, records) in file.entities.iter() {
+        let names = records.iter().map(|r| r.name.as_str()).collect::<Vec<_>>();
+        if names.contains(&"MANIFOLD_SOLID_BREP") || names.contains(&"BREP_WITH_VOIDS") {
+            geometry.solids.push(reader.solid(*id)?);
+        } else if used.contains(id) {
+            continue;
+        } else if names.contains(&"B_SPLINE_CURVE_WITH_KNOTS") {
+            geometry.curves.push(reader.b_spline_curve(*id)?);
+        } else if names.contains(&"CIRCLE") {
+            let Curve::Circle { placement, radius } = reader.curve(*id)? else {
+                unreachable!()
+            };
+            geometry
+                .curves
+                .push(NurbsCurve::<HSpace3>::circle(&placement, radius));
+        } else if names.contains(&"B_SPLINE_SURFACE_WITH_KNOTS") {
+            geometry.surfaces.push(reader.b_spline_surface(*id)?);
+        }
+    }
+
+    Ok(geometry)
+}
+
+fn collect_references(params: &[Parameter], used: &mut BTreeSet<u64>) {
+    for param in params {
+        match param {
+            Parameter::Reference(id) => {
+                used.insert(*id);
+            }
+            Parameter::List(items) | Parameter::Typed(_, items) => collect_references(items, used),
+            _ => {}
+        }
+    }
+}
+
+/// The geometry of an edge, which is bounded by the edge's vertices
+enum Curve {
+    Line,
+    Circle { placement: EPlacement3, radius: f64 },
+    Spline(NurbsCurve<HSpace3>),
+}
+
+/// The geometry of a face, which is bounded by the face's loops
+enum Surface {
+    Plane,
+    Spline(NurbsSurface<HSpace3>),
+}
+
+/// An edge of a B-rep, between two of its points
+struct EdgeCurve {
+    start: usize,
+    end: usize,
+    curve: u64,
+    same_sense: bool,
+}
+
+/// A face of a B-rep, with the polygons of its loops in the form taken by
+/// `Solid::from_polygons`
+struct FaceSurface {
+    loops: Vec<Vec<usize>>,
+    surface: u64,
+    same_sense: bool,
+}
+
+/// Reads entities of a file, checking their types and parameters
+struct Reader<'a> {
+    file: &'a StepFile,
+
+    /// The number of millimeters in the file's unit of length
+    scale: f64,
+}
+impl<'a> Reader<'a> {
+    fn entity(&self, id: u64) -> ExchangeResult<&'a [Record]> {
+        self.file
+            .entities
+            .get(&id)
+            .map(|records| records.as_slice())
+            .ok_or(ExchangeError::MissingEntity(id))
+    }
+
+    /// Finds the record of an entity with one of the given names, which for a
+    /// complex entity may be one of several
+    fn record(&self, id: u64, names: &[&'static str]) -> ExchangeResult<Params<'a>> {
+        let records = self.entity(id)?;
+        records
+            .iter()
+            .find(|r| names.contains(&r.name.as_str()))
+            .map(|record| Params { id, record })
+            .ok_or_else(|| ExchangeError::UnexpectedEntity {
+                id,
+                expected: names[0],
+                found: records
+                    .iter()
+                    .map(|r| r.name.as_str())
+                    .collect::<Vec<_>>()
+                    .join(" "),
+            })
+    }
+
+    fn name(&self, id: u64) -> ExchangeResult<&'a str> {
+        Ok(self.entity(id)?[0].name.as_str())
+    }
+
+    fn point(&self, id: u64) -> ExchangeResult<EVec3> {
+        let params = self.record(id, &["CARTESIAN_POINT"])?;
+        Ok(vector(&params, params.reals(1)?)? * self.scale)
+    }
+
+    /// The number of millimeters in the file's unit of length, which is the one
+    /// assigned to its representation context. Files that don't give one are
+    /// taken to be in millimeters.
+    fn length_scale(&self) -> ExchangeResult<f64> {
+        let assigned = self
+            .file
+            .entities
+            .values()
+            .flatten()
+            .find(|r| r.name == "GLOBAL_UNIT_ASSIGNED_CONTEXT")
+            .and_then(|r| r.params.first()?.as_list())
+            .map(|units| units.iter().filter_map(|u| u.as_reference()).collect())
+            .unwrap_or_else(|| self.file.entities.keys().copied().collect::<Vec<_>>());
+        let is_length = |id: &u64| match self.entity(*id) {
+            Ok(records) => records.iter().any(|r| r.name == "LENGTH_UNIT"),
+            Err(_) => false,
+        };
+        let unit = assigned.into_iter().find(is_length);
+        match unit {
+            Some(id) => self.unit_scale(id),
+            None => Ok(1.0),
+        }
+    }
+
+    /// The number of millimeters in a unit of length, either an SI unit with an
+    /// optional prefix or a unit defined as a multiple of another
+    fn unit_scale(&self, id: u64) -> ExchangeResult<f64> {
+        if let Ok(si) = self.record(id, &["SI_UNIT"]) {
+            let prefix = match si.get(0)? {
+                Parameter::Unset => "",
+                Parameter::Enumeration(prefix) => prefix.as_str(),
+                _ => return Err(si.invalid("has an invalid prefix")),
+            };
+            return Ok(match prefix {
+                "" => 1000.0,
+                "MILLI" => 1.0,
+                "CENTI" => 10.0,
+                "DECI" => 100.0,
+                "KILO" => 1_000_000.0,
+                "MICRO" => 0.001,
+                "NANO" => 0.000_001,
+                _ => {
+                    return Err(ExchangeError::Unsupported(format!(
+                        "STEP length unit prefix {prefix}"
+                    )))
+                }
+            });
+        }
+
+        let conversion = self.record(id, &["CONVERSION_BASED_UNIT"])?;
+        let measure = self.record(
+            conversion.reference(1)?,
+            &["LENGTH_MEASURE_WITH_UNIT", "MEASURE_WITH_UNIT"],
+        )?;
+        let value = match measure.get(0)? {
+            Parameter::Typed(_, value) => value.first().and_then(|v| v.as_real()),
+            value => value.as_real(),
+        }
+        .ok_or_else(|| measure.invalid("has no value"))?;
+        Ok(value * self.unit_scale(measure.reference(1)?)?)
+    }
+
+    fn direction(&self, id: u64) -> ExchangeResult<EVec3> {
+        let params = self.record(id, &["DIRECTION"])?;
+        Ok(vector(&params, params.reals(1)?)?.normalize())
+    }
+
+    fn placement(&self, id: u64) -> ExchangeResult<EPlacement3> {
+        let params = self.record(id, &["AXIS2_PLACEMENT_3D"])?;
+        let origin = self.point(params.reference(1)?)?;
+        let axis = match params.get(2)? {
+            Parameter::Unset => EVec3::new(0.0, 0.0, 1.0),
+            _ => self.direction(params.reference(2)?)?,
+        };
+
+        Ok(match params.get(3)? {
+            Parameter::Unset => EPlacement3::from_axis(origin, axis),
+            _ => EPlacement3::from_axis_and_x(origin, axis, self.direction(params.reference(3)?)?),
+        })
+    }
+
+    fn curve(&self, id: u64) -> ExchangeResult<Curve> {
+        match self.name(id)? {
+            "LINE" => Ok(Curve::Line),
+            "CIRCLE" => {
+                let params = self.record(id, &["CIRCLE"])?;
+                Ok(Curve::Circle {
+                    placement: self.placement(params.reference(1)?)?,
+                    radius: params.real(2)? * self.scale,
+                })
+            }
+            "SURFACE_CURVE" | "SEAM_CURVE" => {
+                let params = self.record(id, &["SURFACE_CURVE", "SEAM_CURVE"])?;
+                self.curve(params.reference(1)?)
+            }
+            _ => Ok(Curve::Spline(self.b_spline_curve(id)?)),
+        }
+    }
+
+    /// Reads a B-spline curve, written either as a simple entity or, if it is
+    /// rational, as a complex entity whose parts hold its attributes
+    fn b_spline_curve(&self, id: u64) -> ExchangeResult<NurbsCurve<HSpace3>> {
+        let knots = self.record(id, &["B_SPLINE_CURVE_WITH_KNOTS"])?;
+        let simple = self.entity(id)?.len() == 1;
+        let (curve, first) = if simple {
+            (knots, 1)
+        } else {
+            (self.record(id, &["B_SPLINE_CURVE"])?, 0)
+        };
+        let knot_offset = if simple { 6 } else { 0 };
+
+        let degree = curve.integer(first)? as usize;
+        let points = curve
+            .references(first + 1)?
+            .into_iter()
+            .map(|p| self.point(p))
+            .collect::<ExchangeResult<Vec<_>>>()?;
+        let knot_vector = expand_knots(
+            &knots,
+            knots.integers(knot_offset)?,
+            knots.reals(knot_offset + 1)?,
+        )?;
+        let weights = match self.record(id, &["RATIONAL_B_SPLINE_CURVE"]) {
+            Ok(rational) => rational.reals(0)?,
+            Err(_) => vec![1.0; points.len()],
+        };
+
+        if weights.len() != points.len() || knot_vector.len() != points.len() + degree + 1 {
+            return Err(curve.invalid("the numbers of points, weights and knots do not match"));
+        }
+
+        Ok(NurbsCurve::new(
+            points
+                .iter()
+                .zip(weights.iter())
+                .map(|(p, w)| HVec3::new(p.x, p.y, p.z, *w))
+                .collect(),
+            knot_vector,
+        ))
+    }
+
+    fn surface(&self, id: u64) -> ExchangeResult<Surface> {
+        match self.name(id)? {
+            "PLANE" => Ok(Surface::Plane),
+            _ => Ok(Surface::Spline(self.b_spline_surface(id)?)),
+        }
+    }
+
+    /// Reads a B-spline surface, written like a B-spline curve
+    fn b_spline_surface(&self, id: u64) -> ExchangeResult<NurbsSurface<HSpace3>> {
+        let knots = self.record(id, &["B_SPLINE_SURFACE_WITH_KNOTS"])?;
+        let simple = self.entity(id)?.len() == 1;
+        let (surface, first) = if simple {
+            (knots, 1)
+        } else {
+            (self.record(id, &["B_SPLINE_SURFACE"])?, 0)
+        };
+        let knot_offset = if simple { 8 } else { 0 };
+
+        let (degree_u, degree_v) = (
+            surface.integer(first)? as usize,
+            surface.integer(first + 1)? as usize,
+        );
+        let points = surface
+            .list(first + 2)?
+            .iter()
+            .map(|row| {
+                row.as_list()
+                    .ok_or_else(|| surface.invalid("control points are not a list of rows"))?
+                    .iter()
+                    .map(|p| {
+                        p.as_reference()
+                            .ok_or_else(|| surface.invalid("control points are not references"))
+                            .and_then(|p| self.point(p))
+                    })
+                    .collect::<ExchangeResult<Vec<_>>>()
+            })
+            .collect::<ExchangeResult<Vec<_>>>()?;
+        let knot_vector_u = expand_knots(
+            &knots,
+            knots.integers(knot_offset)?,
+            knots.reals(knot_offset + 2)?,
+        )?;
+        let knot_vector_v = expand_knots(
+            &knots,
+            knots.integers(knot_offset + 1)?,
+            knots.reals(knot_offset + 3)?,
+        )?;
+        let weights = match self.record(id, &["RATIONAL_B_SPLINE_SURFACE"]) {
+            Ok(rational) => rational
+                .list(0)?
+                .iter()
+                .map(|row| {
+                    row.as_list()
+                        .and_then(|row| row.iter().map(|w| w.as_real()).collect::<Option<Vec<_>>>())
+                        .ok_or_else(|| rational.invalid("weights are not a list of rows"))
+                })
+                .collect::<ExchangeResult<Vec<_>>>()?,
+            Err(_) => points.iter().map(|row| vec![1.0; row.len()]).collect(),
+        };
+
+        let columns = points.first().map_or(0, |row| row.len());
+        if points.is_empty()
+            || points.iter().any(|row| row.len() != columns)
+            || weights.len() != points.len()
+            || weights.iter().any(|row| row.len() != columns)
+            || knot_vector_u.len() != points.len() + degree_u + 1
+            || knot_vector_v.len() != columns + degree_v + 1
+        {
+            return Err(surface.invalid("the numbers of points, weights and knots do not match"));
+        }
+
+        Ok(NurbsSurface::new(
+            points
+                .iter()
+                .zip(weights.iter())
+                .map(|(row, weights)| {
+                    row.iter()
+                        .zip(weights.iter())
+                        .map(|(p, w)| HVec3::new(p.x, p.y, p.z, *w))
+                        .collect()
+                })
+                .collect(),
+            knot_vector_u,
+            knot_vector_v,
+        ))
+    }
+
+    /// Reads a solid from a manifold solid B-rep, including any voids
+    fn solid(&self, id: u64) -> ExchangeResult<Solid> {
+        let brep = self.record(id, &["MANIFOLD_SOLID_BREP", "BREP_WITH_VOIDS"])?;
+        let mut shells = vec![(brep.reference(1)?, false)];
+        if brep.record.name == "BREP_WITH_VOIDS" {
+            for void in brep.references(2)? {
+                let oriented = self.record(void, &["ORIENTED_CLOSED_SHELL"])?;
+                shells.push((oriented.reference(2)?, !oriented.bool(3)?));
+            }
+        }
+
+        let mut points = Vec::new();
+        let mut vertices = BTreeMap::<u64, usize>::new();
+        let mut edges = BTreeMap::<u64, EdgeCurve>::new();
+        let mut faces = Vec::new();
+        for (shell, reversed) in shells {
+            let shell = self.record(shell, &["CLOSED_SHELL", "OPEN_SHELL"])?;
+            for face in shell.references(1)? {
+                let face = self.record(face, &["ADVANCED_FACE", "FACE_SURFACE"])?;
+                let mut loops = Vec::new();
+                for bound in face.references(1)? {
+                    let bound_params = self.record(bound, &["FACE_OUTER_BOUND", "FACE_BOUND"])?;
+                    let edge_loop = self.record(bound_params.reference(1)?, &["EDGE_LOOP"])?;
+
+                    let mut indices = Vec::new();
+                    for oriented in edge_loop.references(1)? {
+                        let oriented = self.record(oriented, &["ORIENTED_EDGE"])?;
+                        let edge_id = oriented.reference(3)?;
+                        if let Entry::Vacant(entry) = edges.entry(edge_id) {
+                            let edge = self.record(edge_id, &["EDGE_CURVE"])?;
+                            let mut vertex = |index: usize| -> ExchangeResult<usize> {
+                                let vertex_id = edge.reference(index)?;
+                                if let Some(point) = vertices.get(&vertex_id) {
+                                    return Ok(*point);
+                                }
+                                let vertex = self.record(vertex_id, &["VERTEX_POINT"])?;
+                                points.push(self.point(vertex.reference(1)?)?);
+                                vertices.insert(vertex_id, points.len() - 1);
+                                Ok(points.len() - 1)
+                            };
+                            let (start, end) = (vertex(1)?, vertex(2)?);
+                            entry.insert(EdgeCurve {
+                                start,
+                                end,
+                                curve: edge.reference(3)?,
+                                same_sense: edge.bool(4)?,
+                            });
+                        }
+
+                        let edge = &edges[&edge_id];
+                        indices.push(if oriented.bool(4)? {
+                            edge.start
+                        } else {
+                            edge.end
+                        });
+                    }
+
+                    if bound_params.bool(2)? == reversed {
+                        indices.reverse();
+                    }
+                    if bound_params.record.name == "FACE_OUTER_BOUND" {
+                        loops.insert(0, indices);
+                    } else {
+                        loops.push(indices);
+                    }
+                }
+
+                faces.push(FaceSurface {
+                    loops,
+                    surface: face.reference(2)?,
+                    same_sense: face.bool(3)? != reversed,
+                });
+            }
+        }
+
+        let polygons = faces.iter().map(|f| f.loops.clone()).collect::<Vec<_>>();
+        let (mut solid, vertex_ids) = Solid::from_polygons_mapped(&points, &polygons)?;
+
+        // Faces are made in order, and planar ones already have fitted surfaces
+        let face_ids = solid.faces().map(|(id, _)| id).collect::<Vec<_>>();
+        for (face, id) in faces.iter().zip(face_ids) {
+            let Surface::Spline(surface) = self.surface(face.surface)? else {
+                continue;
+            };
+
+            for he in solid.face_half_edges(id)? {
+                let start = solid
+                    .vertex(solid.half_edge(he).unwrap().origin)
+                    .unwrap()
+                    .point;
+                let end = solid.vertex(solid.half_edge_target(he)?).unwrap().point;
+                let (start, end) = (
+                    surface.distance_to_point(start, TOL).first.param,
+                    surface.distance_to_point(end, TOL).first.param,
+                );
+                solid.set_pcurve(he, NurbsCurve::<HSpace2>::line(start, end))?;
+            }
+            solid.set_face_surface(id, surface, face.same_sense)?;
+        }
+
+        for edge in edges.values() {
+            let (start, end) = (points[edge.start], points[edge.end]);
+            let curve = match self.curve(edge.curve)? {
+                Curve::Line => NurbsCurve::<HSpace3>::line(start, end),
+                Curve::Circle { placement, radius } => {
+                    let angle = |p: EVec3| {
+                        let local = placement.to_local(p);
+                        local.y.atan2(local.x)
+                    };
+                    let (from, mut to) = (angle(start), angle(end));
+                    if edge.same_sense {
+                        while to <= from + TOL {
+                            to += 2.0 * PI;
+                        }
+                    } else {
+                        while to >= from - TOL {
+                            to -= 2.0 * PI;
+                        }
+                    }
+                    NurbsCurve::<HSpace3>::arc(&placement, radius, from, to)
+                }
+                Curve::Spline(curve) if edge.same_sense => curve,
+                Curve::Spline(curve) => curve.reverse(),
+            };
+
+            let (a, b) = (vertex_ids[&edge.start], vertex_ids[&edge.end]);
+            let id = solid
+                .edge_between(a, b)
+                .ok_or_else(|| ExchangeError::Unsupported(format!("edge #{}", edge.curve)))?;
+            let first = solid.edge(id).unwrap().half_edges[0];
+            let curve = if solid.half_edge(first).unwrap().origin == a {
+                curve
+            } else {
+                curve.reverse()
+            };
+            solid.set_edge_curve(id, curve)?;
+        }
+
+        solid.validate()?;
+        Ok(solid)
+    }
+}
+
+/// The parameters of one record of an entity
+#[derive(Clone, Copy)]
+struct Params<'a> {
+    id: u64,
+    record: &'a Record,
+}
+impl<'a> Params<'a> {
+    fn invalid(&self, message: &str) -> ExchangeError {
+        ExchangeError::InvalidParameters {
+            id: self.id,
+            message: format!("{} {}", self.record.name, message),
+        }
+    }
+
+    fn get(&self, index: usize) -> ExchangeResult<&'a Parameter> {
+        self.record
+            .params
+            .get(index)
+            .ok_or_else(|| self.invalid(&format!("has no parameter {index}")))
+    }
+
+    fn real(&self, index: usize) -> ExchangeResult<f64> {
+        self.get(index)?
+            .as_real()
+            .ok_or_else(|| self.invalid(&format!("parameter {index} is not a number")))
+    }
+
+    fn integer(&self, index: usize) -> ExchangeResult<i64> {
+        self.get(index)?
+            .as_integer()
+            .ok_or_else(|| self.invalid(&format!("parameter {index} is not an integer")))
+    }
+
+    fn bool(&self, index: usize) -> ExchangeResult<bool> {
+        self.get(index)?
+            .as_bool()
+            .ok_or_else(|| self.invalid(&format!("parameter {index} is not a boolean")))
+    }
+
+    fn reference(&self, index: usize) -> ExchangeResult<u64> {
+        self.get(index)?
+            .as_reference()
+            .ok_or_else(|| self.invalid(&format!("parameter {index} is not a reference")))
+    }
+
+    fn list(&self, index: usize) -> ExchangeResult<&'a [Parameter]> {
+        self.get(index)?
+            .as_list()
+            .ok_or_else(|| self.invalid(&format!("parameter {index} is not a list")))
+    }
+
+    fn reals(&self, index: usize) -> ExchangeResult<Vec<f64>> {
+        self.list(index)?
+            .iter()
+            .map(|p| p.as_real())
+            .collect::<Option<Vec<_>>>()
+            .ok_or_else(|| self.invalid(&format!("parameter {index} is not a list of numbers")))
+    }
+
+    fn integers(&self, index: usize) -> ExchangeResult<Vec<i64>> {
+        self.list(index)?
+            .iter()
+            .map(|p| p.as_integer())
+            .collect::<Option<Vec<_>>>()
+            .ok_or_else(|| self.invalid(&format!("parameter {index} is not a list of integers")))
+    }
+
+    fn references(&self, index: usize) -> ExchangeResult<Vec<u64>> {
+        self.list(index)?
+            .iter()
+            .map(|p| p.as_reference())
+            .collect::<Option<Vec<_>>>()
+            .ok_or_else(|| self.invalid(&format!("parameter {index} is not a list of references")))
+    }
+}
+
+fn vector(params: &Params, coordinates: Vec<f64>) -> ExchangeResult<EVec3> {
+    match coordinates[..] {
+        [x, y] => Ok(EVec3::new(x, y, 0.0)),
+        [x, y, z] => Ok(EVec3::new(x, y, z)),
+        _ => Err(params.invalid("does not have 2 or 3 coordinates")),
+    }
+}
+
+/// Repeats each distinct knot by its multiplicity
+fn expand_knots(
+    params: &Params,
+    multiplicities: Vec<i64>,
+    knots: Vec<f64>,
+) -> ExchangeResult<KnotVector> {
+    if multiplicities.len() != knots.len() || multiplicities.iter().any(|m| *m < 1) {
+        return Err(params.invalid("has mismatched knots and multiplicities"));
+    }
+
+    Ok(KnotVector::from_vec(
+        knots
+            .iter()
+            .zip(multiplicities.iter())
+            .flat_map(|(knot, count)| vec![*knot; *count as usize])
+            .collect(),
+    ))
+}
@@ -0,0 +1,443 @@
+use std::{collections::BTreeMap, fmt::Write};
+
+use space::{hspace::HSpace3, EVec3, EVector, Tolerance, VolumeIntegrals};
+use spline::{math::knot_vector::KnotVector, nurbs_curve::NurbsCurve, nurbs_surface::NurbsSurface};
+use topology::{
+    entities::{EdgeId, FaceId, VertexId},
+    solid::Solid,
+};
+
+use crate::{format::real, geometry::Geometry};
+
+use super::Schema;
+
+/// Writes geometry as an ISO 10303-21 file. Everything goes into one shape
+/// representation of a single product, with lengths in millimeters: free curves
+/// and surfaces in a geometric set, and solids as manifold solid B-reps, with
+/// voids if they have inner shells. Curves and surfaces are always written as
+/// B-splines.
+pub fn write(geometry: &Geometry, schema: Schema, name: &str) -> String {
+    let mut writer = Writer::default();
+    let (protocol, context, year) = match schema {
+        Schema::Ap203 => ("config_control_design", "config_control_design", 1994),
+        Schema::Ap214 => ("automotive_design", "automotive_design", 2000),
+    };
+
+    let app_context = writer.add(format!("APPLICATION_CONTEXT('{context}')"));
+    writer.add(format!(
+        "APPLICATION_PROTOCOL_DEFINITION('international standard','{protocol}',{year},#{app_context})"
+    ));
+    let product_context = writer.add(format!("PRODUCT_CONTEXT('',#{app_context},'mechanical')"));
+    let name = string(name);
+    let product = writer.add(format!("PRODUCT({name},{name},'',(#{product_context}))"));
+    let formation = writer.add(format!("PRODUCT_DEFINITION_FORMATION('','',#{product})"));
+    let definition_context = writer.add(format!(
+        "PRODUCT_DEFINITION_CONTEXT('part definition',#{app_context},'design')"
+    ));
+    let definition = writer.add(format!(
+        "PRODUCT_DEFINITION('design','',#{formation},#{definition_context})"
+    ));
+    let shape = writer.add(format!("PRODUCT_DEFINITION_SHAPE('','',#{definition})"));
+
+    let length = writer.add("(LENGTH_UNIT()NAMED_UNIT(*)SI_UNIT(.MILLI.,.METRE.))".to_string());
+    let angle = writer.add("(NAMED_UNIT(*)PLANE_ANGLE_UNIT()SI_UNIT($,.RADIAN.))".to_string());
+    let solid_angle =
+        writer.add("(NAMED_UNIT(*)SI_UNIT($,.STERADIAN.)SOLID_ANGLE_UNIT())".to_string());
+    let uncertainty = writer.add(format!(
+        "UNCERTAINTY_MEASURE_WITH_UNIT(LENGTH_MEASURE({}),#{length},'distance_accuracy_value','')",
+        real(space::TOL)
+    ));
+    let representation_context = writer.add(format!(
+        "(GEOMETRIC_REPRESENTATION_CONTEXT(3)\
+        GLOBAL_UNCERTAINTY_ASSIGNED_CONTEXT((#{uncertainty}))\
+        GLOBAL_UNIT_ASSIGNED_CONTEXT((#{length},#{angle},#{solid_angle}))\
+        REPRESENTATION_CONTEXT('',''))"
+    ));
+
+    let mut items = Vec::new();
+    let mut free = Vec::new();
+    for curve in geometry.curves.iter() {
+        free.push(writer.curve(curve));
+    }
+    for surface in geometry.surfaces.iter() {
+        free.push(writer.surface(surface));
+    }
+    if !free.is_empty() {
+        items.push(writer.add(format!("GEOMETRIC_SET('',{})", references(&free))));
+    }
+    for solid in geometry.solids.iter() {
+        items.extend(writer.solid(solid));
+    }
+
+    let representation = writer.add(format!(
+        "SHAPE_REPRESENTATION('',{},#{representation_context})",
+        references(&items)
+    ));
+    writer.add(format!(
+        "SHAPE_DEFINITION_REPRESENTATION(#{shape},#{representation})"
+    ));
+
+    let mut text = String::new();
+    writeln!(text, "ISO-10303-21;").unwrap();
+    writeln!(text, "HEADER;").unwrap();
+    writeln!(text, "FILE_DESCRIPTION((''),'2;1');").unwrap();
+    writeln!(text, "FILE_NAME({name},'',(''),(''),'cadit','cadit','');").unwrap();
+    writeln!(text, "FILE_SCHEMA(('{}'));", schema.name()).unwrap();
+    writeln!(text, "ENDSEC;").unwrap();
+    writeln!(text, "DATA;").unwrap();
+    for (id, entity) in writer.entities.iter().enumerate() {
+        writeln!(text, "#{}={entity};", id + 1).unwrap();
+    }
+    writeln!(text, "ENDSEC;").unwrap();
+    writeln!(text, "END-ISO-10303-21;").unwrap();
+    text
+}
+
+/// Collects the entities of a file, numbering them from 1 in the order they are
+/// added
+#[derive(Default)]
+struct Writer {
+    entities: Vec<String>,
+}
+impl Writer {
+    fn add(&mut self, entity: String) -> usize {
+        self.entities.push(entity);
+        self.entities.len()
+    }
+
+    fn point(&mut self, point: EVec3) -> usize {
+        self.add(format!(
+            "CARTESIAN_POINT('',({},{},{}))",
+            real(point.x),
+            real(point.y),
+            real(point.z)
+        ))
+    }
+
+    fn curve(&mut self, curve: &NurbsCurve<HSpace3>) -> usize {
+        let points = curve
+            .control_points()
+            .iter()
+            .map(|p| self.point(EVec3::new(p.x, p.y, p.z)))
+            .collect::<Vec<_>>();
+        let (multiplicities, knots) = knot_runs(curve.knot_vector());
+        let weights = curve
+            .control_points()
+            .iter()
+            .map(|p| p.h)
+            .collect::<Vec<_>>();
+        let degree = curve.degree();
+        let closed = logical(curve.is_closed(&Tolerance::DEFAULT));
+
+        if weights.iter().all(|w| *w == 1.0) {
+            self.add(format!(
+                "B_SPLINE_CURVE_WITH_KNOTS('',{degree},{},.UNSPECIFIED.,{closed},.F.,{multiplicities},{knots},.UNSPECIFIED.)",
+                references(&points),
+            ))
+        } else {
+            self.add(format!(
+                "(BOUNDED_CURVE()B_SPLINE_CURVE({degree},{},.UNSPECIFIED.,{closed},.F.)\
+                B_SPLINE_CURVE_WITH_KNOTS({multiplicities},{knots},.UNSPECIFIED.)\
+                CURVE()GEOMETRIC_REPRESENTATION_ITEM()RATIONAL_B_SPLINE_CURVE({})\
+                REPRESENTATION_ITEM(''))",
+                references(&points),
+                reals(&weights),
+            ))
+        }
+    }
+
+    fn surface(&mut self, surface: &NurbsSurface<HSpace3>) -> usize {
+        let points = surface
+            .control_points()
+            .iter()
+            .map(|row| {
+                let row = row
+                    .iter()
+                    .map(|p| self.point(EVec3::new(p.x, p.y, p.z)))
+                    .collect::<Vec<_>>();
+                references(&row)
+            })
+            .collect::<Vec<_>>()
+            .join(",");
+        let weights = surface
+            .control_points()
+            .iter()
+            .map(|row| row.iter().map(|p| p.h).collect::<Vec<_>>())
+            .collect::<Vec<_>>();
+        let (multiplicities_u, knots_u) = knot_runs(surface.knot_vector_u());
+        let (multiplicities_v, knots_v) = knot_runs(surface.knot_vector_v());
+        let (degree_u, degree_v) = (surface.degree_u(), surface.degree_v());
+
+        if weights.iter().flatten().all(|w| *w == 1.0) {
+            self.add(format!(
+                "B_SPLINE_SURFACE_WITH_KNOTS('',{degree_u},{degree_v},({points}),.UNSPECIFIED.,.F.,.F.,.F.,\
+                {multiplicities_u},{multiplicities_v},{knots_u},{knots_v},.UNSPECIFIED.)"
+            ))
+        } else {
+            let weights = weights
+                .iter()
+                .map(|row| reals(row))
+                .collect::<Vec<_>>()
+                .join(",");
+            self.add(format!(
+                "(BOUNDED_SURFACE()B_SPLINE_SURFACE({degree_u},{degree_v},({points}),.UNSPECIFIED.,.F.,.F.,.F.)\
+                B_SPLINE_SURFACE_WITH_KNOTS({multiplicities_u},{multiplicities_v},{knots_u},{knots_v},.UNSPECIFIED.)\
+                GEOMETRIC_REPRESENTATION_ITEM()RATIONAL_B_SPLINE_SURFACE(({weights}))\
+                REPRESENTATION_ITEM('')SURFACE())"
+            ))
+        }
+    }
+
+    /// Writes a solid as one B-rep for each of its outer shells, returning their IDs
+    fn solid(&mut self, solid: &Solid) -> Vec<usize> {
+        let mut vertices = BTreeMap::<VertexId, usize>::new();
+        for (id, vertex) in solid.vertices() {
+            let point = self.point(vertex.point);
+            vertices.insert(id, self.add(format!("VERTEX_POINT('',#{point})")));
+        }
+
+        let mut edges = BTreeMap::<EdgeId, usize>::new();
+        for (id, edge) in solid.edges() {
+            let start = solid.half_edge(edge.half_edges[0]).unwrap().origin;
+            let end = solid.half_edge(edge.half_edges[1]).unwrap().origin;
+            let curve = match &edge.curve {
+                Some(curve) => self.curve(curve),
+                None => self.curve(&NurbsCurve::<HSpace3>::line(
+                    solid.vertex(start).unwrap().point,
+                    solid.vertex(end).unwrap().point,
+                )),
+            };
+            edges.insert(
+                id,
+                self.add(format!(
+                    "EDGE_CURVE('',#{},#{},#{curve},.T.)",
+                    vertices[&start], vertices[&end]
+                )),
+            );
+        }
+
+        // Shells enclosing negative volume are voids, and belong to the outer shell
+        // whose bounds contain them
+        let shells = solid
+            .shells()
+            .map(|(_, shell)| (shell, shell_volume(solid, &shell.faces)))
+            .collect::<Vec<_>>();
+        let mut outer = shells
+            .iter()
+            .filter(|(_, volume)| *volume >= 0.0)
+            .map(|(shell, _)| {
+                let faces = self.faces(solid, &shell.faces, &vertices, &edges, false);
+                let closed = self.add(format!("CLOSED_SHELL('',{})", references(&faces)));
+                (shell, closed, Vec::new())
+            })
+            .collect::<Vec<_>>();
+
+        for (shell, _) in shells.iter().filter(|(_, volume)| *volume < 0.0) {
+            let point = solid
+                .loop_points(solid.face(shell.faces[0]).unwrap().outer_loop)
+                .unwrap()[0];
+            let Some(index) = outer
+                .iter()
+                .position(|(outer, _, _)| shell_bounds_contain(solid, &outer.faces, point))
+            else {
+                continue;
+            };
+
+            // Voids are written facing out of the empty space they enclose, and
+            // flipped by their orientation
+            let faces = self.faces(solid, &shell.faces, &vertices, &edges, true);
+            let closed = self.add(format!("CLOSED_SHELL('',{})", references(&faces)));
+            let oriented = self.add(format!("ORIENTED_CLOSED_SHELL('',*,#{closed},.F.)"));
+            outer[index].2.push(oriented);
+        }
+
+        outer
+            .into_iter()
+            .map(|(_, closed, voids)| {
+                if voids.is_empty() {
+                    self.add(format!("MANIFOLD_SOLID_BREP('',#{closed})"))
+                } else {
+                    self.add(format!(
+                        "BREP_WITH_VOIDS('',#{closed},{})",
+                        references(&voids)
+                    ))
+                }
+            })
+            .collect()
+    }
+
+    /// Writes faces of a solid, optionally reversing them so their bounds run the
+    /// other way and their normals point into the solid
+    fn faces(
+        &mut self,
+        solid: &Solid,
+        faces: &[FaceId],
+        vertices: &BTreeMap<VertexId, usize>,
+        edges: &BTreeMap<EdgeId, usize>,
+        reversed: bool,
+    ) -> Vec<usize> {
+        let mut ids = Vec::new();
+        for id in faces.iter() {
+            let face = solid.face(*id).unwrap();
+            let bounds = face
+                .loops
+                .iter()
+                .map(|loop_id| {
+                    let oriented = solid
+                        .loop_half_edges(*loop_id)
+                        .unwrap()
+                        .iter()
+                        .filter_map(|he| {
+                            let edge = solid.half_edge(*he).unwrap().edge?;
+                            let forward = solid.edge(edge).unwrap().half_edges[0] == *he;
+                            Some(self.add(format!(
+                                "ORIENTED_EDGE('',*,*,#{},{})",
+                                edges[&edge],
+                                logical(forward)
+                            )))
+                        })
+                        .collect::<Vec<_>>();
+                    let edge_loop = if oriented.is_empty() {
+                        // A loop with no edges is a single vertex
+                        let vertex = solid.loop_vertices(*loop_id).unwrap()[0];
+                        self.add(format!("VERTEX_LOOP('',#{})", vertices[&vertex]))
+                    } else {
+                        self.add(format!("EDGE_LOOP('',{})", references(&oriented)))
+                    };
+                    let kind = if *loop_id == face.outer_loop {
+                        "FACE_OUTER_BOUND"
+                    } else {
+                        "FACE_BOUND"
+                    };
+                    self.add(format!("{kind}('',#{edge_loop},{})", logical(!reversed)))
+                })
+                .collect::<Vec<_>>();
+
+            let surface = match &face.surface {
+                Some(surface) => self.surface(surface),
+                None => {
+                    // Faces built from polygons always have surfaces, so this only
+                    // covers solids put together by hand
+                    let points = solid.loop_points(face.outer_loop).unwrap();
+                    let origin = self.point(points[0]);
+                    let axis = self.direction(newell_normal(&points));
+                    let reference = self.direction((points[1] - points[0]).normalize());
+                    let placement = self.add(format!(
+                        "AXIS2_PLACEMENT_3D('',#{origin},#{axis},#{reference})"
+                    ));
+                    self.add(format!("PLANE('',#{placement})"))
+                }
+            };
+            let same_sense = face.surface.is_none() || face.same_sense;
+            ids.push(self.add(format!(
+                "ADVANCED_FACE('',{},#{surface},{})",
+                references(&bounds),
+                logical(same_sense != reversed)
+            )));
+        }
+        ids
+    }
+
+    fn direction(&mut self, direction: EVec3) -> usize {
+        self.add(format!(
+            "DIRECTION('',({},{},{}))",
+            real(direction.x),
+            real(direction.y),
+            real(direction.z)
+        ))
+    }
+}
+
+/// The volume enclosed by a shell, which is negative for a void, found from its
+/// vertices as if its faces were flat
+fn shell_volume(solid: &Solid, faces: &[FaceId]) -> f64 {
+    let mut integrals = VolumeIntegrals::new();
+    for face in faces.iter() {
+        for loop_id in solid.face(*face).unwrap().loops.iter() {
+            let points = solid.loop_points(*loop_id).unwrap();
+            for pair in points[1..].windows(2) {
+                integrals.add_triangle(points[0], pair[0], pair[1]);
+            }
+        }
+    }
+    integrals.volume
+}
+
+fn shell_bounds_contain(solid: &Solid, faces: &[FaceId], point: EVec3) -> bool {
+    let points = faces
+        .iter()
+        .flat_map(|face| {
+            solid
+                .loop_points(solid.face(*face).unwrap().outer_loop)
+                .unwrap()
+        })
+        .collect::<Vec<_>>();
+    let inside = |value: f64, axis: fn(&EVec3) -> f64| {
+        points.iter().any(|p| axis(p) <= value) && points.iter().any(|p| axis(p) >= value)
+    };
+    inside(point.x, |p| p.x) && inside(point.y, |p| p.y) && inside(point.z, |p| p.z)
+}
+
+fn newell_normal(points: &[EVec3]) -> EVec3 {
+    points
+        .iter()
+        .zip(points.iter().cycle().skip(1))
+        .fold(EVec3::zero(), |normal, (a, b)| normal + a.cross(b))
+        .normalize()
+}
+
+fn reals(values: &[f64]) -> String {
+    format!(
+        "({})",
+        values
+            .iter()
+            .map(|v| real(*v))
+            .collect::<Vec<_>>()
+            .join(",")
+    )
+}
+
+fn references(ids: &[usize]) -> String {
+    format!(
+        "({})",
+        ids.iter()
+            .map(|id| format!("#{id}"))
+            .collect::<Vec<_>>()
+            .join(",")
+    )
+}
+
+fn string(value: &str) -> String {
+    format!("'{}'", value.replace('\'', "''"))
+}
+
+fn logical(value: bool) -> &'static str {
+    if value {
+        ".T."
+    } else {
+        ".F."
+    }
+}
+
+/// Splits a knot vector into its distinct knots and their multiplicities, as
+/// STEP lists them
+fn knot_runs(knot_vector: &KnotVector) -> (String, String) {
+    let mut runs: Vec<(usize, f64)> = Vec::new();
+    for knot in knot_vector.iter() {
+        match runs.last_mut() {
+            Some((count, last)) if *last == *knot => *count += 1,
+            _ => runs.push((1, *knot)),
+        }
+    }
+
+    (
+        format!(
+            "({})",
+            runs.iter()
+                .map(|(count, _)| count.to_string())
+                .collect::<Vec<_>>()
+                .join(",")
+        ),
+        reals(&runs.iter().map(|(_, knot)| *knot).collect::<Vec<_>>()),
+    )
+}
@@ -0,0 +1,314 @@
+use std::{collections::BTreeMap, f64::consts::PI};
+
+use space::{
+    hspace::{HSpace2, HSpace3},
+    EPlacement3, EVec2, EVec3, EVector, Tolerance, TOL,
+};
+use spline::{nurbs_curve::NurbsCurve, nurbs_surface::NurbsSurface};
+use topology::{
+    entities::{EdgeId, FaceId, VertexId},
+    error::{TopologyError, TopologyResult},
+    solid::Solid,
+};
+
+use crate::{
+    error::{FeatureError, FeatureResult},
+    feature::Combine,
+};
+
+/// The largest angle spanned by one face of a revolved feature's sides. Full turns
+/// are split so that no face closes up on itself.
+const REVOLVE_SPAN: f64 = PI / 2.0;
+
+/// The largest angle spanned by one face of a revolved feature's sides when they
+/// are faceted to be combined with a body
+pub const MAX_REVOLVE_STEP: f64 = PI / 18.0;
+
+/// The number of straight segments a curved edge is divided into when looking for
+/// the edge nearest to a point
+const EDGE_SAMPLES: usize = 16;
+
+/// Checks that a sketch's polygons enclose some area
+pub(crate) fn check_profile(
+    outer: &[EVec2],
+    holes: &[Vec<EVec2>],
+    tolerance: &Tolerance,
+) -> FeatureResult<()> {
+    for polygon in std::iter::once(outer).chain(holes.iter().map(|h| &h[..])) {
+        if polygon.len() < 3 {
+            return Err(FeatureError::InvalidProfile(
+                "polygons need at least three points",
+            ));
+        }
+        if signed_area(polygon).abs() <= tolerance.linear {
+            return Err(FeatureError::InvalidProfile(
+                "polygons must enclose an area",
+            ));
+        }
+    }
+    Ok(())
+}
+
+pub(crate) fn extrude(
+    placement: &EPlacement3,
+    outer: &[EVec2],
+    holes: &[Vec<EVec2>],
+    depth: f64,
+    tolerance: &Tolerance,
+) -> FeatureResult<Solid> {
+    if !depth.is_finite() || depth.abs() <= tolerance.linear {
+        return Err(FeatureError::InvalidParameter("depth", depth));
+    }
+
+    // Negative depths extrude the other way, from a plane moved back by the depth
+    let placement = if depth < 0.0 {
+        EPlacement3::new(
+            placement.origin + placement.z_dir() * depth,
+            placement.x_dir,
+            placement.y_dir,
+        )
+    } else {
+        placement.clone()
+    };
+    Ok(Solid::prism(&placement, outer, holes, depth.abs())?)
+}
+
+/// Revolves a sketch around the Y axis of its placement, turning the positive X
+/// side towards the positive Z side like an extrusion would. The sides are exact
+/// surfaces of revolution, or flat facets spanning at most [`MAX_REVOLVE_STEP`] if
+/// `faceted` is set.
+pub(crate) fn revolve(
+    placement: &EPlacement3,
+    outer: &[EVec2],
+    holes: &[Vec<EVec2>],
+    angle: f64,
+    faceted: bool,
+    tolerance: &Tolerance,
+) -> FeatureResult<Solid> {
+    if !(angle > tolerance.angular && angle <= 2.0 * PI + tolerance.angular) {
+        return Err(FeatureError::InvalidParameter("angle", angle));
+    }
+    if outer
+        .iter()
+        .chain(holes.iter().flatten())
+        .any(|p| p.x <= tolerance.linear)
+    {
+        return Err(FeatureError::InvalidProfile(
+            "revolved sketches must lie on the positive X side of the axis",
+        ));
+    }
+
+    // A full turn joins up with itself and needs no end caps
+    let full = angle >= 2.0 * PI - tolerance.angular;
+    let span = if faceted {
+        MAX_REVOLVE_STEP
+    } else {
+        REVOLVE_SPAN
+    };
+    let steps = ((angle / span).ceil() as usize).max(if full { 3 } else { 1 });
+    let rings = if full { steps } else { steps + 1 };
+
+    let mut points = Vec::new();
+    let mut start_cap = Vec::new();
+    let mut end_cap = Vec::new();
+    let mut sides = Vec::new();
+    for (l, polygon) in std::iter::once(outer)
+        .chain(holes.iter().map(|h| &h[..]))
+        .enumerate()
+    {
+        // Oriented as for a prism, with the sweep in place of the Z axis
+        let mut polygon = polygon.to_vec();
+        if (signed_area(&polygon) > 0.0) != (l == 0) {
+            polygon.reverse();
+        }
+
+        let start = points.len();
+        let n = polygon.len();
+        for k in 0..rings {
+            let (sin, cos) = (angle * k as f64 / steps as f64).sin_cos();
+            for p in polygon.iter() {
+                points.push(placement.to_global(EVec3::new(p.x * cos, p.y, p.x * sin)));
+            }
+        }
+
+        let at = |i: usize, k: usize| start + (k % rings) * n + i % n;
+        start_cap.push((0..n).rev().map(|i| at(i, 0)).collect::<Vec<_>>());
+        end_cap.push((0..n).map(|i| at(i, steps)).collect::<Vec<_>>());
+        for k in 0..steps {
+            sides.extend((0..n).map(|i| [at(i, k), at(i + 1, k), at(i + 1, k + 1), at(i, k + 1)]));
+        }
+    }
+
+    let mut faces = if full {
+        Vec::new()
+    } else {
+        vec![start_cap, end_cap]
+    };
+    let first_side = faces.len();
+    faces.extend(sides.iter().map(|side| vec![side.to_vec()]));
+    let (mut solid, vertices) = Solid::from_polygons_mapped(&points, &faces)?;
+    if faceted {
+        return Ok(solid);
+    }
+
+    // Turning X towards Z is counterclockwise around -Y. Faces are numbered in the
+    // order of their polygons.
+    let axis = EPlacement3::from_axis(placement.origin, -placement.y_dir);
+    let step = angle / steps as f64;
+    let face_ids = solid.faces().map(|(id, _)| id).collect::<Vec<_>>();
+    for (s, side) in sides.iter().enumerate() {
+        set_revolved_surface(
+            &mut solid,
+            face_ids[first_side + s],
+            &axis,
+            step,
+            side.map(|i| points[i]),
+            side.map(|i| vertices[&i]),
+        )?;
+    }
+
+    solid.validate_with(tolerance)?;
+    Ok(solid)
+}
+
+/// Gives a side of a revolved solid the surface swept by its first edge, and
+/// curves and p-curves that follow it. The corners are the ends of the edge before
+/// and after the sweep, in the order of the face's loop.
+fn set_revolved_surface(
+    solid: &mut Solid,
+    face: FaceId,
+    axis: &EPlacement3,
+    step: f64,
+    points: [EVec3; 4],
+    corners: [VertexId; 4],
+) -> TopologyResult<()> {
+    let [a0, b0, b1, a1] = points;
+    let surface = NurbsSurface::revolve(&NurbsCurve::<HSpace3>::line(a0, b0), axis, step);
+    let (min_u, max_u) = (surface.min_u(), surface.max_u());
+    let (min_v, max_v) = (surface.min_v(), surface.max_v());
+    let params = corners
+        .into_iter()
+        .zip([
+            EVec2::new(min_u, min_v),
+            EVec2::new(min_u, max_v),
+            EVec2::new(max_u, max_v),
+            EVec2::new(max_u, min_v),
+        ])
+        .collect::<BTreeMap<_, _>>();
+
+    // The ends of the edge sweep out arcs along the surface's boundaries
+    let boundary = |column: usize| {
+        NurbsCurve::<HSpace3>::new(
+            surface
+                .control_points()
+                .iter()
+                .map(|row| row[column])
+                .collect(),
+            surface.knot_vector_u().clone(),
+        )
+    };
+    let arcs = [
+        (corners[0], corners[3], boundary(0)),
+        (
+            corners[1],
+            corners[2],
+            boundary(surface.control_points()[0].len() - 1),
+        ),
+    ];
+
+    let outward = (b1 - a0).cross(&(a1 - b0));
+    let same_sense = match surface.normal((min_u + max_u) / 2.0, (min_v + max_v) / 2.0) {
+        Some(normal) => normal.dot(&outward) > 0.0,
+        None => true,
+    };
+    solid.set_face_surface(face, surface, same_sense)?;
+
+    for he in solid.face_half_edges(face)? {
+        let half_edge = solid
+            .half_edge(he)
+            .ok_or(TopologyError::MissingHalfEdge(he))?;
+        let (from, edge) = (half_edge.origin, half_edge.edge);
+        let to = solid.half_edge_target(he)?;
+        solid.set_pcurve(he, NurbsCurve::<HSpace2>::line(params[&from], params[&to]))?;
+
+        for (start, end, arc) in arcs.iter() {
+            if (from, to) == (*start, *end) || (from, to) == (*end, *start) {
+                let edge = edge.ok_or(TopologyError::HalfEdgeWithoutEdge(he))?;
+                let first = solid.edge(edge).ok_or(TopologyError::MissingEdge(edge))?;
+                let first = solid
+                    .half_edge(first.half_edges[0])
+                    .ok_or(TopologyError::MissingHalfEdge(first.half_edges[0]))?
+                    .origin;
+                let arc = if first == *start {
+                    arc.clone()
+                } else {
+                    arc.reverse()
+                };
+                solid.set_edge_curve(edge, arc)?;
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Combines a feature's solid with the body made by the features before it
+pub(crate) fn combine(
+    body: Option<&Solid>,
+    tool: Solid,
+    combine: Combine,
+    tolerance: &Tolerance,
+) -> FeatureResult<Solid> {
+    match (body, combine) {
+        (Some(body), combine) => Ok(body.boolean_with(&tool, combine.op(), tolerance)?),
+        (None, Combine::Add) => Ok(tool),
+        (None, _) => Err(FeatureError::NoBody),
+    }
+}
+
+/// Finds the edge of a solid that passes nearest to a point
+pub(crate) fn nearest_edge(solid: &Solid, point: EVec3) -> Option<EdgeId> {
+    solid
+        .edges()
+        .filter_map(|(id, edge)| {
+            let samples = match &edge.curve {
+                Some(curve) => (0..=EDGE_SAMPLES)
+                    .map(|i| {
+                        let t = i as f64 / EDGE_SAMPLES as f64;
+                        curve.point(curve.min_u() + (curve.max_u() - curve.min_u()) * t)
+                    })
+                    .collect::<Vec<_>>(),
+                None => {
+                    let half_edge = edge.half_edges[0];
+                    let origin = solid.half_edge(half_edge)?.origin;
+                    let target = solid.half_edge_target(half_edge).ok()?;
+                    vec![solid.vertex(origin)?.point, solid.vertex(target)?.point]
+                }
+            };
+            let distance = samples
+                .windows(2)
+                .map(|w| segment_distance(point, w[0], w[1]))
+                .fold(f64::MAX, f64::min);
+            Some((id, distance))
+        })
+        .min_by(|a, b| a.1.total_cmp(&b.1))
+        .map(|(id, _)| id)
+}
+
+fn segment_distance(point: EVec3, a: EVec3, b: EVec3) -> f64 {
+    let ab = b - a;
+    let t = if ab.magnitude2() <= TOL * TOL {
+        0.0
+    } else {
+        ((point - a).dot(&ab) / ab.magnitude2()).clamp(0.0, 1.0)
+    };
+    (a + ab * t - point).magnitude()
+}
+
+fn signed_area(polygon: &[EVec2]) -> f64 {
+    polygon
+        .iter()
+        .zip(polygon.iter().cycle().skip(1))
+        .map(|(a, b)| a.x * b.y - b.x * a.y)
+        .sum::<f64>()
+        / 2.0
+}
@@ -0,0 +1,46 @@
+use parameters::error::ParameterError;
+use thiserror::Error;
+use topology::error::{BooleanError, TopologyError};
+
+use crate::feature::FeatureId;
+
+pub type FeatureResult<T> = Result<T, FeatureError>;
+
+#[derive(Debug, Error, PartialEq)]
+pub enum FeatureError {
+    #[error("Feature {0:?} does not exist")]
+    MissingFeature(FeatureId),
+
+    #[error("Feature {0:?} is not an earlier sketch")]
+    InvalidReference(FeatureId),
+
+    #[error("Feature {0:?} is used by feature {1:?}")]
+    InUse(FeatureId, FeatureId),
+
+    #[error("Feature {0:?} is suppressed or after the rollback bar")]
+    InactiveInput(FeatureId),
+
+    #[error("Feature {0:?} failed to build")]
+    FailedInput(FeatureId),
+
+    #[error("There is no body to modify")]
+    NoBody,
+
+    #[error("Missing parameter `{0}`")]
+    MissingParameter(&'static str),
+
+    #[error("Parameter `{0}` is out of range: {1}")]
+    InvalidParameter(&'static str, f64),
+
+    #[error("Could not evaluate parameter `{0}`: {1}")]
+    Expression(String, ParameterError),
+
+    #[error("Invalid sketch profile: {0}")]
+    InvalidProfile(&'static str),
+
+    #[error("Could not build the solid: {0}")]
+    Topology(#[from] TopologyError),
+
+    #[error("Could not combine the solids: {0}")]
+    Boolean(#[from] BooleanError),
+}
@@ -0,0 +1,117 @@
+use std::collections::BTreeMap;
+
+use parameters::{expression::Expression, unit::Unit};
+use serde::{Deserialize, Serialize};
+use space::{EPlacement3, EVec2, EVec3};
+use topology::mesh_boolean::BooleanOp;
+
+use crate::error::{FeatureError, FeatureResult};
+
+/// Identifies a feature for as long as it is in its tree, wherever it is moved
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
+pub struct FeatureId(pub(crate) u64);
+
+/// How a feature's solid is combined with the body built by the features before it
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Combine {
+    Add,
+    Cut,
+    Intersect,
+}
+impl Combine {
+    pub(crate) fn op(&self) -> BooleanOp {
+        match self {
+            Self::Add => BooleanOp::Union,
+            Self::Cut => BooleanOp::Subtract,
+            Self::Intersect => BooleanOp::Intersect,
+        }
+    }
+}
+
+/// What a feature does. Features that build solids add to or modify a single body,
+/// made by the features before them in history order. Sizes and angles are kept
+/// as the feature's parameters, so only the shape of the operation is here.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum FeatureKind {
+    /// A closed polygon with optional holes, in the XY plane of a placement. The
+    /// polygons may run in either direction.
+    Sketch {
+        placement: EPlacement3,
+        outer: Vec<EVec2>,
+        holes: Vec<Vec<EVec2>>,
+    },
+
+    /// Sweeps a sketch along the Z axis of its placement by the `depth` parameter,
+    /// which may be negative
+    Extrude { sketch: FeatureId, combine: Combine },
+
+    /// Sweeps a sketch around the Y axis of its placement by the `angle`
+    /// parameter, in radians, turning the positive X side towards the positive Z
+    /// side. The sketch must lie on the positive X side of the axis. The swept
+    /// sides are exact surfaces of revolution, unless they are combined with a
+    /// body, where they are faceted with flat faces spanning at most
+    /// [`crate::build::MAX_REVOLVE_STEP`].
+    Revolve { sketch: FeatureId, combine: Combine },
+
+    /// Rounds the body's edge nearest to a point by the `radius` parameter
+    Fillet { edge: EVec3 },
+
+    /// Bevels the body's edge nearest to a point by the `distance` parameter
+    Chamfer { edge: EVec3 },
+}
+impl FeatureKind {
+    /// The names of the parameters the feature needs
+    pub fn parameter_names(&self) -> &'static [&'static str] {
+        match self {
+            Self::Sketch { .. } => &[],
+            Self::Extrude { .. } => &["depth"],
+            Self::Revolve { .. } => &["angle"],
+            Self::Fillet { .. } => &["radius"],
+            Self::Chamfer { .. } => &["distance"],
+        }
+    }
+
+    /// The unit that plain numbers in expressions for a parameter are taken to be
+    /// in. Lengths are in millimeters and angles in degrees, though angle
+    /// parameters are kept in radians.
+    pub fn parameter_unit(name: &str) -> Unit {
+        match name {
+            "angle" => Unit::Degree,
+            _ => Unit::Millimeter,
+        }
+    }
+
+    /// The sketch the feature is built from, if any
+    pub fn sketch(&self) -> Option<FeatureId> {
+        match self {
+            Self::Extrude { sketch, .. } | Self::Revolve { sketch, .. } => Some(*sketch),
+            _ => None,
+        }
+    }
+
+    /// Whether the feature builds or modifies the body, rather than only
+    /// describing geometry for other features
+    pub fn is_solid(&self) -> bool {
+        !matches!(self, Self::Sketch { .. })
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Feature {
+    pub id: FeatureId,
+    pub name: String,
+    pub kind: FeatureKind,
+    pub parameters: BTreeMap<String, f64>,
+
+    /// Expressions that set parameters from the values of a parameter table
+    pub expressions: BTreeMap<String, Expression>,
+    pub suppressed: bool,
+}
+impl Feature {
+    pub fn parameter(&self, name: &'static str) -> FeatureResult<f64> {
+        self.parameters
+            .get(name)
+            .copied()
+            .ok_or(FeatureError::MissingParameter(name))
+    }
+}
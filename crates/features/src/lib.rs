@@ -0,0 +1,7 @@
+//! Parametric part modelling: a history of sketches and solid features, kept with
+//! the parameters they were made with and rebuilt whenever those change.
+
+pub mod build;
+pub mod error;
+pub mod feature;
+pub mod tree;
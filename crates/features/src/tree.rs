@@ -0,0 +1,736 @@
+use std::collections::{BTreeMap, HashMap, HashSet};
+
+use parameters::{expression::Expression, Values};
+use serde::{Deserialize, Serialize};
+use space::{EPlacement3, EVec2, Tolerance};
+use topology::solid::Solid;
+
+use crate::{
+    build::{check_profile, combine, extrude, nearest_edge, revolve},
+    error::{FeatureError, FeatureResult},
+    feature::{Feature, FeatureId, FeatureKind},
+};
+
+#[derive(Debug)]
+enum Output {
+    Profile,
+    Body(Solid),
+}
+
+/// A sketch's placement, outer polygon and holes
+type Profile<'a> = (&'a EPlacement3, &'a [EVec2], &'a [Vec<EVec2>]);
+
+/// A feature's last build, along with the builds of its inputs it used
+#[derive(Debug)]
+struct Built {
+    generation: u64,
+    inputs: Vec<(FeatureId, Option<u64>)>,
+    output: FeatureResult<Output>,
+}
+
+/// A part's features in history order. Each feature depends on the sketch it
+/// references and on the body built by the active solid features before it.
+/// Features after the rollback bar and suppressed features are inactive: they
+/// keep their place in the history but aren't built.
+///
+/// Changes only mark features as changed. [`FeatureTree::regenerate`] then
+/// rebuilds them along with the features that depend on them, and keeps the
+/// results of the rest.
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct FeatureTree {
+    features: Vec<Feature>,
+    next_id: u64,
+    rollback: Option<usize>,
+
+    /// The tolerances features are built to, which are kept by the document
+    #[serde(skip)]
+    tolerance: Tolerance,
+    #[serde(skip)]
+    generation: u64,
+    #[serde(skip)]
+    built: HashMap<FeatureId, Built>,
+    #[serde(skip)]
+    dirty: HashSet<FeatureId>,
+}
+impl Clone for FeatureTree {
+    /// Clones the features only. The clone builds them again when it is
+    /// regenerated.
+    fn clone(&self) -> Self {
+        Self {
+            features: self.features.clone(),
+            next_id: self.next_id,
+            rollback: self.rollback,
+            tolerance: self.tolerance,
+            ..Default::default()
+        }
+    }
+}
+impl PartialEq for FeatureTree {
+    fn eq(&self, other: &Self) -> bool {
+        self.features == other.features
+            && self.next_id == other.next_id
+            && self.rollback == other.rollback
+    }
+}
+impl FeatureTree {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn features(&self) -> &[Feature] {
+        &self.features
+    }
+
+    pub fn feature(&self, id: FeatureId) -> Option<&Feature> {
+        self.features.iter().find(|feature| feature.id == id)
+    }
+
+    pub fn index(&self, id: FeatureId) -> FeatureResult<usize> {
+        self.features
+            .iter()
+            .position(|feature| feature.id == id)
+            .ok_or(FeatureError::MissingFeature(id))
+    }
+
+    /// The number of features before the rollback bar
+    pub fn rollback(&self) -> usize {
+        self.rollback
+            .unwrap_or(self.features.len())
+            .min(self.features.len())
+    }
+
+    pub fn tolerance(&self) -> &Tolerance {
+        &self.tolerance
+    }
+
+    /// Changes the tolerances features are built to. Every feature is built
+    /// again when they change.
+    pub fn set_tolerance(&mut self, tolerance: Tolerance) {
+        if tolerance != self.tolerance {
+            self.tolerance = tolerance;
+            self.dirty.extend(self.features.iter().map(|f| f.id));
+        }
+    }
+
+    /// Moves the rollback bar to after the first `position` features, or to the
+    /// end of the history if `None`
+    pub fn set_rollback(&mut self, position: Option<usize>) {
+        self.rollback = position.filter(|position| *position < self.features.len());
+    }
+
+    /// Adds a feature at the rollback bar, moving the bar past it
+    pub fn insert(
+        &mut self,
+        name: &str,
+        kind: FeatureKind,
+        parameters: &[(&str, f64)],
+    ) -> FeatureResult<FeatureId> {
+        let index = self.rollback();
+        self.check_reference(&kind, index)?;
+
+        let id = FeatureId(self.next_id);
+        self.next_id += 1;
+        self.features.insert(
+            index,
+            Feature {
+                id,
+                name: name.to_string(),
+                kind,
+                parameters: parameters
+                    .iter()
+                    .map(|(name, value)| (name.to_string(), *value))
+                    .collect::<BTreeMap<_, _>>(),
+                expressions: BTreeMap::new(),
+                suppressed: false,
+            },
+        );
+        if let Some(rollback) = self.rollback.as_mut() {
+            *rollback += 1;
+        }
+        self.dirty.insert(id);
+        Ok(id)
+    }
+
+    /// Removes a feature that no other feature references
+    pub fn remove(&mut self, id: FeatureId) -> FeatureResult<Feature> {
+        let index = self.index(id)?;
+        if let Some(user) = self.features.iter().find(|f| f.kind.sketch() == Some(id)) {
+            return Err(FeatureError::InUse(id, user.id));
+        }
+
+        if let Some(rollback) = self.rollback.as_mut() {
+            if index < *rollback {
+                *rollback -= 1;
+            }
+        }
+        self.built.remove(&id);
+        self.dirty.remove(&id);
+        Ok(self.features.remove(index))
+    }
+
+    pub fn rename(&mut self, id: FeatureId, name: &str) -> FeatureResult<()> {
+        let index = self.index(id)?;
+        self.features[index].name = name.to_string();
+        Ok(())
+    }
+
+    pub fn set_parameter(&mut self, id: FeatureId, name: &str, value: f64) -> FeatureResult<()> {
+        let index = self.index(id)?;
+        self.features[index]
+            .parameters
+            .insert(name.to_string(), value);
+        self.dirty.insert(id);
+        Ok(())
+    }
+
+    /// Drives a parameter by an expression, or leaves it at its current value if
+    /// `None`. The expression is evaluated by [`Self::apply_parameters`].
+    pub fn set_expression(
+        &mut self,
+        id: FeatureId,
+        name: &str,
+        expression: Option<Expression>,
+    ) -> FeatureResult<()> {
+        let index = self.index(id)?;
+        let expressions = &mut self.features[index].expressions;
+        match expression {
+            Some(expression) => expressions.insert(name.to_string(), expression),
+            None => expressions.remove(name),
+        };
+        Ok(())
+    }
+
+    /// Sets the parameters driven by expressions from the values of a parameter
+    /// table. Only parameters whose values change mark their features for
+    /// rebuilding. Parameters whose expressions fail keep their values, and are
+    /// returned with the reason.
+    pub fn apply_parameters(&mut self, values: &Values) -> Vec<(FeatureId, FeatureError)> {
+        let mut errors = Vec::new();
+        for feature in self.features.iter_mut() {
+            for (name, expression) in feature.expressions.iter() {
+                let value = expression
+                    .evaluate(values)
+                    .and_then(|value| value.base_value(FeatureKind::parameter_unit(name)));
+                match value {
+                    Ok(value) => {
+                        if feature.parameters.get(name) != Some(&value) {
+                            feature.parameters.insert(name.clone(), value);
+                            self.dirty.insert(feature.id);
+                        }
+                    }
+                    Err(error) => {
+                        errors.push((feature.id, FeatureError::Expression(name.clone(), error)))
+                    }
+                }
+            }
+        }
+        errors
+    }
+
+    pub fn set_kind(&mut self, id: FeatureId, kind: FeatureKind) -> FeatureResult<()> {
+        let index = self.index(id)?;
+        self.check_reference(&kind, index)?;
+        self.features[index].kind = kind;
+        self.dirty.insert(id);
+        Ok(())
+    }
+
+    pub fn set_suppressed(&mut self, id: FeatureId, suppressed: bool) -> FeatureResult<()> {
+        let index = self.index(id)?;
+        if self.features[index].suppressed != suppressed {
+            self.features[index].suppressed = suppressed;
+            self.dirty.insert(id);
+        }
+        Ok(())
+    }
+
+    /// Moves a feature to another place in the history. Features must stay after
+    /// the sketches they reference. The rollback bar keeps its position.
+    pub fn move_feature(&mut self, id: FeatureId, index: usize) -> FeatureResult<()> {
+        let from = self.index(id)?;
+        let feature = self.features.remove(from);
+        self.features
+            .insert(index.min(self.features.len()), feature);
+
+        let error = self
+            .features
+            .iter()
+            .enumerate()
+            .find_map(|(i, feature)| self.check_reference(&feature.kind, i).err());
+        if let Some(error) = error {
+            let feature = self.features.remove(index.min(self.features.len() - 1));
+            self.features.insert(from, feature);
+            return Err(error);
+        }
+        self.dirty.insert(id);
+        Ok(())
+    }
+
+    /// Builds the features before the rollback bar that changed or whose inputs
+    /// changed since they were last built, returning the ones that were built
+    pub fn regenerate(&mut self) -> Vec<FeatureId> {
+        let mut rebuilt = Vec::new();
+        for index in 0..self.rollback() {
+            let id = self.features[index].id;
+            if self.features[index].suppressed {
+                self.built.remove(&id);
+                self.dirty.remove(&id);
+                continue;
+            }
+
+            let inputs = self
+                .inputs(index)
+                .into_iter()
+                .map(|input| (input, self.built.get(&input).map(|b| b.generation)))
+                .collect::<Vec<_>>();
+            let current = match self.built.get(&id) {
+                Some(built) => built.inputs == inputs && !self.dirty.contains(&id),
+                None => false,
+            };
+            if current {
+                continue;
+            }
+
+            let output = self.build(index);
+            self.generation += 1;
+            self.built.insert(
+                id,
+                Built {
+                    generation: self.generation,
+                    inputs,
+                    output,
+                },
+            );
+            self.dirty.remove(&id);
+            rebuilt.push(id);
+        }
+        rebuilt
+    }
+
+    /// Why a feature failed to build when it was last regenerated
+    pub fn error(&self, id: FeatureId) -> Option<&FeatureError> {
+        self.built.get(&id)?.output.as_ref().err()
+    }
+
+    /// Whether a feature was built, successfully or not, when it was last
+    /// regenerated
+    pub fn is_built(&self, id: FeatureId) -> bool {
+        self.built.contains_key(&id)
+    }
+
+    /// The body made by the features before the rollback bar: that of the last
+    /// active solid feature that was built successfully
+    pub fn body(&self) -> Option<&Solid> {
+        self.features[..self.rollback()]
+            .iter()
+            .rev()
+            .filter(|feature| feature.kind.is_solid() && !feature.suppressed)
+            .find_map(|feature| match self.built.get(&feature.id) {
+                Some(Built {
+                    output: Ok(Output::Body(body)),
+                    ..
+                }) => Some(body),
+                _ => None,
+            })
+    }
+
+    /// Checks that a feature at an index references an earlier sketch
+    fn check_reference(&self, kind: &FeatureKind, index: usize) -> FeatureResult<()> {
+        match kind.sketch() {
+            Some(sketch)
+                if !self.features[..index].iter().any(|feature| {
+                    feature.id == sketch && matches!(feature.kind, FeatureKind::Sketch { .. })
+                }) =>
+            {
+                Err(FeatureError::InvalidReference(sketch))
+            }
+            _ => Ok(()),
+        }
+    }
+
+    /// The active solid feature before an index, whose body the feature at the
+    /// index builds on
+    fn previous_body(&self, index: usize) -> Option<FeatureId> {
+        self.features[..index]
+            .iter()
+            .rev()
+            .find(|feature| feature.kind.is_solid() && !feature.suppressed)
+            .map(|feature| feature.id)
+    }
+
+    fn inputs(&self, index: usize) -> Vec<FeatureId> {
+        let kind = &self.features[index].kind;
+        let mut inputs = kind.sketch().into_iter().collect::<Vec<_>>();
+        if kind.is_solid() {
+            inputs.extend(self.previous_body(index));
+        }
+        inputs
+    }
+
+    fn input(&self, id: FeatureId) -> FeatureResult<&Output> {
+        match self.built.get(&id) {
+            Some(built) => built.output.as_ref().or(Err(FeatureError::FailedInput(id))),
+            None => Err(FeatureError::InactiveInput(id)),
+        }
+    }
+
+    /// The body a feature builds on
+    fn body_input(&self, index: usize) -> FeatureResult<Option<&Solid>> {
+        match self.previous_body(index) {
+            Some(id) => match self.input(id)? {
+                Output::Body(body) => Ok(Some(body)),
+                Output::Profile => unreachable!("solid features build bodies"),
+            },
+            None => Ok(None),
+        }
+    }
+
+    fn sketch_input(&self, id: FeatureId) -> FeatureResult<Profile<'_>> {
+        self.input(id)?;
+        match &self
+            .feature(id)
+            .ok_or(FeatureError::MissingFeature(id))?
+            .kind
+        {
+            FeatureKind::Sketch {
+                placement,
+                outer,
+                holes,
+            } => Ok((placement, outer, holes)),
+            _ => Err(FeatureError::InvalidReference(id)),
+        }
+    }
+
+    fn build(&self, index: usize) -> FeatureResult<Output> {
+        let feature = &self.features[index];
+        match &feature.kind {
+            FeatureKind::Sketch { outer, holes, .. } => {
+                check_profile(outer, holes, &self.tolerance)?;
+                Ok(Output::Profile)
+            }
+            FeatureKind::Extrude {
+                sketch: id,
+                combine: how,
+            } => {
+                let depth = feature.parameter("depth")?;
+                let (placement, outer, holes) = self.sketch_input(*id)?;
+                let tool = extrude(placement, outer, holes, depth, &self.tolerance)?;
+                let body = self.body_input(index)?;
+                Ok(Output::Body(combine(body, tool, *how, &self.tolerance)?))
+            }
+            FeatureKind::Revolve {
+                sketch: id,
+                combine: how,
+            } => {
+                let angle = feature.parameter("angle")?;
+                let (placement, outer, holes) = self.sketch_input(*id)?;
+                let body = self.body_input(index)?;
+                // Booleans work on tessellations, which need flat faces, and give
+                // a faceted result anyway
+                let faceted = body.is_some();
+                let tool = revolve(placement, outer, holes, angle, faceted, &self.tolerance)?;
+                Ok(Output::Body(combine(body, tool, *how, &self.tolerance)?))
+            }
+            FeatureKind::Fillet { edge } => {
+                let radius = feature.parameter("radius")?;
+                if !(radius > 0.0 && radius.is_finite()) {
+                    return Err(FeatureError::InvalidParameter("radius", radius));
+                }
+                let body = self.body_input(index)?.ok_or(FeatureError::NoBody)?;
+                let edge = nearest_edge(body, *edge).ok_or(FeatureError::NoBody)?;
+                Ok(Output::Body(body.fillet_with(
+                    edge,
+                    radius,
+                    &self.tolerance,
+                )?))
+            }
+            FeatureKind::Chamfer { edge } => {
+                let distance = feature.parameter("distance")?;
+                if !(distance > 0.0 && distance.is_finite()) {
+                    return Err(FeatureError::InvalidParameter("distance", distance));
+                }
+                let body = self.body_input(index)?.ok_or(FeatureError::NoBody)?;
+                let edge = nearest_edge(body, *edge).ok_or(FeatureError::NoBody)?;
+                Ok(Output::Body(body.chamfer_with(
+                    edge,
+                    distance,
+                    distance,
+                    &self.tolerance,
+                )?))
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::f64::consts::PI;
+
+    use parameters::{error::ParameterError, expression::Expression, unit::Unit, ParameterTable};
+    use space::{EPlacement3, EVec2, EVec3, MassProperties, Tolerance};
+    use topology::{mesh::TriMesh, solid::Solid};
+
+    use crate::{
+        error::FeatureError,
+        feature::{Combine, FeatureId, FeatureKind},
+        tree::FeatureTree,
+    };
+
+    fn rectangle(min: (f64, f64), max: (f64, f64)) -> FeatureKind {
+        FeatureKind::Sketch {
+            placement: EPlacement3::default(),
+            outer: vec![
+                EVec2::new(min.0, min.1),
+                EVec2::new(max.0, min.1),
+                EVec2::new(max.0, max.1),
+                EVec2::new(min.0, max.1),
+            ],
+            holes: Vec::new(),
+        }
+    }
+
+    fn volume(solid: &Solid) -> f64 {
+        let mesh = TriMesh::from_solid(solid).unwrap();
+        MassProperties::from_triangles(
+            (0..mesh.triangles.len()).map(|i| mesh.triangle_points(i)),
+            1.0,
+        )
+        .volume
+    }
+
+    /// A 2 x 1 x 1 block with a chamfered corner and a pocket cut into its base
+    fn tree() -> (FeatureTree, [FeatureId; 5]) {
+        let mut tree = FeatureTree::new();
+        let base = tree
+            .insert("Base", rectangle((0.0, 0.0), (2.0, 1.0)), &[])
+            .unwrap();
+        let block = tree
+            .insert(
+                "Block",
+                FeatureKind::Extrude {
+                    sketch: base,
+                    combine: Combine::Add,
+                },
+                &[("depth", 1.0)],
+            )
+            .unwrap();
+        let corner = tree
+            .insert(
+                "Corner",
+                FeatureKind::Chamfer {
+                    edge: EVec3::new(2.0, 0.0, 0.5),
+                },
+                &[("distance", 0.2)],
+            )
+            .unwrap();
+        let outline = tree
+            .insert("Outline", rectangle((0.5, 0.25), (1.0, 0.75)), &[])
+            .unwrap();
+        let pocket = tree
+            .insert(
+                "Pocket",
+                FeatureKind::Extrude {
+                    sketch: outline,
+                    combine: Combine::Cut,
+                },
+                &[("depth", 0.5)],
+            )
+            .unwrap();
+        (tree, [base, block, corner, outline, pocket])
+    }
+
+    #[test]
+    fn regenerate_changed_features() {
+        let (mut tree, [base, block, corner, outline, pocket]) = tree();
+        assert_eq!(tree.regenerate(), [base, block, corner, outline, pocket]);
+        assert!((volume(tree.body().unwrap()) - (2.0 - 0.02 - 0.125)).abs() < 1e-9);
+        assert!(tree.regenerate().is_empty());
+
+        // Only what depends on a change is built again
+        tree.set_parameter(pocket, "depth", 0.25).unwrap();
+        assert_eq!(tree.regenerate(), [pocket]);
+        tree.set_kind(outline, rectangle((0.5, 0.25), (1.5, 0.75)))
+            .unwrap();
+        assert_eq!(tree.regenerate(), [outline, pocket]);
+        tree.set_parameter(block, "depth", 2.0).unwrap();
+        assert_eq!(tree.regenerate(), [block, corner, pocket]);
+        assert!((volume(tree.body().unwrap()) - (4.0 - 0.04 - 0.125)).abs() < 1e-9);
+
+        // Failures are reported on the feature and the features that use it
+        tree.set_parameter(corner, "distance", -1.0).unwrap();
+        assert_eq!(tree.regenerate(), [corner, pocket]);
+        assert_eq!(
+            tree.error(corner),
+            Some(&FeatureError::InvalidParameter("distance", -1.0))
+        );
+        assert_eq!(tree.error(pocket), Some(&FeatureError::FailedInput(corner)));
+        assert!(tree.body().is_some());
+
+        // Everything is built again to new tolerances
+        tree.set_tolerance(Tolerance::new(1e-6, 1e-4));
+        assert_eq!(tree.regenerate().len(), 5);
+        tree.set_tolerance(Tolerance::new(1e-6, 1e-4));
+        assert!(tree.regenerate().is_empty());
+    }
+
+    #[test]
+    fn suppress_reorder_and_roll_back() {
+        let (mut tree, [base, block, corner, outline, pocket]) = tree();
+        tree.regenerate();
+
+        tree.set_suppressed(corner, true).unwrap();
+        assert_eq!(tree.regenerate(), [pocket]);
+        assert!((volume(tree.body().unwrap()) - (2.0 - 0.125)).abs() < 1e-9);
+        assert!(!tree.is_built(corner));
+
+        // Features built beyond the rollback bar are kept, and only rebuilt if
+        // something they depend on changed while they were rolled back
+        tree.set_rollback(Some(2));
+        assert!(tree.regenerate().is_empty());
+        assert!((volume(tree.body().unwrap()) - 2.0).abs() < 1e-9);
+        tree.set_suppressed(corner, false).unwrap();
+        tree.set_rollback(None);
+        assert_eq!(tree.regenerate(), [corner, pocket]);
+
+        // New features go in at the rollback bar
+        tree.set_rollback(Some(2));
+        let edge = tree
+            .insert(
+                "Edge",
+                FeatureKind::Chamfer {
+                    edge: EVec3::new(0.0, 0.0, 0.5),
+                },
+                &[("distance", 0.2)],
+            )
+            .unwrap();
+        assert_eq!(tree.rollback(), 3);
+        assert_eq!(tree.features()[2].id, edge);
+        tree.set_rollback(None);
+        assert_eq!(tree.regenerate(), [edge, corner, pocket]);
+
+        // Sketches may move anywhere before the features that use them
+        tree.move_feature(outline, 0).unwrap();
+        assert_eq!(tree.regenerate(), [outline, pocket]);
+        assert_eq!(
+            tree.move_feature(block, 0),
+            Err(FeatureError::InvalidReference(base))
+        );
+        assert_eq!(tree.features()[2].id, block);
+        assert_eq!(
+            tree.remove(base).unwrap_err(),
+            FeatureError::InUse(base, block)
+        );
+        tree.remove(pocket).unwrap();
+        tree.remove(outline).unwrap();
+        assert!(tree.regenerate().is_empty());
+    }
+
+    #[test]
+    fn revolve() {
+        let mut tree = FeatureTree::new();
+        let ring = tree
+            .insert("Ring", rectangle((1.0, 0.0), (2.0, 1.0)), &[])
+            .unwrap();
+        let turn = tree
+            .insert(
+                "Turn",
+                FeatureKind::Revolve {
+                    sketch: ring,
+                    combine: Combine::Add,
+                },
+                &[("angle", 2.0 * PI)],
+            )
+            .unwrap();
+        tree.regenerate();
+
+        // The sides are exact surfaces of revolution split into quarter turns, and
+        // the outer one lies on a cylinder around the Y axis
+        let body = tree.body().unwrap();
+        assert_eq!((body.num_faces(), body.num_shells()), (16, 1));
+        let radius = |p: EVec3| p.x.hypot(p.z);
+        let outer = body
+            .faces()
+            .filter_map(|(_, f)| f.surface.as_ref())
+            .filter(|s| {
+                [s.min_v(), s.max_v()]
+                    .iter()
+                    .all(|v| (radius(s.point(s.min_u(), *v)) - 2.0).abs() <= 1e-9)
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(outer.len(), 4);
+        for surface in outer {
+            for (u, v) in [(0.3, 0.5), (0.8, 0.1)] {
+                let point = surface.point(
+                    surface.min_u() + (surface.max_u() - surface.min_u()) * u,
+                    surface.min_v() + (surface.max_v() - surface.min_v()) * v,
+                );
+                assert!((radius(point) - 2.0).abs() <= 1e-9);
+            }
+        }
+
+        tree.set_parameter(turn, "angle", PI).unwrap();
+        tree.regenerate();
+        assert_eq!(tree.body().unwrap().num_faces(), 10);
+
+        // Parameters driven by expressions only change when their values do
+        let mut table = ParameterTable::new();
+        table.set("sweep", "90", Unit::Degree).unwrap();
+        tree.set_expression(turn, "angle", Some(Expression::parse("sweep").unwrap()))
+            .unwrap();
+        assert!(tree.apply_parameters(&table.evaluate()).is_empty());
+        assert_eq!(tree.regenerate(), [turn]);
+        assert_eq!(tree.body().unwrap().num_faces(), 6);
+        assert!(tree.apply_parameters(&table.evaluate()).is_empty());
+        assert!(tree.regenerate().is_empty());
+
+        table.set("sweep", "1 mm", Unit::None).unwrap();
+        assert!(matches!(
+            &tree.apply_parameters(&table.evaluate())[..],
+            [(id, FeatureError::Expression(name, ParameterError::IncompatibleUnits(_)))]
+                if *id == turn && name == "angle"
+        ));
+
+        tree.set_kind(ring, rectangle((-1.0, 0.0), (2.0, 1.0)))
+            .unwrap();
+        tree.regenerate();
+        assert!(matches!(
+            tree.error(turn),
+            Some(FeatureError::InvalidProfile(_))
+        ));
+
+        // Revolved into a body, the sides are faceted for the boolean
+        let (mut block, _) = self::tree();
+        block.regenerate();
+        let before = volume(block.body().unwrap());
+        let groove = block
+            .insert("Groove", rectangle((1.5, 0.2), (1.8, 0.4)), &[])
+            .unwrap();
+        let turn = block
+            .insert(
+                "Turn",
+                FeatureKind::Revolve {
+                    sketch: groove,
+                    combine: Combine::Cut,
+                },
+                &[("angle", PI / 2.0)],
+            )
+            .unwrap();
+        block.regenerate();
+        assert!(block.error(turn).is_none());
+        assert!(volume(block.body().unwrap()) < before);
+    }
+
+    #[test]
+    fn serialize() {
+        let (mut tree, [_, block, ..]) = tree();
+        tree.set_rollback(Some(3));
+        tree.set_suppressed(block, true).unwrap();
+
+        let text = serde_json::to_string(&tree).unwrap();
+        let mut loaded: FeatureTree = serde_json::from_str(&text).unwrap();
+        assert_eq!(loaded, tree);
+
+        // Nothing built is saved, so everything is built again
+        assert_eq!(loaded.regenerate().len(), 2);
+    }
+}
@@ -0,0 +1,39 @@
+use thiserror::Error;
+
+pub type ParameterResult<T> = Result<T, ParameterError>;
+
+#[derive(Debug, Clone, Error, PartialEq)]
+pub enum ParameterError {
+    #[error("Syntax error at {position}: {message}")]
+    Syntax {
+        position: usize,
+        message: &'static str,
+    },
+
+    #[error("`{0}` is not a valid parameter name")]
+    InvalidName(String),
+
+    #[error("Unknown parameter `{0}`")]
+    UnknownParameter(String),
+
+    #[error("Unknown unit `{0}`")]
+    UnknownUnit(String),
+
+    #[error("Unknown function `{0}`")]
+    UnknownFunction(String),
+
+    #[error("Function `{0}` takes {1} arguments")]
+    WrongArguments(&'static str, usize),
+
+    #[error("Parameters depend on each other: {}", .0.join(" -> "))]
+    Cycle(Vec<String>),
+
+    #[error("Parameter `{0}` could not be evaluated")]
+    FailedInput(String),
+
+    #[error("Incompatible units: {0}")]
+    IncompatibleUnits(&'static str),
+
+    #[error("The result is not a finite number")]
+    NotFinite,
+}
@@ -0,0 +1,443 @@
+use std::{collections::BTreeSet, f64::consts::PI};
+
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    error::{ParameterError, ParameterResult},
+    unit::{Dimension, Quantity, Unit},
+    Values,
+};
+
+/// The functions expressions can call, with the number of arguments they take
+const FUNCTIONS: [(&str, usize); 10] = [
+    ("abs", 1),
+    ("sqrt", 1),
+    ("sin", 1),
+    ("cos", 1),
+    ("tan", 1),
+    ("asin", 1),
+    ("acos", 1),
+    ("atan", 1),
+    ("min", 2),
+    ("max", 2),
+];
+
+/// Names that mean something in expressions already, so parameters can't have
+/// them
+pub(crate) fn is_reserved(name: &str) -> bool {
+    name == "pi"
+        || Unit::from_symbol(name).is_ok()
+        || FUNCTIONS.iter().any(|(function, _)| *function == name)
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Node {
+    Number(f64),
+    Quantity(f64, Unit),
+    Parameter(String),
+    Negate(Box<Node>),
+    Binary(char, Box<Node>, Box<Node>),
+    Call(&'static str, Vec<Node>),
+}
+
+/// An arithmetic expression of numbers, numbers with units, parameters and
+/// functions, such as `2 * height + 5 mm`. Expressions are saved as the text
+/// they were written as.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(try_from = "String", into = "String")]
+pub struct Expression {
+    text: String,
+    root: Node,
+}
+impl Expression {
+    pub fn parse(text: &str) -> ParameterResult<Self> {
+        let tokens = tokenize(text)?;
+        let mut parser = Parser {
+            tokens: &tokens,
+            next: 0,
+            end: text.len(),
+        };
+        let root = parser.sum()?;
+        if parser.next < tokens.len() {
+            return Err(parser.error("unexpected input after the expression"));
+        }
+        Ok(Self {
+            text: text.to_string(),
+            root,
+        })
+    }
+
+    pub fn text(&self) -> &str {
+        &self.text
+    }
+
+    /// The names of the parameters the expression uses
+    pub fn references(&self) -> BTreeSet<&str> {
+        fn visit<'a>(node: &'a Node, names: &mut BTreeSet<&'a str>) {
+            match node {
+                Node::Parameter(name) => {
+                    names.insert(name);
+                }
+                Node::Negate(a) => visit(a, names),
+                Node::Binary(_, a, b) => {
+                    visit(a, names);
+                    visit(b, names);
+                }
+                Node::Call(_, arguments) => arguments.iter().for_each(|a| visit(a, names)),
+                Node::Number(_) | Node::Quantity(..) => {}
+            }
+        }
+
+        let mut names = BTreeSet::new();
+        visit(&self.root, &mut names);
+        names
+    }
+
+    /// Evaluates the expression with the values of a parameter table
+    pub fn evaluate(&self, values: &Values) -> ParameterResult<Quantity> {
+        let result = evaluate(&self.root, values)?;
+        if result.value.is_finite() {
+            Ok(result)
+        } else {
+            Err(ParameterError::NotFinite)
+        }
+    }
+}
+impl TryFrom<String> for Expression {
+    type Error = ParameterError;
+
+    fn try_from(text: String) -> ParameterResult<Self> {
+        Self::parse(&text)
+    }
+}
+impl From<Expression> for String {
+    fn from(expression: Expression) -> Self {
+        expression.text
+    }
+}
+
+fn evaluate(node: &Node, values: &Values) -> ParameterResult<Quantity> {
+    Ok(match node {
+        Node::Number(value) => Quantity::number(*value),
+        Node::Quantity(value, unit) => Quantity::new(*value, *unit),
+        Node::Parameter(name) => match values.get(name) {
+            Some(Ok(value)) => *value,
+            Some(Err(_)) => return Err(ParameterError::FailedInput(name.clone())),
+            None => return Err(ParameterError::UnknownParameter(name.clone())),
+        },
+        Node::Negate(a) => {
+            let a = evaluate(a, values)?;
+            Quantity {
+                value: -a.value,
+                ..a
+            }
+        }
+        Node::Binary(op, a, b) => {
+            let (a, b) = (evaluate(a, values)?, evaluate(b, values)?);
+            match op {
+                '+' | '-' => {
+                    if a.dimension != b.dimension {
+                        return Err(ParameterError::IncompatibleUnits(
+                            "only like quantities can be added or subtracted",
+                        ));
+                    }
+                    let value = if *op == '+' {
+                        a.value + b.value
+                    } else {
+                        a.value - b.value
+                    };
+                    Quantity { value, ..a }
+                }
+                '*' => Quantity {
+                    value: a.value * b.value,
+                    dimension: a.dimension + b.dimension,
+                },
+                '/' => Quantity {
+                    value: a.value / b.value,
+                    dimension: a.dimension - b.dimension,
+                },
+                '^' => {
+                    if !b.dimension.is_none() {
+                        return Err(ParameterError::IncompatibleUnits(
+                            "exponents must be plain numbers",
+                        ));
+                    }
+                    let power = b.value.round();
+                    if !a.dimension.is_none() && (b.value - power).abs() > 0.0 {
+                        return Err(ParameterError::IncompatibleUnits(
+                            "quantities with units can only be raised to whole powers",
+                        ));
+                    }
+                    Quantity {
+                        value: a.value.powf(b.value),
+                        dimension: Dimension {
+                            length: a.dimension.length * power as i32,
+                            angle: a.dimension.angle * power as i32,
+                        },
+                    }
+                }
+                _ => unreachable!("the parser only makes these operators"),
+            }
+        }
+        Node::Call(function, arguments) => {
+            let arguments = arguments
+                .iter()
+                .map(|a| evaluate(a, values))
+                .collect::<ParameterResult<Vec<_>>>()?;
+            call(function, &arguments)?
+        }
+    })
+}
+
+fn call(function: &str, arguments: &[Quantity]) -> ParameterResult<Quantity> {
+    let a = arguments[0];
+    let angle = || {
+        if a.dimension.is_none() || a.dimension == Dimension::ANGLE {
+            Ok(a.value)
+        } else {
+            Err(ParameterError::IncompatibleUnits(
+                "trigonometric functions take angles",
+            ))
+        }
+    };
+    let ratio = || {
+        if a.dimension.is_none() {
+            Ok(a.value)
+        } else {
+            Err(ParameterError::IncompatibleUnits(
+                "inverse trigonometric functions take plain numbers",
+            ))
+        }
+    };
+    let angle_result = |value: f64| Quantity {
+        value,
+        dimension: Dimension::ANGLE,
+    };
+
+    Ok(match function {
+        "abs" => Quantity {
+            value: a.value.abs(),
+            ..a
+        },
+        "sqrt" => {
+            if a.dimension.length % 2 != 0 || a.dimension.angle % 2 != 0 {
+                return Err(ParameterError::IncompatibleUnits(
+                    "square roots need even powers of units",
+                ));
+            }
+            Quantity {
+                value: a.value.sqrt(),
+                dimension: Dimension {
+                    length: a.dimension.length / 2,
+                    angle: a.dimension.angle / 2,
+                },
+            }
+        }
+        "sin" => Quantity::number(angle()?.sin()),
+        "cos" => Quantity::number(angle()?.cos()),
+        "tan" => Quantity::number(angle()?.tan()),
+        "asin" => angle_result(ratio()?.asin()),
+        "acos" => angle_result(ratio()?.acos()),
+        "atan" => angle_result(ratio()?.atan()),
+        "min" | "max" => {
+            let b = arguments[1];
+            if a.dimension != b.dimension {
+                return Err(ParameterError::IncompatibleUnits(
+                    "only like quantities can be compared",
+                ));
+            }
+            if (function == "min") == (a.value <= b.value) {
+                a
+            } else {
+                b
+            }
+        }
+        _ => unreachable!("the parser only accepts known functions"),
+    })
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Token {
+    Number(f64),
+    Name(String),
+    Symbol(char),
+}
+
+/// Splits text into tokens, each with the position it starts at
+fn tokenize(text: &str) -> ParameterResult<Vec<(usize, Token)>> {
+    let mut tokens = Vec::new();
+    let mut chars = text.char_indices().peekable();
+    while let Some(&(start, c)) = chars.peek() {
+        if c.is_whitespace() {
+            chars.next();
+        } else if c.is_ascii_digit() || c == '.' {
+            let mut end = start;
+            let mut previous = ' ';
+            while let Some(&(i, c)) = chars.peek() {
+                let exponent_sign = (c == '+' || c == '-') && (previous == 'e' || previous == 'E');
+                let exponent = (c == 'e' || c == 'E')
+                    && matches!(
+                        text[i + 1..].chars().next(),
+                        Some(next) if next.is_ascii_digit() || next == '+' || next == '-'
+                    );
+                if c.is_ascii_digit() || c == '.' || exponent || exponent_sign {
+                    end = i + c.len_utf8();
+                    previous = c;
+                    chars.next();
+                } else {
+                    break;
+                }
+            }
+            let value = text[start..end]
+                .parse()
+                .map_err(|_| ParameterError::Syntax {
+                    position: start,
+                    message: "invalid number",
+                })?;
+            tokens.push((start, Token::Number(value)));
+        } else if c.is_alphabetic() || c == '_' {
+            let mut end = start;
+            while let Some(&(i, c)) = chars.peek() {
+                if c.is_alphanumeric() || c == '_' {
+                    end = i + c.len_utf8();
+                    chars.next();
+                } else {
+                    break;
+                }
+            }
+            tokens.push((start, Token::Name(text[start..end].to_string())));
+        } else if "+-*/^(),".contains(c) {
+            tokens.push((start, Token::Symbol(c)));
+            chars.next();
+        } else {
+            return Err(ParameterError::Syntax {
+                position: start,
+                message: "unexpected character",
+            });
+        }
+    }
+    Ok(tokens)
+}
+
+/// A recursive descent parser, with the usual precedence of arithmetic:
+///
+/// ```text
+/// sum     = product { ("+" | "-") product }
+/// product = unary { ("*" | "/") unary }
+/// unary   = "-" unary | power
+/// power   = primary [ "^" unary ]
+/// primary = number [ unit ] | name | name "(" sum { "," sum } ")" | "(" sum ")"
+/// ```
+struct Parser<'a> {
+    tokens: &'a [(usize, Token)],
+    next: usize,
+    end: usize,
+}
+impl<'a> Parser<'a> {
+    fn peek(&self) -> Option<&'a Token> {
+        self.tokens.get(self.next).map(|(_, token)| token)
+    }
+
+    fn error(&self, message: &'static str) -> ParameterError {
+        ParameterError::Syntax {
+            position: self.tokens.get(self.next).map_or(self.end, |(i, _)| *i),
+            message,
+        }
+    }
+
+    fn eat(&mut self, symbol: char) -> bool {
+        if self.peek() == Some(&Token::Symbol(symbol)) {
+            self.next += 1;
+            true
+        } else {
+            false
+        }
+    }
+
+    fn sum(&mut self) -> ParameterResult<Node> {
+        let mut node = self.product()?;
+        loop {
+            let op = match self.peek() {
+                Some(Token::Symbol(op @ ('+' | '-'))) => *op,
+                _ => return Ok(node),
+            };
+            self.next += 1;
+            node = Node::Binary(op, Box::new(node), Box::new(self.product()?));
+        }
+    }
+
+    fn product(&mut self) -> ParameterResult<Node> {
+        let mut node = self.unary()?;
+        loop {
+            let op = match self.peek() {
+                Some(Token::Symbol(op @ ('*' | '/'))) => *op,
+                _ => return Ok(node),
+            };
+            self.next += 1;
+            node = Node::Binary(op, Box::new(node), Box::new(self.unary()?));
+        }
+    }
+
+    fn unary(&mut self) -> ParameterResult<Node> {
+        if self.eat('-') {
+            Ok(Node::Negate(Box::new(self.unary()?)))
+        } else {
+            self.power()
+        }
+    }
+
+    fn power(&mut self) -> ParameterResult<Node> {
+        let base = self.primary()?;
+        if self.eat('^') {
+            Ok(Node::Binary('^', Box::new(base), Box::new(self.unary()?)))
+        } else {
+            Ok(base)
+        }
+    }
+
+    fn primary(&mut self) -> ParameterResult<Node> {
+        let token = self.peek().ok_or_else(|| self.error("expected a value"))?;
+        self.next += 1;
+        match token {
+            Token::Number(value) => match self.peek() {
+                Some(Token::Name(symbol)) => {
+                    let unit = Unit::from_symbol(symbol)?;
+                    self.next += 1;
+                    Ok(Node::Quantity(*value, unit))
+                }
+                _ => Ok(Node::Number(*value)),
+            },
+            Token::Name(name) if self.eat('(') => {
+                let &(function, count) = FUNCTIONS
+                    .iter()
+                    .find(|(function, _)| function == name)
+                    .ok_or_else(|| ParameterError::UnknownFunction(name.clone()))?;
+                let mut arguments = vec![self.sum()?];
+                while self.eat(',') {
+                    arguments.push(self.sum()?);
+                }
+                if !self.eat(')') {
+                    return Err(self.error("expected `)`"));
+                }
+                if arguments.len() != count {
+                    return Err(ParameterError::WrongArguments(function, count));
+                }
+                Ok(Node::Call(function, arguments))
+            }
+            Token::Name(name) if name == "pi" => Ok(Node::Number(PI)),
+            Token::Name(name) => Ok(Node::Parameter(name.clone())),
+            Token::Symbol('(') => {
+                let node = self.sum()?;
+                if self.eat(')') {
+                    Ok(node)
+                } else {
+                    Err(self.error("expected `)`"))
+                }
+            }
+            Token::Symbol(_) => {
+                self.next -= 1;
+                Err(self.error("expected a value"))
+            }
+        }
+    }
+}
@@ -0,0 +1,347 @@
+//! Named parameters defined by expressions, which drive the dimensions of
+//! features and sketches. A parameter's expression may use other parameters, so
+//! the table is evaluated in dependency order, and parameters that depend on
+//! themselves are reported rather than evaluated.
+
+use std::collections::{BTreeMap, BTreeSet};
+
+use serde::{Deserialize, Serialize};
+
+pub mod error;
+pub mod expression;
+pub mod unit;
+
+use error::{ParameterError, ParameterResult};
+use expression::{is_reserved, Expression};
+use unit::{Quantity, Unit};
+
+/// The result of evaluating each parameter of a table, by name
+pub type Values = BTreeMap<String, ParameterResult<Quantity>>;
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Parameter {
+    pub name: String,
+    pub expression: Expression,
+
+    /// The unit the parameter is shown in, which plain numbers in its
+    /// expression are taken to be in. Parameters without one can hold any
+    /// quantity.
+    pub unit: Unit,
+}
+
+/// A part's parameters, in the order they were added
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct ParameterTable {
+    parameters: Vec<Parameter>,
+}
+impl ParameterTable {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn parameters(&self) -> &[Parameter] {
+        &self.parameters
+    }
+
+    pub fn get(&self, name: &str) -> Option<&Parameter> {
+        self.parameters.iter().find(|p| p.name == name)
+    }
+
+    /// Adds a parameter, or changes the one with the same name
+    pub fn set(&mut self, name: &str, expression: &str, unit: Unit) -> ParameterResult<()> {
+        check_name(name)?;
+        let parameter = Parameter {
+            name: name.to_string(),
+            expression: Expression::parse(expression)?,
+            unit,
+        };
+        match self.parameters.iter_mut().find(|p| p.name == name) {
+            Some(existing) => *existing = parameter,
+            None => self.parameters.push(parameter),
+        }
+        Ok(())
+    }
+
+    pub fn remove(&mut self, name: &str) -> Option<Parameter> {
+        let index = self.parameters.iter().position(|p| p.name == name)?;
+        Some(self.parameters.remove(index))
+    }
+
+    /// Renames a parameter. Expressions that use it keep the old name, and fail to
+    /// evaluate until they are changed too.
+    pub fn rename(&mut self, name: &str, new_name: &str) -> ParameterResult<()> {
+        check_name(new_name)?;
+        if self.get(new_name).is_some() {
+            return Err(ParameterError::InvalidName(new_name.to_string()));
+        }
+        let parameter = self
+            .parameters
+            .iter_mut()
+            .find(|p| p.name == name)
+            .ok_or_else(|| ParameterError::UnknownParameter(name.to_string()))?;
+        parameter.name = new_name.to_string();
+        Ok(())
+    }
+
+    /// The parameters in an order where each comes after those its expression
+    /// uses, or the first cycle of parameters that use each other
+    pub fn order(&self) -> ParameterResult<Vec<&Parameter>> {
+        #[derive(Clone, Copy, PartialEq)]
+        enum Mark {
+            New,
+            Visiting,
+            Done,
+        }
+
+        fn visit<'a>(
+            table: &'a ParameterTable,
+            index: usize,
+            marks: &mut [Mark],
+            path: &mut Vec<usize>,
+            order: &mut Vec<&'a Parameter>,
+        ) -> ParameterResult<()> {
+            match marks[index] {
+                Mark::Done => return Ok(()),
+                Mark::Visiting => {
+                    let start = path.iter().position(|i| *i == index).unwrap();
+                    let mut cycle = path[start..]
+                        .iter()
+                        .map(|i| table.parameters[*i].name.clone())
+                        .collect::<Vec<_>>();
+                    cycle.push(table.parameters[index].name.clone());
+                    return Err(ParameterError::Cycle(cycle));
+                }
+                Mark::New => {}
+            }
+
+            marks[index] = Mark::Visiting;
+            path.push(index);
+            let parameter = &table.parameters[index];
+            for name in parameter.expression.references() {
+                if let Some(input) = table.parameters.iter().position(|p| p.name == name) {
+                    visit(table, input, marks, path, order)?;
+                }
+            }
+            path.pop();
+            marks[index] = Mark::Done;
+            order.push(parameter);
+            Ok(())
+        }
+
+        let mut marks = vec![Mark::New; self.parameters.len()];
+        let mut order = Vec::with_capacity(self.parameters.len());
+        for index in 0..self.parameters.len() {
+            visit(self, index, &mut marks, &mut Vec::new(), &mut order)?;
+        }
+        Ok(order)
+    }
+
+    /// Evaluates every parameter. Parameters in a cycle, and those that use them,
+    /// fail with the error of the cycle.
+    pub fn evaluate(&self) -> Values {
+        let mut values = Values::new();
+
+        // Take out cycles one at a time, so the rest can still be ordered
+        let mut table = self.clone();
+        let order = loop {
+            match table.order() {
+                Ok(order) => break order,
+                Err(ParameterError::Cycle(cycle)) => {
+                    for name in cycle.iter() {
+                        table.remove(name);
+                        values.insert(name.clone(), Err(ParameterError::Cycle(cycle.clone())));
+                    }
+                }
+                Err(_) => unreachable!("ordering only fails on cycles"),
+            }
+        };
+
+        for parameter in order {
+            let unit = parameter.unit;
+            let value = parameter.expression.evaluate(&values).and_then(|value| {
+                // Parameters without a unit can hold any quantity, such as an area
+                if unit == Unit::None {
+                    return Ok(value);
+                }
+                Ok(Quantity {
+                    value: value.base_value(unit)?,
+                    dimension: unit.dimension(),
+                })
+            });
+            values.insert(parameter.name.clone(), value);
+        }
+        values
+    }
+
+    /// The parameters that use a parameter, directly or through others
+    pub fn dependents(&self, name: &str) -> BTreeSet<&str> {
+        let mut dependents = BTreeSet::new();
+        let mut queue = vec![name];
+        while let Some(name) = queue.pop() {
+            for parameter in self.parameters.iter() {
+                if parameter.expression.references().contains(name)
+                    && dependents.insert(parameter.name.as_str())
+                {
+                    queue.push(&parameter.name);
+                }
+            }
+        }
+        dependents
+    }
+}
+
+fn check_name(name: &str) -> ParameterResult<()> {
+    let mut chars = name.chars();
+    let valid = matches!(chars.next(), Some(c) if c.is_alphabetic() || c == '_')
+        && chars.all(|c| c.is_alphanumeric() || c == '_')
+        && !is_reserved(name);
+    if valid {
+        Ok(())
+    } else {
+        Err(ParameterError::InvalidName(name.to_string()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::f64::consts::PI;
+
+    use crate::{
+        error::ParameterError,
+        expression::Expression,
+        unit::{Dimension, Quantity, Unit},
+        ParameterTable, Values,
+    };
+
+    fn evaluate(text: &str) -> Result<Quantity, ParameterError> {
+        Expression::parse(text)?.evaluate(&Values::new())
+    }
+
+    #[test]
+    fn expressions() {
+        assert_eq!(evaluate("1 + 2 * 3 ^ 2 / 6"), Ok(Quantity::number(4.0)));
+        assert_eq!(evaluate("-(1 - 4) * -2"), Ok(Quantity::number(-6.0)));
+        assert_eq!(
+            evaluate("2 in + 1 cm"),
+            Ok(Quantity::new(60.8, Unit::Millimeter))
+        );
+        assert_eq!(evaluate("1.5e1mm * 2 mm").unwrap().dimension.length, 2);
+        assert_eq!(
+            evaluate("sqrt(9 m * 1 m)"),
+            Ok(Quantity::new(3.0, Unit::Meter))
+        );
+        assert!((evaluate("cos(60 deg)").unwrap().value - 0.5).abs() < 1e-12);
+        assert_eq!(evaluate("atan(1)").unwrap().dimension, Dimension::ANGLE);
+        assert_eq!(
+            evaluate("max(2 mm, 1 cm)"),
+            Ok(Quantity::new(10.0, Unit::Millimeter))
+        );
+        assert_eq!(evaluate("pi / 2"), Ok(Quantity::number(PI / 2.0)));
+
+        assert!(matches!(
+            evaluate("2 * (3 + 4"),
+            Err(ParameterError::Syntax { position: 10, .. })
+        ));
+        assert!(matches!(
+            evaluate("2 +"),
+            Err(ParameterError::Syntax { .. })
+        ));
+        assert!(matches!(
+            evaluate("3 $"),
+            Err(ParameterError::Syntax { position: 2, .. })
+        ));
+        assert_eq!(
+            evaluate("1 mm + 1 deg"),
+            Err(ParameterError::IncompatibleUnits(
+                "only like quantities can be added or subtracted"
+            ))
+        );
+        assert_eq!(
+            evaluate("2 parsecs"),
+            Err(ParameterError::UnknownUnit("parsecs".to_string()))
+        );
+        assert_eq!(
+            evaluate("floor(2)"),
+            Err(ParameterError::UnknownFunction("floor".to_string()))
+        );
+        assert_eq!(
+            evaluate("min(2)"),
+            Err(ParameterError::WrongArguments("min", 2))
+        );
+        assert_eq!(evaluate("1 / 0"), Err(ParameterError::NotFinite));
+        assert_eq!(
+            evaluate("width"),
+            Err(ParameterError::UnknownParameter("width".to_string()))
+        );
+    }
+
+    #[test]
+    fn table() {
+        let mut table = ParameterTable::new();
+        table
+            .set("width", "2 * height + 5 mm", Unit::Millimeter)
+            .unwrap();
+        table.set("height", "1 in", Unit::Inch).unwrap();
+        table.set("area", "width * height", Unit::None).unwrap();
+        table.set("angle", "45", Unit::Degree).unwrap();
+
+        let order = table.order().unwrap();
+        let names = order.iter().map(|p| p.name.as_str()).collect::<Vec<_>>();
+        assert_eq!(names, ["height", "width", "area", "angle"]);
+
+        let values = table.evaluate();
+        assert_eq!(values["width"], Ok(Quantity::new(55.8, Unit::Millimeter)));
+        assert_eq!(values["angle"], Ok(Quantity::new(PI / 4.0, Unit::Radian)));
+        assert_eq!(values["area"].as_ref().unwrap().value, 55.8 * 25.4);
+        assert_eq!(
+            table.dependents("height").into_iter().collect::<Vec<_>>(),
+            ["area", "width"]
+        );
+
+        // A parameter's unit has to fit its expression
+        table.set("angle", "45 mm", Unit::Degree).unwrap();
+        assert!(matches!(
+            table.evaluate()["angle"],
+            Err(ParameterError::IncompatibleUnits(_))
+        ));
+
+        // Cycles are reported on the parameters in them, and break the ones that
+        // use them
+        table.set("height", "width / 3", Unit::Millimeter).unwrap();
+        let cycle = ParameterError::Cycle(vec![
+            "width".to_string(),
+            "height".to_string(),
+            "width".to_string(),
+        ]);
+        assert_eq!(table.order().err(), Some(cycle.clone()));
+        let values = table.evaluate();
+        assert_eq!(values["width"], Err(cycle.clone()));
+        assert_eq!(values["height"], Err(cycle));
+        assert_eq!(
+            values["area"],
+            Err(ParameterError::FailedInput("width".to_string()))
+        );
+
+        assert_eq!(
+            table.set("sin", "1", Unit::None),
+            Err(ParameterError::InvalidName("sin".to_string()))
+        );
+        assert_eq!(
+            table.set("2x", "1", Unit::None),
+            Err(ParameterError::InvalidName("2x".to_string()))
+        );
+        assert_eq!(
+            table.rename("area", "width"),
+            Err(ParameterError::InvalidName("width".to_string()))
+        );
+        table.rename("area", "surface").unwrap();
+        assert!(table.remove("area").is_none());
+
+        let text = serde_json::to_string(&table).unwrap();
+        assert!(text.contains("\"width / 3\""));
+        assert_eq!(
+            serde_json::from_str::<ParameterTable>(&text).unwrap(),
+            table
+        );
+    }
+}
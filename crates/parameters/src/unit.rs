@@ -0,0 +1,164 @@
+use std::{
+    f64::consts::PI,
+    ops::{Add, Sub},
+};
+
+use serde::{Deserialize, Serialize};
+
+use crate::error::{ParameterError, ParameterResult};
+
+/// The powers of length and angle a quantity is measured in. Areas have a length
+/// power of 2, and plain numbers are dimensionless.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Dimension {
+    pub length: i32,
+    pub angle: i32,
+}
+impl Dimension {
+    pub const NONE: Self = Self {
+        length: 0,
+        angle: 0,
+    };
+    pub const LENGTH: Self = Self {
+        length: 1,
+        angle: 0,
+    };
+    pub const ANGLE: Self = Self {
+        length: 0,
+        angle: 1,
+    };
+
+    pub fn is_none(&self) -> bool {
+        *self == Self::NONE
+    }
+}
+impl Add for Dimension {
+    type Output = Self;
+
+    fn add(self, rhs: Self) -> Self {
+        Self {
+            length: self.length + rhs.length,
+            angle: self.angle + rhs.angle,
+        }
+    }
+}
+impl Sub for Dimension {
+    type Output = Self;
+
+    fn sub(self, rhs: Self) -> Self {
+        Self {
+            length: self.length - rhs.length,
+            angle: self.angle - rhs.angle,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum Unit {
+    /// A plain number
+    None,
+    Millimeter,
+    Centimeter,
+    Meter,
+    Inch,
+    Foot,
+    Degree,
+    Radian,
+}
+impl Unit {
+    pub const ALL: [Self; 8] = [
+        Self::None,
+        Self::Millimeter,
+        Self::Centimeter,
+        Self::Meter,
+        Self::Inch,
+        Self::Foot,
+        Self::Degree,
+        Self::Radian,
+    ];
+
+    /// The name the unit is written as in expressions
+    pub fn symbol(&self) -> &'static str {
+        match self {
+            Self::None => "",
+            Self::Millimeter => "mm",
+            Self::Centimeter => "cm",
+            Self::Meter => "m",
+            Self::Inch => "in",
+            Self::Foot => "ft",
+            Self::Degree => "deg",
+            Self::Radian => "rad",
+        }
+    }
+
+    pub fn from_symbol(symbol: &str) -> ParameterResult<Self> {
+        Self::ALL
+            .into_iter()
+            .find(|unit| *unit != Self::None && unit.symbol() == symbol)
+            .ok_or_else(|| ParameterError::UnknownUnit(symbol.to_string()))
+    }
+
+    pub fn dimension(&self) -> Dimension {
+        match self {
+            Self::None => Dimension::NONE,
+            Self::Millimeter | Self::Centimeter | Self::Meter | Self::Inch | Self::Foot => {
+                Dimension::LENGTH
+            }
+            Self::Degree | Self::Radian => Dimension::ANGLE,
+        }
+    }
+
+    /// The size of the unit in base units: millimeters for lengths and radians
+    /// for angles
+    pub fn factor(&self) -> f64 {
+        match self {
+            Self::None | Self::Millimeter | Self::Radian => 1.0,
+            Self::Centimeter => 10.0,
+            Self::Meter => 1000.0,
+            Self::Inch => 25.4,
+            Self::Foot => 304.8,
+            Self::Degree => PI / 180.0,
+        }
+    }
+}
+
+/// A value in base units along with what it measures
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Quantity {
+    pub value: f64,
+    pub dimension: Dimension,
+}
+impl Quantity {
+    pub fn new(value: f64, unit: Unit) -> Self {
+        Self {
+            value: value * unit.factor(),
+            dimension: unit.dimension(),
+        }
+    }
+
+    pub fn number(value: f64) -> Self {
+        Self {
+            value,
+            dimension: Dimension::NONE,
+        }
+    }
+
+    /// The value in base units, for a quantity measured like `unit`. Plain
+    /// numbers are taken to be in `unit`.
+    pub fn base_value(&self, unit: Unit) -> ParameterResult<f64> {
+        if self.dimension == unit.dimension() {
+            Ok(self.value)
+        } else if self.dimension.is_none() {
+            Ok(self.value * unit.factor())
+        } else {
+            Err(ParameterError::IncompatibleUnits(
+                "the value doesn't measure what is expected",
+            ))
+        }
+    }
+
+    /// The value expressed in `unit`
+    pub fn value_in(&self, unit: Unit) -> ParameterResult<f64> {
+        Ok(self.base_value(unit)? / unit.factor())
+    }
+}
@@ -2,6 +2,7 @@ use cgmath::{
     point3, vec2, vec3, vec4, Angle, Deg, EuclideanSpace, InnerSpace, Matrix4, Point3, Quaternion,
     Rad, Rotation3, SquareMatrix, Vector2, Vector3, Vector4,
 };
+use document::camera::{CameraState, Projection};
 
 #[derive(Debug, Clone, Copy)]
 pub enum CameraAngle {
@@ -230,6 +231,48 @@ impl Camera {
         camera
     }
 
+    /// Recreates a camera saved in a document
+    pub fn from_state(viewport_in_pixels: [u32; 2], state: &CameraState) -> Self {
+        let position = Point3::from(state.position);
+        let direction = Vector3::from(state.direction);
+        let up = Vector3::from(state.up);
+        match state.projection {
+            Projection::Orthographic { height } => Self::create_orthographic(
+                viewport_in_pixels,
+                position,
+                direction,
+                up,
+                height,
+                state.near_dist,
+                state.far_dist,
+            ),
+            Projection::Perspective { fov_y } => Self::create_perspective(
+                viewport_in_pixels,
+                position,
+                direction,
+                up,
+                Rad(fov_y),
+                state.near_dist,
+                state.far_dist,
+            ),
+        }
+    }
+
+    /// The camera's placement and projection, for saving in a document
+    pub fn state(&self) -> CameraState {
+        CameraState {
+            position: self.position.into(),
+            direction: self.direction.into(),
+            up: self.up.into(),
+            projection: match self.projection_type {
+                ProjectionType::Orthographic { height } => Projection::Orthographic { height },
+                ProjectionType::Perspective { fov_y } => Projection::Perspective { fov_y: fov_y.0 },
+            },
+            near_dist: self.near_dist,
+            far_dist: self.far_dist,
+        }
+    }
+
     pub fn set_viewport_in_pixels(&mut self, viewport_in_pixels: [u32; 2]) {
         self.viewport_in_pixels = viewport_in_pixels;
         self.update_projection();
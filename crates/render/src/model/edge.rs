@@ -1,9 +1,10 @@
 use bytemuck::{Pod, Zeroable};
 use cgmath::{Point3, Vector3};
+use space::Transform3;
 
 use crate::Rgba;
 
-use super::ModelObjectId;
+use super::{transform_direction, transform_point, ModelObjectId};
 
 #[derive(Clone, Debug)]
 pub struct ModelEdge {
@@ -41,6 +42,14 @@ impl EdgeVertex {
             expand: [expand.x, expand.y, expand.z],
         }
     }
+
+    /// Moves the vertex into the coordinates of a model's transform
+    pub fn transformed(&self, transform: &Transform3) -> Self {
+        Self {
+            position: transform_point(transform, self.position),
+            expand: transform_direction(transform, self.expand),
+        }
+    }
 }
 
 #[repr(C)]
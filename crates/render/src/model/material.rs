@@ -30,7 +30,7 @@ impl MaterialId {
     }
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct MaterialSet {
     opaque: Vec<OpaqueMaterial>,
     translucent: Vec<TranslucentMaterial>,
@@ -85,6 +85,23 @@ impl MaterialSet {
         }
     }
 
+    /// The color and roughness of a material, if it is in the set
+    pub fn get(&self, id: MaterialId) -> Option<(Rgba, f32)> {
+        match id.0 {
+            MaterialKind::Opaque => self.opaque.get(id.1 as usize).map(|material| {
+                let diffuse = material.diffuse;
+                (
+                    Rgba::new(diffuse.r(), diffuse.g(), diffuse.b(), 1.0),
+                    material.roughness,
+                )
+            }),
+            MaterialKind::Translucent => self
+                .translucent
+                .get(id.1 as usize)
+                .map(|material| (material.diffuse, material.roughness)),
+        }
+    }
+
     pub fn buffer_opaque(
         &self,
         allocator: &(impl MemoryAllocator + ?Sized),
@@ -1,5 +1,13 @@
 use crate::Rgba;
+use cgmath::{point3, vec3};
+use document::Document;
+use exchange::mesh::{Mesh, MeshMaterial, MeshPart, MeshVertex};
+use space::{EVec3, EVector, MassProperties, Tolerance, Transform3};
 use std::sync::Arc;
+use topology::{
+    interference::{check_pairs, PairInterference, PlacedMesh},
+    mesh::TriMesh,
+};
 use vulkano::buffer::{BufferUsage, CpuAccessibleBuffer};
 use vulkano::memory::allocator::MemoryAllocator;
 
@@ -28,7 +36,7 @@ pub struct GeometryBuffers {
     pub point_vertices: Option<Arc<CpuAccessibleBuffer<[BufferedPointVertex]>>>,
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct Geometry {
     models: Vec<Model>,
     materials: MaterialSet,
@@ -49,21 +57,254 @@ impl Geometry {
         self.models.push(model)
     }
 
+    /// Collects the surfaces of all models into a mesh for export, with a part for
+    /// each surface and the materials they use. Surfaces are placed by their
+    /// models' transforms, and positions are taken to be in millimeters.
+    pub fn to_mesh(&self) -> Mesh {
+        let mut mesh = Mesh::new();
+        let mut exported: Vec<(bool, u32)> = Vec::new();
+
+        for (m, model) in self.models.iter().enumerate() {
+            for (s, surface) in model.surfaces.iter().enumerate() {
+                let id = surface.material_id();
+                let key = (id.is_opaque(), id.index());
+                let material = match exported.iter().position(|k| *k == key) {
+                    Some(index) => Some(index),
+                    None => self.materials.get(id).map(|(color, roughness)| {
+                        exported.push(key);
+                        mesh.materials.push(MeshMaterial::new(
+                            &format!("material {}", exported.len()),
+                            color.to_floats(),
+                            roughness,
+                        ));
+                        exported.len() - 1
+                    }),
+                };
+
+                let vector = |v: [f32; 3]| EVec3::new(v[0] as f64, v[1] as f64, v[2] as f64);
+                let mut part = MeshPart::new(&format!("model {} surface {}", m + 1, s + 1));
+                part.material = material;
+                part.vertices = surface
+                    .vertices()
+                    .iter()
+                    .map(|vertex| {
+                        let vertex = vertex.transformed(&model.transform);
+                        MeshVertex::new(vector(vertex.position), vector(vertex.normal))
+                    })
+                    .collect();
+                part.triangles = surface
+                    .indices()
+                    .chunks_exact(3)
+                    .map(|triangle| [0, 1, 2].map(|i| triangle[i] as usize))
+                    .collect();
+                mesh.parts.push(part);
+            }
+        }
+
+        mesh
+    }
+
+    /// Adds an imported mesh as a model for reference, with a surface for each
+    /// part and edges where its triangles meet at more than `feature_angle`
+    /// radians or have no neighbor. Parts without a material are drawn gray.
+    pub fn insert_mesh(&mut self, mesh: &Mesh, feature_angle: f64, edge_color: Rgba) {
+        let materials = mesh
+            .materials
+            .iter()
+            .map(|material| {
+                let [r, g, b, a] = material.color;
+                self.insert_material(Rgba::new(r, g, b, a), material.roughness)
+            })
+            .collect::<Vec<_>>();
+        let mut default_material = None;
+
+        let mut id = 0u32;
+        let mut surfaces = Vec::new();
+        let mut edges = Vec::new();
+        for part in mesh.parts.iter() {
+            let material_id = match part.material {
+                Some(material) => materials[material],
+                None => *default_material.get_or_insert_with(|| {
+                    self.insert_material(Rgba::new(0.8, 0.8, 0.8, 1.0), 0.5)
+                }),
+            };
+
+            surfaces.push(ModelSurface::new(
+                id.into(),
+                part.vertices
+                    .iter()
+                    .map(|vertex| {
+                        let [x, y, z] = vertex.position.f32s();
+                        let [nx, ny, nz] = vertex.normal.f32s();
+                        SurfaceVertex::new(point3(x, y, z), vec3(nx, ny, nz))
+                    })
+                    .collect(),
+                part.triangles
+                    .iter()
+                    .flatten()
+                    .map(|index| *index as u32)
+                    .collect(),
+                material_id,
+            ));
+            id += 1;
+
+            for chain in part.feature_edges(feature_angle) {
+                edges.push(ModelEdge::new(
+                    id.into(),
+                    chain
+                        .iter()
+                        .map(|vertex| {
+                            let [x, y, z] = vertex.position.f32s();
+                            let [nx, ny, nz] = vertex.normal.f32s();
+                            EdgeVertex::new(point3(x, y, z), vec3(nx, ny, nz))
+                        })
+                        .collect(),
+                    edge_color,
+                ));
+                id += 1;
+            }
+        }
+
+        self.insert_model(Model::empty().surfaces(surfaces).edges(edges));
+    }
+
+    /// Adds the body a part document's features build as a model placed by
+    /// `transform`, tessellated to within `deviation` and drawn in the document's
+    /// first material. A part without a body still adds an empty model, so the
+    /// models keep lining up with the parts they were added for.
+    pub fn insert_part(
+        &mut self,
+        document: &Document,
+        transform: Transform3,
+        deviation: f64,
+        feature_angle: f64,
+        edge_color: Rgba,
+    ) {
+        let mut mesh = Mesh::new();
+        if let Some(mut part) = document.body_mesh(deviation) {
+            if let Some(material) = document.materials.first() {
+                mesh.materials.push(MeshMaterial::new(
+                    &material.name,
+                    material.color,
+                    material.roughness,
+                ));
+                part.material = Some(0);
+            }
+            mesh.parts.push(part);
+        }
+
+        self.insert_mesh(&mesh, feature_angle, edge_color);
+        if let Some(model) = self.models.last_mut() {
+            model.transform = transform;
+        }
+    }
+
+    /// Checks every pair of models for interference, taking each model's
+    /// surfaces, placed by its transform, to bound one body. Models are referred
+    /// to by the order they were inserted in.
+    pub fn interference(&self, tolerance: &Tolerance) -> Vec<PairInterference> {
+        let bodies = self
+            .models
+            .iter()
+            .map(Model::placed_mesh)
+            .collect::<Vec<_>>();
+        check_pairs(&bodies, tolerance)
+    }
+
+    /// Adds a model highlighting pairs found by [`Geometry::interference`]. The
+    /// triangles where a pair overlaps are outlined in `overlap_color`, and pairs
+    /// that are apart but closer than `min_clearance` have their closest points
+    /// marked and joined in `clearance_color`.
+    pub fn highlight_interference(
+        &mut self,
+        pairs: &[PairInterference],
+        min_clearance: f64,
+        overlap_color: Rgba,
+        clearance_color: Rgba,
+    ) {
+        let position = |p: EVec3| {
+            let [x, y, z] = p.f32s();
+            point3(x, y, z)
+        };
+        let direction = |v: EVec3| {
+            let [x, y, z] = v.f32s();
+            vec3(x, y, z)
+        };
+        let mut meshes = vec![None; self.models.len()];
+        let mut id = 0u32;
+        let mut edges = Vec::new();
+        let mut points = Vec::new();
+
+        for pair in pairs {
+            let interference = &pair.interference;
+            if interference.interferes() {
+                for (model, contacts) in [
+                    (pair.first, &interference.first_contacts),
+                    (pair.second, &interference.second_contacts),
+                ] {
+                    let mesh: &PlacedMesh =
+                        meshes[model].get_or_insert_with(|| self.models[model].placed_mesh());
+                    for triangle in contacts {
+                        let [a, b, c] = mesh.mesh().triangle_points(*triangle);
+                        let normal = (b - a).cross(&(c - a));
+                        let expand = if normal.magnitude() > f64::EPSILON {
+                            direction(normal.normalize())
+                        } else {
+                            direction(EVec3::zero())
+                        };
+                        let vertex = |p: EVec3| EdgeVertex::new(position(p), expand);
+                        edges.push(ModelEdge::new(
+                            id.into(),
+                            vec![vertex(a), vertex(b), vertex(c), vertex(a)],
+                            overlap_color,
+                        ));
+                        id += 1;
+                    }
+                }
+            } else if let Some(clearance) = &interference.clearance {
+                if clearance.distance > 0.0 && clearance.distance < min_clearance {
+                    let (first, second) = (clearance.first.0, clearance.second.0);
+                    let expand = direction(EVec3::zero());
+                    edges.push(ModelEdge::new(
+                        id.into(),
+                        vec![
+                            EdgeVertex::new(position(first), expand),
+                            EdgeVertex::new(position(second), expand),
+                        ],
+                        clearance_color,
+                    ));
+                    id += 1;
+                    for point in [first, second] {
+                        points.push(ModelPoint::new(
+                            id.into(),
+                            position(point),
+                            expand,
+                            clearance_color,
+                        ));
+                        id += 1;
+                    }
+                }
+            }
+        }
+
+        self.insert_model(Model::empty().edges(edges).points(points));
+    }
+
     pub fn build_buffers(&self, allocator: &(impl MemoryAllocator + ?Sized)) -> GeometryBuffers {
         let (opaque_surface_vertices, opaque_surface_indices) = Self::buffer_surfaces(
             allocator,
             self.models
                 .iter()
-                .flat_map(|model| model.surfaces.iter())
-                .filter(|surface| surface.is_opaque()),
+                .flat_map(|model| model.surfaces.iter().map(move |s| (&model.transform, s)))
+                .filter(|(_, surface)| surface.is_opaque()),
         );
 
         let (translucent_surface_vertices, translucent_surface_indices) = Self::buffer_surfaces(
             allocator,
             self.models
                 .iter()
-                .flat_map(|model| model.surfaces.iter())
-                .filter(|surface| surface.is_translucent()),
+                .flat_map(|model| model.surfaces.iter().map(move |s| (&model.transform, s)))
+                .filter(|(_, surface)| surface.is_translucent()),
         );
 
         let (edge_vertices, edge_indices) = self.buffer_edges(allocator);
@@ -88,7 +329,7 @@ impl Geometry {
 
     fn buffer_surfaces<'a>(
         allocator: &(impl MemoryAllocator + ?Sized),
-        surfaces: impl Iterator<Item = &'a ModelSurface>,
+        surfaces: impl Iterator<Item = (&'a Transform3, &'a ModelSurface)>,
     ) -> (
         Option<Arc<CpuAccessibleBuffer<[BufferedSurfaceVertex]>>>,
         Option<Arc<CpuAccessibleBuffer<[u32]>>>,
@@ -97,13 +338,10 @@ impl Geometry {
         let mut indices: Vec<u32> = Vec::new();
 
         let mut index_offset = 0;
-        for surface in surfaces {
-            vertices.extend(
-                surface
-                    .vertices()
-                    .iter()
-                    .map(|vert| BufferedSurfaceVertex::new(vert, surface.material_id())),
-            );
+        for (transform, surface) in surfaces {
+            vertices.extend(surface.vertices().iter().map(|vert| {
+                BufferedSurfaceVertex::new(&vert.transformed(transform), surface.material_id())
+            }));
 
             indices.extend(surface.indices().iter().map(|i| i + index_offset));
             index_offset += surface.vertices().len() as u32;
@@ -153,7 +391,8 @@ impl Geometry {
             for edge in model.edges.iter() {
                 let color = edge.color();
                 for vertex in edge.vertices().iter() {
-                    vertices.push(BufferedEdgeVertex::new(vertex, color));
+                    let vertex = vertex.transformed(&model.transform);
+                    vertices.push(BufferedEdgeVertex::new(&vertex, color));
 
                     indices.push(index);
                     index += 1;
@@ -199,7 +438,9 @@ impl Geometry {
 
         for model in self.models.iter() {
             for point in model.points.iter() {
-                vertices.push(BufferedPointVertex::new(point));
+                vertices.push(BufferedPointVertex::new(
+                    &point.transformed(&model.transform),
+                ));
             }
         }
 
@@ -222,11 +463,14 @@ impl Geometry {
     }
 }
 
+/// Surfaces, edges and points drawn together, placed in the scene by a transform
+/// from the coordinates they are given in
 #[derive(Debug, Clone)]
 pub struct Model {
     surfaces: Vec<ModelSurface>,
     edges: Vec<ModelEdge>,
     points: Vec<ModelPoint>,
+    transform: Transform3,
 }
 impl Model {
     pub fn empty() -> Self {
@@ -234,14 +478,42 @@ impl Model {
             surfaces: vec![],
             edges: vec![],
             points: vec![],
+            transform: Transform3::identity(),
         }
     }
 
+    /// Places the model in the scene with an affine transform
+    pub fn transform(self, transform: Transform3) -> Self {
+        Self { transform, ..self }
+    }
+
+    /// The model's surfaces as one mesh in scene coordinates, so it can be
+    /// checked against other models as the boundary of a body
+    pub fn placed_mesh(&self) -> PlacedMesh {
+        let mut mesh = TriMesh::default();
+        for surface in self.surfaces.iter() {
+            let offset = mesh.vertices.len();
+            mesh.vertices
+                .extend(surface.vertices().iter().map(|vertex| {
+                    let [x, y, z] = vertex.position;
+                    EVec3::new(x as f64, y as f64, z as f64)
+                }));
+            mesh.triangles.extend(
+                surface
+                    .indices()
+                    .chunks_exact(3)
+                    .map(|triangle| [0, 1, 2].map(|i| offset + triangle[i] as usize)),
+            );
+        }
+        PlacedMesh::new(&mesh, &self.transform)
+    }
+
     pub fn surface(self, surface: ModelSurface) -> Self {
         let Self {
             mut surfaces,
             edges,
             points,
+            transform,
         } = self;
 
         surfaces.push(surface);
@@ -250,6 +522,7 @@ impl Model {
             surfaces,
             edges,
             points,
+            transform,
         }
     }
 
@@ -258,6 +531,7 @@ impl Model {
             mut surfaces,
             edges,
             points,
+            transform,
         } = self;
 
         surfaces.extend(new_surfaces);
@@ -266,6 +540,7 @@ impl Model {
             surfaces,
             edges,
             points,
+            transform,
         }
     }
 
@@ -274,6 +549,7 @@ impl Model {
             surfaces,
             mut edges,
             points,
+            transform,
         } = self;
 
         edges.push(edge);
@@ -282,6 +558,7 @@ impl Model {
             surfaces,
             edges,
             points,
+            transform,
         }
     }
 
@@ -290,6 +567,7 @@ impl Model {
             surfaces,
             mut edges,
             points,
+            transform,
         } = self;
 
         edges.extend(new_edges);
@@ -298,6 +576,7 @@ impl Model {
             surfaces,
             edges,
             points,
+            transform,
         }
     }
 
@@ -306,6 +585,7 @@ impl Model {
             surfaces,
             edges,
             mut points,
+            transform,
         } = self;
 
         points.push(point);
@@ -314,6 +594,7 @@ impl Model {
             surfaces,
             edges,
             points,
+            transform,
         }
     }
 
@@ -322,6 +603,7 @@ impl Model {
             surfaces,
             edges,
             mut points,
+            transform,
         } = self;
 
         points.extend(new_points);
@@ -330,8 +612,15 @@ impl Model {
             surfaces,
             edges,
             points,
+            transform,
         }
     }
+
+    /// Finds the mass properties of the body enclosed by the model's surfaces,
+    /// which must together form a closed mesh
+    pub fn mass_properties(&self, density: f64) -> MassProperties {
+        mass_properties(&self.surfaces, density)
+    }
 }
 
 #[derive(Clone, Debug)]
@@ -341,3 +630,142 @@ impl From<u32> for ModelObjectId {
         Self(value)
     }
 }
+
+/// Moves a point given in single precision by a transform
+pub(crate) fn transform_point(transform: &Transform3, [x, y, z]: [f32; 3]) -> [f32; 3] {
+    transform
+        .apply_point(EVec3::new(x as f64, y as f64, z as f64))
+        .f32s()
+}
+
+/// Turns a direction given in single precision by a transform, keeping its
+/// length. Directions are only kept perpendicular to the surfaces they belong to
+/// by rigid transforms and uniform scales.
+pub(crate) fn transform_direction(transform: &Transform3, [x, y, z]: [f32; 3]) -> [f32; 3] {
+    let direction = EVec3::new(x as f64, y as f64, z as f64);
+    let turned = transform.apply_direction(direction);
+    if turned.magnitude() <= f64::EPSILON {
+        return turned.f32s();
+    }
+    (turned * (direction.magnitude() / turned.magnitude())).f32s()
+}
+
+#[cfg(test)]
+mod tests {
+    use document::{material::MaterialRecord, Document};
+    use features::feature::{Combine, FeatureKind};
+    use space::{EPlacement3, EVec2, EVec3, Tolerance, Transform3};
+    use topology::{interference::PlacedMesh, mesh::TriMesh};
+
+    use crate::Rgba;
+
+    use super::Geometry;
+
+    /// A part whose body is a unit cube with a corner at the origin
+    fn cube() -> Document {
+        let mut document = Document::new();
+        let sketch = document
+            .features
+            .insert(
+                "Sketch 1",
+                FeatureKind::Sketch {
+                    placement: EPlacement3::default(),
+                    outer: vec![
+                        EVec2::new(0.0, 0.0),
+                        EVec2::new(1.0, 0.0),
+                        EVec2::new(1.0, 1.0),
+                        EVec2::new(0.0, 1.0),
+                    ],
+                    holes: Vec::new(),
+                },
+                &[],
+            )
+            .unwrap();
+        document
+            .features
+            .insert(
+                "Extrude 1",
+                FeatureKind::Extrude {
+                    sketch,
+                    combine: Combine::Add,
+                },
+                &[("depth", 1.0)],
+            )
+            .unwrap();
+        document.regenerate();
+        document
+    }
+
+    #[test]
+    fn insert_part() {
+        let mut part = cube();
+        part.materials.push(MaterialRecord {
+            name: "Steel".to_string(),
+            color: [0.6, 0.6, 0.65, 1.0],
+            roughness: 0.3,
+        });
+
+        let mut geometry = Geometry::new();
+        let offset = Transform3::translation(EVec3::new(10.0, 0.0, 0.0));
+        geometry.insert_part(&part, offset, 0.01, 0.5, Rgba::BLACK);
+        geometry.insert_part(
+            &Document::new(),
+            Transform3::identity(),
+            0.01,
+            0.5,
+            Rgba::BLACK,
+        );
+
+        // A part without a body still takes up a model
+        assert_eq!(geometry.models.len(), 2);
+        assert!(geometry.models[1].surfaces.is_empty());
+
+        // The cube is drawn where it's placed, with its edges and material
+        let model = &geometry.models[0];
+        assert_eq!(model.edges.len(), 12);
+        let placed = model.placed_mesh();
+        let tolerance = Tolerance::default();
+        assert!(placed.contains(EVec3::new(10.5, 0.5, 0.5), &tolerance));
+        assert!(!placed.contains(EVec3::new(0.5, 0.5, 0.5), &tolerance));
+        let mesh = geometry.to_mesh();
+        assert_eq!(mesh.materials.len(), 1);
+        assert_eq!(mesh.materials[0].color, [0.6, 0.6, 0.65, 1.0]);
+    }
+
+    #[test]
+    fn highlight_interference() {
+        let part = cube();
+        let mut geometry = Geometry::new();
+        for x in [0.0, 0.5, 2.0] {
+            let placement = Transform3::translation(EVec3::new(x, 0.0, 0.0));
+            geometry.insert_part(&part, placement, 0.01, 0.5, Rgba::BLACK);
+        }
+        let tolerance = Tolerance::default();
+        let pairs = geometry.interference(&tolerance);
+        assert_eq!(pairs.len(), 3);
+        let overlap = &pairs[0].interference;
+        assert!(overlap.interferes());
+
+        // The contacts are the same triangles as on the part's own mesh, placed
+        // the same way
+        let body = part.body_mesh(0.01).unwrap();
+        let vertices = body.vertices.iter().map(|v| v.position).collect();
+        let mesh = TriMesh::new(vertices, body.triangles);
+        let placed =
+            |x: f64| PlacedMesh::new(&mesh, &Transform3::translation(EVec3::new(x, 0.0, 0.0)));
+        let direct = placed(0.0).interference(&placed(0.5), &tolerance);
+        assert_eq!(direct.first_contacts, overlap.first_contacts);
+        assert_eq!(direct.second_contacts, overlap.second_contacts);
+
+        // Overlapping triangles are outlined, and only the pair closer than the
+        // minimum clearance is marked
+        geometry.highlight_interference(&pairs, 0.75, Rgba::RED, Rgba::YELLOW);
+        assert_eq!(geometry.models.len(), 4);
+        let highlight = &geometry.models[3];
+        assert!(highlight.surfaces.is_empty());
+        let outlined = overlap.first_contacts.len() + overlap.second_contacts.len();
+        assert!(outlined > 0);
+        assert_eq!(highlight.edges.len(), outlined + 1);
+        assert_eq!(highlight.points.len(), 2);
+    }
+}
@@ -1,9 +1,10 @@
 use bytemuck::{Pod, Zeroable};
 use cgmath::{Point3, Vector3};
+use space::Transform3;
 
 use crate::Rgba;
 
-use super::ModelObjectId;
+use super::{transform_direction, transform_point, ModelObjectId};
 
 #[derive(Debug, Clone)]
 pub struct ModelPoint {
@@ -26,6 +27,16 @@ impl ModelPoint {
             color,
         }
     }
+
+    /// Moves the point into the coordinates of a model's transform
+    pub fn transformed(&self, transform: &Transform3) -> Self {
+        Self {
+            id: self.id.clone(),
+            position: transform_point(transform, self.position),
+            expand: transform_direction(transform, self.expand),
+            color: self.color,
+        }
+    }
 }
 
 #[repr(C)]
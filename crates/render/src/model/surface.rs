@@ -1,7 +1,8 @@
 use bytemuck::{Pod, Zeroable};
 use cgmath::{Point3, Vector3};
+use space::{EVec3, MassProperties, Transform3, VolumeIntegrals};
 
-use super::{MaterialId, ModelObjectId};
+use super::{transform_direction, transform_point, MaterialId, ModelObjectId};
 
 #[derive(Clone, Debug)]
 pub struct ModelSurface {
@@ -44,6 +45,29 @@ impl ModelSurface {
     pub fn material_id(&self) -> MaterialId {
         self.material_id
     }
+
+    /// Adds the surface's triangles to integrals over the volume they help enclose.
+    /// Triangles must run counterclockwise when seen from outside the volume.
+    pub fn add_volume_integrals(&self, integrals: &mut VolumeIntegrals) {
+        let point = |index: u32| {
+            let [x, y, z] = self.vertices[index as usize].position;
+            EVec3::new(x as f64, y as f64, z as f64)
+        };
+
+        for triangle in self.indices.chunks_exact(3) {
+            integrals.add_triangle(point(triangle[0]), point(triangle[1]), point(triangle[2]));
+        }
+    }
+}
+
+/// Finds the mass properties of the body enclosed by a set of surfaces that
+/// together form a closed mesh
+pub fn mass_properties(surfaces: &[ModelSurface], density: f64) -> MassProperties {
+    let mut integrals = VolumeIntegrals::new();
+    for surface in surfaces.iter() {
+        surface.add_volume_integrals(&mut integrals);
+    }
+    MassProperties::new(&integrals, density)
 }
 
 #[derive(Default, Debug, Copy, Clone)]
@@ -58,6 +82,14 @@ impl SurfaceVertex {
             normal: [normal.x, normal.y, normal.z],
         }
     }
+
+    /// Moves the vertex into the coordinates of a model's transform
+    pub fn transformed(&self, transform: &Transform3) -> Self {
+        Self {
+            position: transform_point(transform, self.position),
+            normal: transform_direction(transform, self.normal),
+        }
+    }
 }
 
 #[repr(C)]
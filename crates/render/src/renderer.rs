@@ -3,7 +3,7 @@ use super::{
     scene::Scene,
 };
 use crate::lights::LightBuffers;
-use crate::model::GeometryBuffers;
+use crate::model::{Geometry, GeometryBuffers};
 use crate::PixelViewport;
 use bytemuck::{Pod, Zeroable};
 use cgmath::{Point3, Vector2, Vector3};
@@ -73,6 +73,8 @@ pub struct Renderer {
 
     light_buffers: LightBuffers,
     geometry_buffers: GeometryBuffers,
+    /// Whether the scene's geometry changed since its buffers were built
+    geometry_changed: bool,
 
     // Image quad buffers
     full_quad_vertex_buffer: Arc<CpuAccessibleBuffer<[ScreenSpaceVertex]>>,
@@ -161,6 +163,7 @@ impl Renderer {
             framebuffers_rebuilt: true,
 
             geometry_buffers,
+            geometry_changed: false,
             light_buffers,
 
             // Image quad buffers
@@ -588,6 +591,10 @@ impl Renderer {
         queue: Arc<Queue>,
     ) {
         self.update_viewport(pixel_viewport, memory_allocator, queue.clone());
+        if self.geometry_changed {
+            self.geometry_buffers = self.scene.geometry_buffers(memory_allocator);
+            self.geometry_changed = false;
+        }
 
         let mut command_buffer_builder = AutoCommandBufferBuilder::primary(
             command_buffer_allocator,
@@ -864,6 +871,12 @@ impl Renderer {
     pub fn scene_mut(&mut self) -> &mut Scene {
         &mut self.scene
     }
+
+    /// Replaces what the scene shows. Its buffers are rebuilt on the next render.
+    pub fn set_geometry(&mut self, geometry: Geometry) {
+        self.scene.set_geometry(geometry);
+        self.geometry_changed = true;
+    }
 }
 
 struct RendererImages {
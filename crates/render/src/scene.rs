@@ -82,6 +82,15 @@ impl Scene {
         &mut self.orientation
     }
 
+    pub fn geometry(&self) -> &Geometry {
+        &self.geometry
+    }
+
+    /// Replaces what the scene shows, keeping its camera, lights and orientation
+    pub fn set_geometry(&mut self, geometry: Geometry) {
+        self.geometry = geometry;
+    }
+
     pub fn geometry_buffers(&self, allocator: &(impl MemoryAllocator + ?Sized)) -> GeometryBuffers {
         self.geometry.build_buffers(allocator)
     }
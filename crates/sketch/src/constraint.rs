@@ -0,0 +1,269 @@
+use std::f64::consts::PI;
+
+use parameters::unit::Unit;
+use space::{EVec2, EVector};
+
+use crate::{
+    entity::{ArcId, Curve, LineId, PointId},
+    error::{SketchError, SketchResult},
+    Sketch,
+};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct ConstraintId(pub(crate) usize);
+
+/// A geometric relation the solver keeps between entities of a sketch. Distances
+/// are in the units of the sketch and angles in radians.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Constraint {
+    /// Two points are in the same place
+    Coincident(PointId, PointId),
+
+    /// A point stays at a position
+    Fix(PointId, EVec2),
+
+    /// A line runs along the sketch's X axis
+    Horizontal(LineId),
+
+    /// A line runs along the sketch's Y axis
+    Vertical(LineId),
+
+    Parallel(LineId, LineId),
+    Perpendicular(LineId, LineId),
+
+    /// Two curves touch without crossing. Curves that share an end point are
+    /// tangent there, continuing smoothly through it. Otherwise only lines and
+    /// arcs can be tangent, wherever they touch.
+    Tangent(Curve, Curve),
+
+    /// Two lines have the same length, or two arcs the same radius
+    Equal(Curve, Curve),
+
+    /// The distance between two points
+    Distance(PointId, PointId, f64),
+
+    /// The angle from the first line's direction to the second's, counterclockwise
+    Angle(LineId, LineId, f64),
+
+    Radius(ArcId, f64),
+}
+impl Constraint {
+    /// The size held by a distance, angle or radius constraint, and the unit that
+    /// plain numbers given for it are taken to be in
+    pub fn dimension(&self) -> Option<(f64, Unit)> {
+        match self {
+            Self::Distance(_, _, distance) => Some((*distance, Unit::Millimeter)),
+            Self::Angle(_, _, angle) => Some((*angle, Unit::Degree)),
+            Self::Radius(_, radius) => Some((*radius, Unit::Millimeter)),
+            _ => None,
+        }
+    }
+
+    pub(crate) fn dimension_mut(&mut self) -> Option<&mut f64> {
+        match self {
+            Self::Distance(_, _, value) | Self::Angle(_, _, value) | Self::Radius(_, value) => {
+                Some(value)
+            }
+            _ => None,
+        }
+    }
+
+    /// The number of equations the constraint adds to the sketch's system
+    pub(crate) fn equations(&self) -> usize {
+        match self {
+            Self::Coincident(..) | Self::Fix(..) => 2,
+            _ => 1,
+        }
+    }
+
+    /// Checks that the entities exist and that the constraint applies to them
+    pub(crate) fn check(&self, sketch: &Sketch) -> SketchResult<()> {
+        let point = |id: PointId| {
+            sketch
+                .point(id)
+                .map(|_| ())
+                .ok_or(SketchError::MissingPoint(id))
+        };
+        let line = |id: LineId| sketch.check_curve(Curve::Line(id));
+
+        match self {
+            Self::Coincident(a, b) => {
+                point(*a)?;
+                point(*b)
+            }
+            Self::Fix(a, _) => point(*a),
+            Self::Horizontal(l) | Self::Vertical(l) => line(*l),
+            Self::Parallel(a, b) | Self::Perpendicular(a, b) => {
+                line(*a)?;
+                line(*b)
+            }
+            Self::Angle(a, b, angle) => {
+                line(*a)?;
+                line(*b)?;
+                if angle.is_finite() {
+                    Ok(())
+                } else {
+                    Err(SketchError::InvalidValue("angle", *angle))
+                }
+            }
+            Self::Tangent(a, b) => {
+                sketch.check_curve(*a)?;
+                sketch.check_curve(*b)?;
+                match (a, b) {
+                    _ if shared_end(sketch, *a, *b).is_some() => Ok(()),
+                    (Curve::Line(_), Curve::Arc(_))
+                    | (Curve::Arc(_), Curve::Line(_))
+                    | (Curve::Arc(_), Curve::Arc(_)) => Ok(()),
+                    _ => Err(SketchError::Unsupported(
+                        "only lines and arcs can be tangent without sharing an end point",
+                    )),
+                }
+            }
+            Self::Equal(a, b) => {
+                sketch.check_curve(*a)?;
+                sketch.check_curve(*b)?;
+                match (a, b) {
+                    (Curve::Line(_), Curve::Line(_)) | (Curve::Arc(_), Curve::Arc(_)) => Ok(()),
+                    _ => Err(SketchError::Unsupported(
+                        "only lines or arcs can be equal to each other",
+                    )),
+                }
+            }
+            Self::Distance(a, b, distance) => {
+                point(*a)?;
+                point(*b)?;
+                if *distance >= 0.0 && distance.is_finite() {
+                    Ok(())
+                } else {
+                    Err(SketchError::InvalidValue("distance", *distance))
+                }
+            }
+            Self::Radius(arc, radius) => {
+                sketch.check_curve(Curve::Arc(*arc))?;
+                if *radius > 0.0 && radius.is_finite() {
+                    Ok(())
+                } else {
+                    Err(SketchError::InvalidValue("radius", *radius))
+                }
+            }
+        }
+    }
+
+    /// Adds how far the points are from meeting the constraint to `out`, one
+    /// residual for each of its equations
+    pub(crate) fn residuals(&self, sketch: &Sketch, points: &[EVec2], out: &mut Vec<f64>) {
+        let p = |id: PointId| points[id.0];
+        let direction = |id: LineId| {
+            let line = &sketch.lines[id.0];
+            p(line.end) - p(line.start)
+        };
+        let radius = |id: ArcId| {
+            let arc = &sketch.arcs[id.0];
+            (p(arc.start) - p(arc.center)).magnitude()
+        };
+
+        match self {
+            Self::Coincident(a, b) => {
+                let d = p(*a) - p(*b);
+                out.extend([d.x, d.y]);
+            }
+            Self::Fix(a, position) => {
+                let d = p(*a) - *position;
+                out.extend([d.x, d.y]);
+            }
+            Self::Horizontal(l) => out.push(direction(*l).y),
+            Self::Vertical(l) => out.push(direction(*l).x),
+            Self::Parallel(a, b) => out.push(sin_between(direction(*a), direction(*b))),
+            Self::Perpendicular(a, b) => {
+                let (a, b) = (direction(*a), direction(*b));
+                out.push(a.dot(&b) / (a.magnitude() * b.magnitude()));
+            }
+            Self::Tangent(a, b) => out.push(match shared_end(sketch, *a, *b) {
+                Some(point) => sin_between(
+                    tangent(sketch, points, *a, point),
+                    tangent(sketch, points, *b, point),
+                ),
+                None => match (a, b) {
+                    (Curve::Line(l), Curve::Arc(arc)) | (Curve::Arc(arc), Curve::Line(l)) => {
+                        let line = &sketch.lines[l.0];
+                        let d = direction(*l);
+                        let to_center = p(sketch.arcs[arc.0].center) - p(line.start);
+                        cross(d, to_center).abs() / d.magnitude() - radius(*arc)
+                    }
+                    (Curve::Arc(a), Curve::Arc(b)) => {
+                        let between =
+                            (p(sketch.arcs[a.0].center) - p(sketch.arcs[b.0].center)).magnitude();
+                        let (ra, rb) = (radius(*a), radius(*b));
+
+                        // Touching from outside or inside, whichever is nearer
+                        let outside = between - (ra + rb);
+                        let inside = between - (ra - rb).abs();
+                        if outside.abs() < inside.abs() {
+                            outside
+                        } else {
+                            inside
+                        }
+                    }
+                    _ => unreachable!("checked when the constraint was added"),
+                },
+            }),
+            Self::Equal(a, b) => out.push(match (a, b) {
+                (Curve::Line(a), Curve::Line(b)) => {
+                    direction(*a).magnitude() - direction(*b).magnitude()
+                }
+                (Curve::Arc(a), Curve::Arc(b)) => radius(*a) - radius(*b),
+                _ => unreachable!("checked when the constraint was added"),
+            }),
+            Self::Distance(a, b, distance) => out.push((p(*a) - p(*b)).magnitude() - distance),
+            Self::Angle(a, b, angle) => {
+                let (a, b) = (direction(*a), direction(*b));
+                let difference = cross(a, b).atan2(a.dot(&b)) - angle;
+                out.push((difference + PI).rem_euclid(2.0 * PI) - PI);
+            }
+            Self::Radius(arc, r) => out.push(radius(*arc) - r),
+        }
+    }
+}
+
+/// The end point two curves share, if any
+fn shared_end(sketch: &Sketch, a: Curve, b: Curve) -> Option<PointId> {
+    let b_ends = sketch.ends(b);
+    sketch
+        .ends(a)
+        .into_iter()
+        .flatten()
+        .find(|end| b_ends.contains(&Some(*end)))
+}
+
+/// The direction of a curve at one of its end points. Only the line along it
+/// matters, not which way it points.
+fn tangent(sketch: &Sketch, points: &[EVec2], curve: Curve, end: PointId) -> EVec2 {
+    let p = |id: PointId| points[id.0];
+    match curve {
+        Curve::Line(id) => {
+            let line = &sketch.lines[id.0];
+            p(line.end) - p(line.start)
+        }
+        Curve::Arc(id) => {
+            let radial = p(end) - p(sketch.arcs[id.0].center);
+            EVec2::new(-radial.y, radial.x)
+        }
+        Curve::Spline(id) => {
+            let spline = &sketch.splines[id.0];
+            let n = spline.points.len();
+            if spline.points[0] == end {
+                p(spline.points[1]) - p(spline.points[0])
+            } else {
+                p(spline.points[n - 1]) - p(spline.points[n - 2])
+            }
+        }
+    }
+}
+
+fn cross(a: EVec2, b: EVec2) -> f64 {
+    a.x * b.y - a.y * b.x
+}
+
+fn sin_between(a: EVec2, b: EVec2) -> f64 {
+    cross(a, b) / (a.magnitude() * b.magnitude())
+}
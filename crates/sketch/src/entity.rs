@@ -0,0 +1,51 @@
+use spline::math::knot_vector::KnotVector;
+
+macro_rules! entity_id {
+    ( $name:ident ) => {
+        #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+        pub struct $name(pub(crate) usize);
+        impl $name {
+            pub fn index(&self) -> usize {
+                self.0
+            }
+        }
+    };
+}
+
+entity_id!(PointId);
+entity_id!(LineId);
+entity_id!(ArcId);
+entity_id!(SplineId);
+
+/// A straight segment between two of the sketch's points
+#[derive(Debug, Clone, PartialEq)]
+pub struct Line {
+    pub start: PointId,
+    pub end: PointId,
+}
+
+/// A circular arc running counterclockwise from its start point to its end
+/// point. The solver keeps both at the same distance from the center.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Arc {
+    pub center: PointId,
+    pub start: PointId,
+    pub end: PointId,
+}
+
+/// A NURBS curve whose control points are points of the sketch, so they can be
+/// constrained like any other. The weights and knots stay as they were made.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Spline {
+    pub points: Vec<PointId>,
+    pub weights: Vec<f64>,
+    pub knots: KnotVector,
+}
+
+/// Any of the sketch's curves
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Curve {
+    Line(LineId),
+    Arc(ArcId),
+    Spline(SplineId),
+}
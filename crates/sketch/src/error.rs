@@ -0,0 +1,33 @@
+use parameters::error::ParameterError;
+use thiserror::Error;
+
+use crate::{
+    constraint::ConstraintId,
+    entity::{Curve, PointId},
+};
+
+pub type SketchResult<T> = Result<T, SketchError>;
+
+#[derive(Debug, Error, PartialEq)]
+pub enum SketchError {
+    #[error("Point {0:?} does not exist")]
+    MissingPoint(PointId),
+
+    #[error("Curve {0:?} does not exist")]
+    MissingCurve(Curve),
+
+    #[error("Value `{0}` is out of range: {1}")]
+    InvalidValue(&'static str, f64),
+
+    #[error("Unsupported constraint: {0}")]
+    Unsupported(&'static str),
+
+    #[error("Constraint {0:?} does not exist")]
+    MissingConstraint(ConstraintId),
+
+    #[error("Constraint {0:?} is not a dimension")]
+    NotADimension(ConstraintId),
+
+    #[error("Could not evaluate the dimension: {0}")]
+    Parameter(#[from] ParameterError),
+}
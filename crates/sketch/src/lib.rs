@@ -0,0 +1,625 @@
+//! Two dimensional sketches on a plane, made of points, lines, arcs and splines
+//! that are held in place by geometric constraints. Solving a sketch moves its
+//! points as little as possible to meet the constraints, and reports how many
+//! degrees of freedom are left and which constraints repeat others.
+
+use std::collections::BTreeMap;
+
+use parameters::{expression::Expression, Values};
+use space::{hspace::HSpace2, EPlacement3, EPlane3, EVec2, EVec3, EVector, HVec2, Tolerance};
+use spline::nurbs_curve::NurbsCurve;
+
+pub mod constraint;
+pub mod entity;
+pub mod error;
+pub mod solver;
+
+use constraint::{Constraint, ConstraintId};
+use entity::{Arc, ArcId, Curve, Line, LineId, PointId, Spline, SplineId};
+use error::{SketchError, SketchResult};
+use solver::{independent_rows, jacobian, solve, System};
+
+/// How much freedom the constraints of a sketch leave it
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ConstraintState {
+    /// The sketch can still move in this many independent ways
+    UnderConstrained(usize),
+
+    /// The constraints fix every point and are independent of each other
+    WellConstrained,
+
+    /// Some constraints repeat others, or can't all be met together
+    OverConstrained,
+}
+
+/// The outcome of solving a sketch
+#[derive(Debug, Clone, PartialEq)]
+pub struct Solution {
+    /// Whether every constraint was met. If not, the sketch is left as it was.
+    pub converged: bool,
+
+    /// The number of independent ways the sketch can still move
+    pub dof: usize,
+
+    /// The constraints that only restate what the ones before them already fix
+    pub redundant: Vec<ConstraintId>,
+}
+impl Solution {
+    pub fn state(&self) -> ConstraintState {
+        if !self.converged || !self.redundant.is_empty() {
+            ConstraintState::OverConstrained
+        } else if self.dof > 0 {
+            ConstraintState::UnderConstrained(self.dof)
+        } else {
+            ConstraintState::WellConstrained
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Sketch {
+    plane: EPlane3,
+    placement: EPlacement3,
+    pub(crate) points: Vec<EVec2>,
+    pub(crate) lines: Vec<Line>,
+    pub(crate) arcs: Vec<Arc>,
+    pub(crate) splines: Vec<Spline>,
+    constraints: Vec<Option<Constraint>>,
+
+    /// Expressions that set the sizes of dimension constraints
+    dimensions: BTreeMap<ConstraintId, Expression>,
+}
+impl Sketch {
+    /// Creates an empty sketch on a plane. Its X and Y axes are an arbitrary pair
+    /// of directions in the plane, about the point of the plane nearest the
+    /// global origin.
+    pub fn new(plane: EPlane3) -> Self {
+        let placement = EPlacement3::from_axis(
+            plane.closest_to_point(&EVec3::new(0.0, 0.0, 0.0)),
+            plane.norm,
+        );
+        Self {
+            plane,
+            placement,
+            points: Vec::new(),
+            lines: Vec::new(),
+            arcs: Vec::new(),
+            splines: Vec::new(),
+            constraints: Vec::new(),
+            dimensions: BTreeMap::new(),
+        }
+    }
+
+    pub fn plane(&self) -> &EPlane3 {
+        &self.plane
+    }
+
+    /// The position of a point of the sketch in global coordinates
+    pub fn to_global(&self, point: EVec2) -> EVec3 {
+        self.placement.planar_to_global(point)
+    }
+
+    pub fn add_point(&mut self, position: EVec2) -> PointId {
+        self.points.push(position);
+        PointId(self.points.len() - 1)
+    }
+
+    pub fn point(&self, id: PointId) -> Option<EVec2> {
+        self.points.get(id.0).copied()
+    }
+
+    /// Moves a point, as when it is dragged. Solving afterwards brings the
+    /// sketch back in line with its constraints, moving the rest of it as little
+    /// as possible.
+    pub fn move_point(&mut self, id: PointId, position: EVec2) -> SketchResult<()> {
+        let point = self
+            .points
+            .get_mut(id.0)
+            .ok_or(SketchError::MissingPoint(id))?;
+        *point = position;
+        Ok(())
+    }
+
+    pub fn add_line(&mut self, start: PointId, end: PointId) -> SketchResult<LineId> {
+        self.check_points(&[start, end])?;
+        self.lines.push(Line { start, end });
+        Ok(LineId(self.lines.len() - 1))
+    }
+
+    pub fn line(&self, id: LineId) -> Option<&Line> {
+        self.lines.get(id.0)
+    }
+
+    pub fn add_arc(
+        &mut self,
+        center: PointId,
+        start: PointId,
+        end: PointId,
+    ) -> SketchResult<ArcId> {
+        self.check_points(&[center, start, end])?;
+        self.arcs.push(Arc { center, start, end });
+        Ok(ArcId(self.arcs.len() - 1))
+    }
+
+    pub fn arc(&self, id: ArcId) -> Option<&Arc> {
+        self.arcs.get(id.0)
+    }
+
+    pub fn arc_radius(&self, id: ArcId) -> Option<f64> {
+        let arc = self.arc(id)?;
+        Some((self.points[arc.start.0] - self.points[arc.center.0]).magnitude())
+    }
+
+    /// Adds a spline, with a new point of the sketch for each of its control
+    /// points
+    pub fn add_spline(&mut self, curve: &NurbsCurve<HSpace2>) -> SplineId {
+        let points = curve
+            .control_points()
+            .iter()
+            .map(|point| self.add_point(EVec2::new(point.x, point.y)))
+            .collect();
+        self.splines.push(Spline {
+            points,
+            weights: curve.control_points().iter().map(|point| point.h).collect(),
+            knots: curve.knot_vector().clone(),
+        });
+        SplineId(self.splines.len() - 1)
+    }
+
+    pub fn spline(&self, id: SplineId) -> Option<&Spline> {
+        self.splines.get(id.0)
+    }
+
+    /// The spline as a curve, through the current positions of its points
+    pub fn spline_curve(&self, id: SplineId) -> Option<NurbsCurve<HSpace2>> {
+        let spline = self.spline(id)?;
+        Some(NurbsCurve::new(
+            spline
+                .points
+                .iter()
+                .zip(spline.weights.iter())
+                .map(|(point, weight)| {
+                    let point = self.points[point.0];
+                    HVec2::new(point.x, point.y, *weight)
+                })
+                .collect(),
+            spline.knots.clone(),
+        ))
+    }
+
+    pub fn add_constraint(&mut self, constraint: Constraint) -> SketchResult<ConstraintId> {
+        constraint.check(self)?;
+        self.constraints.push(Some(constraint));
+        Ok(ConstraintId(self.constraints.len() - 1))
+    }
+
+    pub fn remove_constraint(&mut self, id: ConstraintId) -> Option<Constraint> {
+        self.dimensions.remove(&id);
+        self.constraints.get_mut(id.0)?.take()
+    }
+
+    pub fn constraint(&self, id: ConstraintId) -> Option<&Constraint> {
+        self.constraints.get(id.0)?.as_ref()
+    }
+
+    /// Drives the size of a distance, angle or radius constraint by an
+    /// expression, or leaves it at its current size if `None`. The expression is
+    /// evaluated by [`Self::apply_parameters`].
+    pub fn set_dimension(
+        &mut self,
+        id: ConstraintId,
+        expression: Option<Expression>,
+    ) -> SketchResult<()> {
+        let constraint = self
+            .constraint(id)
+            .ok_or(SketchError::MissingConstraint(id))?;
+        if constraint.dimension().is_none() {
+            return Err(SketchError::NotADimension(id));
+        }
+        match expression {
+            Some(expression) => self.dimensions.insert(id, expression),
+            None => self.dimensions.remove(&id),
+        };
+        Ok(())
+    }
+
+    pub fn dimension_expression(&self, id: ConstraintId) -> Option<&Expression> {
+        self.dimensions.get(&id)
+    }
+
+    /// Sets the sizes of the dimension constraints driven by expressions from the
+    /// values of a parameter table. Dimensions whose expressions fail, or give
+    /// sizes the constraint can't have, keep their current sizes and are
+    /// returned with the reason. The sketch has to be solved again afterwards.
+    pub fn apply_parameters(&mut self, values: &Values) -> Vec<(ConstraintId, SketchError)> {
+        let mut errors = Vec::new();
+        for (id, expression) in self.dimensions.iter() {
+            let Some(mut constraint) = self.constraints[id.0].clone() else {
+                continue;
+            };
+            let (_, unit) = constraint.dimension().unwrap();
+            let result = expression
+                .evaluate(values)
+                .and_then(|value| value.base_value(unit))
+                .map_err(SketchError::from)
+                .and_then(|value| {
+                    *constraint.dimension_mut().unwrap() = value;
+                    constraint.check(self)
+                });
+            match result {
+                Ok(()) => self.constraints[id.0] = Some(constraint),
+                Err(error) => errors.push((*id, error)),
+            }
+        }
+        errors
+    }
+
+    pub fn constraints(&self) -> impl Iterator<Item = (ConstraintId, &Constraint)> {
+        self.constraints
+            .iter()
+            .enumerate()
+            .filter_map(|(i, constraint)| Some((ConstraintId(i), constraint.as_ref()?)))
+    }
+
+    /// Moves the points as little as possible to meet every constraint, and
+    /// reports how constrained the sketch is
+    pub fn solve(&mut self) -> Solution {
+        self.solve_with(&Tolerance::DEFAULT)
+    }
+
+    /// Solves the sketch like [`Self::solve`], meeting the constraints to the
+    /// tolerances of a model rather than the default ones
+    pub fn solve_with(&mut self, tolerance: &Tolerance) -> Solution {
+        let start = self
+            .points
+            .iter()
+            .flat_map(|point| [point.x, point.y])
+            .collect::<Vec<_>>();
+        let mut x = start.clone();
+        let converged = solve(self, &mut x, tolerance);
+        if converged {
+            self.points = unknowns_to_points(&x);
+        } else {
+            // The points stay where they were, so that's where the sketch is
+            // reported on
+            x = start;
+        }
+
+        // Each arc's own equation comes first, so constraints are only counted as
+        // redundant when they repeat other constraints or the shape of an arc
+        let jacobian = jacobian(self, &x);
+        let independent = independent_rows(&jacobian);
+        let mut redundant = Vec::new();
+        let mut row = self.arcs.len();
+        for (id, constraint) in self.constraints() {
+            let rows = row..row + constraint.equations();
+            if rows.clone().any(|row| !independent[row]) {
+                redundant.push(id);
+            }
+            row = rows.end;
+        }
+
+        Solution {
+            converged,
+            dof: x.len() - independent.iter().filter(|i| **i).count(),
+            redundant,
+        }
+    }
+
+    /// The points a curve ends at, if it has ends
+    pub fn ends(&self, curve: Curve) -> [Option<PointId>; 2] {
+        match curve {
+            Curve::Line(id) => self
+                .line(id)
+                .map_or([None, None], |line| [Some(line.start), Some(line.end)]),
+            Curve::Arc(id) => self
+                .arc(id)
+                .map_or([None, None], |arc| [Some(arc.start), Some(arc.end)]),
+            Curve::Spline(id) => match self.spline_curve(id) {
+                Some(curve) if curve.is_clamped() => {
+                    let points = &self.splines[id.0].points;
+                    [points.first().copied(), points.last().copied()]
+                }
+                _ => [None, None],
+            },
+        }
+    }
+
+    pub(crate) fn check_curve(&self, curve: Curve) -> SketchResult<()> {
+        let exists = match curve {
+            Curve::Line(id) => self.line(id).is_some(),
+            Curve::Arc(id) => self.arc(id).is_some(),
+            Curve::Spline(id) => self.spline(id).is_some(),
+        };
+        if exists {
+            Ok(())
+        } else {
+            Err(SketchError::MissingCurve(curve))
+        }
+    }
+
+    fn check_points(&self, points: &[PointId]) -> SketchResult<()> {
+        match points.iter().find(|id| self.point(**id).is_none()) {
+            Some(id) => Err(SketchError::MissingPoint(*id)),
+            None => Ok(()),
+        }
+    }
+}
+impl System for Sketch {
+    fn residuals(&self, x: &[f64]) -> Vec<f64> {
+        let points = unknowns_to_points(x);
+        let mut residuals = Vec::new();
+        for arc in self.arcs.iter() {
+            let center = points[arc.center.0];
+            residuals.push(
+                (points[arc.start.0] - center).magnitude()
+                    - (points[arc.end.0] - center).magnitude(),
+            );
+        }
+        for (_, constraint) in self.constraints() {
+            constraint.residuals(self, &points, &mut residuals);
+        }
+        residuals
+    }
+}
+
+fn unknowns_to_points(x: &[f64]) -> Vec<EVec2> {
+    x.chunks(2).map(|xy| EVec2::new(xy[0], xy[1])).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use std::f64::consts::PI;
+
+    use parameters::{expression::Expression, unit::Unit, ParameterTable};
+    use space::{hspace::HSpace2, EPlane3, EVec2, EVec3, EVector, HVec2};
+    use spline::{math::knot_vector::KnotVector, nurbs_curve::NurbsCurve};
+
+    use crate::{
+        constraint::Constraint,
+        entity::{Curve, PointId},
+        error::SketchError,
+        ConstraintState, Sketch,
+    };
+
+    fn sketch() -> Sketch {
+        Sketch::new(EPlane3::new_from_normal_vec(
+            EVec3::new(0.0, 0.0, 1.0),
+            -2.0,
+        ))
+    }
+
+    fn distance(sketch: &Sketch, a: PointId, b: PointId) -> f64 {
+        (sketch.point(a).unwrap() - sketch.point(b).unwrap()).magnitude()
+    }
+
+    fn close(a: EVec2, b: EVec2) -> bool {
+        (a - b).magnitude() < 1e-6
+    }
+
+    #[test]
+    fn rectangle() {
+        let mut sketch = sketch();
+        let corners = [(0.1, -0.2), (2.7, 0.3), (3.1, 2.2), (-0.3, 1.8)]
+            .map(|(x, y)| sketch.add_point(EVec2::new(x, y)));
+        let sides =
+            [0, 1, 2, 3].map(|i| sketch.add_line(corners[i], corners[(i + 1) % 4]).unwrap());
+        for (i, side) in sides.iter().enumerate() {
+            sketch
+                .add_constraint(if i % 2 == 0 {
+                    Constraint::Horizontal(*side)
+                } else {
+                    Constraint::Vertical(*side)
+                })
+                .unwrap();
+        }
+
+        // Position, width and height are left free
+        let solution = sketch.solve();
+        assert!(solution.converged);
+        assert_eq!(solution.state(), ConstraintState::UnderConstrained(4));
+
+        sketch
+            .add_constraint(Constraint::Fix(corners[0], EVec2::new(0.0, 0.0)))
+            .unwrap();
+        sketch
+            .add_constraint(Constraint::Distance(corners[0], corners[1], 3.0))
+            .unwrap();
+        sketch
+            .add_constraint(Constraint::Distance(corners[1], corners[2], 2.0))
+            .unwrap();
+        assert_eq!(sketch.solve().state(), ConstraintState::WellConstrained);
+        let expected = [(0.0, 0.0), (3.0, 0.0), (3.0, 2.0), (0.0, 2.0)];
+        for (corner, (x, y)) in corners.iter().zip(expected) {
+            assert!(close(sketch.point(*corner).unwrap(), EVec2::new(x, y)));
+        }
+        assert_eq!(
+            sketch.to_global(EVec2::new(0.0, 0.0)),
+            EVec3::new(0.0, 0.0, 2.0)
+        );
+
+        // Opposite sides are already parallel
+        let parallel = sketch
+            .add_constraint(Constraint::Parallel(sides[0], sides[2]))
+            .unwrap();
+        let solution = sketch.solve();
+        assert!(solution.converged);
+        assert_eq!(solution.redundant, vec![parallel]);
+        assert_eq!(solution.state(), ConstraintState::OverConstrained);
+    }
+
+    #[test]
+    fn conflicting_constraints() {
+        let mut sketch = sketch();
+        let a = sketch.add_point(EVec2::new(0.0, 0.0));
+        let b = sketch.add_point(EVec2::new(1.5, 0.5));
+        sketch
+            .add_constraint(Constraint::Distance(a, b, 1.0))
+            .unwrap();
+        let conflict = sketch
+            .add_constraint(Constraint::Distance(a, b, 2.0))
+            .unwrap();
+
+        let solution = sketch.solve();
+        assert!(!solution.converged);
+        assert_eq!(solution.redundant, vec![conflict]);
+        assert_eq!(sketch.point(b), Some(EVec2::new(1.5, 0.5)));
+
+        sketch.remove_constraint(conflict);
+        let solution = sketch.solve();
+        assert!(solution.converged);
+        assert_eq!(solution.state(), ConstraintState::UnderConstrained(3));
+        assert!(
+            ((sketch.point(b).unwrap() - sketch.point(a).unwrap()).magnitude() - 1.0).abs() < 1e-9
+        );
+    }
+
+    #[test]
+    fn parameter_dimensions() {
+        let mut sketch = sketch();
+        let a = sketch.add_point(EVec2::new(0.0, 0.0));
+        let b = sketch.add_point(EVec2::new(1.0, 0.0));
+        let fix = sketch
+            .add_constraint(Constraint::Fix(a, EVec2::new(0.0, 0.0)))
+            .unwrap();
+        let length = sketch
+            .add_constraint(Constraint::Distance(a, b, 1.0))
+            .unwrap();
+        assert_eq!(
+            sketch.set_dimension(fix, Some(Expression::parse("1").unwrap())),
+            Err(SketchError::NotADimension(fix))
+        );
+        sketch
+            .set_dimension(length, Some(Expression::parse("2 * width").unwrap()))
+            .unwrap();
+
+        let mut table = ParameterTable::new();
+        table.set("width", "1 cm", Unit::Millimeter).unwrap();
+        assert!(sketch.apply_parameters(&table.evaluate()).is_empty());
+        assert!(sketch.solve().converged);
+        assert!((distance(&sketch, a, b) - 20.0).abs() < 1e-6);
+
+        // Bad values leave the dimension as it was
+        table.set("width", "-1", Unit::Millimeter).unwrap();
+        let errors = sketch.apply_parameters(&table.evaluate());
+        assert_eq!(
+            errors,
+            vec![(length, SketchError::InvalidValue("distance", -2.0))]
+        );
+        assert_eq!(
+            sketch.constraint(length).unwrap().dimension().unwrap().0,
+            20.0
+        );
+    }
+
+    #[test]
+    fn lines_and_arcs() {
+        let mut sketch = sketch();
+        let points = [
+            (0.0, 0.0),
+            (2.0, 0.3),
+            (-0.2, 1.7),
+            (1.2, 1.4),
+            (0.4, 2.6),
+            (2.2, 1.3),
+        ]
+        .map(|(x, y)| sketch.add_point(EVec2::new(x, y)));
+        let base = sketch.add_line(points[0], points[1]).unwrap();
+        let side = sketch.add_line(points[0], points[2]).unwrap();
+        let arc = sketch.add_arc(points[3], points[4], points[5]).unwrap();
+
+        // Two lines from a common corner, and an arc with five degrees of freedom:
+        // its center, radius and two angles
+        assert_eq!(sketch.solve().dof, 6 + 5);
+        assert_eq!(
+            sketch.add_constraint(Constraint::Angle(base, side, f64::INFINITY)),
+            Err(SketchError::InvalidValue("angle", f64::INFINITY))
+        );
+
+        for constraint in [
+            Constraint::Horizontal(base),
+            Constraint::Perpendicular(base, side),
+            Constraint::Equal(Curve::Line(base), Curve::Line(side)),
+            Constraint::Radius(arc, 0.5),
+            Constraint::Tangent(Curve::Line(base), Curve::Arc(arc)),
+            Constraint::Angle(base, side, PI / 2.0),
+        ] {
+            sketch.add_constraint(constraint).unwrap();
+        }
+        let solution = sketch.solve();
+        assert!(solution.converged);
+        assert_eq!(solution.redundant.len(), 1);
+
+        assert!(
+            (distance(&sketch, points[0], points[1]) - distance(&sketch, points[0], points[2]))
+                .abs()
+                < 1e-9
+        );
+        assert!((sketch.arc_radius(arc).unwrap() - 0.5).abs() < 1e-9);
+        assert!((distance(&sketch, points[3], points[5]) - 0.5).abs() < 1e-9);
+        let [base_start, center] = [points[0], points[3]].map(|id| sketch.point(id).unwrap());
+        assert!((center.y - base_start.y - 0.5).abs() < 1e-9);
+
+        // Arcs can touch each other from either side
+        let [center, start, end] =
+            [(2.0, 2.5), (2.5, 2.0), (3.0, 3.0)].map(|(x, y)| sketch.add_point(EVec2::new(x, y)));
+        let other = sketch.add_arc(center, start, end).unwrap();
+        sketch
+            .add_constraint(Constraint::Tangent(Curve::Arc(arc), Curve::Arc(other)))
+            .unwrap();
+        assert!(sketch.solve().converged);
+        let between = distance(&sketch, center, points[3]);
+        let [r1, r2] = [arc, other].map(|arc| sketch.arc_radius(arc).unwrap());
+        assert!((between - (r1 + r2)).abs() < 1e-9 || (between - (r1 - r2).abs()).abs() < 1e-9);
+    }
+
+    #[test]
+    fn splines() {
+        let mut sketch = sketch();
+        let curve = NurbsCurve::<HSpace2>::new(
+            vec![
+                HVec2::new(1.0, 0.0, 1.0),
+                HVec2::new(2.0, 0.5, 0.5),
+                HVec2::new(3.0, -1.0, 1.0),
+                HVec2::new(4.0, 0.0, 1.0),
+            ],
+            KnotVector::new([0.0, 0.0, 0.0, 0.5, 1.0, 1.0, 1.0]),
+        );
+        let spline = sketch.add_spline(&curve);
+        let [first, last] = sketch.ends(Curve::Spline(spline));
+        let start = sketch.add_point(EVec2::new(0.0, 0.0));
+        let line = sketch.add_line(start, first.unwrap()).unwrap();
+
+        sketch
+            .add_constraint(Constraint::Fix(start, EVec2::new(0.0, 0.0)))
+            .unwrap();
+        sketch.add_constraint(Constraint::Horizontal(line)).unwrap();
+        sketch
+            .add_constraint(Constraint::Tangent(
+                Curve::Line(line),
+                Curve::Spline(spline),
+            ))
+            .unwrap();
+        assert!(sketch.solve().converged);
+
+        // The spline leaves the line's end heading along it
+        let curve = sketch.spline_curve(spline).unwrap();
+        assert!(curve.tangent(curve.min_u()).y.abs() < 1e-6);
+        assert_eq!(curve.control_points()[1].h, 0.5);
+        assert!(close(
+            curve.point(curve.max_u()),
+            sketch.point(last.unwrap()).unwrap()
+        ));
+
+        // Splines can only be tangent where they meet another curve
+        let apart = sketch.add_point(EVec2::new(5.0, 5.0));
+        let other = sketch.add_line(apart, start).unwrap();
+        assert!(matches!(
+            sketch.add_constraint(Constraint::Tangent(
+                Curve::Spline(spline),
+                Curve::Line(other)
+            )),
+            Err(SketchError::Unsupported(_))
+        ));
+    }
+}
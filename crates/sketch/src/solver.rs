@@ -0,0 +1,184 @@
+//! Numerical solution of systems of nonlinear equations, used to solve sketches
+//! and to place the instances of assemblies. The systems usually have fewer
+//! equations than unknowns, so each step is the smallest one that solves the
+//! linearized equations, which leaves whatever isn't constrained where it is.
+
+use space::Tolerance;
+
+/// The most steps taken before giving up on a system
+const MAX_ITERATIONS: usize = 100;
+
+/// The step used to find the derivatives of the equations numerically
+const DIFFERENCE_STEP: f64 = 1e-6;
+
+/// How much of a row of the Jacobian has to be left, relative to its length,
+/// once the rows before it are projected out of it for it to count as
+/// independent of them
+const RANK_TOL: f64 = 1e-6;
+
+/// Equations in a vector of unknowns, which are solved when every residual is
+/// zero
+pub trait System {
+    fn residuals(&self, x: &[f64]) -> Vec<f64>;
+}
+
+/// Solves a system with damped Gauss-Newton steps, starting from `x`. Returns
+/// whether every residual was brought within the linear tolerance. Otherwise `x`
+/// is left where the residuals were smallest.
+pub fn solve(system: &impl System, x: &mut Vec<f64>, tolerance: &Tolerance) -> bool {
+    let mut residuals = system.residuals(x);
+    let mut damping = 1e-9;
+
+    for _ in 0..MAX_ITERATIONS {
+        if max_abs(&residuals) <= tolerance.linear {
+            return true;
+        }
+
+        let jacobian = jacobian(system, x);
+        loop {
+            let Some(step) = step(&jacobian, &residuals, damping) else {
+                return false;
+            };
+            let candidate = x
+                .iter()
+                .zip(step.iter())
+                .map(|(x, s)| x + s)
+                .collect::<Vec<_>>();
+            let candidate_residuals = system.residuals(&candidate);
+            if norm2(&candidate_residuals) < norm2(&residuals) {
+                *x = candidate;
+                residuals = candidate_residuals;
+                damping = (damping / 10.0).max(1e-12);
+                break;
+            }
+
+            damping *= 10.0;
+            if damping > 1e6 {
+                return max_abs(&residuals) <= tolerance.linear;
+            }
+        }
+    }
+    max_abs(&residuals) <= tolerance.linear
+}
+
+/// The derivatives of each residual with respect to each unknown, as rows of
+/// residuals, found by central differences
+pub fn jacobian(system: &impl System, x: &[f64]) -> Vec<Vec<f64>> {
+    let mut x = x.to_vec();
+    let mut columns = Vec::with_capacity(x.len());
+    for i in 0..x.len() {
+        let value = x[i];
+        x[i] = value + DIFFERENCE_STEP;
+        let after = system.residuals(&x);
+        x[i] = value - DIFFERENCE_STEP;
+        let before = system.residuals(&x);
+        x[i] = value;
+
+        columns.push(
+            after
+                .iter()
+                .zip(before.iter())
+                .map(|(a, b)| (a - b) / (2.0 * DIFFERENCE_STEP))
+                .collect::<Vec<_>>(),
+        );
+    }
+
+    let rows = system.residuals(&x).len();
+    (0..rows)
+        .map(|row| columns.iter().map(|column| column[row]).collect())
+        .collect()
+}
+
+/// Which rows of a matrix are independent of the rows before them
+pub fn independent_rows(matrix: &[Vec<f64>]) -> Vec<bool> {
+    let mut basis: Vec<Vec<f64>> = Vec::new();
+    matrix
+        .iter()
+        .map(|row| {
+            let length = norm2(row).sqrt();
+            let mut rest = row.clone();
+
+            // Projecting twice keeps the basis orthogonal despite rounding
+            for _ in 0..2 {
+                for b in basis.iter() {
+                    let dot = dot(&rest, b);
+                    rest.iter_mut()
+                        .zip(b.iter())
+                        .for_each(|(r, b)| *r -= dot * b);
+                }
+            }
+
+            let rest_length = norm2(&rest).sqrt();
+            if rest_length > RANK_TOL * length.max(1.0) {
+                basis.push(rest.iter().map(|r| r / rest_length).collect());
+                true
+            } else {
+                false
+            }
+        })
+        .collect()
+}
+
+/// The smallest step that solves the linearized equations `J dx = -f`, damped to
+/// stay finite when the equations depend on each other:
+/// `dx = -Jᵀ (J Jᵀ + λI)⁻¹ f`
+fn step(jacobian: &[Vec<f64>], residuals: &[f64], damping: f64) -> Option<Vec<f64>> {
+    let m = residuals.len();
+    let mut matrix = (0..m)
+        .map(|i| {
+            (0..m)
+                .map(|j| dot(&jacobian[i], &jacobian[j]) + if i == j { damping } else { 0.0 })
+                .collect::<Vec<_>>()
+        })
+        .collect::<Vec<_>>();
+    let y = solve_linear(&mut matrix, residuals.to_vec())?;
+
+    let n = jacobian.first().map_or(0, |row| row.len());
+    Some(
+        (0..n)
+            .map(|k| -(0..m).map(|i| jacobian[i][k] * y[i]).sum::<f64>())
+            .collect(),
+    )
+}
+
+/// Solves a square linear system by Gaussian elimination with partial pivoting
+fn solve_linear(matrix: &mut [Vec<f64>], mut b: Vec<f64>) -> Option<Vec<f64>> {
+    let n = b.len();
+    for col in 0..n {
+        let pivot =
+            (col..n).max_by(|&i, &j| matrix[i][col].abs().total_cmp(&matrix[j][col].abs()))?;
+        if matrix[pivot][col].abs() <= f64::MIN_POSITIVE {
+            return None;
+        }
+        matrix.swap(col, pivot);
+        b.swap(col, pivot);
+
+        let (above, below) = matrix.split_at_mut(col + 1);
+        let pivot_row = &above[col];
+        for (i, row) in below.iter_mut().enumerate() {
+            let factor = row[col] / pivot_row[col];
+            for (value, pivot_value) in row[col..].iter_mut().zip(pivot_row[col..].iter()) {
+                *value -= factor * pivot_value;
+            }
+            b[col + 1 + i] -= factor * b[col];
+        }
+    }
+
+    for col in (0..n).rev() {
+        let sum = (col + 1..n).map(|k| matrix[col][k] * b[k]).sum::<f64>();
+        b[col] = (b[col] - sum) / matrix[col][col];
+    }
+    Some(b)
+}
+
+fn dot(a: &[f64], b: &[f64]) -> f64 {
+    a.iter().zip(b.iter()).map(|(a, b)| a * b).sum()
+}
+
+fn norm2(v: &[f64]) -> f64 {
+    dot(v, v)
+}
+
+fn max_abs(v: &[f64]) -> f64 {
+    v.iter().fold(0.0, |max, x| x.abs().max(max))
+}
@@ -0,0 +1,146 @@
+use crate::{EVec2, EVec3, EVector, Transform3, TOL};
+
+/// A local coordinate frame in 2D Euclidean space, used to position geometry
+/// that is constructed around the origin.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct EPlacement2 {
+    pub origin: EVec2,
+    pub x_dir: EVec2,
+}
+impl EPlacement2 {
+    pub fn new(origin: EVec2, x_dir: EVec2) -> Self {
+        assert!(
+            x_dir.magnitude() > TOL,
+            "Placement direction must not be zero"
+        );
+
+        Self {
+            origin,
+            x_dir: x_dir.normalize(),
+        }
+    }
+
+    pub fn from_origin(origin: EVec2) -> Self {
+        Self::new(origin, EVec2::new(1.0, 0.0))
+    }
+
+    pub fn y_dir(&self) -> EVec2 {
+        EVec2::new(-self.x_dir.y, self.x_dir.x)
+    }
+
+    /// Converts a point in local coordinates to global coordinates
+    pub fn to_global(&self, local: EVec2) -> EVec2 {
+        self.origin + self.x_dir * local.x + self.y_dir() * local.y
+    }
+
+    /// Converts a point in global coordinates to local coordinates
+    pub fn to_local(&self, global: EVec2) -> EVec2 {
+        let rel = global - self.origin;
+        EVec2::new(rel.dot(&self.x_dir), rel.dot(&self.y_dir()))
+    }
+}
+impl Default for EPlacement2 {
+    fn default() -> Self {
+        Self::from_origin(EVec2::zero())
+    }
+}
+
+/// A right-handed local coordinate frame in 3D Euclidean space. Planar geometry
+/// is constructed in the local XY plane, and the local Z axis is used as the axis
+/// of rotationally symmetric geometry.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct EPlacement3 {
+    pub origin: EVec3,
+    pub x_dir: EVec3,
+    pub y_dir: EVec3,
+}
+impl EPlacement3 {
+    /// Creates a placement from an origin and two in-plane directions. The
+    /// directions are normalized and `y_dir` is made perpendicular to `x_dir`.
+    pub fn new(origin: EVec3, x_dir: EVec3, y_dir: EVec3) -> Self {
+        let x_dir = x_dir.normalize();
+        let y_dir = y_dir - x_dir * y_dir.dot(&x_dir);
+        assert!(
+            y_dir.magnitude() > TOL,
+            "Placement directions must not be parallel"
+        );
+
+        Self {
+            origin,
+            x_dir,
+            y_dir: y_dir.normalize(),
+        }
+    }
+
+    /// Creates a placement whose Z axis points along `axis`, with an arbitrary
+    /// choice of X axis.
+    pub fn from_axis(origin: EVec3, axis: EVec3) -> Self {
+        let axis = axis.normalize();
+
+        // Cross with whichever global axis is least aligned with the given one
+        let reference = if axis.x.abs() <= axis.y.abs() && axis.x.abs() <= axis.z.abs() {
+            EVec3::new(1.0, 0.0, 0.0)
+        } else if axis.y.abs() <= axis.z.abs() {
+            EVec3::new(0.0, 1.0, 0.0)
+        } else {
+            EVec3::new(0.0, 0.0, 1.0)
+        };
+
+        let x_dir = reference.cross(&axis).normalize();
+        let y_dir = axis.cross(&x_dir);
+
+        Self::new(origin, x_dir, y_dir)
+    }
+
+    /// Creates a placement with the Z axis along `axis` and the X axis along the
+    /// component of `x_dir` perpendicular to it.
+    pub fn from_axis_and_x(origin: EVec3, axis: EVec3, x_dir: EVec3) -> Self {
+        let axis = axis.normalize();
+        let x_dir = x_dir - axis * x_dir.dot(&axis);
+        Self::new(origin, x_dir, axis.cross(&x_dir))
+    }
+
+    pub fn from_origin(origin: EVec3) -> Self {
+        Self::new(origin, EVec3::new(1.0, 0.0, 0.0), EVec3::new(0.0, 1.0, 0.0))
+    }
+
+    pub fn z_dir(&self) -> EVec3 {
+        self.x_dir.cross(&self.y_dir)
+    }
+
+    /// Converts a point in the local XY plane to global coordinates
+    pub fn planar_to_global(&self, local: EVec2) -> EVec3 {
+        self.origin + self.x_dir * local.x + self.y_dir * local.y
+    }
+
+    /// Converts a point in local coordinates to global coordinates
+    pub fn to_global(&self, local: EVec3) -> EVec3 {
+        self.origin + self.x_dir * local.x + self.y_dir * local.y + self.z_dir() * local.z
+    }
+
+    /// Converts a point in global coordinates to local coordinates
+    pub fn to_local(&self, global: EVec3) -> EVec3 {
+        let rel = global - self.origin;
+        EVec3::new(
+            rel.dot(&self.x_dir),
+            rel.dot(&self.y_dir),
+            rel.dot(&self.z_dir()),
+        )
+    }
+
+    /// The transform that converts local coordinates to global coordinates
+    pub fn transform(&self) -> Transform3 {
+        let (x, y, z) = (self.x_dir, self.y_dir, self.z_dir());
+        Transform3::affine(
+            [[x.x, y.x, z.x], [x.y, y.y, z.y], [x.z, y.z, z.z]],
+            self.origin,
+        )
+    }
+}
+impl Default for EPlacement3 {
+    fn default() -> Self {
+        Self::from_origin(EVec3::zero())
+    }
+}
@@ -77,6 +77,7 @@ macro_rules! impl_evector_ops {
 
 /// A vector in 1-dimensional Euclidean space
 #[derive(Debug, Copy, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct EVec1 {
     pub x: f64,
 }
@@ -109,6 +110,7 @@ impl_evector_ops!(EVec1, x);
 
 /// A vector in 2-dimensional Euclidean space
 #[derive(Debug, Copy, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct EVec2 {
     pub x: f64,
     pub y: f64,
@@ -149,6 +151,7 @@ impl_evector_ops!(EVec2, x, y);
 
 /// A vector in 3-dimensional Euclidean space
 #[derive(Debug, Copy, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct EVec3 {
     pub x: f64,
     pub y: f64,
@@ -201,7 +204,7 @@ impl_evector_ops!(EVec3, x, y, z);
 
 /// A vector in 4-dimensional Euclidean space
 #[derive(Debug, Copy, Clone, PartialEq)]
-
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct EVec4 {
     pub x: f64,
     pub y: f64,
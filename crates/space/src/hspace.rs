@@ -1,7 +1,7 @@
 use crate::{
     ELine, ELine2, ELine3, EPlane, EPlane3, EUnimplementedLine, EUnimplementedPlane,
     EUnimplementedVector, EVec1, EVec2, EVec3, EVec4, EVector, HUnimplementedVector, HVec1, HVec2,
-    HVec3, HVector, TOL,
+    HVec3, HVector, PointAtInfinity, Transform3, TOL,
 };
 use std::fmt::Debug;
 
@@ -46,6 +46,11 @@ pub trait HSpace: Debug + Clone {
         vec: Self::ProjectedVector,
     ) -> <Self::Lower as HSpace>::ProjectedVector;
     fn truncate_weighted_vec(weighted: Self::WeightedVector) -> Self::ProjectedVector;
+    /// Transforms a control point, failing if it is sent to infinity
+    fn transform_vec(
+        transform: &Transform3,
+        hvec: Self::Vector,
+    ) -> Result<Self::Vector, PointAtInfinity>;
 
     fn make_line_through_points(
         p1: Self::ProjectedVector,
@@ -142,6 +147,13 @@ impl HSpace for HUnimplementedSpace {
     ) -> Self::ProjectedVector {
         unimplemented!()
     }
+
+    fn transform_vec(
+        _transform: &Transform3,
+        _hvec: Self::Vector,
+    ) -> Result<Self::Vector, PointAtInfinity> {
+        unimplemented!()
+    }
 }
 
 #[derive(Debug, Clone)]
@@ -240,6 +252,13 @@ impl HSpace for HSpace1 {
     ) -> Self::ProjectedVector {
         unimplemented!()
     }
+
+    fn transform_vec(
+        transform: &Transform3,
+        hvec: Self::Vector,
+    ) -> Result<Self::Vector, PointAtInfinity> {
+        transform.apply_hvec1(hvec)
+    }
 }
 
 #[derive(Debug, Clone)]
@@ -361,6 +380,13 @@ impl HSpace for HSpace2 {
     ) -> Self::ProjectedVector {
         unimplemented!()
     }
+
+    fn transform_vec(
+        transform: &Transform3,
+        hvec: Self::Vector,
+    ) -> Result<Self::Vector, PointAtInfinity> {
+        transform.apply_hvec2(hvec)
+    }
 }
 
 #[derive(Debug, Clone)]
@@ -490,4 +516,11 @@ impl HSpace for HSpace3 {
     ) -> <Self::Lower as HSpace>::ProjectedVector {
         EVec2 { x: vec.x, y: vec.y }
     }
+
+    fn transform_vec(
+        transform: &Transform3,
+        hvec: Self::Vector,
+    ) -> Result<Self::Vector, PointAtInfinity> {
+        transform.apply_hvec3(hvec)
+    }
 }
@@ -1,15 +1,24 @@
 mod eline;
+mod eplacement;
 mod eplane;
 mod evector;
 mod hvector;
+mod mass;
+mod tolerance;
+mod transform;
 
 pub use eline::*;
+pub use eplacement::*;
 pub use eplane::*;
 pub use evector::*;
 pub use hvector::*;
+pub use mass::*;
+pub use tolerance::*;
+pub use transform::*;
 
 pub mod hspace;
 
+/// The default linear tolerance, for code that isn't given a [`Tolerance`]
 pub const TOL: f64 = 0.0000001;
 
 macro_rules! count_args {
@@ -0,0 +1,229 @@
+use crate::{EVec3, EVector};
+
+/// Integrals of polynomials over the volume enclosed by a closed, outward-facing
+/// surface, accumulated piece by piece over the surface using the divergence
+/// theorem
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct VolumeIntegrals {
+    /// Area of the surface
+    pub area: f64,
+
+    /// Integral of 1
+    pub volume: f64,
+
+    /// Integrals of x, y and z
+    pub first: EVec3,
+
+    /// Integrals of x², y² and z²
+    pub squares: EVec3,
+
+    /// Integrals of xy, yz and zx
+    pub products: EVec3,
+}
+impl VolumeIntegrals {
+    pub fn new() -> Self {
+        Self {
+            area: 0.0,
+            volume: 0.0,
+            first: EVec3::zero(),
+            squares: EVec3::zero(),
+            products: EVec3::zero(),
+        }
+    }
+
+    /// Adds the exact contribution of a triangle whose vertices run
+    /// counterclockwise when seen from outside the volume
+    pub fn add_triangle(&mut self, a: EVec3, b: EVec3, c: EVec3) {
+        // Eberly, "Polyhedral Mass Properties (Revisited)"
+        let d = (b - a).cross(&(c - a));
+        let (x, y, z) = (
+            Subexpressions::new(a.x, b.x, c.x),
+            Subexpressions::new(a.y, b.y, c.y),
+            Subexpressions::new(a.z, b.z, c.z),
+        );
+
+        self.area += d.magnitude() / 2.0;
+        self.volume += d.x * x.f1 / 6.0;
+        self.first += EVec3::new(d.x * x.f2, d.y * y.f2, d.z * z.f2) / 24.0;
+        self.squares += EVec3::new(d.x * x.f3, d.y * y.f3, d.z * z.f3) / 60.0;
+        self.products += EVec3::new(
+            d.x * (a.y * x.g[0] + b.y * x.g[1] + c.y * x.g[2]),
+            d.y * (a.z * y.g[0] + b.z * y.g[1] + c.z * y.g[2]),
+            d.z * (a.x * z.g[0] + b.x * z.g[1] + c.x * z.g[2]),
+        ) / 120.0;
+    }
+
+    /// Adds the contribution of a small piece of surface at `point`, whose area
+    /// is the magnitude of `area` and whose outward normal is its direction. This
+    /// is the integrand of a quadrature rule over a surface.
+    pub fn add_element(&mut self, point: EVec3, area: EVec3) {
+        let EVec3 { x, y, z } = point;
+
+        self.area += area.magnitude();
+        self.volume += point.dot(&area) / 3.0;
+        self.first += EVec3::new(x * x * area.x, y * y * area.y, z * z * area.z) / 2.0;
+        self.squares +=
+            EVec3::new(x * x * x * area.x, y * y * y * area.y, z * z * z * area.z) / 3.0;
+        self.products +=
+            EVec3::new(x * x * y * area.x, y * y * z * area.y, z * z * x * area.z) / 2.0;
+    }
+}
+impl Default for VolumeIntegrals {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Sums of powers of one coordinate over a triangle's vertices
+struct Subexpressions {
+    f1: f64,
+    f2: f64,
+    f3: f64,
+    g: [f64; 3],
+}
+impl Subexpressions {
+    fn new(w0: f64, w1: f64, w2: f64) -> Self {
+        let temp0 = w0 + w1;
+        let f1 = temp0 + w2;
+        let temp1 = w0 * w0;
+        let temp2 = temp1 + w1 * temp0;
+        let f2 = temp2 + w2 * f1;
+        let f3 = w0 * temp1 + w1 * temp2 + w2 * f2;
+
+        Self {
+            f1,
+            f2,
+            f3,
+            g: [w0, w1, w2].map(|w| f2 + w * (f1 + w)),
+        }
+    }
+}
+
+/// The mass properties of a solid body of uniform density
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct MassProperties {
+    /// Area of the body's surface
+    pub area: f64,
+
+    pub volume: f64,
+
+    pub mass: f64,
+
+    /// Center of mass
+    pub centroid: EVec3,
+
+    /// Inertia tensor about the center of mass, along the global axes
+    pub inertia: [[f64; 3]; 3],
+}
+impl MassProperties {
+    pub fn new(integrals: &VolumeIntegrals, density: f64) -> Self {
+        let VolumeIntegrals {
+            area,
+            volume,
+            first,
+            squares,
+            products,
+        } = *integrals;
+
+        let mass = volume * density;
+        let c = if volume.abs() > 0.0 {
+            first / volume
+        } else {
+            EVec3::zero()
+        };
+
+        // Moments about the origin, moved to the center of mass with the parallel
+        // axis theorem
+        let (xx, yy, zz) = (
+            (squares.y + squares.z) * density - mass * (c.y * c.y + c.z * c.z),
+            (squares.z + squares.x) * density - mass * (c.z * c.z + c.x * c.x),
+            (squares.x + squares.y) * density - mass * (c.x * c.x + c.y * c.y),
+        );
+        let (xy, yz, zx) = (
+            -(products.x * density - mass * c.x * c.y),
+            -(products.y * density - mass * c.y * c.z),
+            -(products.z * density - mass * c.z * c.x),
+        );
+
+        Self {
+            area,
+            volume,
+            mass,
+            centroid: c,
+            inertia: [[xx, xy, zx], [xy, yy, yz], [zx, yz, zz]],
+        }
+    }
+
+    /// Finds the mass properties of the body enclosed by a closed triangle mesh,
+    /// with triangles running counterclockwise when seen from outside
+    pub fn from_triangles(triangles: impl IntoIterator<Item = [EVec3; 3]>, density: f64) -> Self {
+        let mut integrals = VolumeIntegrals::new();
+        for [a, b, c] in triangles {
+            integrals.add_triangle(a, b, c);
+        }
+        Self::new(&integrals, density)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{EVec3, EVector, MassProperties, VolumeIntegrals};
+
+    fn block_triangles(min: EVec3, max: EVec3) -> Vec<[EVec3; 3]> {
+        let corner = |i: usize| {
+            EVec3::new(
+                if i & 1 == 0 { min.x } else { max.x },
+                if i & 2 == 0 { min.y } else { max.y },
+                if i & 4 == 0 { min.z } else { max.z },
+            )
+        };
+        [
+            [0, 2, 3, 1],
+            [4, 5, 7, 6],
+            [0, 1, 5, 4],
+            [2, 6, 7, 3],
+            [0, 4, 6, 2],
+            [1, 3, 7, 5],
+        ]
+        .iter()
+        .flat_map(|[a, b, c, d]| {
+            [
+                [corner(*a), corner(*b), corner(*c)],
+                [corner(*a), corner(*c), corner(*d)],
+            ]
+        })
+        .collect()
+    }
+
+    #[test]
+    fn block_mass_properties() {
+        let (min, max) = (EVec3::new(1.0, 2.0, 3.0), EVec3::new(3.0, 3.0, 6.0));
+        let props = MassProperties::from_triangles(block_triangles(min, max), 2.0);
+
+        assert!((props.area - 22.0).abs() <= 1e-12);
+        assert!((props.volume - 6.0).abs() <= 1e-12);
+        assert!((props.mass - 12.0).abs() <= 1e-12);
+        assert!((props.centroid - EVec3::new(2.0, 2.5, 4.5)).magnitude() <= 1e-12);
+
+        // A block's inertia about its center is m(b² + c²)/12 about each axis, with
+        // no products of inertia
+        let (a, b, c) = (2.0, 1.0, 3.0);
+        let expected = [
+            [12.0 * (b * b + c * c) / 12.0, 0.0, 0.0],
+            [0.0, 12.0 * (c * c + a * a) / 12.0, 0.0],
+            [0.0, 0.0, 12.0 * (a * a + b * b) / 12.0],
+        ];
+        for (row, expected_row) in props.inertia.iter().zip(expected.iter()) {
+            for (value, expected) in row.iter().zip(expected_row.iter()) {
+                assert!((value - expected).abs() <= 1e-9);
+            }
+        }
+
+        // Integrating each triangle at its centroid is exact for the volume
+        let mut integrals = VolumeIntegrals::new();
+        for [a, b, c] in block_triangles(min, max) {
+            integrals.add_element((a + b + c) / 3.0, (b - a).cross(&(c - a)) / 2.0);
+        }
+        assert!((integrals.volume - 6.0).abs() <= 1e-12);
+    }
+}
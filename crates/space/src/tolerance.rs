@@ -0,0 +1,67 @@
+use crate::TOL;
+
+/// How close lengths and directions have to be to be treated as the same.
+/// Lengths are in millimeters and angles in radians.
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Tolerance {
+    /// The distance within which points coincide
+    pub linear: f64,
+
+    /// The angle within which directions are parallel
+    pub angular: f64,
+}
+impl Tolerance {
+    pub const DEFAULT: Self = Self {
+        linear: TOL,
+        angular: 1e-5,
+    };
+
+    pub fn new(linear: f64, angular: f64) -> Self {
+        Self { linear, angular }
+    }
+
+    /// The smallest cosine of the angle between two directions that are treated
+    /// as parallel
+    pub fn parallel_cos(&self) -> f64 {
+        self.angular.cos()
+    }
+
+    /// The tolerance that accepts whatever either of two tolerances accepts
+    pub fn looser(&self, other: &Self) -> Self {
+        Self {
+            linear: self.linear.max(other.linear),
+            angular: self.angular.max(other.angular),
+        }
+    }
+}
+impl Default for Tolerance {
+    fn default() -> Self {
+        Self::DEFAULT
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Tolerance;
+    use crate::TOL;
+
+    #[test]
+    fn parallel_cos() {
+        assert_eq!(Tolerance::default(), Tolerance::new(TOL, 1e-5));
+
+        let tolerance = Tolerance::new(1e-3, 0.1);
+        assert_eq!(tolerance.parallel_cos(), 0.1f64.cos());
+        // Directions a little inside the angle count as parallel and ones outside don't
+        assert!(0.09f64.cos() >= tolerance.parallel_cos());
+        assert!(0.11f64.cos() < tolerance.parallel_cos());
+    }
+
+    #[test]
+    fn looser() {
+        let tight = Tolerance::new(1e-6, 0.1);
+        let loose = Tolerance::new(1e-3, 1e-5);
+        assert_eq!(tight.looser(&loose), Tolerance::new(1e-3, 0.1));
+        assert_eq!(loose.looser(&tight), tight.looser(&loose));
+    }
+}
@@ -0,0 +1,281 @@
+use std::fmt;
+
+use crate::{EPlane3, EVec3, EVec4, EVector, HVec1, HVec2, HVec3, TOL};
+
+/// A 4x4 homogeneous transformation matrix acting on 3D points. Affine transforms
+/// have a bottom row of `[0, 0, 0, 1]`. Any other bottom row makes the transform
+/// projective, which rational curves and surfaces still represent exactly since the
+/// matrix is applied to their weighted control points.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Transform3 {
+    pub m: [[f64; 4]; 4],
+}
+impl Transform3 {
+    pub fn identity() -> Self {
+        Self::from_matrix([
+            [1.0, 0.0, 0.0, 0.0],
+            [0.0, 1.0, 0.0, 0.0],
+            [0.0, 0.0, 1.0, 0.0],
+            [0.0, 0.0, 0.0, 1.0],
+        ])
+    }
+
+    /// Creates a transform from a row-major matrix
+    pub fn from_matrix(m: [[f64; 4]; 4]) -> Self {
+        Self { m }
+    }
+
+    /// Creates an affine transform from a row-major 3x3 linear part followed by a
+    /// translation
+    pub fn affine(linear: [[f64; 3]; 3], translation: EVec3) -> Self {
+        let t = [translation.x, translation.y, translation.z];
+        let mut m = [[0.0; 4]; 4];
+        for (row, m_row) in m.iter_mut().take(3).enumerate() {
+            m_row[..3].copy_from_slice(&linear[row]);
+            m_row[3] = t[row];
+        }
+        m[3][3] = 1.0;
+
+        Self::from_matrix(m)
+    }
+
+    pub fn translation(offset: EVec3) -> Self {
+        Self::affine([[1.0, 0.0, 0.0], [0.0, 1.0, 0.0], [0.0, 0.0, 1.0]], offset)
+    }
+
+    /// Scales independently along each axis, about the origin
+    pub fn scale(factors: EVec3) -> Self {
+        Self::affine(
+            [
+                [factors.x, 0.0, 0.0],
+                [0.0, factors.y, 0.0],
+                [0.0, 0.0, factors.z],
+            ],
+            EVec3::zero(),
+        )
+    }
+
+    pub fn uniform_scale(factor: f64) -> Self {
+        Self::scale(EVec3::new(factor, factor, factor))
+    }
+
+    /// Rotates counterclockwise by `angle` radians about an axis through the origin
+    pub fn rotation(axis: EVec3, angle: f64) -> Self {
+        let EVec3 { x, y, z } = axis.normalize();
+        let (sin, cos) = angle.sin_cos();
+        let t = 1.0 - cos;
+
+        Self::affine(
+            [
+                [t * x * x + cos, t * x * y - sin * z, t * x * z + sin * y],
+                [t * x * y + sin * z, t * y * y + cos, t * y * z - sin * x],
+                [t * x * z - sin * y, t * y * z + sin * x, t * z * z + cos],
+            ],
+            EVec3::zero(),
+        )
+    }
+
+    /// Rotates counterclockwise by `angle` radians about an axis through `origin`
+    pub fn rotation_about(origin: EVec3, axis: EVec3, angle: f64) -> Self {
+        Self::translation(-origin)
+            .then(&Self::rotation(axis, angle))
+            .then(&Self::translation(origin))
+    }
+
+    /// Reflects across a plane
+    pub fn mirror(plane: &EPlane3) -> Self {
+        let plane = plane.normalize();
+        let EVec3 { x, y, z } = plane.norm;
+
+        Self::affine(
+            [
+                [1.0 - 2.0 * x * x, -2.0 * x * y, -2.0 * x * z],
+                [-2.0 * x * y, 1.0 - 2.0 * y * y, -2.0 * y * z],
+                [-2.0 * x * z, -2.0 * y * z, 1.0 - 2.0 * z * z],
+            ],
+            plane.norm * (-2.0 * plane.d),
+        )
+    }
+
+    /// Returns the transform that applies `self` followed by `next`
+    pub fn then(&self, next: &Self) -> Self {
+        let mut m = [[0.0; 4]; 4];
+        for (row, m_row) in m.iter_mut().enumerate() {
+            for (col, value) in m_row.iter_mut().enumerate() {
+                *value = (0..4).map(|k| next.m[row][k] * self.m[k][col]).sum();
+            }
+        }
+
+        Self::from_matrix(m)
+    }
+
+    /// Computes the inverse transform, or `None` if the matrix is singular
+    pub fn inverse(&self) -> Option<Self> {
+        // Gauss-Jordan elimination with partial pivoting
+        let mut a = self.m;
+        let mut inv = Self::identity().m;
+
+        for col in 0..4 {
+            let pivot = (col..4)
+                .max_by(|&r1, &r2| a[r1][col].abs().total_cmp(&a[r2][col].abs()))
+                .unwrap();
+
+            if a[pivot][col].abs() <= TOL {
+                return None;
+            }
+
+            a.swap(col, pivot);
+            inv.swap(col, pivot);
+
+            let scale = a[col][col];
+            for k in 0..4 {
+                a[col][k] /= scale;
+                inv[col][k] /= scale;
+            }
+
+            for row in 0..4 {
+                if row != col {
+                    let factor = a[row][col];
+                    for k in 0..4 {
+                        a[row][k] -= factor * a[col][k];
+                        inv[row][k] -= factor * inv[col][k];
+                    }
+                }
+            }
+        }
+
+        Some(Self::from_matrix(inv))
+    }
+
+    pub fn is_affine(&self) -> bool {
+        self.m[3] == [0.0, 0.0, 0.0, 1.0]
+    }
+
+    /// Multiplies a point in weighted homogeneous form by the matrix
+    pub fn apply_weighted(&self, weighted: EVec4) -> EVec4 {
+        let v = [weighted.x, weighted.y, weighted.z, weighted.w];
+        let row = |r: usize| (0..4).map(|k| self.m[r][k] * v[k]).sum::<f64>();
+
+        EVec4::new(row(0), row(1), row(2), row(3))
+    }
+
+    /// Transforms a Euclidean point. Projective transforms may send the point to
+    /// infinity, where it has no Euclidean position; use
+    /// [`Transform3::apply_weighted`] or [`Transform3::apply_hvec3`] when that
+    /// can happen.
+    pub fn apply_point(&self, point: EVec3) -> EVec3 {
+        let p = self.apply_weighted(EVec4::new(point.x, point.y, point.z, 1.0));
+        EVec3::new(p.x / p.w, p.y / p.w, p.z / p.w)
+    }
+
+    /// Transforms a direction, ignoring translation. Only meaningful for affine
+    /// transforms.
+    pub fn apply_direction(&self, dir: EVec3) -> EVec3 {
+        let p = self.apply_weighted(EVec4::new(dir.x, dir.y, dir.z, 0.0));
+        EVec3::new(p.x, p.y, p.z)
+    }
+
+    /// Transforms a control point. The point is weighted, multiplied by the matrix
+    /// and unweighted again, so the result carries the new homogeneous weight.
+    /// Fails if the new weight is not positive, which projective transforms give
+    /// points on or behind the plane they send to infinity.
+    pub fn apply_hvec3(&self, hvec: HVec3) -> Result<HVec3, PointAtInfinity> {
+        let p = self.apply_weighted(EVec4::new(
+            hvec.x * hvec.h,
+            hvec.y * hvec.h,
+            hvec.z * hvec.h,
+            hvec.h,
+        ));
+        if p.w <= 0.0 {
+            return Err(PointAtInfinity);
+        }
+        Ok(HVec3::new(p.x / p.w, p.y / p.w, p.z / p.w, p.w))
+    }
+
+    /// Transforms a planar control point, treating it as lying in the XY plane. Any
+    /// Z component of the result is discarded.
+    pub fn apply_hvec2(&self, hvec: HVec2) -> Result<HVec2, PointAtInfinity> {
+        let p = self.apply_hvec3(HVec3::new(hvec.x, hvec.y, 0.0, hvec.h))?;
+        Ok(HVec2::new(p.x, p.y, p.h))
+    }
+
+    /// Transforms a 1D control point, treating it as lying on the X axis. Any Y or Z
+    /// components of the result are discarded.
+    pub fn apply_hvec1(&self, hvec: HVec1) -> Result<HVec1, PointAtInfinity> {
+        let p = self.apply_hvec3(HVec3::new(hvec.x, 0.0, 0.0, hvec.h))?;
+        Ok(HVec1::new(p.x, p.h))
+    }
+}
+impl Default for Transform3 {
+    fn default() -> Self {
+        Self::identity()
+    }
+}
+
+/// A projective transform sent a control point to or beyond infinity, leaving it
+/// with a weight that is not positive
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PointAtInfinity;
+impl fmt::Display for PointAtInfinity {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "the transform sends a point to infinity")
+    }
+}
+impl std::error::Error for PointAtInfinity {}
+
+#[cfg(test)]
+mod tests {
+    use std::f64::consts::FRAC_PI_2;
+
+    use crate::{EPlane3, EVec3, EVector, HVec3, PointAtInfinity, Transform3, TOL};
+
+    #[test]
+    fn rotation_about_axis() {
+        let rotation = Transform3::rotation_about(
+            EVec3::new(1.0, 1.0, 0.0),
+            EVec3::new(0.0, 0.0, 1.0),
+            FRAC_PI_2,
+        );
+
+        let rotated = rotation.apply_point(EVec3::new(2.0, 1.0, 3.0));
+        assert!((rotated - EVec3::new(1.0, 2.0, 3.0)).magnitude() <= TOL);
+    }
+
+    #[test]
+    fn mirror_and_inverse() {
+        let mirror = Transform3::mirror(&EPlane3::new_general_form(0.0, 2.0, 0.0, -2.0));
+        let point = EVec3::new(3.0, 4.0, -1.0);
+        let mirrored = mirror.apply_point(point);
+        assert!((mirrored - EVec3::new(3.0, -2.0, -1.0)).magnitude() <= TOL);
+
+        let transform = Transform3::rotation(EVec3::new(1.0, 2.0, 3.0), 0.7)
+            .then(&Transform3::scale(EVec3::new(2.0, 0.5, 3.0)))
+            .then(&Transform3::translation(EVec3::new(-1.0, 4.0, 2.0)));
+        let inverse = transform.inverse().unwrap();
+        assert!((inverse.apply_point(transform.apply_point(point)) - point).magnitude() <= TOL);
+        assert!(Transform3::uniform_scale(0.0).inverse().is_none());
+    }
+
+    #[test]
+    fn projective_transform_changes_weight() {
+        let mut perspective = Transform3::identity();
+        perspective.m[3] = [0.0, 0.0, 0.5, 1.0];
+
+        let transformed = perspective
+            .apply_hvec3(HVec3::new(1.0, 2.0, 2.0, 0.5))
+            .unwrap();
+        assert!((transformed.h - 1.0).abs() <= TOL);
+        assert!((transformed.x - 0.5).abs() <= TOL);
+        assert!(!perspective.is_affine());
+
+        // Points at z = -2 go to infinity, and those past it would flip sign
+        assert!(matches!(
+            perspective.apply_hvec3(HVec3::new(1.0, 2.0, -2.0, 0.5)),
+            Err(PointAtInfinity)
+        ));
+        assert!(matches!(
+            perspective.apply_hvec3(HVec3::new(1.0, 2.0, -3.0, 1.0)),
+            Err(PointAtInfinity)
+        ));
+    }
+}
@@ -1,7 +1,7 @@
 use once_cell::unsync::OnceCell;
 use space::{
     hspace::{HSpace, HSpace2, HSpace3},
-    EVector, HVec2, HVec3, HVector, TOL,
+    EVector, HVec2, HVec3, HVector, PointAtInfinity, Tolerance, Transform3,
 };
 
 use crate::math::{
@@ -42,6 +42,19 @@ impl<H: HSpace> BezierCurve<H> {
         self.control_points.len() - 1
     }
 
+    /// Applies a transform to the control points in homogeneous form, so the
+    /// shape of rational curves is preserved exactly. Leaves the curve unchanged
+    /// and fails if a projective transform sends a control point to infinity.
+    pub fn transform(&mut self, transform: &Transform3) -> Result<(), PointAtInfinity> {
+        self.control_points = self
+            .control_points
+            .iter()
+            .map(|point| H::transform_vec(transform, *point))
+            .collect::<Result<_, _>>()?;
+        self.weighted_control_points = OnceCell::new();
+        Ok(())
+    }
+
     pub fn line_intersection_plot(
         &self,
         line: &H::EuclideanLine,
@@ -74,7 +87,13 @@ impl<H: HSpace> BezierCurve<H> {
         (self_points, der_points)
     }
 
-    pub fn line_intersections(&self, line: &H::EuclideanLine) -> Vec<H::ProjectedVector> {
+    /// Finds where the curve crosses a line. Points within the linear tolerance
+    /// of each other are reported once.
+    pub fn line_intersections(
+        &self,
+        line: &H::EuclideanLine,
+        tolerance: &Tolerance,
+    ) -> Vec<H::ProjectedVector> {
         // Get coefficients for an implicit Bezier curve oriented so the line is
         // along the X-axis. Do the same for this curve's derivative curve, which
         // we'll need for Newton iteration.
@@ -103,7 +122,7 @@ impl<H: HSpace> BezierCurve<H> {
             accum_weight += self.control_points[i].homogeneous_component();
             let u_initial = accum_weight / total_weight;
 
-            let zero = newton_vec(u_initial, 50, 0.0, 1.0, |u| {
+            let zero = newton_vec(u_initial, 50, 0.0, 1.0, tolerance.linear, |u| {
                 (
                     decasteljau(&self_coefficients, u),
                     decasteljau(&der_coefficients, u),
@@ -123,7 +142,7 @@ impl<H: HSpace> BezierCurve<H> {
 
         // Newton iteration may have converged on the same points, so remove any duplicates.
         // TODO: Sort these somehow, deduplication does nothing unless duplicates are next to each other
-        points.dedup_by(|a, b| (*a - *b).magnitude() <= TOL);
+        points.dedup_by(|a, b| (*a - *b).magnitude() <= tolerance.linear);
 
         points
     }
@@ -164,6 +183,7 @@ impl<H: HSpace> BezierCurve<H> {
         min_u: Option<f64>,
         max_u: Option<f64>,
         include_endpoints: bool,
+        tolerance: &Tolerance,
     ) -> Vec<(f64, H::ProjectedVector)> {
         let (min_u, max_u) = {
             let min_u = min_u.unwrap_or(0.0);
@@ -177,7 +197,7 @@ impl<H: HSpace> BezierCurve<H> {
         };
 
         let try_point = |u_initial: f64| {
-            newton_f64(u_initial, 10000, min_u, max_u, |u| {
+            newton_f64(u_initial, 10000, min_u, max_u, tolerance.linear, |u| {
                 let ders: Vec<H::ProjectedVector> =
                     rational_curve_derivatives::<H>(&self.weighted_control_points(), u, 2);
 
@@ -264,7 +284,7 @@ impl<H: HSpace> BezierCurve<H> {
 
         // Remove any duplicates if the Newton iteration converged on the same point(s)
         points.sort_by(|a, b| a.0.partial_cmp(&b.0).unwrap_or(std::cmp::Ordering::Equal));
-        points.dedup_by(|a, b| (a.1 - b.1).magnitude() <= TOL);
+        points.dedup_by(|a, b| (a.1 - b.1).magnitude() <= tolerance.linear);
 
         points
     }
@@ -275,11 +295,13 @@ impl<H: HSpace> BezierCurve<H> {
         min_u: Option<f64>,
         max_u: Option<f64>,
         include_endpoints: bool,
+        tolerance: &Tolerance,
     ) -> Option<HausdorffResult<H>> {
         let mut max = 0.0;
         let mut max_u_and_point: Option<(f64, H::ProjectedVector)> = None;
 
-        let candidates = self.hausdorff_candidates(line, min_u, max_u, include_endpoints);
+        let candidates =
+            self.hausdorff_candidates(line, min_u, max_u, include_endpoints, tolerance);
 
         for (u, point) in candidates {
             let dist = H::line_dist_to_projected_point(line, &point);
@@ -322,3 +344,40 @@ pub struct HausdorffResult<H: HSpace> {
     pub u: f64,
     pub point: H::ProjectedVector,
 }
+
+#[cfg(test)]
+mod tests {
+    use space::{hspace::HSpace3, EVec3, EVector, HVec3, PointAtInfinity, Transform3, TOL};
+
+    use crate::{bezier_curve::BezierCurve, math::FloatRange};
+
+    #[test]
+    fn projective_transform_is_exact() {
+        let mut transform = Transform3::rotation(EVec3::new(0.0, 1.0, 1.0), 1.1)
+            .then(&Transform3::translation(EVec3::new(2.0, -1.0, 0.5)));
+        transform.m[3] = [0.2, 0.1, -0.1, 1.0];
+
+        let arc = BezierCurve::<HSpace3>::example_quarter_circle_xy();
+        let mut transformed = BezierCurve::<HSpace3>::example_quarter_circle_xy();
+        transformed.transform(&transform).unwrap();
+
+        for u in FloatRange::new(0.0, 1.0, 40) {
+            let expected = transform.apply_point(arc.point(u));
+            assert!((transformed.point(u) - expected).magnitude() <= TOL);
+        }
+    }
+
+    #[test]
+    fn transform_to_infinity_is_rejected() {
+        let mut transform = Transform3::identity();
+        transform.m[3] = [-1.0, 0.0, 0.0, 1.0];
+
+        // The second control point lands exactly on the plane sent to infinity
+        let mut line = BezierCurve::<HSpace3>::new(vec![
+            HVec3::new(0.0, 0.0, 0.0, 1.0),
+            HVec3::new(1.0, 0.0, 0.0, 1.0),
+        ]);
+        assert_eq!(line.transform(&transform), Err(PointAtInfinity));
+        assert!((line.point(1.0) - EVec3::new(1.0, 0.0, 0.0)).magnitude() <= TOL);
+    }
+}
@@ -2,7 +2,7 @@ use iter_tools::Itertools;
 use once_cell::unsync::OnceCell;
 use space::{
     hspace::{HSpace, HSpace3},
-    EVec2, EVector, HVec3, HVector,
+    EVec2, EVector, HVec3, HVector, PointAtInfinity, Tolerance, Transform3,
 };
 
 use crate::math::{
@@ -22,6 +22,10 @@ impl<H: HSpace> BezierSurface<H> {
         }
     }
 
+    pub fn control_points(&self) -> &[Vec<H::Vector>] {
+        &self.control_points
+    }
+
     pub fn weighted_control_points(&self) -> &[Vec<H::WeightedVector>] {
         self.weighted_control_points.get_or_init(|| {
             self.control_points
@@ -67,11 +71,30 @@ impl<H: HSpace> BezierSurface<H> {
         self.control_points = new_pts;
     }
 
+    /// Applies a transform to the control points in homogeneous form, so the
+    /// shape of rational surfaces is preserved exactly. Leaves the surface
+    /// unchanged and fails if a projective transform sends a control point to
+    /// infinity.
+    pub fn transform(&mut self, transform: &Transform3) -> Result<(), PointAtInfinity> {
+        self.control_points = self
+            .control_points
+            .iter()
+            .map(|row| {
+                row.iter()
+                    .map(|point| H::transform_vec(transform, *point))
+                    .collect()
+            })
+            .collect::<Result<_, _>>()?;
+        self.weighted_control_points = OnceCell::new();
+        Ok(())
+    }
+
     pub fn hausdorff_candidates(
         &self,
         plane: &H::EuclideanPlane,
         min_uv: Option<EVec2>,
         max_uv: Option<EVec2>,
+        tolerance: &Tolerance,
     ) -> Vec<(EVec2, H::ProjectedVector)> {
         let (min_uv, max_uv) = {
             let min_uv = min_uv.unwrap_or(EVec2::new(0.0, 0.0));
@@ -87,7 +110,7 @@ impl<H: HSpace> BezierSurface<H> {
         };
 
         let try_point = |uv: EVec2| {
-            newton(uv, 10000, min_uv, max_uv, |uv| {
+            newton(uv, 10000, min_uv, max_uv, tolerance.linear, |uv| {
                 let ders: Vec<Vec<H::ProjectedVector>> = rational_surface_derivatives::<H>(
                     &self.weighted_control_points(),
                     2,
@@ -177,3 +200,28 @@ impl BezierSurface<HSpace3> {
         ]))
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use space::{hspace::HSpace3, EVec3, EVector, Transform3, TOL};
+
+    use crate::{bezier_surface::BezierSurface, math::FloatRange};
+
+    #[test]
+    fn affine_transform_moves_surface() {
+        let transform = Transform3::scale(EVec3::new(2.0, 1.0, 0.5))
+            .then(&Transform3::rotation(EVec3::new(1.0, -1.0, 2.0), 0.8))
+            .then(&Transform3::translation(EVec3::new(0.0, 3.0, -1.0)));
+
+        let sphere = BezierSurface::<HSpace3>::example_eighth_sphere();
+        let mut transformed = BezierSurface::<HSpace3>::example_eighth_sphere();
+        transformed.transform(&transform).unwrap();
+
+        for u in FloatRange::new(0.0, 1.0, 10) {
+            for v in FloatRange::new(0.0, 1.0, 10) {
+                let expected = transform.apply_point(sphere.point(u, v));
+                assert!((transformed.point(u, v) - expected).magnitude() <= TOL);
+            }
+        }
+    }
+}
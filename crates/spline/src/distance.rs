@@ -0,0 +1,692 @@
+use std::{cmp::Ordering, collections::BinaryHeap};
+
+use space::{
+    hspace::{HSpace, HSpace3},
+    EVec2, EVec3, EVec4, EVector, HVec3,
+};
+
+use crate::{
+    math::{bezier::decasteljau, nurbs::curve_decompose},
+    nurbs_curve::NurbsCurve,
+    nurbs_surface::NurbsSurface,
+};
+
+/// Maximum number of pairs of pieces examined while searching for the closest
+/// points, after which the best pair found so far is polished
+const MAX_PAIRS: usize = 20000;
+
+/// Maximum number of Newton steps taken when projecting a point
+const NEWTON_STEPS: usize = 64;
+
+/// Maximum number of alternating projections used to polish a pair of closest
+/// points
+const POLISH_STEPS: usize = 64;
+
+/// A point where a minimum distance is reached, and its parameters on the shape
+/// it lies on: `()` for a point, `u` for a curve and `(u, v)` for a surface
+#[derive(Debug, Clone)]
+pub struct Witness<T> {
+    pub param: T,
+    pub point: EVec3,
+}
+
+/// The minimum distance between two shapes and the points where it is reached
+#[derive(Debug, Clone)]
+pub struct MinDistance<A, B> {
+    pub distance: f64,
+    pub first: Witness<A>,
+    pub second: Witness<B>,
+}
+impl<A, B> MinDistance<A, B> {
+    fn new(first: Witness<A>, second: Witness<B>) -> Self {
+        Self {
+            distance: (second.point - first.point).magnitude(),
+            first,
+            second,
+        }
+    }
+}
+
+impl NurbsCurve<HSpace3> {
+    /// Finds the point on the curve closest to `point`. The search narrows down
+    /// pieces of the curve by their bounding boxes until it is within `tolerance`
+    /// of the minimum, and then polishes the result with Newton's method.
+    pub fn distance_to_point(&self, point: EVec3, tolerance: f64) -> MinDistance<f64, ()> {
+        let (piece, _) = closest_pieces(curve_pieces(self), vec![Piece::Point(point)], tolerance);
+        let u = project_to_curve(self, point, piece.x);
+        MinDistance::new(
+            Witness {
+                param: u,
+                point: self.point(u),
+            },
+            Witness { param: (), point },
+        )
+    }
+
+    /// Finds the closest points between this curve and another, to within
+    /// `tolerance` of the minimum distance before polishing
+    pub fn distance_to_curve(&self, other: &Self, tolerance: f64) -> MinDistance<f64, f64> {
+        let (a, b) = closest_pieces(curve_pieces(self), curve_pieces(other), tolerance);
+        let (u, s) = polish(
+            a.x,
+            b.x,
+            |u| self.point(u),
+            |s| other.point(s),
+            |u, p| project_to_curve(self, p, u),
+            |s, p| project_to_curve(other, p, s),
+        );
+        MinDistance::new(
+            Witness {
+                param: u,
+                point: self.point(u),
+            },
+            Witness {
+                param: s,
+                point: other.point(s),
+            },
+        )
+    }
+
+    /// Finds the closest points between this curve and a surface, to within
+    /// `tolerance` of the minimum distance before polishing
+    pub fn distance_to_surface(
+        &self,
+        surface: &NurbsSurface<HSpace3>,
+        tolerance: f64,
+    ) -> MinDistance<f64, EVec2> {
+        let (a, b) = closest_pieces(curve_pieces(self), surface_pieces(surface), tolerance);
+        let (u, uv) = polish(
+            a.x,
+            b,
+            |u| self.point(u),
+            |uv: EVec2| surface.point(uv.x, uv.y),
+            |u, p| project_to_curve(self, p, u),
+            |uv, p| project_to_surface(surface, p, uv),
+        );
+        MinDistance::new(
+            Witness {
+                param: u,
+                point: self.point(u),
+            },
+            Witness {
+                param: uv,
+                point: surface.point(uv.x, uv.y),
+            },
+        )
+    }
+}
+
+impl NurbsSurface<HSpace3> {
+    /// Finds the point on the surface closest to `point`. See
+    /// [`NurbsCurve::distance_to_point`].
+    pub fn distance_to_point(&self, point: EVec3, tolerance: f64) -> MinDistance<EVec2, ()> {
+        let (uv, _) = closest_pieces(surface_pieces(self), vec![Piece::Point(point)], tolerance);
+        let uv = project_to_surface(self, point, uv);
+        MinDistance::new(
+            Witness {
+                param: uv,
+                point: self.point(uv.x, uv.y),
+            },
+            Witness { param: (), point },
+        )
+    }
+
+    /// Finds the closest points between this surface and another, to within
+    /// `tolerance` of the minimum distance before polishing
+    pub fn distance_to_surface(&self, other: &Self, tolerance: f64) -> MinDistance<EVec2, EVec2> {
+        let (a, b) = closest_pieces(surface_pieces(self), surface_pieces(other), tolerance);
+        let (uv, st) = polish(
+            a,
+            b,
+            |uv: EVec2| self.point(uv.x, uv.y),
+            |st: EVec2| other.point(st.x, st.y),
+            |uv, p| project_to_surface(self, p, uv),
+            |st, p| project_to_surface(other, p, st),
+        );
+        MinDistance::new(
+            Witness {
+                param: uv,
+                point: self.point(uv.x, uv.y),
+            },
+            Witness {
+                param: st,
+                point: other.point(st.x, st.y),
+            },
+        )
+    }
+}
+
+/// Refines the parameter of the point on a curve closest to `point` with Newton's
+/// method, starting from `u`
+pub(crate) fn project_to_curve(curve: &NurbsCurve<HSpace3>, point: EVec3, u: f64) -> f64 {
+    let (min, max) = (curve.min_u(), curve.max_u());
+    let mut u = u;
+    let mut best = (curve.point(u) - point).magnitude();
+
+    for _ in 0..NEWTON_STEPS {
+        let ders = curve.derivatives(u, 2);
+        let between = ders[0] - point;
+        let denom = ders[2].dot(&between) + ders[1].dot(&ders[1]);
+        if denom <= 0.0 {
+            break;
+        }
+
+        let next = (u - ders[1].dot(&between) / denom).clamp(min, max);
+        let distance = (curve.point(next) - point).magnitude();
+        if distance > best {
+            break;
+        }
+
+        let step = (next - u).abs();
+        u = next;
+        best = distance;
+        if step <= f64::EPSILON * (max - min) {
+            break;
+        }
+    }
+
+    u
+}
+
+/// Refines the parameters of the point on a surface closest to `point` with
+/// Newton's method, starting from `uv`
+pub(crate) fn project_to_surface(
+    surface: &NurbsSurface<HSpace3>,
+    point: EVec3,
+    uv: EVec2,
+) -> EVec2 {
+    let (min, max) = (
+        EVec2::new(surface.min_u(), surface.min_v()),
+        EVec2::new(surface.max_u(), surface.max_v()),
+    );
+    let mut uv = uv;
+    let mut best = (surface.point(uv.x, uv.y) - point).magnitude();
+
+    for _ in 0..NEWTON_STEPS {
+        let ders = surface.derivatives(uv.x, uv.y, 2);
+        let between = ders[0][0] - point;
+        let (su, sv) = (ders[1][0], ders[0][1]);
+
+        let (f, g) = (su.dot(&between), sv.dot(&between));
+        let a = ders[2][0].dot(&between) + su.dot(&su);
+        let b = ders[1][1].dot(&between) + su.dot(&sv);
+        let d = ders[0][2].dot(&between) + sv.dot(&sv);
+        let det = a * d - b * b;
+        if det.abs() <= f64::EPSILON {
+            break;
+        }
+
+        let next = EVec2::new(
+            (uv.x - (d * f - b * g) / det).clamp(min.x, max.x),
+            (uv.y - (a * g - b * f) / det).clamp(min.y, max.y),
+        );
+        let distance = (surface.point(next.x, next.y) - point).magnitude();
+        if distance > best {
+            break;
+        }
+
+        let step = (next - uv).magnitude();
+        uv = next;
+        best = distance;
+        if step <= f64::EPSILON * (max - min).magnitude() {
+            break;
+        }
+    }
+
+    uv
+}
+
+/// Polishes a pair of nearly closest points by projecting each onto the other's
+/// shape in turn, until neither moves
+fn polish<A: Copy, B: Copy>(
+    a: A,
+    b: B,
+    point_a: impl Fn(A) -> EVec3,
+    point_b: impl Fn(B) -> EVec3,
+    project_a: impl Fn(A, EVec3) -> A,
+    project_b: impl Fn(B, EVec3) -> B,
+) -> (A, B) {
+    let (mut a, mut b) = (a, b);
+    let mut distance = (point_a(a) - point_b(b)).magnitude();
+
+    for _ in 0..POLISH_STEPS {
+        let next_b = project_b(b, point_a(a));
+        let next_a = project_a(a, point_b(next_b));
+        let next = (point_a(next_a) - point_b(next_b)).magnitude();
+        if next > distance {
+            break;
+        }
+
+        a = next_a;
+        b = next_b;
+        if distance - next <= f64::EPSILON * distance.max(1.0) {
+            break;
+        }
+        distance = next;
+    }
+
+    (a, b)
+}
+
+/// A piece of a shape, bounded by the convex hull of its control points. Curve
+/// and surface pieces are rational Bezier segments and patches with weighted
+/// control points, covering a range of the parameters of the shape they came
+/// from.
+#[derive(Debug, Clone)]
+enum Piece {
+    Point(EVec3),
+    Curve {
+        control_points: Vec<EVec4>,
+        range: (f64, f64),
+    },
+    Surface {
+        control_points: Vec<Vec<EVec4>>,
+        range_u: (f64, f64),
+        range_v: (f64, f64),
+    },
+}
+impl Piece {
+    /// The corners of the piece's axis-aligned bounding box
+    fn bounds(&self) -> (EVec3, EVec3) {
+        let points: Vec<EVec3> = match self {
+            Self::Point(point) => vec![*point],
+            Self::Curve { control_points, .. } => control_points.iter().map(project).collect(),
+            Self::Surface { control_points, .. } => {
+                control_points.iter().flatten().map(project).collect()
+            }
+        };
+
+        points
+            .iter()
+            .skip(1)
+            .fold((points[0], points[0]), |(min, max), p| {
+                (
+                    EVec3::new(min.x.min(p.x), min.y.min(p.y), min.z.min(p.z)),
+                    EVec3::new(max.x.max(p.x), max.y.max(p.y), max.z.max(p.z)),
+                )
+            })
+    }
+
+    /// The parameters and point in the middle of the piece
+    fn middle(&self) -> (EVec2, EVec3) {
+        let mid = |range: (f64, f64)| (range.0 + range.1) / 2.0;
+        match self {
+            Self::Point(point) => (EVec2::zero(), *point),
+            Self::Curve {
+                control_points,
+                range,
+            } => (
+                EVec2::new(mid(*range), 0.0),
+                project(&decasteljau(control_points, 0.5)),
+            ),
+            Self::Surface {
+                control_points,
+                range_u,
+                range_v,
+            } => {
+                let column = control_points
+                    .iter()
+                    .map(|row| decasteljau(row, 0.5))
+                    .collect::<Vec<_>>();
+                (
+                    EVec2::new(mid(*range_u), mid(*range_v)),
+                    project(&decasteljau(&column, 0.5)),
+                )
+            }
+        }
+    }
+
+    /// Splits the piece in half, across its longer direction for surfaces
+    fn split(&self) -> Option<[Self; 2]> {
+        let halve = |range: (f64, f64)| {
+            let mid = (range.0 + range.1) / 2.0;
+            [(range.0, mid), (mid, range.1)]
+        };
+
+        match self {
+            Self::Point(_) => None,
+            Self::Curve {
+                control_points,
+                range,
+            } => {
+                let (first, second) = split_control_points(control_points);
+                let [first_range, second_range] = halve(*range);
+                Some([
+                    Self::Curve {
+                        control_points: first,
+                        range: first_range,
+                    },
+                    Self::Curve {
+                        control_points: second,
+                        range: second_range,
+                    },
+                ])
+            }
+            Self::Surface {
+                control_points,
+                range_u,
+                range_v,
+            } => {
+                let along = |points: &mut dyn Iterator<Item = (EVec4, EVec4)>| {
+                    points
+                        .map(|(a, b)| (project(&a) - project(&b)).magnitude())
+                        .fold(0.0, f64::max)
+                };
+                let length_u = along(
+                    &mut control_points
+                        .windows(2)
+                        .flat_map(|rows| rows[0].iter().copied().zip(rows[1].iter().copied())),
+                );
+                let length_v = along(
+                    &mut control_points
+                        .iter()
+                        .flat_map(|row| row.windows(2).map(|pair| (pair[0], pair[1]))),
+                );
+
+                if length_u >= length_v {
+                    let columns = transpose(control_points)
+                        .iter()
+                        .map(|column| split_control_points(column))
+                        .collect::<Vec<_>>();
+                    let first = transpose(&columns.iter().map(|c| c.0.clone()).collect::<Vec<_>>());
+                    let second =
+                        transpose(&columns.iter().map(|c| c.1.clone()).collect::<Vec<_>>());
+                    let [first_u, second_u] = halve(*range_u);
+                    Some([
+                        Self::Surface {
+                            control_points: first,
+                            range_u: first_u,
+                            range_v: *range_v,
+                        },
+                        Self::Surface {
+                            control_points: second,
+                            range_u: second_u,
+                            range_v: *range_v,
+                        },
+                    ])
+                } else {
+                    let (first, second): (Vec<_>, Vec<_>) = control_points
+                        .iter()
+                        .map(|row| split_control_points(row))
+                        .unzip();
+                    let [first_v, second_v] = halve(*range_v);
+                    Some([
+                        Self::Surface {
+                            control_points: first,
+                            range_u: *range_u,
+                            range_v: first_v,
+                        },
+                        Self::Surface {
+                            control_points: second,
+                            range_u: *range_u,
+                            range_v: second_v,
+                        },
+                    ])
+                }
+            }
+        }
+    }
+}
+
+/// Splits the control points of a Bezier curve at its middle with de Casteljau's
+/// algorithm
+fn split_control_points(control_points: &[EVec4]) -> (Vec<EVec4>, Vec<EVec4>) {
+    let mut points = control_points.to_vec();
+    let mut first = vec![points[0]];
+    let mut second = vec![points[points.len() - 1]];
+    for k in 1..points.len() {
+        for i in 0..points.len() - k {
+            points[i] = (points[i] + points[i + 1]) * 0.5;
+        }
+        first.push(points[0]);
+        second.push(points[points.len() - k - 1]);
+    }
+    second.reverse();
+    (first, second)
+}
+
+fn transpose(rows: &[Vec<EVec4>]) -> Vec<Vec<EVec4>> {
+    (0..rows[0].len())
+        .map(|j| rows.iter().map(|row| row[j]).collect())
+        .collect()
+}
+
+fn project(weighted: &EVec4) -> EVec3 {
+    EVec3::new(weighted.x, weighted.y, weighted.z) / weighted.w
+}
+
+fn weight(control_points: &[HVec3]) -> Vec<EVec4> {
+    control_points
+        .iter()
+        .map(|p| HSpace3::weight_vec(*p))
+        .collect()
+}
+
+/// Splits a clamped curve into Bezier segments
+fn curve_pieces(curve: &NurbsCurve<HSpace3>) -> Vec<Piece> {
+    let curve = if curve.is_clamped() {
+        curve.clone()
+    } else {
+        curve.to_clamped()
+    };
+
+    curve_decompose(
+        &weight(curve.control_points()),
+        curve.degree(),
+        curve.knot_vector(),
+    )
+    .into_iter()
+    .zip(curve.distinct_knots().windows(2))
+    .map(|(control_points, span)| Piece::Curve {
+        control_points,
+        range: (span[0], span[1]),
+    })
+    .collect()
+}
+
+/// Splits a surface, which must be clamped, into Bezier patches
+fn surface_pieces(surface: &NurbsSurface<HSpace3>) -> Vec<Piece> {
+    let weighted = surface
+        .control_points()
+        .iter()
+        .map(|row| weight(row))
+        .collect::<Vec<_>>();
+
+    // Split each column along u, then each row of each strip along v
+    let columns = transpose(&weighted)
+        .iter()
+        .map(|column| curve_decompose(column, surface.degree_u(), surface.knot_vector_u()))
+        .collect::<Vec<_>>();
+
+    let knots_u = surface.distinct_knots_u();
+    let knots_v = surface.distinct_knots_v();
+    let mut pieces = Vec::new();
+    for (s, span_u) in knots_u.windows(2).enumerate() {
+        let strip = transpose(&columns.iter().map(|c| c[s].clone()).collect::<Vec<_>>());
+        let rows = strip
+            .iter()
+            .map(|row| curve_decompose(row, surface.degree_v(), surface.knot_vector_v()))
+            .collect::<Vec<_>>();
+
+        for (t, span_v) in knots_v.windows(2).enumerate() {
+            pieces.push(Piece::Surface {
+                control_points: rows.iter().map(|row| row[t].clone()).collect(),
+                range_u: (span_u[0], span_u[1]),
+                range_v: (span_v[0], span_v[1]),
+            });
+        }
+    }
+
+    pieces
+}
+
+/// A pair of pieces waiting to be examined, ordered so the pair whose bounding
+/// boxes are closest comes first
+struct Candidate {
+    bound: f64,
+    pieces: (Piece, Piece),
+}
+impl PartialEq for Candidate {
+    fn eq(&self, other: &Self) -> bool {
+        self.bound == other.bound
+    }
+}
+impl Eq for Candidate {}
+impl PartialOrd for Candidate {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+impl Ord for Candidate {
+    fn cmp(&self, other: &Self) -> Ordering {
+        other.bound.total_cmp(&self.bound)
+    }
+}
+
+/// The distance between two bounding boxes, which no points inside them can be
+/// closer than
+fn box_distance(a: &(EVec3, EVec3), b: &(EVec3, EVec3)) -> f64 {
+    let gap = |min_a: f64, max_a: f64, min_b: f64, max_b: f64| {
+        (min_b - max_a).max(min_a - max_b).max(0.0)
+    };
+    EVec3::new(
+        gap(a.0.x, a.1.x, b.0.x, b.1.x),
+        gap(a.0.y, a.1.y, b.0.y, b.1.y),
+        gap(a.0.z, a.1.z, b.0.z, b.1.z),
+    )
+    .magnitude()
+}
+
+/// Searches pairs of pieces of two shapes for the closest points between them by
+/// branch and bound, and returns their parameters. Pairs are split until the
+/// distance between the middles of the best pair is within `tolerance` of the
+/// smallest distance any remaining pair could have.
+fn closest_pieces(first: Vec<Piece>, second: Vec<Piece>, tolerance: f64) -> (EVec2, EVec2) {
+    let mut best = (f64::MAX, EVec2::zero(), EVec2::zero());
+    let mut queue = BinaryHeap::new();
+
+    for a in first.iter() {
+        for b in second.iter() {
+            consider(a.clone(), b.clone(), tolerance, &mut best, &mut queue);
+        }
+    }
+
+    for _ in 0..MAX_PAIRS {
+        let Some(Candidate { bound, pieces: (a, b) }) = queue.pop() else {
+            break;
+        };
+        if bound >= best.0 - tolerance {
+            break;
+        }
+
+        // Split whichever piece is larger
+        let size = |piece: &Piece| {
+            let (min, max) = piece.bounds();
+            (max - min).magnitude()
+        };
+        let split_first = size(&a) >= size(&b);
+        match (split_first, a.split(), b.split()) {
+            (true, Some(halves), _) | (false, Some(halves), None) => {
+                for half in halves {
+                    consider(half, b.clone(), tolerance, &mut best, &mut queue);
+                }
+            }
+            (_, _, Some(halves)) => {
+                for half in halves {
+                    consider(a.clone(), half, tolerance, &mut best, &mut queue);
+                }
+            }
+            (_, None, None) => {}
+        }
+    }
+
+    (best.1, best.2)
+}
+
+/// Updates the best pair of points found so far with the middles of two pieces,
+/// and queues the pieces for splitting if they could hold a closer pair
+fn consider(
+    a: Piece,
+    b: Piece,
+    tolerance: f64,
+    best: &mut (f64, EVec2, EVec2),
+    queue: &mut BinaryHeap<Candidate>,
+) {
+    let ((param_a, point_a), (param_b, point_b)) = (a.middle(), b.middle());
+    let distance = (point_a - point_b).magnitude();
+    if distance < best.0 {
+        *best = (distance, param_a, param_b);
+    }
+
+    let bound = box_distance(&a.bounds(), &b.bounds());
+    if bound < best.0 - tolerance {
+        queue.push(Candidate {
+            bound,
+            pieces: (a, b),
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::f64::consts::PI;
+
+    use space::{hspace::HSpace3, EPlacement3, EVec2, EVec3, EVector};
+
+    use crate::{nurbs_curve::NurbsCurve, nurbs_surface::NurbsSurface};
+
+    #[test]
+    fn point_curve_and_surface() {
+        let placement = EPlacement3::default();
+        let circle = NurbsCurve::<HSpace3>::arc(&placement, 2.0, 0.0, 2.0 * PI);
+        let point = EVec3::new(3.0, 4.0, 1.0);
+        let result = circle.distance_to_point(point, 1e-9);
+        assert!((result.distance - (9.0_f64 + 1.0).sqrt()).abs() <= 1e-9);
+        assert!((result.first.point - EVec3::new(1.2, 1.6, 0.0)).magnitude() <= 1e-9);
+        assert!((circle.point(result.first.param) - result.first.point).magnitude() <= 1e-12);
+
+        let square = NurbsSurface::rectangle(&placement, 2.0, 2.0);
+        let result = square.distance_to_point(point, 1e-9);
+        assert!((result.first.point - EVec3::new(1.0, 1.0, 0.0)).magnitude() <= 1e-9);
+        assert!((result.first.param - EVec2::new(1.0, 1.0)).magnitude() <= 1e-9);
+    }
+
+    #[test]
+    fn curve_and_surface_pairs() {
+        // Two skew lines are closest along their common perpendicular
+        let a = NurbsCurve::<HSpace3>::line(EVec3::new(-1.0, 0.0, 0.0), EVec3::new(1.0, 0.0, 0.0));
+        let b = NurbsCurve::<HSpace3>::line(EVec3::new(0.5, -1.0, 2.0), EVec3::new(0.5, 1.0, 2.0));
+        let result = a.distance_to_curve(&b, 1e-9);
+        assert!((result.distance - 2.0).abs() <= 1e-9);
+        assert!((result.first.point - EVec3::new(0.5, 0.0, 0.0)).magnitude() <= 1e-9);
+
+        // A circle above a plane is closest to it all around, and a tilted square
+        // above another touches down at its lowest edge
+        let circle = NurbsCurve::<HSpace3>::arc(
+            &EPlacement3::new(
+                EVec3::new(0.0, 0.0, 1.0),
+                EVec3::new(1.0, 0.0, 0.0),
+                EVec3::new(0.0, 0.0, 1.0),
+            ),
+            0.5,
+            0.0,
+            2.0 * PI,
+        );
+        let floor = NurbsSurface::rectangle(&EPlacement3::default(), 4.0, 4.0);
+        let result = circle.distance_to_surface(&floor, 1e-9);
+        assert!((result.distance - 0.5).abs() <= 1e-9);
+        assert!(result.second.point.z.abs() <= 1e-12);
+
+        let tilted = NurbsSurface::rectangle(
+            &EPlacement3::new(
+                EVec3::new(0.0, 0.0, 2.0),
+                EVec3::new(1.0, 0.0, 1.0).normalize(),
+                EVec3::new(0.0, 1.0, 0.0),
+            ),
+            1.0,
+            1.0,
+        );
+        let result = tilted.distance_to_surface(&floor, 1e-9);
+        assert!((result.distance - (2.0 - 0.5 / 2.0_f64.sqrt())).abs() <= 1e-9);
+    }
+}
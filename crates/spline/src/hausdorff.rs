@@ -0,0 +1,388 @@
+use space::{
+    hspace::{HSpace, HSpace3},
+    EVec2, EVec3, EVector,
+};
+
+use crate::{nurbs_curve::NurbsCurve, nurbs_surface::NurbsSurface};
+
+/// Number of samples per knot span used to find the neighborhood of the farthest
+/// and closest points
+const SAMPLES_PER_SPAN: usize = 16;
+
+/// Number of sampled maxima that are refined, in case the largest sample is not
+/// nearest the true maximum
+const REFINED_MAXIMA: usize = 4;
+
+/// Maximum number of iterations used to refine a parameter
+const REFINEMENT_STEPS: usize = 64;
+
+/// The two-sided Hausdorff distance between two shapes: the farthest any point
+/// on either shape is from the other shape
+#[derive(Debug, Clone)]
+pub struct HausdorffDistance<P> {
+    pub distance: f64,
+
+    /// The point on either shape that is farthest from the other shape
+    pub point: P,
+
+    /// The point on the other shape closest to `point`
+    pub closest: P,
+}
+
+impl<H: HSpace> NurbsCurve<H> {
+    /// Finds the Hausdorff distance between this curve and another. The curves
+    /// are sampled within each knot span to find the neighborhood of the farthest
+    /// point, which is then refined.
+    pub fn hausdorff_distance(&self, other: &Self) -> HausdorffDistance<H::ProjectedVector> {
+        let (self_samples, other_samples) = (curve_samples(self), curve_samples(other));
+
+        let forward = directed_over_curve(self, &self_samples, |p| {
+            closest_on_curve(other, &other_samples, p)
+        });
+        let backward = directed_over_curve(other, &other_samples, |p| {
+            closest_on_curve(self, &self_samples, p)
+        });
+
+        if forward.distance >= backward.distance {
+            forward
+        } else {
+            backward
+        }
+    }
+}
+
+impl NurbsCurve<HSpace3> {
+    /// Finds the Hausdorff distance between this curve and a surface. This is the
+    /// larger of the farthest the curve gets from the surface and the farthest the
+    /// surface gets from the curve.
+    pub fn hausdorff_distance_to_surface(
+        &self,
+        surface: &NurbsSurface<HSpace3>,
+    ) -> HausdorffDistance<EVec3> {
+        let (curve_samples, surface_samples) = (curve_samples(self), surface_samples(surface));
+
+        let forward = directed_over_curve(self, &curve_samples, |p| {
+            closest_on_surface(surface, &surface_samples, p)
+        });
+        let backward = directed_over_surface(surface, &surface_samples, |p| {
+            closest_on_curve(self, &curve_samples, p)
+        });
+
+        if forward.distance >= backward.distance {
+            forward
+        } else {
+            backward
+        }
+    }
+
+    /// The largest distance from a point on the curve to the surface. This is the
+    /// error of a curve that is meant to lie on the surface.
+    pub fn directed_hausdorff_distance_to_surface(
+        &self,
+        surface: &NurbsSurface<HSpace3>,
+    ) -> HausdorffDistance<EVec3> {
+        let surface_samples = surface_samples(surface);
+        directed_over_curve(self, &curve_samples(self), |p| {
+            closest_on_surface(surface, &surface_samples, p)
+        })
+    }
+}
+
+/// Samples of a curve's parameter and the point there, evenly spaced within each
+/// knot span
+fn curve_samples<H: HSpace>(curve: &NurbsCurve<H>) -> Vec<(f64, H::ProjectedVector)> {
+    span_params(&curve.distinct_knots())
+        .into_iter()
+        .map(|u| (u, curve.point(u)))
+        .collect()
+}
+
+/// A grid of samples of a surface's parameters and the points there, indexed as
+/// `[u][v]`
+fn surface_samples(surface: &NurbsSurface<HSpace3>) -> Vec<Vec<(EVec2, EVec3)>> {
+    let vs = span_params(&surface.distinct_knots_v());
+    span_params(&surface.distinct_knots_u())
+        .into_iter()
+        .map(|u| {
+            vs.iter()
+                .map(|v| (EVec2::new(u, *v), surface.point(u, *v)))
+                .collect()
+        })
+        .collect()
+}
+
+fn span_params(knots: &[f64]) -> Vec<f64> {
+    let mut params = vec![knots[0]];
+    for span in knots.windows(2) {
+        let step = (span[1] - span[0]) / SAMPLES_PER_SPAN as f64;
+        params.extend((1..=SAMPLES_PER_SPAN).map(|i| span[0] + step * i as f64));
+    }
+    params
+}
+
+/// Finds the point on a curve closest to `point`, starting from the nearest
+/// sample and refining with Newton's method
+fn closest_on_curve<H: HSpace>(
+    curve: &NurbsCurve<H>,
+    samples: &[(f64, H::ProjectedVector)],
+    point: H::ProjectedVector,
+) -> H::ProjectedVector {
+    let (mut u, mut best) = samples
+        .iter()
+        .map(|(u, p)| (*u, *p))
+        .min_by(|a, b| {
+            (a.1 - point)
+                .magnitude()
+                .total_cmp(&(b.1 - point).magnitude())
+        })
+        .unwrap();
+
+    for _ in 0..REFINEMENT_STEPS {
+        let ders = curve.derivatives(u, 2);
+        let between = ders[0] - point;
+        let denom = ders[2].dot(&between) + ders[1].dot(&ders[1]);
+        if denom <= 0.0 {
+            break;
+        }
+
+        let next = (u - ders[1].dot(&between) / denom).clamp(curve.min_u(), curve.max_u());
+        let next_point = curve.point(next);
+        if (next_point - point).magnitude() > (best - point).magnitude() {
+            break;
+        }
+
+        let step = (next - u).abs();
+        u = next;
+        best = next_point;
+        if step <= f64::EPSILON * (curve.max_u() - curve.min_u()) {
+            break;
+        }
+    }
+
+    best
+}
+
+/// Finds the point on a surface closest to `point`, starting from the nearest
+/// sample and refining with Newton's method
+fn closest_on_surface(
+    surface: &NurbsSurface<HSpace3>,
+    samples: &[Vec<(EVec2, EVec3)>],
+    point: EVec3,
+) -> EVec3 {
+    let (mut uv, mut best) = samples
+        .iter()
+        .flatten()
+        .copied()
+        .min_by(|a, b| {
+            (a.1 - point)
+                .magnitude()
+                .total_cmp(&(b.1 - point).magnitude())
+        })
+        .unwrap();
+
+    let (min, max) = (
+        EVec2::new(surface.min_u(), surface.min_v()),
+        EVec2::new(surface.max_u(), surface.max_v()),
+    );
+    for _ in 0..REFINEMENT_STEPS {
+        let ders = surface.derivatives(uv.x, uv.y, 2);
+        let between = ders[0][0] - point;
+        let (su, sv) = (ders[1][0], ders[0][1]);
+
+        let (f, g) = (su.dot(&between), sv.dot(&between));
+        let a = ders[2][0].dot(&between) + su.dot(&su);
+        let b = ders[1][1].dot(&between) + su.dot(&sv);
+        let d = ders[0][2].dot(&between) + sv.dot(&sv);
+        let det = a * d - b * b;
+        if det.abs() <= f64::EPSILON {
+            break;
+        }
+
+        let next = EVec2::new(
+            (uv.x - (d * f - b * g) / det).clamp(min.x, max.x),
+            (uv.y - (a * g - b * f) / det).clamp(min.y, max.y),
+        );
+        let next_point = surface.point(next.x, next.y);
+        if (next_point - point).magnitude() > (best - point).magnitude() {
+            break;
+        }
+
+        let step = (next - uv).magnitude();
+        uv = next;
+        best = next_point;
+        if step <= f64::EPSILON * (max - min).magnitude() {
+            break;
+        }
+    }
+
+    best
+}
+
+/// Finds the point on a curve farthest from another shape, given a function that
+/// finds the closest point on that shape
+fn directed_over_curve<H: HSpace>(
+    curve: &NurbsCurve<H>,
+    samples: &[(f64, H::ProjectedVector)],
+    closest: impl Fn(H::ProjectedVector) -> H::ProjectedVector,
+) -> HausdorffDistance<H::ProjectedVector> {
+    let distance = |u: f64| {
+        let point = curve.point(u);
+        let closest = closest(point);
+        HausdorffDistance {
+            distance: (closest - point).magnitude(),
+            point,
+            closest,
+        }
+    };
+
+    let mut sampled = samples
+        .iter()
+        .enumerate()
+        .map(|(i, (u, _))| (i, distance(*u).distance))
+        .collect::<Vec<_>>();
+    sampled.sort_by(|a, b| b.1.total_cmp(&a.1));
+
+    // Golden section search around the largest samples
+    let ratio = (5.0_f64.sqrt() - 1.0) / 2.0;
+    sampled
+        .iter()
+        .take(REFINED_MAXIMA)
+        .map(|(i, _)| {
+            let mut low = samples[i.saturating_sub(1)].0;
+            let mut high = samples[(i + 1).min(samples.len() - 1)].0;
+            for _ in 0..REFINEMENT_STEPS {
+                let a = high - (high - low) * ratio;
+                let b = low + (high - low) * ratio;
+                if distance(a).distance > distance(b).distance {
+                    high = b;
+                } else {
+                    low = a;
+                }
+            }
+
+            [distance((low + high) / 2.0), distance(samples[*i].0)]
+                .into_iter()
+                .max_by(|a, b| a.distance.total_cmp(&b.distance))
+                .unwrap()
+        })
+        .max_by(|a, b| a.distance.total_cmp(&b.distance))
+        .unwrap()
+}
+
+/// Finds the point on a surface farthest from another shape, given a function
+/// that finds the closest point on that shape
+fn directed_over_surface(
+    surface: &NurbsSurface<HSpace3>,
+    samples: &[Vec<(EVec2, EVec3)>],
+    closest: impl Fn(EVec3) -> EVec3,
+) -> HausdorffDistance<EVec3> {
+    let (min, max) = (
+        EVec2::new(surface.min_u(), surface.min_v()),
+        EVec2::new(surface.max_u(), surface.max_v()),
+    );
+    let distance = |uv: EVec2| {
+        let point = surface.point(uv.x, uv.y);
+        let closest = closest(point);
+        HausdorffDistance {
+            distance: (closest - point).magnitude(),
+            point,
+            closest,
+        }
+    };
+
+    let mut sampled = samples
+        .iter()
+        .flatten()
+        .map(|(uv, _)| (*uv, distance(*uv).distance))
+        .collect::<Vec<_>>();
+    sampled.sort_by(|a, b| b.1.total_cmp(&a.1));
+
+    // Pattern search around the largest samples, starting from the sample spacing
+    let spacing = EVec2::new(
+        (samples[1][0].0.x - samples[0][0].0.x).abs(),
+        (samples[0][1].0.y - samples[0][0].0.y).abs(),
+    );
+    sampled
+        .iter()
+        .take(REFINED_MAXIMA)
+        .map(|(uv, _)| {
+            let mut best = (*uv, distance(*uv));
+            let mut step = spacing;
+            for _ in 0..REFINEMENT_STEPS {
+                let moves = [
+                    EVec2::new(step.x, 0.0),
+                    EVec2::new(-step.x, 0.0),
+                    EVec2::new(0.0, step.y),
+                    EVec2::new(0.0, -step.y),
+                ];
+                let better = moves
+                    .iter()
+                    .map(|m| {
+                        let uv = best.0 + *m;
+                        EVec2::new(uv.x.clamp(min.x, max.x), uv.y.clamp(min.y, max.y))
+                    })
+                    .map(|uv| (uv, distance(uv)))
+                    .max_by(|a, b| a.1.distance.total_cmp(&b.1.distance))
+                    .filter(|(_, d)| d.distance > best.1.distance);
+
+                match better {
+                    Some(better) => best = better,
+                    None => step /= 2.0,
+                }
+            }
+            best.1
+        })
+        .max_by(|a, b| a.distance.total_cmp(&b.distance))
+        .unwrap()
+}
+
+#[cfg(test)]
+mod tests {
+    use space::{
+        hspace::{HSpace2, HSpace3},
+        EPlacement3, EVec2, EVec3, EVector,
+    };
+
+    use crate::{nurbs_curve::NurbsCurve, nurbs_surface::NurbsSurface};
+
+    #[test]
+    fn curve_to_curve() {
+        // The farthest a unit quarter circle gets from the chord joining its ends is
+        // at its middle
+        let arc = NurbsCurve::<HSpace2>::arc(EVec2::zero(), 1.0, 0.0, std::f64::consts::FRAC_PI_2);
+        let chord = NurbsCurve::<HSpace2>::line(EVec2::new(1.0, 0.0), EVec2::new(0.0, 1.0));
+        let result = arc.hausdorff_distance(&chord);
+        assert!((result.distance - (1.0 - 0.5_f64.sqrt())).abs() <= 1e-9);
+        assert!((result.point - EVec2::new(0.5_f64.sqrt(), 0.5_f64.sqrt())).magnitude() <= 1e-6);
+
+        // The distance is symmetric, and is reached on the longer curve when one
+        // curve covers part of the other
+        let short = NurbsCurve::<HSpace2>::line(EVec2::new(0.0, 0.0), EVec2::new(1.0, 0.0));
+        let long = NurbsCurve::<HSpace2>::line(EVec2::new(-1.0, 0.5), EVec2::new(3.0, 0.5));
+        for result in [
+            short.hausdorff_distance(&long),
+            long.hausdorff_distance(&short),
+        ] {
+            assert!((result.distance - (4.0_f64 + 0.25).sqrt()).abs() <= 1e-9);
+            assert!((result.point - EVec2::new(3.0, 0.5)).magnitude() <= 1e-9);
+        }
+    }
+
+    #[test]
+    fn curve_to_surface() {
+        let square = NurbsSurface::rectangle(&EPlacement3::default(), 2.0, 2.0);
+
+        // A diagonal rising off the square is farthest from it at its top, while
+        // the square's other corners are farther still from the diagonal
+        let line =
+            NurbsCurve::<HSpace3>::line(EVec3::new(-1.0, -1.0, 0.0), EVec3::new(1.0, 1.0, 0.5));
+        let directed = line.directed_hausdorff_distance_to_surface(&square);
+        assert!((directed.distance - 0.5).abs() <= 1e-9);
+        assert!((directed.closest - EVec3::new(1.0, 1.0, 0.0)).magnitude() <= 1e-9);
+
+        let result = line.hausdorff_distance_to_surface(&square);
+        let (offset, dir) = (EVec3::new(2.0, 0.0, 0.0), EVec3::new(2.0, 2.0, 0.5));
+        let expected = (offset.dot(&offset) - offset.dot(&dir).powi(2) / dir.dot(&dir)).sqrt();
+        assert!((result.distance - expected).abs() <= 1e-9);
+    }
+}
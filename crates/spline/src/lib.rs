@@ -1,4 +1,11 @@
 pub mod bezier_curve;
 pub mod bezier_surface;
+pub mod distance;
+pub mod hausdorff;
+pub mod mass;
 pub mod math;
 pub mod nurbs_curve;
+pub mod nurbs_surface;
+pub mod offset;
+pub mod primitives;
+pub mod trimmed_surface;
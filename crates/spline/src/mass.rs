@@ -0,0 +1,123 @@
+use space::{hspace::HSpace3, EVector, MassProperties, VolumeIntegrals};
+
+use crate::{bezier_surface::BezierSurface, math::quadrature::gauss_legendre};
+
+/// Extra quadrature points in each direction for rational patches, whose
+/// integrands are not polynomials
+const RATIONAL_EXTRA_POINTS: usize = 8;
+
+impl BezierSurface<HSpace3> {
+    /// Adds the patch's contribution to integrals over the volume it helps enclose.
+    /// The patch's normal must face out of the volume. Polynomial patches are
+    /// integrated exactly by Gauss quadrature, and rational patches to within
+    /// rounding for typical weights.
+    pub fn add_volume_integrals(&self, integrals: &mut VolumeIntegrals) {
+        let rational = self
+            .control_points()
+            .iter()
+            .flatten()
+            .any(|p| p.h != self.control_points()[0][0].h);
+
+        // The integrands have degree 5p - 1 in a direction of degree p, which
+        // needs at least 5p / 2 points
+        let points = |degree: usize| {
+            let extra = if rational { RATIONAL_EXTRA_POINTS } else { 0 };
+            5 * degree / 2 + 1 + extra
+        };
+        let rule_u = gauss_legendre(points(self.degree_u()));
+        let rule_v = gauss_legendre(points(self.degree_v()));
+
+        for (u, weight_u) in rule_u.iter() {
+            for (v, weight_v) in rule_v.iter() {
+                let ders = self.derivatives(*u, *v, 1);
+                let area = ders[1][0].cross(&ders[0][1]) * (weight_u * weight_v);
+                integrals.add_element(ders[0][0], area);
+            }
+        }
+    }
+}
+
+/// Finds the mass properties of the body enclosed by a closed set of patches whose
+/// normals face out of it
+pub fn mass_properties(patches: &[BezierSurface<HSpace3>], density: f64) -> MassProperties {
+    let mut integrals = VolumeIntegrals::new();
+    for patch in patches.iter() {
+        patch.add_volume_integrals(&mut integrals);
+    }
+    MassProperties::new(&integrals, density)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::f64::consts::PI;
+
+    use space::{hspace::HSpace3, EVec3, EVector, HVec3, Transform3};
+
+    use crate::bezier_surface::BezierSurface;
+
+    use super::mass_properties;
+
+    /// One eighth of a unit sphere, with its normal facing outwards
+    fn sphere_octant() -> BezierSurface<HSpace3> {
+        let w = 0.5_f64.sqrt();
+        let meridian = [(1.0, 0.0, 1.0), (1.0, 1.0, w), (0.0, 1.0, 1.0)];
+        BezierSurface::new(
+            [(1.0, 0.0, 1.0), (1.0, 1.0, w), (0.0, 1.0, 1.0)]
+                .iter()
+                .map(|(x, y, h)| {
+                    meridian
+                        .iter()
+                        .map(|(r, z, m)| HVec3::new(x * r, y * r, *z, h * m))
+                        .collect()
+                })
+                .collect(),
+        )
+    }
+
+    #[test]
+    fn sphere_mass_properties() {
+        let octants = (0..8)
+            .map(|i| {
+                let mut octant = sphere_octant();
+                let flip = if i < 4 {
+                    Transform3::identity()
+                } else {
+                    Transform3::rotation(EVec3::new(1.0, 0.0, 0.0), PI)
+                };
+                octant
+                    .transform(
+                        &Transform3::rotation(EVec3::new(0.0, 0.0, 1.0), PI / 2.0 * i as f64)
+                            .then(&flip),
+                    )
+                    .unwrap();
+                octant
+            })
+            .collect::<Vec<_>>();
+
+        let props = mass_properties(&octants, 3.0);
+        assert!((props.area - 4.0 * PI).abs() <= 1e-9);
+        assert!((props.volume - 4.0 * PI / 3.0).abs() <= 1e-9);
+        assert!(props.centroid.magnitude() <= 1e-9);
+
+        // A solid sphere's moment of inertia is 2mr²/5 about any axis
+        for i in 0..3 {
+            for j in 0..3 {
+                let expected = if i == j { 0.4 * props.mass } else { 0.0 };
+                assert!((props.inertia[i][j] - expected).abs() <= 1e-9);
+            }
+        }
+
+        // A cubic patch is integrated exactly
+        let corner = |x: f64, y: f64| HVec3::new(x, y, 0.0, 1.0);
+        let square = BezierSurface::<HSpace3>::new(
+            (0..4)
+                .map(|i| {
+                    (0..4)
+                        .map(|j| corner(i as f64 / 3.0, j as f64 / 3.0))
+                        .collect()
+                })
+                .collect(),
+        );
+        assert!((mass_properties(&[square], 1.0).area - 1.0).abs() <= 1e-12);
+    }
+}
@@ -104,3 +104,84 @@ pub fn curve_derivative_control_points<E: EVector>(
 
     points
 }
+
+pub fn surface_point<E: EVector>(
+    control_points: &[Vec<E>],
+    degree_u: usize,
+    knot_vector_u: &KnotVector,
+    degree_v: usize,
+    knot_vector_v: &KnotVector,
+    u: f64,
+    v: f64,
+) -> E {
+    let span_u = knot_vector_u.find_span(degree_u, control_points.len(), u);
+    let span_v = knot_vector_v.find_span(degree_v, control_points[0].len(), v);
+    let basis_u = eval_basis_function(degree_u, span_u, knot_vector_u, u);
+    let basis_v = eval_basis_function(degree_v, span_v, knot_vector_v, v);
+
+    let mut point = E::zero();
+    for l in 0..=degree_v {
+        let mut temp = E::zero();
+        for k in 0..=degree_u {
+            temp = temp + control_points[span_u - degree_u + k][span_v - degree_v + l] * basis_u[k];
+        }
+        point = point + temp * basis_v[l];
+    }
+
+    point
+}
+
+/// The control net, degrees and knot vectors of a (non-rational) B-spline surface
+pub struct SurfaceDefinition<'a, E> {
+    pub control_points: &'a [Vec<E>],
+    pub degree_u: usize,
+    pub knot_vector_u: &'a KnotVector,
+    pub degree_v: usize,
+    pub knot_vector_v: &'a KnotVector,
+}
+
+/// Evaluates the (non-rational) surface derivatives up to `num_derivatives` at `(u, v)`.
+/// The result is indexed as `[k][l]`, where `k` is the derivative with respect to `u` and
+/// `l` with respect to `v`. Derivatives above the degree of the surface are zero.
+pub fn surface_derivatives_1<E: EVector>(
+    surface: &SurfaceDefinition<E>,
+    num_derivatives: usize,
+    u: f64,
+    v: f64,
+) -> Vec<Vec<E>> {
+    let SurfaceDefinition {
+        control_points,
+        degree_u,
+        knot_vector_u,
+        degree_v,
+        knot_vector_v,
+    } = *surface;
+    let du = usize::min(num_derivatives, degree_u);
+    let dv = usize::min(num_derivatives, degree_v);
+    let mut derivatives = vec![vec![E::zero(); num_derivatives + 1]; num_derivatives + 1];
+
+    let span_u = knot_vector_u.find_span(degree_u, control_points.len(), u);
+    let span_v = knot_vector_v.find_span(degree_v, control_points[0].len(), v);
+    let basis_u = eval_basis_function_derivatives(degree_u, span_u, knot_vector_u, du, u);
+    let basis_v = eval_basis_function_derivatives(degree_v, span_v, knot_vector_v, dv, v);
+
+    let mut temp = vec![E::zero(); degree_v + 1];
+    for k in 0..=du {
+        for s in 0..=degree_v {
+            temp[s] = E::zero();
+            for r in 0..=degree_u {
+                temp[s] = temp[s]
+                    + control_points[span_u - degree_u + r][span_v - degree_v + s] * basis_u[k][r];
+            }
+        }
+
+        let dd = usize::min(num_derivatives - k, dv);
+        for l in 0..=dd {
+            for s in 0..=degree_v {
+                derivatives[k][l] = derivatives[k][l] + temp[s] * basis_v[l][s];
+            }
+        }
+    }
+
+    derivatives
+}
@@ -1,6 +1,6 @@
 use std::ops::{Add, Mul, Sub};
 
-use space::{hspace::HSpace, EVec2, EVector, HVector, TOL};
+use space::{hspace::HSpace, EVec2, EVector, HVector};
 
 use super::binomial_coefficient;
 
@@ -45,7 +45,16 @@ where
     }
 }
 
-pub fn newton_f64<F>(u_guess: f64, max_iter: usize, min_u: f64, max_u: f64, eval: F) -> Option<f64>
+/// Finds a zero of a function near `u_guess` by Newton's method, given its value
+/// and derivative. Values within `tolerance` of zero count as zero.
+pub fn newton_f64<F>(
+    u_guess: f64,
+    max_iter: usize,
+    min_u: f64,
+    max_u: f64,
+    tolerance: f64,
+    eval: F,
+) -> Option<f64>
 where
     F: Fn(f64) -> (f64, f64),
 {
@@ -53,7 +62,7 @@ where
     for _ in 0..max_iter {
         let (self_val, der_val) = eval(u);
 
-        if self_val.abs() <= TOL {
+        if self_val.abs() <= tolerance {
             return Some(u);
         } else {
             u -= self_val / der_val;
@@ -71,6 +80,7 @@ pub fn newton<F>(
     max_iter: usize,
     min_uv: EVec2,
     max_uv: EVec2,
+    tolerance: f64,
     eval: F,
 ) -> Option<EVec2>
 where
@@ -80,7 +90,7 @@ where
     for i in 0..max_iter {
         let (self_val, der_val) = eval(guess);
 
-        if self_val.magnitude() <= TOL {
+        if self_val.magnitude() <= tolerance {
             println!("{} iter", i);
             return Some(guess);
         } else {
@@ -100,6 +110,7 @@ pub fn newton_vec<F, E: EVector>(
     max_iter: usize,
     min_u: f64,
     max_u: f64,
+    tolerance: f64,
     eval: F,
 ) -> Option<f64>
 where
@@ -109,12 +120,12 @@ where
     for _ in 0..max_iter {
         let (self_val, der_val) = eval(u);
 
-        if self_val.magnitude() <= TOL {
+        if self_val.magnitude() <= tolerance {
             return Some(u);
         } else {
             let correction = (self_val / der_val).max_component();
 
-            if correction.abs() < 0.03 * TOL {
+            if correction.abs() < 0.03 * tolerance {
                 return None;
             }
 
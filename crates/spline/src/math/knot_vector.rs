@@ -77,6 +77,18 @@ impl KnotVector {
         multiplicity
     }
 
+    /// Returns the distinct knot values between `min` and `max` (inclusive), in order
+    pub fn distinct_knots(&self, min: f64, max: f64) -> Vec<f64> {
+        let mut knots = self
+            .knots
+            .iter()
+            .cloned()
+            .filter(|k| *k >= min && *k <= max)
+            .collect::<Vec<_>>();
+        knots.dedup();
+        knots
+    }
+
     /// Returns the index of the knot in the knot vector, or None if
     /// if isn't in the vector. If the knot exists multiple times,
     /// it will return the index of the first occurrence.
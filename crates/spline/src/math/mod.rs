@@ -3,6 +3,7 @@ pub mod basis;
 pub mod bezier;
 pub mod knot_vector;
 pub mod nurbs;
+pub mod quadrature;
 
 const BINOMIAL_COEFFICIENTS: [[f64; 10]; 10] = [
     [1.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0],
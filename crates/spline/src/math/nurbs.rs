@@ -1,6 +1,6 @@
 use space::{hspace::HSpace, EVector, HVector};
 
-use super::{basis::eval_basis_function, binomial_coefficient, knot_vector::KnotVector};
+use super::{b_spline, basis::eval_basis_function, binomial_coefficient, knot_vector::KnotVector};
 
 pub fn curve_point<H: HSpace>(
     control_points: &[H::Vector],
@@ -18,6 +18,33 @@ pub fn curve_point<H: HSpace>(
     H::project_vec(H::cast_vec_from_weighted(weighted))
 }
 
+pub fn surface_point<H: HSpace>(
+    control_points: &[Vec<H::Vector>],
+    degree_u: usize,
+    knot_vector_u: &KnotVector,
+    degree_v: usize,
+    knot_vector_v: &KnotVector,
+    u: f64,
+    v: f64,
+) -> H::ProjectedVector {
+    let weighted = control_points
+        .iter()
+        .map(|row| row.iter().map(|p| H::weight_vec(*p)).collect::<Vec<_>>())
+        .collect::<Vec<_>>();
+
+    let point = b_spline::surface_point(
+        &weighted,
+        degree_u,
+        knot_vector_u,
+        degree_v,
+        knot_vector_v,
+        u,
+        v,
+    );
+
+    H::project_vec(H::cast_vec_from_weighted(point))
+}
+
 pub fn curve_derivatives<H: HSpace>(
     h_derivatives: &[H::Vector],
     num_derivatives: usize,
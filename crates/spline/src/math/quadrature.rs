@@ -0,0 +1,59 @@
+use std::f64::consts::PI;
+
+/// Returns the nodes and weights of the `n`-point Gauss-Legendre rule on `[0, 1]`,
+/// which integrates polynomials of degree up to `2n - 1` exactly
+pub fn gauss_legendre(n: usize) -> Vec<(f64, f64)> {
+    assert!(n > 0, "A quadrature rule needs at least one point");
+
+    (0..n)
+        .map(|i| {
+            // Newton's method on the Legendre polynomial, starting from an
+            // approximation of its i-th root
+            let mut x = (PI * (i as f64 + 0.75) / (n as f64 + 0.5)).cos();
+            let mut der = 0.0;
+            for _ in 0..100 {
+                let (value, d) = legendre(n, x);
+                der = d;
+                let step = value / d;
+                x -= step;
+                if step.abs() <= 1e-15 {
+                    break;
+                }
+            }
+
+            let weight = 2.0 / ((1.0 - x * x) * der * der);
+            ((1.0 - x) / 2.0, weight / 2.0)
+        })
+        .collect()
+}
+
+/// Evaluates the Legendre polynomial of degree `n` and its derivative at `x`
+fn legendre(n: usize, x: f64) -> (f64, f64) {
+    let (mut prev, mut value) = (1.0, x);
+    for k in 2..=n {
+        let next = ((2 * k - 1) as f64 * x * value - (k - 1) as f64 * prev) / k as f64;
+        prev = value;
+        value = next;
+    }
+
+    (value, n as f64 * (x * value - prev) / (x * x - 1.0))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::gauss_legendre;
+
+    #[test]
+    fn integrates_polynomials_exactly() {
+        for n in 1..8 {
+            let rule = gauss_legendre(n);
+            for degree in 0..2 * n {
+                let integral = rule
+                    .iter()
+                    .map(|(x, w)| w * x.powi(degree as i32))
+                    .sum::<f64>();
+                assert!((integral - 1.0 / (degree + 1) as f64).abs() <= 1e-13);
+            }
+        }
+    }
+}
@@ -1,6 +1,6 @@
 use space::{
     hspace::{HSpace, HSpace2, HSpace3},
-    EVec2, EVector, HVec2, HVec3,
+    EVec2, EVector, HVec2, HVec3, HVector, PointAtInfinity, Tolerance, Transform3,
 };
 
 use crate::{
@@ -8,7 +8,7 @@ use crate::{
     math::{
         b_spline::{curve_derivative_control_points, curve_derivatives_1, curve_derivatives_2},
         knot_vector::KnotVector,
-        nurbs::{curve_decompose, curve_derivatives, curve_point},
+        nurbs::{curve_decompose, curve_derivatives, curve_insert_knots, curve_point},
     },
 };
 
@@ -20,7 +20,7 @@ pub struct ClosestResult<H: HSpace> {
     pub iterations: usize,
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct NurbsCurve<H: HSpace> {
     control_points: Vec<H::Vector>,
     knot_vector: KnotVector,
@@ -42,12 +42,12 @@ impl<H: HSpace> NurbsCurve<H> {
     }
 
     pub fn decompose(&self) -> Vec<BezierCurve<H>> {
+        if !self.is_clamped() {
+            return self.to_clamped().decompose();
+        }
+
         curve_decompose(
-            &self
-                .control_points
-                .iter()
-                .map(|p| H::weight_vec(p.clone()))
-                .collect::<Vec<_>>(),
+            &self.weighted_control_points(),
             self.degree(),
             &self.knot_vector,
         )
@@ -61,8 +61,8 @@ impl<H: HSpace> NurbsCurve<H> {
         point: H::ProjectedVector,
         u_guess: f64,
         max_iter: usize,
+        tolerance: &Tolerance,
     ) -> Option<ClosestResult<H>> {
-        const TOL: f64 = 0.0000001;
         let mut u = u_guess;
         for i in 0..max_iter {
             let ders = self.derivatives(u, 2);
@@ -70,7 +70,7 @@ impl<H: HSpace> NurbsCurve<H> {
             let vec_to_actual = ders[0] - point;
             let dot_err = ders[1].dot(&vec_to_actual);
 
-            if dot_err.abs() <= TOL {
+            if dot_err.abs() <= tolerance.linear {
                 return Some(ClosestResult {
                     u,
                     closest_point: ders[0],
@@ -112,11 +112,7 @@ impl<H: HSpace> NurbsCurve<H> {
         );
 
         let cpts = curve_derivative_control_points(
-            &self
-                .control_points
-                .iter()
-                .map(|cp| H::weight_vec(cp.clone()))
-                .collect::<Vec<_>>(),
+            &self.weighted_control_points(),
             degree,
             &self.knot_vector,
             0,
@@ -145,45 +141,62 @@ impl<H: HSpace> NurbsCurve<H> {
         self.knot_vector.len() - self.control_points.len() - 1
     }
 
+    /// Applies a transform to the control points in homogeneous form, so the
+    /// shape of rational curves is preserved exactly. Leaves the curve unchanged
+    /// and fails if a projective transform sends a control point to infinity.
+    pub fn transform(&mut self, transform: &Transform3) -> Result<(), PointAtInfinity> {
+        self.control_points = self
+            .control_points
+            .iter()
+            .map(|point| H::transform_vec(transform, *point))
+            .collect::<Result<_, _>>()?;
+        Ok(())
+    }
+
     pub fn point(&self, u: f64) -> H::ProjectedVector {
-        curve_point::<H>(&self.control_points, self.degree(), &self.knot_vector, u)
+        curve_point::<H>(
+            &self.control_points,
+            self.degree(),
+            &self.knot_vector,
+            self.wrap_u(u),
+        )
     }
 
     pub fn derivatives(&self, u: f64, num_ders: usize) -> Vec<H::ProjectedVector> {
         let ders = curve_derivatives_1(
-            &self
-                .control_points
-                .iter()
-                .map(|p| H::weight_vec(p.clone()))
-                .collect::<Vec<_>>(),
+            &self.weighted_control_points(),
             self.degree(),
             &self.knot_vector,
             num_ders,
-            u,
+            self.wrap_u(u),
         )
         .into_iter()
         .map(H::cast_vec_from_weighted)
         .collect::<Vec<_>>();
 
+        // Derivatives above the degree of the curve are zero
+        let mut ders = ders;
+        ders.resize(num_ders + 1, H::Vector::zero());
+
         curve_derivatives::<H>(&ders, num_ders)
     }
 
     pub fn derivative(&self, u: f64, der: usize) -> H::ProjectedVector {
         let ders = curve_derivatives_2(
-            &self
-                .control_points
-                .iter()
-                .map(|p| H::weight_vec(p.clone()))
-                .collect::<Vec<_>>(),
+            &self.weighted_control_points(),
             self.degree(),
             &self.knot_vector,
             der,
-            u,
+            self.wrap_u(u),
         )
         .into_iter()
         .map(H::cast_vec_from_weighted)
         .collect::<Vec<_>>();
 
+        // Derivatives above the degree of the curve are zero
+        let mut ders = ders;
+        ders.resize(der + 1, H::Vector::zero());
+
         curve_derivatives::<H>(&ders, der)[der]
     }
 
@@ -199,11 +212,219 @@ impl<H: HSpace> NurbsCurve<H> {
     }
 
     pub fn min_u(&self) -> f64 {
-        self.knot_vector[0]
+        self.knot_vector[self.degree()]
     }
 
     pub fn max_u(&self) -> f64 {
-        self.knot_vector[self.knot_vector.len() - 1]
+        self.knot_vector[self.knot_vector.len() - self.degree() - 1]
+    }
+
+    /// Maps parameters outside the domain of a closed curve back into it, so the
+    /// curve can be evaluated seamlessly across its seam. Evaluation isn't tied to
+    /// a model, so curves are taken to be closed by the default tolerance.
+    fn wrap_u(&self, u: f64) -> f64 {
+        let min_u = self.min_u();
+        let max_u = self.max_u();
+
+        if (u < min_u || u > max_u) && self.is_closed(&Tolerance::DEFAULT) {
+            min_u + (u - min_u).rem_euclid(max_u - min_u)
+        } else {
+            u
+        }
+    }
+
+    fn weighted_control_points(&self) -> Vec<H::WeightedVector> {
+        self.control_points
+            .iter()
+            .map(|p| H::weight_vec(*p))
+            .collect()
+    }
+
+    /// Whether the curve starts within the linear tolerance of where it ends
+    pub fn is_closed(&self, tolerance: &Tolerance) -> bool {
+        (self.point(self.min_u()) - self.point(self.max_u())).magnitude() <= tolerance.linear
+    }
+
+    /// Whether the first and last `degree + 1` knots are equal, so the curve
+    /// interpolates its first and last control points
+    pub fn is_clamped(&self) -> bool {
+        let degree = self.degree();
+        let len = self.knot_vector.len();
+
+        (1..=degree).all(|i| {
+            self.knot_vector[i] == self.knot_vector[0]
+                && self.knot_vector[len - 1 - i] == self.knot_vector[len - 1]
+        })
+    }
+
+    /// Whether the curve is unclamped with its first `degree` control points
+    /// repeated at the end and a knot spacing that repeats with the same period.
+    /// Such a curve closes with the same continuity it has at its interior knots.
+    /// Points and knot spans within the linear tolerance count as repeated.
+    pub fn is_periodic(&self, tolerance: &Tolerance) -> bool {
+        let degree = self.degree();
+        let num_ctrl_pts = self.control_points.len();
+        if degree == 0 || num_ctrl_pts <= degree || self.is_clamped() {
+            return false;
+        }
+
+        let period = num_ctrl_pts - degree;
+        let weighted = self.weighted_control_points();
+        let points_wrap = (0..degree)
+            .all(|i| (weighted[i] - weighted[period + i]).magnitude() <= tolerance.linear);
+
+        let knots = &self.knot_vector;
+        let spacing_repeats = (0..knots.len() - 1 - period).all(|i| {
+            let span = knots[i + 1] - knots[i];
+            let wrapped_span = knots[period + i + 1] - knots[period + i];
+            (span - wrapped_span).abs() <= tolerance.linear
+        });
+
+        points_wrap && spacing_repeats
+    }
+
+    /// Creates a closed, uniform periodic curve of the given degree. `points` lists
+    /// each control point once; the first `degree` of them are repeated internally
+    /// to close the curve. The domain is `[0, 1]`.
+    pub fn new_periodic(points: &[H::Vector], degree: usize) -> Self {
+        assert!(
+            degree >= 1 && points.len() > degree,
+            "A periodic curve of degree {degree} needs more than {degree} control points (got {})",
+            points.len()
+        );
+
+        let num_points = points.len();
+        let control_points = points
+            .iter()
+            .chain(points.iter().take(degree))
+            .cloned()
+            .collect::<Vec<_>>();
+        let knot_vector = (0..(control_points.len() + degree + 1))
+            .map(|i| (i as f64 - degree as f64) / num_points as f64)
+            .collect();
+
+        Self::new(control_points, knot_vector)
+    }
+
+    /// Creates a curve with the same shape that runs in the opposite direction. The
+    /// domain is unchanged, so `reversed.point(min_u + max_u - u) == point(u)`.
+    pub fn reverse(&self) -> Self {
+        let sum = self.min_u() + self.max_u();
+
+        Self::new(
+            self.control_points.iter().rev().cloned().collect(),
+            self.knot_vector.iter().rev().map(|k| sum - k).collect(),
+        )
+    }
+
+    /// Converts the curve to an equivalent clamped curve by inserting knots at the
+    /// ends of its domain
+    pub fn to_clamped(&self) -> Self {
+        if self.is_clamped() {
+            return Self::new(self.control_points.clone(), self.knot_vector.clone());
+        }
+
+        self.clamp_start().reverse().clamp_start().reverse()
+    }
+
+    /// Clamps the start of the curve by raising the multiplicity of the first knot
+    /// in the domain to the degree, then dropping the knots and control points that
+    /// lie before it
+    fn clamp_start(&self) -> Self {
+        let degree = self.degree();
+        let min_u = self.min_u();
+        if self.knot_vector[0] == min_u {
+            return Self::new(self.control_points.clone(), self.knot_vector.clone());
+        }
+
+        let num_insertions = degree.saturating_sub(self.knot_vector.find_multiplicity(min_u));
+        let (knot_vector, weighted) = if num_insertions > 0 {
+            curve_insert_knots(
+                &self.weighted_control_points(),
+                degree,
+                &self.knot_vector,
+                min_u,
+                num_insertions,
+            )
+        } else {
+            (self.knot_vector.clone(), self.weighted_control_points())
+        };
+
+        let first = knot_vector.find_index(min_u).unwrap();
+
+        Self::new(
+            weighted[(first - 1)..]
+                .iter()
+                .map(|p| H::unweight_vec(*p))
+                .collect(),
+            std::iter::once(min_u)
+                .chain(knot_vector.iter().skip(first).cloned())
+                .collect(),
+        )
+    }
+
+    /// Converts the curve to an equivalent unclamped curve, extending the knot
+    /// vector periodically past each end of the domain
+    pub fn to_unclamped(&self) -> Self {
+        // Piegl & Tiller, The NURBS Book, algorithm A12.1
+        let p = self.degree();
+        let n = self.control_points.len() - 1;
+        let mut knots = self.knot_vector.clone();
+        let mut pw = self.weighted_control_points();
+
+        if p == 0 {
+            return Self::new(self.control_points.clone(), knots);
+        }
+
+        for i in 0..p.saturating_sub(1) {
+            knots[p - i - 1] = knots[p - i] - (knots[n - i + 1] - knots[n - i]);
+            let mut k = p - 1;
+            for j in (0..=i).rev() {
+                let alpha = (knots[p] - knots[k]) / (knots[p + j + 1] - knots[k]);
+                pw[j] = (pw[j] - pw[j + 1] * alpha) / (1.0 - alpha);
+                k -= 1;
+            }
+        }
+        knots[0] = knots[1] - (knots[n - p + 2] - knots[n - p + 1]);
+
+        for i in 0..p.saturating_sub(1) {
+            knots[n + i + 2] = knots[n + i + 1] + (knots[p + i + 1] - knots[p + i]);
+            for j in (0..=i).rev() {
+                let alpha = (knots[n + 1] - knots[n - j]) / (knots[n - j + i + 2] - knots[n - j]);
+                pw[n - j] = (pw[n - j] - pw[n - j - 1] * (1.0 - alpha)) / alpha;
+            }
+        }
+        knots[n + p + 1] = knots[n + p] + (knots[2 * p] - knots[2 * p - 1]);
+
+        Self::new(pw.into_iter().map(H::unweight_vec).collect(), knots)
+    }
+
+    /// Converts a closed curve to periodic form. Returns `None` if the curve is open,
+    /// or if it is less smooth across its seam than a periodic curve would be (for
+    /// example, a circle made of rational quadratic arcs).
+    pub fn to_periodic(&self, tolerance: &Tolerance) -> Option<Self> {
+        if self.is_periodic(tolerance) {
+            return Some(Self::new(
+                self.control_points.clone(),
+                self.knot_vector.clone(),
+            ));
+        }
+
+        if !self.is_closed(tolerance) {
+            return None;
+        }
+
+        let unclamped = self.to_unclamped();
+        if unclamped.is_periodic(tolerance) {
+            Some(unclamped)
+        } else {
+            None
+        }
+    }
+
+    /// Returns the distinct knot values within the curve's domain
+    pub fn distinct_knots(&self) -> Vec<f64> {
+        self.knot_vector.distinct_knots(self.min_u(), self.max_u())
     }
 }
 impl NurbsCurve<HSpace2> {
@@ -322,3 +543,96 @@ impl NurbsCurve<HSpace3> {
         )
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use space::{
+        hspace::{HSpace2, HSpace3},
+        EVec3, EVector, HVec2, Tolerance, Transform3, TOL,
+    };
+
+    use crate::{math::FloatRange, nurbs_curve::NurbsCurve};
+
+    fn example_periodic() -> NurbsCurve<HSpace2> {
+        NurbsCurve::new_periodic(
+            &[
+                HVec2::new(0.0, 0.0, 1.0),
+                HVec2::new(2.0, -1.0, 0.5),
+                HVec2::new(4.0, 1.0, 1.0),
+                HVec2::new(3.0, 3.0, 2.0),
+                HVec2::new(1.0, 4.0, 1.0),
+                HVec2::new(-1.0, 2.0, 1.0),
+            ],
+            3,
+        )
+    }
+
+    #[test]
+    fn periodic_curve_is_smooth_across_seam() {
+        let curve = example_periodic();
+        assert!(curve.is_periodic(&Tolerance::DEFAULT));
+        assert!(curve.is_closed(&Tolerance::DEFAULT));
+        assert!(!curve.is_clamped());
+
+        for der in 0..3 {
+            let before = curve.derivative(curve.max_u(), der);
+            let after = curve.derivative(curve.min_u(), der);
+            assert!((before - after).magnitude() <= TOL * before.magnitude().max(1.0));
+        }
+
+        assert!((curve.point(1.2) - curve.point(0.2)).magnitude() <= TOL);
+        assert!((curve.point(-0.3) - curve.point(0.7)).magnitude() <= TOL);
+    }
+
+    #[test]
+    fn clamped_and_periodic_conversions_keep_shape() {
+        let periodic = example_periodic();
+        let clamped = periodic.to_clamped();
+        assert!(clamped.is_clamped());
+        assert!(clamped.is_closed(&Tolerance::DEFAULT));
+
+        let roundtrip = clamped.to_periodic(&Tolerance::DEFAULT).unwrap();
+        assert!(roundtrip.is_periodic(&Tolerance::DEFAULT));
+
+        for u in FloatRange::new(0.0, 1.0, 40) {
+            assert!((clamped.point(u) - periodic.point(u)).magnitude() <= TOL);
+            assert!((roundtrip.point(u) - periodic.point(u)).magnitude() <= TOL);
+        }
+
+        // Quadratic arcs only meet with tangent continuity in projected space
+        let circle = NurbsCurve::<HSpace2>::example_circle();
+        assert!(circle.is_closed(&Tolerance::DEFAULT));
+        assert!(circle.to_periodic(&Tolerance::DEFAULT).is_none());
+        assert!((circle.point(1.3) - circle.point(0.3)).magnitude() <= TOL);
+    }
+
+    #[test]
+    fn reversed_curve_runs_backwards() {
+        let curve = NurbsCurve::<HSpace3>::example_crazy();
+        let reversed = curve.reverse();
+
+        for u in FloatRange::new(curve.min_u(), curve.max_u(), 40) {
+            let expected = curve.point(u);
+            let actual = reversed.point(curve.min_u() + curve.max_u() - u);
+            assert!((expected - actual).magnitude() <= TOL);
+        }
+
+        assert_eq!(example_periodic().reverse().decompose().len(), 6);
+    }
+
+    #[test]
+    fn projective_transform_is_exact() {
+        let mut transform = Transform3::rotation(EVec3::new(1.0, 1.0, 0.0), 0.6)
+            .then(&Transform3::translation(EVec3::new(0.0, 2.0, 1.0)));
+        transform.m[3] = [0.1, -0.2, 0.05, 1.0];
+
+        let circle = NurbsCurve::<HSpace3>::example_circle();
+        let mut transformed = circle.clone();
+        transformed.transform(&transform).unwrap();
+
+        for u in FloatRange::new(0.0, 1.0, 40) {
+            let expected = transform.apply_point(circle.point(u));
+            assert!((transformed.point(u) - expected).magnitude() <= TOL);
+        }
+    }
+}
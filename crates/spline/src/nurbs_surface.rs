@@ -0,0 +1,219 @@
+use space::{
+    hspace::{HSpace, HSpace3},
+    EVec3, EVector, PointAtInfinity, Transform3,
+};
+
+use crate::{
+    bezier_surface::BezierSurface,
+    math::{
+        b_spline::{surface_derivatives_1, SurfaceDefinition},
+        bezier::surface_derivatives,
+        knot_vector::KnotVector,
+        nurbs::surface_point,
+    },
+};
+
+/// A tensor product NURBS surface. Control points are indexed as `[i][j]`, where
+/// `i` runs along the `u` direction and `j` along the `v` direction.
+#[derive(Debug, Clone)]
+pub struct NurbsSurface<H: HSpace> {
+    control_points: Vec<Vec<H::Vector>>,
+    knot_vector_u: KnotVector,
+    knot_vector_v: KnotVector,
+}
+impl<H: HSpace> NurbsSurface<H> {
+    pub fn new(
+        control_points: Vec<Vec<H::Vector>>,
+        knot_vector_u: KnotVector,
+        knot_vector_v: KnotVector,
+    ) -> Self {
+        assert!(
+            knot_vector_u.len() > control_points.len(),
+            "Knot vector in u is too short for {} rows of control points",
+            control_points.len()
+        );
+        assert!(
+            control_points
+                .iter()
+                .all(|row| row.len() == control_points[0].len()),
+            "All rows of control points must have the same length"
+        );
+        assert!(
+            knot_vector_v.len() > control_points[0].len(),
+            "Knot vector in v is too short for {} columns of control points",
+            control_points[0].len()
+        );
+
+        Self {
+            control_points,
+            knot_vector_u,
+            knot_vector_v,
+        }
+    }
+
+    /// Creates a NURBS surface with the same shape as a Bezier patch
+    pub fn from_bezier(bezier: &BezierSurface<H>) -> Self {
+        let control_points = bezier.control_points().to_vec();
+        let knot_vector_u = Self::bezier_knot_vector(control_points.len() - 1);
+        let knot_vector_v = Self::bezier_knot_vector(control_points[0].len() - 1);
+
+        Self::new(control_points, knot_vector_u, knot_vector_v)
+    }
+
+    fn bezier_knot_vector(degree: usize) -> KnotVector {
+        (0..=degree)
+            .map(|_| 0.0)
+            .chain((0..=degree).map(|_| 1.0))
+            .collect()
+    }
+
+    pub fn control_points(&self) -> &[Vec<H::Vector>] {
+        &self.control_points
+    }
+
+    pub fn knot_vector_u(&self) -> &KnotVector {
+        &self.knot_vector_u
+    }
+
+    pub fn knot_vector_v(&self) -> &KnotVector {
+        &self.knot_vector_v
+    }
+
+    pub fn degree_u(&self) -> usize {
+        self.knot_vector_u.len() - self.control_points.len() - 1
+    }
+
+    pub fn degree_v(&self) -> usize {
+        self.knot_vector_v.len() - self.control_points[0].len() - 1
+    }
+
+    /// Applies a transform to the control points in homogeneous form, so the
+    /// shape of rational surfaces is preserved exactly. Leaves the surface
+    /// unchanged and fails if a projective transform sends a control point to
+    /// infinity.
+    pub fn transform(&mut self, transform: &Transform3) -> Result<(), PointAtInfinity> {
+        self.control_points = self
+            .control_points
+            .iter()
+            .map(|row| {
+                row.iter()
+                    .map(|point| H::transform_vec(transform, *point))
+                    .collect()
+            })
+            .collect::<Result<_, _>>()?;
+        Ok(())
+    }
+
+    pub fn min_u(&self) -> f64 {
+        self.knot_vector_u[self.degree_u()]
+    }
+
+    pub fn max_u(&self) -> f64 {
+        self.knot_vector_u[self.knot_vector_u.len() - self.degree_u() - 1]
+    }
+
+    pub fn min_v(&self) -> f64 {
+        self.knot_vector_v[self.degree_v()]
+    }
+
+    pub fn max_v(&self) -> f64 {
+        self.knot_vector_v[self.knot_vector_v.len() - self.degree_v() - 1]
+    }
+
+    pub fn point(&self, u: f64, v: f64) -> H::ProjectedVector {
+        surface_point::<H>(
+            &self.control_points,
+            self.degree_u(),
+            &self.knot_vector_u,
+            self.degree_v(),
+            &self.knot_vector_v,
+            u,
+            v,
+        )
+    }
+
+    /// Evaluates the derivatives of the surface up to `num_ders` at `(u, v)`.
+    /// The result is indexed as `[k][l]`, where `k` is the derivative with respect
+    /// to `u` and `l` is the derivative with respect to `v`.
+    pub fn derivatives(&self, u: f64, v: f64, num_ders: usize) -> Vec<Vec<H::ProjectedVector>> {
+        let weighted = self
+            .control_points
+            .iter()
+            .map(|row| row.iter().map(|p| H::weight_vec(*p)).collect::<Vec<_>>())
+            .collect::<Vec<_>>();
+
+        let ders = surface_derivatives_1(
+            &SurfaceDefinition {
+                control_points: &weighted,
+                degree_u: self.degree_u(),
+                knot_vector_u: &self.knot_vector_u,
+                degree_v: self.degree_v(),
+                knot_vector_v: &self.knot_vector_v,
+            },
+            num_ders,
+            u,
+            v,
+        )
+        .into_iter()
+        .map(|row| {
+            row.into_iter()
+                .map(H::cast_vec_from_weighted)
+                .collect::<Vec<_>>()
+        })
+        .collect::<Vec<_>>();
+
+        surface_derivatives::<H>(&ders, num_ders)
+    }
+
+    /// Returns the distinct knot values in `u` that lie within the surface's domain
+    pub fn distinct_knots_u(&self) -> Vec<f64> {
+        self.knot_vector_u
+            .distinct_knots(self.min_u(), self.max_u())
+    }
+
+    /// Returns the distinct knot values in `v` that lie within the surface's domain
+    pub fn distinct_knots_v(&self) -> Vec<f64> {
+        self.knot_vector_v
+            .distinct_knots(self.min_v(), self.max_v())
+    }
+}
+impl NurbsSurface<HSpace3> {
+    /// Returns the unit normal of the surface at `(u, v)`, or `None` if the surface
+    /// is degenerate at that point (e.g. at the pole of a sphere).
+    pub fn normal(&self, u: f64, v: f64) -> Option<EVec3> {
+        let ders = self.derivatives(u, v, 1);
+        let normal = ders[1][0].cross(&ders[0][1]);
+        let magnitude = normal.magnitude();
+
+        if magnitude <= space::TOL {
+            None
+        } else {
+            Some(normal / magnitude)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use space::{hspace::HSpace3, EPlane3, EVec3, EVector, Transform3, TOL};
+
+    use crate::{bezier_surface::BezierSurface, math::FloatRange, nurbs_surface::NurbsSurface};
+
+    #[test]
+    fn projective_transform_is_exact() {
+        let mut transform = Transform3::mirror(&EPlane3::new_general_form(1.0, 1.0, 0.0, -1.0))
+            .then(&Transform3::translation(EVec3::new(-1.0, 0.0, 2.0)));
+        transform.m[3] = [0.1, 0.05, 0.2, 1.0];
+
+        let sphere = NurbsSurface::from_bezier(&BezierSurface::<HSpace3>::example_eighth_sphere());
+        let mut transformed = sphere.clone();
+        transformed.transform(&transform).unwrap();
+
+        for u in FloatRange::new(0.0, 1.0, 10) {
+            for v in FloatRange::new(0.0, 1.0, 10) {
+                let expected = transform.apply_point(sphere.point(u, v));
+                assert!((transformed.point(u, v) - expected).magnitude() <= TOL);
+            }
+        }
+    }
+}
@@ -0,0 +1,880 @@
+use space::{
+    hspace::{HSpace2, HSpace3},
+    EVec2, EVec3, EVector, HVec2, HVec3, Tolerance,
+};
+
+use crate::{
+    bezier_surface::BezierSurface,
+    math::{bezier::decasteljau, knot_vector::KnotVector},
+    nurbs_curve::NurbsCurve,
+    nurbs_surface::NurbsSurface,
+};
+
+/// Maximum number of times a curve span is bisected while fitting its offset
+const MAX_CURVE_SUBDIVISIONS: usize = 16;
+
+/// Maximum number of refinement passes while fitting a surface offset
+const MAX_SURFACE_REFINEMENTS: usize = 8;
+
+/// Maximum number of intervals in each direction of a fitted surface offset
+const MAX_SURFACE_INTERVALS: usize = 256;
+
+/// Number of samples per knot span used to look for cusps
+const CUSP_SAMPLES: usize = 32;
+
+/// Number of line segments per fitted span used to look for self-intersections
+const INTERSECTION_SAMPLES: usize = 8;
+
+/// The offset of a planar curve, approximated by a NURBS curve
+#[derive(Debug, Clone)]
+pub struct CurveOffset {
+    /// Piecewise cubic approximation of the offset, parameterized like the base curve
+    pub curve: NurbsCurve<HSpace2>,
+
+    /// Parameters at which the offset distance equals the radius of curvature of the
+    /// base curve. The offset has a cusp and reverses direction at each of these.
+    pub cusps: Vec<f64>,
+
+    /// Places where the offset curve crosses itself
+    pub self_intersections: Vec<OffsetIntersection>,
+
+    /// Largest deviation from the exact offset measured while fitting
+    pub error: f64,
+
+    /// Whether every span met the tolerance. Spans that still miss it after the
+    /// maximum number of bisections are kept as they are.
+    pub converged: bool,
+}
+impl CurveOffset {
+    /// Whether the offset is free of cusps and self-intersections
+    pub fn is_regular(&self) -> bool {
+        self.cusps.is_empty() && self.self_intersections.is_empty()
+    }
+}
+
+/// A point where an offset curve crosses itself
+#[derive(Debug, Clone)]
+pub struct OffsetIntersection {
+    pub u1: f64,
+    pub u2: f64,
+    pub point: EVec2,
+}
+
+/// The offset of a surface along its normal, approximated by a NURBS surface
+#[derive(Debug, Clone)]
+pub struct SurfaceOffset {
+    /// Piecewise bicubic approximation of the offset, parameterized like the base surface
+    pub surface: NurbsSurface<HSpace3>,
+
+    /// Parameters at which the base surface has no well-defined normal
+    pub singularities: Vec<EVec2>,
+
+    /// Parameters at which the offset distance reaches a principal radius of
+    /// curvature of the base surface, so the offset folds over itself
+    pub folds: Vec<EVec2>,
+
+    /// Largest deviation from the exact offset measured while fitting
+    pub error: f64,
+
+    /// Whether every cell met the tolerance. Refinement stops short of it once it
+    /// reaches the maximum number of passes or intervals.
+    pub converged: bool,
+}
+impl SurfaceOffset {
+    /// Whether the offset is free of singularities and folds
+    pub fn is_regular(&self) -> bool {
+        self.singularities.is_empty() && self.folds.is_empty()
+    }
+}
+
+impl NurbsCurve<HSpace2> {
+    /// Offsets the curve by `distance` along its normal (see [`NurbsCurve::normal`]).
+    /// The result is approximated by a piecewise cubic curve that deviates from the
+    /// exact offset by no more than `tolerance`, unless the fit gives up first (see
+    /// [`CurveOffset::converged`]).
+    pub fn offset(&self, distance: f64, tolerance: f64) -> CurveOffset {
+        self.offset_with(distance, tolerance, &Tolerance::DEFAULT)
+    }
+
+    /// Offsets the curve like [`Self::offset`], to the tolerances of a model
+    /// rather than the default ones. The curve is taken to stop where its speed
+    /// is within the linear tolerance of zero.
+    pub fn offset_with(&self, distance: f64, tolerance: f64, model: &Tolerance) -> CurveOffset {
+        let exact = ExactCurveOffset {
+            curve: self,
+            distance,
+            tolerance: model,
+        };
+        let knots = self.distinct_knots();
+
+        let mut samples = vec![exact.sample(knots[0])];
+        let mut error: f64 = 0.0;
+        for span in knots.windows(2) {
+            let start = samples[samples.len() - 1];
+            let end = exact.sample(span[1]);
+            error = error.max(fit_curve_span(
+                &exact,
+                tolerance,
+                start,
+                end,
+                0,
+                &mut samples,
+            ));
+        }
+
+        CurveOffset {
+            curve: hermite_curve(&samples),
+            cusps: find_cusps(&exact, &knots),
+            self_intersections: find_self_intersections(&samples, model),
+            error,
+            converged: error <= tolerance,
+        }
+    }
+}
+
+impl NurbsSurface<HSpace3> {
+    /// Offsets the surface by `distance` along its normal (see [`NurbsSurface::normal`]).
+    /// The result is approximated by a piecewise bicubic surface that deviates from
+    /// the exact offset by no more than `tolerance`, unless refinement gives up
+    /// first (see [`SurfaceOffset::converged`]).
+    pub fn offset(&self, distance: f64, tolerance: f64) -> SurfaceOffset {
+        self.offset_with(distance, tolerance, &Tolerance::DEFAULT)
+    }
+
+    /// Offsets the surface like [`Self::offset`], to the tolerances of a model
+    /// rather than the default ones. The surface is taken to have no normal where
+    /// the cross product of its partial derivatives is within the linear tolerance
+    /// of zero.
+    pub fn offset_with(&self, distance: f64, tolerance: f64, model: &Tolerance) -> SurfaceOffset {
+        let exact = ExactSurfaceOffset {
+            surface: self,
+            distance,
+            tolerance: model,
+        };
+        let mut us = self.distinct_knots_u();
+        let mut vs = self.distinct_knots_v();
+
+        let mut grid = SurfaceGrid::new(&exact, &us, &vs);
+        let mut refinements = 0;
+        let error = loop {
+            let errors = grid.cell_errors(&exact, &us, &vs);
+            let error = errors.iter().flatten().fold(0.0, |a: f64, b| a.max(*b));
+            if error <= tolerance
+                || refinements == MAX_SURFACE_REFINEMENTS
+                || us.len() * 2 > MAX_SURFACE_INTERVALS
+                || vs.len() * 2 > MAX_SURFACE_INTERVALS
+            {
+                break error;
+            }
+
+            let split_u = errors
+                .iter()
+                .map(|row| row.iter().any(|e| *e > tolerance))
+                .collect::<Vec<_>>();
+            let split_v = (0..vs.len() - 1)
+                .map(|j| errors.iter().any(|row| row[j] > tolerance))
+                .collect::<Vec<_>>();
+
+            us = refine_params(&us, &split_u);
+            vs = refine_params(&vs, &split_v);
+            grid = SurfaceGrid::new(&exact, &us, &vs);
+            refinements += 1;
+        };
+
+        let mut folds = Vec::new();
+        for (i, u) in us.iter().enumerate() {
+            for (j, v) in vs.iter().enumerate() {
+                let mid_u = us.get(i + 1).map(|next| (u + next) / 2.0);
+                let mid_v = vs.get(j + 1).map(|next| (v + next) / 2.0);
+
+                let params = [
+                    Some((*u, *v)),
+                    mid_u.map(|mu| (mu, *v)),
+                    mid_v.map(|mv| (*u, mv)),
+                    mid_u.zip(mid_v),
+                ];
+
+                for (u, v) in params.into_iter().flatten() {
+                    if is_folded(self, distance, u, v) {
+                        folds.push(EVec2::new(u, v));
+                    }
+                }
+            }
+        }
+
+        SurfaceOffset {
+            surface: grid.surface(&us, &vs),
+            singularities: grid.singularities(&us, &vs),
+            folds,
+            error,
+            converged: error <= tolerance,
+        }
+    }
+}
+
+impl BezierSurface<HSpace3> {
+    /// Offsets the surface by `distance` along its normal. See [`NurbsSurface::offset`].
+    pub fn offset(&self, distance: f64, tolerance: f64) -> SurfaceOffset {
+        NurbsSurface::from_bezier(self).offset(distance, tolerance)
+    }
+
+    /// Offsets the surface to the tolerances of a model. See
+    /// [`NurbsSurface::offset_with`].
+    pub fn offset_with(&self, distance: f64, tolerance: f64, model: &Tolerance) -> SurfaceOffset {
+        NurbsSurface::from_bezier(self).offset_with(distance, tolerance, model)
+    }
+}
+
+/// A point on an offset curve along with its derivative
+#[derive(Debug, Clone, Copy)]
+struct CurveSample {
+    u: f64,
+    point: EVec2,
+    der: EVec2,
+}
+
+/// The exact offset of a curve, which fitted offsets are measured against
+#[derive(Clone, Copy)]
+struct ExactCurveOffset<'a> {
+    curve: &'a NurbsCurve<HSpace2>,
+    distance: f64,
+    tolerance: &'a Tolerance,
+}
+impl ExactCurveOffset<'_> {
+    /// Samples the offset at `u`. Where the base curve stops and has no tangent,
+    /// the normal and derivative are taken slightly further along instead.
+    fn sample(&self, u: f64) -> CurveSample {
+        let point = self.curve.point(u);
+        if let Some((normal, der)) = self.derivative(u) {
+            return CurveSample {
+                u,
+                point: point + normal * self.distance,
+                der,
+            };
+        }
+
+        let (min_u, max_u) = (self.curve.min_u(), self.curve.max_u());
+        let step = if u < (min_u + max_u) / 2.0 {
+            max_u - min_u
+        } else {
+            min_u - max_u
+        };
+        for nudge in [0.000001, 0.0001, 0.01] {
+            if let Some((normal, der)) = self.derivative(u + step * nudge) {
+                return CurveSample {
+                    u,
+                    point: point + normal * self.distance,
+                    der,
+                };
+            }
+        }
+
+        CurveSample {
+            u,
+            point,
+            der: EVec2::zero(),
+        }
+    }
+
+    /// Evaluates the unit normal of the curve and the derivative of its offset, or
+    /// `None` where the curve has no tangent
+    fn derivative(&self, u: f64) -> Option<(EVec2, EVec2)> {
+        let ders = self.curve.derivatives(u, 2);
+        let speed = ders[1].magnitude();
+        if speed <= self.tolerance.linear {
+            return None;
+        }
+        let tangent = ders[1] / speed;
+
+        // Derivative of the unit tangent and of the unit normal
+        let tangent_der = (ders[2] - tangent * ders[2].dot(&tangent)) / speed;
+        let normal = EVec2::new(tangent.y, -tangent.x);
+        let normal_der = EVec2::new(tangent_der.y, -tangent_der.x);
+
+        Some((normal, ders[1] + normal_der * self.distance))
+    }
+}
+
+/// Returns the cubic Bezier control points interpolating the positions and
+/// derivatives of two offset samples
+fn hermite_segment(start: &CurveSample, end: &CurveSample) -> [EVec2; 4] {
+    let h = (end.u - start.u) / 3.0;
+    [
+        start.point,
+        start.point + start.der * h,
+        end.point - end.der * h,
+        end.point,
+    ]
+}
+
+/// Fits the offset between two samples, bisecting until it is within `tolerance`,
+/// and returns the largest error of the segments kept
+fn fit_curve_span(
+    exact: &ExactCurveOffset,
+    tolerance: f64,
+    start: CurveSample,
+    end: CurveSample,
+    depth: usize,
+    samples: &mut Vec<CurveSample>,
+) -> f64 {
+    let segment = hermite_segment(&start, &end);
+    let error = [0.25, 0.5, 0.75]
+        .iter()
+        .map(|t| {
+            let sample = exact.sample(start.u + (end.u - start.u) * t);
+            (decasteljau(&segment, *t) - sample.point).magnitude()
+        })
+        .fold(0.0, f64::max);
+
+    if error > tolerance && depth < MAX_CURVE_SUBDIVISIONS {
+        let mid = exact.sample((start.u + end.u) / 2.0);
+        let first = fit_curve_span(exact, tolerance, start, mid, depth + 1, samples);
+        let second = fit_curve_span(exact, tolerance, mid, end, depth + 1, samples);
+        first.max(second)
+    } else {
+        samples.push(end);
+        error
+    }
+}
+
+/// Joins the Hermite segments between consecutive samples into a single cubic curve
+fn hermite_curve(samples: &[CurveSample]) -> NurbsCurve<HSpace2> {
+    let mut control_points = vec![HVec2::new(samples[0].point.x, samples[0].point.y, 1.0)];
+    let mut knots = vec![samples[0].u; 4];
+
+    for pair in samples.windows(2) {
+        let segment = hermite_segment(&pair[0], &pair[1]);
+        control_points.extend(segment[1..].iter().map(|p| HVec2::new(p.x, p.y, 1.0)));
+        knots.extend([pair[1].u; 3]);
+    }
+    knots.push(samples[samples.len() - 1].u);
+
+    NurbsCurve::new(control_points, KnotVector::from_vec(knots))
+}
+
+/// Finds the parameters where the offset curve reverses direction relative to the
+/// base curve, which happens where the offset distance equals the radius of curvature.
+fn find_cusps(exact: &ExactCurveOffset, knots: &[f64]) -> Vec<f64> {
+    let alignment = |u: f64| exact.sample(u).der.dot(&exact.curve.derivative(u, 1));
+
+    let mut cusps: Vec<f64> = Vec::new();
+    for span in knots.windows(2) {
+        let step = (span[1] - span[0]) / CUSP_SAMPLES as f64;
+        let mut prev_u = span[0];
+        let mut prev = alignment(prev_u);
+
+        for i in 1..=CUSP_SAMPLES {
+            let u = span[0] + step * i as f64;
+            let value = alignment(u);
+
+            if prev.signum() != value.signum() {
+                // Bisect to find the sign change
+                let (mut low, mut high, mut low_value) = (prev_u, u, prev);
+                for _ in 0..60 {
+                    let mid = (low + high) / 2.0;
+                    let mid_value = alignment(mid);
+                    if mid_value.signum() == low_value.signum() {
+                        low = mid;
+                        low_value = mid_value;
+                    } else {
+                        high = mid;
+                    }
+                }
+
+                let cusp = (low + high) / 2.0;
+                match cusps.last() {
+                    Some(last) if (cusp - last).abs() <= exact.tolerance.linear => {}
+                    _ => cusps.push(cusp),
+                }
+            }
+
+            prev_u = u;
+            prev = value;
+        }
+    }
+
+    cusps
+}
+
+/// Finds the places where the polyline through the fitted offset crosses itself
+fn find_self_intersections(
+    samples: &[CurveSample],
+    tolerance: &Tolerance,
+) -> Vec<OffsetIntersection> {
+    let mut polyline = vec![(samples[0].u, samples[0].point)];
+    for pair in samples.windows(2) {
+        let segment = hermite_segment(&pair[0], &pair[1]);
+        for i in 1..=INTERSECTION_SAMPLES {
+            let t = i as f64 / INTERSECTION_SAMPLES as f64;
+            polyline.push((
+                pair[0].u + (pair[1].u - pair[0].u) * t,
+                decasteljau(&segment, t),
+            ));
+        }
+    }
+
+    let closed = (polyline[0].1 - polyline[polyline.len() - 1].1).magnitude() <= tolerance.linear;
+    let num_lines = polyline.len() - 1;
+
+    let mut intersections: Vec<OffsetIntersection> = Vec::new();
+    for i in 0..num_lines {
+        for j in (i + 2)..num_lines {
+            if closed && i == 0 && j == num_lines - 1 {
+                continue;
+            }
+
+            let (u1, a1) = polyline[i];
+            let (u2, a2) = polyline[i + 1];
+            let (u3, b1) = polyline[j];
+            let (u4, b2) = polyline[j + 1];
+
+            if let Some((s, t)) = segment_intersection([a1, a2], [b1, b2], tolerance) {
+                let point = a1 + (a2 - a1) * s;
+                let duplicate = intersections
+                    .iter()
+                    .any(|other| (other.point - point).magnitude() <= tolerance.linear);
+
+                if !duplicate {
+                    intersections.push(OffsetIntersection {
+                        u1: u1 + (u2 - u1) * s,
+                        u2: u3 + (u4 - u3) * t,
+                        point,
+                    });
+                }
+            }
+        }
+    }
+
+    intersections
+}
+
+/// Returns the parameters along each segment where two line segments cross.
+/// Segments closer to parallel than the linear tolerance don't cross.
+fn segment_intersection(
+    [a1, a2]: [EVec2; 2],
+    [b1, b2]: [EVec2; 2],
+    tolerance: &Tolerance,
+) -> Option<(f64, f64)> {
+    let da = a2 - a1;
+    let db = b2 - b1;
+    let denom = da.x * db.y - da.y * db.x;
+
+    if denom.abs() <= tolerance.linear * tolerance.linear {
+        return None;
+    }
+
+    let diff = b1 - a1;
+    let s = (diff.x * db.y - diff.y * db.x) / denom;
+    let t = (diff.x * da.y - diff.y * da.x) / denom;
+
+    if s > 0.0 && s <= 1.0 && t > 0.0 && t <= 1.0 {
+        Some((s, t))
+    } else {
+        None
+    }
+}
+
+/// A point on an offset surface along with its partial derivatives
+#[derive(Debug, Clone, Copy)]
+struct SurfaceSample {
+    point: EVec3,
+    der_u: EVec3,
+    der_v: EVec3,
+    twist: EVec3,
+    singular: bool,
+}
+
+/// The exact offset of a surface, which fitted offsets are measured against
+#[derive(Clone, Copy)]
+struct ExactSurfaceOffset<'a> {
+    surface: &'a NurbsSurface<HSpace3>,
+    distance: f64,
+    tolerance: &'a Tolerance,
+}
+impl ExactSurfaceOffset<'_> {
+    fn sample(&self, u: f64, v: f64) -> SurfaceSample {
+        let surface = self.surface;
+        let (point, der_u, der_v, singular) = self.derivatives(u, v);
+
+        // Estimate the twist vector by differencing the u-derivative across v
+        let step = (surface.max_v() - surface.min_v()) * 0.00001;
+        let v_low = f64::max(v - step, surface.min_v());
+        let v_high = f64::min(v + step, surface.max_v());
+        let (_, der_u_low, _, _) = self.derivatives(u, v_low);
+        let (_, der_u_high, _, _) = self.derivatives(u, v_high);
+
+        SurfaceSample {
+            point,
+            der_u,
+            der_v,
+            twist: (der_u_high - der_u_low) / (v_high - v_low),
+            singular,
+        }
+    }
+
+    /// Evaluates the offset point and its partial derivatives. Where the base
+    /// surface has no normal, the derivatives are taken slightly inside the domain
+    /// instead.
+    fn derivatives(&self, u: f64, v: f64) -> (EVec3, EVec3, EVec3, bool) {
+        let surface = self.surface;
+        if let Some((point, der_u, der_v)) = self.exact_derivatives(u, v) {
+            return (point, der_u, der_v, false);
+        }
+
+        let mid_u = (surface.min_u() + surface.max_u()) / 2.0;
+        let mid_v = (surface.min_v() + surface.max_v()) / 2.0;
+        for nudge in [0.000001, 0.0001, 0.01] {
+            let nudged_u = u + (mid_u - u) * nudge;
+            let nudged_v = v + (mid_v - v) * nudge;
+            if let Some((_, der_u, der_v)) = self.exact_derivatives(nudged_u, nudged_v) {
+                let normal = surface
+                    .normal(nudged_u, nudged_v)
+                    .unwrap_or_else(EVec3::zero);
+                return (
+                    surface.point(u, v) + normal * self.distance,
+                    der_u,
+                    der_v,
+                    true,
+                );
+            }
+        }
+
+        (surface.point(u, v), EVec3::zero(), EVec3::zero(), true)
+    }
+
+    fn exact_derivatives(&self, u: f64, v: f64) -> Option<(EVec3, EVec3, EVec3)> {
+        let ders = self.surface.derivatives(u, v, 2);
+        let (point, su, sv) = (ders[0][0], ders[1][0], ders[0][1]);
+        let (suu, suv, svv) = (ders[2][0], ders[1][1], ders[0][2]);
+
+        let raw_normal = su.cross(&sv);
+        let length = raw_normal.magnitude();
+        if length <= self.tolerance.linear {
+            return None;
+        }
+
+        let normal = raw_normal / length;
+
+        // Derivatives of the unit normal
+        let raw_normal_u = suu.cross(&sv) + su.cross(&suv);
+        let raw_normal_v = suv.cross(&sv) + su.cross(&svv);
+        let normal_u = (raw_normal_u - normal * normal.dot(&raw_normal_u)) / length;
+        let normal_v = (raw_normal_v - normal * normal.dot(&raw_normal_v)) / length;
+
+        Some((
+            point + normal * self.distance,
+            su + normal_u * self.distance,
+            sv + normal_v * self.distance,
+        ))
+    }
+}
+
+/// Whether the offset distance reaches one of the principal radii of curvature
+fn is_folded(surface: &NurbsSurface<HSpace3>, distance: f64, u: f64, v: f64) -> bool {
+    let ders = surface.derivatives(u, v, 2);
+    let (su, sv) = (ders[1][0], ders[0][1]);
+    let normal = match surface.normal(u, v) {
+        Some(normal) => normal,
+        None => return false,
+    };
+
+    // First and second fundamental forms
+    let (e, f, g) = (su.dot(&su), su.dot(&sv), sv.dot(&sv));
+    let (l, m, n) = (
+        ders[2][0].dot(&normal),
+        ders[1][1].dot(&normal),
+        ders[0][2].dot(&normal),
+    );
+
+    let det = e * g - f * f;
+    let gaussian = (l * n - m * m) / det;
+    let mean = (e * n - 2.0 * f * m + g * l) / (2.0 * det);
+    let disc = f64::max(mean * mean - gaussian, 0.0).sqrt();
+
+    [mean + disc, mean - disc]
+        .iter()
+        .any(|k| 1.0 - distance * k <= 0.0)
+}
+
+/// Inserts a midpoint into each interval of `params` that is flagged in `split`
+fn refine_params(params: &[f64], split: &[bool]) -> Vec<f64> {
+    let mut refined = vec![params[0]];
+    for (i, pair) in params.windows(2).enumerate() {
+        if split[i] {
+            refined.push((pair[0] + pair[1]) / 2.0);
+        }
+        refined.push(pair[1]);
+    }
+    refined
+}
+
+/// Offset samples at every combination of a set of `u` and `v` parameters
+struct SurfaceGrid {
+    samples: Vec<Vec<SurfaceSample>>,
+}
+impl SurfaceGrid {
+    fn new(exact: &ExactSurfaceOffset, us: &[f64], vs: &[f64]) -> Self {
+        Self {
+            samples: us
+                .iter()
+                .map(|u| vs.iter().map(|v| exact.sample(*u, *v)).collect())
+                .collect(),
+        }
+    }
+
+    /// Returns the bicubic Bezier control net interpolating the samples at the
+    /// corners of cell `(i, j)`, indexed as `[u][v]`
+    fn cell_patch(&self, us: &[f64], vs: &[f64], i: usize, j: usize) -> [[EVec3; 4]; 4] {
+        let hu = (us[i + 1] - us[i]) / 3.0;
+        let hv = (vs[j + 1] - vs[j]) / 3.0;
+
+        let mut net = [[EVec3::zero(); 4]; 4];
+        for (corner_u, sign_u) in [(0, 1.0), (3, -1.0)] {
+            for (corner_v, sign_v) in [(0, 1.0), (3, -1.0)] {
+                let sample = &self.samples[i + corner_u / 3][j + corner_v / 3];
+                let inner_u = (corner_u as f64 + sign_u) as usize;
+                let inner_v = (corner_v as f64 + sign_v) as usize;
+
+                let step_u = sample.der_u * (hu * sign_u);
+                let step_v = sample.der_v * (hv * sign_v);
+
+                net[corner_u][corner_v] = sample.point;
+                net[inner_u][corner_v] = sample.point + step_u;
+                net[corner_u][inner_v] = sample.point + step_v;
+                net[inner_u][inner_v] =
+                    sample.point + step_u + step_v + sample.twist * (hu * hv * sign_u * sign_v);
+            }
+        }
+
+        net
+    }
+
+    /// The error of every cell, indexed as `[i][j]`
+    fn cell_errors(&self, exact: &ExactSurfaceOffset, us: &[f64], vs: &[f64]) -> Vec<Vec<f64>> {
+        (0..us.len() - 1)
+            .map(|i| {
+                (0..vs.len() - 1)
+                    .map(|j| self.cell_error(exact, us, vs, i, j))
+                    .collect()
+            })
+            .collect()
+    }
+
+    fn cell_error(
+        &self,
+        exact: &ExactSurfaceOffset,
+        us: &[f64],
+        vs: &[f64],
+        i: usize,
+        j: usize,
+    ) -> f64 {
+        let net = self.cell_patch(us, vs, i, j);
+
+        let mut error: f64 = 0.0;
+        for s in [0.25, 0.5, 0.75] {
+            for t in [0.25, 0.5, 0.75] {
+                let u = us[i] + (us[i + 1] - us[i]) * s;
+                let v = vs[j] + (vs[j + 1] - vs[j]) * t;
+                let (point, _, _, _) = exact.derivatives(u, v);
+
+                let columns = net
+                    .iter()
+                    .map(|column| decasteljau(column, t))
+                    .collect::<Vec<_>>();
+                let approx = decasteljau(&columns, s);
+
+                error = error.max((approx - point).magnitude());
+            }
+        }
+
+        error
+    }
+
+    fn surface(&self, us: &[f64], vs: &[f64]) -> NurbsSurface<HSpace3> {
+        let mut control_points =
+            vec![vec![HVec3::new(0.0, 0.0, 0.0, 1.0); 3 * vs.len() - 2]; 3 * us.len() - 2];
+
+        for i in 0..us.len() - 1 {
+            for j in 0..vs.len() - 1 {
+                let net = self.cell_patch(us, vs, i, j);
+                for (a, column) in net.iter().enumerate() {
+                    for (b, p) in column.iter().enumerate() {
+                        control_points[3 * i + a][3 * j + b] = HVec3::new(p.x, p.y, p.z, 1.0);
+                    }
+                }
+            }
+        }
+
+        NurbsSurface::new(
+            control_points,
+            Self::cubic_knot_vector(us),
+            Self::cubic_knot_vector(vs),
+        )
+    }
+
+    fn cubic_knot_vector(params: &[f64]) -> KnotVector {
+        let mut knots = vec![params[0]; 4];
+        for param in params[1..params.len() - 1].iter() {
+            knots.extend([*param; 3]);
+        }
+        knots.extend([params[params.len() - 1]; 4]);
+        KnotVector::from_vec(knots)
+    }
+
+    fn singularities(&self, us: &[f64], vs: &[f64]) -> Vec<EVec2> {
+        let mut singularities = Vec::new();
+        for (i, row) in self.samples.iter().enumerate() {
+            for (j, sample) in row.iter().enumerate() {
+                if sample.singular {
+                    singularities.push(EVec2::new(us[i], vs[j]));
+                }
+            }
+        }
+        singularities
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use space::{
+        hspace::{HSpace2, HSpace3},
+        EVec2, EVector, HVec2, HVec3,
+    };
+
+    use crate::{
+        math::{knot_vector::KnotVector, FloatRange},
+        nurbs_curve::NurbsCurve,
+        nurbs_surface::NurbsSurface,
+    };
+
+    #[test]
+    fn circle_offset_is_concentric() {
+        let circle = NurbsCurve::<HSpace2>::example_circle();
+        let offset = circle.offset(0.5, 0.00001);
+
+        assert!(offset.is_regular());
+        for u in FloatRange::new(0.0, 1.0, 100) {
+            let radius = offset.curve.point(u).magnitude();
+            assert!(
+                (radius - 1.5).abs() <= 0.00001,
+                "radius {radius} at u = {u}"
+            );
+        }
+    }
+
+    #[test]
+    fn parabola_offset_past_curvature_has_cusps() {
+        // y = x^2 for x in [-1, 1], whose minimum radius of curvature is 0.5
+        let parabola = NurbsCurve::<HSpace2>::new(
+            vec![
+                HVec2::new(-1.0, 1.0, 1.0),
+                HVec2::new(0.0, -1.0, 1.0),
+                HVec2::new(1.0, 1.0, 1.0),
+            ],
+            KnotVector::new([0.0, 0.0, 0.0, 1.0, 1.0, 1.0]),
+        );
+
+        let offset = parabola.offset(-1.0, 0.0001);
+
+        assert_eq!(offset.cusps.len(), 2);
+        assert_eq!(offset.self_intersections.len(), 1);
+        assert!(offset.self_intersections[0].point.x.abs() <= 0.001);
+    }
+
+    #[test]
+    fn offset_of_stopped_curve_uses_nearby_tangent() {
+        // Runs along the x axis but starts with zero speed
+        let line = NurbsCurve::<HSpace2>::new(
+            vec![
+                HVec2::new(0.0, 0.0, 1.0),
+                HVec2::new(0.0, 0.0, 1.0),
+                HVec2::new(1.0, 0.0, 1.0),
+            ],
+            KnotVector::new([0.0, 0.0, 0.0, 1.0, 1.0, 1.0]),
+        );
+
+        let offset = line.offset(0.5, 0.00001);
+        assert!(offset.converged);
+        for u in FloatRange::new(0.0, 1.0, 20) {
+            let p = offset.curve.point(u);
+            assert!((p.y + 0.5).abs() <= 0.00001, "{p:?} at u = {u}");
+        }
+    }
+
+    #[test]
+    fn offset_reports_unmet_tolerance() {
+        let circle = NurbsCurve::<HSpace2>::example_circle();
+        let offset = circle.offset(0.5, 0.001);
+        assert!(offset.converged);
+        assert!(offset.error <= 0.001);
+
+        let offset = quarter_cylinder().offset(0.25, 0.001);
+        assert!(offset.converged);
+        assert!(offset.error <= 0.001);
+
+        // (t^2, t^3) for t = u - 1/3, whose normal flips at its cusp, so no fit
+        // of the offset across the cusp can be within tolerance
+        let cusp = [
+            (1.0 / 9.0, -1.0 / 27.0),
+            (-1.0 / 9.0, 2.0 / 27.0),
+            (0.0, -4.0 / 27.0),
+            (4.0 / 9.0, 8.0 / 27.0),
+        ];
+        let curve = NurbsCurve::<HSpace2>::new(
+            cusp.iter().map(|(x, y)| HVec2::new(*x, *y, 1.0)).collect(),
+            KnotVector::new([0.0, 0.0, 0.0, 0.0, 1.0, 1.0, 1.0, 1.0]),
+        );
+        let offset = curve.offset(0.1, 0.001);
+        assert!(!offset.converged);
+        assert!(offset.error > 0.001);
+
+        let surface = NurbsSurface::<HSpace3>::new(
+            cusp.iter()
+                .map(|(x, y)| vec![HVec3::new(*x, *y, 0.0, 1.0), HVec3::new(*x, *y, 1.0, 1.0)])
+                .collect(),
+            KnotVector::new([0.0, 0.0, 0.0, 0.0, 1.0, 1.0, 1.0, 1.0]),
+            KnotVector::new([0.0, 0.0, 1.0, 1.0]),
+        );
+        let offset = surface.offset(0.1, 0.001);
+        assert!(!offset.converged);
+        assert!(offset.error > 0.001);
+    }
+
+    fn quarter_cylinder() -> NurbsSurface<HSpace3> {
+        let w = 2.0_f64.sqrt() / 2.0;
+        NurbsSurface::<HSpace3>::new(
+            vec![
+                vec![
+                    HVec3::new(1.0, 0.0, 0.0, 1.0),
+                    HVec3::new(1.0, 0.0, 1.0, 1.0),
+                ],
+                vec![HVec3::new(1.0, 1.0, 0.0, w), HVec3::new(1.0, 1.0, 1.0, w)],
+                vec![
+                    HVec3::new(0.0, 1.0, 0.0, 1.0),
+                    HVec3::new(0.0, 1.0, 1.0, 1.0),
+                ],
+            ],
+            KnotVector::new([0.0, 0.0, 0.0, 1.0, 1.0, 1.0]),
+            KnotVector::new([0.0, 0.0, 1.0, 1.0]),
+        )
+    }
+
+    #[test]
+    fn cylinder_offset_changes_radius() {
+        let cylinder = quarter_cylinder();
+
+        let offset = cylinder.offset(0.25, 0.00001);
+        assert!(offset.is_regular());
+
+        // The normal of this parameterization points away from the axis
+        for u in FloatRange::new(0.0, 1.0, 10) {
+            for v in FloatRange::new(0.0, 1.0, 10) {
+                let p = offset.surface.point(u, v);
+                let radius = EVec2::new(p.x, p.y).magnitude();
+                assert!((radius - 1.25).abs() <= 0.00001, "radius {radius}");
+            }
+        }
+
+        assert!(!cylinder.offset(-1.5, 0.001).folds.is_empty());
+    }
+}
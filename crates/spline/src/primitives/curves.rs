@@ -0,0 +1,504 @@
+use std::f64::consts::{FRAC_PI_2, PI};
+
+use space::{
+    hspace::{HSpace2, HSpace3},
+    EPlacement2, EPlacement3, EVec2, EVec3, EVector, HVec2, HVec3, TOL,
+};
+
+use crate::{math::knot_vector::KnotVector, nurbs_curve::NurbsCurve};
+
+/// Largest parameter span of a single rational segment of a hyperbola. Longer
+/// spans are split so the middle control points stay near the curve.
+const MAX_HYPERBOLA_SEGMENT_SPAN: f64 = 1.0;
+
+impl NurbsCurve<HSpace2> {
+    /// Creates a straight line segment from `start` to `end`
+    pub fn line(start: EVec2, end: EVec2) -> Self {
+        Self::polyline(&[start, end])
+    }
+
+    /// Creates a degree 1 curve through the given points, parameterized by chord length
+    pub fn polyline(points: &[EVec2]) -> Self {
+        let knot_vector = polyline_knots(
+            &points
+                .windows(2)
+                .map(|w| (w[1] - w[0]).magnitude())
+                .collect::<Vec<_>>(),
+        );
+
+        Self::new(
+            points.iter().map(|p| HVec2::new(p.x, p.y, 1.0)).collect(),
+            knot_vector,
+        )
+    }
+
+    /// Creates a circular arc around `center`. Angles are in radians, measured
+    /// counterclockwise from the X axis. The arc runs clockwise if `end_angle` is
+    /// less than `start_angle`.
+    pub fn arc(center: EVec2, radius: f64, start_angle: f64, end_angle: f64) -> Self {
+        Self::elliptical_arc(
+            &EPlacement2::from_origin(center),
+            radius,
+            radius,
+            start_angle,
+            end_angle,
+        )
+    }
+
+    /// Creates the circular arc that starts at `start`, passes through `mid` and
+    /// ends at `end`. Returns `None` if the points are collinear.
+    pub fn arc_through_points(start: EVec2, mid: EVec2, end: EVec2) -> Option<Self> {
+        let orientation = (mid - start).x * (end - start).y - (mid - start).y * (end - start).x;
+        if orientation.abs() <= TOL {
+            return None;
+        }
+
+        let center = circumcenter(start, mid, end);
+        let radius = (start - center).magnitude();
+        let start_angle = (start.y - center.y).atan2(start.x - center.x);
+        let end_angle = (end.y - center.y).atan2(end.x - center.x);
+
+        let sweep = if orientation > 0.0 {
+            (end_angle - start_angle).rem_euclid(2.0 * PI)
+        } else {
+            -(start_angle - end_angle).rem_euclid(2.0 * PI)
+        };
+
+        Some(Self::arc(center, radius, start_angle, start_angle + sweep))
+    }
+
+    /// Creates a full circle around `center`, starting on the positive X axis and
+    /// running counterclockwise
+    pub fn circle(center: EVec2, radius: f64) -> Self {
+        Self::arc(center, radius, 0.0, 2.0 * PI)
+    }
+
+    /// Creates an arc of an ellipse centered on the placement's origin, with its
+    /// major axis along the placement's X axis. Angles are the parametric angles
+    /// of the ellipse.
+    pub fn elliptical_arc(
+        placement: &EPlacement2,
+        major_radius: f64,
+        minor_radius: f64,
+        start_angle: f64,
+        end_angle: f64,
+    ) -> Self {
+        let (points, knot_vector) =
+            elliptical_arc_local(major_radius, minor_radius, start_angle, end_angle);
+        Self::new(place_points_2(&points, placement), knot_vector)
+    }
+
+    /// Creates a full ellipse centered on the placement's origin, with its major
+    /// axis along the placement's X axis
+    pub fn ellipse(placement: &EPlacement2, major_radius: f64, minor_radius: f64) -> Self {
+        Self::elliptical_arc(placement, major_radius, minor_radius, 0.0, 2.0 * PI)
+    }
+
+    /// Creates a segment of the parabola `y = x^2 / (4 * focal_length)` in the
+    /// placement's coordinates, between the local X values `start` and `end`. The
+    /// vertex is at the placement's origin and the focus is on its Y axis.
+    pub fn parabola(placement: &EPlacement2, focal_length: f64, start: f64, end: f64) -> Self {
+        let (points, knot_vector) = parabola_local(focal_length, start, end);
+        Self::new(place_points_2(&points, placement), knot_vector)
+    }
+
+    /// Creates a segment of the hyperbola branch `(a * cosh(t), b * sinh(t))` in
+    /// the placement's coordinates, between the parameters `start` and `end`. The
+    /// center is at the placement's origin and the vertex lies on its X axis.
+    pub fn hyperbola(
+        placement: &EPlacement2,
+        semi_major: f64,
+        semi_minor: f64,
+        start: f64,
+        end: f64,
+    ) -> Self {
+        let (points, knot_vector) = hyperbola_local(semi_major, semi_minor, start, end);
+        Self::new(place_points_2(&points, placement), knot_vector)
+    }
+}
+impl NurbsCurve<HSpace3> {
+    /// Maps a planar curve onto the XY plane of a placement. Since the mapping is
+    /// affine, rational curves keep their exact shape.
+    pub fn from_planar(curve: &NurbsCurve<HSpace2>, placement: &EPlacement3) -> Self {
+        Self::new(
+            curve
+                .control_points()
+                .iter()
+                .map(|p| {
+                    let placed = placement.planar_to_global(EVec2::new(p.x, p.y));
+                    HVec3::new(placed.x, placed.y, placed.z, p.h)
+                })
+                .collect(),
+            curve.knot_vector().clone(),
+        )
+    }
+
+    /// Creates a straight line segment from `start` to `end`
+    pub fn line(start: EVec3, end: EVec3) -> Self {
+        Self::polyline(&[start, end])
+    }
+
+    /// Creates a degree 1 curve through the given points, parameterized by chord length
+    pub fn polyline(points: &[EVec3]) -> Self {
+        let knot_vector = polyline_knots(
+            &points
+                .windows(2)
+                .map(|w| (w[1] - w[0]).magnitude())
+                .collect::<Vec<_>>(),
+        );
+
+        Self::new(
+            points
+                .iter()
+                .map(|p| HVec3::new(p.x, p.y, p.z, 1.0))
+                .collect(),
+            knot_vector,
+        )
+    }
+
+    /// Creates a circular arc in the placement's XY plane, centered on its origin.
+    /// Angles are measured counterclockwise about the placement's Z axis.
+    pub fn arc(placement: &EPlacement3, radius: f64, start_angle: f64, end_angle: f64) -> Self {
+        Self::from_planar(
+            &NurbsCurve::<HSpace2>::arc(EVec2::zero(), radius, start_angle, end_angle),
+            placement,
+        )
+    }
+
+    /// Creates the circular arc that starts at `start`, passes through `mid` and
+    /// ends at `end`. Returns `None` if the points are collinear.
+    pub fn arc_through_points(start: EVec3, mid: EVec3, end: EVec3) -> Option<Self> {
+        if (mid - start).cross(&(end - start)).magnitude() <= TOL {
+            return None;
+        }
+
+        // Solve in the plane of the points, oriented so the arc is counterclockwise
+        let placement = EPlacement3::new(start, mid - start, end - start);
+        let to_planar = |p: EVec3| {
+            let local = placement.to_local(p);
+            EVec2::new(local.x, local.y)
+        };
+
+        NurbsCurve::<HSpace2>::arc_through_points(to_planar(start), to_planar(mid), to_planar(end))
+            .map(|arc| Self::from_planar(&arc, &placement))
+    }
+
+    /// Creates a full circle in the placement's XY plane, centered on its origin
+    pub fn circle(placement: &EPlacement3, radius: f64) -> Self {
+        Self::arc(placement, radius, 0.0, 2.0 * PI)
+    }
+
+    /// Creates an arc of an ellipse in the placement's XY plane, centered on its
+    /// origin, with its major axis along the placement's X axis
+    pub fn elliptical_arc(
+        placement: &EPlacement3,
+        major_radius: f64,
+        minor_radius: f64,
+        start_angle: f64,
+        end_angle: f64,
+    ) -> Self {
+        Self::from_planar(
+            &NurbsCurve::<HSpace2>::elliptical_arc(
+                &EPlacement2::default(),
+                major_radius,
+                minor_radius,
+                start_angle,
+                end_angle,
+            ),
+            placement,
+        )
+    }
+
+    /// Creates a full ellipse in the placement's XY plane, centered on its origin,
+    /// with its major axis along the placement's X axis
+    pub fn ellipse(placement: &EPlacement3, major_radius: f64, minor_radius: f64) -> Self {
+        Self::elliptical_arc(placement, major_radius, minor_radius, 0.0, 2.0 * PI)
+    }
+
+    /// Creates a segment of a parabola in the placement's XY plane. See
+    /// `NurbsCurve::<HSpace2>::parabola`.
+    pub fn parabola(placement: &EPlacement3, focal_length: f64, start: f64, end: f64) -> Self {
+        Self::from_planar(
+            &NurbsCurve::<HSpace2>::parabola(&EPlacement2::default(), focal_length, start, end),
+            placement,
+        )
+    }
+
+    /// Creates a segment of a hyperbola branch in the placement's XY plane. See
+    /// `NurbsCurve::<HSpace2>::hyperbola`.
+    pub fn hyperbola(
+        placement: &EPlacement3,
+        semi_major: f64,
+        semi_minor: f64,
+        start: f64,
+        end: f64,
+    ) -> Self {
+        Self::from_planar(
+            &NurbsCurve::<HSpace2>::hyperbola(
+                &EPlacement2::default(),
+                semi_major,
+                semi_minor,
+                start,
+                end,
+            ),
+            placement,
+        )
+    }
+}
+
+/// Builds a clamped degree 1 knot vector from the lengths of each polyline segment.
+/// Falls back to uniform spacing if the polyline has no length.
+fn polyline_knots(lengths: &[f64]) -> KnotVector {
+    assert!(!lengths.is_empty(), "A polyline needs at least two points");
+
+    let total = lengths.iter().sum::<f64>();
+    let mut knots = vec![0.0, 0.0];
+    let mut acc = 0.0;
+    for (i, length) in lengths.iter().enumerate().take(lengths.len() - 1) {
+        acc += length;
+        knots.push(if total > TOL {
+            acc / total
+        } else {
+            (i + 1) as f64 / lengths.len() as f64
+        });
+    }
+    knots.extend([1.0, 1.0]);
+
+    KnotVector::from_vec(knots)
+}
+
+/// Builds a clamped quadratic knot vector for `num_segments` rational Bezier
+/// segments joined with double knots
+fn quadratic_segment_knots(num_segments: usize) -> KnotVector {
+    let mut knots = vec![0.0; 3];
+    for i in 1..num_segments {
+        let knot = i as f64 / num_segments as f64;
+        knots.extend([knot, knot]);
+    }
+    knots.extend([1.0; 3]);
+
+    KnotVector::from_vec(knots)
+}
+
+/// Computes the control points of an elliptical arc centered on the origin. The
+/// arc is split into segments of at most a quarter turn, each of which is an exact
+/// rational quadratic.
+fn elliptical_arc_local(
+    rx: f64,
+    ry: f64,
+    start_angle: f64,
+    end_angle: f64,
+) -> (Vec<HVec2>, KnotVector) {
+    let sweep = end_angle - start_angle;
+    assert!(
+        sweep.abs() > TOL && sweep.abs() <= 2.0 * PI + TOL,
+        "Arc sweep must be between 0 and 2π radians (got {sweep})"
+    );
+
+    let num_segments = ((sweep.abs() - TOL) / FRAC_PI_2).ceil().max(1.0) as usize;
+    let half_span = sweep / num_segments as f64 / 2.0;
+    let weight = half_span.cos();
+
+    let on_arc = |angle: f64| HVec2::new(rx * angle.cos(), ry * angle.sin(), 1.0);
+
+    let mut points = vec![on_arc(start_angle)];
+    for i in 0..num_segments {
+        let mid = start_angle + sweep * i as f64 / num_segments as f64 + half_span;
+        points.push(HVec2::new(
+            rx * mid.cos() / weight,
+            ry * mid.sin() / weight,
+            weight,
+        ));
+        points.push(on_arc(
+            start_angle + sweep * (i + 1) as f64 / num_segments as f64,
+        ));
+    }
+
+    // Close full ellipses exactly rather than relying on floating point trig
+    if (sweep.abs() - 2.0 * PI).abs() <= TOL {
+        points[2 * num_segments] = points[0];
+    }
+
+    (points, quadratic_segment_knots(num_segments))
+}
+
+/// Computes the control points of a segment of the parabola `y = x^2 / (4f)`
+fn parabola_local(focal_length: f64, start: f64, end: f64) -> (Vec<HVec2>, KnotVector) {
+    assert!(
+        focal_length.abs() > TOL,
+        "Parabola focal length must not be zero"
+    );
+    assert!(
+        (end - start).abs() > TOL,
+        "Parabola segment must not be empty"
+    );
+
+    let y = |x: f64| x * x / (4.0 * focal_length);
+
+    // The middle control point is where the end tangents intersect
+    let points = vec![
+        HVec2::new(start, y(start), 1.0),
+        HVec2::new((start + end) / 2.0, start * end / (4.0 * focal_length), 1.0),
+        HVec2::new(end, y(end), 1.0),
+    ];
+
+    (points, quadratic_segment_knots(1))
+}
+
+/// Computes the control points of a segment of the hyperbola branch
+/// `(a * cosh(t), b * sinh(t))`
+fn hyperbola_local(a: f64, b: f64, start: f64, end: f64) -> (Vec<HVec2>, KnotVector) {
+    let span = end - start;
+    assert!(span.abs() > TOL, "Hyperbola segment must not be empty");
+
+    let num_segments = (span.abs() / MAX_HYPERBOLA_SEGMENT_SPAN).ceil().max(1.0) as usize;
+    let half_span = span / num_segments as f64 / 2.0;
+    let weight = half_span.cosh();
+
+    let on_branch = |t: f64| HVec2::new(a * t.cosh(), b * t.sinh(), 1.0);
+
+    let mut points = vec![on_branch(start)];
+    for i in 0..num_segments {
+        let mid = start + span * i as f64 / num_segments as f64 + half_span;
+        points.push(HVec2::new(
+            a * mid.cosh() / weight,
+            b * mid.sinh() / weight,
+            weight,
+        ));
+        points.push(on_branch(
+            start + span * (i + 1) as f64 / num_segments as f64,
+        ));
+    }
+
+    (points, quadratic_segment_knots(num_segments))
+}
+
+fn place_points_2(points: &[HVec2], placement: &EPlacement2) -> Vec<HVec2> {
+    points
+        .iter()
+        .map(|p| {
+            let placed = placement.to_global(EVec2::new(p.x, p.y));
+            HVec2::new(placed.x, placed.y, p.h)
+        })
+        .collect()
+}
+
+fn circumcenter(a: EVec2, b: EVec2, c: EVec2) -> EVec2 {
+    let d = 2.0 * (a.x * (b.y - c.y) + b.x * (c.y - a.y) + c.x * (a.y - b.y));
+    let a2 = a.magnitude2();
+    let b2 = b.magnitude2();
+    let c2 = c.magnitude2();
+
+    EVec2::new(
+        (a2 * (b.y - c.y) + b2 * (c.y - a.y) + c2 * (a.y - b.y)) / d,
+        (a2 * (c.x - b.x) + b2 * (a.x - c.x) + c2 * (b.x - a.x)) / d,
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use std::f64::consts::PI;
+
+    use space::{
+        hspace::{HSpace2, HSpace3},
+        EPlacement2, EPlacement3, EVec2, EVec3, EVector, TOL,
+    };
+
+    use crate::{math::FloatRange, nurbs_curve::NurbsCurve};
+
+    #[test]
+    fn arc_points_lie_on_circle() {
+        let center = EVec2::new(1.0, -2.0);
+        let arc = NurbsCurve::<HSpace2>::arc(center, 3.0, 0.25, 4.0);
+
+        for u in FloatRange::new(0.0, 1.0, 50) {
+            assert!(((arc.point(u) - center).magnitude() - 3.0).abs() <= TOL);
+        }
+
+        let start = arc.point(0.0);
+        let end = arc.point(1.0);
+        assert!(
+            (start - (center + EVec2::new(0.25_f64.cos(), 0.25_f64.sin()) * 3.0)).magnitude()
+                <= TOL
+        );
+        assert!(
+            (end - (center + EVec2::new(4.0_f64.cos(), 4.0_f64.sin()) * 3.0)).magnitude() <= TOL
+        );
+    }
+
+    #[test]
+    fn arc_through_points_passes_through_middle() {
+        let start = EVec2::new(1.0, 0.0);
+        let mid = EVec2::new(0.0, -1.0);
+        let end = EVec2::new(-1.0, 0.0);
+        let arc = NurbsCurve::<HSpace2>::arc_through_points(start, mid, end).unwrap();
+
+        // The arc runs clockwise, so its midpoint is below the X axis
+        assert!((arc.point(0.5) - mid).magnitude() <= TOL);
+        assert!((arc.point(1.0) - end).magnitude() <= TOL);
+
+        assert!(NurbsCurve::<HSpace2>::arc_through_points(
+            start,
+            EVec2::new(2.0, 0.0),
+            EVec2::new(3.0, 0.0)
+        )
+        .is_none());
+    }
+
+    #[test]
+    fn conics_satisfy_implicit_equations() {
+        let placement = EPlacement2::new(EVec2::new(2.0, 1.0), EVec2::new(1.0, 1.0));
+
+        let ellipse = NurbsCurve::<HSpace2>::ellipse(&placement, 3.0, 1.5);
+        let parabola = NurbsCurve::<HSpace2>::parabola(&placement, 0.5, -2.0, 3.0);
+        let hyperbola = NurbsCurve::<HSpace2>::hyperbola(&placement, 2.0, 1.0, -2.5, 1.5);
+
+        for u in FloatRange::new(0.0, 1.0, 50) {
+            let p = placement.to_local(ellipse.point(u));
+            assert!(((p.x / 3.0).powi(2) + (p.y / 1.5).powi(2) - 1.0).abs() <= TOL);
+
+            let p = placement.to_local(parabola.point(u));
+            assert!((p.y - p.x * p.x / 2.0).abs() <= TOL);
+
+            let p = placement.to_local(hyperbola.point(u));
+            assert!(((p.x / 2.0).powi(2) - p.y.powi(2) - 1.0).abs() <= TOL);
+        }
+    }
+
+    #[test]
+    fn placed_circle_lies_in_plane() {
+        let placement =
+            EPlacement3::from_axis(EVec3::new(1.0, 2.0, 3.0), EVec3::new(1.0, -1.0, 0.5));
+        let circle = NurbsCurve::<HSpace3>::circle(&placement, 2.0);
+
+        for u in FloatRange::new(0.0, 1.0, 50) {
+            let p = circle.point(u) - placement.origin;
+            assert!((p.magnitude() - 2.0).abs() <= TOL);
+            assert!(p.dot(&placement.z_dir()).abs() <= TOL);
+        }
+
+        assert!((circle.point(0.0) - circle.point(1.0)).magnitude() <= TOL);
+        assert!(
+            (circle.point(0.25) - placement.planar_to_global(EVec2::new(0.0, 2.0))).magnitude()
+                <= TOL
+        );
+        assert!(
+            (NurbsCurve::<HSpace3>::arc(&placement, 2.0, 0.0, PI).point(1.0)
+                - placement.planar_to_global(EVec2::new(-2.0, 0.0)))
+            .magnitude()
+                <= TOL
+        );
+    }
+
+    #[test]
+    fn arc_through_points_in_3d() {
+        let start = EVec3::new(1.0, 0.0, 0.0);
+        let mid = EVec3::new(0.0, 1.0, 1.0);
+        let end = EVec3::new(-1.0, 0.0, 0.0);
+        let arc = NurbsCurve::<HSpace3>::arc_through_points(start, mid, end).unwrap();
+
+        assert!((arc.point(0.0) - start).magnitude() <= TOL);
+        assert!((arc.point(0.5) - mid).magnitude() <= TOL);
+        assert!((arc.point(1.0) - end).magnitude() <= TOL);
+    }
+}
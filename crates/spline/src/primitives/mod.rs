@@ -0,0 +1,2 @@
+pub mod curves;
+pub mod surfaces;
@@ -0,0 +1,191 @@
+use std::f64::consts::{FRAC_PI_2, PI};
+
+use space::{
+    hspace::{HSpace2, HSpace3},
+    EPlacement3, EVec2, EVec3, EVector, HVec3, TOL,
+};
+
+use crate::{math::knot_vector::KnotVector, nurbs_curve::NurbsCurve, nurbs_surface::NurbsSurface};
+
+impl NurbsSurface<HSpace3> {
+    /// Creates a flat rectangle in the placement's XY plane, centered on its origin.
+    /// `u` runs along the placement's X axis and `v` along its Y axis.
+    pub fn rectangle(placement: &EPlacement3, width: f64, height: f64) -> Self {
+        let corner = |x: f64, y: f64| {
+            let p = placement.planar_to_global(EVec2::new(x * width / 2.0, y * height / 2.0));
+            HVec3::new(p.x, p.y, p.z, 1.0)
+        };
+
+        Self::new(
+            vec![
+                vec![corner(-1.0, -1.0), corner(-1.0, 1.0)],
+                vec![corner(1.0, -1.0), corner(1.0, 1.0)],
+            ],
+            KnotVector::new([0.0, 0.0, 1.0, 1.0]),
+            KnotVector::new([0.0, 0.0, 1.0, 1.0]),
+        )
+    }
+
+    /// Sweeps a profile curve around the placement's Z axis, counterclockwise by
+    /// `angle` radians. `u` runs around the axis and `v` along the profile.
+    pub fn revolve(profile: &NurbsCurve<HSpace3>, placement: &EPlacement3, angle: f64) -> Self {
+        // The unit arc supplies the weights and knots in the direction of rotation
+        let arc = NurbsCurve::<HSpace2>::arc(EVec2::zero(), 1.0, 0.0, angle);
+        let axis = placement.z_dir();
+
+        let control_points = arc
+            .control_points()
+            .iter()
+            .map(|a| {
+                profile
+                    .control_points()
+                    .iter()
+                    .map(|p| {
+                        let point = EVec3::new(p.x, p.y, p.z);
+                        let on_axis =
+                            placement.origin + axis * (point - placement.origin).dot(&axis);
+                        let radial = point - on_axis;
+                        let radius = radial.magnitude();
+
+                        let placed = if radius <= TOL {
+                            on_axis
+                        } else {
+                            let x_dir = radial / radius;
+                            let y_dir = axis.cross(&x_dir);
+                            on_axis + (x_dir * a.x + y_dir * a.y) * radius
+                        };
+
+                        HVec3::new(placed.x, placed.y, placed.z, a.h * p.h)
+                    })
+                    .collect()
+            })
+            .collect();
+
+        Self::new(
+            control_points,
+            arc.knot_vector().clone(),
+            profile.knot_vector().clone(),
+        )
+    }
+
+    /// Creates the side of a cylinder around the placement's Z axis, with its base
+    /// on the placement's XY plane
+    pub fn cylinder(placement: &EPlacement3, radius: f64, height: f64) -> Self {
+        Self::cone(placement, radius, radius, height)
+    }
+
+    /// Creates the side of a (possibly truncated) cone around the placement's Z axis,
+    /// with its base on the placement's XY plane. Either radius may be zero to put
+    /// the apex at that end.
+    pub fn cone(placement: &EPlacement3, base_radius: f64, top_radius: f64, height: f64) -> Self {
+        let profile = NurbsCurve::<HSpace3>::line(
+            placement.to_global(EVec3::new(base_radius, 0.0, 0.0)),
+            placement.to_global(EVec3::new(top_radius, 0.0, height)),
+        );
+
+        Self::revolve(&profile, placement, 2.0 * PI)
+    }
+
+    /// Creates a sphere centered on the placement's origin, with its poles on the
+    /// placement's Z axis. `v` runs from the south pole to the north pole.
+    pub fn sphere(placement: &EPlacement3, radius: f64) -> Self {
+        let meridian_plane = EPlacement3::new(placement.origin, placement.x_dir, placement.z_dir());
+        let profile = NurbsCurve::<HSpace3>::arc(&meridian_plane, radius, -FRAC_PI_2, FRAC_PI_2);
+
+        Self::revolve(&profile, placement, 2.0 * PI)
+    }
+
+    /// Creates a torus centered on the placement's origin, revolving around its Z
+    /// axis. `major_radius` is the distance from the axis to the center of the tube.
+    pub fn torus(placement: &EPlacement3, major_radius: f64, minor_radius: f64) -> Self {
+        let tube_plane = EPlacement3::new(
+            placement.to_global(EVec3::new(major_radius, 0.0, 0.0)),
+            placement.x_dir,
+            placement.z_dir(),
+        );
+        let profile = NurbsCurve::<HSpace3>::circle(&tube_plane, minor_radius);
+
+        Self::revolve(&profile, placement, 2.0 * PI)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use space::{hspace::HSpace3, EPlacement3, EVec3, EVector, TOL};
+
+    use crate::{math::FloatRange, nurbs_surface::NurbsSurface};
+
+    fn placement() -> EPlacement3 {
+        EPlacement3::from_axis_and_x(
+            EVec3::new(1.0, -2.0, 0.5),
+            EVec3::new(0.3, 1.0, -0.4),
+            EVec3::new(1.0, 0.0, 0.0),
+        )
+    }
+
+    /// Samples a surface over its domain and returns the points in local coordinates
+    fn local_samples(surface: &NurbsSurface<HSpace3>, placement: &EPlacement3) -> Vec<EVec3> {
+        let mut points = Vec::new();
+        for u in FloatRange::new(surface.min_u(), surface.max_u(), 24) {
+            for v in FloatRange::new(surface.min_v(), surface.max_v(), 24) {
+                points.push(placement.to_local(surface.point(u, v)));
+            }
+        }
+        points
+    }
+
+    #[test]
+    fn rectangle_is_flat() {
+        let placement = placement();
+        let rectangle = NurbsSurface::<HSpace3>::rectangle(&placement, 4.0, 2.0);
+
+        for p in local_samples(&rectangle, &placement) {
+            assert!(p.z.abs() <= TOL);
+            assert!(p.x.abs() <= 2.0 + TOL && p.y.abs() <= 1.0 + TOL);
+        }
+    }
+
+    #[test]
+    fn cylinder_and_cone_have_linear_radius() {
+        let placement = placement();
+        let cylinder = NurbsSurface::<HSpace3>::cylinder(&placement, 1.5, 3.0);
+        let cone = NurbsSurface::<HSpace3>::cone(&placement, 2.0, 0.5, 3.0);
+
+        for p in local_samples(&cylinder, &placement) {
+            assert!((EVec3::new(p.x, p.y, 0.0).magnitude() - 1.5).abs() <= TOL);
+            assert!(p.z >= -TOL && p.z <= 3.0 + TOL);
+        }
+
+        for p in local_samples(&cone, &placement) {
+            let expected = 2.0 - 1.5 * p.z / 3.0;
+            assert!((EVec3::new(p.x, p.y, 0.0).magnitude() - expected).abs() <= TOL);
+        }
+    }
+
+    #[test]
+    fn sphere_points_are_equidistant() {
+        let placement = placement();
+        let sphere = NurbsSurface::<HSpace3>::sphere(&placement, 2.5);
+
+        for p in local_samples(&sphere, &placement) {
+            assert!((p.magnitude() - 2.5).abs() <= TOL);
+        }
+
+        // The normal points away from the center everywhere except the poles
+        let normal = sphere.normal(0.3, 0.4).unwrap();
+        let outward = (sphere.point(0.3, 0.4) - placement.origin).normalize();
+        assert!((normal - outward).magnitude() <= TOL);
+        assert!(sphere.normal(0.3, 0.0).is_none());
+    }
+
+    #[test]
+    fn torus_satisfies_implicit_equation() {
+        let placement = placement();
+        let torus = NurbsSurface::<HSpace3>::torus(&placement, 3.0, 0.75);
+
+        for p in local_samples(&torus, &placement) {
+            let from_tube = EVec3::new(p.x, p.y, 0.0).magnitude() - 3.0;
+            assert!((from_tube.powi(2) + p.z.powi(2) - 0.75_f64.powi(2)).abs() <= TOL);
+        }
+    }
+}
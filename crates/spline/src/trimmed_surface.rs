@@ -0,0 +1,31 @@
+use space::hspace::{HSpace2, HSpace3};
+
+use crate::{nurbs_curve::NurbsCurve, nurbs_surface::NurbsSurface};
+
+/// A surface cut down to the region inside loops of curves in its parameter
+/// space. Each loop is a chain of curves, each starting where the last one ends.
+#[derive(Debug, Clone)]
+pub struct TrimmedSurface {
+    pub surface: NurbsSurface<HSpace3>,
+
+    /// The loop around the region that is kept, or `None` if the region extends
+    /// to the edges of the surface's domain
+    pub outer: Option<Vec<NurbsCurve<HSpace2>>>,
+
+    /// Loops around holes cut out of the region
+    pub inner: Vec<Vec<NurbsCurve<HSpace2>>>,
+}
+impl TrimmedSurface {
+    /// Creates a trimmed surface covering the whole of `surface`
+    pub fn new(surface: NurbsSurface<HSpace3>) -> Self {
+        Self {
+            surface,
+            outer: None,
+            inner: Vec::new(),
+        }
+    }
+
+    pub fn is_trimmed(&self) -> bool {
+        self.outer.is_some() || !self.inner.is_empty()
+    }
+}
@@ -0,0 +1,420 @@
+//! Fillets and chamfers on the edges between planar faces. The edge is replaced by
+//! a new face whose long sides run along the two faces it joined, and whose ends
+//! cut across the faces at each end of the edge.
+
+use std::f64::consts::PI;
+
+use space::{
+    hspace::{HSpace2, HSpace3},
+    EVec2, EVec3, EVector, HVec3, Tolerance,
+};
+use spline::{math::knot_vector::KnotVector, nurbs_curve::NurbsCurve, nurbs_surface::NurbsSurface};
+
+use crate::{
+    entities::{EdgeId, FaceId, VertexId},
+    error::{TopologyError, TopologyResult},
+    solid::Solid,
+};
+
+#[derive(Debug, Clone, Copy)]
+enum Blend {
+    Fillet(f64),
+    Chamfer(f64, f64),
+    ChamferAngle(f64, f64),
+}
+
+impl Solid {
+    /// Rounds an edge between two planar faces with a rolling-ball fillet. The
+    /// fillet is a cylindrical face tangent to both faces, and the faces at the ends
+    /// of the edge are trimmed by the arcs where it meets them. Both ends of the
+    /// edge must join exactly three faces.
+    pub fn fillet(&self, edge: EdgeId, radius: f64) -> TopologyResult<Solid> {
+        self.fillet_with(edge, radius, &Tolerance::DEFAULT)
+    }
+
+    /// Rounds an edge like [`Self::fillet`], to the tolerances of a model
+    pub fn fillet_with(
+        &self,
+        edge: EdgeId,
+        radius: f64,
+        tolerance: &Tolerance,
+    ) -> TopologyResult<Solid> {
+        if !radius.is_finite() || radius <= 0.0 {
+            return Err(TopologyError::InvalidBlend("radius", radius));
+        }
+        self.blend(edge, Blend::Fillet(radius), tolerance)
+    }
+
+    /// Bevels an edge between two planar faces with a flat chamfer, set back
+    /// `distance1` across the face of the edge's first half-edge and `distance2`
+    /// across the other face. Distances are measured perpendicular to the edge.
+    pub fn chamfer(&self, edge: EdgeId, distance1: f64, distance2: f64) -> TopologyResult<Solid> {
+        self.chamfer_with(edge, distance1, distance2, &Tolerance::DEFAULT)
+    }
+
+    /// Bevels an edge like [`Self::chamfer`], to the tolerances of a model
+    pub fn chamfer_with(
+        &self,
+        edge: EdgeId,
+        distance1: f64,
+        distance2: f64,
+        tolerance: &Tolerance,
+    ) -> TopologyResult<Solid> {
+        for distance in [distance1, distance2] {
+            if !distance.is_finite() || distance <= 0.0 {
+                return Err(TopologyError::InvalidBlend("distance", distance));
+            }
+        }
+        self.blend(edge, Blend::Chamfer(distance1, distance2), tolerance)
+    }
+
+    /// Bevels an edge between two planar faces with a flat chamfer, set back
+    /// `distance` across the face of the edge's first half-edge and meeting that
+    /// face at `angle` radians
+    pub fn chamfer_angle(&self, edge: EdgeId, distance: f64, angle: f64) -> TopologyResult<Solid> {
+        self.chamfer_angle_with(edge, distance, angle, &Tolerance::DEFAULT)
+    }
+
+    /// Bevels an edge like [`Self::chamfer_angle`], to the tolerances of a model
+    pub fn chamfer_angle_with(
+        &self,
+        edge: EdgeId,
+        distance: f64,
+        angle: f64,
+        tolerance: &Tolerance,
+    ) -> TopologyResult<Solid> {
+        if !distance.is_finite() || distance <= 0.0 {
+            return Err(TopologyError::InvalidBlend("distance", distance));
+        }
+        if angle.is_nan() || angle <= 0.0 || angle >= PI {
+            return Err(TopologyError::InvalidBlend("angle", angle));
+        }
+        self.blend(edge, Blend::ChamferAngle(distance, angle), tolerance)
+    }
+
+    /// Blends an edge. Faces within the angular tolerance of tangent can't be
+    /// blended, and neither can edges whose blend comes within the linear
+    /// tolerance of the far end of a neighboring edge.
+    fn blend(&self, edge: EdgeId, blend: Blend, tolerance: &Tolerance) -> TopologyResult<Solid> {
+        let [he_a, he_b] = self.check_edge(edge)?.half_edges;
+        let (face_a, face_b) = (self.half_edge_face(he_a)?, self.half_edge_face(he_b)?);
+        let (v0, v1) = (self.he(he_a).origin, self.he(he_b).origin);
+        for v in [v0, v1] {
+            if self.vertex_edges(v)?.len() != 3 {
+                return Err(TopologyError::BlendVertex(v));
+            }
+        }
+
+        // The blend slides the ends of the edge along these edges
+        let (prev_a, next_a) = (self.he(he_a).prev, self.he(he_a).next);
+        let (prev_b, next_b) = (self.he(he_b).prev, self.he(he_b).next);
+        for he in [he_a, prev_a, next_a, prev_b, next_b] {
+            let edge = self
+                .he(he)
+                .edge
+                .ok_or(TopologyError::HalfEdgeWithoutEdge(he))?;
+            if matches!(self.half_edge_curve(he), Some(curve) if curve.degree() != 1) {
+                return Err(TopologyError::CurvedEdge(edge));
+            }
+        }
+
+        let normal_a = self.planar_frame(face_a)?.normal();
+        let normal_b = self.planar_frame(face_b)?.normal();
+        let (p0, p1) = (self.vx(v0).point, self.vx(v1).point);
+        let dir = (p1 - p0).normalize();
+
+        // Directions across each face, away from the edge, and the angle between them
+        let across_a = normal_a.cross(&dir).normalize();
+        let across_b = normal_b.cross(&-dir).normalize();
+        let angle = across_a.dot(&across_b).clamp(-1.0, 1.0).acos();
+        if angle <= tolerance.angular || angle >= PI - tolerance.angular {
+            return Err(TopologyError::TangentFaces(edge));
+        }
+
+        let (setback_a, setback_b) = match blend {
+            Blend::Fillet(radius) => {
+                let setback = radius / (angle / 2.0).tan();
+                (setback, setback)
+            }
+            Blend::Chamfer(distance1, distance2) => (distance1, distance2),
+            Blend::ChamferAngle(distance, chamfer_angle) => {
+                if angle + chamfer_angle >= PI - tolerance.angular {
+                    return Err(TopologyError::BlendTooLarge(edge));
+                }
+                let distance2 = distance * chamfer_angle.sin() / (angle + chamfer_angle).sin();
+                (distance, distance2)
+            }
+        };
+
+        // Moves a point along the edge towards a neighboring vertex until it is the
+        // given distance from the blended edge
+        let slide = |from: EVec3, towards: VertexId, distance: f64| {
+            let offset = self.vx(towards).point - from;
+            let sin = offset.normalize().cross(&dir).magnitude();
+            let along = distance / sin;
+            if sin <= tolerance.angular || along >= offset.magnitude() - tolerance.linear {
+                return Err(TopologyError::BlendTooLarge(edge));
+            }
+            Ok(from + offset.normalize() * along)
+        };
+        let a0 = slide(p0, self.he(prev_a).origin, setback_a)?;
+        let a1 = slide(p1, self.half_edge_target(next_a)?, setback_a)?;
+        let b0 = slide(p0, self.half_edge_target(next_b)?, setback_b)?;
+        let b1 = slide(p1, self.he(prev_b).origin, setback_b)?;
+
+        // Split each end vertex in two and add the blend face between them
+        let mut polygons = self.to_polygons()?;
+        let index = |v: VertexId| {
+            polygons
+                .vertices
+                .iter()
+                .position(|p| *p == Some(v))
+                .ok_or(TopologyError::MissingVertex(v))
+        };
+        let (i0, i1) = (index(v0)?, index(v1)?);
+        let side_a0 = index(self.he(prev_a).origin)?;
+        let side_a1 = index(self.half_edge_target(next_a)?)?;
+
+        let first = polygons.points.len();
+        polygons.points.extend([a0, a1, b0, b1]);
+        polygons.vertices.extend([None; 4]);
+        let (ia0, ia1, ib0, ib1) = (first, first + 1, first + 2, first + 3);
+
+        for (loops, source) in polygons.faces.iter_mut().zip(polygons.sources.iter_mut()) {
+            let face = *source;
+            for ring in loops.iter_mut() {
+                if !ring.contains(&i0) && !ring.contains(&i1) {
+                    continue;
+                }
+                *source = None;
+
+                let n = ring.len();
+                let mut new_ring = Vec::with_capacity(n + 2);
+                for k in 0..n {
+                    let (i, prev) = (ring[k], ring[(k + n - 1) % n]);
+                    let (a, b, side_a) = if i == i0 {
+                        (ia0, ib0, side_a0)
+                    } else if i == i1 {
+                        (ia1, ib1, side_a1)
+                    } else {
+                        new_ring.push(i);
+                        continue;
+                    };
+
+                    if face == Some(face_a) {
+                        new_ring.push(a);
+                    } else if face == Some(face_b) {
+                        new_ring.push(b);
+                    } else if prev == side_a {
+                        new_ring.extend([a, b]);
+                    } else {
+                        new_ring.extend([b, a]);
+                    }
+                }
+                *ring = new_ring;
+            }
+        }
+        polygons.faces.push(vec![vec![ia1, ia0, ib0, ib1]]);
+        polygons.sources.push(None);
+
+        let (mut solid, vertices) = self.rebuild(&polygons)?;
+
+        if let Blend::Fillet(_) = blend {
+            let blend_face = FaceId(polygons.faces.len() - 1);
+            let (a0v, a1v, b0v, b1v) = (
+                vertices[&ia0],
+                vertices[&ia1],
+                vertices[&ib0],
+                vertices[&ib1],
+            );
+            let weight = (angle / 2.0).sin();
+
+            // Sweep the cross section from the first face to the second along the
+            // edge, flipping it if that would make the surface face inwards
+            let mut rows = [(a0, a1), (p0, p1), (b0, b1)];
+            let outward = solid.planar_frame(blend_face)?.normal();
+            let surface = fillet_surface(&rows, weight);
+            let normal = surface
+                .normal(0.5, 0.5)
+                .ok_or(TopologyError::DegenerateBlend(edge))?;
+            let flipped = normal.dot(&outward) < 0.0;
+            if flipped {
+                rows.reverse();
+            }
+            let surface = fillet_surface(&rows, weight);
+            solid.set_face_surface(blend_face, surface, true)?;
+
+            let (u_a, u_b) = if flipped { (1.0, 0.0) } else { (0.0, 1.0) };
+            for (from, to, from_uv, to_uv) in [
+                (a1v, a0v, EVec2::new(u_a, 1.0), EVec2::new(u_a, 0.0)),
+                (a0v, b0v, EVec2::new(u_a, 0.0), EVec2::new(u_b, 0.0)),
+                (b0v, b1v, EVec2::new(u_b, 0.0), EVec2::new(u_b, 1.0)),
+                (b1v, a1v, EVec2::new(u_b, 1.0), EVec2::new(u_a, 1.0)),
+            ] {
+                let he = solid
+                    .half_edge_between(blend_face, from, to)
+                    .ok_or(TopologyError::MissingFaceEdge(blend_face, from, to))?;
+                solid.set_pcurve(he, NurbsCurve::<HSpace2>::line(from_uv, to_uv))?;
+            }
+
+            // The ends of the fillet are arcs across the end faces
+            for (from, corner, to, from_v, to_v) in [(a0, p0, b0, a0v, b0v), (b1, p1, a1, b1v, a1v)]
+            {
+                let he = solid
+                    .half_edge_between(blend_face, from_v, to_v)
+                    .ok_or(TopologyError::MissingFaceEdge(blend_face, from_v, to_v))?;
+                let edge = solid
+                    .check_half_edge(he)?
+                    .edge
+                    .ok_or(TopologyError::HalfEdgeWithoutEdge(he))?;
+                let arc = NurbsCurve::<HSpace3>::new(
+                    vec![
+                        HVec3::new(from.x, from.y, from.z, 1.0),
+                        HVec3::new(corner.x, corner.y, corner.z, weight),
+                        HVec3::new(to.x, to.y, to.z, 1.0),
+                    ],
+                    KnotVector::new([0.0, 0.0, 0.0, 1.0, 1.0, 1.0]),
+                );
+                let arc = if solid.check_edge(edge)?.half_edges[0] == he {
+                    arc
+                } else {
+                    arc.reverse()
+                };
+                solid.set_edge_curve(edge, arc)?;
+
+                let twin = solid
+                    .twin(he)
+                    .ok_or(TopologyError::HalfEdgeWithoutEdge(he))?;
+                let end_face = solid.half_edge_face(twin)?;
+                solid.fit_planar_surface(end_face)?;
+            }
+        }
+
+        solid.validate_with(tolerance)?;
+        Ok(solid)
+    }
+}
+
+/// A fillet surface through rows of three points, the middle one being where the
+/// faces meet. `u` runs across the fillet and `v` along it.
+fn fillet_surface(rows: &[(EVec3, EVec3); 3], weight: f64) -> NurbsSurface<HSpace3> {
+    let control_points = rows
+        .iter()
+        .zip([1.0, weight, 1.0])
+        .map(|((start, end), h)| {
+            vec![
+                HVec3::new(start.x, start.y, start.z, h),
+                HVec3::new(end.x, end.y, end.z, h),
+            ]
+        })
+        .collect();
+
+    NurbsSurface::new(
+        control_points,
+        KnotVector::new([0.0, 0.0, 0.0, 1.0, 1.0, 1.0]),
+        KnotVector::new([0.0, 0.0, 1.0, 1.0]),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use space::{EPlacement3, EVec3, EVector, TOL};
+
+    use crate::{entities::EdgeId, error::TopologyError, mesh::TriMesh, solid::Solid};
+
+    fn block() -> Solid {
+        Solid::block(&EPlacement3::default(), EVec3::new(2.0, 2.0, 2.0)).unwrap()
+    }
+
+    fn edge_at(solid: &Solid, p1: EVec3, p2: EVec3) -> EdgeId {
+        let vertex = |p: EVec3| {
+            solid
+                .vertices()
+                .find(|(_, v)| (v.point - p).magnitude() <= TOL)
+                .unwrap()
+                .0
+        };
+        solid.edge_between(vertex(p1), vertex(p2)).unwrap()
+    }
+
+    #[test]
+    fn chamfer_block_edge() {
+        let solid = block();
+        let edge = edge_at(&solid, EVec3::new(0.0, 0.0, 2.0), EVec3::new(2.0, 0.0, 2.0));
+        let chamfered = solid.chamfer(edge, 0.5, 0.5).unwrap();
+
+        assert_eq!(
+            (
+                chamfered.num_vertices(),
+                chamfered.num_edges(),
+                chamfered.num_faces()
+            ),
+            (10, 15, 7)
+        );
+
+        let mesh = TriMesh::from_solid(&chamfered).unwrap();
+        let volume = (0..mesh.triangles.len())
+            .map(|i| {
+                let [a, b, c] = mesh.triangle_points(i);
+                a.dot(&b.cross(&c)) / 6.0
+            })
+            .sum::<f64>();
+        assert!((volume - 7.75).abs() <= 1e-9);
+
+        // A 45 degree chamfer on a right-angled edge has equal setbacks
+        let angled = solid
+            .chamfer_angle(edge, 0.5, std::f64::consts::FRAC_PI_4)
+            .unwrap();
+        assert!(angled
+            .vertices()
+            .any(|(_, v)| (v.point - EVec3::new(0.0, 0.5, 2.0)).magnitude() <= 1e-9));
+        assert!(angled
+            .vertices()
+            .any(|(_, v)| (v.point - EVec3::new(0.0, 0.0, 1.5)).magnitude() <= 1e-9));
+    }
+
+    #[test]
+    fn fillet_block_edge() {
+        let solid = block();
+        let edge = edge_at(&solid, EVec3::new(0.0, 0.0, 2.0), EVec3::new(2.0, 0.0, 2.0));
+        let radius = 0.5;
+        let filleted = solid.fillet(edge, radius).unwrap();
+        assert_eq!(filleted.num_faces(), 7);
+
+        // The fillet surface and its end arcs lie on a cylinder around the edge,
+        // and the surface faces out of the solid
+        let center = |x: f64| EVec3::new(x, radius, 2.0 - radius);
+        let fillet = filleted
+            .faces()
+            .find_map(|(_, f)| f.surface.as_ref().filter(|s| s.degree_u() == 2))
+            .unwrap();
+        for (u, v) in [(0.0, 0.0), (0.3, 0.6), (0.5, 0.5), (1.0, 1.0)] {
+            let point = fillet.point(u, v);
+            assert!(((point - center(point.x)).magnitude() - radius).abs() <= 1e-9);
+        }
+        let normal = fillet.normal(0.5, 0.5).unwrap();
+        assert!(normal.dot(&(fillet.point(0.5, 0.5) - center(1.0))) > 0.0);
+
+        let arcs = filleted
+            .edges()
+            .filter_map(|(_, e)| e.curve.as_ref().filter(|c| c.degree() == 2))
+            .collect::<Vec<_>>();
+        assert_eq!(arcs.len(), 2);
+        for arc in arcs {
+            let point = arc.point(0.5);
+            assert!(((point - center(point.x)).magnitude() - radius).abs() <= 1e-9);
+        }
+
+        // Blending an edge too much fails
+        assert!(solid.fillet(edge, 3.0).is_err());
+        assert_eq!(
+            solid.fillet(edge, -1.0).unwrap_err(),
+            TopologyError::InvalidBlend("radius", -1.0)
+        );
+        assert!(solid.chamfer(edge, 0.5, f64::NAN).is_err());
+        assert_eq!(
+            solid.chamfer_angle(edge, 0.5, 4.0).unwrap_err(),
+            TopologyError::InvalidBlend("angle", 4.0)
+        );
+    }
+}
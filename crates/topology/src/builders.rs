@@ -0,0 +1,588 @@
+use std::collections::BTreeMap;
+
+use space::{
+    hspace::{HSpace2, HSpace3},
+    EPlacement3, EVec2, EVec3, EVector, HVec2, TOL,
+};
+use spline::{nurbs_curve::NurbsCurve, nurbs_surface::NurbsSurface};
+
+use crate::{
+    entities::{
+        Edge, EdgeId, Face, FaceId, HalfEdge, HalfEdgeId, Loop, LoopId, Shell, ShellId, Vertex,
+        VertexId,
+    },
+    error::{TopologyError, TopologyResult},
+    solid::Solid,
+};
+
+impl Solid {
+    /// Builds a solid with planar faces from a list of points and polygons indexing
+    /// into it. Each face is a list of loops: the outer loop comes first and runs
+    /// counterclockwise when seen from outside the solid, and any holes follow it,
+    /// running clockwise. Every edge must be shared by exactly two faces, running in
+    /// opposite directions. Faces connected by edges are grouped into shells.
+    pub fn from_polygons(points: &[EVec3], faces: &[Vec<Vec<usize>>]) -> TopologyResult<Self> {
+        Self::from_polygons_mapped(points, faces).map(|(solid, _)| solid)
+    }
+
+    /// Builds a solid like `from_polygons`, also returning the vertex made for each
+    /// point that is used
+    pub fn from_polygons_mapped(
+        points: &[EVec3],
+        faces: &[Vec<Vec<usize>>],
+    ) -> TopologyResult<(Self, BTreeMap<usize, VertexId>)> {
+        // Check the polygons and find which face each directed edge belongs to
+        let mut frames = Vec::with_capacity(faces.len());
+        let mut edge_faces = BTreeMap::<(usize, usize), usize>::new();
+        for (f, loops) in faces.iter().enumerate() {
+            if loops.is_empty() {
+                return Err(TopologyError::InvalidPolygon(f));
+            }
+
+            for indices in loops.iter() {
+                if indices.len() < 3 || indices.iter().any(|i| *i >= points.len()) {
+                    return Err(TopologyError::InvalidPolygon(f));
+                }
+
+                for (a, b) in loop_pairs(indices) {
+                    if a == b {
+                        return Err(TopologyError::InvalidPolygon(f));
+                    }
+                    if edge_faces.insert((a, b), f).is_some() {
+                        return Err(TopologyError::NonManifoldEdge(a, b));
+                    }
+                }
+            }
+
+            let outer = loops[0].iter().map(|i| points[*i]).collect::<Vec<_>>();
+            let frame = PlanarFrame::fit(&outer).ok_or(TopologyError::DegeneratePolygon(f))?;
+            frames.push(frame);
+        }
+
+        // Group faces that share edges into shells
+        let mut parents = (0..faces.len()).collect::<Vec<_>>();
+        for (&(a, b), &f) in edge_faces.iter() {
+            let twin_face = *edge_faces
+                .get(&(b, a))
+                .ok_or(TopologyError::OpenEdge(a, b))?;
+            let (root1, root2) = (
+                find_root(&mut parents, f),
+                find_root(&mut parents, twin_face),
+            );
+            parents[root1] = root2;
+        }
+
+        let mut solid = Self::new();
+        let mut shells = BTreeMap::<usize, ShellId>::new();
+        let mut vertices = BTreeMap::<usize, VertexId>::new();
+        let mut half_edges = BTreeMap::<(usize, usize), HalfEdgeId>::new();
+
+        for (f, loops) in faces.iter().enumerate() {
+            let root = find_root(&mut parents, f);
+            let shell = *shells
+                .entry(root)
+                .or_insert_with(|| ShellId(solid.shells.insert(Shell { faces: Vec::new() })));
+
+            let face = FaceId(solid.faces.next_index());
+            let loop_ids = (0..loops.len())
+                .map(|_| {
+                    // The half-edge is filled in once the loop's half-edges exist
+                    LoopId(solid.loops.insert(Loop {
+                        half_edge: HalfEdgeId(usize::MAX),
+                        face,
+                    }))
+                })
+                .collect::<Vec<_>>();
+
+            let frame = &frames[f];
+            for (indices, loop_id) in loops.iter().zip(loop_ids.iter()) {
+                let first = solid.half_edges.next_index();
+                let n = indices.len();
+                solid.lp_mut(*loop_id).half_edge = HalfEdgeId(first);
+                for (k, (a, b)) in loop_pairs(indices).enumerate() {
+                    let origin = *vertices.entry(a).or_insert_with(|| {
+                        VertexId(solid.vertices.insert(Vertex { point: points[a] }))
+                    });
+                    let id = HalfEdgeId(solid.half_edges.insert(HalfEdge {
+                        origin,
+                        edge: None,
+                        loop_id: *loop_id,
+                        next: HalfEdgeId(first + (k + 1) % n),
+                        prev: HalfEdgeId(first + (k + n - 1) % n),
+                        pcurve: Some(NurbsCurve::<HSpace2>::line(
+                            frame.parameter(points[a]),
+                            frame.parameter(points[b]),
+                        )),
+                    }));
+                    half_edges.insert((a, b), id);
+                }
+            }
+
+            solid.faces.insert(Face {
+                outer_loop: loop_ids[0],
+                loops: loop_ids,
+                shell,
+                surface: Some(frame.surface()),
+                same_sense: true,
+            });
+            solid.sh_mut(shell).faces.push(face);
+        }
+
+        for (&(a, b), &he) in half_edges.iter() {
+            if a < b {
+                let twin = half_edges[&(b, a)];
+                let edge = EdgeId(solid.edges.insert(Edge {
+                    half_edges: [he, twin],
+                    curve: Some(NurbsCurve::<HSpace3>::line(points[a], points[b])),
+                }));
+                solid.he_mut(he).edge = Some(edge);
+                solid.he_mut(twin).edge = Some(edge);
+            }
+        }
+
+        solid.validate()?;
+        Ok((solid, vertices))
+    }
+
+    /// Describes the solid as polygons, in the form taken by `from_polygons`
+    pub(crate) fn to_polygons(&self) -> TopologyResult<Polygons> {
+        let mut points = Vec::new();
+        let mut vertices = Vec::new();
+        let mut indices = BTreeMap::new();
+        for (id, vertex) in self.vertices() {
+            indices.insert(id, points.len());
+            points.push(vertex.point);
+            vertices.push(Some(id));
+        }
+
+        let mut sources = Vec::new();
+        let mut faces = Vec::new();
+        for (id, face) in self.faces() {
+            let loops = face
+                .loops
+                .iter()
+                .map(|l| {
+                    let vertices = self.loop_vertices(*l)?;
+                    Ok(vertices.into_iter().map(|v| indices[&v]).collect())
+                })
+                .collect::<TopologyResult<_>>()?;
+            sources.push(Some(id));
+            faces.push(loops);
+        }
+
+        Ok(Polygons {
+            points,
+            vertices,
+            sources,
+            faces,
+        })
+    }
+
+    /// Builds a solid from a modified copy of this solid's polygons. Edges between
+    /// two of this solid's vertices keep their curves, and unchanged faces keep
+    /// their surfaces and p-curves. Changed faces are given planar surfaces.
+    pub(crate) fn rebuild(
+        &self,
+        polygons: &Polygons,
+    ) -> TopologyResult<(Self, BTreeMap<usize, VertexId>)> {
+        let (mut solid, vertices) = Self::from_polygons_mapped(&polygons.points, &polygons.faces)?;
+        let original = vertices
+            .iter()
+            .filter_map(|(point, new)| polygons.vertices[*point].map(|old| (*new, old)))
+            .collect::<BTreeMap<_, _>>();
+
+        let edges = solid.edges().map(|(id, _)| id).collect::<Vec<_>>();
+        for edge in edges {
+            let [he1, he2] = solid.ed(edge).half_edges;
+            let old = original
+                .get(&solid.he(he1).origin)
+                .zip(original.get(&solid.he(he2).origin));
+            if let Some((v1, v2)) = old {
+                let curve = self
+                    .vertex_edges(*v1)?
+                    .into_iter()
+                    .flat_map(|e| self.ed(e).half_edges)
+                    .find(|he| self.he(*he).origin == *v1 && self.half_edge_target(*he) == Ok(*v2))
+                    .and_then(|he| self.half_edge_curve(he));
+                if let Some(curve) = curve {
+                    solid.edges.get_mut(edge.0).unwrap().curve = Some(curve);
+                }
+            }
+        }
+
+        for (f, source) in polygons.sources.iter().enumerate() {
+            let face = FaceId(f);
+            match source {
+                Some(source) => {
+                    let old = self.fc(*source);
+                    let (surface, same_sense) = (old.surface.clone(), old.same_sense);
+                    let new = solid.fc_mut(face);
+                    new.surface = surface;
+                    new.same_sense = same_sense;
+
+                    // Kept faces are made of the original vertices, whose half-edges
+                    // give the p-curves
+                    let unmatched = || TopologyError::UnmatchedKeptFace(*source);
+                    for he in solid.face_half_edges(face)? {
+                        let old = |vertex| original.get(&vertex).copied();
+                        let from = old(solid.he(he).origin).ok_or_else(unmatched)?;
+                        let to = old(solid.half_edge_target(he)?).ok_or_else(unmatched)?;
+                        let pcurve = self
+                            .half_edge_between(*source, from, to)
+                            .and_then(|old| self.he(old).pcurve.clone());
+                        solid.he_mut(he).pcurve = pcurve;
+                    }
+                }
+                None => solid.fit_planar_surface(face)?,
+            }
+        }
+
+        solid.validate()?;
+        Ok((solid, vertices))
+    }
+
+    /// Fits a frame to a planar face, covering its edges. Fails if the face's edges
+    /// do not all lie in one plane.
+    pub(crate) fn planar_frame(&self, id: FaceId) -> TopologyResult<PlanarFrame> {
+        let face = self.check_face(id)?;
+        let outer = self.loop_points(face.outer_loop)?;
+        let mut frame = PlanarFrame::fit(&outer).ok_or(TopologyError::CurvedFace(id))?;
+
+        let mut points = self
+            .face_half_edges(id)?
+            .into_iter()
+            .filter_map(|he| self.half_edge_curve(he))
+            .flat_map(|curve| curve.control_points().to_vec())
+            .map(|p| EVec3::new(p.x, p.y, p.z))
+            .collect::<Vec<_>>();
+        for l in face.loops.iter() {
+            points.extend(self.loop_points(*l)?);
+        }
+        if points
+            .iter()
+            .any(|p| (*p - outer[0]).dot(&frame.normal()).abs() > TOL)
+        {
+            return Err(TopologyError::CurvedFace(id));
+        }
+
+        frame.include(&points);
+        Ok(frame)
+    }
+
+    /// Gives a planar face a rectangular surface covering its edges, and p-curves
+    /// that follow the edges' curves
+    pub(crate) fn fit_planar_surface(&mut self, id: FaceId) -> TopologyResult<()> {
+        let frame = self.planar_frame(id)?;
+        for he in self.face_half_edges(id)? {
+            let pcurve = self.half_edge_curve(he).map(|curve| {
+                let control_points = curve
+                    .control_points()
+                    .iter()
+                    .map(|p| {
+                        let uv = frame.parameter(EVec3::new(p.x, p.y, p.z));
+                        HVec2::new(uv.x, uv.y, p.h)
+                    })
+                    .collect();
+                NurbsCurve::new(control_points, curve.knot_vector().clone())
+            });
+            self.he_mut(he).pcurve = pcurve;
+        }
+
+        let face = self.fc_mut(id);
+        face.surface = Some(frame.surface());
+        face.same_sense = true;
+        Ok(())
+    }
+
+    /// Builds a rectangular block with one corner at the placement's origin,
+    /// extending along its positive axes. Fails unless the size is positive
+    /// along every axis.
+    pub fn block(placement: &EPlacement3, size: EVec3) -> TopologyResult<Self> {
+        if !(size.x > 0.0 && size.y > 0.0 && size.z > 0.0) {
+            return Err(TopologyError::NonPositiveSize(size));
+        }
+        let points = [
+            (0.0, 0.0, 0.0),
+            (1.0, 0.0, 0.0),
+            (1.0, 1.0, 0.0),
+            (0.0, 1.0, 0.0),
+            (0.0, 0.0, 1.0),
+            (1.0, 0.0, 1.0),
+            (1.0, 1.0, 1.0),
+            (0.0, 1.0, 1.0),
+        ]
+        .into_iter()
+        .map(|(x, y, z)| placement.to_global(EVec3::new(x * size.x, y * size.y, z * size.z)))
+        .collect::<Vec<_>>();
+
+        let faces = [
+            [0, 3, 2, 1],
+            [4, 5, 6, 7],
+            [0, 1, 5, 4],
+            [1, 2, 6, 5],
+            [2, 3, 7, 6],
+            [3, 0, 4, 7],
+        ]
+        .into_iter()
+        .map(|f| vec![f.to_vec()])
+        .collect::<Vec<_>>();
+
+        Self::from_polygons(&points, &faces)
+    }
+
+    /// Extrudes a polygon with optional holes along the placement's Z axis. The
+    /// polygons are given in the placement's XY plane, and may run in either
+    /// direction.
+    pub fn prism(
+        placement: &EPlacement3,
+        outer: &[EVec2],
+        holes: &[Vec<EVec2>],
+        height: f64,
+    ) -> TopologyResult<Self> {
+        let mut points = Vec::new();
+        let mut bottom = Vec::new();
+        let mut top = Vec::new();
+        let mut sides = Vec::new();
+
+        for (l, polygon) in std::iter::once(outer)
+            .chain(holes.iter().map(|h| &h[..]))
+            .enumerate()
+        {
+            // The outer polygon runs counterclockwise and the holes clockwise when
+            // seen from above
+            let mut polygon = polygon.to_vec();
+            if (signed_area(&polygon) > 0.0) != (l == 0) {
+                polygon.reverse();
+            }
+
+            let start = points.len();
+            let n = polygon.len();
+            for p in polygon.iter() {
+                points.push(placement.planar_to_global(*p));
+            }
+            for p in polygon.iter() {
+                points.push(placement.to_global(EVec3::new(p.x, p.y, height)));
+            }
+
+            let b = |i: usize| start + i % n;
+            let t = |i: usize| start + n + i % n;
+            bottom.push((0..n).rev().map(b).collect::<Vec<_>>());
+            top.push((0..n).map(t).collect::<Vec<_>>());
+            sides.extend((0..n).map(|i| vec![vec![b(i), b(i + 1), t(i + 1), t(i)]]));
+        }
+
+        let mut faces = vec![bottom, top];
+        faces.extend(sides);
+        Self::from_polygons(&points, &faces)
+    }
+}
+
+/// A solid described as polygons, along with where they came from
+pub(crate) struct Polygons {
+    pub points: Vec<EVec3>,
+
+    /// The vertex each point came from, if any
+    pub vertices: Vec<Option<VertexId>>,
+
+    /// The face each polygon came from, if it is unchanged
+    pub sources: Vec<Option<FaceId>>,
+
+    pub faces: Vec<Vec<Vec<usize>>>,
+}
+
+/// A plane fitted to a planar polygon, with a rectangular surface covering it
+pub(crate) struct PlanarFrame {
+    placement: EPlacement3,
+    min: EVec2,
+    size: EVec2,
+}
+impl PlanarFrame {
+    /// Fits a frame whose Z axis is the polygon's normal, or returns `None` if the
+    /// polygon has no area
+    pub fn fit(polygon: &[EVec3]) -> Option<Self> {
+        // Newell's method
+        let normal = loop_pairs(polygon)
+            .map(|(a, b)| {
+                EVec3::new(
+                    (a.y - b.y) * (a.z + b.z),
+                    (a.z - b.z) * (a.x + b.x),
+                    (a.x - b.x) * (a.y + b.y),
+                )
+            })
+            .sum::<EVec3>();
+        if normal.magnitude() <= TOL {
+            return None;
+        }
+
+        let placement = EPlacement3::from_axis_and_x(polygon[0], normal, polygon[1] - polygon[0]);
+        let local = polygon
+            .iter()
+            .map(|p| {
+                let l = placement.to_local(*p);
+                EVec2::new(l.x, l.y)
+            })
+            .collect::<Vec<_>>();
+        let min = EVec2::new(
+            local.iter().map(|p| p.x).fold(f64::MAX, f64::min),
+            local.iter().map(|p| p.y).fold(f64::MAX, f64::min),
+        );
+        let max = EVec2::new(
+            local.iter().map(|p| p.x).fold(f64::MIN, f64::max),
+            local.iter().map(|p| p.y).fold(f64::MIN, f64::max),
+        );
+
+        Some(Self {
+            placement,
+            min,
+            size: max - min,
+        })
+    }
+
+    pub fn normal(&self) -> EVec3 {
+        self.placement.z_dir()
+    }
+
+    /// Grows the frame to cover more points in its plane
+    pub fn include(&mut self, points: &[EVec3]) {
+        let mut max = self.min + self.size;
+        for point in points.iter() {
+            let local = self.placement.to_local(*point);
+            self.min = EVec2::new(self.min.x.min(local.x), self.min.y.min(local.y));
+            max = EVec2::new(max.x.max(local.x), max.y.max(local.y));
+        }
+        self.size = max - self.min;
+    }
+
+    pub fn surface(&self) -> NurbsSurface<HSpace3> {
+        let center = self.placement.planar_to_global(self.min + self.size / 2.0);
+        let placement = EPlacement3::new(center, self.placement.x_dir, self.placement.y_dir);
+        NurbsSurface::<HSpace3>::rectangle(&placement, self.size.x, self.size.y)
+    }
+
+    /// The parameters of a point on the surface
+    pub fn parameter(&self, point: EVec3) -> EVec2 {
+        let local = self.placement.to_local(point);
+        (EVec2::new(local.x, local.y) - self.min) / self.size
+    }
+}
+
+fn loop_pairs<T: Copy>(items: &[T]) -> impl Iterator<Item = (T, T)> + '_ {
+    items
+        .iter()
+        .zip(items.iter().cycle().skip(1))
+        .map(|(a, b)| (*a, *b))
+}
+
+/// Finds the root of an item's set in a union-find forest, halving the path to it
+/// along the way
+pub(crate) fn find_root(parents: &mut [usize], mut i: usize) -> usize {
+    while parents[i] != i {
+        parents[i] = parents[parents[i]];
+        i = parents[i];
+    }
+    i
+}
+
+fn signed_area(polygon: &[EVec2]) -> f64 {
+    loop_pairs(polygon)
+        .map(|(a, b)| a.x * b.y - b.x * a.y)
+        .sum::<f64>()
+        / 2.0
+}
+
+#[cfg(test)]
+mod tests {
+    use space::{EPlacement3, EVec2, EVec3, EVector, TOL};
+
+    use crate::{error::TopologyError, solid::Solid};
+
+    #[test]
+    fn block() {
+        let solid = Solid::block(
+            &EPlacement3::from_origin(EVec3::new(1.0, 2.0, 3.0)),
+            EVec3::new(2.0, 3.0, 4.0),
+        )
+        .unwrap();
+
+        assert_eq!(
+            (solid.num_vertices(), solid.num_edges(), solid.num_faces()),
+            (8, 12, 6)
+        );
+        assert_eq!(solid.num_shells(), 1);
+        assert_eq!(solid.genus(), 0);
+        let inside_out = EVec3::new(1.0, -1.0, 1.0);
+        assert_eq!(
+            Solid::block(&EPlacement3::default(), inside_out).unwrap_err(),
+            TopologyError::NonPositiveSize(inside_out)
+        );
+
+        // Every face's surface normal points out of the block
+        let center = EVec3::new(2.0, 3.5, 5.0);
+        for (_, face) in solid.faces() {
+            let surface = face.surface.as_ref().unwrap();
+            let point = surface.point(0.5, 0.5);
+            let normal = surface.normal(0.5, 0.5).unwrap();
+            assert!(normal.dot(&(point - center)) > 0.0);
+        }
+
+        // P-curves map onto the edge curves
+        for (id, he) in solid.half_edges() {
+            let pcurve = he.pcurve.as_ref().unwrap();
+            let surface = solid
+                .face(solid.half_edge_face(id).unwrap())
+                .unwrap()
+                .surface
+                .as_ref()
+                .unwrap();
+            let uv = pcurve.point(pcurve.min_u());
+            let start = surface.point(uv.x, uv.y);
+            assert!((start - solid.vertex(he.origin).unwrap().point).magnitude() <= TOL);
+        }
+    }
+
+    #[test]
+    fn prism_with_hole() {
+        let outer = [
+            EVec2::new(0.0, 0.0),
+            EVec2::new(4.0, 0.0),
+            EVec2::new(4.0, 4.0),
+            EVec2::new(0.0, 4.0),
+        ];
+        let hole = vec![
+            EVec2::new(1.0, 1.0),
+            EVec2::new(3.0, 1.0),
+            EVec2::new(3.0, 3.0),
+            EVec2::new(1.0, 3.0),
+        ];
+        let solid = Solid::prism(&EPlacement3::default(), &outer, &[hole], 2.0).unwrap();
+
+        assert_eq!(
+            (solid.num_vertices(), solid.num_edges(), solid.num_faces()),
+            (16, 24, 10)
+        );
+        assert_eq!(solid.num_rings(), 2);
+        assert_eq!(solid.genus(), 1);
+    }
+
+    #[test]
+    fn open_and_non_manifold_polygons() {
+        let points = [
+            EVec3::new(0.0, 0.0, 0.0),
+            EVec3::new(1.0, 0.0, 0.0),
+            EVec3::new(0.0, 1.0, 0.0),
+            EVec3::new(0.0, 0.0, 1.0),
+        ];
+
+        let open = [vec![vec![0, 2, 1]], vec![vec![0, 1, 3]]];
+        assert!(matches!(
+            Solid::from_polygons(&points, &open),
+            Err(TopologyError::OpenEdge(_, _))
+        ));
+
+        let non_manifold = [vec![vec![0, 2, 1]], vec![vec![0, 2, 3]]];
+        assert_eq!(
+            Solid::from_polygons(&points, &non_manifold).unwrap_err(),
+            TopologyError::NonManifoldEdge(0, 2)
+        );
+    }
+}
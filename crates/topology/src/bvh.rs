@@ -0,0 +1,247 @@
+//! Bounding volume hierarchies of axis-aligned boxes, used to find the nearby
+//! parts of two bodies without comparing every pair of triangles
+
+use space::{EVec3, EVector};
+
+use crate::mesh::TriMesh;
+
+/// Largest number of items kept in a leaf of the hierarchy
+const LEAF_SIZE: usize = 4;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Aabb {
+    pub min: EVec3,
+    pub max: EVec3,
+}
+impl Aabb {
+    /// The smallest box containing the points, or `None` if there are none
+    pub fn from_points(points: &[EVec3]) -> Option<Self> {
+        let (first, rest) = points.split_first()?;
+        Some(rest.iter().fold(
+            Self {
+                min: *first,
+                max: *first,
+            },
+            |bounds, point| bounds.include(*point),
+        ))
+    }
+
+    /// The smallest box containing a triangle
+    pub fn from_triangle(points: &[EVec3; 3]) -> Self {
+        Self {
+            min: points[0],
+            max: points[0],
+        }
+        .include(points[1])
+        .include(points[2])
+    }
+
+    /// The smallest box containing this box and a point
+    pub fn include(&self, point: EVec3) -> Self {
+        Self {
+            min: EVec3::new(
+                self.min.x.min(point.x),
+                self.min.y.min(point.y),
+                self.min.z.min(point.z),
+            ),
+            max: EVec3::new(
+                self.max.x.max(point.x),
+                self.max.y.max(point.y),
+                self.max.z.max(point.z),
+            ),
+        }
+    }
+
+    /// The smallest box containing both boxes
+    pub fn union(&self, other: &Self) -> Self {
+        self.include(other.min).include(other.max)
+    }
+
+    pub fn center(&self) -> EVec3 {
+        (self.min + self.max) / 2.0
+    }
+
+    /// Widens the box by `margin` on every side
+    pub fn expand(&self, margin: f64) -> Self {
+        let margin = EVec3::new(margin, margin, margin);
+        Self {
+            min: self.min - margin,
+            max: self.max + margin,
+        }
+    }
+
+    /// The smallest distance between a point in this box and a point in the other,
+    /// which is 0 if they overlap
+    pub fn distance(&self, other: &Self) -> f64 {
+        let gap = |min_a: f64, max_a: f64, min_b: f64, max_b: f64| {
+            (min_b - max_a).max(min_a - max_b).max(0.0)
+        };
+        EVec3::new(
+            gap(self.min.x, self.max.x, other.min.x, other.max.x),
+            gap(self.min.y, self.max.y, other.min.y, other.max.y),
+            gap(self.min.z, self.max.z, other.min.z, other.max.z),
+        )
+        .magnitude()
+    }
+
+    pub fn overlaps(&self, other: &Self) -> bool {
+        self.min.x <= other.max.x
+            && other.min.x <= self.max.x
+            && self.min.y <= other.max.y
+            && other.min.y <= self.max.y
+            && self.min.z <= other.max.z
+            && other.min.z <= self.max.z
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum BvhContents {
+    /// Indices of the items in a leaf
+    Leaf(Vec<usize>),
+
+    /// Indices of the two child nodes
+    Branch(usize, usize),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct BvhNode {
+    pub bounds: Aabb,
+    pub contents: BvhContents,
+}
+
+/// A binary tree of boxes over a set of items, each node bounding all the items
+/// below it. Nodes are split at the median of their items' centers along their
+/// longest axis.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Bvh {
+    nodes: Vec<BvhNode>,
+}
+impl Bvh {
+    /// Builds a hierarchy over items with the given bounds. Items are referred to
+    /// by their index in `bounds`.
+    pub fn new(bounds: &[Aabb]) -> Self {
+        let mut bvh = Self { nodes: Vec::new() };
+        if !bounds.is_empty() {
+            bvh.build(bounds, (0..bounds.len()).collect());
+        }
+        bvh
+    }
+
+    /// Index of the root node, or `None` if the hierarchy is empty
+    pub fn root(&self) -> Option<usize> {
+        if self.nodes.is_empty() {
+            None
+        } else {
+            Some(0)
+        }
+    }
+
+    pub fn node(&self, index: usize) -> &BvhNode {
+        &self.nodes[index]
+    }
+
+    /// Finds the pairs of items from two hierarchies whose bounds overlap once
+    /// each is widened by `margin`
+    pub fn overlapping_pairs(&self, other: &Self, margin: f64) -> Vec<(usize, usize)> {
+        let mut pairs = Vec::new();
+        let (Some(a), Some(b)) = (self.root(), other.root()) else {
+            return pairs;
+        };
+
+        let mut stack = vec![(a, b)];
+        while let Some((a, b)) = stack.pop() {
+            let (node_a, node_b) = (self.node(a), other.node(b));
+            if !node_a
+                .bounds
+                .expand(margin)
+                .overlaps(&node_b.bounds.expand(margin))
+            {
+                continue;
+            }
+
+            match (&node_a.contents, &node_b.contents) {
+                (BvhContents::Leaf(items_a), BvhContents::Leaf(items_b)) => {
+                    for a in items_a {
+                        for b in items_b {
+                            pairs.push((*a, *b));
+                        }
+                    }
+                }
+                (BvhContents::Branch(left, right), BvhContents::Leaf(_)) => {
+                    stack.push((*left, b));
+                    stack.push((*right, b));
+                }
+                (_, BvhContents::Branch(left, right)) => {
+                    stack.push((a, *left));
+                    stack.push((a, *right));
+                }
+            }
+        }
+
+        pairs
+    }
+
+    /// Finds the items whose bounds overlap a box
+    pub fn overlapping(&self, bounds: &Aabb) -> Vec<usize> {
+        let mut items = Vec::new();
+        let mut stack: Vec<usize> = self.root().into_iter().collect();
+        while let Some(index) = stack.pop() {
+            let node = self.node(index);
+            if !node.bounds.overlaps(bounds) {
+                continue;
+            }
+            match &node.contents {
+                BvhContents::Leaf(leaf) => items.extend(leaf.iter().copied()),
+                BvhContents::Branch(left, right) => stack.extend([*left, *right]),
+            }
+        }
+        items
+    }
+
+    fn build(&mut self, bounds: &[Aabb], mut items: Vec<usize>) -> usize {
+        let node_bounds = items
+            .iter()
+            .skip(1)
+            .fold(bounds[items[0]], |acc, item| acc.union(&bounds[*item]));
+        let index = self.nodes.len();
+        self.nodes.push(BvhNode {
+            bounds: node_bounds,
+            contents: BvhContents::Leaf(Vec::new()),
+        });
+
+        if items.len() <= LEAF_SIZE {
+            self.nodes[index].contents = BvhContents::Leaf(items);
+            return index;
+        }
+
+        let size = node_bounds.max - node_bounds.min;
+        let axis = |b: &Aabb| {
+            let center = b.center();
+            if size.x >= size.y && size.x >= size.z {
+                center.x
+            } else if size.y >= size.z {
+                center.y
+            } else {
+                center.z
+            }
+        };
+        items.sort_by(|a, b| axis(&bounds[*a]).total_cmp(&axis(&bounds[*b])));
+        let upper = items.split_off(items.len() / 2);
+
+        let left = self.build(bounds, items);
+        let right = self.build(bounds, upper);
+        self.nodes[index].contents = BvhContents::Branch(left, right);
+        index
+    }
+}
+
+impl TriMesh {
+    /// Builds a hierarchy over the mesh's triangles
+    pub fn bvh(&self) -> Bvh {
+        Bvh::new(
+            &(0..self.triangles.len())
+                .map(|i| Aabb::from_triangle(&self.triangle_points(i)))
+                .collect::<Vec<_>>(),
+        )
+    }
+}
@@ -0,0 +1,362 @@
+//! Minimum distances between triangle meshes. Pairs of nodes from the meshes'
+//! bounding volume hierarchies are searched closest first, and pairs of triangles
+//! are only compared exactly once their boxes could hold a closer pair than the
+//! best found so far.
+
+use std::{cmp::Ordering, collections::BinaryHeap};
+
+use space::{EVec3, EVector, Tolerance};
+
+use crate::{
+    bvh::{Aabb, Bvh, BvhContents},
+    mesh::TriMesh,
+};
+
+/// The minimum distance between two meshes and the points where it is reached
+#[derive(Debug, Clone, PartialEq)]
+pub struct MeshDistance {
+    pub distance: f64,
+
+    /// Closest point on the first mesh, and the index of its triangle
+    pub first: (EVec3, usize),
+
+    /// Closest point on the second mesh, and the index of its triangle
+    pub second: (EVec3, usize),
+}
+
+impl TriMesh {
+    /// Finds the closest points between the surfaces of two meshes. Returns `None`
+    /// if either mesh has no triangles.
+    pub fn distance(&self, other: &TriMesh) -> Option<MeshDistance> {
+        self.distance_with(&self.bvh(), other, &other.bvh(), &Tolerance::DEFAULT)
+    }
+
+    /// Finds the closest points between the surfaces of two meshes, reusing
+    /// hierarchies built earlier with [`TriMesh::bvh`]. Triangles that cross to
+    /// within the linear tolerance are 0 apart.
+    pub fn distance_with(
+        &self,
+        bvh: &Bvh,
+        other: &TriMesh,
+        other_bvh: &Bvh,
+        tolerance: &Tolerance,
+    ) -> Option<MeshDistance> {
+        let mut best: Option<MeshDistance> = None;
+        let mut queue = BinaryHeap::new();
+        queue.push(NodePair {
+            bound: 0.0,
+            nodes: (bvh.root()?, other_bvh.root()?),
+        });
+
+        while let Some(NodePair {
+            bound,
+            nodes: (a, b),
+        }) = queue.pop()
+        {
+            if matches!(&best, Some(best) if bound >= best.distance) {
+                break;
+            }
+
+            let (node_a, node_b) = (bvh.node(a), other_bvh.node(b));
+            let mut push = |a: usize, b: usize| {
+                queue.push(NodePair {
+                    bound: bvh.node(a).bounds.distance(&other_bvh.node(b).bounds),
+                    nodes: (a, b),
+                })
+            };
+
+            match (&node_a.contents, &node_b.contents) {
+                (BvhContents::Leaf(items_a), BvhContents::Leaf(items_b)) => {
+                    for i in items_a {
+                        for j in items_b {
+                            let (distance, p, q) = triangle_distance(
+                                self.triangle_points(*i),
+                                other.triangle_points(*j),
+                                tolerance,
+                            );
+                            if !matches!(&best, Some(best) if distance >= best.distance) {
+                                best = Some(MeshDistance {
+                                    distance,
+                                    first: (p, *i),
+                                    second: (q, *j),
+                                });
+                            }
+                        }
+                    }
+                }
+                (BvhContents::Branch(left, right), BvhContents::Leaf(_)) => {
+                    push(*left, b);
+                    push(*right, b);
+                }
+                (BvhContents::Leaf(_), BvhContents::Branch(left, right)) => {
+                    push(a, *left);
+                    push(a, *right);
+                }
+                (BvhContents::Branch(left_a, right_a), BvhContents::Branch(left_b, right_b)) => {
+                    // Descend into the larger node
+                    let size = |bounds: &Aabb| (bounds.max - bounds.min).magnitude();
+                    if size(&node_a.bounds) >= size(&node_b.bounds) {
+                        push(*left_a, b);
+                        push(*right_a, b);
+                    } else {
+                        push(a, *left_b);
+                        push(a, *right_b);
+                    }
+                }
+            }
+        }
+
+        best
+    }
+}
+
+/// A pair of hierarchy nodes waiting to be searched, ordered so the pair whose
+/// boxes are closest comes first
+struct NodePair {
+    bound: f64,
+    nodes: (usize, usize),
+}
+impl PartialEq for NodePair {
+    fn eq(&self, other: &Self) -> bool {
+        self.bound == other.bound
+    }
+}
+impl Eq for NodePair {}
+impl PartialOrd for NodePair {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+impl Ord for NodePair {
+    fn cmp(&self, other: &Self) -> Ordering {
+        other.bound.total_cmp(&self.bound)
+    }
+}
+
+/// Finds the closest points between two triangles, returning the distance
+/// between them and the points on the first and second triangle. Triangles that
+/// cross each other are 0 apart at a point where an edge of one passes through
+/// the other, to within the linear tolerance.
+pub fn triangle_distance(
+    a: [EVec3; 3],
+    b: [EVec3; 3],
+    tolerance: &Tolerance,
+) -> (f64, EVec3, EVec3) {
+    let edges = |t: [EVec3; 3]| [(t[0], t[1]), (t[1], t[2]), (t[2], t[0])];
+
+    for (edge, triangle) in edges(a)
+        .iter()
+        .map(|edge| (edge, b))
+        .chain(edges(b).iter().map(|edge| (edge, a)))
+    {
+        if let Some(point) = segment_triangle_intersection(*edge, triangle, tolerance) {
+            return (0.0, point, point);
+        }
+    }
+
+    let mut best = (f64::MAX, a[0], b[0]);
+    let mut consider = |p: EVec3, q: EVec3| {
+        let distance = (q - p).magnitude();
+        if distance < best.0 {
+            best = (distance, p, q);
+        }
+    };
+
+    for p in a {
+        consider(p, closest_point_on_triangle(p, b));
+    }
+    for q in b {
+        consider(closest_point_on_triangle(q, a), q);
+    }
+    for (p0, p1) in edges(a) {
+        for (q0, q1) in edges(b) {
+            let (p, q) = closest_points_on_segments(p0, p1, q0, q1);
+            consider(p, q);
+        }
+    }
+
+    best
+}
+
+/// Finds the point on a triangle closest to `point`
+pub fn closest_point_on_triangle(point: EVec3, [a, b, c]: [EVec3; 3]) -> EVec3 {
+    // Ericson, "Real-Time Collision Detection", 5.1.5
+    let (ab, ac, ap) = (b - a, c - a, point - a);
+    let (d1, d2) = (ab.dot(&ap), ac.dot(&ap));
+    if d1 <= 0.0 && d2 <= 0.0 {
+        return a;
+    }
+
+    let bp = point - b;
+    let (d3, d4) = (ab.dot(&bp), ac.dot(&bp));
+    if d3 >= 0.0 && d4 <= d3 {
+        return b;
+    }
+
+    let vc = d1 * d4 - d3 * d2;
+    if vc <= 0.0 && d1 >= 0.0 && d3 <= 0.0 {
+        return a + ab * (d1 / (d1 - d3));
+    }
+
+    let cp = point - c;
+    let (d5, d6) = (ab.dot(&cp), ac.dot(&cp));
+    if d6 >= 0.0 && d5 <= d6 {
+        return c;
+    }
+
+    let vb = d5 * d2 - d1 * d6;
+    if vb <= 0.0 && d2 >= 0.0 && d6 <= 0.0 {
+        return a + ac * (d2 / (d2 - d6));
+    }
+
+    let va = d3 * d6 - d5 * d4;
+    if va <= 0.0 && d4 - d3 >= 0.0 && d5 - d6 >= 0.0 {
+        return b + (c - b) * ((d4 - d3) / ((d4 - d3) + (d5 - d6)));
+    }
+
+    let denom = va + vb + vc;
+    if denom.abs() <= f64::EPSILON {
+        // Degenerate triangle, so fall back to its edges
+        return [(a, b), (b, c), (c, a)]
+            .iter()
+            .map(|(p, q)| closest_points_on_segments(point, point, *p, *q).1)
+            .min_by(|p, q| {
+                (*p - point)
+                    .magnitude()
+                    .total_cmp(&(*q - point).magnitude())
+            })
+            .unwrap();
+    }
+    a + ab * (vb / denom) + ac * (vc / denom)
+}
+
+/// Finds the closest points between two segments, returning the point on the
+/// first and the point on the second
+pub fn closest_points_on_segments(p0: EVec3, p1: EVec3, q0: EVec3, q1: EVec3) -> (EVec3, EVec3) {
+    // Ericson, "Real-Time Collision Detection", 5.1.9
+    let (d1, d2, r) = (p1 - p0, q1 - q0, p0 - q0);
+    let (a, e, f) = (d1.dot(&d1), d2.dot(&d2), d2.dot(&r));
+
+    let (s, t) = if a <= f64::EPSILON && e <= f64::EPSILON {
+        (0.0, 0.0)
+    } else if a <= f64::EPSILON {
+        (0.0, (f / e).clamp(0.0, 1.0))
+    } else {
+        let c = d1.dot(&r);
+        if e <= f64::EPSILON {
+            ((-c / a).clamp(0.0, 1.0), 0.0)
+        } else {
+            let b = d1.dot(&d2);
+            let denom = a * e - b * b;
+            let s = if denom > 0.0 {
+                ((b * f - c * e) / denom).clamp(0.0, 1.0)
+            } else {
+                0.0
+            };
+
+            let t = (b * s + f) / e;
+            if t < 0.0 {
+                ((-c / a).clamp(0.0, 1.0), 0.0)
+            } else if t > 1.0 {
+                (((b - c) / a).clamp(0.0, 1.0), 1.0)
+            } else {
+                (s, t)
+            }
+        }
+    };
+
+    (p0 + d1 * s, q0 + d2 * t)
+}
+
+/// Finds where a segment passes through a triangle, if it does. Segments lying in
+/// the plane of the triangle are not counted, since their crossings are found
+/// from the triangle's edges instead.
+fn segment_triangle_intersection(
+    (p0, p1): (EVec3, EVec3),
+    [a, b, c]: [EVec3; 3],
+    tolerance: &Tolerance,
+) -> Option<EVec3> {
+    let normal = (b - a).cross(&(c - a));
+    let scale = normal.magnitude();
+    if scale <= f64::EPSILON {
+        return None;
+    }
+
+    let linear = tolerance.linear;
+    let (h0, h1) = (normal.dot(&(p0 - a)) / scale, normal.dot(&(p1 - a)) / scale);
+    if (h0 > linear && h1 > linear) || (h0 < -linear && h1 < -linear) || (h0 - h1).abs() <= linear {
+        return None;
+    }
+
+    let point = p0 + (p1 - p0) * (h0 / (h0 - h1));
+    let inside = [(a, b), (b, c), (c, a)]
+        .iter()
+        .all(|(p, q)| (*q - *p).cross(&(point - *p)).dot(&normal) >= -linear * scale);
+    if inside {
+        Some(point)
+    } else {
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use space::{EPlacement3, EVec3, EVector, Tolerance};
+
+    use crate::{mesh::TriMesh, solid::Solid};
+
+    use super::triangle_distance;
+
+    fn block_mesh(origin: EVec3, size: EVec3) -> TriMesh {
+        let placement =
+            EPlacement3::new(origin, EVec3::new(1.0, 0.0, 0.0), EVec3::new(0.0, 1.0, 0.0));
+        TriMesh::from_solid(&Solid::block(&placement, size).unwrap()).unwrap()
+    }
+
+    #[test]
+    fn mesh_distance() {
+        let a = block_mesh(EVec3::new(0.0, 0.0, 0.0), EVec3::new(1.0, 1.0, 1.0));
+        let b = block_mesh(EVec3::new(3.0, 4.0, 0.5), EVec3::new(1.0, 1.0, 1.0));
+        let result = a.distance(&b).unwrap();
+
+        // The closest features are the facing vertical edges
+        assert!((result.distance - (4.0_f64 + 9.0).sqrt()).abs() <= 1e-12);
+        assert!((result.first.0.x - 1.0).abs() <= 1e-12 && (result.first.0.y - 1.0).abs() <= 1e-12);
+        assert!(
+            (result.second.0 - result.first.0 - EVec3::new(2.0, 3.0, 0.0)).magnitude() <= 1e-12
+        );
+
+        let overlapping = block_mesh(EVec3::new(0.5, 0.5, 0.5), EVec3::new(1.0, 1.0, 1.0));
+        assert_eq!(a.distance(&overlapping).unwrap().distance, 0.0);
+        assert!(a.distance(&TriMesh::default()).is_none());
+    }
+
+    #[test]
+    fn crossing_triangles() {
+        let a = [
+            EVec3::new(-1.0, -1.0, 0.0),
+            EVec3::new(1.0, -1.0, 0.0),
+            EVec3::new(0.0, 1.0, 0.0),
+        ];
+        let b = [
+            EVec3::new(0.0, 0.0, -1.0),
+            EVec3::new(0.0, 0.0, 1.0),
+            EVec3::new(0.0, 2.0, 1.0),
+        ];
+        let (distance, p, q) = triangle_distance(a, b, &Tolerance::default());
+        assert_eq!(distance, 0.0);
+        assert_eq!(p, q);
+
+        // The triangles cross along the segment from the origin to (0, 1, 0)
+        assert!(p.x.abs() <= 1e-12 && p.z.abs() <= 1e-12);
+        assert!((-1e-12..=1.0 + 1e-12).contains(&p.y));
+
+        // Lifted clear of the first triangle, the second is closest at its lowest
+        // corner
+        let lifted = b.map(|p| p + EVec3::new(0.0, 0.0, 1.5));
+        let (distance, p, q) = triangle_distance(a, lifted, &Tolerance::default());
+        assert!((distance - 0.5).abs() <= 1e-12);
+        assert!((p - EVec3::new(0.0, 0.0, 0.0)).magnitude() <= 1e-12);
+        assert!((q - EVec3::new(0.0, 0.0, 0.5)).magnitude() <= 1e-12);
+    }
+}
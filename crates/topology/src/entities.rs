@@ -0,0 +1,127 @@
+use space::{
+    hspace::{HSpace2, HSpace3},
+    EVec3,
+};
+use spline::{nurbs_curve::NurbsCurve, nurbs_surface::NurbsSurface};
+
+macro_rules! entity_id {
+    ( $name:ident ) => {
+        #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+        pub struct $name(pub(crate) usize);
+        impl $name {
+            pub fn index(&self) -> usize {
+                self.0
+            }
+        }
+    };
+}
+
+entity_id!(VertexId);
+entity_id!(HalfEdgeId);
+entity_id!(EdgeId);
+entity_id!(LoopId);
+entity_id!(FaceId);
+entity_id!(ShellId);
+
+/// Storage for one kind of topological entity. Removed entities leave a hole so
+/// the IDs of the remaining entities stay valid.
+#[derive(Debug, Clone)]
+pub(crate) struct Arena<T> {
+    items: Vec<Option<T>>,
+}
+impl<T> Arena<T> {
+    pub fn new() -> Self {
+        Self { items: Vec::new() }
+    }
+
+    /// The index the next inserted item will get
+    pub fn next_index(&self) -> usize {
+        self.items.len()
+    }
+
+    pub fn insert(&mut self, item: T) -> usize {
+        self.items.push(Some(item));
+        self.items.len() - 1
+    }
+
+    pub fn get(&self, index: usize) -> Option<&T> {
+        self.items.get(index).and_then(|item| item.as_ref())
+    }
+
+    pub fn get_mut(&mut self, index: usize) -> Option<&mut T> {
+        self.items.get_mut(index).and_then(|item| item.as_mut())
+    }
+
+    pub fn remove(&mut self, index: usize) -> Option<T> {
+        self.items.get_mut(index).and_then(|item| item.take())
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (usize, &T)> {
+        self.items
+            .iter()
+            .enumerate()
+            .filter_map(|(i, item)| item.as_ref().map(|item| (i, item)))
+    }
+
+    pub fn len(&self) -> usize {
+        self.items.iter().filter(|item| item.is_some()).count()
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Vertex {
+    pub point: EVec3,
+}
+
+/// One side of an edge, running in the direction of its loop. A loop made of a
+/// single vertex and no edges is represented by one half-edge without an edge.
+#[derive(Debug, Clone)]
+pub struct HalfEdge {
+    pub origin: VertexId,
+    pub edge: Option<EdgeId>,
+    pub loop_id: LoopId,
+    pub next: HalfEdgeId,
+    pub prev: HalfEdgeId,
+
+    /// The edge's curve in the parameter space of the face's surface
+    pub pcurve: Option<NurbsCurve<HSpace2>>,
+}
+
+/// A pair of half-edges between two vertices. The curve runs from the origin of
+/// the first half-edge to the origin of the second.
+#[derive(Debug, Clone)]
+pub struct Edge {
+    pub half_edges: [HalfEdgeId; 2],
+    pub curve: Option<NurbsCurve<HSpace3>>,
+}
+
+/// A closed cycle of half-edges bounding a face
+#[derive(Debug, Clone)]
+pub struct Loop {
+    pub half_edge: HalfEdgeId,
+    pub face: FaceId,
+}
+
+/// A bounded region of a surface. The outer loop runs counterclockwise when seen
+/// from outside the solid, and any inner loops (rings) run clockwise.
+#[derive(Debug, Clone)]
+pub struct Face {
+    pub outer_loop: LoopId,
+    pub loops: Vec<LoopId>,
+    pub shell: ShellId,
+    pub surface: Option<NurbsSurface<HSpace3>>,
+
+    /// Whether the surface normal points out of the solid
+    pub same_sense: bool,
+}
+impl Face {
+    pub fn inner_loops(&self) -> impl Iterator<Item = &LoopId> {
+        self.loops.iter().filter(|l| **l != self.outer_loop)
+    }
+}
+
+/// A connected set of faces enclosing a volume
+#[derive(Debug, Clone)]
+pub struct Shell {
+    pub faces: Vec<FaceId>,
+}
@@ -0,0 +1,165 @@
+use space::EVec3;
+use thiserror::Error;
+
+use crate::entities::{EdgeId, FaceId, HalfEdgeId, LoopId, VertexId};
+
+pub type TopologyResult<T> = Result<T, TopologyError>;
+
+#[derive(Debug, Error, PartialEq)]
+pub enum TopologyError {
+    #[error("Vertex {0:?} does not exist")]
+    MissingVertex(VertexId),
+
+    #[error("Half-edge {0:?} does not exist")]
+    MissingHalfEdge(HalfEdgeId),
+
+    #[error("Edge {0:?} does not exist")]
+    MissingEdge(EdgeId),
+
+    #[error("Half-edge {0:?} does not belong to an edge")]
+    HalfEdgeWithoutEdge(HalfEdgeId),
+
+    #[error("Loop {0:?} does not exist")]
+    MissingLoop(LoopId),
+
+    #[error("Face {0:?} does not exist")]
+    MissingFace(FaceId),
+
+    #[error("Half-edges {0:?} and {1:?} do not start at the same vertex")]
+    DifferentVertices(HalfEdgeId, HalfEdgeId),
+
+    #[error("Half-edges {0:?} and {1:?} are not in the same loop")]
+    DifferentLoops(HalfEdgeId, HalfEdgeId),
+
+    #[error("Edge {0:?} has the same loop on both sides")]
+    EdgeInSingleLoop(EdgeId),
+
+    #[error("Edge {0:?} separates two different loops")]
+    EdgeBetweenLoops(EdgeId),
+
+    #[error("Vertex {1:?} is not an end of edge {0:?}")]
+    NotEdgeVertex(EdgeId, VertexId),
+
+    #[error("Face {1:?} is not on either side of edge {0:?}")]
+    NotEdgeFace(EdgeId, FaceId),
+
+    #[error("A face cannot be merged into itself ({0:?})")]
+    SameFace(FaceId),
+
+    #[error("Loop {0:?} is not closed")]
+    OpenLoop(LoopId),
+
+    #[error("Loop {0:?} is not listed by its face")]
+    OrphanLoop(LoopId),
+
+    #[error("Half-edge {0:?} is not linked correctly to its neighbors")]
+    BrokenLinks(HalfEdgeId),
+
+    #[error("The half-edges of edge {0:?} do not run in opposite directions")]
+    MismatchedHalfEdges(EdgeId),
+
+    #[error("Edge {0:?} does not end at its vertices")]
+    MismatchedEdgeCurve(EdgeId),
+
+    #[error(
+        "Euler-Poincaré formula violated: V - E + F - R = {vertices} - {edges} + {faces} - {rings} \
+        is not a valid value for {shells} shell(s)"
+    )]
+    EulerPoincare {
+        vertices: usize,
+        edges: usize,
+        faces: usize,
+        rings: usize,
+        shells: usize,
+    },
+
+    #[error("Polygon {0} has fewer than 3 vertices or refers to a missing point")]
+    InvalidPolygon(usize),
+
+    #[error("Polygon {0} is degenerate and has no plane")]
+    DegeneratePolygon(usize),
+
+    #[error("More than two faces share the edge from point {0} to point {1}")]
+    NonManifoldEdge(usize, usize),
+
+    #[error("The edge from point {0} to point {1} has a face on only one side")]
+    OpenEdge(usize, usize),
+
+    #[error("A face kept from face {0:?} has a vertex the original solid does not")]
+    UnmatchedKeptFace(FaceId),
+
+    #[error("The size {0:?} is not positive along every axis")]
+    NonPositiveSize(EVec3),
+
+    #[error("Face {0:?} is not a planar polygon with straight edges")]
+    CurvedFace(FaceId),
+
+    #[error("Edge {0:?} is not a straight line")]
+    CurvedEdge(EdgeId),
+
+    #[error("Vertex {0:?} must join exactly three faces to blend an edge ending at it")]
+    BlendVertex(VertexId),
+
+    #[error("The faces on either side of edge {0:?} are tangent, so it cannot be blended")]
+    TangentFaces(EdgeId),
+
+    #[error("The blend {0} {1} is out of range")]
+    InvalidBlend(&'static str, f64),
+
+    #[error("The blend on edge {0:?} is too large for the faces around it")]
+    BlendTooLarge(EdgeId),
+
+    #[error("The blend on edge {0:?} is degenerate and has no surface normal")]
+    DegenerateBlend(EdgeId),
+
+    #[error("Face {0:?} has no half-edge from vertex {1:?} to vertex {2:?}")]
+    MissingFaceEdge(FaceId, VertexId, VertexId),
+
+    #[error("The thickness {0} is out of range")]
+    InvalidThickness(f64),
+
+    #[error("Removed faces may not share an edge, but both faces of edge {0:?} are removed")]
+    AdjacentRemovedFaces(EdgeId),
+
+    #[error("The offset faces around vertex {0:?} do not meet at a single point")]
+    ShellVertex(VertexId),
+
+    #[error("The walls are too thick for face {0:?}, which turns inside out when offset")]
+    ShellTooThick(FaceId),
+
+    #[error("The surface must be clamped at the edges of its domain")]
+    UnclampedSurface,
+
+    #[error("The offset surface folds over itself or has points where its normal is undefined")]
+    IrregularOffset,
+}
+
+pub type BooleanResult<T> = Result<T, BooleanError>;
+
+#[derive(Debug, Error, PartialEq)]
+pub enum BooleanError {
+    #[error(
+        "The {operand} operand is not closed: the edge from vertex {from} to vertex {to} \
+        does not have exactly one matching edge running the other way"
+    )]
+    NotClosed {
+        operand: &'static str,
+        from: usize,
+        to: usize,
+    },
+
+    #[error("Could not tell whether a point near {0:?} is inside the {1} operand")]
+    Classification(EVec3, &'static str),
+
+    #[error("Could not tessellate the {0} operand: {1}")]
+    Tessellation(&'static str, TopologyError),
+
+    #[error("The boundary of a merged face does not close up at {0:?}")]
+    OpenFaceBoundary(EVec3),
+
+    #[error("The hole at {0:?} does not lie inside any outer loop of its face")]
+    UnownedHole(EVec3),
+
+    #[error("The result is not a valid solid: {0}")]
+    InvalidResult(#[from] TopologyError),
+}
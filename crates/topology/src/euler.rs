@@ -0,0 +1,509 @@
+//! Euler operators, which change the topology of a solid while keeping the
+//! Euler-Poincaré formula `V - E + F - R = 2(S - H)` balanced. The algorithms
+//! follow Mäntylä, "An Introduction to Solid Modeling", chapter 11.
+
+use space::EVec3;
+
+use crate::{
+    entities::{
+        Edge, EdgeId, Face, FaceId, HalfEdge, HalfEdgeId, Loop, LoopId, Shell, ShellId, Vertex,
+        VertexId,
+    },
+    error::{TopologyError, TopologyResult},
+    solid::Solid,
+};
+
+impl Solid {
+    /// Make Vertex Face Shell: starts a new shell consisting of a single vertex and
+    /// a face whose only loop contains that vertex and no edges
+    pub fn mvfs(&mut self, point: EVec3) -> (VertexId, FaceId) {
+        let vertex = VertexId(self.vertices.insert(Vertex { point }));
+        let shell = ShellId(self.shells.insert(Shell { faces: Vec::new() }));
+        let face = FaceId(self.faces.next_index());
+        let loop_id = self.new_loop(face);
+
+        self.faces.insert(Face {
+            outer_loop: loop_id,
+            loops: vec![loop_id],
+            shell,
+            surface: None,
+            same_sense: true,
+        });
+        self.sh_mut(shell).faces.push(face);
+
+        let half_edge = self.new_half_edge(vertex, loop_id);
+        self.lp_mut(loop_id).half_edge = half_edge;
+
+        (vertex, face)
+    }
+
+    /// Make Edge Vertex: splits the vertex at the start of `he1` and `he2` into
+    /// two vertices joined by a new edge. The half-edges from `he1` around the vertex
+    /// up to (but not including) `he2` move to the new vertex. Passing the same
+    /// half-edge twice adds a dangling edge to a new vertex just before it.
+    pub fn mev(
+        &mut self,
+        he1: HalfEdgeId,
+        he2: HalfEdgeId,
+        point: EVec3,
+    ) -> TopologyResult<(VertexId, EdgeId)> {
+        let old_vertex = self.check_half_edge(he1)?.origin;
+        if self.check_half_edge(he2)?.origin != old_vertex {
+            return Err(TopologyError::DifferentVertices(he1, he2));
+        }
+
+        let new_vertex = VertexId(self.vertices.insert(Vertex { point }));
+        let edge = EdgeId(self.edges.next_index());
+
+        let mut he = he1;
+        while he != he2 {
+            self.he_mut(he).origin = new_vertex;
+            he = match self.twin(he) {
+                Some(twin) => self.he(twin).next,
+                None => break,
+            };
+        }
+
+        let minus = self.add_half_edge(edge, old_vertex, he1);
+        let plus = self.add_half_edge(edge, new_vertex, he2);
+        self.edges.insert(Edge {
+            half_edges: [plus, minus],
+            curve: None,
+        });
+
+        Ok((new_vertex, edge))
+    }
+
+    /// Make Edge Face: joins the start vertices of two half-edges in the same loop
+    /// with a new edge, splitting the loop in two. The half-edges from `he1` up to
+    /// (but not including) `he2` go to the new face, which inherits the surface of
+    /// the face it was split from.
+    pub fn mef(&mut self, he1: HalfEdgeId, he2: HalfEdgeId) -> TopologyResult<(EdgeId, FaceId)> {
+        let old_loop = self.check_half_edge(he1)?.loop_id;
+        if self.check_half_edge(he2)?.loop_id != old_loop {
+            return Err(TopologyError::DifferentLoops(he1, he2));
+        }
+
+        let old_face = self.lp(old_loop).face;
+        let (shell, surface, same_sense) = {
+            let face = self.fc(old_face);
+            (face.shell, face.surface.clone(), face.same_sense)
+        };
+
+        let new_face = FaceId(self.faces.next_index());
+        let new_loop = self.new_loop(new_face);
+        self.faces.insert(Face {
+            outer_loop: new_loop,
+            loops: vec![new_loop],
+            shell,
+            surface,
+            same_sense,
+        });
+        self.sh_mut(shell).faces.push(new_face);
+
+        let edge = EdgeId(self.edges.next_index());
+
+        let mut he = he1;
+        while he != he2 {
+            self.he_mut(he).loop_id = new_loop;
+            he = self.he(he).next;
+        }
+
+        let v1 = self.he(he1).origin;
+        let v2 = self.he(he2).origin;
+        let nhe1 = self.add_half_edge(edge, v2, he1);
+        let nhe2 = self.add_half_edge(edge, v1, he2);
+
+        let nhe1_prev = self.he(nhe1).prev;
+        let nhe2_prev = self.he(nhe2).prev;
+        self.he_mut(nhe1_prev).next = nhe2;
+        self.he_mut(nhe2_prev).next = nhe1;
+        self.he_mut(nhe1).prev = nhe2_prev;
+        self.he_mut(nhe2).prev = nhe1_prev;
+
+        self.edges.insert(Edge {
+            half_edges: [nhe1, nhe2],
+            curve: None,
+        });
+
+        self.lp_mut(new_loop).half_edge = nhe1;
+        self.lp_mut(old_loop).half_edge = nhe2;
+
+        Ok((edge, new_face))
+    }
+
+    /// Kill Edge Make Ring: removes an edge that has the same loop on both sides,
+    /// splitting the loop in two. The half-edges between `he` and its twin become a
+    /// new inner loop of the face.
+    pub fn kemr(&mut self, he: HalfEdgeId) -> TopologyResult<LoopId> {
+        let edge = self
+            .check_half_edge(he)?
+            .edge
+            .ok_or(TopologyError::HalfEdgeWithoutEdge(he))?;
+        let h1 = he;
+        let h2 = self.twin(h1).ok_or(TopologyError::MissingEdge(edge))?;
+
+        let old_loop = self.he(h1).loop_id;
+        if self.he(h2).loop_id != old_loop {
+            return Err(TopologyError::EdgeBetweenLoops(edge));
+        }
+
+        let face = self.lp(old_loop).face;
+        let new_loop = self.new_loop(face);
+        self.fc_mut(face).loops.push(new_loop);
+
+        let h3 = self.he(h1).next;
+        let h2_next = self.he(h2).next;
+        self.he_mut(h1).next = h2_next;
+        self.he_mut(h2_next).prev = h1;
+        self.he_mut(h2).next = h3;
+        self.he_mut(h3).prev = h2;
+
+        let mut h4 = h2;
+        loop {
+            self.he_mut(h4).loop_id = new_loop;
+            h4 = self.he(h4).next;
+            if h4 == h2 {
+                break;
+            }
+        }
+
+        let old_first = self.delete_half_edge(h1);
+        let new_first = self.delete_half_edge(h2);
+        self.lp_mut(old_loop).half_edge = old_first.unwrap();
+        self.lp_mut(new_loop).half_edge = new_first.unwrap();
+        self.edges.remove(edge.0);
+
+        Ok(new_loop)
+    }
+
+    /// Kill Face Make Ring Hole: removes `face`, turning its loops into inner loops
+    /// of `into`. If the faces are in the same shell this makes a through-hole;
+    /// otherwise the two shells are merged.
+    pub fn kfmrh(&mut self, into: FaceId, face: FaceId) -> TopologyResult<()> {
+        self.check_face(into)?;
+        self.check_face(face)?;
+        if into == face {
+            return Err(TopologyError::SameFace(face));
+        }
+
+        let removed = self.faces.remove(face.0).unwrap();
+        for loop_id in removed.loops.iter() {
+            self.lp_mut(*loop_id).face = into;
+        }
+        self.fc_mut(into).loops.extend(removed.loops);
+
+        let shell = self.fc(into).shell;
+        self.sh_mut(removed.shell).faces.retain(|f| *f != face);
+        if removed.shell != shell {
+            let merged = self.shells.remove(removed.shell.0).unwrap();
+            for f in merged.faces.iter() {
+                self.fc_mut(*f).shell = shell;
+            }
+            self.sh_mut(shell).faces.extend(merged.faces);
+        }
+
+        Ok(())
+    }
+
+    /// Kill Edge Vertex: removes an edge and one of its vertices, joining the
+    /// other edges at that vertex to the edge's other vertex. Inverse of `mev`.
+    pub fn kev(&mut self, edge: EdgeId, vertex: VertexId) -> TopologyResult<()> {
+        let [a, b] = self.check_edge(edge)?.half_edges;
+        let (he1, he2) = if self.he(a).origin == vertex {
+            (a, b)
+        } else if self.he(b).origin == vertex {
+            (b, a)
+        } else {
+            return Err(TopologyError::NotEdgeVertex(edge, vertex));
+        };
+
+        let kept = self.he(he2).origin;
+        let mut he = self.he(he2).next;
+        while he != he1 {
+            self.he_mut(he).origin = kept;
+            let twin = self
+                .twin(he)
+                .ok_or(TopologyError::HalfEdgeWithoutEdge(he))?;
+            he = self.he(twin).next;
+        }
+
+        let loop1 = self.he(he1).loop_id;
+        let first = self.delete_half_edge(he1);
+        self.lp_mut(loop1).half_edge = first.unwrap();
+
+        let loop2 = self.he(he2).loop_id;
+        let first = self.delete_half_edge(he2);
+        self.lp_mut(loop2).half_edge = first.unwrap();
+
+        self.edges.remove(edge.0);
+        self.vertices.remove(vertex.0);
+
+        Ok(())
+    }
+
+    /// Kill Edge Face: removes an edge along with `face`, one of the two faces it
+    /// separates. The loops of `face` are merged into the face on the other side.
+    /// Inverse of `mef`.
+    pub fn kef(&mut self, edge: EdgeId, face: FaceId) -> TopologyResult<()> {
+        let [a, b] = self.check_edge(edge)?.half_edges;
+        let (h1, h2) = if self.half_edge_face(b)? == face {
+            (a, b)
+        } else if self.half_edge_face(a)? == face {
+            (b, a)
+        } else {
+            return Err(TopologyError::NotEdgeFace(edge, face));
+        };
+
+        let l1 = self.he(h1).loop_id;
+        let l2 = self.he(h2).loop_id;
+        if l1 == l2 {
+            return Err(TopologyError::EdgeInSingleLoop(edge));
+        }
+
+        let kept_face = self.lp(l1).face;
+        if kept_face == face {
+            return Err(TopologyError::SameFace(face));
+        }
+
+        for he in self.loop_half_edges(l2)? {
+            self.he_mut(he).loop_id = l1;
+        }
+
+        let h1_prev = self.he(h1).prev;
+        let h2_prev = self.he(h2).prev;
+        self.he_mut(h1_prev).next = h2;
+        self.he_mut(h2).prev = h1_prev;
+        self.he_mut(h2_prev).next = h1;
+        self.he_mut(h1).prev = h2_prev;
+
+        self.delete_half_edge(h2);
+        let first = self.delete_half_edge(h1);
+        self.lp_mut(l1).half_edge = first.unwrap();
+
+        let removed = self.faces.remove(face.0).unwrap();
+        for loop_id in removed.loops.iter().filter(|l| **l != l2) {
+            self.lp_mut(*loop_id).face = kept_face;
+            self.fc_mut(kept_face).loops.push(*loop_id);
+        }
+        self.sh_mut(removed.shell).faces.retain(|f| *f != face);
+        self.loops.remove(l2.0);
+        self.edges.remove(edge.0);
+
+        Ok(())
+    }
+
+    fn new_loop(&mut self, face: FaceId) -> LoopId {
+        // The half-edge is filled in once the loop has one
+        LoopId(self.loops.insert(Loop {
+            half_edge: HalfEdgeId(usize::MAX),
+            face,
+        }))
+    }
+
+    /// Creates a half-edge that forms a loop on its own
+    pub(crate) fn new_half_edge(&mut self, origin: VertexId, loop_id: LoopId) -> HalfEdgeId {
+        let id = HalfEdgeId(self.half_edges.next_index());
+        self.half_edges.insert(HalfEdge {
+            origin,
+            edge: None,
+            loop_id,
+            next: id,
+            prev: id,
+            pcurve: None,
+        });
+        id
+    }
+
+    /// Adds a half-edge of `edge` starting at `vertex`, just before `before` in its
+    /// loop. A loop with no edges has its lone half-edge reused instead.
+    fn add_half_edge(&mut self, edge: EdgeId, vertex: VertexId, before: HalfEdgeId) -> HalfEdgeId {
+        if self.he(before).edge.is_none() {
+            let he = self.he_mut(before);
+            he.edge = Some(edge);
+            he.origin = vertex;
+            return before;
+        }
+
+        let loop_id = self.he(before).loop_id;
+        let prev = self.he(before).prev;
+        let id = self.new_half_edge(vertex, loop_id);
+
+        let he = self.he_mut(id);
+        he.edge = Some(edge);
+        he.next = before;
+        he.prev = prev;
+        self.he_mut(prev).next = id;
+        self.he_mut(before).prev = id;
+
+        id
+    }
+
+    /// Unlinks a half-edge from its loop. If it is the last half-edge in the loop it
+    /// is kept without an edge, so the loop still has a vertex. Returns a half-edge
+    /// that remains in the loop, if any.
+    fn delete_half_edge(&mut self, id: HalfEdgeId) -> Option<HalfEdgeId> {
+        let he = self.he(id);
+        if he.edge.is_none() {
+            self.half_edges.remove(id.0);
+            None
+        } else if he.next == id {
+            let he = self.he_mut(id);
+            he.edge = None;
+            he.pcurve = None;
+            Some(id)
+        } else {
+            let (prev, next) = (he.prev, he.next);
+            self.he_mut(prev).next = next;
+            self.he_mut(next).prev = prev;
+            self.half_edges.remove(id.0);
+            Some(prev)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use space::EVec3;
+
+    use crate::{error::TopologyError, solid::Solid};
+
+    #[test]
+    fn build_and_dismantle_tetrahedron() {
+        let mut solid = Solid::new();
+        let (v0, f0) = solid.mvfs(EVec3::new(0.0, 0.0, 0.0));
+
+        // Triangular lamina
+        let he = solid.half_edge_from(f0, v0).unwrap();
+        let (v1, _) = solid.mev(he, he, EVec3::new(1.0, 0.0, 0.0)).unwrap();
+        let he = solid.half_edge_from(f0, v1).unwrap();
+        let (v2, _) = solid.mev(he, he, EVec3::new(0.0, 1.0, 0.0)).unwrap();
+        let (_, f1) = solid
+            .mef(
+                solid.half_edge_between(f0, v0, v1).unwrap(),
+                solid.half_edge_from(f0, v2).unwrap(),
+            )
+            .unwrap();
+        solid.validate().unwrap();
+        assert_eq!(solid.num_faces(), 2);
+
+        // Raise an apex over the lamina and close the remaining faces
+        let he = solid.half_edge_from(f0, v0).unwrap();
+        let (v3, apex_edge) = solid.mev(he, he, EVec3::new(0.0, 0.0, 1.0)).unwrap();
+        let (_, f2) = solid
+            .mef(
+                solid.half_edge_between(f0, v3, v0).unwrap(),
+                solid.half_edge_from(f0, v1).unwrap(),
+            )
+            .unwrap();
+        let (diagonal, _) = solid
+            .mef(
+                solid.half_edge_between(f2, v3, v0).unwrap(),
+                solid.half_edge_from(f2, v2).unwrap(),
+            )
+            .unwrap();
+
+        solid.validate().unwrap();
+        assert_eq!(
+            (solid.num_vertices(), solid.num_edges(), solid.num_faces()),
+            (4, 6, 4)
+        );
+        assert_eq!(solid.genus(), 0);
+        assert!(f1 != f2);
+
+        // Undo the apex
+        let face = solid
+            .half_edge_face(solid.edge(diagonal).unwrap().half_edges[0])
+            .unwrap();
+        solid.kef(diagonal, face).unwrap();
+        let remaining = solid
+            .edges()
+            .find(|(id, edge)| {
+                *id != apex_edge
+                    && edge
+                        .half_edges
+                        .iter()
+                        .any(|he| solid.half_edge(*he).unwrap().origin == v3)
+            })
+            .map(|(id, _)| id)
+            .unwrap();
+        let face = solid
+            .half_edge_face(solid.edge(remaining).unwrap().half_edges[0])
+            .unwrap();
+        solid.kef(remaining, face).unwrap();
+        solid.kev(apex_edge, v3).unwrap();
+        assert_eq!(
+            solid.vertex_edges(v3),
+            Err(TopologyError::MissingVertex(v3))
+        );
+
+        solid.validate().unwrap();
+        assert_eq!(
+            (solid.num_vertices(), solid.num_edges(), solid.num_faces()),
+            (3, 3, 2)
+        );
+    }
+
+    #[test]
+    fn ring_and_hole() {
+        // Square lamina
+        let mut solid = Solid::new();
+        let (v0, bottom) = solid.mvfs(EVec3::new(0.0, 0.0, 0.0));
+        let mut last = v0;
+        for point in [
+            EVec3::new(4.0, 0.0, 0.0),
+            EVec3::new(4.0, 4.0, 0.0),
+            EVec3::new(0.0, 4.0, 0.0),
+        ] {
+            let he = solid.half_edge_from(bottom, last).unwrap();
+            last = solid.mev(he, he, point).unwrap().0;
+        }
+        let first = solid.half_edge_from(bottom, v0).unwrap();
+        let (_, top) = solid
+            .mef(first, solid.half_edge_from(bottom, last).unwrap())
+            .unwrap();
+
+        // A strut into the top face with a square chain of edges on its end
+        let he = solid.half_edge_from(top, v0).unwrap();
+        let (r0, strut) = solid.mev(he, he, EVec3::new(1.0, 1.0, 0.0)).unwrap();
+        let mut last = r0;
+        for point in [
+            EVec3::new(3.0, 1.0, 0.0),
+            EVec3::new(3.0, 3.0, 0.0),
+            EVec3::new(1.0, 3.0, 0.0),
+        ] {
+            let he = solid.half_edge_from(top, last).unwrap();
+            last = solid.mev(he, he, point).unwrap().0;
+        }
+        let (_, inner) = solid
+            .mef(
+                solid.half_edge_from(top, last).unwrap(),
+                solid.half_edge_between(top, r0, v0).unwrap(),
+            )
+            .unwrap();
+        solid.validate().unwrap();
+
+        // Removing the strut leaves the inner square as a ring of the top face
+        let strut_he = solid.half_edge_between(top, v0, r0).unwrap();
+        assert_eq!(solid.half_edge(strut_he).unwrap().edge, Some(strut));
+        solid.kemr(strut_he).unwrap();
+        solid.validate().unwrap();
+        assert_eq!(solid.num_rings(), 1);
+
+        // Merging the inner square's face into the bottom face as a ring makes a
+        // hole through the lamina
+        solid.kfmrh(bottom, inner).unwrap();
+        solid.validate().unwrap();
+        assert_eq!(solid.num_rings(), 2);
+        assert_eq!(solid.genus(), 1);
+    }
+
+    #[test]
+    fn kemr_needs_an_edge() {
+        // The only half-edge of a new face is the lone vertex, with no edge
+        let mut solid = Solid::new();
+        let (vertex, face) = solid.mvfs(EVec3::new(0.0, 0.0, 0.0));
+        let he = solid.half_edge_from(face, vertex).unwrap();
+        assert_eq!(solid.kemr(he), Err(TopologyError::HalfEdgeWithoutEdge(he)));
+    }
+}
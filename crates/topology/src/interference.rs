@@ -0,0 +1,539 @@
+//! Interference and clearance between bodies placed together in a scene. Pairs
+//! of triangles that could touch are found with the bodies' bounding volume
+//! hierarchies and then measured triangle to triangle. Results are only as good
+//! as the meshes: a triangulated planar body is the body itself, while a
+//! tessellated curved one strays from its faces by up to the tessellation's
+//! deviation, and nothing is refined against the faces. The volume the bodies
+//! share is found by integrating over the parts of each body's surface that lie
+//! inside the other, splitting the triangles that cross the other surface until
+//! the pieces can be classified whole or clipped by the plane they cross.
+
+use space::{EVec3, EVector, Tolerance, Transform3, VolumeIntegrals};
+
+use crate::{
+    bvh::{Aabb, Bvh, BvhContents},
+    distance::{closest_point_on_triangle, triangle_distance, MeshDistance},
+    mesh::TriMesh,
+};
+
+/// How many times a triangle crossing the other body's surface is split in four
+const MAX_DEPTH: usize = 6;
+
+/// Directions of the rays cast to tell whether a point is inside a body, tried
+/// in turn until one passes cleanly through the triangles it meets
+const RAY_DIRECTIONS: [[f64; 3]; 3] = [
+    [0.5377, 0.3141, 0.7832],
+    [-0.4142, 0.8660, 0.2718],
+    [0.1732, -0.6180, -0.7563],
+];
+
+/// Barycentric coordinates this close to a triangle's edge make a ray's hit
+/// too uncertain to count
+const EDGE_MARGIN: f64 = 1e-9;
+
+/// A closed mesh placed in a scene, along with the hierarchy over its triangles
+/// so it can be checked against many others
+#[derive(Debug, Clone)]
+pub struct PlacedMesh {
+    mesh: TriMesh,
+    bvh: Bvh,
+}
+impl PlacedMesh {
+    pub fn new(mesh: &TriMesh, transform: &Transform3) -> Self {
+        let mesh = mesh.transformed(transform);
+        let bvh = mesh.bvh();
+        Self { mesh, bvh }
+    }
+
+    /// The mesh in scene coordinates
+    pub fn mesh(&self) -> &TriMesh {
+        &self.mesh
+    }
+
+    pub fn bvh(&self) -> &Bvh {
+        &self.bvh
+    }
+
+    /// Whether a point lies inside the body. Points within the linear tolerance
+    /// of its surface may be found on either side.
+    pub fn contains(&self, point: EVec3, tolerance: &Tolerance) -> bool {
+        RAY_DIRECTIONS
+            .iter()
+            .find_map(|[x, y, z]| {
+                self.ray_parity(point, EVec3::new(*x, *y, *z).normalize(), tolerance)
+            })
+            .unwrap_or(false)
+    }
+
+    /// Finds how this body and another meet
+    pub fn interference(&self, other: &PlacedMesh, tolerance: &Tolerance) -> Interference {
+        let linear = tolerance.linear;
+
+        let mut first_contacts = Vec::new();
+        let mut second_contacts = Vec::new();
+        for (i, j) in self.bvh.overlapping_pairs(&other.bvh, linear) {
+            let (distance, _, _) = triangle_distance(
+                self.mesh.triangle_points(i),
+                other.mesh.triangle_points(j),
+                tolerance,
+            );
+            if distance <= linear {
+                first_contacts.push(i);
+                second_contacts.push(j);
+            }
+        }
+        for contacts in [&mut first_contacts, &mut second_contacts] {
+            contacts.sort_unstable();
+            contacts.dedup();
+        }
+
+        let clearance = self
+            .mesh
+            .distance_with(&self.bvh, &other.mesh, &other.bvh, tolerance);
+
+        let volume = match (self.bounds(), other.bounds()) {
+            (Some(a), Some(b)) if a.expand(linear).overlaps(&b) => {
+                // Integrate about the middle of the overlap to keep rounding small
+                let shared = Aabb {
+                    min: EVec3::new(
+                        a.min.x.max(b.min.x),
+                        a.min.y.max(b.min.y),
+                        a.min.z.max(b.min.z),
+                    ),
+                    max: EVec3::new(
+                        a.max.x.min(b.max.x),
+                        a.max.y.min(b.max.y),
+                        a.max.z.min(b.max.z),
+                    ),
+                };
+                let mut integrals = VolumeIntegrals::new();
+                Overlap::new(
+                    self,
+                    other,
+                    true,
+                    shared.center(),
+                    tolerance,
+                    &mut integrals,
+                )
+                .add_body(&first_contacts);
+                Overlap::new(
+                    other,
+                    self,
+                    false,
+                    shared.center(),
+                    tolerance,
+                    &mut integrals,
+                )
+                .add_body(&second_contacts);
+
+                // Overlaps no thicker than the tolerance over the whole contact
+                // are only touching
+                let total_area = |mesh: &TriMesh, contacts: &[usize]| -> f64 {
+                    contacts
+                        .iter()
+                        .map(|i| area(mesh.triangle_points(*i)))
+                        .sum()
+                };
+                let contact_area = total_area(&self.mesh, &first_contacts)
+                    .min(total_area(&other.mesh, &second_contacts));
+                if integrals.volume > linear * contact_area {
+                    integrals.volume
+                } else {
+                    0.0
+                }
+            }
+            _ => 0.0,
+        };
+
+        Interference {
+            volume,
+            clearance,
+            first_contacts,
+            second_contacts,
+        }
+    }
+
+    fn bounds(&self) -> Option<Aabb> {
+        self.bvh.root().map(|root| self.bvh.node(root).bounds)
+    }
+
+    /// Counts the triangles a ray crosses, returning whether the count is odd,
+    /// or `None` if the ray grazes a triangle too closely to tell
+    fn ray_parity(&self, origin: EVec3, dir: EVec3, tolerance: &Tolerance) -> Option<bool> {
+        let mut inside = false;
+        let mut stack: Vec<usize> = self.bvh.root().into_iter().collect();
+        while let Some(index) = stack.pop() {
+            let node = self.bvh.node(index);
+            if !ray_hits_box(&node.bounds.expand(tolerance.linear), origin, dir) {
+                continue;
+            }
+            match &node.contents {
+                BvhContents::Leaf(items) => {
+                    for item in items {
+                        let triangle = self.mesh.triangle_points(*item);
+                        if ray_crosses(triangle, origin, dir, tolerance)? {
+                            inside = !inside;
+                        }
+                    }
+                }
+                BvhContents::Branch(left, right) => stack.extend([*left, *right]),
+            }
+        }
+        Some(inside)
+    }
+}
+
+/// How two bodies meet
+#[derive(Debug, Clone, PartialEq)]
+pub struct Interference {
+    /// Volume inside both bodies. Overlaps no thicker than the linear tolerance
+    /// count as touching, with no volume.
+    pub volume: f64,
+
+    /// The closest points of the bodies' surfaces, which are 0 apart where they
+    /// touch or cross. `None` if either body has no triangles.
+    pub clearance: Option<MeshDistance>,
+
+    /// Triangles of the first body within the linear tolerance of the second
+    pub first_contacts: Vec<usize>,
+
+    /// Triangles of the second body within the linear tolerance of the first
+    pub second_contacts: Vec<usize>,
+}
+impl Interference {
+    /// Whether the bodies overlap, rather than only touching or standing apart
+    pub fn interferes(&self) -> bool {
+        self.volume > 0.0
+    }
+
+    /// The smallest distance between the bodies' surfaces
+    pub fn clearance_distance(&self) -> Option<f64> {
+        self.clearance.as_ref().map(|clearance| clearance.distance)
+    }
+}
+
+/// How one pair of bodies in a scene meet, with the bodies given by index
+#[derive(Debug, Clone, PartialEq)]
+pub struct PairInterference {
+    pub first: usize,
+    pub second: usize,
+    pub interference: Interference,
+}
+
+/// Checks every pair of bodies in a scene against each other
+pub fn check_pairs(bodies: &[PlacedMesh], tolerance: &Tolerance) -> Vec<PairInterference> {
+    let mut pairs = Vec::new();
+    for (first, a) in bodies.iter().enumerate() {
+        for (second, b) in bodies.iter().enumerate().skip(first + 1) {
+            pairs.push(PairInterference {
+                first,
+                second,
+                interference: a.interference(b, tolerance),
+            });
+        }
+    }
+    pairs
+}
+
+/// Integrates over the part of one body's surface inside another
+struct Overlap<'a> {
+    body: &'a PlacedMesh,
+    other: &'a PlacedMesh,
+
+    /// Whether surface shared with the other body and facing the same way is
+    /// counted. It bounds the overlap, so it is counted from one of the bodies.
+    keep_shared: bool,
+
+    origin: EVec3,
+    tolerance: &'a Tolerance,
+    integrals: &'a mut VolumeIntegrals,
+}
+impl<'a> Overlap<'a> {
+    fn new(
+        body: &'a PlacedMesh,
+        other: &'a PlacedMesh,
+        keep_shared: bool,
+        origin: EVec3,
+        tolerance: &'a Tolerance,
+        integrals: &'a mut VolumeIntegrals,
+    ) -> Self {
+        Self {
+            body,
+            other,
+            keep_shared,
+            origin,
+            tolerance,
+            integrals,
+        }
+    }
+
+    /// Adds the body's triangles, given the sorted indices of the ones within
+    /// the tolerance of the other body. The rest are wholly inside or outside.
+    fn add_body(&mut self, contacts: &[usize]) {
+        let Some(bounds) = self.other.bounds() else {
+            return;
+        };
+        let bounds = bounds.expand(self.tolerance.linear);
+
+        for i in 0..self.body.mesh.triangles.len() {
+            let points = self.body.mesh.triangle_points(i);
+            if contacts.binary_search(&i).is_ok() {
+                self.add_piece(points, 0);
+            } else if Aabb::from_triangle(&points).overlaps(&bounds)
+                && self.other.contains(centroid(points), self.tolerance)
+            {
+                self.add(&points);
+            }
+        }
+    }
+
+    /// Adds the part of a piece of a triangle that is inside the other body
+    fn add_piece(&mut self, piece: [EVec3; 3], depth: usize) {
+        let (mesh, tolerance) = (&self.other.mesh, self.tolerance);
+        let near = self
+            .other
+            .bvh
+            .overlapping(&Aabb::from_triangle(&piece).expand(tolerance.linear))
+            .into_iter()
+            .map(|i| mesh.triangle_points(i))
+            .filter(|triangle| triangle_distance(piece, *triangle, tolerance).0 <= tolerance.linear)
+            .collect::<Vec<_>>();
+
+        if near.is_empty() {
+            if self.other.contains(centroid(piece), tolerance) {
+                self.add(&piece);
+            }
+            return;
+        }
+
+        for triangle in near.iter() {
+            let on_triangle = piece.iter().all(|point| {
+                (closest_point_on_triangle(*point, *triangle) - *point).magnitude()
+                    <= tolerance.linear
+            });
+            if on_triangle {
+                self.add_shared(piece, *triangle);
+                return;
+            }
+        }
+
+        if depth < MAX_DEPTH {
+            let [a, b, c] = piece;
+            let (ab, bc, ca) = ((a + b) / 2.0, (b + c) / 2.0, (c + a) / 2.0);
+            for child in [[a, ab, ca], [ab, b, bc], [ca, bc, c], [ab, bc, ca]] {
+                self.add_piece(child, depth + 1);
+            }
+            return;
+        }
+
+        // Small enough to treat the nearest triangle's plane as the other
+        // body's surface
+        let center = centroid(piece);
+        let nearest = near
+            .iter()
+            .min_by(|a, b| {
+                let distance =
+                    |t: &[EVec3; 3]| (closest_point_on_triangle(center, *t) - center).magnitude();
+                distance(a).total_cmp(&distance(b))
+            })
+            .unwrap();
+        self.clip(piece, *nearest);
+    }
+
+    /// Adds the part of a piece behind the plane of one of the other body's
+    /// triangles
+    fn clip(&mut self, piece: [EVec3; 3], triangle: [EVec3; 3]) {
+        let normal = (triangle[1] - triangle[0]).cross(&(triangle[2] - triangle[0]));
+        if normal.magnitude() <= f64::EPSILON {
+            if self.other.contains(centroid(piece), self.tolerance) {
+                self.add(&piece);
+            }
+            return;
+        }
+        let normal = normal.normalize();
+        let side = |point: EVec3| normal.dot(&(point - triangle[0]));
+        if piece
+            .iter()
+            .all(|point| side(*point).abs() <= self.tolerance.linear)
+        {
+            // In the triangle's plane but only shared where it lies over it,
+            // as the faces of a body beside this one can be in line with it
+            let center = centroid(piece);
+            if (closest_point_on_triangle(center, triangle) - center).magnitude()
+                <= self.tolerance.linear
+            {
+                self.add_shared(piece, triangle);
+            } else if self.other.contains(center, self.tolerance) {
+                self.add(&piece);
+            }
+            return;
+        }
+
+        let mut kept = Vec::new();
+        for k in 0..3 {
+            let (p, q) = (piece[k], piece[(k + 1) % 3]);
+            let (dp, dq) = (side(p), side(q));
+            if dp < 0.0 {
+                kept.push(p);
+            }
+            if (dp < 0.0) != (dq < 0.0) {
+                kept.push(p + (q - p) * (dp / (dp - dq)));
+            }
+        }
+        self.add(&kept);
+    }
+
+    /// Adds a piece lying on the other body's surface if the two face the same
+    /// way and shared surface is kept. Surface where the bodies face each other
+    /// only touches.
+    fn add_shared(&mut self, piece: [EVec3; 3], triangle: [EVec3; 3]) {
+        let normal = |[a, b, c]: [EVec3; 3]| (b - a).cross(&(c - a));
+        if self.keep_shared && normal(piece).dot(&normal(triangle)) > 0.0 {
+            self.add(&piece);
+        }
+    }
+
+    /// Adds a convex polygon whose points run counterclockwise from outside
+    fn add(&mut self, polygon: &[EVec3]) {
+        for k in 1..polygon.len().saturating_sub(1) {
+            self.integrals.add_triangle(
+                polygon[0] - self.origin,
+                polygon[k] - self.origin,
+                polygon[k + 1] - self.origin,
+            );
+        }
+    }
+}
+
+fn centroid([a, b, c]: [EVec3; 3]) -> EVec3 {
+    (a + b + c) / 3.0
+}
+
+fn area([a, b, c]: [EVec3; 3]) -> f64 {
+    (b - a).cross(&(c - a)).magnitude() / 2.0
+}
+
+/// Whether a ray starting at `origin` passes through a box
+fn ray_hits_box(bounds: &Aabb, origin: EVec3, dir: EVec3) -> bool {
+    let axes = |v: EVec3| [v.x, v.y, v.z];
+    let (origin, dir, min, max) = (axes(origin), axes(dir), axes(bounds.min), axes(bounds.max));
+
+    let (mut near, mut far) = (0.0_f64, f64::MAX);
+    for k in 0..3 {
+        if dir[k].abs() <= f64::EPSILON {
+            if origin[k] < min[k] || origin[k] > max[k] {
+                return false;
+            }
+            continue;
+        }
+        let (t0, t1) = ((min[k] - origin[k]) / dir[k], (max[k] - origin[k]) / dir[k]);
+        near = near.max(t0.min(t1));
+        far = far.min(t0.max(t1));
+    }
+    near <= far
+}
+
+/// Whether a ray starting at `origin` passes through a triangle, or `None` if
+/// it passes too close to an edge or runs within the linear tolerance of its
+/// plane
+fn ray_crosses(
+    [a, b, c]: [EVec3; 3],
+    origin: EVec3,
+    dir: EVec3,
+    tolerance: &Tolerance,
+) -> Option<bool> {
+    // Möller and Trumbore, "Fast, Minimum Storage Ray/Triangle Intersection"
+    let (e1, e2) = (b - a, c - a);
+    let normal = e1.cross(&e2);
+    if normal.magnitude() <= f64::EPSILON * e1.magnitude() * e2.magnitude() {
+        return Some(false);
+    }
+
+    let p = dir.cross(&e2);
+    let det = e1.dot(&p);
+    if det.abs() <= EDGE_MARGIN * normal.magnitude() {
+        let in_plane = normal.normalize().dot(&(origin - a)).abs() <= tolerance.linear;
+        return if in_plane { None } else { Some(false) };
+    }
+
+    let s = origin - a;
+    let u = s.dot(&p) / det;
+    let q = s.cross(&e1);
+    let v = dir.dot(&q) / det;
+    let t = e2.dot(&q) / det;
+
+    if t <= 0.0 || u < -EDGE_MARGIN || v < -EDGE_MARGIN || u + v > 1.0 + EDGE_MARGIN {
+        Some(false)
+    } else if u < EDGE_MARGIN || v < EDGE_MARGIN || u + v > 1.0 - EDGE_MARGIN {
+        None
+    } else {
+        Some(true)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use space::{EPlacement3, EVec3, Tolerance, Transform3};
+
+    use crate::{mesh::TriMesh, solid::Solid};
+
+    use super::{check_pairs, PlacedMesh};
+
+    fn block(size: f64) -> TriMesh {
+        let size = EVec3::new(size, size, size);
+        TriMesh::from_solid(&Solid::block(&EPlacement3::default(), size).unwrap()).unwrap()
+    }
+
+    fn placed(mesh: &TriMesh, x: f64, y: f64, z: f64) -> PlacedMesh {
+        PlacedMesh::new(mesh, &Transform3::translation(EVec3::new(x, y, z)))
+    }
+
+    #[test]
+    fn interference_and_clearance() {
+        let tolerance = Tolerance::default();
+        let cube = block(1.0);
+        let a = placed(&cube, 0.0, 0.0, 0.0);
+        assert!(a.contains(EVec3::new(0.5, 0.5, 0.5), &tolerance));
+        assert!(!a.contains(EVec3::new(1.5, 0.5, 0.5), &tolerance));
+
+        // Crossing
+        let result = a.interference(&placed(&cube, 0.5, 0.25, 0.25), &tolerance);
+        assert!(result.interferes());
+        assert!((result.volume - 0.5 * 0.75 * 0.75).abs() <= 1e-9);
+        assert_eq!(result.clearance_distance(), Some(0.0));
+
+        // Overlapping with faces in common, one body mirrored into place
+        let mirrored = PlacedMesh::new(
+            &cube,
+            &Transform3::scale(EVec3::new(-1.0, 1.0, 1.0))
+                .then(&Transform3::translation(EVec3::new(1.5, 0.0, 0.0))),
+        );
+        let result = a.interference(&mirrored, &tolerance);
+        assert!((result.volume - 0.5).abs() <= 1e-9);
+
+        // Touching face to face
+        let result = a.interference(&placed(&cube, 1.0, 0.0, 0.0), &tolerance);
+        assert!(!result.interferes());
+        assert_eq!(result.clearance_distance(), Some(0.0));
+        assert!(!result.first_contacts.is_empty() && !result.second_contacts.is_empty());
+
+        // Apart by less than a loose tolerance, with faces in line beside the gap
+        let loose = Tolerance::new(1e-4, 1e-5);
+        let result = a.interference(&placed(&cube, 0.0, 0.0, 1.0 + 1e-5), &loose);
+        assert!(!result.interferes());
+        assert!(!result.first_contacts.is_empty());
+
+        // One inside the other
+        let inner = placed(&block(0.5), 0.25, 0.25, 0.25);
+        let result = a.interference(&inner, &tolerance);
+        assert!((result.volume - 0.125).abs() <= 1e-9);
+        assert!((result.clearance_distance().unwrap() - 0.25).abs() <= 1e-12);
+        assert!(result.first_contacts.is_empty());
+
+        // Apart, checked as a scene
+        let pairs = check_pairs(&[a, placed(&cube, 3.0, 0.0, 0.0)], &tolerance);
+        assert_eq!((pairs[0].first, pairs[0].second), (0, 1));
+        assert!(!pairs[0].interference.interferes());
+        assert!((pairs[0].interference.clearance_distance().unwrap() - 2.0).abs() <= 1e-12);
+    }
+}
@@ -0,0 +1,13 @@
+mod blend;
+pub mod builders;
+pub mod bvh;
+pub mod distance;
+pub mod entities;
+pub mod error;
+mod euler;
+pub mod interference;
+pub mod mesh;
+pub mod mesh_boolean;
+mod offset;
+pub mod solid;
+mod validate;
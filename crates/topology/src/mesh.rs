@@ -0,0 +1,493 @@
+use std::collections::{HashMap, HashSet};
+
+use space::{hspace::HSpace3, EPlacement3, EVec2, EVec3, EVector, Tolerance, Transform3, TOL};
+use spline::bezier_surface::BezierSurface;
+
+use crate::{
+    builders::PlanarFrame,
+    error::{TopologyError, TopologyResult},
+    solid::Solid,
+};
+
+/// A triangle mesh. Triangles wind counterclockwise when seen from outside the
+/// body they bound.
+#[derive(Debug, Clone, Default)]
+pub struct TriMesh {
+    pub vertices: Vec<EVec3>,
+    pub triangles: Vec<[usize; 3]>,
+}
+impl TriMesh {
+    pub fn new(vertices: Vec<EVec3>, triangles: Vec<[usize; 3]>) -> Self {
+        Self {
+            vertices,
+            triangles,
+        }
+    }
+
+    /// Triangulates the faces of a solid. Only planar faces bounded by straight
+    /// edges are supported.
+    pub fn from_solid(solid: &Solid) -> TopologyResult<Self> {
+        Self::from_solid_with(solid, &Tolerance::DEFAULT)
+    }
+
+    /// Triangulates the faces of a solid like [`Self::from_solid`], taking faces
+    /// to be planar if their vertices are within the linear tolerance of a plane
+    pub fn from_solid_with(solid: &Solid, tolerance: &Tolerance) -> TopologyResult<Self> {
+        let mut vertices = Vec::new();
+        let mut indices = HashMap::new();
+        for (id, vertex) in solid.vertices() {
+            indices.insert(id, vertices.len());
+            vertices.push(vertex.point);
+        }
+
+        let mut triangles = Vec::new();
+        for (id, face) in solid.faces() {
+            let loops = face
+                .loops
+                .iter()
+                .map(|l| {
+                    let vertices = solid.loop_vertices(*l)?;
+                    Ok(vertices
+                        .into_iter()
+                        .map(|v| indices[&v])
+                        .collect::<Vec<_>>())
+                })
+                .collect::<TopologyResult<Vec<_>>>()?;
+
+            let mut half_edges = Vec::new();
+            for l in face.loops.iter() {
+                half_edges.extend(solid.loop_half_edges(*l)?);
+            }
+            let straight = half_edges
+                .into_iter()
+                .filter_map(|he| solid.he(he).edge)
+                .all(|e| match &solid.ed(e).curve {
+                    Some(curve) => curve.degree() == 1,
+                    None => true,
+                });
+
+            let outer = loops[0].iter().map(|i| vertices[*i]).collect::<Vec<_>>();
+            let normal = match PlanarFrame::fit(&outer) {
+                Some(frame) if straight => frame.normal(),
+                _ => return Err(TopologyError::CurvedFace(id)),
+            };
+            let planar = loops
+                .iter()
+                .flatten()
+                .all(|i| (vertices[*i] - outer[0]).dot(&normal).abs() <= tolerance.linear);
+            if !planar {
+                return Err(TopologyError::CurvedFace(id));
+            }
+
+            triangles.extend(triangulate_polygon(&vertices, &loops, normal));
+        }
+
+        Ok(Self::new(vertices, triangles))
+    }
+
+    /// Samples a body made of Bezier patches on a grid of `divisions` by
+    /// `divisions` cells per patch. Patches that share a boundary and are sampled
+    /// at the same points along it are stitched together, as are the collapsed
+    /// edges of degenerate patches.
+    pub fn from_bezier_patches(patches: &[BezierSurface<HSpace3>], divisions: usize) -> Self {
+        let divisions = divisions.max(1);
+        let mut welder = PointWelder::new(TOL * WELD_FACTOR);
+        let mut triangles = Vec::new();
+
+        for patch in patches.iter() {
+            let mut grid = vec![vec![0; divisions + 1]; divisions + 1];
+            for (i, row) in grid.iter_mut().enumerate() {
+                for (j, index) in row.iter_mut().enumerate() {
+                    let u = i as f64 / divisions as f64;
+                    let v = j as f64 / divisions as f64;
+                    *index = welder.insert(patch.point(u, v));
+                }
+            }
+
+            for i in 0..divisions {
+                for j in 0..divisions {
+                    let (p00, p10) = (grid[i][j], grid[i + 1][j]);
+                    let (p01, p11) = (grid[i][j + 1], grid[i + 1][j + 1]);
+                    for triangle in [[p00, p10, p11], [p00, p11, p01]] {
+                        if triangle[0] != triangle[1]
+                            && triangle[1] != triangle[2]
+                            && triangle[2] != triangle[0]
+                        {
+                            triangles.push(triangle);
+                        }
+                    }
+                }
+            }
+        }
+
+        Self::new(welder.into_points(), triangles)
+    }
+
+    pub fn triangle_points(&self, index: usize) -> [EVec3; 3] {
+        self.triangles[index].map(|i| self.vertices[i])
+    }
+
+    /// Moves the mesh by an affine transform. Transforms that mirror reverse the
+    /// triangles so they still wind counterclockwise from outside.
+    pub fn transformed(&self, transform: &Transform3) -> Self {
+        let m = &transform.m;
+        let determinant = m[0][0] * (m[1][1] * m[2][2] - m[1][2] * m[2][1])
+            - m[0][1] * (m[1][0] * m[2][2] - m[1][2] * m[2][0])
+            + m[0][2] * (m[1][0] * m[2][1] - m[1][1] * m[2][0]);
+
+        Self::new(
+            self.vertices
+                .iter()
+                .map(|vertex| transform.apply_point(*vertex))
+                .collect(),
+            self.triangles
+                .iter()
+                .map(|&[a, b, c]| {
+                    if determinant < 0.0 {
+                        [a, c, b]
+                    } else {
+                        [a, b, c]
+                    }
+                })
+                .collect(),
+        )
+    }
+
+    /// Finds a directed edge that is not matched by exactly one edge running the
+    /// other way, if there is one
+    pub fn unmatched_edge(&self) -> Option<(usize, usize)> {
+        let mut counts = HashMap::<(usize, usize), usize>::new();
+        for [a, b, c] in self.triangles.iter() {
+            for edge in [(*a, *b), (*b, *c), (*c, *a)] {
+                *counts.entry(edge).or_insert(0) += 1;
+            }
+        }
+
+        counts
+            .iter()
+            .find(|((a, b), count)| **count != 1 || counts.get(&(*b, *a)) != Some(&1))
+            .map(|(edge, _)| *edge)
+    }
+
+    /// Whether the mesh bounds a volume, with every edge shared by exactly two
+    /// triangles running in opposite directions
+    pub fn is_closed(&self) -> bool {
+        self.unmatched_edge().is_none()
+    }
+}
+
+/// Points closer together than this many linear tolerances are merged when
+/// meshes are stitched
+pub(crate) const WELD_FACTOR: f64 = 10.0;
+
+/// Merges points that lie within a tolerance of each other, using a grid of cells
+/// the size of the tolerance
+pub(crate) struct PointWelder {
+    tolerance: f64,
+    points: Vec<EVec3>,
+    cells: HashMap<[i64; 3], Vec<usize>>,
+}
+impl PointWelder {
+    pub fn new(tolerance: f64) -> Self {
+        Self {
+            tolerance,
+            points: Vec::new(),
+            cells: HashMap::new(),
+        }
+    }
+
+    /// Returns the index of an existing point within the tolerance, or adds the
+    /// point and returns its new index
+    pub fn insert(&mut self, point: EVec3) -> usize {
+        let cell = self.cell(point);
+        for dx in -1..=1 {
+            for dy in -1..=1 {
+                for dz in -1..=1 {
+                    let neighbor = [cell[0] + dx, cell[1] + dy, cell[2] + dz];
+                    if let Some(existing) = self.cells.get(&neighbor).and_then(|indices| {
+                        indices
+                            .iter()
+                            .find(|i| (self.points[**i] - point).magnitude() <= self.tolerance)
+                    }) {
+                        return *existing;
+                    }
+                }
+            }
+        }
+
+        let index = self.points.len();
+        self.points.push(point);
+        self.cells.entry(cell).or_default().push(index);
+        index
+    }
+
+    pub fn points(&self) -> &[EVec3] {
+        &self.points
+    }
+
+    pub fn into_points(self) -> Vec<EVec3> {
+        self.points
+    }
+
+    fn cell(&self, point: EVec3) -> [i64; 3] {
+        [point.x, point.y, point.z].map(|c| (c / self.tolerance).floor() as i64)
+    }
+}
+
+/// Triangulates a planar polygon by ear clipping. The first loop is the outer
+/// boundary, running counterclockwise around `normal`, and any further loops are
+/// holes running clockwise. Holes are joined to the boundary by bridge edges
+/// before clipping.
+pub(crate) fn triangulate_polygon(
+    points: &[EVec3],
+    loops: &[Vec<usize>],
+    normal: EVec3,
+) -> Vec<[usize; 3]> {
+    let placement = EPlacement3::from_axis(points[loops[0][0]], normal);
+    let local = loops
+        .iter()
+        .flatten()
+        .map(|i| {
+            let p = placement.to_local(points[*i]);
+            (*i, EVec2::new(p.x, p.y))
+        })
+        .collect::<HashMap<_, _>>();
+
+    let mut polygon = loops[0].clone();
+    if signed_area(&polygon, &local) < 0.0 {
+        polygon.reverse();
+    }
+
+    let mut holes = loops[1..]
+        .iter()
+        .map(|hole| {
+            let mut hole = hole.clone();
+            if signed_area(&hole, &local) > 0.0 {
+                hole.reverse();
+            }
+            hole
+        })
+        .collect::<Vec<_>>();
+    holes.sort_by(|a, b| max_x(b, &local).total_cmp(&max_x(a, &local)));
+
+    for h in 0..holes.len() {
+        polygon = bridge_hole(&polygon, &holes[h], &holes[h + 1..], &local);
+    }
+
+    clip_ears(polygon, &local)
+}
+
+fn cross(o: EVec2, a: EVec2, b: EVec2) -> f64 {
+    (a.x - o.x) * (b.y - o.y) - (a.y - o.y) * (b.x - o.x)
+}
+
+fn signed_area(polygon: &[usize], local: &HashMap<usize, EVec2>) -> f64 {
+    (0..polygon.len())
+        .map(|k| {
+            let a = local[&polygon[k]];
+            let b = local[&polygon[(k + 1) % polygon.len()]];
+            a.x * b.y - b.x * a.y
+        })
+        .sum::<f64>()
+        / 2.0
+}
+
+fn max_x(polygon: &[usize], local: &HashMap<usize, EVec2>) -> f64 {
+    polygon.iter().map(|i| local[i].x).fold(f64::MIN, f64::max)
+}
+
+/// Whether two segments cross at a point inside both of them
+fn segments_cross(a: EVec2, b: EVec2, c: EVec2, d: EVec2) -> bool {
+    let (d1, d2) = (cross(a, b, c), cross(a, b, d));
+    let (d3, d4) = (cross(c, d, a), cross(c, d, b));
+    d1 * d2 < 0.0 && d3 * d4 < 0.0
+}
+
+/// Joins a hole to a counterclockwise polygon with a pair of bridge edges from the
+/// hole's rightmost vertex to the nearest polygon vertex it can see
+fn bridge_hole(
+    polygon: &[usize],
+    hole: &[usize],
+    other_holes: &[Vec<usize>],
+    local: &HashMap<usize, EVec2>,
+) -> Vec<usize> {
+    let m_pos = (0..hole.len())
+        .max_by(|a, b| local[&hole[*a]].x.total_cmp(&local[&hole[*b]].x))
+        .unwrap();
+    let m = hole[m_pos];
+    let mp = local[&m];
+
+    let edges = |ring: &[usize]| {
+        (0..ring.len())
+            .map(|k| (ring[k], ring[(k + 1) % ring.len()]))
+            .collect::<Vec<_>>()
+    };
+    let mut obstacles = edges(polygon);
+    obstacles.extend(edges(hole));
+    for other in other_holes.iter() {
+        obstacles.extend(edges(other));
+    }
+
+    let mut candidates = (0..polygon.len()).collect::<Vec<_>>();
+    candidates.sort_by(|a, b| {
+        let da = (local[&polygon[*a]] - mp).magnitude2();
+        let db = (local[&polygon[*b]] - mp).magnitude2();
+        da.total_cmp(&db)
+    });
+
+    let n = polygon.len();
+    let bridge = candidates.into_iter().find(|c| {
+        let v = polygon[*c];
+        let vp = local[&v];
+
+        // The bridge must leave the vertex into the polygon's interior
+        let prev = local[&polygon[(c + n - 1) % n]];
+        let next = local[&polygon[(c + 1) % n]];
+        let inside = if cross(prev, vp, next) > 0.0 {
+            cross(prev, vp, mp) > 0.0 && cross(vp, next, mp) > 0.0
+        } else {
+            cross(prev, vp, mp) > 0.0 || cross(vp, next, mp) > 0.0
+        };
+
+        inside
+            && obstacles.iter().all(|(a, b)| {
+                [*a, *b].contains(&v)
+                    || [*a, *b].contains(&m)
+                    || !segments_cross(vp, mp, local[a], local[b])
+            })
+    });
+
+    // Fall back to the nearest vertex if nothing is visible, which can only
+    // happen for self-intersecting input
+    let c = bridge.unwrap_or(0);
+
+    let mut bridged = polygon[..=c].to_vec();
+    bridged.extend(hole[m_pos..].iter().chain(hole[..=m_pos].iter()));
+    bridged.extend(polygon[c..].iter());
+    bridged
+}
+
+/// Clips ears off a counterclockwise polygon until only a triangle remains
+fn clip_ears(mut polygon: Vec<usize>, local: &HashMap<usize, EVec2>) -> Vec<[usize; 3]> {
+    let mut triangles = Vec::new();
+    let scale = polygon
+        .iter()
+        .map(|i| local[i].magnitude2())
+        .fold(0.0, f64::max)
+        .max(1.0);
+    let eps = TOL * TOL * scale;
+
+    while polygon.len() > 3 {
+        let n = polygon.len();
+        let ear = (0..n).find(|k| {
+            let (a, b, c) = (polygon[(k + n - 1) % n], polygon[*k], polygon[(k + 1) % n]);
+            let (pa, pb, pc) = (local[&a], local[&b], local[&c]);
+            if cross(pa, pb, pc) <= eps {
+                return false;
+            }
+
+            let corners = HashSet::from([a, b, c]);
+            polygon.iter().filter(|i| !corners.contains(i)).all(|i| {
+                let p = local[i];
+                cross(pa, pb, p) < -eps || cross(pb, pc, p) < -eps || cross(pc, pa, p) < -eps
+            })
+        });
+
+        match ear {
+            Some(k) => {
+                triangles.push([polygon[(k + n - 1) % n], polygon[k], polygon[(k + 1) % n]]);
+                polygon.remove(k);
+            }
+            None => {
+                // Drop a vertex that has no area on either side, or give up on a
+                // polygon that has no ears left
+                let flat = (0..n).find(|k| {
+                    let (a, b, c) = (polygon[(k + n - 1) % n], polygon[*k], polygon[(k + 1) % n]);
+                    cross(local[&a], local[&b], local[&c]).abs() <= eps
+                });
+                match flat {
+                    Some(k) => {
+                        polygon.remove(k);
+                    }
+                    None => return triangles,
+                }
+            }
+        }
+    }
+
+    if polygon.len() == 3 && cross(local[&polygon[0]], local[&polygon[1]], local[&polygon[2]]) > eps
+    {
+        triangles.push([polygon[0], polygon[1], polygon[2]]);
+    }
+
+    triangles
+}
+
+#[cfg(test)]
+mod tests {
+    use space::{EPlacement3, EVec2, EVec3, Tolerance};
+
+    use crate::{error::TopologyError, solid::Solid};
+
+    use super::TriMesh;
+
+    #[test]
+    fn triangulate_prism_with_hole() {
+        let outer = [
+            EVec2::new(0.0, 0.0),
+            EVec2::new(4.0, 0.0),
+            EVec2::new(4.0, 4.0),
+            EVec2::new(0.0, 4.0),
+        ];
+        let hole = vec![
+            EVec2::new(1.0, 1.0),
+            EVec2::new(3.0, 1.0),
+            EVec2::new(3.0, 3.0),
+            EVec2::new(1.0, 3.0),
+        ];
+        let solid = Solid::prism(&EPlacement3::default(), &outer, &[hole], 2.0).unwrap();
+        let mesh = TriMesh::from_solid(&solid).unwrap();
+
+        // 8 triangles for each end face and 2 for each of the 8 sides
+        assert_eq!(mesh.triangles.len(), 32);
+        assert!(mesh.is_closed());
+
+        let open = TriMesh::new(
+            vec![
+                EVec3::new(0.0, 0.0, 0.0),
+                EVec3::new(1.0, 0.0, 0.0),
+                EVec3::new(0.0, 1.0, 0.0),
+            ],
+            vec![[0, 1, 2]],
+        );
+        assert!(!open.is_closed());
+    }
+
+    #[test]
+    fn planar_within_tolerance() {
+        // A unit cube with one top corner raised a little, which warps the three
+        // faces around it
+        let mut points = (0..8)
+            .map(|i| EVec3::new((i & 1) as f64, (i >> 1 & 1) as f64, (i >> 2) as f64))
+            .collect::<Vec<_>>();
+        points[7].z += 1e-5;
+        let faces = [
+            [0, 2, 3, 1],
+            [4, 5, 7, 6],
+            [0, 1, 5, 4],
+            [2, 6, 7, 3],
+            [0, 4, 6, 2],
+            [1, 3, 7, 5],
+        ]
+        .map(|face| vec![face.to_vec()]);
+        let solid = Solid::from_polygons(&points, &faces).unwrap();
+
+        assert!(matches!(
+            TriMesh::from_solid(&solid),
+            Err(TopologyError::CurvedFace(_))
+        ));
+        let mesh = TriMesh::from_solid_with(&solid, &Tolerance::new(1e-4, 1e-5)).unwrap();
+        assert_eq!(mesh.triangles.len(), 12);
+        assert!(mesh.is_closed());
+    }
+}
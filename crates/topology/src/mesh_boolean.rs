@@ -0,0 +1,887 @@
+//! Boolean operations between closed bodies, carried out on triangle meshes. Each
+//! triangle of one body is split along the planes of the triangles of the other
+//! body that cross it, so that no fragment crosses the other body's surface. The
+//! fragments are then classified as inside, outside or on the other body, and the
+//! ones the operation keeps are stitched back together.
+//!
+//! This is only part of solid booleans. Solids are tessellated first, so curved
+//! faces come back as planar facets and the result is only exact for bodies with
+//! planar faces and straight edges. Booleans on the B-rep itself, with
+//! surface-surface intersection and trimming of Bezier and NURBS faces, are still
+//! to do (see `notes/roadmap.md`).
+
+use std::collections::{HashMap, HashSet};
+
+use space::{EPlacement3, EVec2, EVec3, EVector, Tolerance};
+
+use crate::{
+    builders::find_root,
+    error::{BooleanError, BooleanResult, TopologyError},
+    mesh::{triangulate_polygon, PointWelder, TriMesh, WELD_FACTOR},
+    solid::Solid,
+};
+
+/// The limits an operation works to, from the tolerances of the model
+#[derive(Debug, Clone, Copy)]
+struct Precision {
+    linear: f64,
+
+    /// Points closer than this are merged, and are taken to lie on planes this
+    /// close to them
+    weld: f64,
+
+    /// Normals whose dot product is at least this are treated as parallel
+    parallel_cos: f64,
+}
+impl Precision {
+    fn new(tolerance: &Tolerance) -> Self {
+        Self {
+            linear: tolerance.linear,
+            weld: tolerance.linear * WELD_FACTOR,
+            parallel_cos: tolerance.parallel_cos(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BooleanOp {
+    Union,
+    Subtract,
+    Intersect,
+}
+
+impl TriMesh {
+    /// Combines two closed meshes
+    pub fn boolean(&self, other: &TriMesh, op: BooleanOp) -> BooleanResult<TriMesh> {
+        self.boolean_with(other, op, &Tolerance::DEFAULT)
+    }
+
+    /// Combines two closed meshes, treating points within the linear tolerance as
+    /// the same and planes within the angular tolerance as parallel
+    pub fn boolean_with(
+        &self,
+        other: &TriMesh,
+        op: BooleanOp,
+        tolerance: &Tolerance,
+    ) -> BooleanResult<TriMesh> {
+        let precision = Precision::new(tolerance);
+        let (points, polygons) = boolean_polygons(self, other, op, &precision)?;
+        let mut triangles = Vec::new();
+        for (p, polygon) in polygons.iter().enumerate() {
+            let normal = newell_normal(&points, polygon, &precision)
+                .ok_or(TopologyError::DegeneratePolygon(p))?;
+            triangles.extend(triangulate_polygon(
+                &points,
+                std::slice::from_ref(polygon),
+                normal,
+            ));
+        }
+
+        Ok(TriMesh::new(points, triangles))
+    }
+}
+
+impl Solid {
+    /// Combines two solids by way of their tessellations. The result has planar
+    /// faces and straight edges, with coplanar neighboring faces merged, so any
+    /// curved faces of the operands are replaced by facets.
+    pub fn boolean(&self, other: &Solid, op: BooleanOp) -> BooleanResult<Solid> {
+        self.boolean_with(other, op, &Tolerance::DEFAULT)
+    }
+
+    /// Combines two solids like [`Self::boolean`], to the tolerances of a model
+    pub fn boolean_with(
+        &self,
+        other: &Solid,
+        op: BooleanOp,
+        tolerance: &Tolerance,
+    ) -> BooleanResult<Solid> {
+        let tessellate = |solid, operand| {
+            TriMesh::from_solid_with(solid, tolerance)
+                .map_err(|e| BooleanError::Tessellation(operand, e))
+        };
+        let (a, b) = (tessellate(self, "first")?, tessellate(other, "second")?);
+        let precision = Precision::new(tolerance);
+        let (points, polygons) = boolean_polygons(&a, &b, op, &precision)?;
+        let faces = merge_coplanar(&points, &polygons, &precision)?;
+
+        Ok(Solid::from_polygons(&points, &faces)?)
+    }
+
+    pub fn union(&self, other: &Solid) -> BooleanResult<Solid> {
+        self.boolean(other, BooleanOp::Union)
+    }
+
+    pub fn subtract(&self, other: &Solid) -> BooleanResult<Solid> {
+        self.boolean(other, BooleanOp::Subtract)
+    }
+
+    pub fn intersect(&self, other: &Solid) -> BooleanResult<Solid> {
+        self.boolean(other, BooleanOp::Intersect)
+    }
+}
+
+/// Where a fragment of one body lies relative to the other body. Fragments on the
+/// other body's surface are split by whether their normals agree.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Location {
+    Inside,
+    Outside,
+    OnSame,
+    OnOpposite,
+}
+
+struct Triangle {
+    points: [EVec3; 3],
+    normal: EVec3,
+    d: f64,
+    min: EVec3,
+    max: EVec3,
+}
+impl Triangle {
+    fn new(points: [EVec3; 3], precision: &Precision) -> Option<Self> {
+        let normal = (points[1] - points[0]).cross(&(points[2] - points[0]));
+        if normal.magnitude() <= precision.linear * precision.linear {
+            return None;
+        }
+
+        let normal = normal.normalize();
+        let component = |f: fn(f64, f64) -> f64| {
+            EVec3::new(
+                f(f(points[0].x, points[1].x), points[2].x),
+                f(f(points[0].y, points[1].y), points[2].y),
+                f(f(points[0].z, points[1].z), points[2].z),
+            )
+        };
+
+        Some(Self {
+            points,
+            normal,
+            d: normal.dot(&points[0]),
+            min: component(f64::min),
+            max: component(f64::max),
+        })
+    }
+
+    fn distance(&self, point: EVec3) -> f64 {
+        self.normal.dot(&point) - self.d
+    }
+
+    fn overlaps_box(&self, other: &Triangle, precision: &Precision) -> bool {
+        let weld = precision.weld;
+        self.min.x <= other.max.x + weld
+            && self.min.y <= other.max.y + weld
+            && self.min.z <= other.max.z + weld
+            && other.min.x <= self.max.x + weld
+            && other.min.y <= self.max.y + weld
+            && other.min.z <= self.max.z + weld
+    }
+
+    fn is_coplanar(&self, other: &Triangle, precision: &Precision) -> bool {
+        self.normal.dot(&other.normal).abs() >= precision.parallel_cos
+            && other
+                .points
+                .iter()
+                .all(|p| self.distance(*p).abs() <= precision.weld)
+    }
+
+    /// Whether the triangles cross along a segment of nonzero length
+    fn crosses(&self, other: &Triangle, precision: &Precision) -> bool {
+        let to_self = other.points.map(|p| snap(self.distance(p), precision));
+        let to_other = self.points.map(|p| snap(other.distance(p), precision));
+        let one_sided = |d: &[f64; 3]| d.iter().all(|d| *d >= 0.0) || d.iter().all(|d| *d <= 0.0);
+        if one_sided(&to_self) || one_sided(&to_other) {
+            return false;
+        }
+
+        let dir = self.normal.cross(&other.normal).normalize();
+        match (
+            chord(&self.points, &to_other, dir),
+            chord(&other.points, &to_self, dir),
+        ) {
+            (Some((min1, max1)), Some((min2, max2))) => {
+                min1.max(min2) < max1.min(max2) - precision.weld
+            }
+            _ => false,
+        }
+    }
+
+    /// Whether a point in the triangle's plane lies inside it
+    fn contains(&self, point: EVec3, precision: &Precision) -> bool {
+        (0..3).all(|k| {
+            let a = self.points[k];
+            let b = self.points[(k + 1) % 3];
+            let edge = b - a;
+            let inward = self.normal.cross(&edge).normalize();
+            inward.dot(&(point - a)) >= -precision.weld
+        })
+    }
+
+    /// Intersects a ray with the triangle. Returns `None` if the ray passes too
+    /// close to an edge or along the plane to give a reliable answer.
+    fn ray_hit(&self, origin: EVec3, dir: EVec3, precision: &Precision) -> Option<bool> {
+        let weld = precision.weld;
+        let e1 = self.points[1] - self.points[0];
+        let e2 = self.points[2] - self.points[0];
+        let p = dir.cross(&e2);
+        let det = e1.dot(&p);
+        if det.abs() <= precision.linear * e1.magnitude() * e2.magnitude() {
+            return if self.distance(origin).abs() <= weld {
+                None
+            } else {
+                Some(false)
+            };
+        }
+
+        let s = origin - self.points[0];
+        let u = s.dot(&p) / det;
+        let q = s.cross(&e1);
+        let v = dir.dot(&q) / det;
+        let t = e2.dot(&q) / det;
+
+        let eps = precision.linear;
+        if u < -eps || v < -eps || u + v > 1.0 + eps || t < -weld {
+            Some(false)
+        } else if u < eps || v < eps || u + v > 1.0 - eps || t <= weld {
+            None
+        } else {
+            Some(true)
+        }
+    }
+}
+
+fn snap(distance: f64, precision: &Precision) -> f64 {
+    if distance.abs() <= precision.weld {
+        0.0
+    } else {
+        distance
+    }
+}
+
+/// The extent along `dir` of the part of a triangle that lies in a plane, given
+/// the distances of its corners from the plane
+fn chord(points: &[EVec3; 3], distances: &[f64; 3], dir: EVec3) -> Option<(f64, f64)> {
+    let mut crossings = Vec::new();
+    for k in 0..3 {
+        let (p, q) = (points[k], points[(k + 1) % 3]);
+        let (dp, dq) = (distances[k], distances[(k + 1) % 3]);
+        if dp == 0.0 {
+            crossings.push(p);
+        }
+        if dp * dq < 0.0 {
+            crossings.push(p + (q - p) * (dp / (dp - dq)));
+        }
+    }
+
+    let projected = crossings.iter().map(|p| p.dot(&dir));
+    let min = projected.clone().fold(f64::MAX, f64::min);
+    let max = projected.fold(f64::MIN, f64::max);
+    (!crossings.is_empty()).then_some((min, max))
+}
+
+fn triangles(mesh: &TriMesh, precision: &Precision) -> Vec<Triangle> {
+    (0..mesh.triangles.len())
+        .filter_map(|i| Triangle::new(mesh.triangle_points(i), precision))
+        .collect()
+}
+
+/// Splits a triangle along the planes of the other body's triangles that cross it.
+/// A coplanar triangle splits it along its edges instead.
+fn split_triangle(
+    triangle: &Triangle,
+    others: &[Triangle],
+    precision: &Precision,
+) -> Vec<Vec<EVec3>> {
+    let mut planes: Vec<(EVec3, f64)> = Vec::new();
+    let mut add_plane = |normal: EVec3, d: f64| {
+        let duplicate = planes.iter().any(|(n, pd)| {
+            let dot = n.dot(&normal);
+            dot.abs() >= precision.parallel_cos && (pd * dot.signum() - d).abs() <= precision.weld
+        });
+        if !duplicate {
+            planes.push((normal, d));
+        }
+    };
+
+    for other in others
+        .iter()
+        .filter(|o| triangle.overlaps_box(o, precision))
+    {
+        if triangle.is_coplanar(other, precision) {
+            for k in 0..3 {
+                let a = other.points[k];
+                let b = other.points[(k + 1) % 3];
+                let normal = (b - a).cross(&other.normal).normalize();
+                add_plane(normal, normal.dot(&a));
+            }
+        } else if triangle.crosses(other, precision) {
+            add_plane(other.normal, other.d);
+        }
+    }
+
+    let mut fragments = vec![triangle.points.to_vec()];
+    for (normal, d) in planes {
+        fragments = fragments
+            .into_iter()
+            .flat_map(|fragment| split_polygon(fragment, normal, d, precision))
+            .collect();
+    }
+
+    fragments
+}
+
+/// Splits a convex polygon in two by a plane, or returns it unchanged if the plane
+/// does not cross it
+fn split_polygon(
+    polygon: Vec<EVec3>,
+    normal: EVec3,
+    d: f64,
+    precision: &Precision,
+) -> Vec<Vec<EVec3>> {
+    let distances = polygon
+        .iter()
+        .map(|p| snap(normal.dot(p) - d, precision))
+        .collect::<Vec<_>>();
+    if distances.iter().all(|d| *d >= 0.0) || distances.iter().all(|d| *d <= 0.0) {
+        return vec![polygon];
+    }
+
+    let mut front = Vec::new();
+    let mut back = Vec::new();
+    for k in 0..polygon.len() {
+        let next = (k + 1) % polygon.len();
+        let (p, q) = (polygon[k], polygon[next]);
+        let (dp, dq) = (distances[k], distances[next]);
+
+        if dp >= 0.0 {
+            front.push(p);
+        }
+        if dp <= 0.0 {
+            back.push(p);
+        }
+        if dp * dq < 0.0 {
+            let crossing = p + (q - p) * (dp / (dp - dq));
+            front.push(crossing);
+            back.push(crossing);
+        }
+    }
+
+    vec![front, back]
+}
+
+/// Directions to cast classification rays in, chosen to be unlikely to line up
+/// with the edges of typical models
+const RAY_DIRECTIONS: [(f64, f64, f64); 5] = [
+    (0.5773, 0.3815, 0.7218),
+    (-0.2875, 0.8121, 0.5078),
+    (0.6925, -0.5343, 0.4846),
+    (-0.4403, -0.3127, -0.8416),
+    (0.1139, 0.9647, -0.2376),
+];
+
+/// Classifies a point of a fragment with the given normal against a closed body by
+/// casting rays and counting how many times they cross the body's surface
+fn classify(
+    point: EVec3,
+    normal: EVec3,
+    others: &[Triangle],
+    operand: &'static str,
+    precision: &Precision,
+) -> BooleanResult<Location> {
+    for other in others.iter() {
+        let dot = normal.dot(&other.normal);
+        if dot.abs() >= precision.parallel_cos
+            && other.distance(point).abs() <= precision.weld
+            && other.contains(point, precision)
+        {
+            return Ok(if dot > 0.0 {
+                Location::OnSame
+            } else {
+                Location::OnOpposite
+            });
+        }
+    }
+
+    for (x, y, z) in RAY_DIRECTIONS {
+        let dir = EVec3::new(x, y, z).normalize();
+        let hits = others
+            .iter()
+            .map(|other| other.ray_hit(point, dir, precision))
+            .collect::<Option<Vec<_>>>();
+
+        if let Some(hits) = hits {
+            let crossings = hits.into_iter().filter(|hit| *hit).count();
+            return Ok(if crossings % 2 == 1 {
+                Location::Inside
+            } else {
+                Location::Outside
+            });
+        }
+    }
+
+    Err(BooleanError::Classification(point, operand))
+}
+
+/// Whether a fragment of one operand is kept in the result, and if so whether it
+/// is flipped
+fn keep(op: BooleanOp, first: bool, location: Location) -> Option<bool> {
+    match (op, first, location) {
+        (BooleanOp::Union, true, Location::Outside | Location::OnSame)
+        | (BooleanOp::Union, false, Location::Outside)
+        | (BooleanOp::Intersect, true, Location::Inside | Location::OnSame)
+        | (BooleanOp::Intersect, false, Location::Inside)
+        | (BooleanOp::Subtract, true, Location::Outside | Location::OnOpposite) => Some(false),
+        (BooleanOp::Subtract, false, Location::Inside) => Some(true),
+        _ => None,
+    }
+}
+
+/// Computes the polygons bounding the result of a boolean operation, sharing
+/// vertices wherever they meet
+fn boolean_polygons(
+    a: &TriMesh,
+    b: &TriMesh,
+    op: BooleanOp,
+    precision: &Precision,
+) -> BooleanResult<(Vec<EVec3>, Vec<Vec<usize>>)> {
+    for (mesh, operand) in [(a, "first"), (b, "second")] {
+        if let Some((from, to)) = mesh.unmatched_edge() {
+            return Err(BooleanError::NotClosed { operand, from, to });
+        }
+    }
+
+    let (ta, tb) = (triangles(a, precision), triangles(b, precision));
+    let mut polygons = Vec::new();
+    for (first, triangles, others, operand) in
+        [(true, &ta, &tb, "second"), (false, &tb, &ta, "first")]
+    {
+        for triangle in triangles.iter() {
+            for mut fragment in split_triangle(triangle, others, precision) {
+                let centroid = fragment.iter().copied().sum::<EVec3>() / fragment.len() as f64;
+                let location = classify(centroid, triangle.normal, others, operand, precision)?;
+                if let Some(flip) = keep(op, first, location) {
+                    if flip {
+                        fragment.reverse();
+                    }
+                    polygons.push(fragment);
+                }
+            }
+        }
+    }
+
+    Ok(stitch(polygons, precision.weld))
+}
+
+/// Merges the vertices of polygons that meet, drops polygons with no area, and
+/// inserts vertices that lie along other polygons' edges into those edges so every
+/// edge is matched by an edge of a neighboring polygon
+fn stitch(polygons: Vec<Vec<EVec3>>, weld: f64) -> (Vec<EVec3>, Vec<Vec<usize>>) {
+    let mut welder = PointWelder::new(weld);
+    let mut indexed = Vec::new();
+    for polygon in polygons {
+        let mut indices = polygon
+            .into_iter()
+            .map(|p| welder.insert(p))
+            .collect::<Vec<_>>();
+        indices.dedup();
+        while indices.len() > 1 && indices.first() == indices.last() {
+            indices.pop();
+        }
+
+        let points = welder.points();
+        let perimeter = (0..indices.len())
+            .map(|k| (points[indices[(k + 1) % indices.len()]] - points[indices[k]]).magnitude())
+            .sum::<f64>();
+        if indices.len() >= 3 && newell(points, &indices).magnitude() > weld * perimeter {
+            indexed.push(indices);
+        }
+    }
+    let points = welder.into_points();
+
+    let mut by_x = indexed
+        .iter()
+        .flatten()
+        .copied()
+        .collect::<HashSet<_>>()
+        .into_iter()
+        .collect::<Vec<_>>();
+    by_x.sort_by(|a, b| points[*a].x.total_cmp(&points[*b].x));
+
+    let repaired = indexed
+        .iter()
+        .map(|polygon| {
+            let mut repaired = Vec::with_capacity(polygon.len());
+            for k in 0..polygon.len() {
+                let (a, b) = (polygon[k], polygon[(k + 1) % polygon.len()]);
+                repaired.push(a);
+
+                let (pa, pb) = (points[a], points[b]);
+                let edge = pb - pa;
+                let length = edge.magnitude();
+                let (min_x, max_x) = (pa.x.min(pb.x) - weld, pa.x.max(pb.x) + weld);
+                let start = by_x.partition_point(|i| points[*i].x < min_x);
+
+                let mut between = by_x[start..]
+                    .iter()
+                    .take_while(|i| points[**i].x <= max_x)
+                    .filter_map(|i| {
+                        let t = (points[*i] - pa).dot(&edge) / (length * length);
+                        let off_line = (pa + edge * t - points[*i]).magnitude();
+                        (*i != a
+                            && *i != b
+                            && t * length > weld
+                            && (1.0 - t) * length > weld
+                            && off_line <= weld)
+                            .then_some((t, *i))
+                    })
+                    .collect::<Vec<_>>();
+                between.sort_by(|x, y| x.0.total_cmp(&y.0));
+                repaired.extend(between.into_iter().map(|(_, i)| i));
+            }
+            repaired
+        })
+        .collect();
+
+    (points, repaired)
+}
+
+fn newell(points: &[EVec3], polygon: &[usize]) -> EVec3 {
+    (0..polygon.len())
+        .map(|k| {
+            let a = points[polygon[k]];
+            let b = points[polygon[(k + 1) % polygon.len()]];
+            EVec3::new(
+                (a.y - b.y) * (a.z + b.z),
+                (a.z - b.z) * (a.x + b.x),
+                (a.x - b.x) * (a.y + b.y),
+            )
+        })
+        .sum()
+}
+
+/// The unit normal of a polygon, or `None` if it has too little area to have one
+fn newell_normal(points: &[EVec3], polygon: &[usize], precision: &Precision) -> Option<EVec3> {
+    let normal = newell(points, polygon);
+    let magnitude = normal.magnitude();
+    (magnitude > precision.linear * precision.linear).then(|| normal / magnitude)
+}
+
+/// Groups neighboring coplanar polygons into faces, each given as an outer loop
+/// followed by its holes. Vertices left in the middle of straight edges are
+/// removed. Fails if the boundary of a group doesn't form closed loops that nest
+/// properly.
+fn merge_coplanar(
+    points: &[EVec3],
+    polygons: &[Vec<usize>],
+    precision: &Precision,
+) -> BooleanResult<Vec<Vec<Vec<usize>>>> {
+    let planes = polygons
+        .iter()
+        .enumerate()
+        .map(|(p, polygon)| {
+            let normal = newell_normal(points, polygon, precision)
+                .ok_or(TopologyError::DegeneratePolygon(p))?;
+            Ok((normal, normal.dot(&points[polygon[0]])))
+        })
+        .collect::<BooleanResult<Vec<_>>>()?;
+
+    let mut edge_polygons = HashMap::new();
+    for (p, polygon) in polygons.iter().enumerate() {
+        for k in 0..polygon.len() {
+            edge_polygons.insert((polygon[k], polygon[(k + 1) % polygon.len()]), p);
+        }
+    }
+
+    let mut parents = (0..polygons.len()).collect::<Vec<_>>();
+    for (&(a, b), &p) in edge_polygons.iter() {
+        if let Some(&q) = edge_polygons.get(&(b, a)) {
+            let ((n1, d1), (n2, d2)) = (planes[p], planes[q]);
+            if n1.dot(&n2) >= precision.parallel_cos && (d1 - d2).abs() <= precision.weld {
+                let (root1, root2) = (find_root(&mut parents, p), find_root(&mut parents, q));
+                parents[root1] = root2;
+            }
+        }
+    }
+
+    let mut groups = HashMap::<usize, Vec<usize>>::new();
+    for p in 0..polygons.len() {
+        let root = find_root(&mut parents, p);
+        groups.entry(root).or_default().push(p);
+    }
+    let mut groups = groups.into_values().collect::<Vec<_>>();
+    groups.sort();
+
+    let mut faces = Vec::new();
+    for group in groups {
+        let edges = group
+            .iter()
+            .flat_map(|p| {
+                let polygon = &polygons[*p];
+                (0..polygon.len()).map(move |k| (polygon[k], polygon[(k + 1) % polygon.len()]))
+            })
+            .collect::<HashSet<_>>();
+
+        // Edges inside the group cancel out, leaving the boundary
+        let mut outgoing = HashMap::<usize, Vec<usize>>::new();
+        let mut boundary = edges
+            .iter()
+            .filter(|(a, b)| !edges.contains(&(*b, *a)))
+            .copied()
+            .collect::<Vec<_>>();
+        boundary.sort();
+        for (a, b) in boundary.iter() {
+            outgoing.entry(*a).or_default().push(*b);
+        }
+
+        let mut loops = Vec::new();
+        for (start, _) in boundary.iter() {
+            while let Some(mut current) = outgoing.get_mut(start).and_then(|o| o.pop()) {
+                let mut ring = vec![*start];
+                while current != *start {
+                    ring.push(current);
+                    current = outgoing
+                        .get_mut(&current)
+                        .and_then(|o| o.pop())
+                        .ok_or(BooleanError::OpenFaceBoundary(points[current]))?;
+                }
+                loops.push(ring);
+            }
+        }
+
+        let normal = planes[group[0]].0;
+        faces.extend(assign_holes(points, loops, normal)?);
+    }
+
+    remove_straight_vertices(points, &mut faces, precision.weld);
+    Ok(faces)
+}
+
+/// Sorts the loops of a planar region into outer loops, which run counterclockwise
+/// around the normal, each followed by the holes inside it. Fails if a hole is not
+/// inside any outer loop.
+fn assign_holes(
+    points: &[EVec3],
+    loops: Vec<Vec<usize>>,
+    normal: EVec3,
+) -> BooleanResult<Vec<Vec<Vec<usize>>>> {
+    let (outers, holes): (Vec<_>, Vec<_>) = loops
+        .into_iter()
+        .partition(|ring| newell(points, ring).dot(&normal) > 0.0);
+
+    let placement = EPlacement3::from_axis(EVec3::zero(), normal);
+    let planar = |i: usize| {
+        let p = placement.to_local(points[i]);
+        EVec2::new(p.x, p.y)
+    };
+    let area = |ring: &[usize]| newell(points, ring).dot(&normal).abs();
+
+    let mut faces = outers
+        .into_iter()
+        .map(|outer| vec![outer])
+        .collect::<Vec<_>>();
+    for hole in holes {
+        // The smallest outer loop that contains the hole
+        let p = planar(hole[0]);
+        let owner = (0..faces.len())
+            .filter(|f| {
+                let outer = &faces[*f][0];
+                let mut inside = false;
+                for k in 0..outer.len() {
+                    let a = planar(outer[k]);
+                    let b = planar(outer[(k + 1) % outer.len()]);
+                    if (a.y > p.y) != (b.y > p.y)
+                        && p.x < a.x + (p.y - a.y) * (b.x - a.x) / (b.y - a.y)
+                    {
+                        inside = !inside;
+                    }
+                }
+                inside
+            })
+            .min_by(|f1, f2| area(&faces[*f1][0]).total_cmp(&area(&faces[*f2][0])))
+            .ok_or(BooleanError::UnownedHole(points[hole[0]]))?;
+        faces[owner].push(hole);
+    }
+
+    Ok(faces)
+}
+
+/// Removes vertices that join exactly two collinear edges
+fn remove_straight_vertices(points: &[EVec3], faces: &mut [Vec<Vec<usize>>], weld: f64) {
+    let mut neighbors = HashMap::<usize, HashSet<usize>>::new();
+    for ring in faces.iter().flatten() {
+        for k in 0..ring.len() {
+            let (a, b) = (ring[k], ring[(k + 1) % ring.len()]);
+            neighbors.entry(a).or_default().insert(b);
+            neighbors.entry(b).or_default().insert(a);
+        }
+    }
+
+    let straight = neighbors
+        .iter()
+        .filter(|(v, n)| {
+            let mut n = n.iter();
+            match (n.next(), n.next(), n.next()) {
+                (Some(a), Some(b), None) => {
+                    let (pa, pb, pv) = (points[*a], points[*b], points[**v]);
+                    (pa - pv).cross(&(pb - pv)).magnitude() <= weld * (pb - pa).magnitude()
+                }
+                _ => false,
+            }
+        })
+        .map(|(v, _)| *v)
+        .collect::<HashSet<_>>();
+
+    for ring in faces.iter_mut().flatten() {
+        if ring.iter().filter(|v| !straight.contains(v)).count() >= 3 {
+            ring.retain(|v| !straight.contains(v));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use space::{EPlacement3, EVec3, EVector, Tolerance};
+
+    use crate::{error::BooleanError, mesh::TriMesh, solid::Solid};
+
+    use super::{assign_holes, merge_coplanar, newell_normal, BooleanOp, Precision};
+
+    fn cube(corner: (f64, f64, f64), size: f64) -> Solid {
+        Solid::block(
+            &EPlacement3::from_origin(EVec3::new(corner.0, corner.1, corner.2)),
+            EVec3::new(size, size, size),
+        )
+        .unwrap()
+    }
+
+    fn volume(solid: &Solid) -> f64 {
+        let mesh = TriMesh::from_solid(solid).unwrap();
+        (0..mesh.triangles.len())
+            .map(|i| {
+                let [a, b, c] = mesh.triangle_points(i);
+                a.dot(&b.cross(&c)) / 6.0
+            })
+            .sum()
+    }
+
+    #[test]
+    fn overlapping_cubes() {
+        let a = cube((0.0, 0.0, 0.0), 2.0);
+        let b = cube((1.0, 1.0, 1.0), 2.0);
+
+        let union = a.union(&b).unwrap();
+        assert_eq!(union.num_faces(), 12);
+        assert_eq!(union.genus(), 0);
+        assert!((volume(&union) - 15.0).abs() <= 1e-9);
+
+        let intersection = a.intersect(&b).unwrap();
+        assert_eq!(
+            (
+                intersection.num_vertices(),
+                intersection.num_edges(),
+                intersection.num_faces()
+            ),
+            (8, 12, 6)
+        );
+        assert!((volume(&intersection) - 1.0).abs() <= 1e-9);
+
+        let difference = a.subtract(&b).unwrap();
+        assert_eq!(difference.num_faces(), 9);
+        assert!((volume(&difference) - 7.0).abs() <= 1e-9);
+    }
+
+    #[test]
+    fn coincident_faces() {
+        let a = cube((0.0, 0.0, 0.0), 1.0);
+        let b = cube((1.0, 0.0, 0.0), 1.0);
+        let union = a.union(&b).unwrap();
+        assert_eq!(
+            (union.num_vertices(), union.num_edges(), union.num_faces()),
+            (8, 12, 6)
+        );
+        assert!((volume(&union) - 2.0).abs() <= 1e-9);
+
+        // A cube flush with the top of another and sunk into it makes a pocket
+        let c = cube((0.0, 0.0, 0.0), 3.0);
+        let d = cube((1.0, 1.0, 2.0), 1.0);
+        let pocket = c.subtract(&d).unwrap();
+        assert_eq!(pocket.num_faces(), 11);
+        assert_eq!(pocket.genus(), 0);
+        assert!((volume(&pocket) - 26.0).abs() <= 1e-9);
+    }
+
+    #[test]
+    fn open_operand() {
+        let closed = TriMesh::from_solid(&cube((0.0, 0.0, 0.0), 1.0)).unwrap();
+        let mut open = closed.clone();
+        open.triangles.pop();
+
+        assert!(matches!(
+            closed.boolean(&open, BooleanOp::Union),
+            Err(BooleanError::NotClosed {
+                operand: "second",
+                ..
+            })
+        ));
+    }
+
+    #[test]
+    fn loose_tolerance() {
+        // A cube a hair off the side of another is a separate body at the default
+        // tolerance, and flush with it at a looser one
+        let loose = Tolerance::new(1e-5, 1e-5);
+        let a = cube((0.0, 0.0, 0.0), 1.0);
+        let b = cube((1.0 + 2e-6, 0.0, 0.0), 1.0);
+
+        let strict = a.union(&b).unwrap();
+        assert_eq!((strict.num_shells(), strict.num_faces()), (2, 12));
+        let union = a.boolean_with(&b, BooleanOp::Union, &loose).unwrap();
+        assert_eq!(
+            (union.num_shells(), union.num_vertices(), union.num_faces()),
+            (1, 8, 6)
+        );
+        assert!((volume(&union) - 2.0).abs() <= 1e-5);
+
+        // Overlapping by less than the tolerance, they only touch
+        let c = cube((0.5, 0.5, 1.0 - 2e-6), 1.0);
+        let [a, c] = [&a, &c].map(|solid| TriMesh::from_solid(solid).unwrap());
+        let overlap = a.boolean_with(&c, BooleanOp::Intersect, &loose).unwrap();
+        assert!(overlap.triangles.is_empty());
+    }
+
+    #[test]
+    fn broken_faces() {
+        let precision = Precision::new(&Tolerance::DEFAULT);
+        let points = [
+            EVec3::new(0.0, 0.0, 0.0),
+            EVec3::new(1.0, 0.0, 0.0),
+            EVec3::new(1.0, 1.0, 0.0),
+            EVec3::new(2.0, 0.5, 0.0),
+            EVec3::new(0.5, 0.8, 0.0),
+            EVec3::new(-1.0, -0.5, 0.0),
+            EVec3::new(2.0, 0.0, 0.0),
+        ];
+        assert_eq!(newell_normal(&points, &[0, 1, 6], &precision), None);
+
+        // Two overlapping polygons run along the same edge, which leaves a
+        // boundary that doesn't close
+        let polygons = [
+            vec![0, 1, 4],
+            vec![0, 4, 5],
+            vec![1, 0, 5],
+            vec![0, 1, 2],
+            vec![2, 1, 3],
+        ];
+        assert_eq!(
+            merge_coplanar(&points, &polygons, &precision),
+            Err(BooleanError::OpenFaceBoundary(points[0]))
+        );
+
+        // A hole with no outer loop around it
+        let normal = EVec3::new(0.0, 0.0, 1.0);
+        assert_eq!(
+            assign_holes(&points, vec![vec![0, 2, 1]], normal),
+            Err(BooleanError::UnownedHole(points[0]))
+        );
+    }
+}
@@ -0,0 +1,677 @@
+//! Operations that offset the faces of a solid or a surface to give material a
+//! thickness: hollowing shells out a solid, and thickening turns a surface into a
+//! solid.
+
+use std::collections::BTreeMap;
+
+use space::{
+    hspace::{HSpace, HSpace2, HSpace3},
+    EVec2, EVec3, EVec4, EVector, HVec3, Tolerance,
+};
+use spline::{
+    math::{knot_vector::KnotVector, nurbs::curve_decompose},
+    nurbs_curve::NurbsCurve,
+    nurbs_surface::NurbsSurface,
+};
+
+use crate::{
+    builders::PlanarFrame,
+    entities::FaceId,
+    error::{TopologyError, TopologyResult},
+    solid::Solid,
+};
+
+impl Solid {
+    /// Hollows out a solid whose faces are planar polygons, leaving walls of the
+    /// given thickness. Each face is offset into the solid, and the offset faces
+    /// become the inside of the walls. The removed faces are left open: they are
+    /// not offset, and are replaced by rims joining the outside of the walls to
+    /// the inside. If no faces are removed, the solid is left with a closed cavity.
+    /// Removed faces may not share edges with each other.
+    pub fn hollow(&self, thickness: f64, removed: &[FaceId]) -> TopologyResult<Solid> {
+        self.hollow_with(thickness, removed, &Tolerance::DEFAULT)
+    }
+
+    /// Hollows out a solid like [`Self::hollow`], to the tolerances of a model
+    pub fn hollow_with(
+        &self,
+        thickness: f64,
+        removed: &[FaceId],
+        tolerance: &Tolerance,
+    ) -> TopologyResult<Solid> {
+        if !thickness.is_finite() || thickness <= 0.0 {
+            return Err(TopologyError::InvalidThickness(thickness));
+        }
+
+        for face in removed.iter() {
+            self.check_face(*face)?;
+        }
+        for (id, edge) in self.edges() {
+            let [a, b] = edge.half_edges;
+            let (face_a, face_b) = (self.half_edge_face(a)?, self.half_edge_face(b)?);
+            if removed.contains(&face_a) && removed.contains(&face_b) {
+                return Err(TopologyError::AdjacentRemovedFaces(id));
+            }
+            if matches!(&edge.curve, Some(curve) if curve.degree() != 1) {
+                return Err(TopologyError::CurvedEdge(id));
+            }
+        }
+
+        // The plane of each face after offsetting, as a normal and the normal's dot
+        // product with points on the plane
+        let mut planes = BTreeMap::new();
+        for (id, face) in self.faces() {
+            let normal = self.planar_frame(id)?.normal();
+            let point = self.loop_points(face.outer_loop)?[0];
+            let offset = if removed.contains(&id) {
+                0.0
+            } else {
+                thickness
+            };
+            planes.insert(id, (normal, normal.dot(&point) - offset));
+        }
+
+        let mut polygons = self.to_polygons()?;
+        let mut inner = BTreeMap::new();
+        for (v, vertex) in polygons.vertices.iter().enumerate() {
+            let Some(id) = *vertex else {
+                continue;
+            };
+            let mut vertex_planes = Vec::new();
+            for e in self.vertex_edges(id)? {
+                for he in self.check_edge(e)?.half_edges {
+                    let face = self.half_edge_face(he)?;
+                    let plane = planes.get(&face).ok_or(TopologyError::MissingFace(face))?;
+                    vertex_planes.push(*plane);
+                }
+            }
+
+            let point = offset_vertex(self.vx(id).point, &vertex_planes, tolerance)
+                .ok_or(TopologyError::ShellVertex(id))?;
+            inner.insert(v, polygons.points.len());
+            polygons.points.push(point);
+        }
+        polygons.vertices.extend(vec![None; inner.len()]);
+
+        let inner_loop = |f: usize, ring: &[usize]| {
+            ring.iter()
+                .map(|i| {
+                    inner
+                        .get(i)
+                        .copied()
+                        .ok_or(TopologyError::InvalidPolygon(f))
+                })
+                .collect::<TopologyResult<Vec<_>>>()
+        };
+        let points = &polygons.points;
+        let normal = |ring: &[usize]| {
+            let ring = ring.iter().map(|i| points[*i]).collect::<Vec<_>>();
+            PlanarFrame::fit(&ring).map(|frame| frame.normal())
+        };
+
+        // The inside of the walls faces the other way to the outside. Offsetting a
+        // face too far turns its loops inside out.
+        let mut inner_faces = Vec::new();
+        let mut rims = Vec::new();
+        for (f, (loops, source)) in polygons.faces.iter().zip(&polygons.sources).enumerate() {
+            let Some(source) = *source else {
+                continue;
+            };
+            let mut offsets = Vec::with_capacity(loops.len());
+            for ring in loops.iter() {
+                let mut offset = inner_loop(f, ring)?;
+                let flipped = match (normal(ring), normal(&offset)) {
+                    (Some(outer), Some(inner)) => outer.dot(&inner) <= 0.0,
+                    _ => true,
+                };
+                if flipped {
+                    return Err(TopologyError::ShellTooThick(source));
+                }
+                offset.reverse();
+                offsets.push(offset);
+            }
+
+            if removed.contains(&source) {
+                // Each loop of a removed face becomes a rim between it and its
+                // offset, which lies inside an outer loop and outside a hole
+                for (l, (ring, offset)) in loops.iter().zip(offsets).enumerate() {
+                    rims.push((
+                        f,
+                        l,
+                        if l == 0 {
+                            vec![ring.clone(), offset]
+                        } else {
+                            vec![offset, ring.clone()]
+                        },
+                    ));
+                }
+            } else {
+                inner_faces.push((source, offsets));
+            }
+        }
+
+        for (f, l, rim) in rims {
+            if l == 0 {
+                polygons.faces[f] = rim;
+                polygons.sources[f] = None;
+            } else {
+                polygons.faces.push(rim);
+                polygons.sources.push(None);
+            }
+        }
+        let first_inner = polygons.faces.len();
+        let sources = inner_faces
+            .iter()
+            .map(|(source, _)| *source)
+            .collect::<Vec<_>>();
+        polygons.sources.extend(vec![None; inner_faces.len()]);
+        polygons
+            .faces
+            .extend(inner_faces.into_iter().map(|(_, loops)| loops));
+
+        let (mut solid, _) = self.rebuild(&polygons)?;
+
+        // The surface inside each wall is the offset of the plane outside it,
+        // grown to cover the inner face
+        for (k, source) in sources.into_iter().enumerate() {
+            let face = FaceId(first_inner + k);
+            let mut frame = self.planar_frame(source)?;
+            for l in solid.check_face(face)?.loops.clone() {
+                frame.include(&solid.loop_points(l)?);
+            }
+
+            let offset = frame
+                .surface()
+                .offset_with(-thickness, tolerance.linear, tolerance);
+            if !offset.is_regular() {
+                return Err(TopologyError::IrregularOffset);
+            }
+            let same_sense = match offset.surface.normal(0.5, 0.5) {
+                Some(normal) => normal.dot(&solid.outer_loop_normal(face)?) > 0.0,
+                None => return Err(TopologyError::IrregularOffset),
+            };
+            solid.set_face_surface(face, offset.surface, same_sense)?;
+
+            for he in solid.face_half_edges(face)? {
+                let from = solid.vx(solid.he(he).origin).point;
+                let to = solid.vx(solid.half_edge_target(he)?).point;
+                let (from, to) = (frame.parameter(from), frame.parameter(to));
+                solid.set_pcurve(he, NurbsCurve::<HSpace2>::line(from, to))?;
+            }
+        }
+
+        solid.validate_with(tolerance)?;
+        Ok(solid)
+    }
+
+    /// Builds a solid from a surface by offsetting it along its normal. The solid is
+    /// bounded by the surface, its offset, and ruled faces joining their
+    /// boundaries. A negative thickness offsets the surface against its normal. The
+    /// offset is approximated to within `tolerance`, and must not fold over itself
+    /// or pass through points where the surface has no normal.
+    pub fn thicken(
+        surface: &NurbsSurface<HSpace3>,
+        thickness: f64,
+        tolerance: f64,
+    ) -> TopologyResult<Solid> {
+        Self::thicken_with(surface, thickness, tolerance, &Tolerance::DEFAULT)
+    }
+
+    /// Builds a solid from a surface like [`Self::thicken`], to the tolerances of
+    /// a model rather than the default ones
+    pub fn thicken_with(
+        surface: &NurbsSurface<HSpace3>,
+        thickness: f64,
+        tolerance: f64,
+        model: &Tolerance,
+    ) -> TopologyResult<Solid> {
+        if !thickness.is_finite() || thickness == 0.0 {
+            return Err(TopologyError::InvalidThickness(thickness));
+        }
+
+        let clamped = |knots: &KnotVector, degree: usize| {
+            knots.find_multiplicity(knots.first()) > degree
+                && knots.find_multiplicity(knots.last()) > degree
+        };
+        if !clamped(surface.knot_vector_u(), surface.degree_u())
+            || !clamped(surface.knot_vector_v(), surface.degree_v())
+        {
+            return Err(TopologyError::UnclampedSurface);
+        }
+
+        let offset = surface.offset_with(thickness, tolerance, model);
+        if !offset.is_regular() {
+            return Err(TopologyError::IrregularOffset);
+        }
+        let offset = offset.surface;
+
+        let (min_u, max_u) = (surface.min_u(), surface.max_u());
+        let (min_v, max_v) = (surface.min_v(), surface.max_v());
+        let corners = [
+            EVec2::new(min_u, min_v),
+            EVec2::new(max_u, min_v),
+            EVec2::new(max_u, max_v),
+            EVec2::new(min_u, max_v),
+        ];
+
+        // Points 0-3 are the corners of the surface and 4-7 those of its offset,
+        // both counterclockwise around the surface's normal
+        let points = corners
+            .iter()
+            .map(|c| surface.point(c.x, c.y))
+            .chain(corners.iter().map(|c| offset.point(c.x, c.y)))
+            .collect::<Vec<_>>();
+
+        // Side k joins the boundaries from corner k to corner k + 1
+        let sides = (0..4)
+            .map(|k| (k, (k + 1) % 4))
+            .map(|(a, b)| {
+                let boundary = match (a, b) {
+                    (0, 1) => Boundary::MinV,
+                    (1, 2) => Boundary::MaxU,
+                    (2, 3) => Boundary::MaxV,
+                    _ => Boundary::MinU,
+                };
+                (a, b, boundary)
+            })
+            .collect::<Vec<_>>();
+
+        let mut faces = vec![vec![vec![3, 2, 1, 0]], vec![vec![4, 5, 6, 7]]];
+        faces.extend(
+            sides
+                .iter()
+                .map(|(a, b, _)| vec![vec![*a, *b, b + 4, a + 4]]),
+        );
+        if thickness < 0.0 {
+            for loops in faces.iter_mut() {
+                loops[0].reverse();
+            }
+        }
+
+        let (mut solid, vertices) = Self::from_polygons_mapped(&points, &faces)?;
+
+        // The surfaces of the faces, and the parameters of their corners
+        let mut face_surfaces = vec![
+            (
+                surface.clone(),
+                (0..4).map(|k| (k, corners[k])).collect::<Vec<_>>(),
+            ),
+            (
+                offset.clone(),
+                (0..4).map(|k| (k + 4, corners[k])).collect(),
+            ),
+        ];
+        for (a, b, boundary) in sides.iter() {
+            let side = ruled_surface(surface, &offset, *boundary);
+            let (t_a, t_b) = match boundary {
+                Boundary::MinV | Boundary::MaxU => (0.0, 1.0),
+                Boundary::MaxV | Boundary::MinU => (1.0, 0.0),
+            };
+            let (t_a, t_b) = (
+                side.min_v() + (side.max_v() - side.min_v()) * t_a,
+                side.min_v() + (side.max_v() - side.min_v()) * t_b,
+            );
+            let (min_s, max_s) = (side.min_u(), side.max_u());
+            face_surfaces.push((
+                side,
+                vec![
+                    (*a, EVec2::new(min_s, t_a)),
+                    (*b, EVec2::new(min_s, t_b)),
+                    (b + 4, EVec2::new(max_s, t_b)),
+                    (a + 4, EVec2::new(max_s, t_a)),
+                ],
+            ));
+        }
+
+        for (f, (face_surface, params)) in face_surfaces.into_iter().enumerate() {
+            let face = FaceId(f);
+            let outward = solid.outer_loop_normal(face)?;
+            let mid = (params[0].1 + params[2].1) / 2.0;
+            let same_sense = match face_surface.normal(mid.x, mid.y) {
+                Some(normal) => normal.dot(&outward) > 0.0,
+                None => true,
+            };
+            solid.set_face_surface(face, face_surface, same_sense)?;
+
+            let params = params
+                .into_iter()
+                .map(|(point, param)| (vertices[&point], param))
+                .collect::<BTreeMap<_, _>>();
+            for he in solid.loop_half_edges(solid.fc(face).outer_loop)? {
+                let (from, to) = (solid.he(he).origin, solid.half_edge_target(he)?);
+                solid.set_pcurve(he, NurbsCurve::<HSpace2>::line(params[&from], params[&to]))?;
+            }
+        }
+
+        // The edges around the surface and its offset follow their boundaries
+        for (a, b, boundary) in sides {
+            for (base, from, to) in [(surface, a, b), (&offset, a + 4, b + 4)] {
+                let edge = solid.edge_between(vertices[&from], vertices[&to]).unwrap();
+                let start = solid
+                    .vx(solid.he(solid.ed(edge).half_edges[0]).origin)
+                    .point;
+                let curve = boundary.curve(base);
+                let curve = if (curve.point(curve.min_u()) - start).magnitude() <= model.linear {
+                    curve
+                } else {
+                    curve.reverse()
+                };
+                solid.set_edge_curve(edge, curve)?;
+            }
+        }
+
+        solid.validate_with(model)?;
+        Ok(solid)
+    }
+
+    /// The normal of a face's outer loop, pointing out of the solid
+    fn outer_loop_normal(&self, id: FaceId) -> TopologyResult<EVec3> {
+        let points = self.loop_points(self.check_face(id)?.outer_loop)?;
+        Ok(PlanarFrame::fit(&points)
+            .map(|frame| frame.normal())
+            .unwrap_or_else(EVec3::zero))
+    }
+}
+
+/// Finds where the offset planes around a vertex meet. Each plane is given as a
+/// unit normal and the normal's dot product with points on it. Where fewer than
+/// three independent planes meet, the vertex stays put in the directions they
+/// leave free. Planes within the angular tolerance of parallel count as one.
+/// Returns `None` if the planes do not meet at a single point to within the linear
+/// tolerance.
+fn offset_vertex(point: EVec3, planes: &[(EVec3, f64)], tolerance: &Tolerance) -> Option<EVec3> {
+    let most = |measure: &dyn Fn(EVec3) -> f64| {
+        planes
+            .iter()
+            .copied()
+            .max_by(|a, b| measure(a.0).total_cmp(&measure(b.0)))
+            .filter(|(n, _)| measure(*n) > tolerance.angular)
+    };
+
+    let (n1, c1) = planes[0];
+    let (n2, c2) = most(&|n| n1.cross(&n).magnitude()).unwrap_or_else(|| {
+        let n = any_perpendicular(n1);
+        (n, n.dot(&point))
+    });
+    let (n3, c3) = most(&|n| n1.dot(&n2.cross(&n)).abs()).unwrap_or_else(|| {
+        let n = n1.cross(&n2).normalize();
+        (n, n.dot(&point))
+    });
+
+    let det = n1.dot(&n2.cross(&n3));
+    let result = (n2.cross(&n3) * c1 + n3.cross(&n1) * c2 + n1.cross(&n2) * c3) / det;
+
+    if planes
+        .iter()
+        .all(|(n, c)| (n.dot(&result) - c).abs() <= tolerance.linear)
+    {
+        Some(result)
+    } else {
+        None
+    }
+}
+
+/// A unit vector perpendicular to the given one
+fn any_perpendicular(vec: EVec3) -> EVec3 {
+    let axis = if vec.x.abs() < 0.5 {
+        EVec3::new(1.0, 0.0, 0.0)
+    } else {
+        EVec3::new(0.0, 1.0, 0.0)
+    };
+    vec.cross(&axis).normalize()
+}
+
+/// One of the four boundaries of a surface's domain
+#[derive(Debug, Clone, Copy)]
+enum Boundary {
+    MinU,
+    MaxU,
+    MinV,
+    MaxV,
+}
+impl Boundary {
+    /// The boundary of a clamped surface as a curve, running in the direction of
+    /// increasing parameter
+    fn curve(&self, surface: &NurbsSurface<HSpace3>) -> NurbsCurve<HSpace3> {
+        let control_points = surface.control_points();
+        match self {
+            Self::MinU => {
+                NurbsCurve::new(control_points[0].clone(), surface.knot_vector_v().clone())
+            }
+            Self::MaxU => NurbsCurve::new(
+                control_points[control_points.len() - 1].clone(),
+                surface.knot_vector_v().clone(),
+            ),
+            Self::MinV => NurbsCurve::new(
+                control_points.iter().map(|row| row[0]).collect(),
+                surface.knot_vector_u().clone(),
+            ),
+            Self::MaxV => NurbsCurve::new(
+                control_points
+                    .iter()
+                    .map(|row| row[row.len() - 1])
+                    .collect(),
+                surface.knot_vector_u().clone(),
+            ),
+        }
+    }
+}
+
+/// A ruled surface from a boundary of a surface to the same boundary of its
+/// offset. `u` runs from the surface to the offset, and `v` along the boundary.
+/// Both boundaries are split into Bezier pieces at the knots of either and raised
+/// to the same degree, so the surface follows each of them exactly.
+fn ruled_surface(
+    surface: &NurbsSurface<HSpace3>,
+    offset: &NurbsSurface<HSpace3>,
+    boundary: Boundary,
+) -> NurbsSurface<HSpace3> {
+    let (base, offset) = (boundary.curve(surface), boundary.curve(offset));
+    let degree = base.degree().max(offset.degree());
+    let mut breaks = base.distinct_knots();
+    breaks.extend(offset.distinct_knots());
+    breaks.sort_by(f64::total_cmp);
+    breaks.dedup();
+
+    let mut knots = vec![breaks[0]; degree + 1];
+    for knot in &breaks[1..breaks.len() - 1] {
+        knots.extend(vec![*knot; degree]);
+    }
+    knots.extend(vec![breaks[breaks.len() - 1]; degree + 1]);
+
+    NurbsSurface::new(
+        vec![
+            bezier_pieces(&base, &breaks, degree),
+            bezier_pieces(&offset, &breaks, degree),
+        ],
+        KnotVector::new([0.0, 0.0, 1.0, 1.0]),
+        KnotVector::from_vec(knots),
+    )
+}
+
+/// The control points of a clamped curve written as Bezier pieces of the given
+/// degree, which must be at least the curve's, joining at the given parameters.
+/// The parameters must include the curve's knots.
+fn bezier_pieces(curve: &NurbsCurve<HSpace3>, breaks: &[f64], degree: usize) -> Vec<HVec3> {
+    let weighted = curve
+        .control_points()
+        .iter()
+        .map(|p| HSpace3::weight_vec(*p))
+        .collect::<Vec<_>>();
+    let spans = curve_decompose(&weighted, curve.degree(), curve.knot_vector());
+
+    let mut pieces = Vec::new();
+    for (mut piece, span) in spans.into_iter().zip(curve.distinct_knots().windows(2)) {
+        // Split the span where the pieces join, measuring the remaining piece
+        // from its own start
+        let mut start = span[0];
+        for at in breaks.iter().filter(|t| **t > span[0] && **t < span[1]) {
+            let (left, right) = split_bezier(&piece, (at - start) / (span[1] - start));
+            pieces.push(left);
+            piece = right;
+            start = *at;
+        }
+        pieces.push(piece);
+    }
+
+    let mut control_points = Vec::new();
+    for piece in pieces {
+        let piece = (piece.len() - 1..degree).fold(piece, |piece, _| elevate_bezier(&piece));
+        let skip = usize::from(!control_points.is_empty());
+        control_points.extend(piece.into_iter().skip(skip));
+    }
+    control_points
+        .into_iter()
+        .map(HSpace3::unweight_vec)
+        .collect()
+}
+
+/// Splits a Bezier curve at a parameter between 0 and 1 with de Casteljau's
+/// algorithm
+fn split_bezier(points: &[EVec4], t: f64) -> (Vec<EVec4>, Vec<EVec4>) {
+    let mut row = points.to_vec();
+    let (mut left, mut right) = (Vec::new(), Vec::new());
+    loop {
+        left.push(row[0]);
+        right.push(row[row.len() - 1]);
+        if row.len() == 1 {
+            break;
+        }
+        row = row
+            .windows(2)
+            .map(|pair| pair[0] * (1.0 - t) + pair[1] * t)
+            .collect();
+    }
+    right.reverse();
+    (left, right)
+}
+
+/// Raises the degree of a Bezier curve by one without changing its shape
+fn elevate_bezier(points: &[EVec4]) -> Vec<EVec4> {
+    let n = points.len();
+    let mut elevated = vec![points[0]];
+    for i in 1..n {
+        let a = i as f64 / n as f64;
+        elevated.push(points[i - 1] * a + points[i] * (1.0 - a));
+    }
+    elevated.push(points[n - 1]);
+    elevated
+}
+
+#[cfg(test)]
+mod tests {
+    use space::{EPlacement3, EVec3, EVector, HVec3, TOL};
+    use spline::{math::knot_vector::KnotVector, nurbs_surface::NurbsSurface};
+
+    use crate::{entities::FaceId, error::TopologyError, mesh::TriMesh, solid::Solid};
+
+    fn volume(solid: &Solid) -> f64 {
+        let mesh = TriMesh::from_solid(solid).unwrap();
+        (0..mesh.triangles.len())
+            .map(|i| {
+                let [a, b, c] = mesh.triangle_points(i);
+                a.dot(&b.cross(&c)) / 6.0
+            })
+            .sum()
+    }
+
+    #[test]
+    fn hollow_block() {
+        let block = Solid::block(&EPlacement3::default(), EVec3::new(2.0, 2.0, 2.0)).unwrap();
+        let top = block
+            .faces()
+            .find(|(id, _)| (block.planar_frame(*id).unwrap().normal().z - 1.0).abs() <= TOL)
+            .unwrap()
+            .0;
+
+        let open = block.hollow(0.25, &[top]).unwrap();
+        assert_eq!((open.num_faces(), open.num_shells()), (11, 1));
+        assert!((volume(&open) - (8.0 - 1.5 * 1.5 * 1.75)).abs() <= 1e-9);
+
+        let closed = block.hollow(0.25, &[]).unwrap();
+        assert_eq!((closed.num_faces(), closed.num_shells()), (12, 2));
+        assert!((volume(&closed) - (8.0 - 1.5 * 1.5 * 1.5)).abs() <= 1e-9);
+
+        // Every face's surface faces out of the solid and passes through its
+        // vertices where the p-curves put them
+        for (id, face) in closed.faces() {
+            let surface = face.surface.as_ref().unwrap();
+            let sense = if face.same_sense { 1.0 } else { -1.0 };
+            let normal = surface.normal(0.5, 0.5).unwrap() * sense;
+            assert!(normal.dot(&closed.outer_loop_normal(id).unwrap()) > 0.0);
+            for he in closed.face_half_edges(id).unwrap() {
+                let he = closed.half_edge(he).unwrap();
+                let uv = he.pcurve.as_ref().unwrap().point(0.0);
+                let point = closed.vertex(he.origin).unwrap().point;
+                assert!((surface.point(uv.x, uv.y) - point).magnitude() <= 1e-9);
+            }
+        }
+
+        assert!(block.hollow(1.5, &[top]).is_err());
+        assert_eq!(
+            block.hollow(0.0, &[top]).unwrap_err(),
+            TopologyError::InvalidThickness(0.0)
+        );
+        assert!(block.hollow(0.25, &[FaceId(100)]).is_err());
+    }
+
+    #[test]
+    fn thicken_surface() {
+        let flat = NurbsSurface::rectangle(&EPlacement3::default(), 2.0, 3.0);
+        let slab = Solid::thicken(&flat, 0.5, 1e-6).unwrap();
+        assert_eq!(
+            (slab.num_vertices(), slab.num_edges(), slab.num_faces()),
+            (8, 12, 6)
+        );
+        for z in [0.0, 0.5] {
+            let corners = slab
+                .vertices()
+                .filter(|(_, v)| (v.point.z - z).abs() <= 1e-9)
+                .count();
+            assert_eq!(corners, 4);
+        }
+
+        // A surface bent into an arc gives a curved plate whose faces are the
+        // thickness apart
+        let bent = NurbsSurface::new(
+            [(-1.0, 0.0, 1.0), (0.0, 1.0, 0.5), (1.0, 0.0, 1.0)]
+                .iter()
+                .map(|(x, z, h)| vec![HVec3::new(*x, 0.0, *z, *h), HVec3::new(*x, 2.0, *z, *h)])
+                .collect(),
+            KnotVector::new([0.0, 0.0, 0.0, 1.0, 1.0, 1.0]),
+            KnotVector::new([0.0, 0.0, 1.0, 1.0]),
+        );
+        let plate = Solid::thicken(&bent, -0.2, 1e-6).unwrap();
+        assert_eq!(plate.num_faces(), 6);
+        let offset = plate
+            .faces()
+            .filter_map(|(_, f)| f.surface.as_ref())
+            .find(|s| s.degree_u() == 3 && s.degree_v() == 3)
+            .unwrap();
+        for (u, v) in [(0.0, 0.0), (0.3, 0.7), (1.0, 0.5)] {
+            let base = bent.point(u, v);
+            let normal = bent.normal(u, v).unwrap();
+            assert!((offset.point(u, v) - (base - normal * 0.2)).magnitude() <= 1e-6);
+        }
+
+        // The side along the arc follows it exactly
+        let side = plate
+            .faces()
+            .filter_map(|(_, f)| f.surface.as_ref())
+            .find(|s| {
+                s.degree_u() == 1
+                    && [0.0, 0.5, 1.0]
+                        .iter()
+                        .all(|v| s.point(0.0, *v).y.abs() <= 1e-9)
+            })
+            .unwrap();
+        for t in [0.0, 0.2, 0.5, 0.9, 1.0] {
+            assert!((side.point(0.0, t) - bent.point(t, 0.0)).magnitude() <= 1e-12);
+        }
+
+        assert_eq!(
+            Solid::thicken(&flat, 0.0, 1e-6).unwrap_err(),
+            TopologyError::InvalidThickness(0.0)
+        );
+    }
+}
@@ -0,0 +1,340 @@
+use space::{
+    hspace::{HSpace2, HSpace3},
+    EVec3,
+};
+use spline::{nurbs_curve::NurbsCurve, nurbs_surface::NurbsSurface};
+
+use crate::{
+    entities::{
+        Arena, Edge, EdgeId, Face, FaceId, HalfEdge, HalfEdgeId, Loop, LoopId, Shell, ShellId,
+        Vertex, VertexId,
+    },
+    error::{TopologyError, TopologyResult},
+};
+
+/// A boundary representation of a solid, made of shells of faces bounded by loops
+/// of half-edges
+#[derive(Debug, Clone)]
+pub struct Solid {
+    pub(crate) vertices: Arena<Vertex>,
+    pub(crate) half_edges: Arena<HalfEdge>,
+    pub(crate) edges: Arena<Edge>,
+    pub(crate) loops: Arena<Loop>,
+    pub(crate) faces: Arena<Face>,
+    pub(crate) shells: Arena<Shell>,
+}
+impl Solid {
+    /// Creates an empty solid. Use `mvfs` to start building it.
+    pub fn new() -> Self {
+        Self {
+            vertices: Arena::new(),
+            half_edges: Arena::new(),
+            edges: Arena::new(),
+            loops: Arena::new(),
+            faces: Arena::new(),
+            shells: Arena::new(),
+        }
+    }
+
+    pub fn vertex(&self, id: VertexId) -> Option<&Vertex> {
+        self.vertices.get(id.0)
+    }
+
+    pub fn half_edge(&self, id: HalfEdgeId) -> Option<&HalfEdge> {
+        self.half_edges.get(id.0)
+    }
+
+    pub fn edge(&self, id: EdgeId) -> Option<&Edge> {
+        self.edges.get(id.0)
+    }
+
+    pub fn loop_(&self, id: LoopId) -> Option<&Loop> {
+        self.loops.get(id.0)
+    }
+
+    pub fn face(&self, id: FaceId) -> Option<&Face> {
+        self.faces.get(id.0)
+    }
+
+    pub fn shell(&self, id: ShellId) -> Option<&Shell> {
+        self.shells.get(id.0)
+    }
+
+    pub fn vertices(&self) -> impl Iterator<Item = (VertexId, &Vertex)> {
+        self.vertices.iter().map(|(i, v)| (VertexId(i), v))
+    }
+
+    pub fn half_edges(&self) -> impl Iterator<Item = (HalfEdgeId, &HalfEdge)> {
+        self.half_edges.iter().map(|(i, h)| (HalfEdgeId(i), h))
+    }
+
+    pub fn edges(&self) -> impl Iterator<Item = (EdgeId, &Edge)> {
+        self.edges.iter().map(|(i, e)| (EdgeId(i), e))
+    }
+
+    pub fn loops(&self) -> impl Iterator<Item = (LoopId, &Loop)> {
+        self.loops.iter().map(|(i, l)| (LoopId(i), l))
+    }
+
+    pub fn faces(&self) -> impl Iterator<Item = (FaceId, &Face)> {
+        self.faces.iter().map(|(i, f)| (FaceId(i), f))
+    }
+
+    pub fn shells(&self) -> impl Iterator<Item = (ShellId, &Shell)> {
+        self.shells.iter().map(|(i, s)| (ShellId(i), s))
+    }
+
+    pub fn num_vertices(&self) -> usize {
+        self.vertices.len()
+    }
+
+    pub fn num_edges(&self) -> usize {
+        self.edges.len()
+    }
+
+    pub fn num_faces(&self) -> usize {
+        self.faces.len()
+    }
+
+    pub fn num_loops(&self) -> usize {
+        self.loops.len()
+    }
+
+    pub fn num_shells(&self) -> usize {
+        self.shells.len()
+    }
+
+    /// The number of inner loops over all faces
+    pub fn num_rings(&self) -> usize {
+        self.num_loops() - self.num_faces()
+    }
+
+    /// The number of through-holes, derived from the Euler-Poincaré formula
+    /// `V - E + F - R = 2(S - H)`
+    pub fn genus(&self) -> isize {
+        let lhs = self.num_vertices() as isize - self.num_edges() as isize
+            + self.num_faces() as isize
+            - self.num_rings() as isize;
+        self.num_shells() as isize - lhs / 2
+    }
+
+    /// The half-edges of a loop, in order
+    pub fn loop_half_edges(&self, id: LoopId) -> TopologyResult<Vec<HalfEdgeId>> {
+        let start = self.check_loop(id)?.half_edge;
+        let mut half_edges = vec![start];
+        let mut current = self.check_half_edge(start)?.next;
+        while current != start && half_edges.len() <= self.half_edges.next_index() {
+            half_edges.push(current);
+            current = self.check_half_edge(current)?.next;
+        }
+        Ok(half_edges)
+    }
+
+    /// The vertices of a loop, in order
+    pub fn loop_vertices(&self, id: LoopId) -> TopologyResult<Vec<VertexId>> {
+        Ok(self
+            .loop_half_edges(id)?
+            .into_iter()
+            .map(|he| self.he(he).origin)
+            .collect())
+    }
+
+    /// The positions of the vertices of a loop, in order
+    pub fn loop_points(&self, id: LoopId) -> TopologyResult<Vec<EVec3>> {
+        self.loop_vertices(id)?
+            .into_iter()
+            .map(|v| Ok(self.check_vertex(v)?.point))
+            .collect()
+    }
+
+    /// The other half of a half-edge's edge, or `None` if the half-edge doesn't
+    /// exist or belongs to a loop with no edges
+    pub fn twin(&self, id: HalfEdgeId) -> Option<HalfEdgeId> {
+        let [a, b] = self.edge(self.half_edge(id)?.edge?)?.half_edges;
+        Some(if a == id { b } else { a })
+    }
+
+    /// The face a half-edge belongs to
+    pub fn half_edge_face(&self, id: HalfEdgeId) -> TopologyResult<FaceId> {
+        let loop_id = self.check_half_edge(id)?.loop_id;
+        Ok(self.check_loop(loop_id)?.face)
+    }
+
+    /// The vertex at the end of a half-edge
+    pub fn half_edge_target(&self, id: HalfEdgeId) -> TopologyResult<VertexId> {
+        let next = self.check_half_edge(id)?.next;
+        Ok(self.check_half_edge(next)?.origin)
+    }
+
+    /// Finds a half-edge of the face that starts at the vertex
+    pub fn half_edge_from(&self, face: FaceId, vertex: VertexId) -> Option<HalfEdgeId> {
+        self.face_half_edges(face)
+            .ok()?
+            .into_iter()
+            .find(|he| self.he(*he).origin == vertex)
+    }
+
+    /// Finds the half-edge of the face that runs from `from` to `to`
+    pub fn half_edge_between(
+        &self,
+        face: FaceId,
+        from: VertexId,
+        to: VertexId,
+    ) -> Option<HalfEdgeId> {
+        self.face_half_edges(face)
+            .ok()?
+            .into_iter()
+            .find(|he| self.he(*he).origin == from && self.half_edge_target(*he) == Ok(to))
+    }
+
+    /// The curve of a half-edge's edge, running in the direction of the half-edge
+    pub fn half_edge_curve(&self, id: HalfEdgeId) -> Option<NurbsCurve<HSpace3>> {
+        let edge = self.edge(self.half_edge(id)?.edge?)?;
+        let curve = edge.curve.as_ref()?;
+        if edge.half_edges[0] == id {
+            Some(curve.clone())
+        } else {
+            Some(curve.reverse())
+        }
+    }
+
+    /// Finds the edge between two vertices, if there is one
+    pub fn edge_between(&self, v1: VertexId, v2: VertexId) -> Option<EdgeId> {
+        self.half_edges()
+            .find(|(id, he)| he.origin == v1 && self.half_edge_target(*id) == Ok(v2))
+            .and_then(|(_, he)| he.edge)
+    }
+
+    /// The edges that meet at a vertex
+    pub fn vertex_edges(&self, vertex: VertexId) -> TopologyResult<Vec<EdgeId>> {
+        self.check_vertex(vertex)?;
+        Ok(self
+            .half_edges()
+            .filter(|(_, he)| he.origin == vertex)
+            .filter_map(|(_, he)| he.edge)
+            .collect())
+    }
+
+    /// The half-edges of all the loops of a face
+    pub fn face_half_edges(&self, id: FaceId) -> TopologyResult<Vec<HalfEdgeId>> {
+        let mut half_edges = Vec::new();
+        for l in self.check_face(id)?.loops.iter() {
+            half_edges.extend(self.loop_half_edges(*l)?);
+        }
+        Ok(half_edges)
+    }
+
+    /// Sets the surface of a face. `same_sense` says whether the surface normal
+    /// points out of the solid.
+    pub fn set_face_surface(
+        &mut self,
+        id: FaceId,
+        surface: NurbsSurface<HSpace3>,
+        same_sense: bool,
+    ) -> TopologyResult<()> {
+        self.check_face(id)?;
+        let face = self.fc_mut(id);
+        face.surface = Some(surface);
+        face.same_sense = same_sense;
+        Ok(())
+    }
+
+    pub fn set_edge_curve(&mut self, id: EdgeId, curve: NurbsCurve<HSpace3>) -> TopologyResult<()> {
+        self.check_edge(id)?;
+        self.edges.get_mut(id.0).unwrap().curve = Some(curve);
+        Ok(())
+    }
+
+    pub fn set_pcurve(
+        &mut self,
+        id: HalfEdgeId,
+        pcurve: NurbsCurve<HSpace2>,
+    ) -> TopologyResult<()> {
+        self.check_half_edge(id)?;
+        self.he_mut(id).pcurve = Some(pcurve);
+        Ok(())
+    }
+
+    pub(crate) fn vx(&self, id: VertexId) -> &Vertex {
+        self.vertices
+            .get(id.0)
+            .unwrap_or_else(|| panic!("Vertex {id:?} does not exist"))
+    }
+
+    pub(crate) fn he(&self, id: HalfEdgeId) -> &HalfEdge {
+        self.half_edges
+            .get(id.0)
+            .unwrap_or_else(|| panic!("Half-edge {id:?} does not exist"))
+    }
+
+    pub(crate) fn he_mut(&mut self, id: HalfEdgeId) -> &mut HalfEdge {
+        self.half_edges
+            .get_mut(id.0)
+            .unwrap_or_else(|| panic!("Half-edge {id:?} does not exist"))
+    }
+
+    pub(crate) fn ed(&self, id: EdgeId) -> &Edge {
+        self.edges
+            .get(id.0)
+            .unwrap_or_else(|| panic!("Edge {id:?} does not exist"))
+    }
+
+    pub(crate) fn lp(&self, id: LoopId) -> &Loop {
+        self.loops
+            .get(id.0)
+            .unwrap_or_else(|| panic!("Loop {id:?} does not exist"))
+    }
+
+    pub(crate) fn lp_mut(&mut self, id: LoopId) -> &mut Loop {
+        self.loops
+            .get_mut(id.0)
+            .unwrap_or_else(|| panic!("Loop {id:?} does not exist"))
+    }
+
+    pub(crate) fn fc(&self, id: FaceId) -> &Face {
+        self.faces
+            .get(id.0)
+            .unwrap_or_else(|| panic!("Face {id:?} does not exist"))
+    }
+
+    pub(crate) fn fc_mut(&mut self, id: FaceId) -> &mut Face {
+        self.faces
+            .get_mut(id.0)
+            .unwrap_or_else(|| panic!("Face {id:?} does not exist"))
+    }
+
+    pub(crate) fn sh_mut(&mut self, id: ShellId) -> &mut Shell {
+        self.shells
+            .get_mut(id.0)
+            .unwrap_or_else(|| panic!("Shell {id:?} does not exist"))
+    }
+
+    pub(crate) fn check_vertex(&self, id: VertexId) -> TopologyResult<&Vertex> {
+        self.vertices
+            .get(id.0)
+            .ok_or(TopologyError::MissingVertex(id))
+    }
+
+    pub(crate) fn check_half_edge(&self, id: HalfEdgeId) -> TopologyResult<&HalfEdge> {
+        self.half_edges
+            .get(id.0)
+            .ok_or(TopologyError::MissingHalfEdge(id))
+    }
+
+    pub(crate) fn check_edge(&self, id: EdgeId) -> TopologyResult<&Edge> {
+        self.edges.get(id.0).ok_or(TopologyError::MissingEdge(id))
+    }
+
+    pub(crate) fn check_loop(&self, id: LoopId) -> TopologyResult<&Loop> {
+        self.loops.get(id.0).ok_or(TopologyError::MissingLoop(id))
+    }
+
+    pub(crate) fn check_face(&self, id: FaceId) -> TopologyResult<&Face> {
+        self.faces.get(id.0).ok_or(TopologyError::MissingFace(id))
+    }
+}
+impl Default for Solid {
+    fn default() -> Self {
+        Self::new()
+    }
+}
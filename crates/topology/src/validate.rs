@@ -0,0 +1,125 @@
+use space::{EVector, Tolerance};
+
+use crate::{
+    entities::{EdgeId, HalfEdgeId, LoopId},
+    error::{TopologyError, TopologyResult},
+    solid::Solid,
+};
+
+impl Solid {
+    /// Checks that the solid is consistent: half-edges are linked into closed loops,
+    /// every loop is listed by its face, edges have two half-edges running in
+    /// opposite directions, edge curves end at their vertices, and the entity counts
+    /// satisfy the Euler-Poincaré formula.
+    pub fn validate(&self) -> TopologyResult<()> {
+        self.validate_with(&Tolerance::DEFAULT)
+    }
+
+    /// Checks the solid like [`Self::validate`], to the tolerances of a model
+    pub fn validate_with(&self, tolerance: &Tolerance) -> TopologyResult<()> {
+        for (id, he) in self.half_edges() {
+            let next = self
+                .half_edge(he.next)
+                .ok_or(TopologyError::BrokenLinks(id))?;
+            let prev = self
+                .half_edge(he.prev)
+                .ok_or(TopologyError::BrokenLinks(id))?;
+            if next.prev != id || prev.next != id || next.loop_id != he.loop_id {
+                return Err(TopologyError::BrokenLinks(id));
+            }
+            if self.vertex(he.origin).is_none() {
+                return Err(TopologyError::MissingVertex(he.origin));
+            }
+            if self.loop_(he.loop_id).is_none() {
+                return Err(TopologyError::MissingLoop(he.loop_id));
+            }
+        }
+
+        for (id, lp) in self.loops() {
+            self.validate_loop(id, lp.half_edge)?;
+
+            let face = self.check_face(lp.face)?;
+            if !face.loops.contains(&id) {
+                return Err(TopologyError::OrphanLoop(id));
+            }
+        }
+
+        for (_, face) in self.faces() {
+            for loop_id in face.loops.iter() {
+                if self.loop_(*loop_id).is_none() {
+                    return Err(TopologyError::MissingLoop(*loop_id));
+                }
+            }
+        }
+
+        for (id, edge) in self.edges() {
+            self.validate_edge(id)?;
+
+            if let Some(curve) = &edge.curve {
+                let start = self.vx(self.he(edge.half_edges[0]).origin).point;
+                let end = self.vx(self.he(edge.half_edges[1]).origin).point;
+                let curve_start = curve.point(curve.min_u());
+                let curve_end = curve.point(curve.max_u());
+                if (curve_start - start).magnitude() > tolerance.linear
+                    || (curve_end - end).magnitude() > tolerance.linear
+                {
+                    return Err(TopologyError::MismatchedEdgeCurve(id));
+                }
+            }
+        }
+
+        // V - E + F - R = 2(S - H) with H >= 0, so the left side must be even and no
+        // more than 2S
+        let (vertices, edges, faces, rings, shells) = (
+            self.num_vertices(),
+            self.num_edges(),
+            self.num_faces(),
+            self.num_rings(),
+            self.num_shells(),
+        );
+        let lhs = vertices as isize - edges as isize + faces as isize - rings as isize;
+        if lhs % 2 != 0 || lhs > 2 * shells as isize {
+            return Err(TopologyError::EulerPoincare {
+                vertices,
+                edges,
+                faces,
+                rings,
+                shells,
+            });
+        }
+
+        Ok(())
+    }
+
+    fn validate_loop(&self, id: LoopId, start: HalfEdgeId) -> TopologyResult<()> {
+        let mut current = start;
+        for _ in 0..=self.half_edges.next_index() {
+            let he = self.check_half_edge(current)?;
+            if he.loop_id != id {
+                return Err(TopologyError::OpenLoop(id));
+            }
+
+            current = he.next;
+            if current == start {
+                return Ok(());
+            }
+        }
+
+        Err(TopologyError::OpenLoop(id))
+    }
+
+    fn validate_edge(&self, id: EdgeId) -> TopologyResult<()> {
+        let [a, b] = self.ed(id).half_edges;
+        let (he_a, he_b) = (self.check_half_edge(a)?, self.check_half_edge(b)?);
+        if a == b
+            || he_a.edge != Some(id)
+            || he_b.edge != Some(id)
+            || self.half_edge_target(a)? != he_b.origin
+            || self.half_edge_target(b)? != he_a.origin
+        {
+            return Err(TopologyError::MismatchedHalfEdges(id));
+        }
+
+        Ok(())
+    }
+}
@@ -11,7 +11,7 @@ use render::{
     Rgba,
 };
 use space::hspace::{HSpace, HSpace2};
-use space::{EVec2, EVector, HVec2};
+use space::{EVec2, EVector, HVec2, Tolerance};
 use spline::bezier_curve::BezierCurve;
 use spline::math::FloatRange;
 use std::time::Instant;
@@ -88,7 +88,7 @@ impl App {
         };
 
         let intersection_points = {
-            let points = curve.line_intersections(&line);
+            let points = curve.line_intersections(&line, &Tolerance::default());
 
             let intersection_points = points
                 .into_iter()
@@ -110,7 +110,7 @@ impl App {
             let mut times = vec![0u128; num_times];
             for i in 0..num_times {
                 let start = Instant::now();
-                curve.hausdorff_to_line(&line, None, None, true);
+                curve.hausdorff_to_line(&line, None, None, true, &Tolerance::default());
                 let dur = (Instant::now() - start).as_micros();
                 times[i] = dur;
             }
@@ -119,13 +119,15 @@ impl App {
                 times.into_iter().sum::<u128>() as f64 / num_times as f64
             );
 
-            if let Some(hausdorff) = curve.hausdorff_to_line(&line, None, None, true) {
+            if let Some(hausdorff) =
+                curve.hausdorff_to_line(&line, None, None, true, &Tolerance::default())
+            {
                 println!("Hausdorff distance: {}", hausdorff.distance);
                 println!("Hausdorff point: {:?}", hausdorff.point);
                 println!("Hausdorff U: {}", hausdorff.u);
             }
 
-            let points = curve.hausdorff_candidates(&line, None, None, true);
+            let points = curve.hausdorff_candidates(&line, None, None, true, &Tolerance::default());
             let hausdorff_points = points
                 .into_iter()
                 .map(|p| {
@@ -13,7 +13,7 @@ use render::{
     Rgba,
 };
 use space::hspace::{HSpace, HSpace3};
-use space::{EVector, HVec3};
+use space::{EVector, HVec3, Tolerance};
 use spline::bezier_curve::BezierCurve;
 use spline::math::FloatRange;
 use tools::make_grid;
@@ -96,7 +96,7 @@ impl App {
         };
 
         let intersection_points = {
-            let points = curve.line_intersections(&line);
+            let points = curve.line_intersections(&line, &Tolerance::default());
 
             let intersection_points = points
                 .into_iter()
@@ -121,7 +121,7 @@ impl App {
             let mut times = vec![0u128; num_times];
             for i in 0..num_times {
                 let start = Instant::now();
-                curve.hausdorff_to_line(&line, min_u, max_u, true);
+                curve.hausdorff_to_line(&line, min_u, max_u, true, &Tolerance::default());
                 let dur = (Instant::now() - start).as_micros();
                 times[i] = dur;
             }
@@ -130,7 +130,8 @@ impl App {
                 times.into_iter().sum::<u128>() as f64 / num_times as f64
             );
 
-            let hausdorff = curve.hausdorff_to_line(&line, min_u, max_u, true);
+            let hausdorff =
+                curve.hausdorff_to_line(&line, min_u, max_u, true, &Tolerance::default());
 
             if let Some(ref hausdorff) = hausdorff {
                 println!("Hausdorff distance: {}", hausdorff.distance);
@@ -138,7 +139,8 @@ impl App {
                 println!("Hausdorff U: {}", hausdorff.u);
             }
 
-            let points = curve.hausdorff_candidates(&line, min_u, max_u, true);
+            let points =
+                curve.hausdorff_candidates(&line, min_u, max_u, true, &Tolerance::default());
             let hausdorff_points = points
                 .into_iter()
                 .map(|p| {
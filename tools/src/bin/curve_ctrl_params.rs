@@ -11,7 +11,7 @@ use render::{
     Rgba,
 };
 use space::hspace::{HSpace, HSpace2};
-use space::{EVector, HVec2};
+use space::{EVector, HVec2, Tolerance};
 use spline::math::FloatRange;
 use spline::{math::knot_vector::KnotVector, nurbs_curve::NurbsCurve};
 use tools::make_grid;
@@ -48,7 +48,12 @@ impl App {
         for (i, point) in curve.control_points().iter().enumerate() {
             let u = curve.u_at_control_point(i);
             let closest = curve
-                .find_closest(HSpace2::project_vec(point.clone()), u, 20)
+                .find_closest(
+                    HSpace2::project_vec(point.clone()),
+                    u,
+                    20,
+                    &Tolerance::default(),
+                )
                 .unwrap();
             closest_lines.push(ModelEdge::new(
                 0.into(),
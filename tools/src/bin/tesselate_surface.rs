@@ -11,7 +11,7 @@ use render::{
     Rgb,
 };
 use space::hspace::HSpace3;
-use space::{EVec3, EVector, HVec3};
+use space::{EVec3, EVector, HVec3, Tolerance};
 use tesselate::naive;
 use tools::make_grid;
 
@@ -52,7 +52,8 @@ impl App {
 
         let plane =
             space::EPlane3::new_from_normal_vec(EVec3::new(1.0, -1.0, 0.0).normalize(), 0.0);
-        let hausdorff_candidates = surface1.hausdorff_candidates(&plane, None, None);
+        let hausdorff_candidates =
+            surface1.hausdorff_candidates(&plane, None, None, &Tolerance::default());
 
         println!("{} {:?}", hausdorff_candidates.len(), hausdorff_candidates);
 